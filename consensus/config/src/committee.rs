@@ -82,6 +82,23 @@ impl Committee {
         (committee, key_pairs)
     }
 
+    /// Creates a committee for local testing, where every authority listens on a distinct
+    /// loopback UDP port.
+    pub fn local_committee_and_keys(
+        epoch: Epoch,
+        authorities_stake: Vec<Stake>,
+    ) -> (Self, Vec<(NetworkKeyPair, ProtocolKeyPair)>) {
+        let (mut committee, key_pairs) = Self::new_for_test(epoch, authorities_stake);
+        for authority in committee.authorities.iter_mut() {
+            let socket = std::net::UdpSocket::bind("127.0.0.1:0")
+                .expect("Failed to bind to a loopback UDP port");
+            let port = socket.local_addr().unwrap().port();
+            authority.address = format!("/ip4/127.0.0.1/udp/{port}").parse().unwrap();
+            authority.hostname = format!("127.0.0.1:{port}");
+        }
+        (committee, key_pairs)
+    }
+
     /// Public accessors for Committee fields.
 
     pub fn epoch(&self) -> Epoch {
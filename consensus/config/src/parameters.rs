@@ -1,7 +1,7 @@
 // Copyright (c) MangoNet Labs Ltd.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::time::Duration;
+use std::{path::PathBuf, time::Duration};
 
 use serde::{Deserialize, Serialize};
 
@@ -16,18 +16,33 @@ pub struct Parameters {
     /// Time to wait for parent round leader before sealing a block.
     #[serde(default = "Parameters::default_leader_timeout")]
     pub leader_timeout: Duration,
+
+    /// The database path. It must be set when running an authority.
+    #[serde(default)]
+    pub db_path: Option<PathBuf>,
 }
 
 impl Parameters {
     pub fn default_leader_timeout() -> Duration {
         Duration::from_millis(250)
     }
+
+    pub fn db_path_str_unsafe(&self) -> String {
+        self.db_path
+            .as_ref()
+            .expect("DB path is not set")
+            .as_path()
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
 }
 
 impl Default for Parameters {
     fn default() -> Self {
         Self {
             leader_timeout: Parameters::default_leader_timeout(),
+            db_path: None,
         }
     }
 }
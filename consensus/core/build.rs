@@ -0,0 +1,55 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+fn main() -> Result<()> {
+    let out_dir = if env::var("DUMP_GENERATED_ANEMO").is_ok() {
+        PathBuf::from("")
+    } else {
+        PathBuf::from(env::var("OUT_DIR")?)
+    };
+
+    build_anemo_services(&out_dir);
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=DUMP_GENERATED_ANEMO");
+
+    Ok(())
+}
+
+fn build_anemo_services(out_dir: &Path) {
+    let codec_path = "mango_network::codec::anemo::BcsSnappyCodec";
+
+    let service = anemo_build::manual::Service::builder()
+        .name("ConsensusRpc")
+        .package("consensus")
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("send_block")
+                .route_name("SendBlock")
+                .request_type("crate::network::anemo_network::SendBlockRequest")
+                .response_type("crate::network::anemo_network::SendBlockResponse")
+                .codec_path(codec_path)
+                .build(),
+        )
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("fetch_blocks")
+                .route_name("FetchBlocks")
+                .request_type("crate::network::anemo_network::FetchBlocksRequest")
+                .response_type("crate::network::anemo_network::FetchBlocksResponse")
+                .codec_path(codec_path)
+                .build(),
+        )
+        .build();
+
+    anemo_build::manual::Builder::new()
+        .out_dir(out_dir)
+        .compile(&[service]);
+}
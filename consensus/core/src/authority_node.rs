@@ -6,27 +6,51 @@ use std::time::Instant;
 
 use crate::block_manager::BlockManager;
 use consensus_config::{AuthorityIndex, Committee, NetworkKeyPair, Parameters, ProtocolKeyPair};
-use prometheus::Registry;
+use fastcrypto::traits::KeyPair as _;
 use mgo_protocol_config::ProtocolConfig;
+use parking_lot::RwLock;
+use prometheus::Registry;
 use tracing::info;
 
+use crate::authority_service::AuthorityService;
 use crate::block_verifier::BlockVerifier;
+use crate::broadcaster::Broadcaster;
 use crate::context::Context;
 use crate::core::{Core, CoreSignals};
-use crate::core_thread::CoreThreadDispatcher;
+use crate::core_thread::{CoreThreadDispatcher, CoreThreadDispatcherHandle};
+use crate::dag_state::DagState;
+use crate::leader_schedule::LeaderSchedule;
 use crate::leader_timeout::{LeaderTimeoutTask, LeaderTimeoutTaskHandle};
 use crate::metrics::initialise_metrics;
+use crate::network::NetworkManager;
+use crate::storage::rocksdb_store::RocksDBStore;
+use crate::storage::Store;
+use crate::synchronizer::{Synchronizer, SynchronizerHandle};
 use crate::transactions_client::{TransactionsClient, TransactionsConsumer};
 
-pub struct AuthorityNode {
+pub(crate) struct AuthorityNode<N, V>
+where
+    V: BlockVerifier,
+    N: NetworkManager<AuthorityService<CoreThreadDispatcher, V>>,
+{
     context: Arc<Context>,
     start_time: Instant,
     transactions_client: Arc<TransactionsClient>,
     leader_timeout_handle: LeaderTimeoutTaskHandle,
+    core_thread_handle: CoreThreadDispatcherHandle,
+    broadcaster: Broadcaster,
+    synchronizer: Arc<SynchronizerHandle>,
+    network_manager: N,
+    _phantom: std::marker::PhantomData<V>,
 }
 
-impl AuthorityNode {
+impl<N, V> AuthorityNode<N, V>
+where
+    V: BlockVerifier,
+    N: NetworkManager<AuthorityService<CoreThreadDispatcher, V>>,
+{
     #[allow(unused)]
+    #[allow(clippy::too_many_arguments)]
     async fn start(
         own_index: AuthorityIndex,
         committee: Committee,
@@ -35,7 +59,7 @@ impl AuthorityNode {
         // To avoid accidentally leaking the private key, the key pair should only be stored in core
         block_signer: NetworkKeyPair,
         _signer: ProtocolKeyPair,
-        _block_verifier: impl BlockVerifier,
+        block_verifier: V,
         registry: Registry,
     ) -> Self {
         info!("Starting authority with index {}", own_index);
@@ -46,44 +70,108 @@ impl AuthorityNode {
             protocol_config,
             initialise_metrics(registry),
         ));
+        let store = Arc::new(RocksDBStore::new(&context.parameters.db_path_str_unsafe()));
+        let network_manager = N::new(context.clone());
+        Self::start_with(
+            context,
+            store,
+            network_manager,
+            block_signer,
+            block_verifier,
+        )
+        .await
+    }
+
+    /// Starts the authority on the provided storage and network, which can then be simulated.
+    pub(crate) async fn start_with(
+        context: Arc<Context>,
+        store: Arc<dyn Store>,
+        mut network_manager: N,
+        block_signer: NetworkKeyPair,
+        block_verifier: V,
+    ) -> Self {
         let start_time = Instant::now();
 
+        // Get the network client, so blocks can be broadcasted once created.
+        let network_client = network_manager.client();
+
         // Create the transactions client and the transactions consumer
         let (client, tx_receiver) = TransactionsClient::new(context.clone());
         let tx_consumer = TransactionsConsumer::new(tx_receiver, context.clone(), None);
 
         // Construct Core
+        let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store.clone())));
+        // Restore the leader schedule from the last persisted reputation scores, so it is the same
+        // as on the other authorities after a restart.
+        let leader_schedule = LeaderSchedule::from_store(context.clone(), store);
         let (core_signals, signals_receivers) = CoreSignals::new();
-        let block_manager = BlockManager::new();
+        let block_manager = BlockManager::new(context.clone(), dag_state.clone());
+        // The network keypair is needed by the network to authenticate this authority to peers.
+        let network_keypair = block_signer.copy();
         let core = Core::new(
             context.clone(),
+            leader_schedule,
             tx_consumer,
             block_manager,
             core_signals,
             block_signer,
+            dag_state.clone(),
         );
 
-        let (core_dispatcher, core_dispatcher_handle) =
+        let (core_dispatcher, core_thread_handle) =
             CoreThreadDispatcher::start(core, context.clone());
+        let broadcaster =
+            Broadcaster::new(context.clone(), network_client.clone(), &signals_receivers);
         let leader_timeout_handle =
-            LeaderTimeoutTask::start(core_dispatcher, signals_receivers, context.clone());
+            LeaderTimeoutTask::start(core_dispatcher.clone(), signals_receivers, context.clone());
+
+        let block_verifier = Arc::new(block_verifier);
+        let core_dispatcher = Arc::new(core_dispatcher);
+        let synchronizer = Synchronizer::start(
+            context.clone(),
+            network_client,
+            block_verifier.clone(),
+            core_dispatcher.clone(),
+        );
+
+        let network_service = Arc::new(AuthorityService::new(
+            context.clone(),
+            block_verifier,
+            core_dispatcher,
+            synchronizer.clone(),
+            dag_state,
+        ));
+        network_manager
+            .install_service(network_keypair, network_service)
+            .await;
 
         Self {
             context,
             start_time,
-            leader_timeout_handle,
             transactions_client: Arc::new(client),
+            leader_timeout_handle,
+            core_thread_handle,
+            broadcaster,
+            synchronizer,
+            network_manager,
+            _phantom: Default::default(),
         }
     }
 
     #[allow(unused)]
-    async fn stop(self) {
+    pub(crate) async fn stop(mut self) {
         info!(
             "Stopping authority. Total run time: {:?}",
             self.start_time.elapsed()
         );
 
+        // First shutdown components calling into Core.
+        self.network_manager.stop().await;
         self.leader_timeout_handle.stop().await;
+        self.broadcaster.stop();
+        self.synchronizer.stop();
+        // Shutdown Core last, as components above may be calling into it.
+        self.core_thread_handle.stop();
 
         self.context
             .metrics
@@ -100,26 +188,34 @@ impl AuthorityNode {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use consensus_config::{Committee, NetworkKeyPair, Parameters, ProtocolKeyPair};
     use fastcrypto::traits::ToFromBytes;
     use prometheus::Registry;
+    use tempfile::TempDir;
 
     use crate::authority_node::AuthorityNode;
     use crate::block_verifier::TestBlockVerifier;
     use crate::context::Context;
+    use crate::network::anemo_network::AnemoManager;
 
     #[tokio::test]
     async fn start_and_stop() {
-        let (committee, keypairs) = Committee::new_for_test(0, vec![1]);
+        let (committee, keypairs) = Committee::local_committee_and_keys(0, vec![1]);
         let registry = Registry::new();
-        let parameters = Parameters::default();
+        let temp_dir = TempDir::new().unwrap();
+        let parameters = Parameters {
+            db_path: Some(temp_dir.path().to_path_buf()),
+            ..Default::default()
+        };
         let block_verifier = TestBlockVerifier {};
 
         let (own_index, _) = committee.authorities().last().unwrap();
         let block_signer = NetworkKeyPair::from_bytes(keypairs[0].0.as_bytes()).unwrap();
         let signer = ProtocolKeyPair::from_bytes(keypairs[0].1.as_bytes()).unwrap();
 
-        let authority = AuthorityNode::start(
+        let authority: AuthorityNode<AnemoManager, TestBlockVerifier> = AuthorityNode::start(
             own_index,
             committee,
             parameters,
@@ -137,4 +233,94 @@ mod tests {
 
         authority.stop().await;
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_authority_committee_over_loopback() {
+        const NUM_OF_AUTHORITIES: usize = 4;
+        const TARGET_ROUND: i64 = 10;
+
+        let (committee, keypairs) =
+            Committee::local_committee_and_keys(0, vec![1; NUM_OF_AUTHORITIES]);
+
+        let mut authorities = Vec::new();
+        let mut temp_dirs = Vec::new();
+        for (index, _authority) in committee.authorities() {
+            let temp_dir = TempDir::new().unwrap();
+            let parameters = Parameters {
+                db_path: Some(temp_dir.path().to_path_buf()),
+                ..Default::default()
+            };
+            temp_dirs.push(temp_dir);
+
+            // Each authority needs its own copy of the committee.
+            let committee = Committee::new(
+                committee.epoch(),
+                committee
+                    .authorities()
+                    .map(|(_, a)| consensus_config::Authority {
+                        stake: a.stake,
+                        address: a.address.clone(),
+                        hostname: a.hostname.clone(),
+                        network_key: a.network_key.clone(),
+                        protocol_key: a.protocol_key.clone(),
+                    })
+                    .collect(),
+            );
+            let (network_keypair, protocol_keypair) = &keypairs[index.value()];
+            let authority: AuthorityNode<AnemoManager, TestBlockVerifier> = AuthorityNode::start(
+                index,
+                committee,
+                parameters,
+                Context::default_protocol_config_for_testing(),
+                NetworkKeyPair::from_bytes(network_keypair.as_bytes()).unwrap(),
+                ProtocolKeyPair::from_bytes(protocol_keypair.as_bytes()).unwrap(),
+                TestBlockVerifier {},
+                Registry::new(),
+            )
+            .await;
+            authorities.push(authority);
+        }
+
+        // All authorities should advance their rounds by exchanging blocks over the network.
+        tokio::time::timeout(Duration::from_secs(60), async {
+            loop {
+                let all_advanced = authorities.iter().all(|authority| {
+                    authority
+                        .context
+                        .metrics
+                        .node_metrics
+                        .threshold_clock_round
+                        .get()
+                        >= TARGET_ROUND
+                });
+                if all_advanced {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .expect("Authorities did not reach the target round in time");
+
+        // Blocks have been exchanged with every peer.
+        for authority in &authorities {
+            let network_metrics = &authority.context.metrics.network_metrics;
+            for (index, peer) in authority.context.committee.authorities() {
+                if index == authority.context.own_index {
+                    continue;
+                }
+                assert!(
+                    network_metrics
+                        .inbound_requests
+                        .with_label_values(&[&peer.hostname, "send_block"])
+                        .get()
+                        > 0
+                );
+            }
+        }
+
+        for authority in authorities {
+            authority.stop().await;
+        }
+    }
 }
@@ -0,0 +1,351 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use consensus_config::AuthorityIndex;
+use mango_metrics::monitored_scope;
+use parking_lot::RwLock;
+
+use crate::{
+    block::{BlockAPI as _, BlockRef, SignedBlock, VerifiedBlock},
+    block_verifier::{BlockVerifier, SignedBlockVerifier},
+    context::Context,
+    core_thread::CoreThreadDispatcherInterface,
+    dag_state::DagState,
+    ensure,
+    error::{ConsensusError, ConsensusResult},
+    network::NetworkService,
+    synchronizer::SynchronizerHandle,
+};
+
+/// Maximum number of blocks that can be requested by a peer in one `fetch_blocks` request.
+pub(crate) const MAX_BLOCKS_PER_FETCH: usize = 1000;
+
+/// Authority's network service implementation, agnostic to the actual networking stack used.
+pub(crate) struct AuthorityService<C: CoreThreadDispatcherInterface, V: BlockVerifier> {
+    context: Arc<Context>,
+    signed_block_verifier: SignedBlockVerifier,
+    block_verifier: Arc<V>,
+    core_dispatcher: Arc<C>,
+    synchronizer: Arc<SynchronizerHandle>,
+    dag_state: Arc<RwLock<DagState>>,
+}
+
+impl<C: CoreThreadDispatcherInterface, V: BlockVerifier> AuthorityService<C, V> {
+    pub(crate) fn new(
+        context: Arc<Context>,
+        block_verifier: Arc<V>,
+        core_dispatcher: Arc<C>,
+        synchronizer: Arc<SynchronizerHandle>,
+        dag_state: Arc<RwLock<DagState>>,
+    ) -> Self {
+        Self {
+            signed_block_verifier: SignedBlockVerifier::new(context.clone()),
+            context,
+            block_verifier,
+            core_dispatcher,
+            synchronizer,
+            dag_state,
+        }
+    }
+}
+
+#[async_trait]
+impl<C: CoreThreadDispatcherInterface, V: BlockVerifier> NetworkService for AuthorityService<C, V> {
+    async fn handle_send_block(
+        &self,
+        peer: AuthorityIndex,
+        serialized_block: Bytes,
+    ) -> ConsensusResult<()> {
+        let _scope = monitored_scope("AuthorityService::handle_send_block");
+
+        let signed_block: SignedBlock =
+            bcs::from_bytes(&serialized_block).map_err(ConsensusError::MalformedBlock)?;
+
+        // Reject blocks not produced by the peer. Peers only send their own blocks, and blocks
+        // from other authorities are fetched explicitly.
+        ensure!(
+            signed_block.author() == peer,
+            ConsensusError::UnexpectedAuthority {
+                block_author: signed_block.author(),
+                peer,
+            }
+        );
+
+        if let Err(e) = self.signed_block_verifier.verify(&signed_block) {
+            self.context
+                .metrics
+                .node_metrics
+                .invalid_blocks
+                .with_label_values(&[&self.context.committee.authority(peer).hostname, e.name()])
+                .inc();
+            return Err(e);
+        }
+        self.block_verifier
+            .verify(&signed_block)
+            .await
+            .map_err(|e| ConsensusError::InvalidBlock(e.to_string()))?;
+        let verified_block = VerifiedBlock::new_verified(signed_block, serialized_block)?;
+
+        let missing_blocks = self
+            .core_dispatcher
+            .add_blocks(vec![verified_block])
+            .await
+            .map_err(|_| ConsensusError::Shutdown)?;
+        // The peer has sent a block referencing the missing blocks, so it should have them.
+        if !missing_blocks.is_empty() {
+            self.synchronizer.fetch_blocks(peer, missing_blocks);
+        }
+
+        Ok(())
+    }
+
+    async fn handle_fetch_blocks(
+        &self,
+        peer: AuthorityIndex,
+        block_refs: Vec<BlockRef>,
+    ) -> ConsensusResult<Vec<Bytes>> {
+        let _scope = monitored_scope("AuthorityService::handle_fetch_blocks");
+
+        ensure!(
+            block_refs.len() <= MAX_BLOCKS_PER_FETCH,
+            ConsensusError::TooManyFetchBlocksRequested(peer, block_refs.len())
+        );
+
+        // Blocks that are not found are skipped, the peer can retry with other authorities.
+        let blocks = self
+            .dag_state
+            .read()
+            .get_blocks(&block_refs)
+            .into_iter()
+            .flatten()
+            .map(|block| block.serialized().clone())
+            .collect();
+
+        Ok(blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use consensus_config::{NetworkKeyPair, ProtocolKeyPair};
+    use parking_lot::Mutex;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::{
+        block::{BlockAPI, BlockDigest, TestBlock},
+        block_verifier::TestBlockVerifier,
+        core_thread::CoreError,
+        storage::mem_store::MemStore,
+    };
+
+    #[derive(Default)]
+    struct FakeCoreThreadDispatcher {
+        blocks: Mutex<Vec<VerifiedBlock>>,
+        missing_blocks: Mutex<BTreeSet<BlockRef>>,
+    }
+
+    #[async_trait]
+    impl CoreThreadDispatcherInterface for FakeCoreThreadDispatcher {
+        async fn add_blocks(
+            &self,
+            blocks: Vec<VerifiedBlock>,
+        ) -> Result<BTreeSet<BlockRef>, CoreError> {
+            self.blocks.lock().extend(blocks);
+            Ok(self.missing_blocks.lock().clone())
+        }
+
+        async fn force_new_block(&self, _round: u32) -> Result<(), CoreError> {
+            unimplemented!()
+        }
+
+        async fn get_missing_blocks(&self) -> Result<BTreeSet<BlockRef>, CoreError> {
+            unimplemented!()
+        }
+    }
+
+    struct TestFixture {
+        keys: Vec<(NetworkKeyPair, ProtocolKeyPair)>,
+        core_dispatcher: Arc<FakeCoreThreadDispatcher>,
+        dag_state: Arc<RwLock<DagState>>,
+        fetch_receiver: mpsc::Receiver<(AuthorityIndex, BTreeSet<BlockRef>)>,
+        service: AuthorityService<FakeCoreThreadDispatcher, TestBlockVerifier>,
+    }
+
+    fn create_service() -> TestFixture {
+        let (context, keys) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let core_dispatcher = Arc::new(FakeCoreThreadDispatcher::default());
+        let store = Arc::new(MemStore::new());
+        let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store)));
+        let (synchronizer, fetch_receiver) = SynchronizerHandle::new_for_test();
+        let service = AuthorityService::new(
+            context,
+            Arc::new(TestBlockVerifier),
+            core_dispatcher.clone(),
+            synchronizer,
+            dag_state.clone(),
+        );
+        TestFixture {
+            keys,
+            core_dispatcher,
+            dag_state,
+            fetch_receiver,
+            service,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_send_block() {
+        let TestFixture {
+            keys,
+            core_dispatcher,
+            service,
+            ..
+        } = create_service();
+
+        // A block signed by its author is accepted.
+        let author = AuthorityIndex::new_for_test(1);
+        let ancestors = (0..3)
+            .map(|i| BlockRef::new(4, AuthorityIndex::new_for_test(i), BlockDigest::MIN))
+            .collect::<Vec<_>>();
+        let block = TestBlock::new(5, 1)
+            .set_ancestors(ancestors.clone())
+            .build();
+        let signed_block = SignedBlock::new(block, &keys[1].0).unwrap();
+        let serialized = signed_block.serialize().unwrap();
+        service
+            .handle_send_block(author, serialized.clone())
+            .await
+            .unwrap();
+        let received = core_dispatcher.blocks.lock().clone();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].round(), 5);
+        assert_eq!(received[0].author(), author);
+
+        // The same block relayed by another peer is rejected.
+        let result = service
+            .handle_send_block(AuthorityIndex::new_for_test(2), serialized)
+            .await;
+        assert!(matches!(
+            result,
+            Err(ConsensusError::UnexpectedAuthority { .. })
+        ));
+
+        // A block signed with the wrong key is rejected.
+        let block = TestBlock::new(5, 1)
+            .set_timestamp_ms(1)
+            .set_ancestors(ancestors.clone())
+            .build();
+        let signed_block = SignedBlock::new(block, &keys[2].0).unwrap();
+        let result = service
+            .handle_send_block(author, signed_block.serialize().unwrap())
+            .await;
+        assert!(matches!(
+            result,
+            Err(ConsensusError::SignatureVerificationFailure(_))
+        ));
+
+        // A block without a quorum of parents is rejected.
+        let block = TestBlock::new(5, 1)
+            .set_ancestors(ancestors[..2].to_vec())
+            .build();
+        let signed_block = SignedBlock::new(block, &keys[1].0).unwrap();
+        let result = service
+            .handle_send_block(author, signed_block.serialize().unwrap())
+            .await;
+        assert!(matches!(
+            result,
+            Err(ConsensusError::InsufficientParentStakes { .. })
+        ));
+
+        // Garbage bytes are rejected.
+        let result = service
+            .handle_send_block(author, Bytes::from_static(b"not a block"))
+            .await;
+        assert!(matches!(result, Err(ConsensusError::MalformedBlock(_))));
+
+        assert_eq!(core_dispatcher.blocks.lock().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_handle_send_block_fetches_missing_ancestors() {
+        let TestFixture {
+            keys,
+            core_dispatcher,
+            mut fetch_receiver,
+            service,
+            ..
+        } = create_service();
+
+        let author = AuthorityIndex::new_for_test(1);
+        let ancestors = (0..3)
+            .map(|i| BlockRef::new(4, AuthorityIndex::new_for_test(i), BlockDigest::MIN))
+            .collect::<Vec<_>>();
+        let block = TestBlock::new(5, 1)
+            .set_ancestors(ancestors.clone())
+            .build();
+        let serialized = SignedBlock::new(block, &keys[1].0)
+            .unwrap()
+            .serialize()
+            .unwrap();
+
+        // Ancestors missing from Core are requested from the peer that sent the block.
+        let missing_blocks = ancestors.iter().cloned().collect::<BTreeSet<_>>();
+        *core_dispatcher.missing_blocks.lock() = missing_blocks.clone();
+        service.handle_send_block(author, serialized).await.unwrap();
+        assert_eq!(fetch_receiver.try_recv().unwrap(), (author, missing_blocks));
+
+        // Nothing is requested when the causal history of the block is complete.
+        core_dispatcher.missing_blocks.lock().clear();
+        let block = TestBlock::new(5, 1)
+            .set_timestamp_ms(1)
+            .set_ancestors(ancestors)
+            .build();
+        let serialized = SignedBlock::new(block, &keys[1].0)
+            .unwrap()
+            .serialize()
+            .unwrap();
+        service.handle_send_block(author, serialized).await.unwrap();
+        assert!(fetch_receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_handle_fetch_blocks() {
+        let TestFixture {
+            dag_state, service, ..
+        } = create_service();
+        let peer = AuthorityIndex::new_for_test(1);
+
+        let blocks = (1..=3)
+            .map(|round| VerifiedBlock::new_for_test(TestBlock::new(round, 2).build()))
+            .collect::<Vec<_>>();
+        dag_state.write().accept_blocks(blocks.clone());
+
+        // Known blocks are returned, unknown ones are skipped.
+        let mut block_refs = blocks.iter().map(|b| b.reference()).collect::<Vec<_>>();
+        block_refs.push(VerifiedBlock::new_for_test(TestBlock::new(4, 2).build()).reference());
+        let fetched = service.handle_fetch_blocks(peer, block_refs).await.unwrap();
+        assert_eq!(
+            fetched,
+            blocks
+                .iter()
+                .map(|b| b.serialized().clone())
+                .collect::<Vec<_>>()
+        );
+
+        // Too many blocks requested at once.
+        let block_refs = vec![blocks[0].reference(); MAX_BLOCKS_PER_FETCH + 1];
+        let result = service.handle_fetch_blocks(peer, block_refs).await;
+        assert!(matches!(
+            result,
+            Err(ConsensusError::TooManyFetchBlocksRequested(_, _))
+        ));
+    }
+}
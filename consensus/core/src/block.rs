@@ -23,7 +23,7 @@ use crate::context::Context;
 use crate::ensure;
 use crate::error::{ConsensusError, ConsensusResult};
 
-const GENESIS_ROUND: Round = 0;
+pub(crate) const GENESIS_ROUND: Round = 0;
 
 /// Round number of a block.
 pub type Round = u32;
@@ -105,7 +105,6 @@ pub struct BlockV1 {
     epoch: Epoch,
     round: Round,
     author: AuthorityIndex,
-    // TODO: during verification ensure that timestamp_ms >= ancestors.timestamp
     timestamp_ms: BlockTimestampMs,
     ancestors: Vec<BlockRef>,
     transactions: Vec<Transaction>,
@@ -308,8 +307,6 @@ pub(crate) struct SignedBlock {
 }
 
 impl SignedBlock {
-    // TODO: add verification.
-
     /// Should only be used when constructing the genesis blocks
     pub(crate) fn new_genesis(block: Block) -> Self {
         Self {
@@ -330,9 +327,7 @@ impl SignedBlock {
         })
     }
 
-    #[allow(unused)]
-    /// This method is only verifying the block's signature for now. It does not do any kind of validation so it can
-    /// fail for numerous reasons.
+    /// Verifies the block's signature. The rest of the block is validated by `SignedBlockVerifier`.
     pub(crate) fn verify(&self, context: &Context) -> ConsensusResult<()> {
         let digest = compute_digest(&self.inner)?;
 
@@ -356,6 +351,15 @@ impl SignedBlock {
     }
 }
 
+/// Allow quick access on the underlying Block without having to always refer to the inner block ref.
+impl Deref for SignedBlock {
+    type Target = Block;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
 /// VerifiedBlock allows full access to its content.
 /// It should be relatively cheap to copy.
 #[derive(Clone)]
@@ -466,6 +470,55 @@ impl fmt::Debug for VerifiedBlock {
     }
 }
 
+/// Proof that an authority has equivocated, ie it has signed two different blocks for the same
+/// round. Both blocks carry the signature of the author, so anyone can verify the evidence.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct EquivocationEvidence {
+    pub author: AuthorityIndex,
+    pub round: Round,
+    /// The serialized `SignedBlock`s.
+    pub blocks: [Bytes; 2],
+}
+
+impl EquivocationEvidence {
+    pub(crate) fn new(first: &VerifiedBlock, second: &VerifiedBlock) -> Self {
+        assert_eq!(
+            Slot::from(first.reference()),
+            Slot::from(second.reference()),
+            "Equivocating blocks should have the same author and round"
+        );
+        assert_ne!(first.digest(), second.digest(), "Blocks should differ");
+        Self {
+            author: first.author(),
+            round: first.round(),
+            blocks: [first.serialized().clone(), second.serialized().clone()],
+        }
+    }
+
+    /// Verifies that the blocks are two different blocks for the same round, both signed by the
+    /// author.
+    pub(crate) fn verify(&self, context: &Context) -> ConsensusResult<()> {
+        let mut digests = vec![];
+        for serialized in &self.blocks {
+            let block: SignedBlock = bcs::from_bytes(serialized)?;
+            ensure!(
+                block.author() == self.author && block.round() == self.round,
+                ConsensusError::InvalidEquivocationEvidence(format!(
+                    "block is not at slot {}",
+                    Slot::new(self.round, self.author)
+                ))
+            );
+            block.verify(context)?;
+            digests.push(compute_digest(&block.inner)?);
+        }
+        ensure!(
+            digests[0] != digests[1],
+            ConsensusError::InvalidEquivocationEvidence("blocks are identical".to_string())
+        );
+        Ok(())
+    }
+}
+
 /// Creates fake blocks for testing.
 #[cfg(test)]
 pub(crate) struct TestBlock {
@@ -525,7 +578,7 @@ impl TestBlock {
 
 #[cfg(test)]
 mod tests {
-    use crate::block::{SignedBlock, TestBlock};
+    use crate::block::{EquivocationEvidence, SignedBlock, TestBlock, VerifiedBlock};
     use crate::context::Context;
     use crate::error::ConsensusError;
     use fastcrypto::error::FastCryptoError;
@@ -561,4 +614,43 @@ mod tests {
             err => panic!("Unexpected error: {err:?}"),
         }
     }
+
+    #[test]
+    fn test_equivocation_evidence() {
+        let (context, key_pairs) = Context::new_for_test(4);
+        let sign = |block, key_index: usize| {
+            let signed_block = SignedBlock::new(block, &key_pairs[key_index].0).unwrap();
+            VerifiedBlock::new_verified_unserialized(signed_block).unwrap()
+        };
+
+        let first = sign(TestBlock::new(10, 2).set_timestamp_ms(1).build(), 2);
+        let second = sign(TestBlock::new(10, 2).set_timestamp_ms(2).build(), 2);
+        let evidence = EquivocationEvidence::new(&first, &second);
+        assert!(evidence.verify(&context).is_ok());
+
+        // The same block twice is not an equivocation.
+        let mut invalid = evidence.clone();
+        invalid.blocks[1] = invalid.blocks[0].clone();
+        assert!(matches!(
+            invalid.verify(&context),
+            Err(ConsensusError::InvalidEquivocationEvidence(_))
+        ));
+
+        // A block at another round.
+        let mut invalid = evidence.clone();
+        invalid.round = 11;
+        assert!(matches!(
+            invalid.verify(&context),
+            Err(ConsensusError::InvalidEquivocationEvidence(_))
+        ));
+
+        // A block not signed by the author.
+        let forged = sign(TestBlock::new(10, 2).set_timestamp_ms(3).build(), 1);
+        let mut invalid = evidence;
+        invalid.blocks[1] = forged.serialized().clone();
+        assert!(matches!(
+            invalid.verify(&context),
+            Err(ConsensusError::SignatureVerificationFailure(_))
+        ));
+    }
 }
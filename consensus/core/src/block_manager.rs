@@ -1,53 +1,658 @@
 // Copyright (c) MangoNet Labs Ltd.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::block::{BlockAPI, BlockRef, VerifiedBlock};
-use std::collections::HashSet;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::Instant,
+};
+
+use parking_lot::RwLock;
+use tracing::{debug, trace, warn};
+
+use crate::{
+    block::{BlockAPI, BlockRef, VerifiedBlock},
+    block_verifier::SignedBlockVerifier,
+    context::Context,
+    dag_state::DagState,
+};
+
+struct SuspendedBlock {
+    block: VerifiedBlock,
+    /// The ancestors of the block that have not been accepted yet.
+    missing_ancestors: BTreeSet<BlockRef>,
+    /// When the block got suspended.
+    timestamp: Instant,
+}
+
+impl SuspendedBlock {
+    fn new(block: VerifiedBlock, missing_ancestors: BTreeSet<BlockRef>) -> Self {
+        Self {
+            block,
+            missing_ancestors,
+            timestamp: Instant::now(),
+        }
+    }
+}
 
 /// Block manager suspends incoming blocks until they are connected to the existing graph,
 /// returning newly connected blocks.
 /// TODO: As it is possible to have Byzantine validators who produce Blocks without valid causal
 /// history we need to make sure that BlockManager takes care of that and avoid OOM (Out Of Memory)
 /// situations.
-#[allow(dead_code)]
 pub(crate) struct BlockManager {
-    // TODO: dummy implementation, just keep all the block references
-    accepted_blocks: HashSet<BlockRef>,
+    context: Arc<Context>,
+    dag_state: Arc<RwLock<DagState>>,
+    /// Checks blocks against their ancestors, once these have been accepted.
+    block_verifier: SignedBlockVerifier,
+
+    /// Keeps all the suspended blocks. A suspended block is a block that is missing part of its causal history and thus
+    /// can't be immediately processed. A block will remain in this map until all its causal history has been successfully
+    /// processed.
+    suspended_blocks: BTreeMap<BlockRef, SuspendedBlock>,
+    /// A map that keeps all the blocks that we are missing (keys) and the corresponding blocks that reference the missing blocks
+    /// as ancestors and need them to get unsuspended. It is possible for a missing dependency (key) to be a suspended block, so
+    /// the block has been already fetched but it self is still missing some of its ancestors to be processed.
+    missing_ancestors: BTreeMap<BlockRef, BTreeSet<BlockRef>>,
+    /// Keeps all the blocks that we actually miss and haven't fetched them yet. That set will basically contain all the
+    /// keys from the `missing_ancestors` minus any keys that exist in `suspended_blocks`.
+    missing_blocks: BTreeSet<BlockRef>,
 }
 
-#[allow(dead_code)]
 impl BlockManager {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(context: Arc<Context>, dag_state: Arc<RwLock<DagState>>) -> Self {
         Self {
-            accepted_blocks: HashSet::new(),
+            block_verifier: SignedBlockVerifier::new(context.clone()),
+            context,
+            dag_state,
+            suspended_blocks: BTreeMap::new(),
+            missing_ancestors: BTreeMap::new(),
+            missing_blocks: BTreeSet::new(),
         }
     }
 
     /// Tries to accept the provided blocks assuming that all their causal history exists. The method
     /// returns all the blocks that have been successfully processed in round ascending order, that includes also previously
-    /// suspended blocks that have now been able to get accepted.
-    pub(crate) fn add_blocks(&mut self, mut blocks: Vec<VerifiedBlock>) -> Vec<VerifiedBlock> {
-        // TODO: add implementation - for now just dummy/test. Accept everything assuming history exists and cache them
-        // to ensure that they are not returned if they already processed.
-        blocks.sort_by_key(|b1| b1.round());
-        blocks
+    /// suspended blocks that have now been able to get accepted. Accepted blocks are written to DagState in causal order.
+    /// Blocks that are invalid with regards to their ancestors are dropped, together with the suspended blocks depending
+    /// on them. Method also returns a set with the newly missing ancestors, that need to be fetched.
+    pub(crate) fn try_accept_blocks(
+        &mut self,
+        mut blocks: Vec<VerifiedBlock>,
+    ) -> (Vec<VerifiedBlock>, BTreeSet<BlockRef>) {
+        blocks.sort_by_key(|b| b.round());
+
+        let mut accepted_blocks = vec![];
+        let missing_blocks_before = self.missing_blocks.clone();
+
+        for block in blocks {
+            // Try to accept the input block.
+            let block_ref = block.reference();
+            let Some(block) = self.try_accept_one_block(block) else {
+                continue;
+            };
+            let Some(block) = self.verify_and_accept_block(block) else {
+                continue;
+            };
+
+            // If the block is accepted, try to unsuspend its children blocks if any.
+            let unsuspended_blocks = self.try_unsuspend_children_blocks(block_ref);
+
+            accepted_blocks.push(block);
+            accepted_blocks.extend(unsuspended_blocks);
+        }
+
+        // Newly missing blocks are the ones that weren't already known as missing.
+        let missing_blocks_after = self
+            .missing_blocks
+            .difference(&missing_blocks_before)
+            .cloned()
+            .collect();
+
+        self.report_metrics();
+
+        (accepted_blocks, missing_blocks_after)
+    }
+
+    /// Tries to accept the provided block. To accept a block its ancestors must have been already successfully accepted. If
+    /// block is accepted then Some result is returned. None is returned when either the block is suspended or the block
+    /// has been already accepted before.
+    fn try_accept_one_block(&mut self, block: VerifiedBlock) -> Option<VerifiedBlock> {
+        let block_ref = block.reference();
+
+        // Skip the block if it's already suspended or accepted.
+        if self.suspended_blocks.contains_key(&block_ref)
+            || self.dag_state.read().contains_block(&block_ref)
+        {
+            return None;
+        }
+
+        let mut missing_ancestors = BTreeSet::new();
+
+        // Check which ancestors are neither suspended nor accepted. Ancestors that are suspended are
+        // already fetched, so they only need to be tracked as dependencies.
+        let ancestors = block.ancestors();
+        let ancestors_exist = self.dag_state.read().contains_blocks(ancestors);
+        for (ancestor, exists) in ancestors.iter().zip(ancestors_exist) {
+            if exists {
+                continue;
+            }
+
+            // Register the block as "waiting" on the ancestor.
+            self.missing_ancestors
+                .entry(*ancestor)
+                .or_default()
+                .insert(block_ref);
+            missing_ancestors.insert(*ancestor);
+
+            // Add the ancestor to the missing blocks set only if it doesn't already exist in the suspended blocks.
+            if !self.suspended_blocks.contains_key(ancestor) {
+                self.missing_blocks.insert(*ancestor);
+            }
+        }
+
+        // The block is no longer missing, as it has been received.
+        self.missing_blocks.remove(&block_ref);
+
+        if !missing_ancestors.is_empty() {
+            let hostname = self
+                .context
+                .committee
+                .authority(block.author())
+                .hostname
+                .as_str();
+            self.context
+                .metrics
+                .node_metrics
+                .block_suspensions
+                .with_label_values(&[hostname])
+                .inc();
+            trace!(
+                "Block {} suspended, missing ancestors {:?}",
+                block_ref,
+                missing_ancestors
+            );
+            self.suspended_blocks
+                .insert(block_ref, SuspendedBlock::new(block, missing_ancestors));
+            return None;
+        }
+
+        Some(block)
+    }
+
+    /// Checks the block against its ancestors, which must all have been accepted, and accepts it into DagState. An
+    /// invalid block is dropped together with the suspended blocks depending on it, and None is returned.
+    fn verify_and_accept_block(&mut self, block: VerifiedBlock) -> Option<VerifiedBlock> {
+        let ancestors = self
+            .dag_state
+            .read()
+            .get_blocks(block.ancestors())
             .into_iter()
-            .flat_map(|block| {
-                self.accepted_blocks
-                    .insert(block.reference())
-                    .then_some(block)
+            .zip(block.ancestors())
+            .map(|(ancestor, ancestor_ref)| {
+                ancestor.unwrap_or_else(|| panic!("Ancestor {} should be accepted", ancestor_ref))
             })
-            .collect()
+            .collect::<Vec<_>>();
+
+        if let Err(e) = self.block_verifier.check_ancestors(&block, &ancestors) {
+            warn!("Block {} rejected: {}", block.reference(), e);
+            let hostname = self
+                .context
+                .committee
+                .authority(block.author())
+                .hostname
+                .as_str();
+            self.context
+                .metrics
+                .node_metrics
+                .invalid_blocks
+                .with_label_values(&[hostname, e.name()])
+                .inc();
+            self.drop_suspended_descendants(block.reference());
+            return None;
+        }
+
+        // Accepting blocks one by one keeps the causal order, so the DAG always contains the full causal history of
+        // a block.
+        self.dag_state.write().accept_block(block.clone());
+        Some(block)
+    }
+
+    /// Drops the suspended blocks that depend, directly or transitively, on a rejected block, as they can never be
+    /// accepted.
+    fn drop_suspended_descendants(&mut self, rejected_block: BlockRef) {
+        let mut to_drop = vec![rejected_block];
+        while let Some(block_ref) = to_drop.pop() {
+            let Some(children_refs) = self.missing_ancestors.remove(&block_ref) else {
+                continue;
+            };
+            for child_ref in children_refs {
+                let Some(child) = self.suspended_blocks.remove(&child_ref) else {
+                    continue;
+                };
+                // Stop waiting for the other missing ancestors of the dropped block.
+                for ancestor in &child.missing_ancestors {
+                    if let Some(waiting) = self.missing_ancestors.get_mut(ancestor) {
+                        waiting.remove(&child_ref);
+                        if waiting.is_empty() {
+                            self.missing_ancestors.remove(ancestor);
+                            self.missing_blocks.remove(ancestor);
+                        }
+                    }
+                }
+                debug!(
+                    "Suspended block {} dropped, as it depends on rejected block {}",
+                    child_ref, rejected_block
+                );
+                to_drop.push(child_ref);
+            }
+        }
+    }
+
+    /// Given an accepted block `accepted_block` it attempts to accept all the suspended children blocks assuming such exist.
+    /// All the unsuspended / accepted blocks are returned as a vector in causal order.
+    fn try_unsuspend_children_blocks(&mut self, accepted_block: BlockRef) -> Vec<VerifiedBlock> {
+        let mut unsuspended_blocks = vec![];
+        let mut to_process_blocks = vec![accepted_block];
+
+        while let Some(block_ref) = to_process_blocks.pop() {
+            // And try to check if its direct children can be unsuspended
+            let Some(children_refs) = self.missing_ancestors.remove(&block_ref) else {
+                continue;
+            };
+            for r in children_refs {
+                // Skip the child if it has been dropped, because it also depends on a rejected block.
+                if !self.suspended_blocks.contains_key(&r) {
+                    continue;
+                }
+                // For each dependency try to unsuspend it. If that's successful then we add it to the queue so
+                // we can recursively try to unsuspend its children.
+                if let Some(block) = self.try_unsuspend_block(&r, &block_ref) {
+                    if self.verify_and_accept_block(block.block.clone()).is_some() {
+                        to_process_blocks.push(block.block.reference());
+                        unsuspended_blocks.push(block);
+                    }
+                }
+            }
+        }
+
+        let now = Instant::now();
+        for block in &unsuspended_blocks {
+            let hostname = self
+                .context
+                .committee
+                .authority(block.block.author())
+                .hostname
+                .as_str();
+            self.context
+                .metrics
+                .node_metrics
+                .block_unsuspensions
+                .with_label_values(&[hostname])
+                .inc();
+            debug!(
+                "Block {} unsuspended after {:?}",
+                block.block.reference(),
+                now.saturating_duration_since(block.timestamp)
+            );
+        }
+
+        // A child is only unsuspended once all its ancestors are accepted, so sorting by round
+        // yields a causal order.
+        let mut unsuspended_blocks = unsuspended_blocks
+            .into_iter()
+            .map(|block| block.block)
+            .collect::<Vec<_>>();
+        unsuspended_blocks.sort_by_key(|b| b.round());
+        unsuspended_blocks
+    }
+
+    /// Attempts to unsuspend a block by checking its input `accepted_dependency` and removing it from its list
+    /// of missing ancestors. If the block has no missing ancestors any more, then it is removed from the
+    /// suspended blocks and returned.
+    fn try_unsuspend_block(
+        &mut self,
+        block_ref: &BlockRef,
+        accepted_dependency: &BlockRef,
+    ) -> Option<SuspendedBlock> {
+        let block = self
+            .suspended_blocks
+            .get_mut(block_ref)
+            .expect("Block should be in suspended map");
+
+        assert!(
+            block.missing_ancestors.remove(accepted_dependency),
+            "Block reference {} should be present in missing dependencies of {:?}",
+            block_ref,
+            block.block
+        );
+
+        if block.missing_ancestors.is_empty() {
+            // we have no missing dependency, so we unsuspend the block and return it
+            return self.suspended_blocks.remove(block_ref);
+        }
+        None
     }
 
     /// Returns all the blocks that are currently missing and needed in order to accept suspended
     /// blocks.
-    pub(crate) fn missing_blocks(&self) -> Vec<BlockRef> {
-        unimplemented!()
+    pub(crate) fn missing_blocks(&self) -> BTreeSet<BlockRef> {
+        self.missing_blocks.clone()
     }
 
     /// Returns all the suspended blocks whose causal history we miss hence we can't accept them yet.
     pub(crate) fn suspended_blocks(&self) -> Vec<BlockRef> {
-        unimplemented!()
+        self.suspended_blocks.keys().cloned().collect()
+    }
+
+    fn report_metrics(&self) {
+        let metrics = &self.context.metrics.node_metrics;
+        metrics
+            .block_manager_suspended_blocks
+            .set(self.suspended_blocks.len() as i64);
+        metrics
+            .block_manager_missing_blocks
+            .set(self.missing_blocks.len() as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use consensus_config::AuthorityIndex;
+    use rand::{prelude::SliceRandom, rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        block::{Block, BlockDigest, Round, TestBlock},
+        storage::mem_store::MemStore,
+    };
+
+    /// Creates a fully connected DAG from round 1 up to and including `stop`, with every block
+    /// referencing all the blocks of the previous round.
+    fn create_dag(context: &Arc<Context>, stop: Round) -> Vec<VerifiedBlock> {
+        let (genesis_my, mut genesis) = Block::genesis(context.clone());
+        genesis.push(genesis_my);
+        let mut ancestors = genesis.iter().map(|b| b.reference()).collect::<Vec<_>>();
+
+        let mut blocks = vec![];
+        for round in 1..=stop {
+            let round_blocks = context
+                .committee
+                .authorities()
+                .map(|(index, _)| {
+                    VerifiedBlock::new_for_test(
+                        TestBlock::new(round, index.value() as u32)
+                            .set_ancestors(ancestors.clone())
+                            .build(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            ancestors = round_blocks.iter().map(|b| b.reference()).collect();
+            blocks.extend(round_blocks);
+        }
+        blocks
+    }
+
+    fn create_block_manager() -> (Arc<Context>, Arc<RwLock<DagState>>, BlockManager) {
+        let (context, _key_pairs) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let store = Arc::new(MemStore::new());
+        let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store)));
+        let block_manager = BlockManager::new(context.clone(), dag_state.clone());
+        (context, dag_state, block_manager)
+    }
+
+    #[test]
+    fn suspend_blocks_with_missing_ancestors() {
+        let (context, dag_state, mut block_manager) = create_block_manager();
+
+        // Take the blocks of rounds 2 and 3, and omit the ones of round 1.
+        let all_blocks = create_dag(&context, 3);
+        let round_2_blocks = all_blocks
+            .iter()
+            .filter(|block| block.round() >= 2)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let (accepted_blocks, missing) = block_manager.try_accept_blocks(round_2_blocks.clone());
+
+        // Nothing is accepted, and all the round 1 blocks are reported missing.
+        assert!(accepted_blocks.is_empty());
+        let round_1_refs = all_blocks
+            .iter()
+            .filter(|block| block.round() == 1)
+            .map(|block| block.reference())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(missing, round_1_refs);
+        assert_eq!(block_manager.missing_blocks(), round_1_refs);
+
+        // All the blocks are suspended.
+        let mut suspended = block_manager.suspended_blocks();
+        suspended.sort();
+        let mut expected = round_2_blocks
+            .iter()
+            .map(|block| block.reference())
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(suspended, expected);
+
+        // Nothing has been written to DagState.
+        for block in &round_2_blocks {
+            assert!(!dag_state.read().contains_block(&block.reference()));
+        }
+
+        // Trying to accept the same blocks again doesn't report them as newly missing.
+        let (accepted_blocks, missing) = block_manager.try_accept_blocks(round_2_blocks);
+        assert!(accepted_blocks.is_empty());
+        assert!(missing.is_empty());
+        assert_eq!(block_manager.missing_blocks(), round_1_refs);
+    }
+
+    #[test]
+    fn accept_blocks_with_complete_causal_history() {
+        let (context, dag_state, mut block_manager) = create_block_manager();
+
+        // Blocks of round 1 only reference genesis, which is always available.
+        let all_blocks = create_dag(&context, 2);
+
+        let (accepted_blocks, missing) = block_manager.try_accept_blocks(all_blocks.clone());
+
+        assert_eq!(accepted_blocks, all_blocks);
+        assert!(missing.is_empty());
+        assert!(block_manager.missing_blocks().is_empty());
+        assert!(block_manager.suspended_blocks().is_empty());
+        for block in &all_blocks {
+            assert!(dag_state.read().contains_block(&block.reference()));
+        }
+
+        // Accepting the same blocks again is a no-op.
+        let (accepted_blocks, missing) = block_manager.try_accept_blocks(all_blocks);
+        assert!(accepted_blocks.is_empty());
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn unsuspend_blocks_in_topological_order() {
+        let (context, dag_state, mut block_manager) = create_block_manager();
+
+        let all_blocks = create_dag(&context, 5);
+
+        // Submit all blocks except the ones of round 1, in reverse order.
+        let (round_1_blocks, later_blocks): (Vec<_>, Vec<_>) = all_blocks
+            .iter()
+            .cloned()
+            .partition(|block| block.round() == 1);
+        for block in later_blocks.iter().rev() {
+            let (accepted_blocks, _missing) = block_manager.try_accept_blocks(vec![block.clone()]);
+            assert!(accepted_blocks.is_empty());
+        }
+        assert_eq!(block_manager.suspended_blocks().len(), later_blocks.len());
+
+        // Now submit the round 1 blocks, one at a time. Only the last one unsuspends the rest.
+        let (last, rest) = round_1_blocks.split_last().unwrap();
+        for block in rest {
+            let (accepted_blocks, _missing) = block_manager.try_accept_blocks(vec![block.clone()]);
+            assert_eq!(accepted_blocks, vec![block.clone()]);
+        }
+        let (accepted_blocks, missing) = block_manager.try_accept_blocks(vec![last.clone()]);
+        assert!(missing.is_empty());
+        assert_eq!(accepted_blocks.len(), later_blocks.len() + 1);
+        assert_eq!(accepted_blocks[0], *last);
+
+        // Blocks are returned in causal order.
+        let mut accepted_refs = rest
+            .iter()
+            .map(|block| block.reference())
+            .collect::<BTreeSet<_>>();
+        for block in &accepted_blocks {
+            for ancestor in block.ancestors() {
+                assert!(
+                    ancestor.round == 0 || accepted_refs.contains(ancestor),
+                    "Block {} accepted before its ancestor {}",
+                    block.reference(),
+                    ancestor
+                );
+            }
+            accepted_refs.insert(block.reference());
+        }
+
+        assert!(block_manager.suspended_blocks().is_empty());
+        assert!(block_manager.missing_blocks().is_empty());
+        for block in &all_blocks {
+            assert!(dag_state.read().contains_block(&block.reference()));
+        }
+    }
+
+    #[test]
+    fn accept_blocks_in_random_order() {
+        let (context, _key_pairs) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let all_blocks = create_dag(&context, 10);
+
+        for seed in 0..100u8 {
+            let mut blocks = all_blocks.clone();
+            blocks.shuffle(&mut StdRng::from_seed([seed; 32]));
+
+            let store = Arc::new(MemStore::new());
+            let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store)));
+            let mut block_manager = BlockManager::new(context.clone(), dag_state.clone());
+
+            // Feed the blocks one by one, and ensure every block is eventually accepted exactly once.
+            let mut all_accepted_blocks = vec![];
+            for block in blocks {
+                let (accepted_blocks, _missing) = block_manager.try_accept_blocks(vec![block]);
+                all_accepted_blocks.extend(accepted_blocks);
+            }
+
+            all_accepted_blocks.sort_by_key(|b| b.reference());
+            let mut expected = all_blocks.clone();
+            expected.sort_by_key(|b| b.reference());
+            assert_eq!(all_accepted_blocks, expected);
+
+            assert!(block_manager.suspended_blocks().is_empty());
+            assert!(block_manager.missing_blocks().is_empty());
+            for block in &all_blocks {
+                assert!(dag_state.read().contains_block(&block.reference()));
+            }
+        }
+    }
+
+    #[test]
+    fn reference_to_unknown_genesis_is_missing() {
+        let (_context, _dag_state, mut block_manager) = create_block_manager();
+
+        // A round 0 ancestor that is not one of the genesis blocks is treated as missing.
+        let fake_genesis = BlockRef::new(0, AuthorityIndex::new_for_test(1), BlockDigest::MIN);
+        let block = VerifiedBlock::new_for_test(
+            TestBlock::new(1, 2)
+                .set_ancestors(vec![fake_genesis])
+                .build(),
+        );
+
+        let (accepted_blocks, missing) = block_manager.try_accept_blocks(vec![block.clone()]);
+        assert!(accepted_blocks.is_empty());
+        assert_eq!(missing, BTreeSet::from([fake_genesis]));
+        assert_eq!(block_manager.suspended_blocks(), vec![block.reference()]);
+    }
+
+    #[test]
+    fn reject_block_older_than_ancestors() {
+        let (context, dag_state, mut block_manager) = create_block_manager();
+
+        // Round 1 blocks with a timestamp of 10ms.
+        let (genesis_my, mut genesis) = Block::genesis(context.clone());
+        genesis.push(genesis_my);
+        let round_1_blocks = (0..4)
+            .map(|author| {
+                VerifiedBlock::new_for_test(
+                    TestBlock::new(1, author)
+                        .set_timestamp_ms(10)
+                        .set_ancestors(genesis.iter().map(|b| b.reference()).collect())
+                        .build(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let round_1_refs = round_1_blocks
+            .iter()
+            .map(|b| b.reference())
+            .collect::<Vec<_>>();
+
+        // A round 2 block older than its ancestors, and a valid round 2 block.
+        let invalid_block = VerifiedBlock::new_for_test(
+            TestBlock::new(2, 0)
+                .set_timestamp_ms(5)
+                .set_ancestors(round_1_refs.clone())
+                .build(),
+        );
+        let valid_block = VerifiedBlock::new_for_test(
+            TestBlock::new(2, 1)
+                .set_timestamp_ms(20)
+                .set_ancestors(round_1_refs)
+                .build(),
+        );
+        // A round 3 block that depends on both, and is also missing an unknown ancestor.
+        let unknown_ref = BlockRef::new(2, AuthorityIndex::new_for_test(3), BlockDigest::MIN);
+        let descendant = VerifiedBlock::new_for_test(
+            TestBlock::new(3, 2)
+                .set_timestamp_ms(30)
+                .set_ancestors(vec![
+                    invalid_block.reference(),
+                    valid_block.reference(),
+                    unknown_ref,
+                ])
+                .build(),
+        );
+
+        // The descendant is suspended first.
+        let (accepted_blocks, _missing) = block_manager.try_accept_blocks(vec![descendant.clone()]);
+        assert!(accepted_blocks.is_empty());
+        assert_eq!(
+            block_manager.suspended_blocks(),
+            vec![descendant.reference()]
+        );
+
+        // The invalid block is rejected, and the descendant is dropped with it.
+        let mut blocks = round_1_blocks.clone();
+        blocks.push(invalid_block.clone());
+        blocks.push(valid_block.clone());
+        let (accepted_blocks, _missing) = block_manager.try_accept_blocks(blocks);
+        let mut expected = round_1_blocks;
+        expected.push(valid_block);
+        assert_eq!(accepted_blocks, expected);
+        assert!(!dag_state.read().contains_block(&invalid_block.reference()));
+        assert!(block_manager.suspended_blocks().is_empty());
+        assert!(block_manager.missing_blocks().is_empty());
+        assert_eq!(
+            context
+                .metrics
+                .node_metrics
+                .invalid_blocks
+                .with_label_values(&[
+                    &context.committee.authority(invalid_block.author()).hostname,
+                    "InvalidBlockTimestamp"
+                ])
+                .get(),
+            1
+        );
     }
 }
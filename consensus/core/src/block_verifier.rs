@@ -1,9 +1,19 @@
 // Copyright (c) MangoNet Labs Ltd.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::block::Block;
+use std::{collections::BTreeSet, sync::Arc};
+
 use async_trait::async_trait;
 
+use crate::{
+    block::{Block, BlockAPI, BlockRef, SignedBlock, VerifiedBlock, GENESIS_ROUND},
+    context::Context,
+    ensure,
+    error::{ConsensusError, ConsensusResult},
+    stake_aggregator::{QuorumThreshold, StakeAggregator},
+    transactions_client::MAX_CONSUMED_TRANSACTIONS_PER_REQUEST,
+};
+
 /// The interfaces to verify the legitimacy of a statement block's contents.
 #[async_trait]
 pub trait BlockVerifier: Send + Sync + 'static {
@@ -29,3 +39,346 @@ impl BlockVerifier for TestBlockVerifier {
         Ok(())
     }
 }
+
+/// Verifies the consensus rules of blocks received from peers, independently of the application
+/// level checks of `BlockVerifier`.
+///
+/// `verify()` only needs the block itself, so it runs before a block is processed. The checks that
+/// need the ancestor blocks are done by `check_ancestors()`, once the causal history of the block
+/// has been accepted.
+pub(crate) struct SignedBlockVerifier {
+    context: Arc<Context>,
+    genesis: BTreeSet<BlockRef>,
+}
+
+impl SignedBlockVerifier {
+    pub(crate) fn new(context: Arc<Context>) -> Self {
+        let (my_genesis, others_genesis) = Block::genesis(context.clone());
+        let genesis = others_genesis
+            .iter()
+            .chain(std::iter::once(&my_genesis))
+            .map(|block| block.reference())
+            .collect();
+        Self { context, genesis }
+    }
+
+    /// Verifies the signature, the ancestors and the transactions of the block.
+    pub(crate) fn verify(&self, block: &SignedBlock) -> ConsensusResult<()> {
+        let committee = &self.context.committee;
+
+        ensure!(
+            block.epoch() == committee.epoch(),
+            ConsensusError::WrongEpoch {
+                expected: committee.epoch(),
+                actual: block.epoch(),
+            }
+        );
+        // Genesis blocks are created locally and never received from peers.
+        ensure!(
+            block.round() != GENESIS_ROUND,
+            ConsensusError::UnexpectedGenesisBlock
+        );
+        ensure!(
+            committee.exists(block.author()),
+            ConsensusError::InvalidAuthorityIndex {
+                index: block.author(),
+                max: committee.size() - 1,
+            }
+        );
+
+        block.verify(&self.context)?;
+
+        // An authority references at most one block of every other authority, except when
+        // blocks of an equivocating authority are referenced, which is still bounded.
+        let max_ancestors = committee.size() * 2;
+        ensure!(
+            block.ancestors().len() <= max_ancestors,
+            ConsensusError::TooManyAncestors(block.ancestors().len(), max_ancestors)
+        );
+
+        let mut seen_ancestors = BTreeSet::new();
+        let mut parents_stake = StakeAggregator::<QuorumThreshold>::new();
+        let mut has_parents_quorum = false;
+        for ancestor in block.ancestors() {
+            ensure!(
+                committee.exists(ancestor.author),
+                ConsensusError::InvalidAuthorityIndex {
+                    index: ancestor.author,
+                    max: committee.size() - 1,
+                }
+            );
+            ensure!(
+                ancestor.round < block.round(),
+                ConsensusError::InvalidAncestorRound {
+                    ancestor: ancestor.round,
+                    block: block.round(),
+                }
+            );
+            ensure!(
+                ancestor.round != GENESIS_ROUND || self.genesis.contains(ancestor),
+                ConsensusError::InvalidGenesisAncestor(*ancestor)
+            );
+            ensure!(
+                seen_ancestors.insert(*ancestor),
+                ConsensusError::DuplicateAncestor(*ancestor)
+            );
+            if ancestor.round + 1 == block.round() {
+                has_parents_quorum = parents_stake.add(ancestor.author, committee);
+            }
+        }
+        // A block can only be proposed once the authority has seen a quorum of blocks from the
+        // previous round, as the threshold clock only advances then.
+        ensure!(
+            has_parents_quorum,
+            ConsensusError::InsufficientParentStakes {
+                parent_stakes: parents_stake.stake(),
+                quorum: committee.quorum_threshold(),
+            }
+        );
+
+        self.verify_transactions(block)
+    }
+
+    /// Checks that the block is not older than its ancestors, so timestamps are monotonic along
+    /// the DAG. The `ancestors` must be the blocks referenced by `block`.
+    pub(crate) fn check_ancestors(
+        &self,
+        block: &VerifiedBlock,
+        ancestors: &[VerifiedBlock],
+    ) -> ConsensusResult<()> {
+        for ancestor in ancestors {
+            ensure!(
+                ancestor.timestamp_ms() <= block.timestamp_ms(),
+                ConsensusError::InvalidBlockTimestamp {
+                    ancestor: ancestor.reference(),
+                    ancestor_timestamp_ms: ancestor.timestamp_ms(),
+                    block_timestamp_ms: block.timestamp_ms(),
+                }
+            );
+        }
+        Ok(())
+    }
+
+    fn verify_transactions(&self, block: &SignedBlock) -> ConsensusResult<()> {
+        let protocol_config = &self.context.protocol_config;
+        let max_transaction_size = protocol_config.consensus_max_transaction_size_bytes();
+        let max_block_bytes = protocol_config.consensus_max_transactions_in_block_bytes();
+
+        let transactions = block.transactions();
+        ensure!(
+            transactions.len() as u64 <= MAX_CONSUMED_TRANSACTIONS_PER_REQUEST,
+            ConsensusError::TooManyTransactions {
+                count: transactions.len(),
+                limit: MAX_CONSUMED_TRANSACTIONS_PER_REQUEST,
+            }
+        );
+
+        let mut total_size = 0;
+        for transaction in transactions {
+            let size = transaction.data().len() as u64;
+            ensure!(
+                size <= max_transaction_size,
+                ConsensusError::TransactionTooLarge {
+                    size,
+                    limit: max_transaction_size,
+                }
+            );
+            total_size += size;
+        }
+        ensure!(
+            total_size <= max_block_bytes,
+            ConsensusError::TooManyTransactionBytes {
+                size: total_size,
+                limit: max_block_bytes,
+            }
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use consensus_config::{AuthorityIndex, NetworkKeyPair};
+
+    use super::*;
+    use crate::block::{BlockDigest, BlockTimestampMs, TestBlock, Transaction};
+
+    fn sign(block: Block, signer: &NetworkKeyPair) -> SignedBlock {
+        SignedBlock::new(block, signer).unwrap()
+    }
+
+    fn parents(round: u32, authorities: &[u32]) -> Vec<BlockRef> {
+        authorities
+            .iter()
+            .map(|a| BlockRef::new(round, AuthorityIndex::new_for_test(*a), BlockDigest::MIN))
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_block() {
+        let (context, keys) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let verifier = SignedBlockVerifier::new(context.clone());
+        let signer = &keys[1].0;
+
+        // A block with a quorum of parents is valid.
+        let block = TestBlock::new(5, 1)
+            .set_ancestors(parents(4, &[0, 1, 2]))
+            .build();
+        verifier.verify(&sign(block, signer)).unwrap();
+
+        // Ancestors from older rounds are allowed, as long as a quorum of parents exists.
+        let mut ancestors = parents(4, &[0, 1, 3]);
+        ancestors.extend(parents(2, &[2]));
+        let block = TestBlock::new(5, 1).set_ancestors(ancestors).build();
+        verifier.verify(&sign(block, signer)).unwrap();
+
+        // Round 1 blocks reference the genesis blocks.
+        let (my_genesis, mut genesis) = Block::genesis(context.clone());
+        genesis.push(my_genesis);
+        let block = TestBlock::new(1, 1)
+            .set_ancestors(genesis.iter().map(|b| b.reference()).collect())
+            .build();
+        verifier.verify(&sign(block, signer)).unwrap();
+    }
+
+    #[test]
+    fn test_verify_block_failures() {
+        let (context, keys) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let verifier = SignedBlockVerifier::new(context.clone());
+        let signer = &keys[1].0;
+        let valid_ancestors = parents(4, &[0, 1, 2]);
+
+        let block = TestBlock::new(5, 1)
+            .set_epoch(1)
+            .set_ancestors(valid_ancestors.clone())
+            .build();
+        assert!(matches!(
+            verifier.verify(&sign(block, signer)),
+            Err(ConsensusError::WrongEpoch { .. })
+        ));
+
+        let block = TestBlock::new(0, 1).build();
+        assert!(matches!(
+            verifier.verify(&sign(block, signer)),
+            Err(ConsensusError::UnexpectedGenesisBlock)
+        ));
+
+        let block = TestBlock::new(5, 7)
+            .set_ancestors(valid_ancestors.clone())
+            .build();
+        assert!(matches!(
+            verifier.verify(&sign(block, signer)),
+            Err(ConsensusError::InvalidAuthorityIndex { .. })
+        ));
+
+        // Signed by another authority.
+        let block = TestBlock::new(5, 1)
+            .set_ancestors(valid_ancestors.clone())
+            .build();
+        assert!(matches!(
+            verifier.verify(&sign(block, &keys[2].0)),
+            Err(ConsensusError::SignatureVerificationFailure(_))
+        ));
+
+        let mut ancestors = valid_ancestors.clone();
+        ancestors.extend(parents(5, &[3]));
+        let block = TestBlock::new(5, 1).set_ancestors(ancestors).build();
+        assert!(matches!(
+            verifier.verify(&sign(block, signer)),
+            Err(ConsensusError::InvalidAncestorRound { .. })
+        ));
+
+        let block = TestBlock::new(1, 1)
+            .set_ancestors(parents(0, &[0, 1, 2]))
+            .build();
+        assert!(matches!(
+            verifier.verify(&sign(block, signer)),
+            Err(ConsensusError::InvalidGenesisAncestor(_))
+        ));
+
+        let mut ancestors = valid_ancestors.clone();
+        ancestors.push(valid_ancestors[0]);
+        let block = TestBlock::new(5, 1).set_ancestors(ancestors).build();
+        assert!(matches!(
+            verifier.verify(&sign(block, signer)),
+            Err(ConsensusError::DuplicateAncestor(_))
+        ));
+
+        let block = TestBlock::new(5, 1)
+            .set_ancestors(parents(4, &[0, 1]))
+            .build();
+        assert!(matches!(
+            verifier.verify(&sign(block, signer)),
+            Err(ConsensusError::InsufficientParentStakes { .. })
+        ));
+
+        let max_transaction_size = context
+            .protocol_config
+            .consensus_max_transaction_size_bytes() as usize;
+        let block = TestBlock::new(5, 1)
+            .set_ancestors(valid_ancestors.clone())
+            .set_transactions(vec![Transaction::new(vec![0; max_transaction_size + 1])])
+            .build();
+        assert!(matches!(
+            verifier.verify(&sign(block, signer)),
+            Err(ConsensusError::TransactionTooLarge { .. })
+        ));
+
+        let max_block_bytes = context
+            .protocol_config
+            .consensus_max_transactions_in_block_bytes() as usize;
+        let num_transactions = max_block_bytes / max_transaction_size + 1;
+        let block = TestBlock::new(5, 1)
+            .set_ancestors(valid_ancestors)
+            .set_transactions(vec![
+                Transaction::new(vec![0; max_transaction_size]);
+                num_transactions
+            ])
+            .build();
+        assert!(matches!(
+            verifier.verify(&sign(block, signer)),
+            Err(ConsensusError::TooManyTransactionBytes { .. })
+        ));
+    }
+
+    #[test]
+    fn test_check_ancestors() {
+        let (context, _) = Context::new_for_test(4);
+        let verifier = SignedBlockVerifier::new(Arc::new(context));
+
+        let ancestors = (0..3)
+            .map(|author| {
+                VerifiedBlock::new_for_test(
+                    TestBlock::new(4, author)
+                        .set_timestamp_ms(1_000 + author as BlockTimestampMs)
+                        .build(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let ancestor_refs = ancestors.iter().map(|b| b.reference()).collect::<Vec<_>>();
+
+        // Timestamps equal to the ancestors' ones are allowed.
+        let block = VerifiedBlock::new_for_test(
+            TestBlock::new(5, 3)
+                .set_timestamp_ms(1_002)
+                .set_ancestors(ancestor_refs.clone())
+                .build(),
+        );
+        verifier.check_ancestors(&block, &ancestors).unwrap();
+
+        // The block can't be older than an ancestor.
+        let block = VerifiedBlock::new_for_test(
+            TestBlock::new(5, 3)
+                .set_timestamp_ms(1_001)
+                .set_ancestors(ancestor_refs)
+                .build(),
+        );
+        assert!(matches!(
+            verifier.check_ancestors(&block, &ancestors),
+            Err(ConsensusError::InvalidBlockTimestamp { .. })
+        ));
+    }
+}
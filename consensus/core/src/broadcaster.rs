@@ -0,0 +1,173 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{sync::Arc, time::Duration};
+
+use consensus_config::AuthorityIndex;
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    task::JoinSet,
+};
+use tracing::{trace, warn};
+
+use crate::{
+    block::{BlockAPI as _, VerifiedBlock},
+    context::Context,
+    core::CoreSignalsReceivers,
+    network::NetworkClient,
+};
+
+/// Number of times to retry sending a block to a peer before giving up on that block.
+const SEND_BLOCK_RETRIES: usize = 3;
+
+/// Delay between attempts to send a block to a peer.
+const SEND_BLOCK_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Broadcaster sends newly created blocks to each peer over the network.
+///
+/// For each peer, blocks are sent in the order they were created, one at a time, so a slow
+/// peer only delays delivery to itself. A peer that falls too far behind skips the blocks
+/// that are no longer buffered, and is expected to fetch them instead.
+pub(crate) struct Broadcaster {
+    senders: JoinSet<()>,
+}
+
+impl Broadcaster {
+    pub(crate) fn new<C: NetworkClient>(
+        context: Arc<Context>,
+        network_client: Arc<C>,
+        signals_receivers: &CoreSignalsReceivers,
+    ) -> Self {
+        let mut senders = JoinSet::new();
+        for (index, _authority) in context.committee.authorities() {
+            // Skip sending Block to self.
+            if index == context.own_index {
+                continue;
+            }
+            senders.spawn(Self::push_blocks(
+                context.clone(),
+                network_client.clone(),
+                signals_receivers.block_broadcast_receiver(),
+                index,
+            ));
+        }
+        Self { senders }
+    }
+
+    pub(crate) fn stop(&mut self) {
+        // Intentionally not waiting for senders to exit, to speed up shutdown.
+        self.senders.abort_all();
+    }
+
+    async fn push_blocks<C: NetworkClient>(
+        context: Arc<Context>,
+        network_client: Arc<C>,
+        mut rx_block_broadcast: broadcast::Receiver<VerifiedBlock>,
+        peer: AuthorityIndex,
+    ) {
+        let peer_hostname = context.committee.authority(peer).hostname.clone();
+        loop {
+            let block = match rx_block_broadcast.recv().await {
+                Ok(block) => block,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Broadcast to {peer_hostname} lagged behind, skipped {skipped} blocks");
+                    continue;
+                }
+                Err(RecvError::Closed) => {
+                    trace!("Block broadcast channel closed, stopping sends to {peer_hostname}");
+                    return;
+                }
+            };
+
+            for attempt in 1..=SEND_BLOCK_RETRIES {
+                match network_client.send_block(peer, block.serialized()).await {
+                    Ok(()) => break,
+                    Err(e) => {
+                        trace!(
+                            "Failed to send block {} to {peer_hostname} (attempt {attempt}): {e:?}",
+                            block.reference()
+                        );
+                        if attempt == SEND_BLOCK_RETRIES {
+                            warn!(
+                                "Giving up sending block {} of round {} to {peer_hostname}: {e}",
+                                block.reference(),
+                                block.round()
+                            );
+                        } else {
+                            tokio::time::sleep(SEND_BLOCK_RETRY_DELAY).await;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use parking_lot::Mutex;
+
+    use super::*;
+    use crate::{
+        block::{BlockRef, TestBlock},
+        core::CoreSignals,
+        error::ConsensusResult,
+    };
+
+    #[derive(Default)]
+    struct FakeNetworkClient {
+        blocks_sent: Mutex<BTreeMap<AuthorityIndex, Vec<Bytes>>>,
+    }
+
+    #[async_trait]
+    impl NetworkClient for FakeNetworkClient {
+        async fn send_block(&self, peer: AuthorityIndex, block: &Bytes) -> ConsensusResult<()> {
+            self.blocks_sent
+                .lock()
+                .entry(peer)
+                .or_default()
+                .push(block.clone());
+            Ok(())
+        }
+
+        async fn fetch_blocks(
+            &self,
+            _peer: AuthorityIndex,
+            _block_refs: Vec<BlockRef>,
+        ) -> ConsensusResult<Vec<Bytes>> {
+            unimplemented!("Unimplemented")
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn test_broadcaster_sends_to_all_peers() {
+        let (context, _keys) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let network_client = Arc::new(FakeNetworkClient::default());
+        let (mut core_signals, signals_receiver) = CoreSignals::new();
+        let mut broadcaster =
+            Broadcaster::new(context.clone(), network_client.clone(), &signals_receiver);
+
+        let block = VerifiedBlock::new_for_test(TestBlock::new(9, 0).build());
+        core_signals.new_block_ready(&block);
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let blocks_sent = network_client.blocks_sent.lock();
+        assert_eq!(blocks_sent.len(), 3);
+        for (index, _) in context.committee.authorities() {
+            if index == context.own_index {
+                assert!(!blocks_sent.contains_key(&index));
+            } else {
+                assert_eq!(blocks_sent[&index], vec![block.serialized().clone()]);
+            }
+        }
+        drop(blocks_sent);
+
+        broadcaster.stop();
+    }
+}
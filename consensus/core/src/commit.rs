@@ -40,6 +40,31 @@ pub(crate) struct Commit {
     pub last_committed_rounds: Vec<Round>,
 }
 
+/// The output of consensus is an ordered list of [`CommittedSubDag`]. The application
+/// can arbitrarily sort the blocks within each sub-dag (but using a deterministic algorithm).
+#[allow(unused)]
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CommittedSubDag {
+    /// A reference to the leader of the sub-dag
+    pub leader: BlockRef,
+    /// All the committed blocks that are part of this sub-dag
+    pub blocks: Vec<VerifiedBlock>,
+    /// Index of the commit.
+    pub commit_index: CommitIndex,
+}
+
+#[allow(unused)]
+impl CommittedSubDag {
+    /// Creates a new committed sub-dag.
+    pub fn new(leader: BlockRef, blocks: Vec<VerifiedBlock>, commit_index: CommitIndex) -> Self {
+        Self {
+            leader,
+            blocks,
+            commit_index,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 #[allow(unused)]
 pub(crate) enum Decision {
@@ -1,12 +1,18 @@
 // Copyright (c) MangoNet Labs Ltd.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
 
 use consensus_config::{AuthorityIndex, Committee, Parameters};
 use mgo_protocol_config::ProtocolConfig;
 
-use crate::metrics::Metrics;
+use crate::{
+    block::{timestamp_utc_ms, BlockTimestampMs},
+    metrics::Metrics,
+};
 
 #[cfg(test)]
 use crate::metrics::test_metrics;
@@ -27,6 +33,8 @@ pub(crate) struct Context {
     pub protocol_config: ProtocolConfig,
     /// Metrics of this authority.
     pub metrics: Arc<Metrics>,
+    /// Access to local clock
+    pub clock: Arc<Clock>,
 }
 
 #[allow(dead_code)]
@@ -44,6 +52,7 @@ impl Context {
             parameters,
             protocol_config,
             metrics,
+            clock: Arc::new(Clock::new()),
         }
     }
 
@@ -99,4 +108,48 @@ impl Context {
         self.metrics = metrics;
         self
     }
+
+    #[cfg(test)]
+    pub(crate) fn with_clock(mut self, clock: Arc<Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+/// A clock that returns the system time, unless it has been created as a simulated clock. A
+/// simulated clock only moves when it is explicitly advanced, which makes block timestamps
+/// reproducible in simulations.
+#[derive(Default)]
+pub(crate) struct Clock {
+    simulated_timestamp_ms: Option<AtomicU64>,
+}
+
+impl Clock {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new_simulated(timestamp_ms: BlockTimestampMs) -> Self {
+        Self {
+            simulated_timestamp_ms: Some(AtomicU64::new(timestamp_ms)),
+        }
+    }
+
+    /// Returns the current time in milliseconds since the UNIX epoch.
+    pub(crate) fn timestamp_utc_ms(&self) -> BlockTimestampMs {
+        match &self.simulated_timestamp_ms {
+            Some(timestamp_ms) => timestamp_ms.load(Ordering::Relaxed),
+            None => timestamp_utc_ms(),
+        }
+    }
+
+    /// Advances a simulated clock. Time never goes backwards.
+    #[cfg(test)]
+    pub(crate) fn advance_to(&self, timestamp_ms: BlockTimestampMs) {
+        self.simulated_timestamp_ms
+            .as_ref()
+            .expect("Only a simulated clock can be advanced")
+            .fetch_max(timestamp_ms, Ordering::Relaxed);
+    }
 }
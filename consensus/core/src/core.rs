@@ -1,23 +1,34 @@
 // Copyright (c) MangoNet Labs Ltd.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::{collections::HashSet, sync::Arc};
 
 use crate::{
     block::{
-        timestamp_utc_ms, Block, BlockAPI, BlockRef, BlockTimestampMs, BlockV1, Round, SignedBlock,
-        VerifiedBlock,
+        Block, BlockAPI, BlockRef, BlockTimestampMs, BlockV1, Round, SignedBlock, Slot,
+        VerifiedBlock, GENESIS_ROUND,
     },
     block_manager::BlockManager,
+    commit::{CommitIndex, CommittedSubDag, LeaderStatus},
     context::Context,
+    dag_state::DagState,
+    leader_schedule::LeaderSchedule,
+    linearizer::Linearizer,
     threshold_clock::ThresholdClock,
     transactions_client::TransactionsConsumer,
+    universal_committer::{
+        universal_committer_builder::UniversalCommitterBuilder, UniversalCommitter,
+    },
 };
 
 use consensus_config::{AuthorityIndex, NetworkKeyPair};
 use mango_metrics::monitored_scope;
-use tokio::sync::watch;
+use parking_lot::RwLock;
+use tokio::sync::{broadcast, watch};
+
+/// Number of newly created blocks buffered for the broadcast to peers.
+const BROADCAST_BACKLOG_CAPACITY: usize = 100;
 
 #[allow(dead_code)]
 pub(crate) struct Core {
@@ -37,57 +48,108 @@ pub(crate) struct Core {
     signals: CoreSignals,
     /// The keypair to be used for block signing
     block_signer: NetworkKeyPair,
+    /// Keeps the accepted blocks of the DAG, including our own proposals.
+    dag_state: Arc<RwLock<DagState>>,
+    /// The leader schedule, which is updated from the reputation scores of the committed sub-dags.
+    leader_schedule: LeaderSchedule,
+    /// The committer that decides the leaders of the DAG.
+    committer: UniversalCommitter,
+    /// Expands the committed leaders into commits of their causal history.
+    linearizer: Linearizer,
+    /// The last leader that has been decided, either committed or skipped.
+    last_decided_leader: Slot,
 }
 
 #[allow(dead_code)]
 impl Core {
     pub(crate) fn new(
         context: Arc<Context>,
+        leader_schedule: LeaderSchedule,
         transactions_consumer: TransactionsConsumer,
         block_manager: BlockManager,
         mut signals: CoreSignals,
         block_signer: NetworkKeyPair,
+        dag_state: Arc<RwLock<DagState>>,
     ) -> Self {
-        // TODO: restore the threshold clock round based on the last quorum data in storage when crash/recover
-        let mut threshold_clock = ThresholdClock::new(0, context.clone());
-
-        // TODO: restore based on DagState, for now we just init via the genesis
-        let (genesis_my, mut genesis_others) = Block::genesis(context.clone());
-        genesis_others.push(genesis_my.clone());
+        // Recover the last proposed block from DagState, so we never propose again for a round we have already
+        // proposed for. On a fresh start this is our genesis block. Leaders are decided again from the last committed
+        // leader onwards.
+        let (last_proposed_block, highest_accepted_round, last_decided_leader) = {
+            let dag_state = dag_state.read();
+            (
+                dag_state.get_last_block_for_authority(context.own_index),
+                dag_state.highest_accepted_round(),
+                dag_state
+                    .last_committed_leader()
+                    .unwrap_or(Slot::new(GENESIS_ROUND, AuthorityIndex::ZERO)),
+            )
+        };
 
-        // populate the threshold clock to properly advance the round & also the pending ancestors
+        // Populate the threshold clock to properly advance the round & also the pending ancestors, by replaying the
+        // accepted blocks from the round of our last proposal onwards. Earlier blocks are already referenced by our
+        // last proposal.
+        let mut threshold_clock = ThresholdClock::new(0, context.clone());
         let mut pending_ancestors: BTreeMap<Round, Vec<VerifiedBlock>> = BTreeMap::new();
-        for ancestor in genesis_others {
-            threshold_clock.add_block(ancestor.reference());
-            pending_ancestors
-                .entry(ancestor.round())
-                .or_default()
-                .push(ancestor)
+        for round in last_proposed_block.round()..=highest_accepted_round {
+            for ancestor in dag_state.read().get_cached_blocks_at_round(round) {
+                threshold_clock.add_block(ancestor.reference());
+                pending_ancestors
+                    .entry(ancestor.round())
+                    .or_default()
+                    .push(ancestor)
+            }
         }
 
+        if last_proposed_block.round() > 0 {
+            tracing::info!(
+                "Recovered last proposed block {} and threshold clock round {}",
+                last_proposed_block,
+                threshold_clock.get_round()
+            );
+        }
+        context
+            .metrics
+            .node_metrics
+            .threshold_clock_round
+            .set(threshold_clock.get_round() as i64);
+
         // emit a signal for the last threshold clock round, even if that's unnecessary it will ensure that the timeout
         // logic will trigger to attempt a block creation.
         signals.new_round(threshold_clock.get_round());
 
+        let committer = UniversalCommitterBuilder::new(
+            context.clone(),
+            leader_schedule.clone(),
+            dag_state.clone(),
+        )
+        .build();
+        let linearizer = Linearizer::new(context.clone(), dag_state.clone());
+
         Self {
             context,
             threshold_clock,
-            last_proposed_block: genesis_my,
+            last_proposed_block,
             transactions_consumer,
             pending_ancestors,
             block_manager,
             signals,
             block_signer,
+            dag_state,
+            leader_schedule,
+            committer,
+            linearizer,
+            last_decided_leader,
         }
     }
 
     /// Processes the provided blocks and accepts them if possible when their causal history exists.
     /// The method returns the references of parents that are unknown and need to be fetched.
-    pub(crate) fn add_blocks(&mut self, blocks: Vec<VerifiedBlock>) -> Vec<BlockRef> {
+    pub(crate) fn add_blocks(&mut self, blocks: Vec<VerifiedBlock>) -> BTreeSet<BlockRef> {
         let _scope = monitored_scope("Core::add_blocks");
 
-        // Try to accept them via the block manager
-        let accepted_blocks = self.block_manager.add_blocks(blocks);
+        // Try to accept them via the block manager. Accepted blocks are written to DagState, so they are
+        // available to the rest of the system, ex to serve peers.
+        let (accepted_blocks, missing_blocks) = self.block_manager.try_accept_blocks(blocks);
 
         // Advance the threshold clock. If advanced to a new round then send a signal that a new quorum has been received.
         if let Some(new_round) = self
@@ -117,11 +179,19 @@ impl Core {
                 .push(accepted_block);
         }
 
-        // Attempt to create a new block
+        // Commit the leaders that can be decided with the accepted blocks, and attempt to create a new block
+        self.try_commit();
         let _ = self.try_new_block(false);
 
-        // TODO: we don't deal for now with missed references, will address later.
-        vec![]
+        // Persist the accepted blocks, if not already done when creating a new block.
+        self.dag_state.write().flush();
+
+        missing_blocks
+    }
+
+    /// Returns all the blocks that are currently missing and needed in order to accept the suspended blocks.
+    pub(crate) fn get_missing_blocks(&self) -> BTreeSet<BlockRef> {
+        self.block_manager.missing_blocks()
     }
 
     /// Force creating a new block for the dictated round. This is used when a leader timeout occurs.
@@ -154,7 +224,7 @@ impl Core {
             // Probably proposing for all the intermediate rounds might not make much sense.
 
             // 1. Consume the ancestors to be included in proposal
-            let now = timestamp_utc_ms();
+            let now = self.context.clock.timestamp_utc_ms();
             let ancestors = self.ancestors_to_propose(clock_round, now);
 
             //2. consume the next transactions to be included.
@@ -187,16 +257,24 @@ impl Core {
                 .or_default()
                 .push(verified_block.clone());
 
-            // TODO: use the DagState instead to accept the block directly. Will address on follow up PR when I'll inject
-            // the DagState. Now that's necessary to ensure that blocks aren't double processed.
-            self.block_manager.add_blocks(vec![verified_block.clone()]);
+            // Accept the block into DagState and persist it, before it gets broadcasted, so we never equivocate
+            // after a restart. The block manager checks DagState, so the block won't be processed again if received
+            // from a peer.
+            {
+                let mut dag_state = self.dag_state.write();
+                dag_state.accept_block(verified_block.clone());
+                dag_state.flush();
+            }
 
             self.last_proposed_block = verified_block.clone();
 
             tracing::debug!("New block created {}", verified_block);
 
             //5. emit an event that a new block is ready
-            self.signals.new_block_ready(verified_block.reference());
+            self.signals.new_block_ready(&verified_block);
+
+            //6. our own block may decide more leaders
+            self.try_commit();
 
             return Some(verified_block);
         }
@@ -204,6 +282,76 @@ impl Core {
         None
     }
 
+    /// Runs the committer over the DAG and commits the decided leaders along with their causal history. Every
+    /// `CONSENSUS_COMMITS_PER_SCHEDULE` commits the leader schedule is updated from the reputation scores of the
+    /// committed sub-dags, and the leaders after the last commit are decided again with the new schedule.
+    fn try_commit(&mut self) -> Vec<CommittedSubDag> {
+        let _scope = monitored_scope("Core::try_commit");
+
+        let mut committed_subdags = Vec::new();
+        loop {
+            let mut commits_until_update = self
+                .leader_schedule
+                .commits_until_leader_schedule_update(self.last_commit_index());
+            if commits_until_update == 0 {
+                self.update_leader_schedule();
+                commits_until_update = self
+                    .leader_schedule
+                    .commits_until_leader_schedule_update(self.last_commit_index());
+            }
+
+            let decided_leaders = self.committer.try_commit(self.last_decided_leader);
+            if decided_leaders.is_empty() {
+                break;
+            }
+            for leader in decided_leaders {
+                self.last_decided_leader = Slot::new(leader.round(), leader.authority());
+                let LeaderStatus::Commit(leader_block) = leader else {
+                    continue;
+                };
+                tracing::debug!("Committing leader {}", leader_block);
+                committed_subdags.push(self.linearizer.handle_commit(leader_block));
+                commits_until_update -= 1;
+                // The leaders after the schedule update need to be elected with the new schedule.
+                if commits_until_update == 0 {
+                    break;
+                }
+            }
+        }
+
+        // Persist the commits, so they are recovered after a restart.
+        self.dag_state.write().flush();
+
+        committed_subdags
+    }
+
+    /// Updates the leader schedule from the committed sub-dags that have not been scored yet. The commits are
+    /// persisted before the reputation scores, so the scores never refer to commits that are lost on a restart.
+    fn update_leader_schedule(&mut self) {
+        let (store, unscored_subdags) = {
+            let mut dag_state = self.dag_state.write();
+            dag_state.flush();
+            (
+                dag_state.store(),
+                dag_state.take_unscored_committed_subdags(),
+            )
+        };
+        tracing::info!(
+            "Updating the leader schedule at commit index {}",
+            self.last_commit_index()
+        );
+        self.leader_schedule
+            .update_leader_schedule(store.as_ref(), &unscored_subdags)
+            .unwrap_or_else(|e| panic!("Failed to write to storage: {:?}", e));
+    }
+
+    fn last_commit_index(&self) -> CommitIndex {
+        self.dag_state
+            .read()
+            .last_commit()
+            .map_or(0, |commit| commit.index)
+    }
+
     /// Retrieves the next ancestors to propose to form a block at `clock_round` round. Also the `block_timestamp` is provided
     /// to sanity check that everything that goes into the proposal is ensured to have a timestamp < block_timestamp
     fn ancestors_to_propose(
@@ -231,7 +379,8 @@ impl Core {
             .flat_map(|block| block.ancestors())
             .collect();
 
-        let mut to_propose = HashSet::new();
+        // Keep the ancestors ordered, so the proposed block does not depend on hashing.
+        let mut to_propose = BTreeSet::new();
         for block in ancestors.into_iter() {
             if !all_ancestors_parents.contains(&block.reference()) {
                 to_propose.insert(block.reference());
@@ -274,11 +423,7 @@ impl Core {
 
     /// Returns the leaders of the provided round.
     fn leaders(&self, round: Round) -> Vec<AuthorityIndex> {
-        // TODO: this info will be retrieved from the base committers. For now just do a simple round robin so we can
-        // use it in tests.
-        vec![AuthorityIndex::new_for_test(
-            round % self.context.committee.size() as u32,
-        )]
+        self.committer.get_leaders(round)
     }
 
     fn last_proposed_round(&self) -> Round {
@@ -295,6 +440,7 @@ impl Core {
 pub(crate) struct CoreSignals {
     new_round_sender: watch::Sender<Round>,
     block_ready_sender: watch::Sender<Option<BlockRef>>,
+    tx_block_broadcast: broadcast::Sender<VerifiedBlock>,
 }
 
 impl CoreSignals {
@@ -302,23 +448,30 @@ impl CoreSignals {
     pub fn new() -> (Self, CoreSignalsReceivers) {
         let (block_ready_sender, block_ready_receiver) = watch::channel(None);
         let (new_round_sender, new_round_receiver) = watch::channel(0);
+        let (tx_block_broadcast, rx_block_broadcast) =
+            broadcast::channel(BROADCAST_BACKLOG_CAPACITY);
 
         let me = Self {
             block_ready_sender,
             new_round_sender,
+            tx_block_broadcast,
         };
 
         let receivers = CoreSignalsReceivers {
             block_ready_receiver,
             new_round_receiver,
+            rx_block_broadcast,
         };
 
         (me, receivers)
     }
 
-    /// Sends a signal to all the waiters that a new block has been produced.
-    pub fn new_block_ready(&mut self, block: BlockRef) {
-        self.block_ready_sender.send(Some(block)).ok();
+    /// Sends a signal to all the waiters that a new block has been produced, and queues the block
+    /// for broadcasting to peers.
+    pub fn new_block_ready(&mut self, block: &VerifiedBlock) {
+        self.block_ready_sender.send(Some(block.reference())).ok();
+        // An error only means that there is no subscriber, ex the network has not started yet.
+        self.tx_block_broadcast.send(block.clone()).ok();
     }
 
     /// Sends a signal that threshold clock has advanced to new round. The `round_number` is the round at which the
@@ -332,6 +485,7 @@ impl CoreSignals {
 pub(crate) struct CoreSignalsReceivers {
     block_ready_receiver: watch::Receiver<Option<BlockRef>>,
     new_round_receiver: watch::Receiver<Round>,
+    rx_block_broadcast: broadcast::Receiver<VerifiedBlock>,
 }
 
 #[allow(dead_code)]
@@ -343,17 +497,27 @@ impl CoreSignalsReceivers {
     pub(crate) fn new_round_receiver(&self) -> watch::Receiver<Round> {
         self.new_round_receiver.clone()
     }
+
+    /// Returns a receiver of all the blocks proposed from now on.
+    pub(crate) fn block_broadcast_receiver(&self) -> broadcast::Receiver<VerifiedBlock> {
+        self.rx_block_broadcast.resubscribe()
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::block::TestBlock;
+    use crate::storage::mem_store::MemStore;
+    use crate::storage::rocksdb_store::RocksDBStore;
+    use crate::storage::Store;
     use crate::transactions_client::TransactionsClient;
     use consensus_config::{Committee, Stake};
+    use mgo_protocol_config::ProtocolConfig;
+    use rstest::rstest;
     use std::collections::BTreeSet;
     use std::time::Duration;
-    use mgo_protocol_config::ProtocolConfig;
+    use tempfile::TempDir;
 
     #[tokio::test]
     async fn test_core_propose_after_genesis() {
@@ -365,17 +529,21 @@ mod test {
 
         let (context, mut key_pairs) = Context::new_for_test(4);
         let context = Arc::new(context);
-        let block_manager = BlockManager::new();
         let (transactions_client, tx_receiver) = TransactionsClient::new(context.clone());
         let transactions_consumer = TransactionsConsumer::new(tx_receiver, context.clone(), None);
         let (signals, _signal_receivers) = CoreSignals::new();
+        let store = Arc::new(MemStore::new());
+        let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store)));
+        let block_manager = BlockManager::new(context.clone(), dag_state.clone());
 
         let mut core = Core::new(
             context.clone(),
+            LeaderSchedule::new(context.clone()),
             transactions_consumer,
             block_manager,
             signals,
             key_pairs.remove(context.own_index.value()).0,
+            dag_state,
         );
 
         // Send some transactions
@@ -437,17 +605,21 @@ mod test {
     async fn test_core_propose_once_receiving_a_quorum() {
         let (context, mut key_pairs) = Context::new_for_test(4);
         let context = Arc::new(context);
-        let block_manager = BlockManager::new();
         let (_transactions_client, tx_receiver) = TransactionsClient::new(context.clone());
         let transactions_consumer = TransactionsConsumer::new(tx_receiver, context.clone(), None);
         let (signals, _signal_receivers) = CoreSignals::new();
+        let store = Arc::new(MemStore::new());
+        let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store)));
+        let block_manager = BlockManager::new(context.clone(), dag_state.clone());
 
         let mut core = Core::new(
             context.clone(),
+            LeaderSchedule::new(context.clone()),
             transactions_consumer,
             block_manager,
             signals,
             key_pairs.remove(context.own_index.value()).0,
+            dag_state,
         );
 
         let mut expected_ancestors = BTreeSet::new();
@@ -478,6 +650,49 @@ mod test {
         assert_eq!(ancestors, expected_ancestors);
     }
 
+    #[tokio::test]
+    async fn test_core_add_blocks_with_missing_ancestors() {
+        let (context, mut key_pairs) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let (_transactions_client, tx_receiver) = TransactionsClient::new(context.clone());
+        let transactions_consumer = TransactionsConsumer::new(tx_receiver, context.clone(), None);
+        let (signals, _signal_receivers) = CoreSignals::new();
+        let store = Arc::new(MemStore::new());
+        let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store)));
+        let block_manager = BlockManager::new(context.clone(), dag_state.clone());
+
+        let mut core = Core::new(
+            context.clone(),
+            LeaderSchedule::new(context.clone()),
+            transactions_consumer,
+            block_manager,
+            signals,
+            key_pairs.remove(context.own_index.value()).0,
+            dag_state.clone(),
+        );
+
+        // A block of round 2 that references a round 1 block not received yet.
+        let block_1 = VerifiedBlock::new_for_test(TestBlock::new(1, 1).build());
+        let block_2 = VerifiedBlock::new_for_test(
+            TestBlock::new(2, 1)
+                .set_ancestors(vec![block_1.reference()])
+                .build(),
+        );
+
+        // The missing ancestor is reported, and the block is not accepted.
+        let missing = core.add_blocks(vec![block_2.clone()]);
+        assert_eq!(missing, BTreeSet::from([block_1.reference()]));
+        assert_eq!(core.get_missing_blocks(), missing);
+        assert!(!dag_state.read().contains_block(&block_2.reference()));
+
+        // Once the ancestor is received, both blocks are accepted.
+        let missing = core.add_blocks(vec![block_1.clone()]);
+        assert!(missing.is_empty());
+        assert!(core.get_missing_blocks().is_empty());
+        assert!(dag_state.read().contains_block(&block_1.reference()));
+        assert!(dag_state.read().contains_block(&block_2.reference()));
+    }
+
     #[tokio::test]
     async fn test_core_try_new_block_leader_timeout() {
         // Create the cores for all authorities
@@ -630,6 +845,197 @@ mod test {
         }
     }
 
+    #[rstest]
+    #[tokio::test]
+    async fn test_core_recover_after_restart(#[values(false, true)] use_rocksdb: bool) {
+        let (context, mut key_pairs) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let block_signer = key_pairs.remove(context.own_index.value()).0;
+        let temp_dir = TempDir::new().unwrap();
+        let open_store = || -> Arc<dyn Store> {
+            if use_rocksdb {
+                Arc::new(RocksDBStore::new(temp_dir.path().to_str().unwrap()))
+            } else {
+                Arc::new(MemStore::new())
+            }
+        };
+        let create_core = |store: Arc<dyn Store>, block_signer: NetworkKeyPair| {
+            let (_transactions_client, tx_receiver) = TransactionsClient::new(context.clone());
+            let transactions_consumer =
+                TransactionsConsumer::new(tx_receiver, context.clone(), None);
+            let (signals, _signal_receivers) = CoreSignals::new();
+            let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store.clone())));
+            let block_manager = BlockManager::new(context.clone(), dag_state.clone());
+            Core::new(
+                context.clone(),
+                LeaderSchedule::from_store(context.clone(), store),
+                transactions_consumer,
+                block_manager,
+                signals,
+                block_signer,
+                dag_state,
+            )
+        };
+
+        // Creates the blocks of the other authorities for a round. They reference each other's blocks of the
+        // previous round, which is enough to form a quorum.
+        let (_, genesis) = Block::genesis(context.clone());
+        let mut ancestors = genesis.iter().map(|b| b.reference()).collect::<Vec<_>>();
+        let mut create_round_blocks = |round: Round| {
+            let blocks = (1..context.committee.size() as u32)
+                .map(|author| {
+                    VerifiedBlock::new_for_test(
+                        TestBlock::new(round, author)
+                            .set_timestamp_ms(round as BlockTimestampMs)
+                            .set_ancestors(ancestors.clone())
+                            .build(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            ancestors = blocks.iter().map(|b| b.reference()).collect();
+            blocks
+        };
+
+        // Run the authority for a few rounds. After receiving the blocks of round 3, it proposes for round 4.
+        let store = open_store();
+        let mut core = create_core(store.clone(), block_signer.copy());
+        for round in 1..=3 {
+            core.add_blocks(create_round_blocks(round));
+        }
+        let last_proposed_block = core.last_proposed_block().clone();
+        assert_eq!(last_proposed_block.round(), 4);
+        assert_eq!(core.threshold_clock.get_round(), 4);
+
+        // Kill the authority mid-round, and restart it from the store. The in-memory store stands in for the
+        // disk, so the same instance is used after the restart.
+        drop(core);
+        let store = if use_rocksdb {
+            drop(store);
+            open_store()
+        } else {
+            store
+        };
+        let mut core = create_core(store, block_signer);
+
+        // The last proposed block and the threshold clock round are recovered.
+        assert_eq!(core.last_proposed_block(), &last_proposed_block);
+        assert_eq!(core.threshold_clock.get_round(), 4);
+
+        // The authority does not propose again for a round it has already proposed for.
+        assert!(core.try_new_block(true).is_none());
+        assert!(core.force_new_block(4).is_none());
+
+        // Once the other authorities move to round 4, it proposes for round 5 referencing its recovered block.
+        core.add_blocks(create_round_blocks(4));
+        let proposed_block = core.last_proposed_block();
+        assert_eq!(proposed_block.round(), 5);
+        assert!(proposed_block
+            .ancestors()
+            .contains(&last_proposed_block.reference()));
+    }
+
+    #[tokio::test]
+    async fn test_core_commit_and_update_leader_schedule() {
+        let (context, _) = Context::new_for_test(4);
+        let mut protocol_config = context.protocol_config.clone();
+        protocol_config.set_consensus_bad_nodes_stake_threshold(33);
+        let (committee, signers) = Committee::new_for_test(0, vec![1, 1, 1, 1]);
+        let stores = (0..4)
+            .map(|_| Arc::new(MemStore::new()) as Arc<dyn Store>)
+            .collect::<Vec<_>>();
+        let create_core = |index: usize| {
+            let context = Arc::new(
+                Context::new_for_test(4)
+                    .0
+                    .with_committee(committee.clone())
+                    .with_authority_index(AuthorityIndex::new_for_test(index as u32))
+                    .with_protocol_config(protocol_config.clone()),
+            );
+            let (_transactions_client, tx_receiver) = TransactionsClient::new(context.clone());
+            let transactions_consumer =
+                TransactionsConsumer::new(tx_receiver, context.clone(), None);
+            let (signals, _signal_receivers) = CoreSignals::new();
+            let dag_state = Arc::new(RwLock::new(DagState::new(
+                context.clone(),
+                stores[index].clone(),
+            )));
+            let block_manager = BlockManager::new(context.clone(), dag_state.clone());
+            let leader_schedule =
+                LeaderSchedule::from_store(context.clone(), stores[index].clone())
+                    .with_num_commits_per_schedule(3);
+            Core::new(
+                context,
+                leader_schedule,
+                transactions_consumer,
+                block_manager,
+                signals,
+                signers[index].0.copy(),
+                dag_state,
+            )
+        };
+
+        // Authority 3 never proposes, so it gets no reputation and it is swapped out of the leader schedule once
+        // enough leaders have been committed. Until then, its leader rounds time out.
+        let mut cores = (0..3).map(create_core).collect::<Vec<_>>();
+        let slow_authority = AuthorityIndex::new_for_test(3);
+        assert_eq!(cores[0].leaders(15), vec![slow_authority]);
+
+        let mut last_round_blocks = Vec::new();
+        for round in 1..=20 {
+            let mut this_round_blocks = Vec::new();
+            for core in &mut cores {
+                core.add_blocks(last_round_blocks.clone());
+                core.force_new_block(round);
+                assert_eq!(core.last_proposed_round(), round);
+                this_round_blocks.push(core.last_proposed_block().clone());
+            }
+            last_round_blocks = this_round_blocks;
+        }
+
+        for (index, core) in cores.iter().enumerate() {
+            // The leaders of rounds 6, 9 and 12 have been committed by the committer, and have been scored.
+            let last_commit = core.dag_state.read().last_commit().cloned().unwrap();
+            assert!(last_commit.index > 3);
+            assert_eq!(
+                core.context
+                    .metrics
+                    .node_metrics
+                    .leader_schedule_updates
+                    .get(),
+                1
+            );
+            let scores = stores[index]
+                .read_last_reputation_scores()
+                .unwrap()
+                .unwrap();
+            assert_eq!(scores.last_commit_index, 3);
+            assert_eq!(scores.scores_per_authority[slow_authority], 0);
+
+            // The slow authority is not elected as leader anymore, and every authority agrees on the schedule.
+            assert_ne!(core.leaders(15), vec![slow_authority]);
+            assert_eq!(core.leaders(15), cores[0].leaders(15));
+        }
+
+        // After a restart the schedule is restored from the store, and the commits resume from the last commit.
+        let leaders = (1..=30)
+            .map(|round| cores[1].leaders(round))
+            .collect::<Vec<_>>();
+        let last_decided_leader = cores[1].last_decided_leader;
+        drop(cores);
+        let core = create_core(1);
+        assert_eq!(
+            (1..=30)
+                .map(|round| core.leaders(round))
+                .collect::<Vec<_>>(),
+            leaders
+        );
+        assert!(core.last_decided_leader.round <= last_decided_leader.round);
+        assert_eq!(
+            core.dag_state.read().last_committed_leader(),
+            Some(core.last_decided_leader)
+        );
+    }
+
     /// Creates cores for the specified number of authorities for their corresponding stakes. The method returns the
     /// cores and their respective signal receivers are returned in `AuthorityIndex` order asc.
     fn create_cores(authorities: Vec<Stake>) -> Vec<(Core, CoreSignalsReceivers)> {
@@ -644,20 +1050,24 @@ mod test {
 
             let context = Arc::new(context);
 
-            let block_manager = BlockManager::new();
             let (_transactions_client, tx_receiver) = TransactionsClient::new(context.clone());
             let transactions_consumer =
                 TransactionsConsumer::new(tx_receiver, context.clone(), None);
             let (signals, signal_receivers) = CoreSignals::new();
 
             let block_signer = signers.remove(index).0;
+            let store = Arc::new(MemStore::new());
+            let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store)));
+            let block_manager = BlockManager::new(context.clone(), dag_state.clone());
 
             let core = Core::new(
-                context,
+                context.clone(),
+                LeaderSchedule::new(context),
                 transactions_consumer,
                 block_manager,
                 signals,
                 block_signer,
+                dag_state,
             );
 
             cores.push((core, signal_receivers));
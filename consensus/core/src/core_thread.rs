@@ -3,7 +3,7 @@
 
 use async_trait::async_trait;
 use mango_metrics::{metered_channel, monitored_scope};
-use std::{collections::HashSet, fmt::Debug, sync::Arc, thread};
+use std::{collections::BTreeSet, fmt::Debug, sync::Arc, thread};
 use thiserror::Error;
 use tokio::sync::oneshot;
 use tokio::sync::oneshot::error::RecvError;
@@ -21,11 +21,12 @@ const CORE_THREAD_COMMANDS_CHANNEL_SIZE: usize = 32;
 /// The interface to adhere the implementations of the core thread dispatcher. Also allows the easier mocking during unit tests.
 #[async_trait]
 pub(crate) trait CoreThreadDispatcherInterface: Sync + Send + 'static {
-    async fn add_blocks(&self, blocks: Vec<VerifiedBlock>) -> Result<Vec<BlockRef>, CoreError>;
+    async fn add_blocks(&self, blocks: Vec<VerifiedBlock>)
+        -> Result<BTreeSet<BlockRef>, CoreError>;
 
     async fn force_new_block(&self, round: Round) -> Result<(), CoreError>;
 
-    async fn get_missing_blocks(&self) -> Result<Vec<HashSet<BlockRef>>, CoreError>;
+    async fn get_missing_blocks(&self) -> Result<BTreeSet<BlockRef>, CoreError>;
 }
 
 #[allow(unused)]
@@ -67,10 +68,10 @@ impl CoreThread {
                     sender.send(()).ok();
                 }
                 CoreThreadCommand::GetMissing(sender) => {
-                    // TODO: implement the logic to fetch the missing blocks.
-                    sender.send(vec![]).ok();
+                    sender.send(self.core.get_missing_blocks()).ok();
                 }
             }
+            self.context.metrics.node_metrics.core_lock_processed.inc();
         }
     }
 }
@@ -84,11 +85,11 @@ pub(crate) struct CoreThreadDispatcher {
 
 enum CoreThreadCommand {
     /// Add blocks to be processed and accepted
-    AddBlocks(Vec<VerifiedBlock>, oneshot::Sender<Vec<BlockRef>>),
+    AddBlocks(Vec<VerifiedBlock>, oneshot::Sender<BTreeSet<BlockRef>>),
     /// Called when a leader timeout occurs and a block should be produced
     ForceNewBlock(Round, oneshot::Sender<()>),
     /// Request missing blocks that need to be synced.
-    GetMissing(oneshot::Sender<Vec<HashSet<BlockRef>>>),
+    GetMissing(oneshot::Sender<BTreeSet<BlockRef>>),
 }
 
 #[derive(Error, Debug)]
@@ -142,7 +143,10 @@ impl CoreThreadDispatcher {
 #[async_trait]
 #[allow(unused)]
 impl CoreThreadDispatcherInterface for CoreThreadDispatcher {
-    async fn add_blocks(&self, blocks: Vec<VerifiedBlock>) -> Result<Vec<BlockRef>, CoreError> {
+    async fn add_blocks(
+        &self,
+        blocks: Vec<VerifiedBlock>,
+    ) -> Result<BTreeSet<BlockRef>, CoreError> {
         let (sender, receiver) = oneshot::channel();
         self.send(CoreThreadCommand::AddBlocks(blocks, sender))
             .await;
@@ -156,7 +160,7 @@ impl CoreThreadDispatcherInterface for CoreThreadDispatcher {
         receiver.await.map_err(Shutdown)
     }
 
-    async fn get_missing_blocks(&self) -> Result<Vec<HashSet<BlockRef>>, CoreError> {
+    async fn get_missing_blocks(&self) -> Result<BTreeSet<BlockRef>, CoreError> {
         let (sender, receiver) = oneshot::channel();
         self.send(CoreThreadCommand::GetMissing(sender)).await;
         receiver.await.map_err(Shutdown)
@@ -169,22 +173,30 @@ mod test {
     use crate::block_manager::BlockManager;
     use crate::context::Context;
     use crate::core::CoreSignals;
+    use crate::dag_state::DagState;
+    use crate::leader_schedule::LeaderSchedule;
+    use crate::storage::mem_store::MemStore;
     use crate::transactions_client::{TransactionsClient, TransactionsConsumer};
+    use parking_lot::RwLock;
 
     #[tokio::test]
     async fn test_core_thread() {
         let (context, mut key_pairs) = Context::new_for_test(4);
         let context = Arc::new(context);
-        let block_manager = BlockManager::new();
         let (_transactions_client, tx_receiver) = TransactionsClient::new(context.clone());
         let transactions_consumer = TransactionsConsumer::new(tx_receiver, context.clone(), None);
         let (signals, _signal_receivers) = CoreSignals::new();
+        let store = Arc::new(MemStore::new());
+        let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store)));
+        let block_manager = BlockManager::new(context.clone(), dag_state.clone());
         let core = Core::new(
             context.clone(),
+            LeaderSchedule::new(context.clone()),
             transactions_consumer,
             block_manager,
             signals,
             key_pairs.remove(context.own_index.value()).0,
+            dag_state,
         );
 
         let (core_dispatcher, handle) = CoreThreadDispatcher::start(core, context);
@@ -12,8 +12,10 @@ use std::{
 use consensus_config::AuthorityIndex;
 
 use crate::{
-    block::{BlockAPI, BlockDigest, BlockRef, Round, Slot, VerifiedBlock},
-    commit::Commit,
+    block::{
+        Block, BlockAPI, BlockDigest, BlockRef, EquivocationEvidence, Round, Slot, VerifiedBlock,
+    },
+    commit::{Commit, CommittedSubDag},
     context::Context,
     storage::Store,
 };
@@ -33,6 +35,9 @@ const CACHED_ROUNDS: Round = 100;
 pub(crate) struct DagState {
     context: Arc<Context>,
 
+    // The genesis blocks of the current epoch, which are always considered accepted.
+    genesis: BTreeMap<BlockRef, VerifiedBlock>,
+
     // Caches blocks within CACHED_ROUNDS from the last committed round per authority.
     // Note: uncommitted blocks will always be in memory.
     recent_blocks: BTreeMap<BlockRef, VerifiedBlock>,
@@ -47,13 +52,23 @@ pub(crate) struct DagState {
     // Highest round of blocks accepted.
     highest_accepted_round: Round,
 
+    // Committed sub-dags that have not been used to calculate reputation scores yet. These are
+    // restored from storage on startup, so the leader schedule is updated consistently.
+    unscored_committed_subdags: Vec<CommittedSubDag>,
+
+    // Accepted blocks and commits that have not been persisted yet.
+    blocks_to_write: Vec<VerifiedBlock>,
+    commits_to_write: Vec<Commit>,
+
     // Persistent storage for blocks, commits and other consensus data.
     store: Arc<dyn Store>,
 }
 
 #[allow(unused)]
 impl DagState {
-    /// Initializes DagState from storage.
+    /// Initializes DagState from storage. Recent blocks of every authority, the last commit and
+    /// the commits that have not been scored yet are recovered, so an authority resumes from
+    /// where it stopped.
     pub(crate) fn new(context: Arc<Context>, store: Arc<dyn Store>) -> Self {
         let num_authorities = context.committee.size();
         let last_commit = store.read_last_commit().unwrap();
@@ -62,13 +77,24 @@ impl DagState {
             None => vec![0; num_authorities],
         };
 
+        let (genesis_my, mut genesis_others) = Block::genesis(context.clone());
+        genesis_others.push(genesis_my);
+        let genesis = genesis_others
+            .into_iter()
+            .map(|block| (block.reference(), block))
+            .collect();
+
         let mut state = Self {
             context,
+            genesis,
             recent_blocks: BTreeMap::new(),
             cached_refs: vec![BTreeSet::new(); num_authorities],
             last_commit,
             store,
             highest_accepted_round: 0,
+            unscored_committed_subdags: vec![],
+            blocks_to_write: vec![],
+            commits_to_write: vec![],
         };
 
         for (i, round) in last_committed_rounds.into_iter().enumerate() {
@@ -78,15 +104,88 @@ impl DagState {
                 .scan_blocks_by_author(authority_index, round.saturating_sub(CACHED_ROUNDS))
                 .unwrap();
             for block in blocks {
-                state.accept_block(block);
+                state.update_block_metadata(block);
             }
         }
 
+        // Recover the commits after the last reputation scores, which are needed for the next
+        // leader schedule update.
+        let last_scored_commit_index = state
+            .store
+            .read_last_reputation_scores()
+            .unwrap()
+            .map_or(0, |scores| scores.last_commit_index);
+        let unscored_commits = state
+            .store
+            .scan_commits(last_scored_commit_index + 1)
+            .unwrap();
+        for commit in unscored_commits {
+            let subdag = state.committed_subdag(&commit);
+            state.unscored_committed_subdags.push(subdag);
+        }
+
         state
     }
 
-    /// Accepts a block into DagState and keeps it in memory.
+    /// Accepts a block into DagState and keeps it in memory. The block is persisted on the next flush.
     pub(crate) fn accept_block(&mut self, block: VerifiedBlock) {
+        self.check_equivocation(&block);
+        self.update_block_metadata(block.clone());
+        self.blocks_to_write.push(block);
+    }
+
+    /// Records an equivocation evidence when the block conflicts with an accepted block of the same
+    /// author and round. Only the first conflict in a slot is recorded.
+    fn check_equivocation(&self, block: &VerifiedBlock) {
+        let block_ref = block.reference();
+        // Own slots are guarded against multiple blocks when updating the metadata.
+        if block_ref.author == self.context.own_index {
+            return;
+        }
+
+        let mut slot_refs = self.cached_refs[block_ref.author].range((
+            Included(BlockRef::new(
+                block_ref.round,
+                block_ref.author,
+                BlockDigest::MIN,
+            )),
+            Included(BlockRef::new(
+                block_ref.round,
+                block_ref.author,
+                BlockDigest::MAX,
+            )),
+        ));
+        let Some(existing_ref) = slot_refs.next() else {
+            return;
+        };
+        if *existing_ref == block_ref || slot_refs.next().is_some() {
+            return;
+        }
+
+        let existing_block = self
+            .get_blocks(&[*existing_ref])
+            .pop()
+            .flatten()
+            .unwrap_or_else(|| panic!("Block {:?} should be available!", existing_ref));
+        tracing::warn!(
+            "Equivocation detected: authority {} proposed blocks {} and {}",
+            block_ref.author,
+            existing_ref,
+            block_ref
+        );
+        self.store
+            .write_equivocation_evidence(&EquivocationEvidence::new(&existing_block, block))
+            .unwrap_or_else(|e| panic!("Failed to write to storage: {:?}", e));
+        self.context
+            .metrics
+            .node_metrics
+            .equivocations
+            .with_label_values(&[&self.context.committee.authority(block_ref.author).hostname])
+            .inc();
+    }
+
+    /// Updates the in-memory state with the block, without persisting it.
+    fn update_block_metadata(&mut self, block: VerifiedBlock) {
         let block_ref = block.reference();
         let block_round = block.round();
 
@@ -106,13 +205,121 @@ impl DagState {
     }
 
     /// Accepts a blocks into DagState and keeps it in memory.
-    #[cfg(test)]
     pub(crate) fn accept_blocks(&mut self, blocks: Vec<VerifiedBlock>) {
         for block in blocks {
             self.accept_block(block);
         }
     }
 
+    /// Gets blocks by checking cached recent blocks in memory, then storage.
+    /// An element is None when the corresponding block is not found.
+    pub(crate) fn get_blocks(&self, block_refs: &[BlockRef]) -> Vec<Option<VerifiedBlock>> {
+        let mut blocks = vec![None; block_refs.len()];
+        let mut missing = Vec::new();
+
+        for (index, block_ref) in block_refs.iter().enumerate() {
+            if let Some(block) = self
+                .genesis
+                .get(block_ref)
+                .or_else(|| self.recent_blocks.get(block_ref))
+            {
+                blocks[index] = Some(block.clone());
+            } else {
+                missing.push((index, *block_ref));
+            }
+        }
+
+        if missing.is_empty() {
+            return blocks;
+        }
+
+        let missing_refs = missing.iter().map(|(_, r)| *r).collect::<Vec<_>>();
+        let store_results = self
+            .store
+            .read_blocks(&missing_refs)
+            .unwrap_or_else(|e| panic!("Failed to read from storage: {:?}", e));
+        for ((index, _), result) in missing.into_iter().zip(store_results.into_iter()) {
+            blocks[index] = result;
+        }
+
+        blocks
+    }
+
+    /// Returns the last block proposed by the authority, or its genesis block if no block has been
+    /// proposed yet.
+    pub(crate) fn get_last_block_for_authority(&self, authority: AuthorityIndex) -> VerifiedBlock {
+        if let Some(last_ref) = self.cached_refs[authority].last() {
+            if let Some(block) = self.get_blocks(&[*last_ref]).pop().flatten() {
+                return block;
+            }
+            panic!("Block {:?} should be available!", last_ref);
+        }
+        self.genesis
+            .values()
+            .find(|block| block.author() == authority)
+            .cloned()
+            .expect("Genesis block should exist for every authority")
+    }
+
+    /// Gets all the blocks of a round that are cached in memory, including the genesis blocks for
+    /// round 0. Unlike `get_uncommitted_blocks_at_round()`, the round can have committed blocks.
+    pub(crate) fn get_cached_blocks_at_round(&self, round: Round) -> Vec<VerifiedBlock> {
+        if round == 0 {
+            return self.genesis.values().cloned().collect();
+        }
+        self.recent_blocks
+            .range((
+                Included(BlockRef::new(round, AuthorityIndex::ZERO, BlockDigest::MIN)),
+                Excluded(BlockRef::new(
+                    round + 1,
+                    AuthorityIndex::ZERO,
+                    BlockDigest::MIN,
+                )),
+            ))
+            .map(|(_, block)| block.clone())
+            .collect()
+    }
+
+    /// Checks whether a block exists in the DAG, including the genesis blocks.
+    pub(crate) fn contains_block(&self, block_ref: &BlockRef) -> bool {
+        self.contains_blocks(&[*block_ref])[0]
+    }
+
+    /// Checks whether blocks exist in the DAG, by checking the genesis blocks and cached refs
+    /// first, then storage.
+    pub(crate) fn contains_blocks(&self, block_refs: &[BlockRef]) -> Vec<bool> {
+        let mut exist = vec![false; block_refs.len()];
+        let mut missing = Vec::new();
+
+        for (index, block_ref) in block_refs.iter().enumerate() {
+            if self.genesis.contains_key(block_ref)
+                || self
+                    .cached_refs
+                    .get(block_ref.author.value())
+                    .is_some_and(|refs| refs.contains(block_ref))
+            {
+                exist[index] = true;
+            } else {
+                missing.push((index, *block_ref));
+            }
+        }
+
+        if missing.is_empty() {
+            return exist;
+        }
+
+        let missing_refs = missing.iter().map(|(_, r)| *r).collect::<Vec<_>>();
+        let store_results = self
+            .store
+            .contains_blocks(&missing_refs)
+            .unwrap_or_else(|e| panic!("Failed to read from storage: {:?}", e));
+        for ((index, _), result) in missing.into_iter().zip(store_results.into_iter()) {
+            exist[index] = result;
+        }
+
+        exist
+    }
+
     /// Gets a copy of an uncommitted block. Returns None if not found.
     /// Uncommitted blocks must exist in memory, so only in-memory blocks are checked.
     pub(crate) fn get_uncommitted_block(&self, reference: &BlockRef) -> Option<VerifiedBlock> {
@@ -206,6 +413,76 @@ impl DagState {
         self.highest_accepted_round
     }
 
+    /// Adds a commit to DagState. The committed blocks must have been accepted already. The
+    /// commit is persisted on the next flush.
+    pub(crate) fn add_commit(&mut self, commit: Commit) {
+        if let Some(last_commit) = &self.last_commit {
+            assert_eq!(
+                commit.index,
+                last_commit.index + 1,
+                "Commit {} does not follow the last commit {}",
+                commit.index,
+                last_commit.index
+            );
+        }
+        let subdag = self.committed_subdag(&commit);
+        self.unscored_committed_subdags.push(subdag);
+        self.last_commit = Some(commit.clone());
+        self.commits_to_write.push(commit);
+    }
+
+    /// Returns the last commit, if any.
+    pub(crate) fn last_commit(&self) -> Option<&Commit> {
+        self.last_commit.as_ref()
+    }
+
+    /// Returns the leader of the last commit, if any.
+    pub(crate) fn last_committed_leader(&self) -> Option<Slot> {
+        self.last_commit.as_ref().map(|commit| commit.leader.into())
+    }
+
+    /// Returns the committed sub-dags that have not been scored yet.
+    pub(crate) fn unscored_committed_subdags(&self) -> &[CommittedSubDag] {
+        &self.unscored_committed_subdags
+    }
+
+    /// Takes the committed sub-dags that have not been scored yet, once they have been used to
+    /// update the leader schedule.
+    pub(crate) fn take_unscored_committed_subdags(&mut self) -> Vec<CommittedSubDag> {
+        std::mem::take(&mut self.unscored_committed_subdags)
+    }
+
+    /// Returns the storage that DagState persists to.
+    pub(crate) fn store(&self) -> Arc<dyn Store> {
+        self.store.clone()
+    }
+
+    /// Persists the accepted blocks and commits that have not been written to storage yet.
+    /// Own blocks must be flushed before they are sent to peers, so an authority does not
+    /// equivocate after a restart.
+    pub(crate) fn flush(&mut self) {
+        if self.blocks_to_write.is_empty() && self.commits_to_write.is_empty() {
+            return;
+        }
+        let blocks = std::mem::take(&mut self.blocks_to_write);
+        let commits = std::mem::take(&mut self.commits_to_write);
+        self.store
+            .write(blocks, commits)
+            .unwrap_or_else(|e| panic!("Failed to write to storage: {:?}", e));
+    }
+
+    fn committed_subdag(&self, commit: &Commit) -> CommittedSubDag {
+        let blocks = self
+            .get_blocks(&commit.blocks)
+            .into_iter()
+            .zip(commit.blocks.iter())
+            .map(|(block, block_ref)| {
+                block.unwrap_or_else(|| panic!("Committed block {:?} should exist!", block_ref))
+            })
+            .collect();
+        CommittedSubDag::new(commit.leader, blocks, commit.index)
+    }
+
     /// Highest round where a block is committed, which is last commit's leader round.
     fn last_commit_round(&self) -> Round {
         match &self.last_commit {
@@ -222,6 +499,7 @@ mod test {
     use super::*;
     use crate::{
         block::{BlockDigest, BlockRef, BlockTimestampMs, TestBlock, VerifiedBlock},
+        leader_scoring::ReputationScores,
         storage::mem_store::MemStore,
     };
 
@@ -487,4 +765,150 @@ mod test {
             expected_refs, ancestors_refs
         );
     }
+
+    #[test]
+    fn flush_and_recover() {
+        let (context, _) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let store = Arc::new(MemStore::new());
+        let mut dag_state = DagState::new(context.clone(), store.clone());
+
+        // Accept blocks for rounds 1 ~ 5 from all authorities.
+        let mut ancestors = vec![];
+        let mut blocks = vec![];
+        for round in 1..=5 {
+            let round_blocks = (0..4)
+                .map(|author| {
+                    VerifiedBlock::new_for_test(
+                        TestBlock::new(round, author)
+                            .set_ancestors(ancestors.clone())
+                            .build(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            ancestors = round_blocks.iter().map(|b| b.reference()).collect();
+            blocks.extend(round_blocks);
+        }
+        dag_state.accept_blocks(blocks.clone());
+
+        // Commit the blocks of rounds 1 and 2, with the leader of round 2.
+        let commit = Commit {
+            index: 1,
+            leader: blocks[5].reference(),
+            blocks: blocks[..8].iter().map(|b| b.reference()).collect(),
+            last_committed_rounds: vec![2; 4],
+        };
+        dag_state.add_commit(commit.clone());
+        assert_eq!(
+            dag_state.last_committed_leader(),
+            Some(Slot::from(blocks[5].reference()))
+        );
+        assert_eq!(dag_state.unscored_committed_subdags().len(), 1);
+
+        // Nothing is persisted until flushed.
+        assert!(store.read_last_commit().unwrap().is_none());
+        assert_eq!(
+            store.contains_blocks(&[blocks[0].reference()]).unwrap(),
+            vec![false]
+        );
+        dag_state.flush();
+
+        // Restart from the store.
+        drop(dag_state);
+        let dag_state = DagState::new(context.clone(), store.clone());
+
+        assert_eq!(dag_state.last_commit(), Some(&commit));
+        assert_eq!(dag_state.highest_accepted_round(), 5);
+        for block in &blocks {
+            assert!(dag_state.contains_block(&block.reference()));
+        }
+        for author in 0..4 {
+            let author = AuthorityIndex::new_for_test(author);
+            let last_block = dag_state.get_last_block_for_authority(author);
+            assert_eq!(last_block.round(), 5);
+            assert_eq!(last_block.author(), author);
+        }
+        assert_eq!(
+            dag_state.get_cached_blocks_at_round(5),
+            blocks[16..].to_vec()
+        );
+
+        // The commit has not been scored yet, so it is recovered as pending.
+        let unscored = dag_state.unscored_committed_subdags();
+        assert_eq!(unscored.len(), 1);
+        assert_eq!(unscored[0].leader, commit.leader);
+        assert_eq!(unscored[0].blocks, blocks[..8].to_vec());
+
+        // Once scored, the commit is no longer recovered as pending.
+        store
+            .write_reputation_scores(&ReputationScores::new(1, 1, vec![0; 4]))
+            .unwrap();
+        let dag_state = DagState::new(context, store);
+        assert!(dag_state.unscored_committed_subdags().is_empty());
+        assert_eq!(dag_state.last_commit(), Some(&commit));
+    }
+
+    #[test]
+    fn get_last_block_for_authority_from_genesis() {
+        let (context, _) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let store = Arc::new(MemStore::new());
+        let dag_state = DagState::new(context.clone(), store);
+
+        for (index, _) in context.committee.authorities() {
+            let block = dag_state.get_last_block_for_authority(index);
+            assert_eq!(block.round(), 0);
+            assert_eq!(block.author(), index);
+        }
+        assert_eq!(dag_state.get_cached_blocks_at_round(0).len(), 4);
+    }
+
+    #[test]
+    fn accept_block_detects_equivocation() {
+        let (context, _) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let store = Arc::new(MemStore::new());
+        let mut dag_state = DagState::new(context.clone(), store.clone());
+        let author = AuthorityIndex::new_for_test(2);
+        let equivocations = || {
+            context
+                .metrics
+                .node_metrics
+                .equivocations
+                .with_label_values(&[&context.committee.authority(author).hostname])
+                .get()
+        };
+
+        // Three different blocks for the same slot.
+        let blocks = (1..=3)
+            .map(|timestamp_ms| {
+                VerifiedBlock::new_for_test(
+                    TestBlock::new(5, author.value() as u32)
+                        .set_timestamp_ms(timestamp_ms)
+                        .build(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        dag_state.accept_block(blocks[0].clone());
+        assert!(store
+            .read_equivocation_evidences(author)
+            .unwrap()
+            .is_empty());
+        assert_eq!(equivocations(), 0);
+
+        // The second block is an equivocation.
+        dag_state.accept_block(blocks[1].clone());
+        let evidences = store.read_equivocation_evidences(author).unwrap();
+        assert_eq!(
+            evidences,
+            vec![EquivocationEvidence::new(&blocks[0], &blocks[1])]
+        );
+        assert_eq!(equivocations(), 1);
+
+        // Further blocks in the same slot are not reported again.
+        dag_state.accept_block(blocks[2].clone());
+        assert_eq!(store.read_equivocation_evidences(author).unwrap().len(), 1);
+        assert_eq!(equivocations(), 1);
+    }
 }
@@ -1,13 +1,17 @@
 // Copyright (c) MangoNet Labs Ltd.
 // SPDX-License-Identifier: Apache-2.0
 
+use consensus_config::{AuthorityIndex, Epoch, Stake};
 use fastcrypto::error::FastCryptoError;
+use strum_macros::IntoStaticStr;
 use thiserror::Error;
 use typed_store::TypedStoreError;
 
+use crate::block::{BlockRef, BlockTimestampMs, Round};
+
 /// Errors that can occur when processing blocks, reading from storage, or encountering shutdown.
 #[allow(unused)]
-#[derive(Clone, Debug, Error)]
+#[derive(Clone, Debug, Error, IntoStaticStr)]
 pub enum ConsensusError {
     #[error("Error deserializing block: {0}")]
     MalformedBlock(#[from] bcs::Error),
@@ -20,6 +24,92 @@ pub enum ConsensusError {
 
     #[error("Unknown authority provided: {0}")]
     UnknownAuthority(String),
+
+    #[error("Block author {block_author} does not match the sending peer {peer}")]
+    UnexpectedAuthority {
+        block_author: AuthorityIndex,
+        peer: AuthorityIndex,
+    },
+
+    #[error("Invalid block: {0}")]
+    InvalidBlock(String),
+
+    #[error("Block has wrong epoch: expected {expected}, actual {actual}")]
+    WrongEpoch { expected: Epoch, actual: Epoch },
+
+    #[error("Genesis blocks should only be generated from Committee!")]
+    UnexpectedGenesisBlock,
+
+    #[error("Invalid authority index: {index} > {max}")]
+    InvalidAuthorityIndex { index: AuthorityIndex, max: usize },
+
+    #[error("Too many ancestors in the block: {0} > {1}")]
+    TooManyAncestors(usize, usize),
+
+    #[error("Ancestor's round ({ancestor}) should be lower than the block's round ({block})")]
+    InvalidAncestorRound { ancestor: Round, block: Round },
+
+    #[error("Ancestor {0:?} is not a genesis block")]
+    InvalidGenesisAncestor(BlockRef),
+
+    #[error("Ancestor {0:?} is referenced more than once")]
+    DuplicateAncestor(BlockRef),
+
+    #[error("Insufficient stake from parents: {parent_stakes} < {quorum}")]
+    InsufficientParentStakes { parent_stakes: Stake, quorum: Stake },
+
+    #[error("Too many transactions in the block: {count} > {limit}")]
+    TooManyTransactions { count: usize, limit: u64 },
+
+    #[error("Transaction is too large: {size} > {limit} bytes")]
+    TransactionTooLarge { size: u64, limit: u64 },
+
+    #[error("Too many transaction bytes in the block: {size} > {limit}")]
+    TooManyTransactionBytes { size: u64, limit: u64 },
+
+    #[error(
+        "Block timestamp {block_timestamp_ms} is lower than the timestamp \
+        {ancestor_timestamp_ms} of ancestor {ancestor:?}"
+    )]
+    InvalidBlockTimestamp {
+        ancestor: BlockRef,
+        ancestor_timestamp_ms: BlockTimestampMs,
+        block_timestamp_ms: BlockTimestampMs,
+    },
+
+    #[error("Invalid equivocation evidence: {0}")]
+    InvalidEquivocationEvidence(String),
+
+    #[error("Too many blocks have been requested from authority {0}: {1}")]
+    TooManyFetchBlocksRequested(AuthorityIndex, usize),
+
+    #[error("Authority {index} returned block {block_ref:?} that was not requested")]
+    UnexpectedFetchedBlock {
+        index: AuthorityIndex,
+        block_ref: BlockRef,
+    },
+
+    #[error("Peer {0} is not connected")]
+    PeerDisconnected(String),
+
+    #[error("Network request to peer {0} timed out")]
+    NetworkRequestTimeout(String),
+
+    #[error("Network error: {0}")]
+    NetworkError(String),
+
+    #[error("Network has not been started")]
+    NetworkNotStarted,
+
+    #[error("Consensus has shut down")]
+    Shutdown,
+}
+
+impl ConsensusError {
+    /// Returns the variant name of the error, for use as a metric label.
+    pub fn name(&self) -> &'static str {
+        self.into()
+    }
 }
 
 #[allow(unused)]
@@ -1,30 +1,138 @@
 // Copyright (c) MangoNet Labs Ltd.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::sync::Arc;
+use std::{
+    collections::BTreeSet,
+    fmt::{Debug, Formatter},
+    sync::Arc,
+};
 
+use consensus_config::{AuthorityIndex, Stake};
+use parking_lot::RwLock;
 use rand::{prelude::SliceRandom, rngs::StdRng, SeedableRng};
+use tracing::{debug, trace};
 
-use consensus_config::AuthorityIndex;
+use crate::{
+    commit::{CommitIndex, CommittedSubDag},
+    context::Context,
+    error::ConsensusResult,
+    leader_scoring::{ReputationScoreCalculator, ReputationScores},
+    storage::Store,
+};
 
-use crate::context::Context;
+/// The number of commits after which the reputation scores are calculated and the leader
+/// schedule gets updated.
+pub(crate) const CONSENSUS_COMMITS_PER_SCHEDULE: u64 = 300;
 
 /// The LeaderSchedule is responsible for producing the leader schedule across
-/// an epoch. For now it is a simple wrapper around Context to provide a leader
-/// for a round deterministically.
-// TODO: complete full leader schedule changes
+/// an epoch. It provides methods to derive the leader of a round based on the
+/// current leader swap table. This struct can be cloned and shared freely as
+/// the internal parts are atomically updated.
 #[derive(Clone)]
 pub(crate) struct LeaderSchedule {
     context: Arc<Context>,
+    num_commits_per_schedule: u64,
+    leader_swap_table: Arc<RwLock<LeaderSwapTable>>,
 }
 
 #[allow(unused)]
 impl LeaderSchedule {
     pub fn new(context: Arc<Context>) -> Self {
-        Self { context }
+        Self::with_leader_swap_table(context, LeaderSwapTable::default())
     }
 
+    pub fn with_leader_swap_table(context: Arc<Context>, table: LeaderSwapTable) -> Self {
+        Self {
+            context,
+            num_commits_per_schedule: CONSENSUS_COMMITS_PER_SCHEDULE,
+            leader_swap_table: Arc::new(RwLock::new(table)),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_num_commits_per_schedule(mut self, num_commits_per_schedule: u64) -> Self {
+        self.num_commits_per_schedule = num_commits_per_schedule;
+        self
+    }
+
+    /// Restores the LeaderSchedule from storage. The last persisted reputation scores are used
+    /// to rebuild the LeaderSwapTable, so all authorities agree on the schedule after a restart.
+    pub fn from_store(context: Arc<Context>, store: Arc<dyn Store>) -> Self {
+        let table = store
+            .read_last_reputation_scores()
+            .unwrap_or_else(|e| panic!("Failed to read from storage: {:?}", e))
+            .map_or(LeaderSwapTable::default(), |reputation_scores| {
+                LeaderSwapTable::new(
+                    context.clone(),
+                    reputation_scores,
+                    Self::bad_nodes_stake_threshold(&context),
+                )
+            });
+        Self::with_leader_swap_table(context, table)
+    }
+
+    /// Returns the number of commits remaining until the next leader schedule update, given the
+    /// index of the last commit.
+    pub fn commits_until_leader_schedule_update(&self, last_commit_index: CommitIndex) -> u64 {
+        let last_scored_commit_index = self
+            .leader_swap_table
+            .read()
+            .reputation_scores
+            .last_commit_index;
+        assert!(
+            last_commit_index >= last_scored_commit_index,
+            "Last commit index {last_commit_index} is lower than the last scored commit index {last_scored_commit_index}"
+        );
+        self.num_commits_per_schedule
+            .saturating_sub(last_commit_index - last_scored_commit_index)
+    }
+
+    /// Calculates the reputation scores from the committed sub-dags of the schedule period that
+    /// just completed, persists them and updates the leader swap table. Any leader elected from
+    /// now on uses the new swap table.
+    pub fn update_leader_schedule(
+        &self,
+        store: &dyn Store,
+        unscored_subdags: &[CommittedSubDag],
+    ) -> ConsensusResult<()> {
+        let reputation_scores =
+            ReputationScoreCalculator::new(self.context.clone(), unscored_subdags).calculate();
+        reputation_scores.update_metrics(self.context.clone());
+
+        // Persist the scores before using them, so the same schedule is restored after a restart.
+        store.write_reputation_scores(&reputation_scores)?;
+
+        self.update_leader_swap_table(LeaderSwapTable::new(
+            self.context.clone(),
+            reputation_scores,
+            Self::bad_nodes_stake_threshold(&self.context),
+        ));
+        self.context
+            .metrics
+            .node_metrics
+            .leader_schedule_updates
+            .inc();
+        Ok(())
+    }
+
+    /// Atomically updates the leader swap table with the new provided one. Any leader queried from
+    /// now on will get calculated according to this swap table until a new one is provided again.
+    fn update_leader_swap_table(&self, table: LeaderSwapTable) {
+        trace!("Updating {:?}", table);
+        *self.leader_swap_table.write() = table;
+    }
+
+    /// Elects the leader of a round, swapping it with a good performer if it is among the
+    /// worst performers of the last schedule period.
     pub fn elect_leader(&self, round: u32, leader_offset: u32) -> AuthorityIndex {
+        let leader = self.elect_base_leader(round, leader_offset);
+        self.leader_swap_table
+            .read()
+            .swap(leader, round, leader_offset)
+            .unwrap_or(leader)
+    }
+
+    fn elect_base_leader(&self, round: u32, leader_offset: u32) -> AuthorityIndex {
         cfg_if::cfg_if! {
             // TODO: we need to differentiate the leader strategy in tests, so for
             // some type of testing (ex sim tests) we can use the staked approach.
@@ -64,13 +172,179 @@ impl LeaderSchedule {
 
         leader_index
     }
+
+    /// The total stake (as a percentage) of the worst performing authorities that get swapped out
+    /// of the schedule. Swapping is disabled when the protocol config does not set it.
+    fn bad_nodes_stake_threshold(context: &Context) -> u64 {
+        context
+            .protocol_config
+            .consensus_bad_nodes_stake_threshold_as_option()
+            .unwrap_or(0)
+    }
+}
+
+/// The LeaderSwapTable swaps the leaders with the worst reputation scores out of the schedule,
+/// replacing them with the authorities with the best scores.
+#[derive(Default, Clone)]
+pub(crate) struct LeaderSwapTable {
+    /// The list of `f` (by configurable stake) authorities with best scores as
+    /// those defined by the provided `ReputationScores`. Those authorities will
+    /// be used in the position of the `bad_nodes` on the final leader schedule.
+    good_nodes: Vec<AuthorityIndex>,
+    /// The set of `f` (by configurable stake) authorities with the worst scores
+    /// as those defined by the provided `ReputationScores`. Every time where such
+    /// authority is elected as leader on the schedule, it will swapped by one of
+    /// the authorities of the `good_nodes`.
+    bad_nodes: BTreeSet<AuthorityIndex>,
+    /// The scores the table has been built from.
+    reputation_scores: ReputationScores,
+}
+
+impl LeaderSwapTable {
+    // Constructs a new table based on the provided reputation scores. The
+    // `bad_nodes_stake_threshold` designates the total (by stake) nodes that
+    // will be considered as "bad" based on their scores and will be replaced by
+    // good nodes. The `bad_nodes_stake_threshold` should be in the range of [0 - 33].
+    pub(crate) fn new(
+        context: Arc<Context>,
+        reputation_scores: ReputationScores,
+        bad_nodes_stake_threshold: u64,
+    ) -> Self {
+        assert!(
+            (0..=33).contains(&bad_nodes_stake_threshold),
+            "The bad_nodes_stake_threshold should be in range [0 - 33], out of bounds parameter detected"
+        );
+
+        let authorities = reputation_scores.authorities_by_score_desc(context.clone());
+
+        // Calculating the good nodes.
+        let good_nodes = Self::retrieve_first_nodes(
+            &context,
+            authorities.iter().cloned(),
+            bad_nodes_stake_threshold,
+        );
+
+        // Calculating the bad nodes. We reverse the sorted authorities to score ascending
+        // so we get the first low scorers up to the dictated stake threshold.
+        let bad_nodes = Self::retrieve_first_nodes(
+            &context,
+            authorities.iter().rev().cloned(),
+            bad_nodes_stake_threshold,
+        )
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+
+        for good_node in &good_nodes {
+            debug!(
+                "Good node {} with score {} for commits [{}, {}]",
+                context.committee.authority(*good_node).hostname,
+                reputation_scores.scores_per_authority[*good_node],
+                reputation_scores.first_commit_index,
+                reputation_scores.last_commit_index,
+            );
+        }
+        for bad_node in &bad_nodes {
+            debug!(
+                "Bad node {} with score {} for commits [{}, {}]",
+                context.committee.authority(*bad_node).hostname,
+                reputation_scores.scores_per_authority[*bad_node],
+                reputation_scores.first_commit_index,
+                reputation_scores.last_commit_index,
+            );
+        }
+
+        Self {
+            good_nodes,
+            bad_nodes,
+            reputation_scores,
+        }
+    }
+
+    /// Checks whether the provided leader is a bad performer and needs to be swapped in the schedule
+    /// with a good performer. If not, then the method returns None. Otherwise the leader to swap with
+    /// is returned instead. The `leader_round` and `leader_offset` are used as a seed to random function
+    /// in order to calculate the good node that will swap in that round with the bad node. We are
+    /// intentionally not doing weighted randomness as we want to give to all the good nodes equal
+    /// opportunity to get swapped with bad nodes and not have one node with enough stake end up
+    /// swapping bad nodes more frequently than the others on the final schedule.
+    pub(crate) fn swap(
+        &self,
+        leader: AuthorityIndex,
+        leader_round: u32,
+        leader_offset: u32,
+    ) -> Option<AuthorityIndex> {
+        if !self.bad_nodes.contains(&leader) {
+            return None;
+        }
+
+        let mut seed_bytes = [0u8; 32];
+        seed_bytes[24..28].copy_from_slice(&leader_round.to_le_bytes());
+        seed_bytes[28..32].copy_from_slice(&leader_offset.to_le_bytes());
+        let mut rng = StdRng::from_seed(seed_bytes);
+
+        let good_node = *self
+            .good_nodes
+            .choose(&mut rng)
+            .expect("There should be at least one good node available");
+
+        trace!(
+            "Swapping bad leader {} -> {} for round {} with offset {}",
+            leader,
+            good_node,
+            leader_round,
+            leader_offset
+        );
+
+        Some(good_node)
+    }
+
+    // Retrieves the first nodes provided by the iterator `authorities` until the
+    // `stake_threshold` has been reached. The `stake_threshold` should be between
+    // [0, 100] and expresses the percentage of stake that is considered the cutoff.
+    // It's the caller's responsibility to ensure that the elements of the `authorities`
+    // input is already sorted.
+    fn retrieve_first_nodes(
+        context: &Context,
+        authorities: impl Iterator<Item = (AuthorityIndex, u64)>,
+        stake_threshold: u64,
+    ) -> Vec<AuthorityIndex> {
+        let mut filtered_authorities = Vec::new();
+
+        let mut stake = 0;
+        for (authority_idx, _score) in authorities {
+            stake += context.committee.stake(authority_idx);
+
+            // If the total accumulated stake has surpassed the stake threshold
+            // then we omit this last authority and we exit the loop.
+            if stake > (stake_threshold * context.committee.total_stake()) / 100 as Stake {
+                break;
+            }
+            filtered_authorities.push(authority_idx);
+        }
+
+        filtered_authorities
+    }
+}
+
+impl Debug for LeaderSwapTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!(
+            "LeaderSwapTable for commits [{}, {}], good_nodes: {:?}, bad_nodes: {:?}",
+            self.reputation_scores.first_commit_index,
+            self.reputation_scores.last_commit_index,
+            self.good_nodes,
+            self.bad_nodes,
+        ))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use crate::block::{Block, TestBlock, VerifiedBlock};
     use crate::metrics::test_metrics;
+    use crate::storage::mem_store::MemStore;
     use consensus_config::{Committee, Parameters};
     use mgo_protocol_config::ProtocolConfig;
 
@@ -137,4 +411,129 @@ mod tests {
             leader_schedule.elect_leader_stake_based(1, 2)
         );
     }
+
+    #[test]
+    fn test_leader_swap_table() {
+        let (context, _) = Context::new_for_test(4);
+        let context = Arc::new(context);
+
+        // With a threshold of 33% of the stake, one authority is swapped out of 4.
+        let reputation_scores = ReputationScores::new(1, 10, vec![4, 1, 3, 2]);
+        let table = LeaderSwapTable::new(context, reputation_scores, 33);
+
+        assert_eq!(table.good_nodes, vec![AuthorityIndex::new_for_test(0)]);
+        assert_eq!(
+            table.bad_nodes,
+            BTreeSet::from([AuthorityIndex::new_for_test(1)])
+        );
+
+        // Only the bad node gets swapped, with the good node.
+        assert_eq!(
+            table.swap(AuthorityIndex::new_for_test(1), 5, 0),
+            Some(AuthorityIndex::new_for_test(0))
+        );
+        for index in [0, 2, 3] {
+            assert_eq!(table.swap(AuthorityIndex::new_for_test(index), 5, 0), None);
+        }
+    }
+
+    #[test]
+    fn test_leader_swap_table_no_threshold() {
+        let (context, _) = Context::new_for_test(4);
+        let context = Arc::new(context);
+
+        let reputation_scores = ReputationScores::new(1, 10, vec![4, 1, 3, 2]);
+        let table = LeaderSwapTable::new(context, reputation_scores, 0);
+
+        assert!(table.good_nodes.is_empty());
+        assert!(table.bad_nodes.is_empty());
+        assert_eq!(table.swap(AuthorityIndex::new_for_test(1), 5, 0), None);
+    }
+
+    #[test]
+    fn test_update_and_restore_leader_schedule() {
+        let (context, _) = Context::new_for_test(4);
+        let mut protocol_config = context.protocol_config.clone();
+        protocol_config.set_consensus_bad_nodes_stake_threshold(33);
+        let context = Arc::new(Context::new(
+            context.own_index,
+            context.committee,
+            context.parameters,
+            protocol_config,
+            test_metrics(),
+        ));
+        let store = Arc::new(MemStore::new());
+
+        let leader_schedule = LeaderSchedule::new(context.clone()).with_num_commits_per_schedule(3);
+        assert_eq!(leader_schedule.commits_until_leader_schedule_update(0), 3);
+        assert_eq!(leader_schedule.commits_until_leader_schedule_update(2), 1);
+        assert_eq!(
+            leader_schedule.elect_leader(1, 0),
+            AuthorityIndex::new_for_test(1)
+        );
+
+        // Create 3 committed sub-dags, where authority 1 is never referenced by others.
+        let slow_authority = AuthorityIndex::new_for_test(1);
+        let (genesis_my, mut genesis) = Block::genesis(context.clone());
+        genesis.push(genesis_my);
+        let mut ancestors = genesis.iter().map(|b| b.reference()).collect::<Vec<_>>();
+        let mut subdags = vec![];
+        for round in 1..=3 {
+            let blocks = context
+                .committee
+                .authorities()
+                .map(|(index, _)| {
+                    let block_ancestors = ancestors
+                        .iter()
+                        .filter(|a| a.author == index || a.author != slow_authority || round == 1)
+                        .cloned()
+                        .collect();
+                    VerifiedBlock::new_for_test(
+                        TestBlock::new(round, index.value() as u32)
+                            .set_ancestors(block_ancestors)
+                            .build(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            ancestors = blocks.iter().map(|b| b.reference()).collect();
+            subdags.push(CommittedSubDag::new(
+                blocks[0].reference(),
+                blocks,
+                round as CommitIndex,
+            ));
+        }
+
+        leader_schedule
+            .update_leader_schedule(store.as_ref(), &subdags)
+            .unwrap();
+
+        // The slow authority is swapped out of the schedule.
+        assert_eq!(leader_schedule.commits_until_leader_schedule_update(3), 3);
+        assert_ne!(leader_schedule.elect_leader(1, 0), slow_authority);
+        assert_eq!(
+            leader_schedule.elect_leader(2, 0),
+            AuthorityIndex::new_for_test(2)
+        );
+        assert_eq!(
+            context.metrics.node_metrics.leader_schedule_updates.get(),
+            1
+        );
+
+        // The scores have been persisted, so the schedule is restored identically.
+        let stored_scores = store.read_last_reputation_scores().unwrap().unwrap();
+        assert_eq!(stored_scores.last_commit_index, 3);
+        assert_eq!(stored_scores.scores_per_authority[slow_authority], 0);
+
+        let restored_schedule = LeaderSchedule::from_store(context.clone(), store);
+        for round in 1..=20 {
+            assert_eq!(
+                restored_schedule.elect_leader(round, 0),
+                leader_schedule.elect_leader(round, 0)
+            );
+        }
+        assert_eq!(
+            restored_schedule.commits_until_leader_schedule_update(5),
+            298
+        );
+    }
 }
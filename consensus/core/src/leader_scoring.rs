@@ -0,0 +1,242 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+use consensus_config::AuthorityIndex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    block::{BlockAPI, BlockRef, GENESIS_ROUND},
+    commit::{CommitIndex, CommittedSubDag},
+    context::Context,
+    stake_aggregator::{QuorumThreshold, StakeAggregator},
+};
+
+/// Reputation scores of the authorities, calculated over a range of commits. The scores are
+/// stored once the range of a leader schedule is complete, so the schedule can be restored
+/// after a restart.
+#[derive(Clone, Default, Debug, Deserialize, Serialize, PartialEq)]
+pub(crate) struct ReputationScores {
+    /// Score per authority. Vec index is the `AuthorityIndex`.
+    pub scores_per_authority: Vec<u64>,
+    /// The first commit (inclusive) the scores have been calculated from.
+    pub first_commit_index: CommitIndex,
+    /// The last commit (inclusive) the scores have been calculated from.
+    pub last_commit_index: CommitIndex,
+}
+
+impl ReputationScores {
+    pub(crate) fn new(
+        first_commit_index: CommitIndex,
+        last_commit_index: CommitIndex,
+        scores_per_authority: Vec<u64>,
+    ) -> Self {
+        Self {
+            scores_per_authority,
+            first_commit_index,
+            last_commit_index,
+        }
+    }
+
+    /// Returns the authorities sorted by score in descending order. Authorities with equal
+    /// scores are sorted by index, so the result is deterministic across authorities.
+    pub(crate) fn authorities_by_score_desc(
+        &self,
+        context: Arc<Context>,
+    ) -> Vec<(AuthorityIndex, u64)> {
+        let mut authorities: Vec<_> = self
+            .scores_per_authority
+            .iter()
+            .enumerate()
+            .map(|(index, score)| {
+                (
+                    context
+                        .committee
+                        .to_authority_index(index)
+                        .expect("Should be a valid AuthorityIndex"),
+                    *score,
+                )
+            })
+            .collect();
+
+        authorities.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        authorities
+    }
+
+    pub(crate) fn update_metrics(&self, context: Arc<Context>) {
+        for (index, score) in self.scores_per_authority.iter().enumerate() {
+            let authority_index = context
+                .committee
+                .to_authority_index(index)
+                .expect("Should be a valid AuthorityIndex");
+            let authority = context.committee.authority(authority_index);
+            context
+                .metrics
+                .node_metrics
+                .reputation_scores
+                .with_label_values(&[&authority.hostname])
+                .set(*score as i64);
+        }
+    }
+}
+
+/// Calculates the reputation scores of the authorities from a range of committed sub-dags.
+///
+/// An authority scores a point every time one of its blocks is included as an ancestor by a
+/// committed block of another authority, and every time one of its blocks is certified, ie
+/// referenced by a quorum of committed blocks of the next round. Slow or unresponsive
+/// authorities are included and certified less often, so they end up with lower scores.
+pub(crate) struct ReputationScoreCalculator<'a> {
+    context: Arc<Context>,
+    unscored_subdags: &'a [CommittedSubDag],
+}
+
+impl<'a> ReputationScoreCalculator<'a> {
+    pub(crate) fn new(context: Arc<Context>, unscored_subdags: &'a [CommittedSubDag]) -> Self {
+        assert!(
+            !unscored_subdags.is_empty(),
+            "Attempted to calculate scores with no unscored subdags"
+        );
+        Self {
+            context,
+            unscored_subdags,
+        }
+    }
+
+    pub(crate) fn calculate(&self) -> ReputationScores {
+        let committee = &self.context.committee;
+        let mut scores_per_authority = vec![0_u64; committee.size()];
+        let mut votes: BTreeMap<BlockRef, StakeAggregator<QuorumThreshold>> = BTreeMap::new();
+        let mut certified = BTreeSet::new();
+
+        for block in self
+            .unscored_subdags
+            .iter()
+            .flat_map(|subdag| subdag.blocks.iter())
+        {
+            for ancestor in block.ancestors() {
+                // Genesis blocks are produced by every authority, so they don't count.
+                if ancestor.round == GENESIS_ROUND {
+                    continue;
+                }
+
+                // Inclusion: the ancestor has been referenced by another authority.
+                if ancestor.author != block.author() {
+                    scores_per_authority[ancestor.author] += 1;
+                }
+
+                // Certification: the ancestor is voted by blocks of the next round.
+                if ancestor.round + 1 == block.round()
+                    && votes
+                        .entry(*ancestor)
+                        .or_insert_with(StakeAggregator::new)
+                        .add(block.author(), committee)
+                {
+                    certified.insert(*ancestor);
+                }
+            }
+        }
+
+        for block_ref in certified {
+            scores_per_authority[block_ref.author] += 1;
+        }
+
+        let first_commit_index = self
+            .unscored_subdags
+            .iter()
+            .map(|subdag| subdag.commit_index)
+            .min()
+            .unwrap();
+        let last_commit_index = self
+            .unscored_subdags
+            .iter()
+            .map(|subdag| subdag.commit_index)
+            .max()
+            .unwrap();
+
+        ReputationScores::new(first_commit_index, last_commit_index, scores_per_authority)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{Block, TestBlock, VerifiedBlock};
+
+    /// Creates one committed sub-dag per round in `1..=rounds`, where the blocks of every round
+    /// reference all the blocks of the previous round, except the blocks of `slow_authority` which
+    /// are never referenced by the other authorities.
+    fn create_subdags(
+        context: &Arc<Context>,
+        rounds: u32,
+        slow_authority: Option<AuthorityIndex>,
+    ) -> Vec<CommittedSubDag> {
+        let (genesis_my, mut genesis) = Block::genesis(context.clone());
+        genesis.push(genesis_my);
+        let mut ancestors = genesis.iter().map(|b| b.reference()).collect::<Vec<_>>();
+
+        let mut subdags = vec![];
+        for round in 1..=rounds {
+            let blocks = context
+                .committee
+                .authorities()
+                .map(|(index, _)| {
+                    let block_ancestors = ancestors
+                        .iter()
+                        .filter(|a| {
+                            a.author == index || Some(a.author) != slow_authority || round == 1
+                        })
+                        .cloned()
+                        .collect();
+                    VerifiedBlock::new_for_test(
+                        TestBlock::new(round, index.value() as u32)
+                            .set_ancestors(block_ancestors)
+                            .build(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            ancestors = blocks.iter().map(|b| b.reference()).collect();
+            let leader = blocks[round as usize % blocks.len()].reference();
+            subdags.push(CommittedSubDag::new(leader, blocks, round as CommitIndex));
+        }
+        subdags
+    }
+
+    #[test]
+    fn test_reputation_scores_fully_connected() {
+        let (context, _) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let subdags = create_subdags(&context, 4, None);
+
+        let scores = ReputationScoreCalculator::new(context.clone(), &subdags).calculate();
+
+        // Blocks of rounds 1..=3 are referenced by the 3 other authorities and certified.
+        assert_eq!(scores.scores_per_authority, vec![12; 4]);
+        assert_eq!(scores.first_commit_index, 1);
+        assert_eq!(scores.last_commit_index, 4);
+    }
+
+    #[test]
+    fn test_reputation_scores_slow_authority() {
+        let (context, _) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let slow_authority = AuthorityIndex::new_for_test(3);
+        let subdags = create_subdags(&context, 4, Some(slow_authority));
+
+        let scores = ReputationScoreCalculator::new(context.clone(), &subdags).calculate();
+
+        // The slow authority is never referenced by others, so it is never certified either.
+        assert_eq!(scores.scores_per_authority[slow_authority], 0);
+        // The others are referenced by the 3 other authorities on each round, and certified.
+        assert_eq!(scores.scores_per_authority[..3], vec![12; 3]);
+
+        let authorities = scores.authorities_by_score_desc(context);
+        assert_eq!(authorities.last().unwrap(), &(slow_authority, 0));
+        assert_eq!(authorities[0], (AuthorityIndex::new_for_test(0), 12));
+    }
+}
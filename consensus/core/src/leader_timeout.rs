@@ -62,6 +62,10 @@ impl<D: CoreThreadDispatcherInterface> LeaderTimeoutTask<D> {
 
         loop {
             tokio::select! {
+                // Branches are polled in order, so the task behaves the same way on every run
+                // under a simulated clock.
+                biased;
+
                 // when leader timer expires then we attempt to trigger the creation of a new block.
                 // If we already timed out before then the branch gets disabled so we don't attempt
                 // all the time to produce already produced blocks for that round.
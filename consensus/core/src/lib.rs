@@ -2,11 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod authority_node;
+mod authority_service;
 mod authority_signature;
 mod base_committer;
 mod block;
 mod block_manager;
 mod block_verifier;
+mod broadcaster;
 mod commit;
 mod context;
 mod core;
@@ -14,14 +16,19 @@ mod core_thread;
 mod dag_state;
 mod error;
 mod leader_schedule;
+mod leader_scoring;
+mod linearizer;
 mod metrics;
 mod network;
 mod stake_aggregator;
 mod storage;
+mod synchronizer;
 mod threshold_clock;
 mod transactions_client;
 mod universal_committer;
 
 mod leader_timeout;
 #[cfg(test)]
+mod simulator;
+#[cfg(test)]
 mod test_dag;
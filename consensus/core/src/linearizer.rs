@@ -0,0 +1,142 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{cmp::max, collections::HashSet, sync::Arc};
+
+use parking_lot::RwLock;
+
+use crate::{
+    block::{BlockAPI, VerifiedBlock},
+    commit::{Commit, CommittedSubDag},
+    context::Context,
+    dag_state::DagState,
+};
+
+/// Expands committed leaders into the sub-dags of their causal history, and adds the
+/// resulting commits to DagState.
+pub(crate) struct Linearizer {
+    context: Arc<Context>,
+    dag_state: Arc<RwLock<DagState>>,
+}
+
+impl Linearizer {
+    pub(crate) fn new(context: Arc<Context>, dag_state: Arc<RwLock<DagState>>) -> Self {
+        Self { context, dag_state }
+    }
+
+    /// Commits the leader block along with every block of its causal history that has not been
+    /// committed by an earlier commit. The blocks of the sub-dag are ordered by round, and then
+    /// by authority.
+    pub(crate) fn handle_commit(&mut self, leader_block: VerifiedBlock) -> CommittedSubDag {
+        let mut dag_state = self.dag_state.write();
+        let (index, mut last_committed_rounds) = match dag_state.last_commit() {
+            Some(commit) => (commit.index + 1, commit.last_committed_rounds.clone()),
+            None => (1, vec![0; self.context.committee.size()]),
+        };
+
+        let leader = leader_block.reference();
+        let mut visited = HashSet::from([leader]);
+        let mut buffer = vec![leader_block];
+        let mut blocks = Vec::new();
+        while let Some(block) = buffer.pop() {
+            // Blocks at or below the last committed round of their authority have been committed
+            // already, as every block references the previous block of its authority.
+            let ancestors = block
+                .ancestors()
+                .iter()
+                .filter(|ancestor| {
+                    ancestor.round > last_committed_rounds[ancestor.author]
+                        && visited.insert(**ancestor)
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            for (ancestor, block_ref) in
+                dag_state.get_blocks(&ancestors).into_iter().zip(&ancestors)
+            {
+                buffer.push(ancestor.unwrap_or_else(|| {
+                    panic!("Block {:?} to commit should have been accepted!", block_ref)
+                }));
+            }
+            blocks.push(block);
+        }
+        blocks.sort_by_key(|block| (block.round(), block.author()));
+
+        for block in &blocks {
+            let last_committed_round = &mut last_committed_rounds[block.author()];
+            *last_committed_round = max(*last_committed_round, block.round());
+        }
+        dag_state.add_commit(Commit {
+            index,
+            leader,
+            blocks: blocks.iter().map(|block| block.reference()).collect(),
+            last_committed_rounds,
+        });
+
+        CommittedSubDag::new(leader, blocks, index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        block::{Block, TestBlock},
+        storage::mem_store::MemStore,
+    };
+
+    #[test]
+    fn test_handle_commit() {
+        let (context, _) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let dag_state = Arc::new(RwLock::new(DagState::new(
+            context.clone(),
+            Arc::new(MemStore::new()),
+        )));
+        let mut linearizer = Linearizer::new(context.clone(), dag_state.clone());
+
+        // Every authority proposes for rounds 1 to 4, referencing all the blocks of the previous
+        // round.
+        let (genesis_my, mut genesis) = Block::genesis(context.clone());
+        genesis.push(genesis_my);
+        let mut ancestors = genesis.iter().map(|b| b.reference()).collect::<Vec<_>>();
+        let mut rounds = vec![];
+        for round in 1..=4 {
+            let blocks = (0..4)
+                .map(|author| {
+                    VerifiedBlock::new_for_test(
+                        TestBlock::new(round, author)
+                            .set_ancestors(ancestors.clone())
+                            .build(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            dag_state.write().accept_blocks(blocks.clone());
+            ancestors = blocks.iter().map(|b| b.reference()).collect();
+            rounds.push(blocks);
+        }
+
+        // The first commit includes the whole history of the leader, without the genesis blocks.
+        let leader = rounds[1][2].clone();
+        let subdag = linearizer.handle_commit(leader.clone());
+        assert_eq!(subdag.commit_index, 1);
+        assert_eq!(subdag.leader, leader.reference());
+        assert_eq!(subdag.blocks.len(), 5);
+        assert_eq!(subdag.blocks[..4], rounds[0][..]);
+        assert_eq!(subdag.blocks[4], leader);
+
+        // The next commit only includes the blocks that have not been committed yet.
+        let leader = rounds[3][0].clone();
+        let subdag = linearizer.handle_commit(leader.clone());
+        assert_eq!(subdag.commit_index, 2);
+        assert_eq!(subdag.blocks.len(), 8);
+        assert!(subdag.blocks.iter().all(|b| b.round() > 1));
+        assert!(!subdag.blocks.contains(&rounds[1][2]));
+        assert_eq!(subdag.blocks.last(), Some(&leader));
+
+        let dag_state = dag_state.read();
+        let last_commit = dag_state.last_commit().unwrap();
+        assert_eq!(last_commit.index, 2);
+        assert_eq!(last_commit.last_committed_rounds, vec![4, 3, 3, 3]);
+        assert_eq!(dag_state.unscored_committed_subdags().len(), 2);
+    }
+}
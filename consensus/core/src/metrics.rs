@@ -2,9 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use prometheus::{
-    register_histogram_with_registry, register_int_counter_vec_with_registry,
-    register_int_counter_with_registry, register_int_gauge_with_registry, Histogram, IntCounter,
-    IntCounterVec, IntGauge, Registry,
+    register_histogram_vec_with_registry, register_histogram_with_registry,
+    register_int_counter_vec_with_registry, register_int_counter_with_registry,
+    register_int_gauge_vec_with_registry, register_int_gauge_with_registry, Histogram,
+    HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Registry,
 };
 use std::sync::Arc;
 
@@ -17,15 +18,18 @@ const LATENCY_SEC_BUCKETS: &[f64] = &[
 pub(crate) struct Metrics {
     pub node_metrics: NodeMetrics,
     pub channel_metrics: ChannelMetrics,
+    pub network_metrics: NetworkMetrics,
 }
 
 pub(crate) fn initialise_metrics(registry: Registry) -> Arc<Metrics> {
     let node_metrics = NodeMetrics::new(&registry);
     let channel_metrics = ChannelMetrics::new(&registry);
+    let network_metrics = NetworkMetrics::new(&registry);
 
     Arc::new(Metrics {
         node_metrics,
         channel_metrics,
+        network_metrics,
     })
 }
 
@@ -41,8 +45,17 @@ pub(crate) struct NodeMetrics {
     pub committed_leaders_total: IntCounterVec,
     pub core_lock_enqueued: IntCounter,
     pub core_lock_dequeued: IntCounter,
+    pub core_lock_processed: IntCounter,
     pub leader_timeout_total: IntCounter,
     pub threshold_clock_round: IntGauge,
+    pub block_suspensions: IntCounterVec,
+    pub block_unsuspensions: IntCounterVec,
+    pub block_manager_suspended_blocks: IntGauge,
+    pub block_manager_missing_blocks: IntGauge,
+    pub reputation_scores: IntGaugeVec,
+    pub leader_schedule_updates: IntCounter,
+    pub invalid_blocks: IntCounterVec,
+    pub equivocations: IntCounterVec,
 }
 
 impl NodeMetrics {
@@ -80,6 +93,12 @@ impl NodeMetrics {
                 registry,
             )
             .unwrap(),
+            core_lock_processed: register_int_counter_with_registry!(
+                "core_lock_processed",
+                "Number of processed core requests",
+                registry,
+            )
+            .unwrap(),
             leader_timeout_total: register_int_counter_with_registry!(
                 "leader_timeout_total",
                 "Total number of leader timeouts",
@@ -91,6 +110,51 @@ impl NodeMetrics {
                 "The current threshold clock round. We only advance to a new round when a quorum of parents have been synced.",
                 registry,
             ).unwrap(),
+            block_suspensions: register_int_counter_vec_with_registry!(
+                "block_suspensions",
+                "The number of block suspensions. The counter is reported uniquely, so if a block is sent for reprocessing while already suspended then is not double counted",
+                &["authority"],
+                registry,
+            ).unwrap(),
+            block_unsuspensions: register_int_counter_vec_with_registry!(
+                "block_unsuspensions",
+                "The number of block unsuspensions.",
+                &["authority"],
+                registry,
+            ).unwrap(),
+            block_manager_suspended_blocks: register_int_gauge_with_registry!(
+                "block_manager_suspended_blocks",
+                "The number of blocks currently suspended in the block manager, waiting for their causal history",
+                registry,
+            ).unwrap(),
+            block_manager_missing_blocks: register_int_gauge_with_registry!(
+                "block_manager_missing_blocks",
+                "The number of blocks currently missing in the block manager, that need to be fetched",
+                registry,
+            ).unwrap(),
+            reputation_scores: register_int_gauge_vec_with_registry!(
+                "reputation_scores",
+                "Reputation scores of each authority, as calculated on the last leader schedule update",
+                &["authority"],
+                registry,
+            ).unwrap(),
+            leader_schedule_updates: register_int_counter_with_registry!(
+                "leader_schedule_updates",
+                "Total number of leader schedule updates based on reputation scores",
+                registry,
+            ).unwrap(),
+            invalid_blocks: register_int_counter_vec_with_registry!(
+                "invalid_blocks",
+                "Number of blocks rejected by verification, per author and error",
+                &["authority", "error"],
+                registry,
+            ).unwrap(),
+            equivocations: register_int_counter_vec_with_registry!(
+                "equivocations",
+                "Number of detected equivocations, ie different blocks from the same author and round, per author",
+                &["authority"],
+                registry,
+            ).unwrap(),
         }
     }
 }
@@ -132,3 +196,96 @@ impl ChannelMetrics {
         }
     }
 }
+
+/// Network metrics are labelled by the hostname of the peer, as listed in the committee, and by
+/// the RPC method.
+pub(crate) struct NetworkMetrics {
+    /// total outbound requests per peer and method
+    pub outbound_requests: IntCounterVec,
+    /// total failed outbound requests per peer, method and error
+    pub outbound_request_errors: IntCounterVec,
+    /// latency of outbound requests per peer and method
+    pub outbound_request_latency: HistogramVec,
+    /// total serialized bytes sent per peer and method
+    pub outbound_bytes: IntCounterVec,
+    /// total inbound requests per peer and method
+    pub inbound_requests: IntCounterVec,
+    /// total failed inbound requests per peer, method and error
+    pub inbound_request_errors: IntCounterVec,
+    /// total serialized bytes received per peer and method
+    pub inbound_bytes: IntCounterVec,
+    /// total inbound requests that could not be attributed to a committee member
+    pub inbound_unknown_peer_requests: IntCounter,
+    /// 1 if the peer is connected, 0 otherwise
+    pub peer_connected: IntGaugeVec,
+}
+
+impl NetworkMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            outbound_requests: register_int_counter_vec_with_registry!(
+                "network_outbound_requests",
+                "Total number of outbound requests per peer and method",
+                &["peer", "method"],
+                registry,
+            )
+            .unwrap(),
+            outbound_request_errors: register_int_counter_vec_with_registry!(
+                "network_outbound_request_errors",
+                "Total number of failed outbound requests per peer, method and error",
+                &["peer", "method", "error"],
+                registry,
+            )
+            .unwrap(),
+            outbound_request_latency: register_histogram_vec_with_registry!(
+                "network_outbound_request_latency",
+                "Latency of outbound requests per peer and method",
+                &["peer", "method"],
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+            outbound_bytes: register_int_counter_vec_with_registry!(
+                "network_outbound_bytes",
+                "Total number of serialized bytes sent per peer and method",
+                &["peer", "method"],
+                registry,
+            )
+            .unwrap(),
+            inbound_requests: register_int_counter_vec_with_registry!(
+                "network_inbound_requests",
+                "Total number of inbound requests per peer and method",
+                &["peer", "method"],
+                registry,
+            )
+            .unwrap(),
+            inbound_request_errors: register_int_counter_vec_with_registry!(
+                "network_inbound_request_errors",
+                "Total number of failed inbound requests per peer, method and error",
+                &["peer", "method", "error"],
+                registry,
+            )
+            .unwrap(),
+            inbound_bytes: register_int_counter_vec_with_registry!(
+                "network_inbound_bytes",
+                "Total number of serialized bytes received per peer and method",
+                &["peer", "method"],
+                registry,
+            )
+            .unwrap(),
+            inbound_unknown_peer_requests: register_int_counter_with_registry!(
+                "network_inbound_unknown_peer_requests",
+                "Total number of inbound requests from peers outside of the committee",
+                registry,
+            )
+            .unwrap(),
+            peer_connected: register_int_gauge_vec_with_registry!(
+                "network_peer_connected",
+                "Whether the peer is connected (1) or not (0)",
+                &["peer"],
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+}
@@ -0,0 +1,773 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anemo::{
+    rpc::Status,
+    types::{response::StatusCode, PeerEvent, PeerInfo},
+    PeerId, Request, Response,
+};
+use anemo_tower::auth::{AllowedPeers, RequireAuthorizationLayer};
+use arc_swap::ArcSwapOption;
+use async_trait::async_trait;
+use bytes::Bytes;
+use consensus_config::{Authority, AuthorityIndex, NetworkKeyPair, NetworkPublicKey};
+use fastcrypto::traits::KeyPair as _;
+use serde::{Deserialize, Serialize};
+use tokio::{sync::broadcast::error::RecvError, task::JoinHandle};
+use tracing::{debug, error, info, warn};
+
+use super::{NetworkClient, NetworkManager, NetworkService};
+use crate::{
+    block::BlockRef,
+    context::Context,
+    error::{ConsensusError, ConsensusResult},
+};
+
+mod anemo_gen {
+    include!(concat!(env!("OUT_DIR"), "/consensus.ConsensusRpc.rs"));
+}
+
+use anemo_gen::{
+    consensus_rpc_client::ConsensusRpcClient,
+    consensus_rpc_server::{ConsensusRpc, ConsensusRpcServer},
+};
+
+/// Timeout of a single outbound request, including the time spent waiting for the peer to
+/// connect.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often to check if a peer has been connected, while a request to it is pending.
+const PEER_CONNECTION_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Number of attempts to bind the network to the authority's address before giving up.
+const NETWORK_BIND_RETRIES: u32 = 30;
+
+/// Implements RPC client for Consensus.
+pub(crate) struct AnemoClient {
+    context: Arc<Context>,
+    network: Arc<ArcSwapOption<anemo::Network>>,
+}
+
+impl AnemoClient {
+    pub(crate) fn new(context: Arc<Context>) -> Self {
+        Self {
+            context,
+            network: Arc::new(ArcSwapOption::default()),
+        }
+    }
+
+    pub(crate) fn set_network(&self, network: anemo::Network) {
+        self.network.store(Some(Arc::new(network)));
+    }
+
+    /// Returns a client to the peer, waiting up to `timeout` for the peer to get connected.
+    async fn get_client(
+        &self,
+        peer: AuthorityIndex,
+        timeout: Duration,
+    ) -> ConsensusResult<ConsensusRpcClient<anemo::Peer>> {
+        let network = self
+            .network
+            .load_full()
+            .ok_or(ConsensusError::NetworkNotStarted)?;
+        let authority = self.context.committee.authority(peer);
+        let peer_id = to_peer_id(&authority.network_key);
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(peer) = network.peer(peer_id) {
+                return Ok(ConsensusRpcClient::new(peer));
+            }
+            if Instant::now() >= deadline {
+                return Err(ConsensusError::PeerDisconnected(authority.hostname.clone()));
+            }
+            tokio::time::sleep(PEER_CONNECTION_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Records the outcome of an outbound request to the per-peer metrics.
+    fn report_outbound<T>(
+        &self,
+        peer: AuthorityIndex,
+        method: &str,
+        start: Instant,
+        result: &ConsensusResult<T>,
+    ) {
+        let metrics = &self.context.metrics.network_metrics;
+        let hostname = self.context.committee.authority(peer).hostname.as_str();
+        metrics
+            .outbound_requests
+            .with_label_values(&[hostname, method])
+            .inc();
+        metrics
+            .outbound_request_latency
+            .with_label_values(&[hostname, method])
+            .observe(start.elapsed().as_secs_f64());
+        if let Err(e) = result {
+            metrics
+                .outbound_request_errors
+                .with_label_values(&[hostname, method, e.name()])
+                .inc();
+        }
+    }
+}
+
+#[async_trait]
+impl NetworkClient for AnemoClient {
+    async fn send_block(&self, peer: AuthorityIndex, block: &Bytes) -> ConsensusResult<()> {
+        let start = Instant::now();
+        let result = async {
+            let mut client = self.get_client(peer, REQUEST_TIMEOUT).await?;
+            let request = Request::new(SendBlockRequest {
+                block: block.clone(),
+            })
+            .with_timeout(REQUEST_TIMEOUT.saturating_sub(start.elapsed()));
+            client
+                .send_block(request)
+                .await
+                .map_err(|e| to_consensus_error(&self.context, peer, e))?;
+            Ok(())
+        }
+        .await;
+
+        self.context
+            .metrics
+            .network_metrics
+            .outbound_bytes
+            .with_label_values(&[
+                self.context.committee.authority(peer).hostname.as_str(),
+                "send_block",
+            ])
+            .inc_by(block.len() as u64);
+        self.report_outbound(peer, "send_block", start, &result);
+        result
+    }
+
+    async fn fetch_blocks(
+        &self,
+        peer: AuthorityIndex,
+        block_refs: Vec<BlockRef>,
+    ) -> ConsensusResult<Vec<Bytes>> {
+        let start = Instant::now();
+        let result = async {
+            let mut client = self.get_client(peer, REQUEST_TIMEOUT).await?;
+            let request = Request::new(FetchBlocksRequest { block_refs })
+                .with_timeout(REQUEST_TIMEOUT.saturating_sub(start.elapsed()));
+            let response = client
+                .fetch_blocks(request)
+                .await
+                .map_err(|e| to_consensus_error(&self.context, peer, e))?;
+            Ok(response.into_body().blocks)
+        }
+        .await;
+
+        self.report_outbound(peer, "fetch_blocks", start, &result);
+        result
+    }
+}
+
+/// Converts an anemo RPC status of a request to `peer` into a `ConsensusError`.
+fn to_consensus_error(context: &Context, peer: AuthorityIndex, status: Status) -> ConsensusError {
+    let hostname = context.committee.authority(peer).hostname.clone();
+    match status.status() {
+        StatusCode::RequestTimeout => ConsensusError::NetworkRequestTimeout(hostname),
+        _ => ConsensusError::NetworkError(format!("request to {hostname} failed: {status:?}")),
+    }
+}
+
+/// Proxies anemo requests to the NetworkService, after resolving the authenticated peer.
+struct AnemoServiceProxy<S: NetworkService> {
+    context: Arc<Context>,
+    peer_map: BTreeMap<PeerId, AuthorityIndex>,
+    service: Arc<S>,
+}
+
+impl<S: NetworkService> AnemoServiceProxy<S> {
+    fn new(context: Arc<Context>, service: Arc<S>) -> Self {
+        let peer_map = context
+            .committee
+            .authorities()
+            .map(|(index, authority)| (to_peer_id(&authority.network_key), index))
+            .collect();
+        Self {
+            context,
+            peer_map,
+            service,
+        }
+    }
+
+    /// Resolves the authority that has been authenticated for the request.
+    /// The TLS handshake of anemo guarantees that `peer_id` is owned by the sender.
+    fn authority_for_request<T>(&self, request: &Request<T>) -> Result<AuthorityIndex, Status> {
+        let index = request
+            .peer_id()
+            .and_then(|peer_id| self.peer_map.get(peer_id))
+            .copied();
+        index.ok_or_else(|| {
+            self.context
+                .metrics
+                .network_metrics
+                .inbound_unknown_peer_requests
+                .inc();
+            Status::new_with_message(
+                StatusCode::Unauthorized,
+                "request is not from a committee member",
+            )
+        })
+    }
+
+    /// Records the outcome of an inbound request to the per-peer metrics.
+    fn report_inbound<T>(&self, peer: AuthorityIndex, method: &str, result: &ConsensusResult<T>) {
+        let metrics = &self.context.metrics.network_metrics;
+        let hostname = self.context.committee.authority(peer).hostname.as_str();
+        metrics
+            .inbound_requests
+            .with_label_values(&[hostname, method])
+            .inc();
+        if let Err(e) = result {
+            metrics
+                .inbound_request_errors
+                .with_label_values(&[hostname, method, e.name()])
+                .inc();
+        }
+    }
+}
+
+#[async_trait]
+impl<S: NetworkService> ConsensusRpc for AnemoServiceProxy<S> {
+    async fn send_block(
+        &self,
+        request: Request<SendBlockRequest>,
+    ) -> Result<Response<SendBlockResponse>, Status> {
+        let peer = self.authority_for_request(&request)?;
+        let block = request.into_body().block;
+        self.context
+            .metrics
+            .network_metrics
+            .inbound_bytes
+            .with_label_values(&[
+                self.context.committee.authority(peer).hostname.as_str(),
+                "send_block",
+            ])
+            .inc_by(block.len() as u64);
+
+        let result = self.service.handle_send_block(peer, block).await;
+        self.report_inbound(peer, "send_block", &result);
+        result.map_err(|e| {
+            Status::new_with_message(StatusCode::BadRequest, format!("invalid block: {e}"))
+        })?;
+        Ok(Response::new(SendBlockResponse {}))
+    }
+
+    async fn fetch_blocks(
+        &self,
+        request: Request<FetchBlocksRequest>,
+    ) -> Result<Response<FetchBlocksResponse>, Status> {
+        let peer = self.authority_for_request(&request)?;
+        let block_refs = request.into_body().block_refs;
+
+        let result = self.service.handle_fetch_blocks(peer, block_refs).await;
+        self.report_inbound(peer, "fetch_blocks", &result);
+        let blocks = result.map_err(|e| {
+            Status::new_with_message(StatusCode::BadRequest, format!("cannot fetch blocks: {e}"))
+        })?;
+        Ok(Response::new(FetchBlocksResponse { blocks }))
+    }
+}
+
+/// Manages the lifetime of the anemo network.
+pub(crate) struct AnemoManager {
+    context: Arc<Context>,
+    client: Arc<AnemoClient>,
+    network: Option<anemo::Network>,
+    connection_monitor: Option<JoinHandle<()>>,
+}
+
+#[async_trait]
+impl<S: NetworkService> NetworkManager<S> for AnemoManager {
+    type Client = AnemoClient;
+
+    fn new(context: Arc<Context>) -> Self {
+        Self {
+            client: Arc::new(AnemoClient::new(context.clone())),
+            context,
+            network: None,
+            connection_monitor: None,
+        }
+    }
+
+    fn client(&self) -> Arc<Self::Client> {
+        self.client.clone()
+    }
+
+    async fn install_service(&mut self, network_keypair: NetworkKeyPair, service: Arc<S>) {
+        assert!(self.network.is_none(), "Network has already been started!");
+
+        let server = ConsensusRpcServer::new(AnemoServiceProxy::new(self.context.clone(), service));
+        // Only authorities of the current committee are allowed to send requests.
+        let committee_peer_ids = self
+            .context
+            .committee
+            .authorities()
+            .map(|(_, authority)| to_peer_id(&authority.network_key));
+        let routes = anemo::Router::new().add_rpc_service(server).route_layer(
+            RequireAuthorizationLayer::new(AllowedPeers::new(committee_peer_ids)),
+        );
+
+        let own_authority = self.context.committee.authority(self.context.own_index);
+        let own_address = to_mango_multiaddr(own_authority);
+        let bind_address = own_address
+            .udp_multiaddr_to_listen_address()
+            .unwrap_or_else(|| panic!("Invalid consensus address {}", own_authority.address));
+        assert_eq!(
+            to_peer_id(&own_authority.network_key),
+            to_peer_id(network_keypair.public()),
+            "Network keypair does not match the committee!"
+        );
+        let private_key = network_keypair.private().0.to_bytes();
+
+        let anemo_config = {
+            let mut quic_config = anemo::QuicConfig::default();
+            // Allow more concurrent streams for burst activity.
+            quic_config.max_concurrent_bidi_streams = Some(10_000);
+            quic_config.allow_failed_socket_buffer_size_setting = true;
+            quic_config.max_idle_timeout_ms = Some(30_000);
+            // Enable keep alives every 5s
+            quic_config.keep_alive_interval_ms = Some(5_000);
+            let mut config = anemo::Config::default();
+            config.quic = Some(quic_config);
+            config.inbound_request_timeout_ms = Some(REQUEST_TIMEOUT.as_millis() as u64);
+            config.outbound_request_timeout_ms = Some(REQUEST_TIMEOUT.as_millis() as u64);
+            config.shutdown_idle_timeout_ms = Some(1_000);
+            config.connectivity_check_interval_ms = Some(2_000);
+            config.connection_backoff_ms = Some(1_000);
+            config.max_connection_backoff_ms = Some(20_000);
+            config
+        };
+
+        let mut retries_left = NETWORK_BIND_RETRIES;
+        let network = loop {
+            let network_result = anemo::Network::bind(bind_address)
+                .server_name("consensus")
+                .private_key(private_key)
+                .config(anemo_config.clone())
+                .start(routes.clone());
+            match network_result {
+                Ok(network) => break network,
+                Err(e) => {
+                    retries_left -= 1;
+                    if retries_left == 0 {
+                        panic!("Failed to start consensus network at {bind_address}: {e:?}");
+                    }
+                    error!(
+                        "Address {bind_address} should be available for the consensus network, retrying in one second: {e:?}",
+                    );
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        };
+        info!(
+            "Consensus network of authority {} started at {}",
+            self.context.own_index, bind_address
+        );
+
+        // Register all other authorities as known peers, so anemo connects to them eagerly
+        // and verifies their identity with TLS.
+        for (index, authority) in self.context.committee.authorities() {
+            if index == self.context.own_index {
+                continue;
+            }
+            let address = match to_mango_multiaddr(authority).to_anemo_address() {
+                Ok(address) => address,
+                Err(e) => {
+                    warn!(
+                        "Skipping authority {index} with invalid address {}: {e}",
+                        authority.address
+                    );
+                    continue;
+                }
+            };
+            network.known_peers().insert(PeerInfo {
+                peer_id: to_peer_id(&authority.network_key),
+                affinity: anemo::types::PeerAffinity::High,
+                address: vec![address],
+            });
+        }
+
+        self.connection_monitor = Some(tokio::spawn(monitor_connections(
+            self.context.clone(),
+            network.clone(),
+        )));
+        self.client.set_network(network.clone());
+        self.network = Some(network);
+    }
+
+    async fn stop(&mut self) {
+        if let Some(connection_monitor) = self.connection_monitor.take() {
+            connection_monitor.abort();
+        }
+        if let Some(network) = self.network.take() {
+            if let Err(e) = network.shutdown().await {
+                warn!("Failure when shutting down consensus network: {e:?}");
+            }
+        }
+    }
+}
+
+/// Keeps the per-peer connection metrics up to date.
+async fn monitor_connections(context: Arc<Context>, network: anemo::Network) {
+    let peers: BTreeMap<PeerId, String> = context
+        .committee
+        .authorities()
+        .filter(|(index, _)| *index != context.own_index)
+        .map(|(_, authority)| {
+            (
+                to_peer_id(&authority.network_key),
+                authority.hostname.clone(),
+            )
+        })
+        .collect();
+    let metrics = &context.metrics.network_metrics;
+
+    let Ok((mut subscriber, connected_peers)) = network.subscribe() else {
+        return;
+    };
+    // Drop the network handle, so the monitor does not keep the network alive.
+    drop(network);
+
+    for hostname in peers.values() {
+        metrics.peer_connected.with_label_values(&[hostname]).set(0);
+    }
+    for peer_id in connected_peers {
+        if let Some(hostname) = peers.get(&peer_id) {
+            metrics.peer_connected.with_label_values(&[hostname]).set(1);
+        }
+    }
+
+    loop {
+        match subscriber.recv().await {
+            Ok(PeerEvent::NewPeer(peer_id)) => {
+                if let Some(hostname) = peers.get(&peer_id) {
+                    debug!("Connected to peer {hostname}");
+                    metrics.peer_connected.with_label_values(&[hostname]).set(1);
+                }
+            }
+            Ok(PeerEvent::LostPeer(peer_id, reason)) => {
+                if let Some(hostname) = peers.get(&peer_id) {
+                    debug!("Disconnected from peer {hostname}: {reason:?}");
+                    metrics.peer_connected.with_label_values(&[hostname]).set(0);
+                }
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                warn!("Connection monitor lagged behind by {skipped} peer events");
+            }
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+fn to_peer_id(key: &NetworkPublicKey) -> PeerId {
+    PeerId(key.0.to_bytes())
+}
+
+fn to_mango_multiaddr(authority: &Authority) -> mango_network::Multiaddr {
+    authority
+        .address
+        .to_string()
+        .parse()
+        .unwrap_or_else(|e| panic!("Invalid consensus address {}: {e:?}", authority.address))
+}
+
+/// Network message types.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct SendBlockRequest {
+    // Serialized SignedBlock.
+    block: Bytes,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct SendBlockResponse {}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct FetchBlocksRequest {
+    block_refs: Vec<BlockRef>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct FetchBlocksResponse {
+    // Serialized SignedBlock.
+    blocks: Vec<Bytes>,
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use anemo::{rpc::Status, types::response::StatusCode};
+    use bytes::Bytes;
+    use consensus_config::{AuthorityIndex, Committee, NetworkKeyPair};
+    use fastcrypto::traits::ToFromBytes;
+    use parking_lot::Mutex;
+
+    use super::{to_consensus_error, AnemoClient, AnemoManager};
+    use crate::{
+        block::{BlockDigest, BlockRef},
+        context::Context,
+        error::{ConsensusError, ConsensusResult},
+        network::{NetworkClient, NetworkManager, NetworkService},
+    };
+
+    /// Records the requests it receives, and fails the ones that carry no data.
+    #[derive(Default)]
+    struct TestService {
+        received_blocks: Mutex<Vec<(AuthorityIndex, Bytes)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl NetworkService for TestService {
+        async fn handle_send_block(
+            &self,
+            peer: AuthorityIndex,
+            block: Bytes,
+        ) -> ConsensusResult<()> {
+            if block.is_empty() {
+                return Err(ConsensusError::NetworkError("empty block".to_string()));
+            }
+            self.received_blocks.lock().push((peer, block));
+            Ok(())
+        }
+
+        async fn handle_fetch_blocks(
+            &self,
+            _peer: AuthorityIndex,
+            block_refs: Vec<BlockRef>,
+        ) -> ConsensusResult<Vec<Bytes>> {
+            if block_refs.is_empty() {
+                return Err(ConsensusError::NetworkError("no blocks".to_string()));
+            }
+            Ok(block_refs
+                .iter()
+                .map(|block_ref| Bytes::from(block_ref.to_string()))
+                .collect())
+        }
+    }
+
+    /// Contexts of every authority of a committee listening on loopback addresses.
+    fn committee_contexts(size: usize) -> (Vec<Arc<Context>>, Vec<NetworkKeyPair>) {
+        let (committee, keypairs) = Committee::local_committee_and_keys(0, vec![1; size]);
+        // Each authority needs its own copy of the committee.
+        let committee_bytes = bcs::to_bytes(&committee).unwrap();
+        let contexts = (0..size)
+            .map(|index| {
+                let (context, _) = Context::new_for_test(size);
+                Arc::new(
+                    context
+                        .with_committee(bcs::from_bytes(&committee_bytes).unwrap())
+                        .with_authority_index(AuthorityIndex::new_for_test(index as u32)),
+                )
+            })
+            .collect();
+        let network_keypairs = keypairs
+            .iter()
+            .map(|(network_keypair, _)| {
+                NetworkKeyPair::from_bytes(network_keypair.as_bytes()).unwrap()
+            })
+            .collect();
+        (contexts, network_keypairs)
+    }
+
+    /// Starts the network of every authority of the committee, each with its own service.
+    async fn start_networks(size: usize) -> (Vec<AnemoManager>, Vec<Arc<TestService>>) {
+        let (contexts, network_keypairs) = committee_contexts(size);
+        let mut managers = vec![];
+        let mut services = vec![];
+        for (context, network_keypair) in contexts.into_iter().zip(network_keypairs) {
+            let service = Arc::new(TestService::default());
+            let mut manager: AnemoManager = NetworkManager::<TestService>::new(context);
+            manager
+                .install_service(network_keypair, service.clone())
+                .await;
+            managers.push(manager);
+            services.push(service);
+        }
+        (managers, services)
+    }
+
+    fn client(manager: &AnemoManager) -> Arc<AnemoClient> {
+        NetworkManager::<TestService>::client(manager)
+    }
+
+    async fn stop_networks(managers: Vec<AnemoManager>) {
+        for mut manager in managers {
+            NetworkManager::<TestService>::stop(&mut manager).await;
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_send_block() {
+        let (managers, services) = start_networks(2).await;
+        let client = client(&managers[0]);
+        let peer = AuthorityIndex::new_for_test(1);
+
+        let block = Bytes::from_static(b"block");
+        client.send_block(peer, &block).await.unwrap();
+        assert_eq!(
+            *services[1].received_blocks.lock(),
+            vec![(AuthorityIndex::new_for_test(0), block.clone())]
+        );
+        assert!(services[0].received_blocks.lock().is_empty());
+
+        let metrics = &managers[0].context.metrics.network_metrics;
+        let hostname = managers[0]
+            .context
+            .committee
+            .authority(peer)
+            .hostname
+            .as_str();
+        assert_eq!(
+            metrics
+                .outbound_requests
+                .with_label_values(&[hostname, "send_block"])
+                .get(),
+            1
+        );
+        assert_eq!(
+            metrics
+                .outbound_bytes
+                .with_label_values(&[hostname, "send_block"])
+                .get(),
+            block.len() as u64
+        );
+
+        stop_networks(managers).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_fetch_blocks() {
+        let (managers, _services) = start_networks(2).await;
+        let client = client(&managers[1]);
+        let peer = AuthorityIndex::new_for_test(0);
+
+        let block_refs = vec![
+            BlockRef::new(1, peer, BlockDigest::MIN),
+            BlockRef::new(2, peer, BlockDigest::MAX),
+        ];
+        let blocks = client.fetch_blocks(peer, block_refs.clone()).await.unwrap();
+        assert_eq!(
+            blocks,
+            block_refs
+                .iter()
+                .map(|block_ref| Bytes::from(block_ref.to_string()))
+                .collect::<Vec<_>>()
+        );
+
+        stop_networks(managers).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_service_errors() {
+        let (managers, services) = start_networks(2).await;
+        let client = client(&managers[0]);
+        let peer = AuthorityIndex::new_for_test(1);
+
+        // Errors of the peer's service are returned as network errors, and counted on both
+        // sides.
+        let err = client.send_block(peer, &Bytes::new()).await.unwrap_err();
+        assert!(matches!(err, ConsensusError::NetworkError(_)), "{err:?}");
+        assert!(services[1].received_blocks.lock().is_empty());
+        let err = client.fetch_blocks(peer, vec![]).await.unwrap_err();
+        assert!(matches!(err, ConsensusError::NetworkError(_)), "{err:?}");
+
+        let hostname = managers[0]
+            .context
+            .committee
+            .authority(peer)
+            .hostname
+            .as_str();
+        let outbound_errors = &managers[0]
+            .context
+            .metrics
+            .network_metrics
+            .outbound_request_errors;
+        for method in ["send_block", "fetch_blocks"] {
+            assert_eq!(
+                outbound_errors
+                    .with_label_values(&[hostname, method, "NetworkError"])
+                    .get(),
+                1
+            );
+        }
+        let own_hostname = managers[1]
+            .context
+            .committee
+            .authority(AuthorityIndex::new_for_test(0))
+            .hostname
+            .as_str();
+        assert_eq!(
+            managers[1]
+                .context
+                .metrics
+                .network_metrics
+                .inbound_request_errors
+                .with_label_values(&[own_hostname, "send_block", "NetworkError"])
+                .get(),
+            1
+        );
+
+        stop_networks(managers).await;
+    }
+
+    #[tokio::test]
+    async fn test_network_not_started() {
+        let (contexts, _) = committee_contexts(2);
+        let client = AnemoClient::new(contexts[0].clone());
+        let peer = AuthorityIndex::new_for_test(1);
+
+        let err = client
+            .send_block(peer, &Bytes::from_static(b"block"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ConsensusError::NetworkNotStarted), "{err:?}");
+        let err = client.fetch_blocks(peer, vec![]).await.unwrap_err();
+        assert!(matches!(err, ConsensusError::NetworkNotStarted), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn test_error_mapping() {
+        let (contexts, _) = committee_contexts(2);
+        let peer = AuthorityIndex::new_for_test(1);
+        let hostname = contexts[0].committee.authority(peer).hostname.clone();
+
+        let err = to_consensus_error(
+            &contexts[0],
+            peer,
+            Status::new_with_message(StatusCode::RequestTimeout, "timed out"),
+        );
+        assert!(
+            matches!(&err, ConsensusError::NetworkRequestTimeout(host) if *host == hostname),
+            "{err:?}"
+        );
+
+        for status_code in [
+            StatusCode::BadRequest,
+            StatusCode::Unauthorized,
+            StatusCode::InternalServerError,
+        ] {
+            let err = to_consensus_error(
+                &contexts[0],
+                peer,
+                Status::new_with_message(status_code, "failed"),
+            );
+            let ConsensusError::NetworkError(message) = &err else {
+                panic!("Unexpected error {err:?}");
+            };
+            assert!(message.contains(&hostname));
+        }
+    }
+}
@@ -5,26 +5,38 @@ use async_trait::async_trait;
 use std::sync::Arc;
 
 use bytes::Bytes;
-use consensus_config::AuthorityIndex;
+use consensus_config::{AuthorityIndex, NetworkKeyPair};
 
-use crate::{block::BlockRef, error::ConsensusResult};
+use crate::{block::BlockRef, context::Context, error::ConsensusResult};
+
+pub(crate) mod anemo_network;
 
 /// An `AuthorityNode` holds a `NetworkManager` until shutdown.
-pub(crate) trait NetworkManager<C, S>
+/// Users of `NetworkManager` must call `install_service()` before the network is started,
+/// and `stop()` when shutting down.
+#[async_trait]
+pub(crate) trait NetworkManager<S>: Send + Sync
 where
-    C: NetworkClient,
     S: NetworkService,
 {
+    type Client: NetworkClient;
+
+    /// Creates a new network manager.
+    fn new(context: Arc<Context>) -> Self;
+
     /// Returns the network client.
-    fn client(&self) -> Arc<C>;
+    fn client(&self) -> Arc<Self::Client>;
+
+    /// Installs network service and starts the network, authenticating with the provided keypair.
+    async fn install_service(&mut self, network_keypair: NetworkKeyPair, service: Arc<S>);
 
-    /// Installs network service.
-    fn install_service(&self, service: Box<S>);
+    /// Stops the network service.
+    async fn stop(&mut self);
 }
 
 /// Network client for communicating with peers.
 #[async_trait]
-pub(crate) trait NetworkClient: Send + Sync {
+pub(crate) trait NetworkClient: Send + Sync + 'static {
     /// Sends a serialized SignedBlock to a peer.
     async fn send_block(&self, peer: AuthorityIndex, block: &Bytes) -> ConsensusResult<()>;
 
@@ -37,8 +49,10 @@ pub(crate) trait NetworkClient: Send + Sync {
 }
 
 /// Network service for handling requests from peers.
+/// The `peer` argument is the authority that has been authenticated for the request, and can
+/// be trusted.
 #[async_trait]
-pub(crate) trait NetworkService: Send + Sync {
+pub(crate) trait NetworkService: Send + Sync + 'static {
     async fn handle_send_block(&self, peer: AuthorityIndex, block: Bytes) -> ConsensusResult<()>;
     async fn handle_fetch_blocks(
         &self,
@@ -0,0 +1,835 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A deterministic simulator for consensus core.
+//!
+//! The simulator runs an `AuthorityNode` for every authority of a committee, connected by an
+//! in-memory network implementing `NetworkManager` and `NetworkClient`, so blocks are broadcasted,
+//! served and fetched by the same components as over the real network. Each message is delayed by
+//! a latency drawn from a seeded rng of its link.
+//!
+//! The authorities run on a single threaded tokio runtime with a paused clock, which the simulator
+//! advances one millisecond at a time along with the clock of the authorities. Before advancing
+//! the clock, and after delivering every message, the simulator waits for the authorities to be
+//! idle: every command sent to a Core thread has been processed, and every task is blocked on a
+//! timer or on a message in flight. So the authorities process the same events in the same order
+//! on every run, and a run is reproducible from its seed.
+//!
+//! Faults can be injected into a run: message delays, network partitions, crashes with recovery
+//! from storage, and Byzantine authorities that equivocate or withhold their blocks. The commits
+//! of every honest authority are checked for safety (commit sequences never diverge) and liveness
+//! (authorities keep committing).
+
+use std::{
+    cmp::max,
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use consensus_config::{AuthorityIndex, NetworkKeyPair, Parameters};
+use parking_lot::Mutex;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use tokio::sync::oneshot;
+
+use crate::{
+    authority_node::AuthorityNode,
+    block::{
+        Block, BlockAPI, BlockRef, BlockTimestampMs, BlockV1, Round, SignedBlock, Transaction,
+        VerifiedBlock, GENESIS_ROUND,
+    },
+    block_verifier::{SignedBlockVerifier, TestBlockVerifier},
+    commit::Commit,
+    context::{Clock, Context},
+    error::{ConsensusError, ConsensusResult},
+    network::{NetworkClient, NetworkManager, NetworkService},
+    stake_aggregator::{QuorumThreshold, StakeAggregator},
+    storage::{mem_store::MemStore, Store},
+};
+
+#[path = "tests/simulator_tests.rs"]
+mod simulator_tests;
+
+/// Number of consecutive polls without any activity, after which the authorities are idle.
+const IDLE_POLLS: usize = 10;
+
+/// A fault injected into a simulation.
+#[derive(Clone, Debug)]
+pub(crate) enum Fault {
+    /// The `authorities` can't exchange messages with the rest of the committee between
+    /// `start_ms` (inclusive) and `end_ms` (exclusive). Messages sent across the partition
+    /// are delivered after it heals.
+    Partition {
+        authorities: BTreeSet<AuthorityIndex>,
+        start_ms: BlockTimestampMs,
+        end_ms: BlockTimestampMs,
+    },
+    /// The authority crashes at `at_ms`, and the requests sent to it fail while it is down. If
+    /// `restart_ms` is set, it restarts then by recovering its state from storage.
+    Crash {
+        authority: AuthorityIndex,
+        at_ms: BlockTimestampMs,
+        restart_ms: Option<BlockTimestampMs>,
+    },
+    /// The authority is Byzantine and proposes two conflicting blocks for every round, each
+    /// one sent to half of its peers.
+    Equivocate { authority: AuthorityIndex },
+    /// The authority is Byzantine and sends its blocks only to `recipients`. It also never
+    /// serves fetch requests, so its blocks can only be fetched from the recipients.
+    Withhold {
+        authority: AuthorityIndex,
+        recipients: BTreeSet<AuthorityIndex>,
+    },
+}
+
+/// Configuration of a simulation run.
+#[derive(Clone, Debug)]
+pub(crate) struct SimulationConfig {
+    pub committee_size: usize,
+    /// Seed of the rng driving the run. The same config and seed always produce the same run.
+    pub seed: u64,
+    /// Minimum latency of a message between two authorities.
+    pub min_latency_ms: u64,
+    /// Maximum latency of a message between two authorities.
+    pub max_latency_ms: u64,
+    /// Leader timeout of the authorities.
+    pub leader_timeout_ms: u64,
+    /// Simulated duration of the run.
+    pub duration_ms: u64,
+    pub faults: Vec<Fault>,
+}
+
+impl SimulationConfig {
+    pub(crate) fn new(committee_size: usize, seed: u64) -> Self {
+        Self {
+            committee_size,
+            seed,
+            min_latency_ms: 10,
+            max_latency_ms: 100,
+            leader_timeout_ms: 250,
+            duration_ms: 20_000,
+            faults: vec![],
+        }
+    }
+
+    pub(crate) fn with_latency(mut self, min_latency_ms: u64, max_latency_ms: u64) -> Self {
+        assert!(min_latency_ms <= max_latency_ms);
+        self.min_latency_ms = min_latency_ms;
+        self.max_latency_ms = max_latency_ms;
+        self
+    }
+
+    pub(crate) fn with_duration(mut self, duration_ms: u64) -> Self {
+        self.duration_ms = duration_ms;
+        self
+    }
+
+    pub(crate) fn with_fault(mut self, fault: Fault) -> Self {
+        self.faults.push(fault);
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Behavior {
+    Honest,
+    Equivocate,
+    Withhold(BTreeSet<AuthorityIndex>),
+}
+
+/// A message in flight on the simulated network, with the channel its response is sent on.
+enum Message {
+    SendBlock {
+        block: Bytes,
+        reply: oneshot::Sender<ConsensusResult<()>>,
+    },
+    FetchBlocks {
+        block_refs: Vec<BlockRef>,
+        reply: oneshot::Sender<ConsensusResult<Vec<Bytes>>>,
+    },
+    FetchBlocksResponse {
+        result: ConsensusResult<Vec<Bytes>>,
+        reply: oneshot::Sender<ConsensusResult<Vec<Bytes>>>,
+    },
+}
+
+impl Message {
+    /// Messages of different kinds are sent by different tasks, so each kind has its own rng
+    /// and its own order on a link.
+    fn kind(&self) -> u8 {
+        match self {
+            Message::SendBlock { .. } => 0,
+            Message::FetchBlocks { .. } => 1,
+            Message::FetchBlocksResponse { .. } => 2,
+        }
+    }
+}
+
+/// A stream of messages of one kind, from an authority to another.
+type Link = (AuthorityIndex, AuthorityIndex, u8);
+
+struct NetworkState {
+    config: SimulationConfig,
+    behaviors: Vec<Behavior>,
+    now_ms: BlockTimestampMs,
+    /// Network services of the running authorities.
+    services: BTreeMap<AuthorityIndex, Arc<dyn NetworkService>>,
+    /// The rng and the number of messages sent of every link, so the latency and the order of a
+    /// message only depend on the messages sent before it on the same link.
+    links: BTreeMap<Link, (StdRng, u64)>,
+    /// Messages in flight, by delivery time, then by link and order on the link.
+    in_flight: BTreeMap<(BlockTimestampMs, Link, u64), Message>,
+    /// Number of messages sent so far.
+    messages_sent: u64,
+    /// Number of messages being handled by a network service.
+    pending_handlers: usize,
+}
+
+/// The in-memory network connecting the authorities of a simulation.
+#[derive(Clone)]
+struct SimNetwork {
+    state: Arc<Mutex<NetworkState>>,
+}
+
+impl SimNetwork {
+    fn new(config: SimulationConfig, behaviors: Vec<Behavior>) -> Self {
+        let state = NetworkState {
+            config,
+            behaviors,
+            now_ms: 0,
+            services: BTreeMap::new(),
+            links: BTreeMap::new(),
+            in_flight: BTreeMap::new(),
+            messages_sent: 0,
+            pending_handlers: 0,
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    fn client(&self, own_index: AuthorityIndex) -> Arc<SimNetworkClient> {
+        Arc::new(SimNetworkClient {
+            own_index,
+            network: self.clone(),
+        })
+    }
+
+    fn register(&self, authority: AuthorityIndex, service: Arc<dyn NetworkService>) {
+        self.state.lock().services.insert(authority, service);
+    }
+
+    fn unregister(&self, authority: AuthorityIndex) {
+        self.state.lock().services.remove(&authority);
+    }
+
+    /// Sends a message with a random latency. Messages across a partition are held back until
+    /// the partition heals.
+    fn send(&self, from: AuthorityIndex, to: AuthorityIndex, message: Message) {
+        let mut state = self.state.lock();
+        state.messages_sent += 1;
+
+        let link = (from, to, message.kind());
+        let seed = state.config.seed
+            ^ ((from.value() as u64) << 48)
+            ^ ((to.value() as u64) << 32)
+            ^ ((link.2 as u64) << 24);
+        let latency_range = state.config.min_latency_ms..=state.config.max_latency_ms;
+        let (rng, sent) = state
+            .links
+            .entry(link)
+            .or_insert_with(|| (StdRng::seed_from_u64(seed), 0));
+        let latency_ms = rng.gen_range(latency_range);
+        let order = *sent;
+        *sent += 1;
+
+        let mut delivery_ms = state.now_ms + latency_ms;
+        for fault in &state.config.faults {
+            if let Fault::Partition {
+                authorities,
+                start_ms,
+                end_ms,
+            } = fault
+            {
+                if authorities.contains(&from) != authorities.contains(&to)
+                    && (*start_ms..*end_ms).contains(&state.now_ms)
+                {
+                    delivery_ms = max(delivery_ms, end_ms + latency_ms);
+                }
+            }
+        }
+        state.in_flight.insert((delivery_ms, link, order), message);
+    }
+
+    /// Delivers the next message due by `now_ms` to its recipient. Returns false when there is
+    /// none left.
+    fn deliver_next(&self, now_ms: BlockTimestampMs) -> bool {
+        let mut state = self.state.lock();
+        let Some(entry) = state.in_flight.first_entry() else {
+            return false;
+        };
+        if entry.key().0 > now_ms {
+            return false;
+        }
+        let ((_, (from, to, _), _), message) = entry.remove_entry();
+        let service = state.services.get(&to).cloned();
+        let withholding = matches!(state.behaviors[to], Behavior::Withhold(_));
+
+        match message {
+            Message::SendBlock { block, reply } => {
+                let Some(service) = service else {
+                    reply.send(Err(Self::disconnected(to))).ok();
+                    return true;
+                };
+                state.pending_handlers += 1;
+                let network = self.clone();
+                tokio::spawn(async move {
+                    let result = service.handle_send_block(from, block).await;
+                    reply.send(result).ok();
+                    network.state.lock().pending_handlers -= 1;
+                });
+            }
+            Message::FetchBlocks { block_refs, reply } => {
+                let Some(service) = service else {
+                    reply.send(Err(Self::disconnected(to))).ok();
+                    return true;
+                };
+                state.pending_handlers += 1;
+                let network = self.clone();
+                tokio::spawn(async move {
+                    let result = if withholding {
+                        Ok(vec![])
+                    } else {
+                        service.handle_fetch_blocks(from, block_refs).await
+                    };
+                    network.send(to, from, Message::FetchBlocksResponse { result, reply });
+                    network.state.lock().pending_handlers -= 1;
+                });
+            }
+            Message::FetchBlocksResponse { result, reply } => {
+                reply.send(result).ok();
+            }
+        }
+        true
+    }
+
+    fn disconnected(authority: AuthorityIndex) -> ConsensusError {
+        ConsensusError::PeerDisconnected(authority.to_string())
+    }
+}
+
+/// Network client of an authority over the simulated network. Requests complete once they have
+/// been handled by the peer.
+struct SimNetworkClient {
+    own_index: AuthorityIndex,
+    network: SimNetwork,
+}
+
+impl SimNetworkClient {
+    async fn request<T>(
+        &self,
+        peer: AuthorityIndex,
+        message: impl FnOnce(oneshot::Sender<ConsensusResult<T>>) -> Message,
+    ) -> ConsensusResult<T> {
+        let (reply, receiver) = oneshot::channel();
+        self.network.send(self.own_index, peer, message(reply));
+        receiver
+            .await
+            .map_err(|_| ConsensusError::NetworkError("Simulation has ended".to_string()))?
+    }
+}
+
+#[async_trait]
+impl NetworkClient for SimNetworkClient {
+    async fn send_block(&self, peer: AuthorityIndex, block: &Bytes) -> ConsensusResult<()> {
+        let withheld = match &self.network.state.lock().behaviors[self.own_index] {
+            Behavior::Withhold(recipients) => !recipients.contains(&peer),
+            _ => false,
+        };
+        if withheld {
+            return Ok(());
+        }
+        let block = block.clone();
+        self.request(peer, |reply| Message::SendBlock { block, reply })
+            .await
+    }
+
+    async fn fetch_blocks(
+        &self,
+        peer: AuthorityIndex,
+        block_refs: Vec<BlockRef>,
+    ) -> ConsensusResult<Vec<Bytes>> {
+        self.request(peer, |reply| Message::FetchBlocks { block_refs, reply })
+            .await
+    }
+}
+
+/// Network manager of an authority over the simulated network.
+struct SimNetworkManager {
+    own_index: AuthorityIndex,
+    network: SimNetwork,
+    client: Arc<SimNetworkClient>,
+}
+
+impl SimNetworkManager {
+    fn new_simulated(network: SimNetwork, own_index: AuthorityIndex) -> Self {
+        Self {
+            own_index,
+            client: network.client(own_index),
+            network,
+        }
+    }
+}
+
+#[async_trait]
+impl<S: NetworkService> NetworkManager<S> for SimNetworkManager {
+    type Client = SimNetworkClient;
+
+    fn new(_context: Arc<Context>) -> Self {
+        unimplemented!("The simulated network manager is created by the simulator")
+    }
+
+    fn client(&self) -> Arc<Self::Client> {
+        self.client.clone()
+    }
+
+    async fn install_service(&mut self, _network_keypair: NetworkKeyPair, service: Arc<S>) {
+        self.network.register(self.own_index, service);
+    }
+
+    async fn stop(&mut self) {
+        self.network.unregister(self.own_index);
+    }
+}
+
+/// A Byzantine authority proposing two conflicting blocks for every round. It does not run an
+/// `AuthorityNode`, whose `Core` would refuse to accept the blocks of peers referencing its other
+/// versions.
+struct Equivocator {
+    context: Arc<Context>,
+    signer: NetworkKeyPair,
+    block_verifier: SignedBlockVerifier,
+    client: Arc<SimNetworkClient>,
+    state: Mutex<EquivocatorState>,
+}
+
+struct EquivocatorState {
+    blocks: BTreeMap<BlockRef, VerifiedBlock>,
+    last_proposed_round: Round,
+}
+
+impl Equivocator {
+    fn new(context: Arc<Context>, signer: NetworkKeyPair, network: &SimNetwork) -> Self {
+        let (my_genesis, mut genesis) = Block::genesis(context.clone());
+        genesis.push(my_genesis);
+        Self {
+            block_verifier: SignedBlockVerifier::new(context.clone()),
+            client: network.client(context.own_index),
+            context,
+            signer,
+            state: Mutex::new(EquivocatorState {
+                blocks: genesis.into_iter().map(|b| (b.reference(), b)).collect(),
+                last_proposed_round: GENESIS_ROUND,
+            }),
+        }
+    }
+
+    /// Proposes two versions of a block for the round after the highest round with a quorum,
+    /// referencing all the known blocks of that round, and sends each version to half of the
+    /// peers.
+    fn try_propose(&self) {
+        let committee = &self.context.committee;
+        let mut state = self.state.lock();
+        let mut quorum_round = GENESIS_ROUND;
+        let mut aggregators: BTreeMap<Round, StakeAggregator<QuorumThreshold>> = BTreeMap::new();
+        for block_ref in state.blocks.keys() {
+            if aggregators
+                .entry(block_ref.round)
+                .or_insert_with(StakeAggregator::new)
+                .add(block_ref.author, committee)
+            {
+                quorum_round = max(quorum_round, block_ref.round);
+            }
+        }
+
+        let round = quorum_round + 1;
+        if round <= state.last_proposed_round {
+            return;
+        }
+
+        let ancestors: Vec<_> = state
+            .blocks
+            .values()
+            .filter(|b| b.round() == quorum_round)
+            .collect();
+        let timestamp_ms = ancestors
+            .iter()
+            .map(|b| b.timestamp_ms())
+            .fold(self.context.clock.timestamp_utc_ms(), max);
+        let ancestors: Vec<_> = ancestors.iter().map(|b| b.reference()).collect();
+
+        // The versions only differ by their transactions.
+        let versions = [0_u8, 1].map(|version| {
+            let block = Block::V1(BlockV1::new(
+                committee.epoch(),
+                round,
+                self.context.own_index,
+                timestamp_ms,
+                ancestors.clone(),
+                vec![Transaction::new(vec![version])],
+            ));
+            let signed_block = SignedBlock::new(block, &self.signer).expect("Block signing failed");
+            VerifiedBlock::new_verified_unserialized(signed_block).unwrap()
+        });
+        for version in &versions {
+            state.blocks.insert(version.reference(), version.clone());
+        }
+        state.last_proposed_round = round;
+
+        for (i, (peer, _)) in committee
+            .authorities()
+            .filter(|(peer, _)| *peer != self.context.own_index)
+            .enumerate()
+        {
+            let client = self.client.clone();
+            let block = versions[i % 2].serialized().clone();
+            tokio::spawn(async move { client.send_block(peer, &block).await.ok() });
+        }
+    }
+}
+
+#[async_trait]
+impl NetworkService for Equivocator {
+    async fn handle_send_block(&self, _peer: AuthorityIndex, block: Bytes) -> ConsensusResult<()> {
+        let signed_block: SignedBlock =
+            bcs::from_bytes(&block).map_err(ConsensusError::MalformedBlock)?;
+        self.block_verifier.verify(&signed_block)?;
+        let block = VerifiedBlock::new_verified(signed_block, block)?;
+        self.state.lock().blocks.insert(block.reference(), block);
+        self.try_propose();
+        Ok(())
+    }
+
+    async fn handle_fetch_blocks(
+        &self,
+        _peer: AuthorityIndex,
+        block_refs: Vec<BlockRef>,
+    ) -> ConsensusResult<Vec<Bytes>> {
+        let state = self.state.lock();
+        Ok(block_refs
+            .iter()
+            .filter_map(|block_ref| state.blocks.get(block_ref))
+            .map(|block| block.serialized().clone())
+            .collect())
+    }
+}
+
+/// The running process of a simulated authority.
+enum Process {
+    Node {
+        node: AuthorityNode<SimNetworkManager, TestBlockVerifier>,
+        context: Arc<Context>,
+    },
+    Equivocator,
+}
+
+/// A simulated authority, with the state that survives its crashes.
+struct SimAuthority {
+    index: AuthorityIndex,
+    signer: NetworkKeyPair,
+    store: Arc<MemStore>,
+    behavior: Behavior,
+    /// None when the authority is down.
+    process: Option<Process>,
+}
+
+/// The faults happening at a given time of the run.
+enum Event {
+    Crash { authority: AuthorityIndex },
+    Restart { authority: AuthorityIndex },
+}
+
+pub(crate) struct Simulator {
+    config: SimulationConfig,
+    parameters: Parameters,
+    clock: Arc<Clock>,
+    network: SimNetwork,
+    /// Pending events by time. The event id breaks the ties in the order of the faults.
+    events: BTreeMap<(BlockTimestampMs, u64), Event>,
+    authorities: Vec<SimAuthority>,
+    /// The authorities that were running at the end of the simulation.
+    running_at_end: BTreeSet<AuthorityIndex>,
+}
+
+impl Simulator {
+    pub(crate) fn new(config: SimulationConfig) -> Self {
+        let (context, keypairs) = Context::new_for_test(config.committee_size);
+        let parameters = Parameters {
+            leader_timeout: Duration::from_millis(config.leader_timeout_ms),
+            ..Default::default()
+        };
+
+        let authorities: Vec<_> = context
+            .committee
+            .authorities()
+            .map(|(index, _)| {
+                let behavior = config
+                    .faults
+                    .iter()
+                    .find_map(|fault| match fault {
+                        Fault::Equivocate { authority } if *authority == index => {
+                            Some(Behavior::Equivocate)
+                        }
+                        Fault::Withhold {
+                            authority,
+                            recipients,
+                        } if *authority == index => Some(Behavior::Withhold(recipients.clone())),
+                        _ => None,
+                    })
+                    .unwrap_or(Behavior::Honest);
+                SimAuthority {
+                    index,
+                    signer: keypairs[index.value()].0.copy(),
+                    store: Arc::new(MemStore::new()),
+                    behavior,
+                    process: None,
+                }
+            })
+            .collect();
+
+        let mut events = BTreeMap::new();
+        for (id, fault) in config.faults.iter().enumerate() {
+            if let Fault::Crash {
+                authority,
+                at_ms,
+                restart_ms,
+            } = fault
+            {
+                assert_eq!(
+                    authorities[*authority].behavior,
+                    Behavior::Honest,
+                    "Only honest authorities can crash"
+                );
+                let authority = *authority;
+                events.insert((*at_ms, 2 * id as u64), Event::Crash { authority });
+                if let Some(restart_ms) = restart_ms {
+                    assert!(restart_ms > at_ms);
+                    events.insert(
+                        (*restart_ms, 2 * id as u64 + 1),
+                        Event::Restart { authority },
+                    );
+                }
+            }
+        }
+
+        let behaviors = authorities.iter().map(|a| a.behavior.clone()).collect();
+        Self {
+            network: SimNetwork::new(config.clone(), behaviors),
+            config,
+            parameters,
+            clock: Arc::new(Clock::new_simulated(0)),
+            events,
+            authorities,
+            running_at_end: BTreeSet::new(),
+        }
+    }
+
+    /// Starts all the authorities and runs them until the end of the simulation, on a runtime
+    /// whose clock only moves when the simulator advances it.
+    pub(crate) fn run(&mut self) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .start_paused(true)
+            .build()
+            .expect("Failed to build the simulation runtime");
+        runtime.block_on(self.run_authorities());
+    }
+
+    async fn run_authorities(&mut self) {
+        for authority in 0..self.authorities.len() {
+            let authority = self.authorities[authority].index;
+            self.start(authority).await;
+        }
+
+        let mut now_ms = 0;
+        loop {
+            self.wait_for_idle().await;
+            while let Some(entry) = self.events.first_entry() {
+                if entry.key().0 > now_ms {
+                    break;
+                }
+                match entry.remove() {
+                    Event::Crash { authority } => {
+                        tracing::info!("Authority {authority} crashed at {now_ms}ms");
+                        self.stop(authority).await;
+                    }
+                    Event::Restart { authority } => {
+                        tracing::info!("Authority {authority} restarted at {now_ms}ms");
+                        self.start(authority).await;
+                    }
+                }
+                self.wait_for_idle().await;
+            }
+            while self.network.deliver_next(now_ms) {
+                self.wait_for_idle().await;
+            }
+
+            if now_ms >= self.config.duration_ms {
+                break;
+            }
+            now_ms += 1;
+            self.clock.advance_to(now_ms);
+            self.network.state.lock().now_ms = now_ms;
+            tokio::time::advance(Duration::from_millis(1)).await;
+        }
+
+        for authority in 0..self.authorities.len() {
+            let authority = self.authorities[authority].index;
+            if self.authorities[authority].process.is_some() {
+                self.running_at_end.insert(authority);
+                self.stop(authority).await;
+            }
+        }
+        self.network.state.lock().in_flight.clear();
+    }
+
+    async fn start(&mut self, authority: AuthorityIndex) {
+        let context = Arc::new(
+            Context::new_for_test(self.config.committee_size)
+                .0
+                .with_authority_index(authority)
+                .with_parameters(self.parameters.clone())
+                .with_clock(self.clock.clone()),
+        );
+        let sim = &mut self.authorities[authority];
+        let process = match sim.behavior {
+            Behavior::Equivocate => {
+                let equivocator =
+                    Arc::new(Equivocator::new(context, sim.signer.copy(), &self.network));
+                self.network.register(authority, equivocator.clone());
+                equivocator.try_propose();
+                Process::Equivocator
+            }
+            Behavior::Honest | Behavior::Withhold(_) => {
+                let node = AuthorityNode::start_with(
+                    context.clone(),
+                    sim.store.clone(),
+                    SimNetworkManager::new_simulated(self.network.clone(), authority),
+                    sim.signer.copy(),
+                    TestBlockVerifier,
+                )
+                .await;
+                Process::Node { node, context }
+            }
+        };
+        sim.process = Some(process);
+    }
+
+    async fn stop(&mut self, authority: AuthorityIndex) {
+        match self.authorities[authority].process.take() {
+            Some(Process::Node { node, .. }) => node.stop().await,
+            Some(Process::Equivocator) => self.network.unregister(authority),
+            None => {}
+        }
+    }
+
+    /// Waits until every Core thread has processed all the commands sent to it, and the tasks of
+    /// the authorities have stopped sending commands and messages, ie they are all blocked on a
+    /// timer or on a message in flight. The clock can't move while waiting, as this task is
+    /// always ready to run.
+    async fn wait_for_idle(&self) {
+        let mut idle_polls = 0;
+        let mut last_activity = None;
+        while idle_polls < IDLE_POLLS {
+            tokio::task::yield_now().await;
+            let (activity, busy) = self.activity();
+            if busy {
+                idle_polls = 0;
+                std::thread::yield_now();
+            } else if last_activity == Some(activity) {
+                idle_polls += 1;
+            } else {
+                idle_polls = 0;
+            }
+            last_activity = Some(activity);
+        }
+    }
+
+    /// Returns counters that change whenever an authority makes progress, and whether some work
+    /// is still pending.
+    fn activity(&self) -> ((u64, u64), bool) {
+        let (mut enqueued, mut processed) = (0, 0);
+        for sim in &self.authorities {
+            if let Some(Process::Node { context, .. }) = &sim.process {
+                let metrics = &context.metrics.node_metrics;
+                enqueued += metrics.core_lock_enqueued.get();
+                processed += metrics.core_lock_processed.get();
+            }
+        }
+        let state = self.network.state.lock();
+        (
+            (enqueued, state.messages_sent),
+            enqueued != processed || state.pending_handlers > 0,
+        )
+    }
+
+    /// Returns the commits of the authority, in order, as persisted by its `Core`.
+    pub(crate) fn commits(&self, authority: AuthorityIndex) -> Vec<Commit> {
+        self.authorities[authority]
+            .store
+            .scan_commits(1)
+            .expect("Reading from the in-memory store should not fail")
+    }
+
+    /// Returns the number of leaders committed by the authority.
+    pub(crate) fn num_commits(&self, authority: AuthorityIndex) -> usize {
+        self.commits(authority).len()
+    }
+
+    /// Returns the honest authorities, which the invariants are checked for.
+    pub(crate) fn honest_authorities(&self) -> Vec<AuthorityIndex> {
+        self.authorities
+            .iter()
+            .filter(|a| a.behavior == Behavior::Honest)
+            .map(|a| a.index)
+            .collect()
+    }
+
+    /// Checks that the commit sequences of the honest authorities never diverge, ie each one
+    /// is a prefix of the others.
+    pub(crate) fn check_safety(&self) {
+        let honest = self.honest_authorities();
+        for (i, a) in honest.iter().enumerate() {
+            for b in &honest[i + 1..] {
+                let (a_commits, b_commits) = (self.commits(*a), self.commits(*b));
+                let len = a_commits.len().min(b_commits.len());
+                for (a_commit, b_commit) in a_commits[..len].iter().zip(&b_commits[..len]) {
+                    assert_eq!(
+                        a_commit, b_commit,
+                        "Commit sequences of {a} and {b} diverged (seed {})",
+                        self.config.seed
+                    );
+                }
+            }
+        }
+    }
+
+    /// Checks that every honest authority running at the end of the simulation has committed
+    /// at least `min_commits` leaders.
+    pub(crate) fn check_liveness(&self, min_commits: usize) {
+        for authority in self.honest_authorities() {
+            if !self.running_at_end.contains(&authority) {
+                continue;
+            }
+            let num_commits = self.num_commits(authority);
+            assert!(
+                num_commits >= min_commits,
+                "Authority {authority} committed {num_commits} leaders, expected at least \
+                {min_commits} (seed {})",
+                self.config.seed
+            );
+        }
+    }
+}
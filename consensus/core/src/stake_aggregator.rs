@@ -54,6 +54,11 @@ impl<T: CommitteeThreshold> StakeAggregator<T> {
         T::is_threshold(committee, self.stake)
     }
 
+    /// Returns the total stake of the votes added so far.
+    pub fn stake(&self) -> Stake {
+        self.stake
+    }
+
     pub fn clear(&mut self) {
         self.votes.clear();
         self.stake = 0;
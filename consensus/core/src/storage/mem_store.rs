@@ -11,9 +11,10 @@ use parking_lot::RwLock;
 
 use super::Store;
 use crate::{
-    block::{BlockDigest, BlockRef, Round, VerifiedBlock},
+    block::{BlockDigest, BlockRef, EquivocationEvidence, Round, VerifiedBlock},
     commit::{Commit, CommitIndex},
     error::ConsensusResult,
+    leader_scoring::ReputationScores,
 };
 
 /// In-memory storage for testing.
@@ -25,6 +26,8 @@ struct Inner {
     blocks: BTreeMap<(Round, AuthorityIndex, BlockDigest), VerifiedBlock>,
     digests_by_authorities: BTreeSet<(AuthorityIndex, Round, BlockDigest)>,
     commits: BTreeMap<CommitIndex, Commit>,
+    reputation_scores: BTreeMap<CommitIndex, ReputationScores>,
+    equivocation_evidences: BTreeMap<(AuthorityIndex, Round), EquivocationEvidence>,
 }
 
 impl MemStore {
@@ -35,6 +38,8 @@ impl MemStore {
                 blocks: BTreeMap::new(),
                 digests_by_authorities: BTreeSet::new(),
                 commits: BTreeMap::new(),
+                reputation_scores: BTreeMap::new(),
+                equivocation_evidences: BTreeMap::new(),
             }),
         }
     }
@@ -124,4 +129,43 @@ impl Store for MemStore {
         }
         Ok(commits)
     }
+
+    fn write_reputation_scores(&self, scores: &ReputationScores) -> ConsensusResult<()> {
+        let mut inner = self.inner.write();
+        inner
+            .reputation_scores
+            .insert(scores.last_commit_index, scores.clone());
+        Ok(())
+    }
+
+    fn read_last_reputation_scores(&self) -> ConsensusResult<Option<ReputationScores>> {
+        let inner = self.inner.read();
+        Ok(inner
+            .reputation_scores
+            .last_key_value()
+            .map(|(_, scores)| scores.clone()))
+    }
+
+    fn write_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> ConsensusResult<()> {
+        let mut inner = self.inner.write();
+        inner
+            .equivocation_evidences
+            .insert((evidence.author, evidence.round), evidence.clone());
+        Ok(())
+    }
+
+    fn read_equivocation_evidences(
+        &self,
+        authority: AuthorityIndex,
+    ) -> ConsensusResult<Vec<EquivocationEvidence>> {
+        let inner = self.inner.read();
+        Ok(inner
+            .equivocation_evidences
+            .range((
+                Included((authority, Round::MIN)),
+                Included((authority, Round::MAX)),
+            ))
+            .map(|(_, evidence)| evidence.clone())
+            .collect())
+    }
 }
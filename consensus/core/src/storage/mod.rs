@@ -10,9 +10,10 @@ mod store_tests;
 use consensus_config::AuthorityIndex;
 
 use crate::{
-    block::{BlockRef, Round, VerifiedBlock},
+    block::{BlockRef, EquivocationEvidence, Round, VerifiedBlock},
     commit::{Commit, CommitIndex},
     error::ConsensusResult,
+    leader_scoring::ReputationScores,
 };
 
 /// A common interface for consensus storage.
@@ -38,4 +39,19 @@ pub(crate) trait Store: Send + Sync {
 
     /// Reads all commits from start_commit_index.
     fn scan_commits(&self, start_commit_index: CommitIndex) -> ConsensusResult<Vec<Commit>>;
+
+    /// Writes the reputation scores of a completed leader schedule period.
+    fn write_reputation_scores(&self, scores: &ReputationScores) -> ConsensusResult<()>;
+
+    /// Reads the reputation scores of the last completed leader schedule period.
+    fn read_last_reputation_scores(&self) -> ConsensusResult<Option<ReputationScores>>;
+
+    /// Writes the evidence of an equivocation. Only one evidence is kept per author and round.
+    fn write_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> ConsensusResult<()>;
+
+    /// Reads all the equivocation evidences of an authority, ordered by round.
+    fn read_equivocation_evidences(
+        &self,
+        authority: AuthorityIndex,
+    ) -> ConsensusResult<Vec<EquivocationEvidence>>;
 }
@@ -16,9 +16,10 @@ use typed_store::{
 
 use super::Store;
 use crate::{
-    block::{BlockDigest, BlockRef, Round, SignedBlock, VerifiedBlock},
+    block::{BlockDigest, BlockRef, EquivocationEvidence, Round, SignedBlock, VerifiedBlock},
     commit::{Commit, CommitIndex},
     error::ConsensusResult,
+    leader_scoring::ReputationScores,
 };
 
 /// Persistent storage with RocksDB.
@@ -29,6 +30,10 @@ pub(crate) struct RocksDBStore {
     digests_by_authorities: DBMap<(AuthorityIndex, Round, BlockDigest), ()>,
     /// Maps commit index to content.
     commits: DBMap<CommitIndex, Commit>,
+    /// Maps the last commit index of a leader schedule period to the reputation scores of the period.
+    reputation_scores: DBMap<CommitIndex, ReputationScores>,
+    /// Stores the evidence of equivocations by author and round.
+    equivocation_evidences: DBMap<(AuthorityIndex, Round), EquivocationEvidence>,
 }
 
 #[allow(unused)]
@@ -36,6 +41,8 @@ impl RocksDBStore {
     pub(crate) const BLOCKS_CF: &'static str = "blocks";
     pub(crate) const DIGESTS_BY_AUTHORITIES_CF: &'static str = "digests";
     pub(crate) const COMMITS_CF: &'static str = "commits";
+    pub(crate) const REPUTATION_SCORES_CF: &'static str = "reputation_scores";
+    pub(crate) const EQUIVOCATION_EVIDENCES_CF: &'static str = "equivocation_evidences";
 
     /// Creates a new instance of RocksDB storage.
     pub(crate) fn new(path: &str) -> Self {
@@ -57,6 +64,8 @@ impl RocksDBStore {
             ),
             (Self::DIGESTS_BY_AUTHORITIES_CF, cf_options.clone()),
             (Self::COMMITS_CF, cf_options.clone()),
+            (Self::REPUTATION_SCORES_CF, cf_options.clone()),
+            (Self::EQUIVOCATION_EVIDENCES_CF, cf_options.clone()),
         ];
         let rocksdb = open_cf_opts(
             path,
@@ -66,16 +75,20 @@ impl RocksDBStore {
         )
         .expect("Cannot open database");
 
-        let (blocks, digests_by_authorities, commits) = reopen!(&rocksdb,
+        let (blocks, digests_by_authorities, commits, reputation_scores, equivocation_evidences) = reopen!(&rocksdb,
             Self::BLOCKS_CF;<(Round, AuthorityIndex, BlockDigest), bytes::Bytes>,
             Self::DIGESTS_BY_AUTHORITIES_CF;<(AuthorityIndex, Round, BlockDigest), ()>,
-            Self::COMMITS_CF;<u64, Commit>
+            Self::COMMITS_CF;<u64, Commit>,
+            Self::REPUTATION_SCORES_CF;<u64, ReputationScores>,
+            Self::EQUIVOCATION_EVIDENCES_CF;<(AuthorityIndex, Round), EquivocationEvidence>
         );
 
         Self {
             blocks,
             digests_by_authorities,
             commits,
+            reputation_scores,
+            equivocation_evidences,
         }
     }
 }
@@ -175,4 +188,39 @@ impl Store for RocksDBStore {
         }
         Ok(commits)
     }
+
+    fn write_reputation_scores(&self, scores: &ReputationScores) -> ConsensusResult<()> {
+        self.reputation_scores
+            .insert(&scores.last_commit_index, scores)?;
+        Ok(())
+    }
+
+    fn read_last_reputation_scores(&self) -> ConsensusResult<Option<ReputationScores>> {
+        let Some(scores) = self.reputation_scores.safe_iter().skip_to_last().next() else {
+            return Ok(None);
+        };
+        let (_, scores) = scores?;
+        Ok(Some(scores))
+    }
+
+    fn write_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> ConsensusResult<()> {
+        self.equivocation_evidences
+            .insert(&(evidence.author, evidence.round), evidence)?;
+        Ok(())
+    }
+
+    fn read_equivocation_evidences(
+        &self,
+        authority: AuthorityIndex,
+    ) -> ConsensusResult<Vec<EquivocationEvidence>> {
+        let mut evidences = vec![];
+        for kv in self.equivocation_evidences.safe_range_iter((
+            Included((authority, Round::MIN)),
+            Included((authority, Round::MAX)),
+        )) {
+            let (_, evidence) = kv?;
+            evidences.push(evidence);
+        }
+        Ok(evidences)
+    }
 }
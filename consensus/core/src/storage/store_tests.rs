@@ -7,8 +7,9 @@ use tempfile::TempDir;
 
 use super::{mem_store::MemStore, rocksdb_store::RocksDBStore, Store};
 use crate::{
-    block::{BlockDigest, BlockRef, TestBlock, VerifiedBlock},
+    block::{BlockDigest, BlockRef, EquivocationEvidence, TestBlock, VerifiedBlock},
     commit::Commit,
+    leader_scoring::ReputationScores,
 };
 
 /// Test fixture for store tests. Wraps around various store implementations.
@@ -236,3 +237,70 @@ async fn read_and_scan_commits(
         assert_eq!(scanned_commits, written_commits,);
     }
 }
+
+#[rstest]
+#[tokio::test]
+async fn write_and_read_reputation_scores(
+    #[values(TestStore::new_rocksdb_store(), TestStore::new_mem_store())] test_store: TestStore,
+) {
+    let store = test_store.store();
+
+    {
+        let last_scores = store
+            .read_last_reputation_scores()
+            .expect("Read last reputation scores should not fail");
+        assert!(last_scores.is_none(), "{:?}", last_scores);
+    }
+
+    let written_scores = vec![
+        ReputationScores::new(1, 300, vec![10, 20, 30, 40]),
+        ReputationScores::new(301, 600, vec![40, 30, 20, 10]),
+    ];
+    for scores in &written_scores {
+        store.write_reputation_scores(scores).unwrap();
+    }
+
+    {
+        let last_scores = store
+            .read_last_reputation_scores()
+            .expect("Read last reputation scores should not fail");
+        assert_eq!(last_scores.as_ref(), written_scores.last());
+    }
+}
+
+#[rstest]
+#[tokio::test]
+async fn write_and_read_equivocation_evidences(
+    #[values(TestStore::new_rocksdb_store(), TestStore::new_mem_store())] test_store: TestStore,
+) {
+    let store = test_store.store();
+
+    let authority = AuthorityIndex::new_for_test(1);
+    assert!(store
+        .read_equivocation_evidences(authority)
+        .unwrap()
+        .is_empty());
+
+    let evidences = [(5, 1), (3, 1), (4, 2)]
+        .into_iter()
+        .map(|(round, author)| {
+            let first = VerifiedBlock::new_for_test(
+                TestBlock::new(round, author).set_timestamp_ms(1).build(),
+            );
+            let second = VerifiedBlock::new_for_test(
+                TestBlock::new(round, author).set_timestamp_ms(2).build(),
+            );
+            EquivocationEvidence::new(&first, &second)
+        })
+        .collect::<Vec<_>>();
+    for evidence in &evidences {
+        store.write_equivocation_evidence(evidence).unwrap();
+    }
+
+    // Evidences are returned for the requested authority only, by round.
+    let read_evidences = store.read_equivocation_evidences(authority).unwrap();
+    assert_eq!(
+        read_evidences,
+        vec![evidences[1].clone(), evidences[0].clone()]
+    );
+}
@@ -0,0 +1,403 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::BTreeSet, sync::Arc, time::Duration};
+
+use bytes::Bytes;
+use consensus_config::AuthorityIndex;
+use parking_lot::Mutex;
+use tokio::{
+    sync::mpsc,
+    task::JoinSet,
+    time::{interval_at, Instant, MissedTickBehavior},
+};
+use tracing::{debug, warn};
+
+use crate::{
+    authority_service::MAX_BLOCKS_PER_FETCH,
+    block::{BlockRef, SignedBlock, VerifiedBlock},
+    block_verifier::{BlockVerifier, SignedBlockVerifier},
+    context::Context,
+    core_thread::CoreThreadDispatcherInterface,
+    ensure,
+    error::{ConsensusError, ConsensusResult},
+    network::NetworkClient,
+};
+
+/// Interval at which the missing blocks of Core are fetched from the peers in turn, in case they
+/// could not be fetched from the peer that sent the blocks referencing them.
+const SYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of fetch requests from the network service that can be pending. Further requests are
+/// dropped, and their blocks are fetched by the periodic synchronization instead.
+const FETCH_REQUESTS_BUFFER_SIZE: usize = 100;
+
+/// Handle to the synchronizer task, used by the network service to request missing blocks.
+pub(crate) struct SynchronizerHandle {
+    fetch_sender: mpsc::Sender<(AuthorityIndex, BTreeSet<BlockRef>)>,
+    tasks: Mutex<JoinSet<()>>,
+}
+
+impl SynchronizerHandle {
+    /// Requests blocks missing from Core from the peer, which has sent blocks referencing them
+    /// and should be able to serve them.
+    pub(crate) fn fetch_blocks(&self, peer: AuthorityIndex, block_refs: BTreeSet<BlockRef>) {
+        if self.fetch_sender.try_send((peer, block_refs)).is_err() {
+            debug!("Too many pending fetch requests, dropping the request to peer {peer}");
+        }
+    }
+
+    pub(crate) fn stop(&self) {
+        self.tasks.lock().abort_all();
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new_for_test() -> (
+        Arc<Self>,
+        mpsc::Receiver<(AuthorityIndex, BTreeSet<BlockRef>)>,
+    ) {
+        let (fetch_sender, fetch_receiver) = mpsc::channel(FETCH_REQUESTS_BUFFER_SIZE);
+        let handle = Self {
+            fetch_sender,
+            tasks: Mutex::new(JoinSet::new()),
+        };
+        (Arc::new(handle), fetch_receiver)
+    }
+}
+
+/// Synchronizer fetches the blocks that Core is missing from peers, so blocks that were not
+/// received through broadcast, ex while this authority was down or lagging, are still accepted.
+///
+/// Missing ancestors of a received block are first requested from the peer that sent it. Then
+/// all the missing blocks of Core are periodically requested from the peers in turn.
+pub(crate) struct Synchronizer<C: NetworkClient, V: BlockVerifier, D: CoreThreadDispatcherInterface>
+{
+    context: Arc<Context>,
+    network_client: Arc<C>,
+    signed_block_verifier: SignedBlockVerifier,
+    block_verifier: Arc<V>,
+    core_dispatcher: Arc<D>,
+    fetch_receiver: mpsc::Receiver<(AuthorityIndex, BTreeSet<BlockRef>)>,
+    /// Offset from this authority of the next peer to synchronize with periodically.
+    next_peer_offset: usize,
+}
+
+impl<C: NetworkClient, V: BlockVerifier, D: CoreThreadDispatcherInterface> Synchronizer<C, V, D> {
+    pub(crate) fn start(
+        context: Arc<Context>,
+        network_client: Arc<C>,
+        block_verifier: Arc<V>,
+        core_dispatcher: Arc<D>,
+    ) -> Arc<SynchronizerHandle> {
+        let (fetch_sender, fetch_receiver) = mpsc::channel(FETCH_REQUESTS_BUFFER_SIZE);
+        let synchronizer = Self {
+            signed_block_verifier: SignedBlockVerifier::new(context.clone()),
+            context,
+            network_client,
+            block_verifier,
+            core_dispatcher,
+            fetch_receiver,
+            next_peer_offset: 1,
+        };
+        let mut tasks = JoinSet::new();
+        tasks.spawn(synchronizer.run());
+        Arc::new(SynchronizerHandle {
+            fetch_sender,
+            tasks: Mutex::new(tasks),
+        })
+    }
+
+    async fn run(mut self) {
+        let mut interval = interval_at(Instant::now() + SYNC_INTERVAL, SYNC_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                // Branches are polled in order, so the synchronizer behaves the same way on
+                // every run under a simulated clock.
+                biased;
+
+                Some((peer, block_refs)) = self.fetch_receiver.recv() => {
+                    self.synchronize(peer, block_refs).await;
+                }
+                _ = interval.tick() => {
+                    let missing_blocks = match self.core_dispatcher.get_missing_blocks().await {
+                        Ok(missing_blocks) => missing_blocks,
+                        Err(e) => {
+                            debug!("Core thread has shut down, stopping the synchronizer: {e}");
+                            return;
+                        }
+                    };
+                    if !missing_blocks.is_empty() {
+                        let peer = self.next_peer();
+                        self.synchronize(peer, missing_blocks).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fetches the missing blocks from the peer, then the ancestors of the fetched blocks that are
+    /// missing in turn, until the peer has none of the missing blocks left.
+    async fn synchronize(&self, peer: AuthorityIndex, mut missing_blocks: BTreeSet<BlockRef>) {
+        let peer_hostname = &self.context.committee.authority(peer).hostname;
+        while !missing_blocks.is_empty() {
+            let block_refs: Vec<_> = missing_blocks
+                .iter()
+                .take(MAX_BLOCKS_PER_FETCH)
+                .cloned()
+                .collect();
+            let serialized_blocks = match self
+                .network_client
+                .fetch_blocks(peer, block_refs.clone())
+                .await
+            {
+                Ok(serialized_blocks) => serialized_blocks,
+                Err(e) => {
+                    debug!("Failed to fetch blocks from {peer_hostname}: {e}");
+                    return;
+                }
+            };
+            if serialized_blocks.is_empty() {
+                return;
+            }
+            let blocks = match self
+                .verify_blocks(peer, &block_refs, serialized_blocks)
+                .await
+            {
+                Ok(blocks) => blocks,
+                Err(e) => {
+                    warn!("Invalid blocks fetched from {peer_hostname}: {e}");
+                    return;
+                }
+            };
+            missing_blocks = match self.core_dispatcher.add_blocks(blocks).await {
+                Ok(missing_blocks) => missing_blocks,
+                Err(e) => {
+                    debug!("Core thread has shut down, dropping the fetched blocks: {e}");
+                    return;
+                }
+            };
+        }
+    }
+
+    /// Verifies the fetched blocks the same way the network service verifies the blocks sent by
+    /// peers. Only the requested blocks are accepted, as the peer is not trusted.
+    async fn verify_blocks(
+        &self,
+        peer: AuthorityIndex,
+        block_refs: &[BlockRef],
+        serialized_blocks: Vec<Bytes>,
+    ) -> ConsensusResult<Vec<VerifiedBlock>> {
+        let mut blocks = Vec::with_capacity(serialized_blocks.len());
+        for serialized_block in serialized_blocks {
+            let signed_block: SignedBlock =
+                bcs::from_bytes(&serialized_block).map_err(ConsensusError::MalformedBlock)?;
+            self.signed_block_verifier.verify(&signed_block)?;
+            self.block_verifier
+                .verify(&signed_block)
+                .await
+                .map_err(|e| ConsensusError::InvalidBlock(e.to_string()))?;
+            let verified_block = VerifiedBlock::new_verified(signed_block, serialized_block)?;
+            ensure!(
+                block_refs.contains(&verified_block.reference()),
+                ConsensusError::UnexpectedFetchedBlock {
+                    index: peer,
+                    block_ref: verified_block.reference(),
+                }
+            );
+            blocks.push(verified_block);
+        }
+        Ok(blocks)
+    }
+
+    fn next_peer(&mut self) -> AuthorityIndex {
+        let committee_size = self.context.committee.size();
+        let index = (self.context.own_index.value() + self.next_peer_offset) % committee_size;
+        self.next_peer_offset = self.next_peer_offset % (committee_size - 1) + 1;
+        self.context
+            .committee
+            .to_authority_index(index)
+            .expect("Peer index should be in the committee")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use async_trait::async_trait;
+    use consensus_config::{NetworkKeyPair, ProtocolKeyPair};
+
+    use super::*;
+    use crate::{
+        block::{Block, BlockAPI as _, Round, TestBlock},
+        block_verifier::TestBlockVerifier,
+        core_thread::CoreError,
+    };
+
+    /// Serves the blocks it holds, and records the requests.
+    #[derive(Default)]
+    struct FakeNetworkClient {
+        blocks: Mutex<BTreeMap<BlockRef, Bytes>>,
+        requests: Mutex<Vec<(AuthorityIndex, Vec<BlockRef>)>>,
+    }
+
+    #[async_trait]
+    impl NetworkClient for FakeNetworkClient {
+        async fn send_block(&self, _peer: AuthorityIndex, _block: &Bytes) -> ConsensusResult<()> {
+            unimplemented!("Unimplemented")
+        }
+
+        async fn fetch_blocks(
+            &self,
+            peer: AuthorityIndex,
+            block_refs: Vec<BlockRef>,
+        ) -> ConsensusResult<Vec<Bytes>> {
+            self.requests.lock().push((peer, block_refs.clone()));
+            let blocks = self.blocks.lock();
+            Ok(block_refs
+                .iter()
+                .filter_map(|block_ref| blocks.get(block_ref).cloned())
+                .collect())
+        }
+    }
+
+    /// Accepts every block, and reports the ancestors it has not seen as missing.
+    #[derive(Default)]
+    struct FakeCoreThreadDispatcher {
+        blocks: Mutex<BTreeMap<BlockRef, VerifiedBlock>>,
+    }
+
+    #[async_trait]
+    impl CoreThreadDispatcherInterface for FakeCoreThreadDispatcher {
+        async fn add_blocks(
+            &self,
+            blocks: Vec<VerifiedBlock>,
+        ) -> Result<BTreeSet<BlockRef>, CoreError> {
+            let mut known = self.blocks.lock();
+            for block in &blocks {
+                known.insert(block.reference(), block.clone());
+            }
+            Ok(blocks
+                .iter()
+                .flat_map(|block| block.ancestors().to_vec())
+                .filter(|ancestor| ancestor.round > 0 && !known.contains_key(ancestor))
+                .collect())
+        }
+
+        async fn force_new_block(&self, _round: Round) -> Result<(), CoreError> {
+            unimplemented!()
+        }
+
+        async fn get_missing_blocks(&self) -> Result<BTreeSet<BlockRef>, CoreError> {
+            Ok(BTreeSet::new())
+        }
+    }
+
+    fn serialize(
+        round: Round,
+        author: u32,
+        ancestors: Vec<BlockRef>,
+        keys: &[(NetworkKeyPair, ProtocolKeyPair)],
+    ) -> Bytes {
+        let block = TestBlock::new(round, author)
+            .set_ancestors(ancestors)
+            .build();
+        SignedBlock::new(block, &keys[author as usize].0)
+            .unwrap()
+            .serialize()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_synchronize_missing_ancestors() {
+        let (context, keys) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let network_client = Arc::new(FakeNetworkClient::default());
+        let core_dispatcher = Arc::new(FakeCoreThreadDispatcher::default());
+        let handle = Synchronizer::start(
+            context.clone(),
+            network_client.clone(),
+            Arc::new(TestBlockVerifier),
+            core_dispatcher.clone(),
+        );
+
+        // The peer holds 3 rounds of blocks of all authorities, each block referencing all the
+        // blocks of the previous round.
+        let (my_genesis, mut ancestors) = Block::genesis(context.clone());
+        ancestors.push(my_genesis);
+        let mut ancestors: Vec<_> = ancestors.iter().map(|b| b.reference()).collect();
+        let mut rounds = vec![];
+        for round in 1..=3 {
+            let blocks: Vec<_> = (0..4)
+                .map(|author| {
+                    let serialized = serialize(round, author, ancestors.clone(), &keys);
+                    let signed_block: SignedBlock = bcs::from_bytes(&serialized).unwrap();
+                    VerifiedBlock::new_verified(signed_block, serialized).unwrap()
+                })
+                .collect();
+            for block in &blocks {
+                network_client
+                    .blocks
+                    .lock()
+                    .insert(block.reference(), block.serialized().clone());
+            }
+            ancestors = blocks.iter().map(|b| b.reference()).collect();
+            rounds.push(blocks);
+        }
+
+        // Requesting a block of the last round fetches its whole causal history from the peer,
+        // one round at a time.
+        let peer = AuthorityIndex::new_for_test(1);
+        handle.fetch_blocks(peer, BTreeSet::from([rounds[2][1].reference()]));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let received = core_dispatcher.blocks.lock().clone();
+        assert_eq!(received.len(), 9);
+        assert!(received.contains_key(&rounds[2][1].reference()));
+        for block in rounds[..2].iter().flatten() {
+            assert!(received.contains_key(&block.reference()));
+        }
+        let requests = network_client.requests.lock().clone();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|(p, _)| *p == peer));
+        assert_eq!(
+            requests[2].1,
+            rounds[0].iter().map(|b| b.reference()).collect::<Vec<_>>()
+        );
+
+        handle.stop();
+    }
+
+    #[tokio::test]
+    async fn test_unrequested_blocks_are_rejected() {
+        let (context, keys) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        let network_client = Arc::new(FakeNetworkClient::default());
+        let core_dispatcher = Arc::new(FakeCoreThreadDispatcher::default());
+        let (_handle, fetch_receiver) = SynchronizerHandle::new_for_test();
+        let synchronizer = Synchronizer {
+            signed_block_verifier: SignedBlockVerifier::new(context.clone()),
+            context,
+            network_client,
+            block_verifier: Arc::new(TestBlockVerifier),
+            core_dispatcher,
+            fetch_receiver,
+            next_peer_offset: 1,
+        };
+
+        let (my_genesis, mut genesis) = Block::genesis(context.clone());
+        genesis.push(my_genesis);
+        let genesis = genesis.iter().map(|b| b.reference()).collect();
+        let serialized = serialize(1, 1, genesis, &keys);
+        let peer = AuthorityIndex::new_for_test(1);
+        let result = synchronizer
+            .verify_blocks(peer, &[], vec![serialized])
+            .await;
+        assert!(matches!(
+            result,
+            Err(ConsensusError::UnexpectedFetchedBlock { .. })
+        ));
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeSet;
+
+use consensus_config::AuthorityIndex;
+use rstest::rstest;
+
+use crate::simulator::{Fault, SimulationConfig, Simulator};
+
+fn authorities(indices: &[u32]) -> BTreeSet<AuthorityIndex> {
+    indices
+        .iter()
+        .map(|i| AuthorityIndex::new_for_test(*i))
+        .collect()
+}
+
+fn run(config: SimulationConfig) -> Simulator {
+    let mut simulator = Simulator::new(config);
+    simulator.run();
+    simulator.check_safety();
+    simulator
+}
+
+/// All authorities are honest and the network is synchronous.
+#[rstest]
+fn honest_committee(#[values(4, 7)] committee_size: usize, #[values(0, 1, 2)] seed: u64) {
+    telemetry_subscribers::init_for_testing();
+    let simulator = run(SimulationConfig::new(committee_size, seed));
+    simulator.check_liveness(10);
+}
+
+/// The same seed produces the same run, while another seed changes the schedule.
+#[test]
+fn deterministic_runs() {
+    telemetry_subscribers::init_for_testing();
+    let config = SimulationConfig::new(4, 7).with_duration(5_000);
+    let first = run(config.clone());
+    let second = run(config);
+    for authority in first.honest_authorities() {
+        assert!(!first.commits(authority).is_empty());
+        assert_eq!(first.commits(authority), second.commits(authority));
+    }
+}
+
+/// Message latencies are above the leader timeout, so most leaders are skipped, but the
+/// committee still makes progress.
+#[rstest]
+fn slow_network(#[values(0, 1, 2)] seed: u64) {
+    telemetry_subscribers::init_for_testing();
+    let config = SimulationConfig::new(4, seed).with_latency(100, 1_000);
+    let simulator = run(config);
+    simulator.check_liveness(1);
+}
+
+/// An authority is isolated from the rest of the committee for a while, and catches up after
+/// the partition heals.
+#[rstest]
+fn isolated_authority(#[values(0, 1, 2)] seed: u64) {
+    telemetry_subscribers::init_for_testing();
+    let config = SimulationConfig::new(4, seed).with_fault(Fault::Partition {
+        authorities: authorities(&[3]),
+        start_ms: 2_000,
+        end_ms: 8_000,
+    });
+    let simulator = run(config);
+    simulator.check_liveness(10);
+}
+
+/// The committee is split in half, so no quorum can be formed until the partition heals.
+#[rstest]
+fn split_committee(#[values(0, 1, 2)] seed: u64) {
+    telemetry_subscribers::init_for_testing();
+    let config = SimulationConfig::new(4, seed).with_fault(Fault::Partition {
+        authorities: authorities(&[0, 1]),
+        start_ms: 1_000,
+        end_ms: 10_000,
+    });
+    let simulator = run(config);
+    simulator.check_liveness(5);
+}
+
+/// Authorities crash and restart one after the other, recovering their state from storage.
+/// One authority crashes for good at the end.
+#[rstest]
+fn crash_and_restart(#[values(0, 1, 2)] seed: u64) {
+    telemetry_subscribers::init_for_testing();
+    let mut config = SimulationConfig::new(4, seed);
+    for (i, authority) in (0..3).map(AuthorityIndex::new_for_test).enumerate() {
+        let at_ms = 2_000 + 4_000 * i as u64;
+        config = config.with_fault(Fault::Crash {
+            authority,
+            at_ms,
+            restart_ms: Some(at_ms + 2_000),
+        });
+    }
+    config = config.with_fault(Fault::Crash {
+        authority: AuthorityIndex::new_for_test(3),
+        at_ms: 15_000,
+        restart_ms: None,
+    });
+    let simulator = run(config);
+    simulator.check_liveness(10);
+}
+
+/// A Byzantine authority sends conflicting blocks to its peers.
+#[rstest]
+fn equivocating_authority(#[values(4, 7)] committee_size: usize, #[values(0, 1, 2)] seed: u64) {
+    telemetry_subscribers::init_for_testing();
+    let config = SimulationConfig::new(committee_size, seed).with_fault(Fault::Equivocate {
+        authority: AuthorityIndex::new_for_test(1),
+    });
+    let simulator = run(config);
+    simulator.check_liveness(5);
+}
+
+/// A Byzantine authority only sends its blocks to one peer, and does not serve them.
+#[rstest]
+fn withholding_authority(#[values(0, 1, 2)] seed: u64) {
+    telemetry_subscribers::init_for_testing();
+    let config = SimulationConfig::new(4, seed).with_fault(Fault::Withhold {
+        authority: AuthorityIndex::new_for_test(2),
+        recipients: authorities(&[0]),
+    });
+    let simulator = run(config);
+    simulator.check_liveness(5);
+}
+
+/// Byzantine authorities, crashes and partitions in the same run, tolerated by a committee of 7.
+#[rstest]
+fn adversarial_schedule(#[values(0, 1, 2, 3, 4)] seed: u64) {
+    telemetry_subscribers::init_for_testing();
+    let config = SimulationConfig::new(7, seed)
+        .with_latency(10, 400)
+        .with_fault(Fault::Equivocate {
+            authority: AuthorityIndex::new_for_test(5),
+        })
+        .with_fault(Fault::Crash {
+            authority: AuthorityIndex::new_for_test(2),
+            at_ms: 3_000,
+            restart_ms: Some(9_000),
+        })
+        .with_fault(Fault::Partition {
+            authorities: authorities(&[0, 6]),
+            start_ms: 10_000,
+            end_ms: 14_000,
+        });
+    let simulator = run(config);
+    simulator.check_liveness(3);
+}
@@ -10,6 +10,7 @@ use crate::{
     commit::LeaderStatus,
     context::Context,
     dag_state::DagState,
+    leader_schedule::LeaderSchedule,
     storage::mem_store::MemStore,
     test_dag::{build_dag, build_dag_layer},
     universal_committer::universal_committer_builder::UniversalCommitterBuilder,
@@ -27,7 +28,12 @@ fn direct_commit() {
     )));
 
     // Create committer without pipelining and only 1 leader per round
-    let committer = UniversalCommitterBuilder::new(context.clone(), dag_state.clone()).build();
+    let committer = UniversalCommitterBuilder::new(
+        context.clone(),
+        LeaderSchedule::new(context.clone()),
+        dag_state.clone(),
+    )
+    .build();
 
     // Build fully connected dag with empty blocks adding up to voting round of
     // wave 2 to the dag so that we have 2 completed waves and one incomplete wave.
@@ -69,7 +75,12 @@ fn indirect_commit() {
     )));
 
     // Create committer without pipelining and only 1 leader per round
-    let committer = UniversalCommitterBuilder::new(context.clone(), dag_state.clone()).build();
+    let committer = UniversalCommitterBuilder::new(
+        context.clone(),
+        LeaderSchedule::new(context.clone()),
+        dag_state.clone(),
+    )
+    .build();
 
     // Add enough blocks to reach the leader round of wave 1.
     let leader_round_wave_1 = committer.committers[0].leader_round(1);
@@ -12,7 +12,7 @@ use tracing::error;
 /// The maximum number of transactions pending to the queue to be pulled for block proposal
 const MAX_PENDING_TRANSACTIONS: usize = 2_000;
 
-const MAX_CONSUMED_TRANSACTIONS_PER_REQUEST: u64 = 5_000;
+pub(crate) const MAX_CONSUMED_TRANSACTIONS_PER_REQUEST: u64 = 5_000;
 
 /// The TransactionsConsumer is responsible for fetching the next transactions to be included for the block proposals.
 /// The transactions are submitted to a channel which is shared between the TransactionsConsumer and the TransactionsClient
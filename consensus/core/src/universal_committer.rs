@@ -40,11 +40,19 @@ impl UniversalCommitter {
 
         // Try to decide as many leaders as possible, starting with the highest round.
         let mut leaders = VecDeque::new();
+        // With a single leader per round, no other leader of the round of the last decided leader
+        // can be decided. The leader elected for that round may also differ from the last decided
+        // one, when the leader schedule has been updated since.
+        let lowest_round = if self.committers.len() == 1 {
+            last_decided.round + 1
+        } else {
+            last_decided.round
+        };
         // try to commit a leader up to the highest_accepted_round - 2. There is no
         // reason to try and iterate on higher rounds as in order to make a direct
         // decision for a leader at round R we need blocks from round R+2 to figure
         // out that enough certificates and support exist to commit a leader.
-        'outer: for round in (last_decided.round..=highest_accepted_round.saturating_sub(2)).rev() {
+        'outer: for round in (lowest_round..=highest_accepted_round.saturating_sub(2)).rev() {
             for committer in self.committers.iter().rev() {
                 // Skip committers that don't have a leader for this round.
                 let Some(leader) = committer.elect_leader(round) else {
@@ -126,7 +134,7 @@ impl UniversalCommitter {
 /// A builder for a universal committer. By default, the builder creates a single
 /// base committer, that is, a single leader and no pipeline.
 #[allow(unused)]
-mod universal_committer_builder {
+pub(crate) mod universal_committer_builder {
     use super::*;
 
     use crate::{
@@ -144,8 +152,11 @@ mod universal_committer_builder {
     }
 
     impl UniversalCommitterBuilder {
-        pub fn new(context: Arc<Context>, dag_state: Arc<RwLock<DagState>>) -> Self {
-            let leader_schedule = LeaderSchedule::new(context.clone());
+        pub fn new(
+            context: Arc<Context>,
+            leader_schedule: LeaderSchedule,
+            dag_state: Arc<RwLock<DagState>>,
+        ) -> Self {
             Self {
                 context,
                 leader_schedule,
@@ -13,7 +13,7 @@ use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use std::collections::{BTreeMap, BTreeSet};
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -68,6 +68,10 @@ pub struct NodeConfig {
     pub metrics_address: SocketAddr,
     #[serde(default = "default_admin_interface_port")]
     pub admin_interface_port: u16,
+    /// File with the bearer token that requests to the admin endpoints that inspect or change the
+    /// node's runtime state must present. These endpoints are disabled if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_interface_auth_token_path: Option<PathBuf>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub consensus_config: Option<ConsensusConfig>,
@@ -132,6 +136,16 @@ pub struct NodeConfig {
     #[serde(default)]
     pub transaction_deny_config: TransactionDenyConfig,
 
+    /// If set, the transaction deny config is read from this file instead of
+    /// `transaction-deny-config`, and reloaded whenever the file changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_deny_config_path: Option<PathBuf>,
+
+    /// File that every change of the transaction deny config made while the node is running is
+    /// appended to. Changes are logged with the `transaction_deny_audit` target in any case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_deny_audit_log_path: Option<PathBuf>,
+
     #[serde(default)]
     pub certificate_deny_config: CertificateDenyConfig,
 
@@ -165,6 +179,9 @@ pub struct NodeConfig {
     #[serde(default = "default_overload_threshold_config")]
     pub overload_threshold_config: OverloadThresholdConfig,
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admission_control_config: Option<AdmissionControlConfig>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub run_with_range: Option<RunWithRange>,
 }
@@ -294,6 +311,12 @@ impl NodeConfig {
         self.db_path.join("db_checkpoints")
     }
 
+    /// Checkpoints of the dbs taken through the admin interface, kept apart from the end of epoch
+    /// checkpoints so that they are not uploaded.
+    pub fn manual_db_checkpoint_path(&self) -> PathBuf {
+        self.db_path.join("manual_db_checkpoints")
+    }
+
     pub fn archive_path(&self) -> PathBuf {
         self.db_path.join("archive")
     }
@@ -643,6 +666,10 @@ pub struct DBCheckpointConfig {
     pub perform_index_db_checkpoints_at_epoch_end: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prune_and_compact_before_upload: Option<bool>,
+    /// Upload only the files which are not in the previous db checkpoint, along with a manifest
+    /// listing the files of each db checkpoint. Defaults to false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub incremental_upload: Option<bool>,
 }
 
 #[derive(Debug, Clone)]
@@ -756,6 +783,64 @@ fn default_overload_threshold_config() -> OverloadThresholdConfig {
     OverloadThresholdConfig::default()
 }
 
+/// Configuration for per-client admission control in the validator gRPC server.
+/// Unlike `OverloadThresholdConfig`, which sheds load globally, admission control limits
+/// the rate of requests from each transaction sender, client IP and mutated shared object,
+/// so that a single spammy client cannot degrade the validator for everyone else.
+/// A limit that is not set is not enforced.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AdmissionControlConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender_limit: Option<TokenBucketConfig>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ip_limit: Option<TokenBucketConfig>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shared_object_limit: Option<TokenBucketConfig>,
+
+    // Client IPs that are not subject to `client_ip_limit`, e.g. known RPC providers that
+    // forward the traffic of many users.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub allowlisted_client_ips: BTreeSet<IpAddr>,
+
+    // Senders that are not subject to `sender_limit`.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub allowlisted_senders: BTreeSet<MgoAddress>,
+
+    // The maximum number of keys tracked for each limit. The least recently seen keys are
+    // evicted first, which resets their buckets. Must be positive.
+    #[serde(default = "default_admission_control_max_tracked_keys")]
+    pub max_tracked_keys: NonZeroUsize,
+}
+
+/// A token bucket holding at most `capacity` tokens and refilled by `refill_rate_per_sec`
+/// tokens every second. Each admitted request consumes one token.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TokenBucketConfig {
+    pub capacity: u64,
+    pub refill_rate_per_sec: u64,
+}
+
+fn default_admission_control_max_tracked_keys() -> NonZeroUsize {
+    NonZeroUsize::new(100_000).unwrap()
+}
+
+impl Default for AdmissionControlConfig {
+    fn default() -> Self {
+        Self {
+            sender_limit: None,
+            client_ip_limit: None,
+            shared_object_limit: None,
+            allowlisted_client_ips: BTreeSet::new(),
+            allowlisted_senders: BTreeSet::new(),
+            max_tracked_keys: default_admission_control_max_tracked_keys(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Eq)]
 pub struct Genesis {
     #[serde(flatten)]
@@ -952,7 +1037,7 @@ mod tests {
     use mgo_keys::keypair_file::{write_authority_keypair_to_file, write_keypair_to_file};
     use mgo_types::crypto::{get_key_pair_from_rng, AuthorityKeyPair, NetworkKeyPair, MgoKeyPair};
 
-    use super::Genesis;
+    use super::{AdmissionControlConfig, Genesis};
     use crate::NodeConfig;
 
     #[test]
@@ -965,6 +1050,20 @@ mod tests {
         assert_eq!(g, loaded_genesis);
     }
 
+    #[test]
+    fn admission_control_config_max_tracked_keys() {
+        let config: AdmissionControlConfig =
+            serde_yaml::from_str("sender-limit:\n  capacity: 10\n  refill-rate-per-sec: 1\n")
+                .unwrap();
+        assert_eq!(config.max_tracked_keys.get(), 100_000);
+
+        let config: AdmissionControlConfig =
+            serde_yaml::from_str("max-tracked-keys: 10\n").unwrap();
+        assert_eq!(config.max_tracked_keys.get(), 10);
+
+        assert!(serde_yaml::from_str::<AdmissionControlConfig>("max-tracked-keys: 0\n").is_err());
+    }
+
     #[test]
     fn fullnode_template() {
         const TEMPLATE: &str = include_str!("../data/fullnode-template.yaml");
@@ -1,12 +1,17 @@
 // Copyright (c) MangoNet Labs Ltd.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::{Debug, Display};
 
+use move_core_types::language_storage::{StructTag, TypeTag};
 use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
 use mgo_types::base_types::{ObjectID, MgoAddress};
 
+use crate::Config;
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct TransactionDenyConfig {
@@ -71,6 +76,11 @@ pub struct TransactionDenyConfig {
     /// A list of disabled OAuth providers for zkLogin
     #[serde(default)]
     zklogin_disabled_providers: HashSet<String>,
+
+    /// Rules denying transactions by what they do, rather than by the objects, packages or
+    /// addresses they use.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    rules: Vec<TransactionDenyRule>,
     // TODO: We could consider add a deny list for types that we want to disable public transfer.
     // TODO: We could also consider disable more types of commands, such as transfer, split and etc.
 }
@@ -118,6 +128,224 @@ impl TransactionDenyConfig {
     pub fn zklogin_disabled_providers(&self) -> &HashSet<String> {
         &self.zklogin_disabled_providers
     }
+
+    pub fn rules(&self) -> &[TransactionDenyRule] {
+        &self.rules
+    }
+
+    /// Returns the rules that have not expired at `now_ms`.
+    pub fn active_rules(&self, now_ms: u64) -> impl Iterator<Item = &TransactionDenyPredicate> {
+        self.rules
+            .iter()
+            .filter(move |rule| !rule.is_expired(now_ms))
+            .map(|rule| &rule.predicate)
+    }
+
+    /// Describes every change made by replacing this config with `new`, one line per change.
+    /// Used to record rule changes in the audit log.
+    pub fn diff(&self, new: &Self) -> Vec<String> {
+        let mut changes = vec![];
+        diff_list(
+            "object deny list",
+            &self.object_deny_list,
+            &new.object_deny_list,
+            &mut changes,
+        );
+        diff_list(
+            "package deny list",
+            &self.package_deny_list,
+            &new.package_deny_list,
+            &mut changes,
+        );
+        diff_list(
+            "address deny list",
+            &self.address_deny_list,
+            &new.address_deny_list,
+            &mut changes,
+        );
+        for (name, old_value, new_value) in [
+            (
+                "package-publish-disabled",
+                self.package_publish_disabled,
+                new.package_publish_disabled,
+            ),
+            (
+                "package-upgrade-disabled",
+                self.package_upgrade_disabled,
+                new.package_upgrade_disabled,
+            ),
+            (
+                "shared-object-disabled",
+                self.shared_object_disabled,
+                new.shared_object_disabled,
+            ),
+            (
+                "user-transaction-disabled",
+                self.user_transaction_disabled,
+                new.user_transaction_disabled,
+            ),
+            (
+                "receiving-objects-disabled",
+                self.receiving_objects_disabled,
+                new.receiving_objects_disabled,
+            ),
+            (
+                "zklogin-sig-disabled",
+                self.zklogin_sig_disabled,
+                new.zklogin_sig_disabled,
+            ),
+        ] {
+            if old_value != new_value {
+                changes.push(format!("set {name} from {old_value} to {new_value}"));
+            }
+        }
+        diff_list(
+            "zklogin disabled providers",
+            &self.zklogin_disabled_providers,
+            &new.zklogin_disabled_providers,
+            &mut changes,
+        );
+        for rule in &self.rules {
+            if !new.rules.contains(rule) {
+                changes.push(format!("removed rule {rule}"));
+            }
+        }
+        for rule in &new.rules {
+            if !self.rules.contains(rule) {
+                changes.push(format!("added rule {rule}"));
+            }
+        }
+        changes
+    }
+}
+
+impl Config for TransactionDenyConfig {}
+
+fn diff_list<'a, T: Ord + Debug + 'a>(
+    name: &str,
+    old: impl IntoIterator<Item = &'a T>,
+    new: impl IntoIterator<Item = &'a T>,
+    changes: &mut Vec<String>,
+) {
+    let old: BTreeSet<_> = old.into_iter().collect();
+    let new: BTreeSet<_> = new.into_iter().collect();
+    for removed in old.difference(&new) {
+        changes.push(format!("removed {removed:?} from {name}"));
+    }
+    for added in new.difference(&old) {
+        changes.push(format!("added {added:?} to {name}"));
+    }
+}
+
+/// A predicate denying matching transactions, with an optional expiry.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TransactionDenyRule {
+    #[serde(flatten)]
+    pub predicate: TransactionDenyPredicate,
+
+    /// Unix timestamp in milliseconds from which the rule no longer applies. Rules without an
+    /// expiry apply until they are removed from the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at_ms: Option<u64>,
+}
+
+impl TransactionDenyRule {
+    pub fn new(predicate: TransactionDenyPredicate) -> Self {
+        Self {
+            predicate,
+            expires_at_ms: None,
+        }
+    }
+
+    pub fn expiring_at_ms(mut self, expires_at_ms: u64) -> Self {
+        self.expires_at_ms = Some(expires_at_ms);
+        self
+    }
+
+    pub fn is_expired(&self, now_ms: u64) -> bool {
+        self.expires_at_ms
+            .is_some_and(|expires_at_ms| now_ms >= expires_at_ms)
+    }
+}
+
+impl Display for TransactionDenyRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.predicate)?;
+        if let Some(expires_at_ms) = self.expires_at_ms {
+            write!(f, " expiring at {expires_at_ms}ms")?;
+        }
+        Ok(())
+    }
+}
+
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum TransactionDenyPredicate {
+    /// Denies transactions calling the Move function `package::module::function` directly.
+    /// Like the package deny list, only the given package ID is matched, not other versions
+    /// of the package.
+    MoveFunction {
+        package: ObjectID,
+        module: String,
+        function: String,
+    },
+
+    /// Denies transactions with an input object of this Move type, e.g. `0x2::coin::Coin`.
+    /// A type given without type parameters matches all of its instantiations.
+    InputObjectType {
+        #[serde(rename = "object-type")]
+        #[serde_as(as = "DisplayFromStr")]
+        object_type: StructTag,
+    },
+
+    /// Denies transactions transferring more than `max-amount` of coins of `coin-type` in total,
+    /// e.g. `0x2::mgo::MGO`. Only amounts known before execution are counted: coins split off
+    /// with pure amounts, and whole input coins (including the gas coin, and the coins merged
+    /// into them) transferred directly. A coin passed to a Move call, by value or by reference,
+    /// or put into a vector counts as transferring its whole value. Transactions splitting off
+    /// amounts that are not pure `u64` inputs are denied.
+    CoinTransferAmount {
+        #[serde(rename = "coin-type")]
+        #[serde_as(as = "DisplayFromStr")]
+        coin_type: TypeTag,
+        #[serde(rename = "max-amount")]
+        max_amount: u64,
+    },
+}
+
+impl TransactionDenyPredicate {
+    /// Whether an object of type `object_type` matches an `InputObjectType` predicate for
+    /// `denied_type`.
+    pub fn object_type_matches(denied_type: &StructTag, object_type: &StructTag) -> bool {
+        if denied_type.type_params.is_empty() {
+            denied_type.address == object_type.address
+                && denied_type.module == object_type.module
+                && denied_type.name == object_type.name
+        } else {
+            denied_type == object_type
+        }
+    }
+}
+
+impl Display for TransactionDenyPredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransactionDenyPredicate::MoveFunction {
+                package,
+                module,
+                function,
+            } => write!(f, "deny calls to {package}::{module}::{function}"),
+            TransactionDenyPredicate::InputObjectType { object_type } => {
+                write!(f, "deny input objects of type {object_type}")
+            }
+            TransactionDenyPredicate::CoinTransferAmount {
+                coin_type,
+                max_amount,
+            } => write!(f, "deny transfers of more than {max_amount} {coin_type}"),
+        }
+    }
 }
 
 #[derive(Default)]
@@ -183,4 +411,104 @@ impl TransactionDenyConfigBuilder {
         self.config.zklogin_disabled_providers.insert(provider);
         self
     }
+
+    pub fn add_rule(mut self, rule: TransactionDenyRule) -> Self {
+        self.config.rules.push(rule);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"
+rules:
+  - kind: move-function
+    package: "0x3"
+    module: mgo_system
+    function: request_add_stake
+  - kind: input-object-type
+    object-type: "0x2::coin::Coin"
+    expires-at-ms: 1000
+  - kind: coin-transfer-amount
+    coin-type: "0x2::mgo::MGO"
+    max-amount: 100
+"#;
+
+    #[test]
+    fn parse_rules() {
+        let config: TransactionDenyConfig = serde_yaml::from_str(RULES).unwrap();
+        assert_eq!(config.rules().len(), 3);
+        assert_eq!(
+            config.rules()[0].predicate.to_string(),
+            format!(
+                "deny calls to {}::mgo_system::request_add_stake",
+                ObjectID::from_single_byte(3)
+            )
+        );
+        assert_eq!(config.rules()[1].expires_at_ms, Some(1000));
+        assert_eq!(
+            config.rules()[2].predicate,
+            TransactionDenyPredicate::CoinTransferAmount {
+                coin_type: "0x2::mgo::MGO".parse().unwrap(),
+                max_amount: 100,
+            }
+        );
+        assert_eq!(config.active_rules(999).count(), 3);
+        assert_eq!(config.active_rules(1000).count(), 2);
+
+        let serialized = serde_yaml::to_string(&config).unwrap();
+        let deserialized: TransactionDenyConfig = serde_yaml::from_str(&serialized).unwrap();
+        assert_eq!(config.rules(), deserialized.rules());
+
+        assert!(serde_yaml::from_str::<TransactionDenyConfig>(
+            "rules: [{kind: input-object-type, object-type: not-a-type}]"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn object_type_matches() {
+        let coin: StructTag = "0x2::coin::Coin".parse().unwrap();
+        let mgo_coin: StructTag = "0x2::coin::Coin<0x2::mgo::MGO>".parse().unwrap();
+        let other_coin: StructTag = "0x2::coin::Coin<0x42::foo::FOO>".parse().unwrap();
+        assert!(TransactionDenyPredicate::object_type_matches(
+            &coin, &mgo_coin
+        ));
+        assert!(TransactionDenyPredicate::object_type_matches(
+            &mgo_coin, &mgo_coin
+        ));
+        assert!(!TransactionDenyPredicate::object_type_matches(
+            &mgo_coin,
+            &other_coin
+        ));
+    }
+
+    #[test]
+    fn diff() {
+        let object = ObjectID::from_single_byte(1);
+        let rule = TransactionDenyRule::new(TransactionDenyPredicate::InputObjectType {
+            object_type: "0x2::coin::Coin".parse().unwrap(),
+        });
+        let old = TransactionDenyConfigBuilder::new()
+            .add_denied_object(object)
+            .add_rule(rule.clone())
+            .build();
+        assert!(old.diff(&old.clone()).is_empty());
+
+        let new = TransactionDenyConfigBuilder::new()
+            .disable_package_publish()
+            .add_rule(rule.clone().expiring_at_ms(1000))
+            .build();
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                format!("removed {object:?} from object deny list"),
+                "set package-publish-disabled from false to true".to_string(),
+                format!("removed rule {rule}"),
+                format!("added rule {rule} expiring at 1000ms"),
+            ]
+        );
+    }
 }
@@ -12,6 +12,7 @@ use std::sync::Arc;
 use mgo_core::test_utils::{make_cert_with_large_committee, make_dummy_tx};
 use mgo_types::committee::Committee;
 use mgo_types::crypto::{get_key_pair, AccountKeyPair, AuthorityKeyPair};
+use mgo_types::passkey_authenticator::PasskeyRelyingParties;
 use mgo_types::transaction::CertifiedTransaction;
 
 use fastcrypto_zkp::bn254::zk_login_api::ZkLoginEnv;
@@ -80,6 +81,8 @@ fn async_verifier_bench(c: &mut Criterion) {
                         ZkLoginEnv::Test,
                         true,
                         true,
+                        None,
+                        PasskeyRelyingParties::default(),
                     ));
 
                     b.iter(|| {
@@ -0,0 +1,355 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use lru::LruCache;
+use mgo_config::node::{AdmissionControlConfig, TokenBucketConfig};
+use mgo_types::base_types::{MgoAddress, ObjectID};
+use mgo_types::error::MgoError;
+use mgo_types::transaction::{SenderSignedData, TransactionDataAPI};
+use parking_lot::Mutex;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+
+/// What a request to the validator is charged to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AdmissionKey {
+    Sender(MgoAddress),
+    ClientIp(IpAddr),
+    SharedObject(ObjectID),
+}
+
+impl AdmissionKey {
+    /// Label used in metrics.
+    pub fn key_type(&self) -> &'static str {
+        match self {
+            AdmissionKey::Sender(_) => "sender",
+            AdmissionKey::ClientIp(_) => "client_ip",
+            AdmissionKey::SharedObject(_) => "shared_object",
+        }
+    }
+}
+
+impl fmt::Display for AdmissionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdmissionKey::Sender(sender) => write!(f, "sender {sender}"),
+            AdmissionKey::ClientIp(ip) => write!(f, "client ip {ip}"),
+            AdmissionKey::SharedObject(object_id) => write!(f, "shared object {object_id}"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AdmissionRejection {
+    pub key: AdmissionKey,
+    /// Time until the bucket of `key` has a token again.
+    pub retry_after: Duration,
+}
+
+impl From<AdmissionRejection> for MgoError {
+    fn from(rejection: AdmissionRejection) -> Self {
+        MgoError::TooManyRequests {
+            key: rejection.key.to_string(),
+            retry_after_ms: rejection
+                .retry_after
+                .as_millis()
+                .try_into()
+                .unwrap_or(u64::MAX),
+        }
+    }
+}
+
+/// Token bucket admission control for the validator gRPC server, keyed by transaction sender,
+/// client IP and mutated shared object. Each limit is only enforced when configured.
+///
+/// A request is charged to its keys in turn, and is rejected by the first key without tokens.
+/// Tokens already consumed from the keys checked before are not returned, so a client that keeps
+/// being rejected does not regain capacity faster than its buckets refill.
+///
+/// The sender and shared objects of a request are taken from its transaction data, which anyone
+/// can fill in. They must only be charged once the signatures are verified, so that forged
+/// requests cannot use up the limits of another sender or of a shared object.
+pub struct AdmissionController {
+    sender_limiter: Option<RateLimiter<MgoAddress>>,
+    client_ip_limiter: Option<RateLimiter<IpAddr>>,
+    shared_object_limiter: Option<RateLimiter<ObjectID>>,
+    allowlisted_client_ips: BTreeSet<IpAddr>,
+    allowlisted_senders: BTreeSet<MgoAddress>,
+}
+
+impl AdmissionController {
+    pub fn new(config: AdmissionControlConfig) -> Self {
+        let max_tracked_keys = config.max_tracked_keys;
+        Self {
+            sender_limiter: config
+                .sender_limit
+                .map(|limit| RateLimiter::new(limit, max_tracked_keys)),
+            client_ip_limiter: config
+                .client_ip_limit
+                .map(|limit| RateLimiter::new(limit, max_tracked_keys)),
+            shared_object_limiter: config
+                .shared_object_limit
+                .map(|limit| RateLimiter::new(limit, max_tracked_keys)),
+            allowlisted_client_ips: config.allowlisted_client_ips,
+            allowlisted_senders: config.allowlisted_senders,
+        }
+    }
+
+    /// Admits or rejects a transaction received from `client_ip`, before its signatures are
+    /// verified. Certificates are usually submitted on behalf of their sender by fullnodes and
+    /// other drivers of many users, so they are not charged to the client IP they are received
+    /// from.
+    pub fn check_client_ip(&self, client_ip: Option<IpAddr>) -> Result<(), AdmissionRejection> {
+        self.check_client_ip_at(client_ip, Instant::now())
+    }
+
+    /// Admits or rejects a transaction or certificate with `data`, whose signatures have been
+    /// verified. Only mutably accessed shared objects are limited, since read-only access to
+    /// objects like the clock does not contend.
+    pub fn check_transaction(&self, data: &SenderSignedData) -> Result<(), AdmissionRejection> {
+        let transaction_data = data.transaction_data();
+        let shared_objects = transaction_data
+            .shared_input_objects()
+            .into_iter()
+            .filter(|object| object.mutable)
+            .map(|object| object.id());
+        self.check_transaction_at(transaction_data.sender(), shared_objects, Instant::now())
+    }
+
+    fn check_client_ip_at(
+        &self,
+        client_ip: Option<IpAddr>,
+        now: Instant,
+    ) -> Result<(), AdmissionRejection> {
+        if let (Some(limiter), Some(ip)) = (&self.client_ip_limiter, client_ip) {
+            if !self.allowlisted_client_ips.contains(&ip) {
+                limiter
+                    .try_acquire(ip, now)
+                    .map_err(|retry_after| AdmissionRejection {
+                        key: AdmissionKey::ClientIp(ip),
+                        retry_after,
+                    })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_transaction_at(
+        &self,
+        sender: MgoAddress,
+        shared_objects: impl IntoIterator<Item = ObjectID>,
+        now: Instant,
+    ) -> Result<(), AdmissionRejection> {
+        if let Some(limiter) = &self.sender_limiter {
+            if !self.allowlisted_senders.contains(&sender) {
+                limiter
+                    .try_acquire(sender, now)
+                    .map_err(|retry_after| AdmissionRejection {
+                        key: AdmissionKey::Sender(sender),
+                        retry_after,
+                    })?;
+            }
+        }
+        if let Some(limiter) = &self.shared_object_limiter {
+            for object_id in shared_objects {
+                limiter
+                    .try_acquire(object_id, now)
+                    .map_err(|retry_after| AdmissionRejection {
+                        key: AdmissionKey::SharedObject(object_id),
+                        retry_after,
+                    })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Token buckets for the most recently seen keys. Evicted keys start again with a full bucket.
+struct RateLimiter<K: Hash + Eq + Copy> {
+    config: TokenBucketConfig,
+    buckets: Mutex<LruCache<K, TokenBucket>>,
+}
+
+impl<K: Hash + Eq + Copy> RateLimiter<K> {
+    fn new(config: TokenBucketConfig, max_tracked_keys: NonZeroUsize) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(LruCache::new(max_tracked_keys)),
+        }
+    }
+
+    /// Consumes a token from the bucket of `key`. Returns the time until a token is available
+    /// if the bucket is empty.
+    fn try_acquire(&self, key: K, now: Instant) -> Result<(), Duration> {
+        let capacity = self.config.capacity as f64;
+        let refill_rate = self.config.refill_rate_per_sec as f64;
+
+        let mut buckets = self.buckets.lock();
+        if !buckets.contains(&key) {
+            buckets.put(
+                key,
+                TokenBucket {
+                    tokens: capacity,
+                    last_refill: now,
+                },
+            );
+        }
+        let bucket = buckets.get_mut(&key).unwrap();
+
+        let elapsed = now.saturating_duration_since(bucket.last_refill);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * refill_rate).min(capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else if refill_rate > 0.0 {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / refill_rate))
+        } else {
+            Err(Duration::MAX)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(capacity: u64, refill_rate_per_sec: u64) -> Option<TokenBucketConfig> {
+        Some(TokenBucketConfig {
+            capacity,
+            refill_rate_per_sec,
+        })
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let limiter = RateLimiter::new(limit(2, 2).unwrap(), NonZeroUsize::new(10).unwrap());
+        let start = Instant::now();
+
+        // The bucket starts full.
+        assert_eq!(limiter.try_acquire(1, start), Ok(()));
+        assert_eq!(limiter.try_acquire(1, start), Ok(()));
+        assert_eq!(
+            limiter.try_acquire(1, start),
+            Err(Duration::from_millis(500))
+        );
+        // Other keys have their own bucket.
+        assert_eq!(limiter.try_acquire(2, start), Ok(()));
+
+        // A token is refilled every 500ms, up to the capacity.
+        let now = start + Duration::from_millis(250);
+        assert_eq!(limiter.try_acquire(1, now), Err(Duration::from_millis(250)));
+        let now = start + Duration::from_millis(500);
+        assert_eq!(limiter.try_acquire(1, now), Ok(()));
+        let now = start + Duration::from_secs(10);
+        assert_eq!(limiter.try_acquire(1, now), Ok(()));
+        assert_eq!(limiter.try_acquire(1, now), Ok(()));
+        assert!(limiter.try_acquire(1, now).is_err());
+    }
+
+    #[test]
+    fn test_token_bucket_eviction() {
+        let limiter = RateLimiter::new(limit(1, 0).unwrap(), NonZeroUsize::new(2).unwrap());
+        let now = Instant::now();
+
+        assert_eq!(limiter.try_acquire(1, now), Ok(()));
+        assert_eq!(limiter.try_acquire(1, now), Err(Duration::MAX));
+        assert_eq!(limiter.try_acquire(2, now), Ok(()));
+        assert_eq!(limiter.try_acquire(3, now), Ok(()));
+        // Key 1 was the least recently seen and got evicted, so its bucket is full again.
+        assert_eq!(limiter.try_acquire(1, now), Ok(()));
+    }
+
+    #[test]
+    fn test_admission_controller() {
+        let (ip, allowlisted_ip): (IpAddr, IpAddr) =
+            ("10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap());
+        let (sender, other_sender, allowlisted_sender) = (
+            MgoAddress::random_for_testing_only(),
+            MgoAddress::random_for_testing_only(),
+            MgoAddress::random_for_testing_only(),
+        );
+        let (object, other_object) = (ObjectID::random(), ObjectID::random());
+        let controller = AdmissionController::new(AdmissionControlConfig {
+            sender_limit: limit(1, 1),
+            client_ip_limit: limit(2, 1),
+            shared_object_limit: limit(1, 1),
+            allowlisted_client_ips: BTreeSet::from([allowlisted_ip]),
+            allowlisted_senders: BTreeSet::from([allowlisted_sender]),
+            ..Default::default()
+        });
+        let now = Instant::now();
+        let rejected_key = |result: Result<(), AdmissionRejection>| result.unwrap_err().key;
+
+        assert!(controller
+            .check_transaction_at(sender, [object], now)
+            .is_ok());
+        assert_eq!(
+            rejected_key(controller.check_transaction_at(sender, [], now)),
+            AdmissionKey::Sender(sender)
+        );
+        assert_eq!(
+            rejected_key(controller.check_transaction_at(other_sender, [object], now)),
+            AdmissionKey::SharedObject(object)
+        );
+
+        assert!(controller.check_client_ip_at(Some(ip), now).is_ok());
+        assert!(controller.check_client_ip_at(Some(ip), now).is_ok());
+        assert_eq!(
+            rejected_key(controller.check_client_ip_at(Some(ip), now)),
+            AdmissionKey::ClientIp(ip)
+        );
+
+        // Allowlisted client IPs and senders are only subject to the other limits.
+        for _ in 0..10 {
+            assert!(controller
+                .check_client_ip_at(Some(allowlisted_ip), now)
+                .is_ok());
+            assert!(controller
+                .check_transaction_at(allowlisted_sender, [], now)
+                .is_ok());
+        }
+        assert!(controller
+            .check_transaction_at(allowlisted_sender, [other_object], now)
+            .is_ok());
+        assert_eq!(
+            rejected_key(controller.check_transaction_at(allowlisted_sender, [other_object], now)),
+            AdmissionKey::SharedObject(other_object)
+        );
+
+        // Requests without a known client IP are not limited by IP.
+        assert!(controller.check_client_ip_at(None, now).is_ok());
+        let now = now + Duration::from_secs(1);
+        assert!(controller
+            .check_transaction_at(sender, [object], now)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_rejection_error() {
+        let object = ObjectID::random();
+        let error: MgoError = AdmissionRejection {
+            key: AdmissionKey::SharedObject(object),
+            retry_after: Duration::from_millis(1500),
+        }
+        .into();
+        assert_eq!(
+            error,
+            MgoError::TooManyRequests {
+                key: format!("shared object {object}"),
+                retry_after_ms: 1500,
+            }
+        );
+        assert_eq!(error.is_retryable(), (true, true));
+    }
+}
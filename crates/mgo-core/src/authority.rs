@@ -65,9 +65,10 @@ use mgo_config::node::{
 use mgo_config::transaction_deny_config::TransactionDenyConfig;
 use mgo_framework::{BuiltInFramework, SystemPackage};
 use mgo_json_rpc_types::{
-    DevInspectResults, DryRunTransactionBlockResponse, EventFilter, MgoEvent, MgoMoveValue,
-    MgoObjectDataFilter, MgoTransactionBlockData, MgoTransactionBlockEffects,
-    MgoTransactionBlockEvents, TransactionFilter,
+    DevInspectResults, DryRunTransactionBlockResponse, EffectsWithInput, EventFilter, Filter,
+    MgoAppliedStateOverrides, MgoEvent, MgoMoveValue, MgoObjectDataFilter, MgoTransactionBlockData,
+    MgoTransactionBlockEffects, MgoTransactionBlockEvents, SubscriptionCursor, SubscriptionGap,
+    SubscriptionItem, TransactionFilter,
 };
 use mgo_macros::{fail_point, fail_point_async, fail_point_if};
 use mgo_protocol_config::{ProtocolConfig, SupportedProtocolVersions};
@@ -89,6 +90,7 @@ use mgo_types::effects::{
 use mgo_types::error::{ExecutionError, UserInputError};
 use mgo_types::event::{Event, EventID};
 use mgo_types::executable_transaction::VerifiedExecutableTransaction;
+use mgo_types::execution_trace::ExecutionTracer;
 use mgo_types::gas::{GasCostSummary, MgoGasStatus};
 use mgo_types::inner_temporary_store::{
     InnerTemporaryStore, ObjectMap, TemporaryModuleResolver, TemporaryPackageStore, TxCoins,
@@ -108,8 +110,10 @@ use mgo_types::messages_grpc::{
 };
 use mgo_types::metrics::{BytecodeVerifierMetrics, LimitsMetrics};
 use mgo_types::object::{MoveObject, Owner, PastObjectRead, OBJECT_START_VERSION};
+use mgo_types::state_override::{StateOverrideStore, StateOverrides};
 use mgo_types::storage::{
-    BackingPackageStore, BackingStore, ObjectKey, ObjectOrTombstone, ObjectStore, WriteKind,
+    BackingPackageStore, BackingStore, ObjectKey, ObjectOrTombstone, ObjectStore, PackageObject,
+    WriteKind,
 };
 use mgo_types::mgo_system_state::epoch_start_mgo_system_state::EpochStartSystemStateTrait;
 use mgo_types::mgo_system_state::MgoSystemStateTrait;
@@ -122,7 +126,7 @@ use mgo_types::{
     fp_ensure,
     object::{Object, ObjectRead},
     transaction::*,
-    MGO_SYSTEM_ADDRESS,
+    MGO_CLOCK_OBJECT_ID, MGO_SYSTEM_ADDRESS,
 };
 use mgo_types::{is_system_package, TypeTag};
 use typed_store::{Map, TypedStoreError};
@@ -138,6 +142,7 @@ use crate::checkpoints::CheckpointStore;
 use crate::consensus_adapter::ConsensusAdapter;
 use crate::epoch::committee_store::CommitteeStore;
 use crate::execution_driver::execution_process;
+use crate::execution_trace::decode_execution_trace;
 use crate::in_mem_execution_cache::{ExecutionCache, ExecutionCacheRead, ExecutionCacheWrite};
 use crate::metrics::LatencyObserver;
 use crate::metrics::RateTracker;
@@ -145,7 +150,7 @@ use crate::module_cache_metrics::ResolverMetrics;
 use crate::overload_monitor::{overload_monitor, AuthorityOverloadInfo};
 use crate::stake_aggregator::StakeAggregator;
 use crate::state_accumulator::{StateAccumulator, WrappedObject};
-use crate::subscription_handler::SubscriptionHandler;
+use crate::subscription_handler::{BackfillPage, BackfillPosition, SubscriptionHandler};
 use crate::transaction_input_loader::TransactionInputLoader;
 use crate::transaction_manager::TransactionManager;
 
@@ -186,6 +191,7 @@ pub mod authority_store_pruner;
 pub mod authority_store_tables;
 pub mod authority_store_types;
 pub mod epoch_start_configuration;
+pub mod shared_object_congestion_tracker;
 pub mod test_authority_builder;
 
 pub(crate) mod authority_notify_read;
@@ -695,7 +701,7 @@ pub struct AuthorityState {
     tx_execution_shutdown: Mutex<Option<oneshot::Sender<()>>>,
 
     pub metrics: Arc<AuthorityMetrics>,
-    _pruner: AuthorityStorePruner,
+    pruner: AuthorityStorePruner,
     _authority_per_epoch_pruner: AuthorityPerEpochStorePruner,
 
     /// Take db checkpoints of different dbs
@@ -704,7 +710,8 @@ pub struct AuthorityState {
     /// Config controlling what kind of expensive safety checks to perform.
     expensive_safety_check_config: ExpensiveSafetyCheckConfig,
 
-    transaction_deny_config: TransactionDenyConfig,
+    /// Can be replaced while the node is running, see `reload_transaction_deny_config`.
+    transaction_deny_config: ArcSwap<TransactionDenyConfig>,
 
     certificate_deny_config: CertificateDenyConfig,
 
@@ -778,8 +785,16 @@ impl AuthorityState {
         tx_data.check_version_supported(epoch_store.protocol_config())?;
         tx_data.validity_check(epoch_store.protocol_config())?;
 
+        // Checks to see if the transaction has expired
+        tx_data.expiration().check(
+            epoch_store.epoch(),
+            epoch_store.get_chain_identifier(),
+            || self.input_loader.read_consensus_time_ms(),
+        )?;
+
         let input_object_kinds = tx_data.input_objects()?;
         let receiving_objects_refs = tx_data.receiving_objects();
+        let transaction_deny_config = self.transaction_deny_config.load_full();
 
         // Note: the deny checks may do redundant package loads but:
         // - they only load packages when there is an active package deny map
@@ -789,7 +804,7 @@ impl AuthorityState {
             transaction.tx_signatures(),
             &input_object_kinds,
             &receiving_objects_refs,
-            &self.transaction_deny_config,
+            &transaction_deny_config,
             self.get_backing_package_store().as_ref(),
         )?;
 
@@ -803,6 +818,12 @@ impl AuthorityState {
             )
             .await?;
 
+        mgo_transaction_checks::deny::check_input_objects_for_signing(
+            tx_data,
+            &input_objects,
+            &transaction_deny_config,
+        )?;
+
         let (_gas_status, checked_input_objects) = mgo_transaction_checks::check_transaction_input(
             epoch_store.protocol_config(),
             epoch_store.reference_gas_price(),
@@ -873,14 +894,6 @@ impl AuthorityState {
             return Err(MgoError::ValidatorHaltedAtEpochEnd);
         }
 
-        // Checks to see if the transaction has expired
-        if match &transaction.inner().data().transaction_data().expiration() {
-            TransactionExpiration::None => false,
-            TransactionExpiration::Epoch(epoch) => *epoch < epoch_store.epoch(),
-        } {
-            return Err(MgoError::TransactionExpired);
-        }
-
         let signed = self.handle_transaction_impl(transaction, epoch_store).await;
         match signed {
             Ok(s) => Ok(HandleTransactionResponse {
@@ -1135,6 +1148,35 @@ impl AuthorityState {
         .map_err(|e| MgoError::FileIOError(e.to_string()))
     }
 
+    /// Dumps the state needed to replay the transaction `tx_digest`, which must have been executed
+    /// in the current epoch, to the state debug dump directory. Returns the path of the dump.
+    pub fn dump_transaction_state(&self, tx_digest: &TransactionDigest) -> MgoResult<PathBuf> {
+        let transaction = self
+            .database
+            .get_transaction_block(tx_digest)?
+            .ok_or(MgoError::TransactionNotFound { digest: *tx_digest })?;
+        let effects = self
+            .database
+            .get_executed_effects(tx_digest)?
+            .ok_or(MgoError::TransactionNotFound { digest: *tx_digest })?;
+        let dump_dir = self
+            .debug_dump_config
+            .dump_file_directory
+            .as_ref()
+            .cloned()
+            .unwrap_or(std::env::temp_dir());
+        let epoch_store = self.load_epoch_store_one_call_per_task();
+
+        NodeStateDump::new_from_executed_transaction(
+            transaction.into_message(),
+            &effects,
+            &self.database,
+            &epoch_store,
+        )?
+        .write_to_file(&dump_dir)
+        .map_err(|e| MgoError::FileIOError(e.to_string()))
+    }
+
     #[instrument(level = "trace", skip_all)]
     pub(crate) async fn process_certificate(
         &self,
@@ -1486,6 +1528,31 @@ impl AuthorityState {
         BTreeMap<ObjectID, (ObjectRef, Object, WriteKind)>,
         TransactionEffects,
         Option<ObjectID>,
+    )> {
+        let (response, written_with_kind, effects, mock_gas, _) = self
+            .dry_exec_transaction_with_overrides(
+                transaction,
+                transaction_digest,
+                StateOverrides::default(),
+            )
+            .await?;
+        Ok((response, written_with_kind, effects, mock_gas))
+    }
+
+    /// Dry runs the transaction against the live state with `state_overrides` applied. Also
+    /// returns the overridden objects, which stand in for the live inputs when computing object
+    /// and balance changes.
+    pub async fn dry_exec_transaction_with_overrides(
+        &self,
+        transaction: TransactionData,
+        transaction_digest: TransactionDigest,
+        state_overrides: StateOverrides,
+    ) -> MgoResult<(
+        DryRunTransactionBlockResponse,
+        BTreeMap<ObjectID, (ObjectRef, Object, WriteKind)>,
+        TransactionEffects,
+        Option<ObjectID>,
+        BTreeMap<ObjectID, Object>,
     )> {
         let epoch_store = self.load_epoch_store_one_call_per_task();
         if !self.is_fullnode(&epoch_store) {
@@ -1506,30 +1573,40 @@ impl AuthorityState {
 
         let input_object_kinds = transaction.input_objects()?;
         let receiving_object_refs = transaction.receiving_objects();
+        let transaction_deny_config = self.transaction_deny_config.load_full();
 
         mgo_transaction_checks::deny::check_transaction_for_signing(
             &transaction,
             &[],
             &input_object_kinds,
             &receiving_object_refs,
-            &self.transaction_deny_config,
+            &transaction_deny_config,
             self.get_backing_package_store().as_ref(),
         )?;
 
-        let (input_objects, receiving_objects) = self
-            .input_loader
-            .read_objects_for_dry_run_exec(
-                &transaction_digest,
+        let overridden_objects = self.resolve_state_overrides(&state_overrides, epoch_store)?;
+        let (mut input_objects, receiving_objects) = self
+            .read_objects_with_overrides(
+                Some(&transaction_digest),
                 &input_object_kinds,
                 &receiving_object_refs,
+                &overridden_objects,
                 epoch_store.protocol_config(),
             )
             .await?;
 
+        mgo_transaction_checks::deny::check_input_objects_for_signing(
+            &transaction,
+            &input_objects,
+            &transaction_deny_config,
+        )?;
+
+        let sender = state_overrides.sender.unwrap_or(transaction.sender());
+        // Overridden gas coins are referred to by their overridden digests.
+        let mut gas_object_refs = overridden_gas_refs(transaction.gas(), &overridden_objects);
+
         // make a gas object if one was not provided
-        let mut gas_object_refs = transaction.gas().to_vec();
-        let ((gas_status, checked_input_objects), mock_gas) = if transaction.gas().is_empty() {
-            let sender = transaction.sender();
+        let mock_gas_object = if transaction.gas().is_empty() {
             // use a 1B mgo coin
             const MIST_TO_MGO: u64 = 1_000_000_000;
             const DRY_RUN_MGO: u64 = 1_000_000_000;
@@ -1540,50 +1617,77 @@ impl AuthorityState {
                 Owner::AddressOwner(sender),
                 TransactionDigest::genesis_marker(),
             );
-            let gas_object_ref = gas_object.compute_object_reference();
-            gas_object_refs = vec![gas_object_ref];
-            (
-                mgo_transaction_checks::check_transaction_input_with_given_gas(
+            gas_object_refs = vec![gas_object.compute_object_reference()];
+            Some(gas_object)
+        } else {
+            None
+        };
+        let mock_gas = mock_gas_object.as_ref().map(|gas_object| gas_object.id());
+
+        let (gas_status, checked_input_objects) = match (state_overrides.sender, mock_gas_object) {
+            (Some(_), mock_gas_object) => {
+                // The simulated sender need not own the transaction's owned inputs, so only the
+                // lightweight checks of dev inspect are performed.
+                if let Some(gas_object) = &mock_gas_object {
+                    input_objects.push(ObjectReadResult::new_from_gas_object(gas_object));
+                }
+                let checked_input_objects = mgo_transaction_checks::check_dev_inspect_input(
                     epoch_store.protocol_config(),
-                    epoch_store.reference_gas_price(),
-                    &transaction,
+                    transaction.kind(),
                     input_objects,
                     receiving_objects,
-                    gas_object,
-                    &self.metrics.bytecode_verifier_metrics,
-                )?,
-                Some(gas_object_id),
-            )
-        } else {
-            (
-                mgo_transaction_checks::check_transaction_input(
+                )?;
+                let gas_status = MgoGasStatus::new(
+                    transaction.gas_budget(),
+                    transaction.gas_price(),
+                    epoch_store.reference_gas_price(),
+                    epoch_store.protocol_config(),
+                )?;
+                (gas_status, checked_input_objects)
+            }
+            (None, Some(gas_object)) => {
+                mgo_transaction_checks::check_transaction_input_with_given_gas(
                     epoch_store.protocol_config(),
                     epoch_store.reference_gas_price(),
                     &transaction,
                     input_objects,
-                    &receiving_objects,
+                    receiving_objects,
+                    gas_object,
                     &self.metrics.bytecode_verifier_metrics,
-                )?,
-                None,
-            )
+                )?
+            }
+            (None, None) => mgo_transaction_checks::check_transaction_input(
+                epoch_store.protocol_config(),
+                epoch_store.reference_gas_price(),
+                &transaction,
+                input_objects,
+                &receiving_objects,
+                &self.metrics.bytecode_verifier_metrics,
+            )?,
         };
 
         let protocol_config = epoch_store.protocol_config();
-        let (kind, signer, _) = transaction.execution_parts();
+        let (kind, _, _) = transaction.execution_parts();
 
         let silent = true;
         let executor = mgo_execution::executor(protocol_config, silent, None)
             .expect("Creating an executor should not fail here");
 
+        let backing_store = self.get_backing_store();
+        let store = StateOverrideStore::new(&overridden_objects, backing_store.as_ref());
+        let epoch_id = state_overrides
+            .epoch
+            .unwrap_or_else(|| epoch_store.epoch_start_config().epoch_data().epoch_id());
+
         let expensive_checks = false;
         let (inner_temp_store, _, effects, _execution_error) = executor
             .execute_transaction_to_effects(
-                self.get_backing_store().as_ref(),
+                &store,
                 protocol_config,
                 self.metrics.limits_metrics.clone(),
                 expensive_checks,
                 self.certificate_deny_config.certificate_deny_set(),
-                &epoch_store.epoch_start_config().epoch_data().epoch_id(),
+                &epoch_id,
                 epoch_store
                     .epoch_start_config()
                     .epoch_data()
@@ -1592,7 +1696,7 @@ impl AuthorityState {
                 gas_object_refs,
                 gas_status,
                 kind,
-                signer,
+                sender,
                 transaction_digest,
             );
         let tx_digest = *effects.transaction_digest();
@@ -1605,7 +1709,7 @@ impl AuthorityState {
                 .executor()
                 .type_layout_resolver(Box::new(TemporaryPackageStore::new(
                     &inner_temp_store,
-                    self.execution_cache.clone(),
+                    StateOverrideStore::new(&overridden_objects, self.execution_cache.as_ref()),
                 )));
         // Returning empty vector here because we recalculate changes in the rpc layer.
         let object_changes = Vec::new();
@@ -1636,6 +1740,13 @@ impl AuthorityState {
             })
             .collect();
 
+        let state_overrides = (!state_overrides.is_empty()).then(|| {
+            MgoAppliedStateOverrides::new(
+                &state_overrides,
+                touched_overridden_objects(&inner_temp_store, &overridden_objects),
+            )
+        });
+
         Ok((
             DryRunTransactionBlockResponse {
                 input: MgoTransactionBlockData::try_from(transaction, &module_cache).map_err(
@@ -1655,13 +1766,140 @@ impl AuthorityState {
                 )?,
                 object_changes,
                 balance_changes,
+                state_overrides,
             },
             written_with_kind,
             effects,
             mock_gas,
+            overridden_objects,
         ))
     }
 
+    /// Resolves `state_overrides` against the live state into the objects that replace the live
+    /// ones: objects with overridden contents or balances, injected packages and the `Clock` with
+    /// an overridden timestamp. Injected packages are verified as if they were being published.
+    fn resolve_state_overrides(
+        &self,
+        state_overrides: &StateOverrides,
+        epoch_store: &AuthorityPerEpochStore,
+    ) -> MgoResult<BTreeMap<ObjectID, Object>> {
+        let protocol_config = epoch_store.protocol_config();
+        let mut overridden_objects = BTreeMap::new();
+        let object_store = self.get_object_store();
+        let package_store = self.get_backing_package_store();
+        let mut layout_resolver = epoch_store
+            .executor()
+            .type_layout_resolver(Box::new(package_store.as_ref()));
+        let not_found = |object_id| UserInputError::ObjectNotFound {
+            object_id,
+            version: None,
+        };
+
+        for (object_id, object_override) in &state_overrides.objects {
+            let object = object_store
+                .get_object(object_id)?
+                .ok_or(not_found(*object_id))?;
+            overridden_objects.insert(
+                *object_id,
+                object_override.apply(&object, layout_resolver.as_mut(), protocol_config)?,
+            );
+        }
+
+        if let Some(timestamp_ms) = state_overrides.timestamp_ms {
+            let mut clock = match overridden_objects.remove(&MGO_CLOCK_OBJECT_ID) {
+                Some(clock) => clock,
+                None => object_store
+                    .get_object(&MGO_CLOCK_OBJECT_ID)?
+                    .ok_or(not_found(MGO_CLOCK_OBJECT_ID))?,
+            }
+            .into_inner();
+            clock
+                .data
+                .try_as_move_mut()
+                .expect("Clock must be a Move object")
+                .set_clock_timestamp_ms_unsafe(timestamp_ms);
+            overridden_objects.insert(MGO_CLOCK_OBJECT_ID, clock.into());
+        }
+
+        let mut verifier = mgo_execution::verifier(
+            protocol_config,
+            /* is_metered */ false,
+            &self.metrics.bytecode_verifier_metrics,
+        );
+        for package_override in &state_overrides.packages {
+            let mut dependencies = Vec::with_capacity(package_override.dependencies.len());
+            for dependency_id in &package_override.dependencies {
+                let dependency = match overridden_objects.get(dependency_id) {
+                    Some(package) if package.is_package() => PackageObject::new(package.clone()),
+                    _ => package_store.get_package_object(dependency_id)?.ok_or(
+                        UserInputError::DependentPackageNotFound {
+                            package_id: *dependency_id,
+                        },
+                    )?,
+                };
+                dependencies.push(dependency);
+            }
+            let package = package_override.to_package_object(
+                protocol_config,
+                dependencies.iter().map(|package| package.move_package()),
+                |modules| verifier.verify_module_bundle(protocol_config, modules),
+            )?;
+            overridden_objects.insert(package.id(), package);
+        }
+
+        Ok(overridden_objects)
+    }
+
+    /// Reads the input objects of a dry run (or, without a digest, a dev inspect), with the
+    /// overridden objects in place of the live ones.
+    async fn read_objects_with_overrides(
+        &self,
+        tx_digest: Option<&TransactionDigest>,
+        input_object_kinds: &[InputObjectKind],
+        receiving_object_refs: &[ObjectRef],
+        overridden_objects: &BTreeMap<ObjectID, Object>,
+        protocol_config: &ProtocolConfig,
+    ) -> MgoResult<(InputObjects, ReceivingObjects)> {
+        let (overridden_kinds, live_kinds): (Vec<_>, Vec<_>) = input_object_kinds
+            .iter()
+            .copied()
+            .partition(|kind| overridden_objects.contains_key(&kind.object_id()));
+
+        let (mut input_objects, receiving_objects) = match tx_digest {
+            Some(tx_digest) => {
+                self.input_loader
+                    .read_objects_for_dry_run_exec(
+                        tx_digest,
+                        &live_kinds,
+                        receiving_object_refs,
+                        protocol_config,
+                    )
+                    .await?
+            }
+            None => {
+                self.input_loader
+                    .read_objects_for_dev_inspect(
+                        &live_kinds,
+                        receiving_object_refs,
+                        protocol_config,
+                    )
+                    .await?
+            }
+        };
+
+        for kind in overridden_kinds {
+            let object = overridden_objects[&kind.object_id()].clone();
+            let kind = match kind {
+                InputObjectKind::ImmOrOwnedMoveObject(_) => {
+                    InputObjectKind::ImmOrOwnedMoveObject(object.compute_object_reference())
+                }
+                kind => kind,
+            };
+            input_objects.push(ObjectReadResult::new(kind, object.into()));
+        }
+        Ok((input_objects, receiving_objects))
+    }
+
     /// The object ID for gas can be any object ID, even for an uncreated object
     #[allow(clippy::collapsible_else_if)]
     pub async fn dev_inspect_transaction_block(
@@ -1674,6 +1912,8 @@ impl AuthorityState {
         gas_objects: Option<Vec<ObjectRef>>,
         show_raw_txn_data_and_effects: Option<bool>,
         skip_checks: Option<bool>,
+        state_overrides: Option<StateOverrides>,
+        trace: Option<bool>,
     ) -> MgoResult<DevInspectResults> {
         let epoch_store = self.load_epoch_store_one_call_per_task();
 
@@ -1691,6 +1931,9 @@ impl AuthorityState {
 
         let show_raw_txn_data_and_effects = show_raw_txn_data_and_effects.unwrap_or(false);
         let skip_checks = skip_checks.unwrap_or(true);
+        let trace = trace.unwrap_or(false);
+        let state_overrides = state_overrides.unwrap_or_default();
+        let sender = state_overrides.sender.unwrap_or(sender);
         let reference_gas_price = epoch_store.reference_gas_price();
         let protocol_config = epoch_store.protocol_config();
         let max_tx_gas = protocol_config.max_tx_gas();
@@ -1725,25 +1968,34 @@ impl AuthorityState {
 
         let input_object_kinds = transaction.input_objects()?;
         let receiving_object_refs = transaction.receiving_objects();
+        let transaction_deny_config = self.transaction_deny_config.load_full();
 
         mgo_transaction_checks::deny::check_transaction_for_signing(
             &transaction,
             &[],
             &input_object_kinds,
             &receiving_object_refs,
-            &self.transaction_deny_config,
+            &transaction_deny_config,
             self.get_backing_package_store().as_ref(),
         )?;
 
+        let overridden_objects = self.resolve_state_overrides(&state_overrides, &epoch_store)?;
         let (mut input_objects, receiving_objects) = self
-            .input_loader
-            .read_objects_for_dev_inspect(
+            .read_objects_with_overrides(
+                None,
                 &input_object_kinds,
                 &receiving_object_refs,
+                &overridden_objects,
                 protocol_config,
             )
             .await?;
 
+        mgo_transaction_checks::deny::check_input_objects_for_signing(
+            &transaction,
+            &input_objects,
+            &transaction_deny_config,
+        )?;
+
         // Create and use a dummy gas object if there is no gas object provided.
         let dummy_gas_object = Object::new_gas_with_balance_and_owner_for_testing(
             DEV_INSPECT_GAS_COIN_VALUE,
@@ -1754,10 +2006,12 @@ impl AuthorityState {
             let gas_object_ref = dummy_gas_object.compute_object_reference();
             vec![gas_object_ref]
         } else {
-            transaction.gas().to_vec()
+            overridden_gas_refs(transaction.gas(), &overridden_objects)
         };
 
-        let (gas_status, checked_input_objects) = if skip_checks {
+        // A simulated sender need not own the transaction's owned inputs.
+        let (gas_status, checked_input_objects) = if skip_checks || state_overrides.sender.is_some()
+        {
             // If we are skipping checks, then we call the check_dev_inspect_input function which will perform
             // only lightweight checks on the transaction input. And if the gas field is empty, that means we will
             // use the dummy gas object so we need to add it to the input objects vector.
@@ -1817,25 +2071,52 @@ impl AuthorityState {
             transaction,
         );
         let transaction_digest = TransactionDigest::new(default_hash(&intent_msg.value));
-        let (inner_temp_store, _, effects, execution_result) = executor.dev_inspect_transaction(
-            self.get_backing_store().as_ref(),
-            protocol_config,
-            self.metrics.limits_metrics.clone(),
-            /* expensive checks */ false,
-            self.certificate_deny_config.certificate_deny_set(),
-            &epoch_store.epoch_start_config().epoch_data().epoch_id(),
-            epoch_store
-                .epoch_start_config()
-                .epoch_data()
-                .epoch_start_timestamp(),
-            checked_input_objects,
-            gas_objects,
-            gas_status,
-            transaction_kind,
-            sender,
-            transaction_digest,
-            skip_checks,
-        );
+        let backing_store = self.get_backing_store();
+        let epoch_id = state_overrides
+            .epoch
+            .unwrap_or_else(|| epoch_store.epoch_start_config().epoch_data().epoch_id());
+        let store = StateOverrideStore::new(&overridden_objects, backing_store.as_ref());
+        let epoch_start_timestamp = epoch_store
+            .epoch_start_config()
+            .epoch_data()
+            .epoch_start_timestamp();
+        let mut tracer = trace.then(ExecutionTracer::new);
+        let (inner_temp_store, _, effects, execution_result) = if let Some(tracer) = &mut tracer {
+            executor.dev_inspect_transaction_with_tracer(
+                &store,
+                protocol_config,
+                self.metrics.limits_metrics.clone(),
+                /* expensive checks */ false,
+                self.certificate_deny_config.certificate_deny_set(),
+                &epoch_id,
+                epoch_start_timestamp,
+                checked_input_objects,
+                gas_objects,
+                gas_status,
+                transaction_kind,
+                sender,
+                transaction_digest,
+                skip_checks,
+                tracer,
+            )?
+        } else {
+            executor.dev_inspect_transaction(
+                &store,
+                protocol_config,
+                self.metrics.limits_metrics.clone(),
+                /* expensive checks */ false,
+                self.certificate_deny_config.certificate_deny_set(),
+                &epoch_id,
+                epoch_start_timestamp,
+                checked_input_objects,
+                gas_objects,
+                gas_status,
+                transaction_kind,
+                sender,
+                transaction_digest,
+                skip_checks,
+            )
+        };
 
         let raw_effects = if show_raw_txn_data_and_effects {
             bcs::to_bytes(&effects).map_err(|_| MgoError::TransactionSerializationError {
@@ -1845,21 +2126,35 @@ impl AuthorityState {
             vec![]
         };
 
-        let package_store =
-            TemporaryPackageStore::new(&inner_temp_store, self.execution_cache.clone());
+        let package_store = TemporaryPackageStore::new(
+            &inner_temp_store,
+            StateOverrideStore::new(&overridden_objects, self.execution_cache.as_ref()),
+        );
 
         let mut layout_resolver = epoch_store
             .executor()
             .type_layout_resolver(Box::new(package_store));
 
-        DevInspectResults::new(
+        let mut results = DevInspectResults::new(
             effects,
             inner_temp_store.events.clone(),
             execution_result,
             raw_txn_data,
             raw_effects,
             layout_resolver.as_mut(),
-        )
+        )?;
+        if !state_overrides.is_empty() {
+            results.state_overrides = Some(MgoAppliedStateOverrides::new(
+                &state_overrides,
+                touched_overridden_objects(&inner_temp_store, &overridden_objects),
+            ));
+        }
+        if let Some(tracer) = tracer {
+            let mut trace = tracer.finish();
+            decode_execution_trace(&mut trace, &inner_temp_store).await;
+            results.trace = Some(trace);
+        }
+        Ok(results)
     }
 
     // Only used for testing because of how epoch store is loaded.
@@ -2449,7 +2744,7 @@ impl AuthorityState {
 
         let _authority_per_epoch_pruner =
             AuthorityPerEpochStorePruner::new(epoch_store.get_parent_path(), &pruning_config);
-        let _pruner = AuthorityStorePruner::new(
+        let pruner = AuthorityStorePruner::new(
             store.perpetual_tables.clone(),
             checkpoint_store.clone(),
             store.objects_lock_table.clone(),
@@ -2477,11 +2772,11 @@ impl AuthorityState {
             transaction_manager,
             tx_execution_shutdown: Mutex::new(Some(tx_execution_shutdown)),
             metrics,
-            _pruner,
+            pruner,
             _authority_per_epoch_pruner,
             db_checkpoint_config: db_checkpoint_config.clone(),
             expensive_safety_check_config,
-            transaction_deny_config,
+            transaction_deny_config: ArcSwap::new(Arc::new(transaction_deny_config)),
             certificate_deny_config,
             debug_dump_config,
             overload_threshold_config: overload_threshold_config.clone(),
@@ -2522,6 +2817,31 @@ impl AuthorityState {
         self.execution_cache.clone()
     }
 
+    pub fn transaction_deny_config(&self) -> Arc<TransactionDenyConfig> {
+        self.transaction_deny_config.load_full()
+    }
+
+    /// Replaces the transaction deny config, which applies to transactions handled from now on.
+    /// Returns the changes made, as described by `TransactionDenyConfig::diff`.
+    pub fn reload_transaction_deny_config(&self, config: TransactionDenyConfig) -> Vec<String> {
+        let config = Arc::new(config);
+        let old_config = self.transaction_deny_config.swap(config.clone());
+        old_config.diff(&config)
+    }
+
+    pub fn pruning_config(&self) -> AuthorityStorePruningConfig {
+        self.pruner.config()
+    }
+
+    /// Replaces the pruning config used by the objects and checkpoints pruner from its next run
+    /// on. Returns the config in effect, see `AuthorityStorePruner::update_config`.
+    pub fn update_pruning_config(
+        &self,
+        config: AuthorityStorePruningConfig,
+    ) -> AuthorityStorePruningConfig {
+        self.pruner.update_config(config)
+    }
+
     pub fn get_backing_package_store(&self) -> Arc<dyn BackingPackageStore> {
         self.execution_cache.clone()
     }
@@ -3723,6 +4043,196 @@ impl AuthorityState {
         Ok(events)
     }
 
+    /// Resolves the cursor of a resumable subscription to the position in the indexes its
+    /// backfill starts at. If the checkpoints from a checkpoint cursor on were pruned, the backfill
+    /// starts with the first checkpoint left and a gap is returned for the pruned ones.
+    pub async fn resolve_subscription_cursor(
+        &self,
+        kv_store: &Arc<TransactionKeyValueStore>,
+        cursor: SubscriptionCursor,
+    ) -> MgoResult<(BackfillPosition, Option<SubscriptionGap>)> {
+        let index_store = self.get_indexes()?;
+        let get_tx_seq = |digest| {
+            index_store
+                .get_transaction_seq(&digest)?
+                .ok_or(MgoError::TransactionNotFound { digest })
+        };
+        let sequence_number = match cursor {
+            SubscriptionCursor::Event(id) => {
+                return Ok(((get_tx_seq(id.tx_digest)?, id.event_seq as usize + 1), None));
+            }
+            SubscriptionCursor::Transaction(digest) => {
+                return Ok(((get_tx_seq(digest)? + 1, 0), None));
+            }
+            SubscriptionCursor::Checkpoint(sequence_number) => sequence_number,
+        };
+
+        let latest = self.get_latest_checkpoint_sequence_number()?;
+        if sequence_number > latest {
+            return Err(UserInputError::VerifiedCheckpointNotFound(sequence_number).into());
+        }
+        let mut contents = kv_store
+            .multi_get_checkpoints_contents(&[sequence_number, latest])
+            .await?
+            .into_iter();
+        let (contents, gap) = match (contents.next().flatten(), contents.next().flatten()) {
+            (Some(contents), _) => (contents, None),
+            (None, Some(mut latest_contents)) => {
+                // Pruning removes the oldest checkpoints, so the first checkpoint left is found
+                // by a binary search between the pruned cursor and the latest checkpoint.
+                let (mut pruned, mut available) = (sequence_number, latest);
+                while available - pruned > 1 {
+                    let mid = pruned + (available - pruned) / 2;
+                    match kv_store
+                        .multi_get_checkpoints_contents(&[mid])
+                        .await?
+                        .pop()
+                        .flatten()
+                    {
+                        Some(contents) => {
+                            available = mid;
+                            latest_contents = contents;
+                        }
+                        None => pruned = mid,
+                    }
+                }
+                let gap = SubscriptionGap::Checkpoints {
+                    start: sequence_number,
+                    end: pruned,
+                };
+                (latest_contents, Some(gap))
+            }
+            (None, None) => {
+                return Err(UserInputError::VerifiedCheckpointNotFound(latest).into());
+            }
+        };
+        let first_tx = contents.iter().next().ok_or(MgoError::UnexpectedMessage)?;
+        Ok(((get_tx_seq(first_tx.transaction)?, 0), gap))
+    }
+
+    /// Reads a page of events for a resumable subscription from the indexes. Events whose data
+    /// was pruned are returned as a gap, instead of failing the page like `query_events` does.
+    pub async fn backfill_events(
+        &self,
+        kv_store: &Arc<TransactionKeyValueStore>,
+        filter: &EventFilter,
+        start: BackfillPosition,
+        limit: usize,
+    ) -> MgoResult<BackfillPage<MgoEvent>> {
+        let index_store = self.get_indexes()?;
+        let event_keys = index_store.all_events(start.0, start.1, limit, false)?;
+        let next = match event_keys.last() {
+            Some((_, tx_digest, event_seq, _)) => {
+                let tx_seq = index_store
+                    .get_transaction_seq(tx_digest)?
+                    .ok_or(MgoError::TransactionNotFound { digest: *tx_digest })?;
+                (tx_seq, event_seq + 1)
+            }
+            None => start,
+        };
+        let caught_up = event_keys.len() < limit;
+
+        let event_digests = event_keys
+            .iter()
+            .map(|(digest, _, _, _)| *digest)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let events = kv_store.multi_get_events(&event_digests).await?;
+        let events_map: HashMap<_, _> = event_digests.into_iter().zip(events).collect();
+
+        let epoch_store = self.load_epoch_store_one_call_per_task();
+        let backing_store = self.execution_cache.as_ref();
+        let mut layout_resolver = epoch_store
+            .executor()
+            .type_layout_resolver(Box::new(backing_store));
+        let mut items = vec![];
+        let mut pruned = vec![];
+        for (digest, tx_digest, event_seq, timestamp) in event_keys {
+            let Some(event) = events_map
+                .get(&digest)
+                .and_then(|events| events.as_ref())
+                .and_then(|events| events.data.get(event_seq))
+            else {
+                if pruned.last() != Some(&tx_digest) {
+                    pruned.push(tx_digest);
+                }
+                continue;
+            };
+            if !pruned.is_empty() {
+                let gap = SubscriptionGap::Transactions(std::mem::take(&mut pruned));
+                items.push(SubscriptionItem::Gap(gap));
+            }
+            let event = MgoEvent::try_from(
+                event.clone(),
+                tx_digest,
+                event_seq as u64,
+                Some(timestamp),
+                layout_resolver.get_annotated_layout(&event.type_)?,
+            )?;
+            if filter.matches(&event) {
+                items.push(SubscriptionItem::Data(event));
+            }
+        }
+        if !pruned.is_empty() {
+            items.push(SubscriptionItem::Gap(SubscriptionGap::Transactions(pruned)));
+        }
+        Ok(BackfillPage {
+            items,
+            next,
+            caught_up,
+        })
+    }
+
+    /// Reads a page of transaction effects for a resumable subscription from the indexes.
+    /// Transactions whose data was pruned are returned as a gap.
+    pub async fn backfill_transactions(
+        &self,
+        kv_store: &Arc<TransactionKeyValueStore>,
+        filter: &TransactionFilter,
+        start: BackfillPosition,
+        limit: usize,
+    ) -> MgoResult<BackfillPage<MgoTransactionBlockEffects>> {
+        let transactions = self.get_indexes()?.transactions_from(start.0, limit)?;
+        let next = transactions
+            .last()
+            .map_or(start, |(tx_seq, _)| (tx_seq + 1, 0));
+        let caught_up = transactions.len() < limit;
+
+        let digests = transactions
+            .into_iter()
+            .map(|(_, digest)| digest)
+            .collect::<Vec<_>>();
+        let (transactions, effects, _) = kv_store.multi_get(&digests, &digests, &[]).await?;
+        let mut items = vec![];
+        let mut pruned = vec![];
+        for ((digest, transaction), effects) in digests.into_iter().zip(transactions).zip(effects) {
+            let (Some(transaction), Some(effects)) = (transaction, effects) else {
+                pruned.push(digest);
+                continue;
+            };
+            if !pruned.is_empty() {
+                let gap = SubscriptionGap::Transactions(std::mem::take(&mut pruned));
+                items.push(SubscriptionItem::Gap(gap));
+            }
+            let effects = EffectsWithInput {
+                effects: effects.try_into()?,
+                input: transaction.data().transaction_data().clone(),
+            };
+            if filter.matches(&effects) {
+                items.push(SubscriptionItem::Data(effects.into()));
+            }
+        }
+        if !pruned.is_empty() {
+            items.push(SubscriptionItem::Gap(SubscriptionGap::Transactions(pruned)));
+        }
+        Ok(BackfillPage {
+            items,
+            next,
+            caught_up,
+        })
+    }
+
     pub async fn insert_genesis_object(&self, object: Object) {
         self.database
             .insert_genesis_object(object)
@@ -4792,6 +5302,39 @@ impl TransactionKeyValueStoreTrait for AuthorityState {
     }
 }
 
+/// Refers to overridden gas coins by their overridden object references.
+fn overridden_gas_refs(
+    gas: &[ObjectRef],
+    overridden_objects: &BTreeMap<ObjectID, Object>,
+) -> Vec<ObjectRef> {
+    gas.iter()
+        .map(|gas_ref| {
+            overridden_objects
+                .get(&gas_ref.0)
+                .map_or(*gas_ref, |gas_object| gas_object.compute_object_reference())
+        })
+        .collect()
+}
+
+/// The overridden objects that the transaction read or modified, either as inputs, as
+/// dynamically loaded child objects or as packages.
+fn touched_overridden_objects(
+    inner_temp_store: &InnerTemporaryStore,
+    overridden_objects: &BTreeMap<ObjectID, Object>,
+) -> Vec<ObjectID> {
+    overridden_objects
+        .keys()
+        .filter(|id| {
+            inner_temp_store.input_objects.contains_key(id)
+                || inner_temp_store.loaded_runtime_objects.contains_key(id)
+                || inner_temp_store
+                    .runtime_packages_loaded_from_db
+                    .contains_key(id)
+        })
+        .copied()
+        .collect()
+}
+
 #[cfg(msim)]
 pub mod framework_injection {
     use move_binary_format::CompiledModule;
@@ -4952,17 +5495,7 @@ impl NodeStateDump {
         }
 
         // Record all the shared objects
-        let mut shared_objects = Vec::new();
-        for kind in effects.input_shared_objects() {
-            match kind {
-                InputSharedObject::Mutate(obj_ref) | InputSharedObject::ReadOnly(obj_ref) => {
-                    if let Some(w) = authority_store.get_object_by_key(&obj_ref.0, obj_ref.1)? {
-                        shared_objects.push(w)
-                    }
-                }
-                InputSharedObject::ReadDeleted(..) | InputSharedObject::MutateDeleted(..) => (),
-            }
-        }
+        let shared_objects = Self::read_shared_objects(effects, authority_store)?;
 
         // Record all loaded child objects
         // Child objects which are read but not mutated are not tracked anywhere else
@@ -4974,12 +5507,7 @@ impl NodeStateDump {
         }
 
         // Record all modified objects
-        let mut modified_at_versions = Vec::new();
-        for (id, ver) in effects.modified_at_versions() {
-            if let Some(w) = authority_store.get_object_by_key(&id, ver)? {
-                modified_at_versions.push(w)
-            }
-        }
+        let modified_at_versions = Self::read_modified_at_versions(effects, authority_store)?;
 
         // Packages read at runtime, which were not previously loaded into the temoorary store
         // Some packages may be fetched at runtime and wont show up in input objects
@@ -5015,6 +5543,106 @@ impl NodeStateDump {
         })
     }
 
+    /// Captures the state of a transaction that was already executed in the current epoch, as far
+    /// as it is still in `authority_store`. Child objects that were only read at runtime are not
+    /// recorded by the store, so unlike `new` they are missing from the dump.
+    pub fn new_from_executed_transaction(
+        sender_signed_data: SenderSignedData,
+        effects: &TransactionEffects,
+        authority_store: &Arc<AuthorityStore>,
+        epoch_store: &Arc<AuthorityPerEpochStore>,
+    ) -> MgoResult<Self> {
+        let tx_digest = *effects.transaction_digest();
+        // Epoch info is only available for the current epoch
+        let executed_epoch = epoch_store.epoch();
+        if effects.executed_epoch() != executed_epoch {
+            return Err(MgoError::UnsupportedFeatureError {
+                error: format!(
+                    "Transaction {tx_digest} was executed in epoch {}, only transactions of the \
+                     current epoch {executed_epoch} can be dumped",
+                    effects.executed_epoch()
+                ),
+            });
+        }
+        let epoch_start_config = epoch_store.epoch_start_config();
+
+        let mut relevant_system_packages = Vec::new();
+        for sys_package_id in BuiltInFramework::all_package_ids() {
+            if let Some(w) = authority_store.get_object(&sys_package_id)? {
+                relevant_system_packages.push(w)
+            }
+        }
+
+        // Shared objects are recorded at the versions assigned to the transaction, below
+        let mut input_objects = Vec::new();
+        for kind in sender_signed_data.transaction_data().input_objects()? {
+            let object = match kind {
+                InputObjectKind::MovePackage(id) => authority_store.get_object(&id)?,
+                InputObjectKind::ImmOrOwnedMoveObject(obj_ref) => {
+                    authority_store.get_object_by_key(&obj_ref.0, obj_ref.1)?
+                }
+                InputObjectKind::SharedMoveObject { .. } => None,
+            };
+            input_objects.extend(object);
+        }
+
+        // Receiving objects are loaded at runtime like child objects
+        let mut loaded_child_objects = Vec::new();
+        for (id, version, _) in sender_signed_data.transaction_data().receiving_objects() {
+            if let Some(w) = authority_store.get_object_by_key(&id, version)? {
+                loaded_child_objects.push(w)
+            }
+        }
+
+        Ok(Self {
+            tx_digest,
+            executed_epoch,
+            reference_gas_price: epoch_store.reference_gas_price(),
+            epoch_start_timestamp_ms: epoch_start_config.epoch_data().epoch_start_timestamp(),
+            protocol_version: epoch_store.protocol_version().as_u64(),
+            relevant_system_packages,
+            shared_objects: Self::read_shared_objects(effects, authority_store)?,
+            loaded_child_objects,
+            modified_at_versions: Self::read_modified_at_versions(effects, authority_store)?,
+            runtime_reads: vec![],
+            sender_signed_data,
+            input_objects,
+            computed_effects: effects.clone(),
+            expected_effects_digest: effects.digest(),
+        })
+    }
+
+    fn read_shared_objects(
+        effects: &TransactionEffects,
+        authority_store: &AuthorityStore,
+    ) -> MgoResult<Vec<Object>> {
+        let mut shared_objects = Vec::new();
+        for kind in effects.input_shared_objects() {
+            match kind {
+                InputSharedObject::Mutate(obj_ref) | InputSharedObject::ReadOnly(obj_ref) => {
+                    if let Some(w) = authority_store.get_object_by_key(&obj_ref.0, obj_ref.1)? {
+                        shared_objects.push(w)
+                    }
+                }
+                InputSharedObject::ReadDeleted(..) | InputSharedObject::MutateDeleted(..) => (),
+            }
+        }
+        Ok(shared_objects)
+    }
+
+    fn read_modified_at_versions(
+        effects: &TransactionEffects,
+        authority_store: &AuthorityStore,
+    ) -> MgoResult<Vec<Object>> {
+        let mut modified_at_versions = Vec::new();
+        for (id, ver) in effects.modified_at_versions() {
+            if let Some(w) = authority_store.get_object_by_key(&id, ver)? {
+                modified_at_versions.push(w)
+            }
+        }
+        Ok(modified_at_versions)
+    }
+
     pub fn all_objects(&self) -> Vec<Object> {
         let mut objects = Vec::new();
         objects.extend(self.relevant_system_packages.clone());
@@ -31,6 +31,8 @@ use mgo_types::committee::CommitteeTrait;
 use mgo_types::crypto::{AuthoritySignInfo, AuthorityStrongQuorumSignInfo};
 use mgo_types::digests::ChainIdentifier;
 use mgo_types::error::{MgoError, MgoResult};
+use mgo_types::multisig::NestedMultiSigLimits;
+use mgo_types::passkey_authenticator::PasskeyRelyingParties;
 use mgo_types::signature::GenericSignature;
 use mgo_types::storage::InputKey;
 use mgo_types::transaction::{
@@ -49,6 +51,7 @@ use typed_store::{
 
 use super::epoch_start_configuration::EpochStartConfigTrait;
 use crate::authority::epoch_start_configuration::{EpochFlag, EpochStartConfiguration};
+use crate::authority::shared_object_congestion_tracker::SharedObjectCongestionTracker;
 use crate::authority::ResolverWrapper;
 use crate::checkpoints::{
     BuilderCheckpointSummary, CheckpointCommitHeight, CheckpointServiceNotify, EpochStats,
@@ -510,14 +513,16 @@ impl DeferralKey {
         )
     }
 
-    fn range_for_consensus_round(future_round: Round) -> (Self, Self) {
+    // Commit rounds are not contiguous, so a transaction deferred to a round that has no commit
+    // is loaded by the first commit after it.
+    fn range_for_up_to_consensus_round(consensus_round: Round) -> (Self, Self) {
         (
             Self::ConsensusRound {
-                future_round,
+                future_round: 0,
                 deferred_from_round: 0,
             },
             Self::ConsensusRound {
-                future_round: future_round.checked_add(1).unwrap(),
+                future_round: consensus_round.checked_add(1).unwrap(),
                 deferred_from_round: 0,
             },
         )
@@ -758,6 +763,16 @@ impl AuthorityPerEpochStore {
             zklogin_env,
             protocol_config.verify_legacy_zklogin_address(),
             protocol_config.accept_zklogin_in_multisig(),
+            protocol_config
+                .max_multisig_nesting_depth_as_option()
+                .map(|max_depth| NestedMultiSigLimits {
+                    max_depth,
+                    max_signatures: protocol_config.max_multisig_signatures(),
+                }),
+            PasskeyRelyingParties {
+                rp_ids: protocol_config.passkey_rp_ids().clone(),
+                origins: protocol_config.passkey_origins().clone(),
+            },
         );
 
         let authenticator_state_exists = epoch_start_configuration
@@ -1220,6 +1235,33 @@ impl AuthorityPerEpochStore {
         Ok(())
     }
 
+    /// Gets all deferred transactions, keyed by the round they are deferred to. Used for
+    /// debugging.
+    pub fn get_all_deferred_transactions(
+        &self,
+    ) -> MgoResult<Vec<(DeferralKey, Vec<VerifiedSequencedConsensusTransaction>)>> {
+        Ok(self
+            .tables()?
+            .deferred_transactions
+            .safe_iter()
+            .collect::<Result<_, _>>()?)
+    }
+
+    /// Gets the shared object versions assigned to up to `limit` certificates, in digest order
+    /// starting from `start`. Used for debugging.
+    pub fn get_assigned_shared_object_versions(
+        &self,
+        start: Option<TransactionDigest>,
+        limit: usize,
+    ) -> MgoResult<Vec<(TransactionDigest, Vec<(ObjectID, SequenceNumber)>)>> {
+        Ok(self
+            .tables()?
+            .assigned_shared_object_versions
+            .safe_iter_with_bounds(start, None)
+            .take(limit)
+            .collect::<Result<_, _>>()?)
+    }
+
     pub fn get_all_pending_consensus_transactions(&self) -> Vec<ConsensusTransaction> {
         self.tables()
             .expect("recovery should not cross epoch boundary")
@@ -1436,12 +1478,12 @@ impl AuthorityPerEpochStore {
         self.load_deferred_transactions(batch, min, max)
     }
 
-    fn load_deferred_transactions_for_consensus_round(
+    fn load_deferred_transactions_for_up_to_consensus_round(
         &self,
         batch: &mut DBBatch,
         consensus_round: u64,
     ) -> MgoResult<Vec<VerifiedSequencedConsensusTransaction>> {
-        let (min, max) = DeferralKey::range_for_consensus_round(consensus_round);
+        let (min, max) = DeferralKey::range_for_up_to_consensus_round(consensus_round);
         self.load_deferred_transactions(batch, min, max)
     }
 
@@ -1490,6 +1532,7 @@ impl AuthorityPerEpochStore {
         commit_round: Round,
         previously_deferred_tx_digests: &HashSet<TransactionDigest>,
         last_randomness_round: RandomnessRound,
+        shared_object_congestion_tracker: &SharedObjectCongestionTracker,
     ) -> Option<DeferralKey> {
         // Defer transaction if it depends on Random object.
         if cert
@@ -1509,8 +1552,17 @@ impl AuthorityPerEpochStore {
             ));
         }
 
-        // placeholder construction to silence lints
-        let _ = DeferralKey::new_for_consensus_round(0, 0);
+        // Defer transaction if one of its shared objects is congested in this commit.
+        if let Some(congested_objects) =
+            shared_object_congestion_tracker.should_defer_due_to_object_congestion(cert)
+        {
+            debug!(
+                "Deferring transaction {:?} to the next commit because of congested objects {:?}",
+                cert.digest(),
+                congested_objects,
+            );
+            return Some(DeferralKey::new_for_consensus_round(commit_round + 1, commit_round));
+        }
 
         None
     }
@@ -2298,7 +2350,7 @@ impl AuthorityPerEpochStore {
         // We do this after updating the last_randomness_round_written above so that every deferred
         // transaction that can be run with this commit is loaded.
         let deferred_tx: Vec<VerifiedSequencedConsensusTransaction> = self
-            .load_deferred_transactions_for_consensus_round(&mut batch, commit_round)?
+            .load_deferred_transactions_for_up_to_consensus_round(&mut batch, commit_round)?
             .into_iter()
             .chain(self.load_deferred_transactions_for_randomness_round(
                 &mut batch,
@@ -2316,9 +2368,9 @@ impl AuthorityPerEpochStore {
             .collect();
         sequenced_transactions.extend(deferred_tx.into_iter());
 
-        PostConsensusTxReorder::reorder(
+        PostConsensusTxReorder::reorder_for_protocol(
             &mut sequenced_transactions,
-            self.protocol_config.consensus_transaction_ordering(),
+            &self.protocol_config,
         );
         let consensus_transactions: Vec<_> = system_transactions
             .into_iter()
@@ -2483,6 +2535,9 @@ impl AuthorityPerEpochStore {
         let mut deferred_txns: BTreeMap<DeferralKey, Vec<VerifiedSequencedConsensusTransaction>> =
             BTreeMap::new();
 
+        let mut shared_object_congestion_tracker =
+            SharedObjectCongestionTracker::from_protocol_config(&self.protocol_config);
+
         let mut randomness_state_updated = false;
         for tx in transactions {
             let key = tx.0.transaction.key();
@@ -2496,6 +2551,7 @@ impl AuthorityPerEpochStore {
                     commit_round,
                     &previously_deferred_tx_digests,
                     last_randomness_round,
+                    &mut shared_object_congestion_tracker,
                 )
                 .await?
             {
@@ -2661,6 +2717,7 @@ impl AuthorityPerEpochStore {
         commit_round: Round,
         previously_deferred_tx_digests: &HashSet<TransactionDigest>,
         last_randomness_round: RandomnessRound,
+        shared_object_congestion_tracker: &mut SharedObjectCongestionTracker,
     ) -> MgoResult<ConsensusCertificateResult> {
         let _scope = monitored_scope("HandleConsensusTransaction");
         let VerifiedSequencedConsensusTransaction(SequencedConsensusTransaction {
@@ -2719,6 +2776,7 @@ impl AuthorityPerEpochStore {
                     commit_round,
                     previously_deferred_tx_digests,
                     last_randomness_round,
+                    shared_object_congestion_tracker,
                 ) {
                     debug!(
                         "Deferring consensus certificate for transaction {:?} until {deferral_key:?}",
@@ -2728,6 +2786,7 @@ impl AuthorityPerEpochStore {
                 }
 
                 if certificate.contains_shared_object() {
+                    shared_object_congestion_tracker.bump_object_execution_count(&certificate);
                     self.record_shared_object_cert_from_consensus(
                         batch,
                         shared_input_next_versions,
@@ -31,6 +31,7 @@ use mgo_types::{
     storage::ObjectKey,
 };
 use tokio::sync::oneshot::{self, Sender};
+use tokio::sync::watch;
 use tokio::time::Instant;
 use tracing::{debug, error, info, warn};
 use typed_store::{Map, TypedStoreError};
@@ -52,6 +53,8 @@ static PERIODIC_PRUNING_TABLES: Lazy<BTreeSet<String>> = Lazy::new(|| {
 });
 pub struct AuthorityStorePruner {
     _objects_pruner_cancel_handle: oneshot::Sender<()>,
+    config: watch::Sender<AuthorityStorePruningConfig>,
+    is_validator: bool,
 }
 
 pub struct AuthorityStorePruningMetrics {
@@ -536,7 +539,7 @@ impl AuthorityStorePruner {
     }
 
     fn setup_pruning(
-        config: AuthorityStorePruningConfig,
+        mut config_receiver: watch::Receiver<AuthorityStorePruningConfig>,
         epoch_duration_ms: u64,
         perpetual_db: Arc<AuthorityPerpetualTables>,
         checkpoint_store: Arc<CheckpointStore>,
@@ -546,6 +549,7 @@ impl AuthorityStorePruner {
         archive_readers: ArchiveReaderBalancer,
     ) -> Sender<()> {
         let (sender, mut recv) = tokio::sync::oneshot::channel();
+        let config = *config_receiver.borrow();
         debug!(
             "Starting object pruning service with num_epochs_to_retain={}",
             config.num_epochs_to_retain
@@ -587,17 +591,13 @@ impl AuthorityStorePruner {
             });
         }
 
-        metrics
-            .num_epochs_to_retain_for_objects
-            .set(config.num_epochs_to_retain as i64);
-        metrics.num_epochs_to_retain_for_checkpoints.set(
-            config
-                .num_epochs_to_retain_for_checkpoints
-                .unwrap_or_default() as i64,
-        );
+        Self::record_config_metrics(&config, &metrics);
 
         tokio::task::spawn(async move {
             loop {
+                // Each run uses the latest config, so that the retention and batch sizes can be
+                // adjusted at runtime.
+                let config = *config_receiver.borrow();
                 tokio::select! {
                     _ = objects_prune_interval.tick(), if config.num_epochs_to_retain != u64::MAX => {
                         if let Err(err) = Self::prune_objects_for_eligible_epochs(&perpetual_db, &checkpoint_store, &objects_lock_table, config, metrics.clone(), indirect_objects_threshold).await {
@@ -609,6 +609,11 @@ impl AuthorityStorePruner {
                             error!("Failed to prune checkpoints: {:?}", err);
                         }
                     },
+                    Ok(()) = config_receiver.changed() => {
+                        let config = *config_receiver.borrow();
+                        info!("Updated pruning config to {:?}", config);
+                        Self::record_config_metrics(&config, &metrics);
+                    },
                     _ = &mut recv => break,
                 }
             }
@@ -616,17 +621,24 @@ impl AuthorityStorePruner {
         sender
     }
 
-    pub fn new(
-        perpetual_db: Arc<AuthorityPerpetualTables>,
-        checkpoint_store: Arc<CheckpointStore>,
-        objects_lock_table: Arc<RwLockTable<ObjectContentDigest>>,
+    fn record_config_metrics(
+        config: &AuthorityStorePruningConfig,
+        metrics: &AuthorityStorePruningMetrics,
+    ) {
+        metrics
+            .num_epochs_to_retain_for_objects
+            .set(config.num_epochs_to_retain as i64);
+        metrics.num_epochs_to_retain_for_checkpoints.set(
+            config
+                .num_epochs_to_retain_for_checkpoints
+                .unwrap_or_default() as i64,
+        );
+    }
+
+    fn sanitize_config(
         mut pruning_config: AuthorityStorePruningConfig,
         is_validator: bool,
-        epoch_duration_ms: u64,
-        registry: &Registry,
-        indirect_objects_threshold: usize,
-        archive_readers: ArchiveReaderBalancer,
-    ) -> Self {
+    ) -> AuthorityStorePruningConfig {
         if pruning_config.num_epochs_to_retain > 0 && pruning_config.num_epochs_to_retain < u64::MAX
         {
             warn!("Using objects pruner with num_epochs_to_retain = {} can lead to performance issues", pruning_config.num_epochs_to_retain);
@@ -637,9 +649,25 @@ impl AuthorityStorePruner {
                 warn!("Consider using an aggressive pruner (num_epochs_to_retain = 0)");
             }
         }
+        pruning_config
+    }
+
+    pub fn new(
+        perpetual_db: Arc<AuthorityPerpetualTables>,
+        checkpoint_store: Arc<CheckpointStore>,
+        objects_lock_table: Arc<RwLockTable<ObjectContentDigest>>,
+        pruning_config: AuthorityStorePruningConfig,
+        is_validator: bool,
+        epoch_duration_ms: u64,
+        registry: &Registry,
+        indirect_objects_threshold: usize,
+        archive_readers: ArchiveReaderBalancer,
+    ) -> Self {
+        let (config, config_receiver) =
+            watch::channel(Self::sanitize_config(pruning_config, is_validator));
         AuthorityStorePruner {
             _objects_pruner_cancel_handle: Self::setup_pruning(
-                pruning_config,
+                config_receiver,
                 epoch_duration_ms,
                 perpetual_db,
                 checkpoint_store,
@@ -648,9 +676,28 @@ impl AuthorityStorePruner {
                 indirect_objects_threshold,
                 archive_readers,
             ),
+            config,
+            is_validator,
         }
     }
 
+    pub fn config(&self) -> AuthorityStorePruningConfig {
+        *self.config.borrow()
+    }
+
+    /// Replaces the config used by the following pruning runs, and returns the config in effect.
+    /// The initial delay and the periodic compaction are only set up when the node starts, so
+    /// changes to `pruning_run_delay_seconds` and `periodic_compaction_threshold_days` have no
+    /// effect.
+    pub fn update_config(
+        &self,
+        config: AuthorityStorePruningConfig,
+    ) -> AuthorityStorePruningConfig {
+        let config = Self::sanitize_config(config, self.is_validator);
+        self.config.send_replace(config);
+        config
+    }
+
     pub fn compact(perpetual_db: &Arc<AuthorityPerpetualTables>) -> Result<(), TypedStoreError> {
         perpetual_db.objects.compact_range(
             &ObjectKey(ObjectID::ZERO, SequenceNumber::MIN),
@@ -0,0 +1,75 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use mgo_protocol_config::{PerObjectCongestionControlMode, ProtocolConfig};
+use mgo_types::base_types::ObjectID;
+use mgo_types::executable_transaction::VerifiedExecutableTransaction;
+use std::collections::HashMap;
+
+/// Counts the transactions scheduled in one consensus commit for every shared object they
+/// mutate, so that transactions touching a congested object can be deferred to a later commit.
+/// A new tracker is created for every commit.
+pub struct SharedObjectCongestionTracker {
+    mode: PerObjectCongestionControlMode,
+    max_txns_per_object: u64,
+    object_execution_count: HashMap<ObjectID, u64>,
+}
+
+impl SharedObjectCongestionTracker {
+    pub fn new(mode: PerObjectCongestionControlMode, max_txns_per_object: Option<u64>) -> Self {
+        let max_txns_per_object = match mode {
+            PerObjectCongestionControlMode::None => u64::MAX,
+            PerObjectCongestionControlMode::TransactionCount => max_txns_per_object
+                .expect("max_txns_per_shared_object_per_commit must be set with TransactionCount"),
+        };
+        Self {
+            mode,
+            max_txns_per_object,
+            object_execution_count: HashMap::new(),
+        }
+    }
+
+    pub fn from_protocol_config(protocol_config: &ProtocolConfig) -> Self {
+        Self::new(
+            protocol_config.per_object_congestion_control_mode(),
+            protocol_config.max_txns_per_shared_object_per_commit_as_option(),
+        )
+    }
+
+    /// Returns the shared objects mutated by `cert` that already reached their limit in this
+    /// commit, or None if `cert` can be scheduled.
+    pub fn should_defer_due_to_object_congestion(
+        &self,
+        cert: &VerifiedExecutableTransaction,
+    ) -> Option<Vec<ObjectID>> {
+        if self.mode.is_none() {
+            return None;
+        }
+        let congested_objects: Vec<_> = cert
+            .shared_input_objects()
+            .filter(|object| {
+                object.mutable
+                    && self
+                        .object_execution_count
+                        .get(&object.id())
+                        .is_some_and(|count| *count >= self.max_txns_per_object)
+            })
+            .map(|object| object.id())
+            .collect();
+        if congested_objects.is_empty() {
+            None
+        } else {
+            Some(congested_objects)
+        }
+    }
+
+    /// Records that `cert` is scheduled in this commit.
+    pub fn bump_object_execution_count(&mut self, cert: &VerifiedExecutableTransaction) {
+        if self.mode.is_none() {
+            return;
+        }
+        for object in cert.shared_input_objects().filter(|object| object.mutable) {
+            *self.object_execution_count.entry(object.id()).or_default() += 1;
+        }
+    }
+}
@@ -12,6 +12,7 @@ use prometheus::{
     IntCounterVec, Registry,
 };
 use std::{io, sync::Arc};
+use mgo_config::node::AdmissionControlConfig;
 use mgo_network::{
     api::{Validator, ValidatorServer},
     tonic,
@@ -38,6 +39,7 @@ use tracing::{error_span, info, Instrument};
 
 use crate::consensus_adapter::ConnectionMonitorStatusForTests;
 use crate::{
+    admission_control::AdmissionController,
     authority::AuthorityState,
     consensus_adapter::{ConsensusAdapter, ConsensusAdapterMetrics},
 };
@@ -81,6 +83,7 @@ pub struct AuthorityServer {
     pub state: Arc<AuthorityState>,
     consensus_adapter: Arc<ConsensusAdapter>,
     pub metrics: Arc<ValidatorServiceMetrics>,
+    admission_controller: Option<Arc<AdmissionController>>,
 }
 
 impl AuthorityServer {
@@ -108,9 +111,15 @@ impl AuthorityServer {
             state,
             consensus_adapter,
             metrics,
+            admission_controller: None,
         }
     }
 
+    pub fn with_admission_control_for_test(mut self, config: AdmissionControlConfig) -> Self {
+        self.admission_controller = Some(Arc::new(AdmissionController::new(config)));
+        self
+    }
+
     pub async fn spawn_for_test(self) -> Result<AuthorityServerHandle, io::Error> {
         let address = self.address.clone();
         self.spawn_with_bind_address_for_test(address).await
@@ -126,6 +135,7 @@ impl AuthorityServer {
                 state: self.state,
                 consensus_adapter: self.consensus_adapter,
                 metrics: self.metrics.clone(),
+                admission_controller: self.admission_controller,
             }))
             .bind(&address)
             .await
@@ -155,6 +165,8 @@ pub struct ValidatorServiceMetrics {
     num_rejected_cert_in_epoch_boundary: IntCounter,
     num_rejected_tx_during_overload: IntCounterVec,
     num_rejected_cert_during_overload: IntCounterVec,
+    num_rejected_tx_by_admission_control: IntCounterVec,
+    num_rejected_cert_by_admission_control: IntCounterVec,
 }
 
 impl ValidatorServiceMetrics {
@@ -227,6 +239,20 @@ impl ValidatorServiceMetrics {
                 registry,
             )
             .unwrap(),
+            num_rejected_tx_by_admission_control: register_int_counter_vec_with_registry!(
+                "validator_service_num_rejected_tx_by_admission_control",
+                "Number of rejected transaction due to the rate limit of a sender, client ip or shared object",
+                &["key_type"],
+                registry,
+            )
+            .unwrap(),
+            num_rejected_cert_by_admission_control: register_int_counter_vec_with_registry!(
+                "validator_service_num_rejected_cert_by_admission_control",
+                "Number of rejected transaction certificate due to the rate limit of a sender, client ip or shared object",
+                &["key_type"],
+                registry,
+            )
+            .unwrap(),
         }
     }
 
@@ -241,6 +267,7 @@ pub struct ValidatorService {
     state: Arc<AuthorityState>,
     consensus_adapter: Arc<ConsensusAdapter>,
     metrics: Arc<ValidatorServiceMetrics>,
+    admission_controller: Option<Arc<AdmissionController>>,
 }
 
 impl ValidatorService {
@@ -248,11 +275,13 @@ impl ValidatorService {
         state: Arc<AuthorityState>,
         consensus_adapter: Arc<ConsensusAdapter>,
         metrics: Arc<ValidatorServiceMetrics>,
+        admission_controller: Option<Arc<AdmissionController>>,
     ) -> Self {
         Self {
             state,
             consensus_adapter,
             metrics,
+            admission_controller,
         }
     }
 
@@ -288,8 +317,10 @@ impl ValidatorService {
             state,
             consensus_adapter,
             metrics,
+            admission_controller,
         } = self;
 
+        let client_ip = request.remote_addr().map(|addr| addr.ip());
         let transaction = request.into_inner();
 
         let epoch_store = state.load_epoch_store_one_call_per_task();
@@ -303,6 +334,13 @@ impl ValidatorService {
             .into());
         }
 
+        if !epoch_store.protocol_config().passkey_auth() && transaction.has_passkey_sig() {
+            return Err(MgoError::UnsupportedFeatureError {
+                error: "passkey is not enabled on this network".to_string(),
+            }
+            .into());
+        }
+
         if !epoch_store.protocol_config().supports_upgraded_multisig()
             && transaction.has_upgraded_multisig()
         {
@@ -312,6 +350,18 @@ impl ValidatorService {
             .into());
         }
 
+        // Only the client IP can be charged before the signatures are verified. The sender and
+        // shared objects are charged below, once the transaction is verified.
+        if let Some(admission_controller) = &admission_controller {
+            if let Err(rejection) = admission_controller.check_client_ip(client_ip) {
+                metrics
+                    .num_rejected_tx_by_admission_control
+                    .with_label_values(&[rejection.key.key_type()])
+                    .inc();
+                return Err(MgoError::from(rejection).into());
+            }
+        }
+
         let overload_check_res =
             state.check_system_overload(&consensus_adapter, transaction.data());
         if let Err(error) = overload_check_res {
@@ -330,6 +380,18 @@ impl ValidatorService {
         })?;
         drop(tx_verif_metrics_guard);
 
+        // Check the rate limits of the sender and shared objects once the transaction is
+        // verified, so that a forged transaction cannot use up the limits of another sender.
+        if let Some(admission_controller) = &admission_controller {
+            if let Err(rejection) = admission_controller.check_transaction(transaction.data()) {
+                metrics
+                    .num_rejected_tx_by_admission_control
+                    .with_label_values(&[rejection.key.key_type()])
+                    .inc();
+                return Err(MgoError::from(rejection).into());
+            }
+        }
+
         let tx_digest = transaction.digest();
 
         // Enable Trace Propagation across spans/processes using tx_digest
@@ -358,6 +420,7 @@ impl ValidatorService {
             state,
             consensus_adapter,
             metrics,
+            admission_controller,
         } = self;
 
         let epoch_store = state.load_epoch_store_one_call_per_task();
@@ -434,6 +497,23 @@ impl ValidatorService {
                     .await?
             };
 
+            // Check the rate limits of the sender once the cert is verified, so that a forged
+            // cert cannot use up the limit of another sender. Certs already sequenced by
+            // consensus are resubmitted to collect their effects, and are not charged again.
+            if let Some(admission_controller) = &admission_controller {
+                if !epoch_store.is_tx_cert_consensus_message_processed(&certificate)? {
+                    if let Err(rejection) =
+                        admission_controller.check_transaction(certificate.data())
+                    {
+                        metrics
+                            .num_rejected_cert_by_admission_control
+                            .with_label_values(&[rejection.key.key_type()])
+                            .inc();
+                        return Err(MgoError::from(rejection).into());
+                    }
+                }
+            }
+
             let reconfiguration_lock = epoch_store.get_reconfig_state_read_lock_guard();
             if !reconfiguration_lock.should_accept_user_certs() {
                 metrics.num_rejected_cert_in_epoch_boundary.inc();
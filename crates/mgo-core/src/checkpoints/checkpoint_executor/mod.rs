@@ -42,7 +42,10 @@ use mgo_types::{
 use mgo_types::{error::MgoResult, transaction::TransactionDataAPI};
 use tap::{TapFallible, TapOptional};
 use tokio::{
-    sync::broadcast::{self, error::RecvError},
+    sync::{
+        broadcast::{self, error::RecvError},
+        watch,
+    },
     task::JoinHandle,
     time::timeout,
 };
@@ -91,6 +94,9 @@ pub struct CheckpointExecutor {
     accumulator: Arc<StateAccumulator>,
     config: CheckpointExecutorConfig,
     metrics: Arc<CheckpointExecutorMetrics>,
+    // While true, no new checkpoints are scheduled for execution. Checkpoints already scheduled
+    // still complete.
+    paused: watch::Receiver<bool>,
 }
 
 impl CheckpointExecutor {
@@ -100,6 +106,7 @@ impl CheckpointExecutor {
         state: Arc<AuthorityState>,
         accumulator: Arc<StateAccumulator>,
         config: CheckpointExecutorConfig,
+        paused: watch::Receiver<bool>,
         prometheus_registry: &Registry,
     ) -> Self {
         Self {
@@ -111,6 +118,7 @@ impl CheckpointExecutor {
             accumulator,
             config,
             metrics: CheckpointExecutorMetrics::new(prometheus_registry),
+            paused,
         }
     }
 
@@ -129,6 +137,7 @@ impl CheckpointExecutor {
             accumulator,
             config: Default::default(),
             metrics: CheckpointExecutorMetrics::new_for_tests(),
+            paused: watch::channel(false).1,
         }
     }
 
@@ -204,14 +213,16 @@ impl CheckpointExecutor {
                 return StopReason::EpochComplete;
             }
 
-            self.schedule_synced_checkpoints(
-                &mut pending,
-                // next_to_schedule will be updated to the next checkpoint to schedule.
-                // This makes sure we don't re-schedule the same checkpoint multiple times.
-                &mut next_to_schedule,
-                epoch_store.clone(),
-                run_with_range,
-            );
+            if !*self.paused.borrow() {
+                self.schedule_synced_checkpoints(
+                    &mut pending,
+                    // next_to_schedule will be updated to the next checkpoint to schedule.
+                    // This makes sure we don't re-schedule the same checkpoint multiple times.
+                    &mut next_to_schedule,
+                    epoch_store.clone(),
+                    run_with_range,
+                );
+            }
 
             self.metrics
                 .checkpoint_exec_inflight
@@ -245,6 +256,14 @@ impl CheckpointExecutor {
                         return StopReason::RunWithRangeCondition;
                     }
                 }
+                // Resume scheduling as soon as execution is unpaused.
+                Ok(()) = self.paused.changed() => {
+                    info!(
+                        "Checkpoint execution {}, next checkpoint to be scheduled: {}",
+                        if *self.paused.borrow() { "paused" } else { "resumed" },
+                        next_to_schedule,
+                    );
+                }
                 // Check for newly synced checkpoints from StateSync.
                 received = timeout(scheduling_timeout, self.mailbox.recv()) => match received {
                     Err(_elapsed) => {
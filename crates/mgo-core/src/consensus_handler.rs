@@ -811,6 +811,7 @@ impl SequencedConsensusTransaction {
 mod tests {
     use super::*;
     use crate::authority::authority_per_epoch_store::{ConsensusStats, ConsensusStatsAPI};
+    use crate::authority::shared_object_congestion_tracker::SharedObjectCongestionTracker;
     use crate::authority::test_authority_builder::TestAuthorityBuilder;
     use crate::checkpoints::CheckpointServiceNoop;
     use crate::consensus_adapter::consensus_tests::{test_certificates, test_gas_objects};
@@ -823,8 +824,12 @@ mod tests {
     use prometheus::Registry;
     use shared_crypto::intent::Intent;
     use std::collections::BTreeSet;
-    use mgo_protocol_config::{ConsensusTransactionOrdering, SupportedProtocolVersions};
-    use mgo_types::base_types::{random_object_ref, AuthorityName, MgoAddress};
+    use move_core_types::identifier::Identifier;
+    use mgo_protocol_config::{
+        ConsensusTransactionOrdering, PerObjectCongestionControlMode, ProtocolConfig,
+        SupportedProtocolVersions,
+    };
+    use mgo_types::base_types::{random_object_ref, AuthorityName, MgoAddress, ObjectID};
     use mgo_types::committee::Committee;
     use mgo_types::messages_consensus::{
         AuthorityCapabilities, ConsensusTransaction, ConsensusTransactionKind,
@@ -832,7 +837,8 @@ mod tests {
     use mgo_types::object::Object;
     use mgo_types::mgo_system_state::epoch_start_mgo_system_state::EpochStartSystemStateTrait;
     use mgo_types::transaction::{
-        CertifiedTransaction, SenderSignedData, TransactionData, TransactionDataAPI,
+        CallArg, CertifiedTransaction, ObjectArg, SenderSignedData, TransactionData,
+        TransactionDataAPI, VerifiedCertificate,
     };
 
     #[tokio::test]
@@ -1047,6 +1053,175 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_order_by_sender_round_robin() {
+        let (a, b, c) = (
+            MgoAddress::random_for_testing_only(),
+            MgoAddress::random_for_testing_only(),
+            MgoAddress::random_for_testing_only(),
+        );
+        let mut v = vec![
+            shared_object_txn(a, 1, &[]),
+            shared_object_txn(a, 2, &[]),
+            shared_object_txn(a, 3, &[]),
+            cap_txn(10),
+            shared_object_txn(b, 4, &[]),
+            shared_object_txn(a, 5, &[]),
+            shared_object_txn(c, 6, &[]),
+            shared_object_txn(b, 7, &[]),
+        ];
+        PostConsensusTxReorder::reorder(&mut v, ConsensusTransactionOrdering::BySenderRoundRobin);
+        assert_eq!(
+            extract(v),
+            vec![
+                "cap(10)".to_string(),
+                "user(1)".to_string(),
+                "user(4)".to_string(),
+                "user(6)".to_string(),
+                "user(2)".to_string(),
+                "user(7)".to_string(),
+                "user(3)".to_string(),
+                "user(5)".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_order_by_gas_price_per_shared_object() {
+        let sender = MgoAddress::random_for_testing_only();
+        let (x, y, z) = (ObjectID::random(), ObjectID::random(), ObjectID::random());
+        let mut v = vec![
+            shared_object_txn(sender, 1, &[x]),
+            user_txn(2),
+            shared_object_txn(sender, 3, &[y]),
+            shared_object_txn(sender, 4, &[x]),
+            cap_txn(10),
+            shared_object_txn(sender, 5, &[y]),
+            // Joins the conflict sets of x and z.
+            shared_object_txn(sender, 6, &[z]),
+            shared_object_txn(sender, 7, &[x, z]),
+        ];
+        PostConsensusTxReorder::reorder(
+            &mut v,
+            ConsensusTransactionOrdering::ByGasPricePerSharedObject,
+        );
+        assert_eq!(
+            extract(v),
+            vec![
+                "user(7)".to_string(),
+                "user(2)".to_string(),
+                "user(5)".to_string(),
+                "user(6)".to_string(),
+                "cap(10)".to_string(),
+                "user(3)".to_string(),
+                "user(4)".to_string(),
+                "user(1)".to_string(),
+            ]
+        );
+
+        // Transactions without shared objects keep their order.
+        let mut v = vec![user_txn(1), cap_txn(10), user_txn(100), user_txn(42)];
+        PostConsensusTxReorder::reorder(
+            &mut v,
+            ConsensusTransactionOrdering::ByGasPricePerSharedObject,
+        );
+        assert_eq!(
+            extract(v),
+            vec![
+                "user(1)".to_string(),
+                "cap(10)".to_string(),
+                "user(100)".to_string(),
+                "user(42)".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sender_round_robin_with_gas_price_per_shared_object() {
+        let (a, b) = (
+            MgoAddress::random_for_testing_only(),
+            MgoAddress::random_for_testing_only(),
+        );
+        let x = ObjectID::random();
+        let txns = || {
+            vec![
+                shared_object_txn(a, 1, &[x]),
+                shared_object_txn(a, 2, &[]),
+                shared_object_txn(a, 3, &[]),
+                shared_object_txn(b, 5, &[x]),
+                shared_object_txn(b, 4, &[]),
+            ]
+        };
+        let mut protocol_config = ProtocolConfig::get_for_max_version_UNSAFE();
+        protocol_config.set_consensus_transaction_ordering_for_testing(
+            ConsensusTransactionOrdering::ByGasPricePerSharedObject,
+        );
+
+        protocol_config.set_consensus_sender_round_robin_for_testing(false);
+        let mut v = txns();
+        PostConsensusTxReorder::reorder_for_protocol(&mut v, &protocol_config);
+        assert_eq!(
+            extract(v),
+            vec![
+                "user(5)".to_string(),
+                "user(2)".to_string(),
+                "user(3)".to_string(),
+                "user(1)".to_string(),
+                "user(4)".to_string(),
+            ]
+        );
+
+        // Senders are interleaved first, then the transactions on x are ordered by gas price
+        // within the positions they occupy after the interleaving.
+        protocol_config.set_consensus_sender_round_robin_for_testing(true);
+        let mut v = txns();
+        PostConsensusTxReorder::reorder_for_protocol(&mut v, &protocol_config);
+        assert_eq!(
+            extract(v),
+            vec![
+                "user(5)".to_string(),
+                "user(1)".to_string(),
+                "user(2)".to_string(),
+                "user(4)".to_string(),
+                "user(3)".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shared_object_congestion_tracker() {
+        let sender = MgoAddress::random_for_testing_only();
+        let (x, y) = (ObjectID::random(), ObjectID::random());
+        let transactions = [
+            executable_txn(shared_object_txn(sender, 1, &[x])),
+            executable_txn(shared_object_txn(sender, 1, &[x, y])),
+            executable_txn(shared_object_txn(sender, 1, &[x])),
+            executable_txn(shared_object_txn(sender, 1, &[y])),
+            executable_txn(shared_object_txn(sender, 1, &[y])),
+            executable_txn(shared_object_txn(sender, 1, &[x, y])),
+        ];
+
+        let mut tracker = SharedObjectCongestionTracker::new(
+            PerObjectCongestionControlMode::TransactionCount,
+            Some(2),
+        );
+        let mut deferred = vec![];
+        for txn in transactions.iter() {
+            match tracker.should_defer_due_to_object_congestion(txn) {
+                Some(congested_objects) => deferred.push(congested_objects),
+                None => tracker.bump_object_execution_count(txn),
+            }
+        }
+        assert_eq!(deferred, vec![vec![x], vec![y], vec![x, y]]);
+
+        let mut tracker =
+            SharedObjectCongestionTracker::new(PerObjectCongestionControlMode::None, None);
+        for txn in transactions.iter() {
+            assert!(tracker.should_defer_due_to_object_congestion(txn).is_none());
+            tracker.bump_object_execution_count(txn);
+        }
+    }
+
     fn extract(v: Vec<VerifiedSequencedConsensusTransaction>) -> Vec<String> {
         v.into_iter().map(extract_one).collect()
     }
@@ -1105,6 +1280,55 @@ mod tests {
         )))
     }
 
+    fn shared_object_txn(
+        sender: MgoAddress,
+        gas_price: u64,
+        shared_objects: &[ObjectID],
+    ) -> VerifiedSequencedConsensusTransaction {
+        let (committee, keypairs) = Committee::new_simple_test_committee();
+        let arguments = shared_objects
+            .iter()
+            .map(|id| {
+                CallArg::Object(ObjectArg::SharedObject {
+                    id: *id,
+                    initial_shared_version: 1.into(),
+                    mutable: true,
+                })
+            })
+            .collect();
+        let data = SenderSignedData::new(
+            TransactionData::new_move_call(
+                sender,
+                ObjectID::random(),
+                Identifier::new("pool").unwrap(),
+                Identifier::new("swap").unwrap(),
+                vec![],
+                random_object_ref(),
+                arguments,
+                1000 * gas_price,
+                gas_price,
+            )
+            .unwrap(),
+            Intent::mgo_transaction(),
+            vec![],
+        );
+        txn(ConsensusTransactionKind::UserTransaction(Box::new(
+            CertifiedTransaction::new_from_keypairs_for_testing(data, &keypairs, &committee),
+        )))
+    }
+
+    fn executable_txn(txn: VerifiedSequencedConsensusTransaction) -> VerifiedExecutableTransaction {
+        match txn.0.transaction {
+            SequencedConsensusTransactionKind::External(ConsensusTransaction {
+                kind: ConsensusTransactionKind::UserTransaction(cert),
+                ..
+            }) => VerifiedExecutableTransaction::new_from_certificate(
+                VerifiedCertificate::new_unchecked(*cert),
+            ),
+            _ => unreachable!(),
+        }
+    }
+
     fn txn(kind: ConsensusTransactionKind) -> VerifiedSequencedConsensusTransaction {
         VerifiedSequencedConsensusTransaction::new_test(ConsensusTransaction {
             kind,
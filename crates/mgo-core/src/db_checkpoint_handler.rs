@@ -20,6 +20,10 @@ use std::time::Duration;
 use mgo_config::node::AuthorityStorePruningConfig;
 use mgo_config::object_storage_config::{ObjectStoreConfig, ObjectStoreType};
 use mgo_storage::mutex_table::RwLockTable;
+use mgo_storage::object_store::db_checkpoint::{
+    find_previous_db_checkpoint_manifest, garbage_collect_db_checkpoint_files,
+    upload_incremental_db_checkpoint,
+};
 use mgo_storage::object_store::util::{
     copy_recursively, find_all_dirs_with_epoch_prefix, find_missing_epochs_dirs,
     path_to_filesystem, put, run_manifest_update_loop, write_snapshot_manifest,
@@ -67,6 +71,9 @@ pub struct DBCheckpointHandler {
     indirect_objects_threshold: usize,
     /// If true, upload will block on state snapshot upload completed marker
     state_snapshot_enabled: bool,
+    /// If true, only files which are not in the previous db checkpoint are uploaded and each
+    /// db checkpoint is described by a manifest
+    incremental_upload: bool,
     /// Pruning objects
     pruning_config: AuthorityStorePruningConfig,
     metrics: Arc<DBCheckpointMetrics>,
@@ -82,6 +89,7 @@ impl DBCheckpointHandler {
         pruning_config: AuthorityStorePruningConfig,
         registry: &Registry,
         state_snapshot_enabled: bool,
+        incremental_upload: bool,
     ) -> Result<Arc<Self>> {
         let input_store_config = ObjectStoreConfig {
             object_store: Some(ObjectStoreType::File),
@@ -102,6 +110,7 @@ impl DBCheckpointHandler {
             prune_and_compact_before_upload,
             indirect_objects_threshold,
             state_snapshot_enabled,
+            incremental_upload,
             pruning_config,
             metrics: DBCheckpointMetrics::new(registry),
         }))
@@ -112,6 +121,7 @@ impl DBCheckpointHandler {
         interval_s: u64,
         prune_and_compact_before_upload: bool,
         state_snapshot_enabled: bool,
+        incremental_upload: bool,
     ) -> Result<Arc<Self>> {
         Ok(Arc::new(DBCheckpointHandler {
             input_object_store: input_object_store_config.make()?,
@@ -127,6 +137,7 @@ impl DBCheckpointHandler {
             prune_and_compact_before_upload,
             indirect_objects_threshold: 0,
             state_snapshot_enabled,
+            incremental_upload,
             pruning_config: AuthorityStorePruningConfig::default(),
             metrics: DBCheckpointMetrics::new(&Registry::default()),
         }))
@@ -282,6 +293,7 @@ impl DBCheckpointHandler {
             .as_ref()
             .expect("Expected object store to exist")
             .clone();
+        let mut uploaded_incremental = false;
         for (epoch, db_path) in dirs {
             // Convert `db_path` to the local filesystem path to where db checkpoint is stored
             let local_db_path = path_to_filesystem(self.input_root_path.clone(), db_path)?;
@@ -300,18 +312,36 @@ impl DBCheckpointHandler {
                     self.prune_and_compact(local_db_path, *epoch).await?;
                 }
 
-                info!("Copying db checkpoint for epoch: {epoch} to remote storage");
-                copy_recursively(
-                    db_path,
-                    &self.input_object_store,
-                    &object_store,
-                    NonZeroUsize::new(20).unwrap(),
-                )
-                .await?;
-
-                // This writes a single "MANIFEST" file which contains a list of all files that make up a db snapshot
-                write_snapshot_manifest(db_path, &object_store, format!("epoch_{}/", epoch))
+                if self.incremental_upload {
+                    info!(
+                        "Uploading incremental db checkpoint for epoch: {epoch} to remote storage"
+                    );
+                    let previous =
+                        find_previous_db_checkpoint_manifest(&object_store, *epoch).await?;
+                    upload_incremental_db_checkpoint(
+                        db_path,
+                        *epoch,
+                        &self.input_object_store,
+                        &object_store,
+                        previous.as_ref(),
+                        NonZeroUsize::new(20).unwrap(),
+                    )
                     .await?;
+                    uploaded_incremental = true;
+                } else {
+                    info!("Copying db checkpoint for epoch: {epoch} to remote storage");
+                    copy_recursively(
+                        db_path,
+                        &self.input_object_store,
+                        &object_store,
+                        NonZeroUsize::new(20).unwrap(),
+                    )
+                    .await?;
+
+                    // This writes a single "MANIFEST" file which contains a list of all files that make up a db snapshot
+                    write_snapshot_manifest(db_path, &object_store, format!("epoch_{}/", epoch))
+                        .await?;
+                }
                 // Drop marker in the output directory that upload completed successfully
                 let bytes = Bytes::from_static(b"success");
                 let success_marker = db_path.child(SUCCESS_MARKER);
@@ -326,6 +356,12 @@ impl DBCheckpointHandler {
             )
             .await?;
         }
+        if uploaded_incremental {
+            // Files of db checkpoints deleted from the remote store or of interrupted uploads
+            // are no longer referenced by any manifest
+            garbage_collect_db_checkpoint_files(&object_store, NonZeroUsize::new(20).unwrap())
+                .await?;
+        }
         Ok(())
     }
 
@@ -371,6 +407,9 @@ mod tests {
     use itertools::Itertools;
     use std::fs;
     use mgo_config::object_storage_config::{ObjectStoreConfig, ObjectStoreType};
+    use mgo_storage::object_store::db_checkpoint::{
+        read_db_checkpoint_manifest, DB_CHECKPOINT_FILES_DIR, DB_CHECKPOINT_MANIFEST_FILENAME,
+    };
     use mgo_storage::object_store::util::{
         find_all_dirs_with_epoch_prefix, find_missing_epochs_dirs, path_to_filesystem,
     };
@@ -411,6 +450,7 @@ mod tests {
             10,
             false,
             false,
+            false,
         )?;
         let local_checkpoints_by_epoch =
             find_all_dirs_with_epoch_prefix(&db_checkpoint_handler.input_object_store, None)
@@ -482,6 +522,7 @@ mod tests {
             10,
             false,
             false,
+            false,
         )?;
 
         fs::create_dir(&local_epoch0_checkpoint)?;
@@ -604,6 +645,7 @@ mod tests {
             10,
             false,
             false,
+            false,
         )?;
 
         let missing_epochs = find_missing_epochs_dirs(
@@ -669,6 +711,7 @@ mod tests {
             10,
             false,
             false,
+            false,
         )?;
 
         let missing_epochs = find_missing_epochs_dirs(
@@ -692,4 +735,78 @@ mod tests {
         assert_eq!(missing_epochs, expected_missing_epochs);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_incremental_upload() -> anyhow::Result<()> {
+        let checkpoint_dir = TempDir::new()?;
+        let checkpoint_dir_path = checkpoint_dir.path();
+        let local_epoch0_checkpoint = checkpoint_dir_path.join("epoch_0");
+        fs::create_dir(&local_epoch0_checkpoint)?;
+        fs::write(local_epoch0_checkpoint.join("000001.sst"), b"Lorem ipsum")?;
+        fs::write(local_epoch0_checkpoint.join("CURRENT"), b"MANIFEST-000001")?;
+        let local_epoch1_checkpoint = checkpoint_dir_path.join("epoch_1");
+        fs::create_dir(&local_epoch1_checkpoint)?;
+        fs::write(local_epoch1_checkpoint.join("000001.sst"), b"Lorem ipsum")?;
+        fs::write(
+            local_epoch1_checkpoint.join("000002.sst"),
+            b"dolor sit amet",
+        )?;
+        fs::write(local_epoch1_checkpoint.join("CURRENT"), b"MANIFEST-000002")?;
+
+        let remote_checkpoint_dir = TempDir::new()?;
+        let remote_checkpoint_dir_path = remote_checkpoint_dir.path();
+
+        let input_store_config = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(checkpoint_dir_path.to_path_buf()),
+            ..Default::default()
+        };
+        let output_store_config = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(remote_checkpoint_dir_path.to_path_buf()),
+            ..Default::default()
+        };
+        let db_checkpoint_handler = DBCheckpointHandler::new_for_test(
+            &input_store_config,
+            Some(&output_store_config),
+            10,
+            false,
+            false,
+            true,
+        )?;
+
+        let missing_epochs = find_missing_epochs_dirs(
+            db_checkpoint_handler.output_object_store.as_ref().unwrap(),
+            SUCCESS_MARKER,
+        )
+        .await?;
+        db_checkpoint_handler
+            .upload_db_checkpoints_to_object_store(missing_epochs)
+            .await?;
+
+        for epoch in 0..2 {
+            let remote_checkpoint = remote_checkpoint_dir_path.join(format!("epoch_{epoch}"));
+            assert!(remote_checkpoint.join(SUCCESS_MARKER).exists());
+            assert!(remote_checkpoint
+                .join(DB_CHECKPOINT_MANIFEST_FILENAME)
+                .exists());
+            // Files are only stored in the shared files directory
+            assert!(!remote_checkpoint.join("000001.sst").exists());
+        }
+        let manifest = read_db_checkpoint_manifest(
+            db_checkpoint_handler.output_object_store.as_ref().unwrap(),
+            1,
+        )
+        .await?;
+        assert_eq!(manifest.files.len(), 3);
+        // 000001.sst is shared by both db checkpoints
+        assert_eq!(
+            fs::read_dir(remote_checkpoint_dir_path.join(DB_CHECKPOINT_FILES_DIR))?.count(),
+            4
+        );
+        assert!(local_epoch1_checkpoint
+            .join(UPLOAD_COMPLETED_MARKER)
+            .exists());
+        Ok(())
+    }
 }
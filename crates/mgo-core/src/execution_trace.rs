@@ -0,0 +1,170 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decodes the values in an [`ExecutionTrace`] into JSON. The execution layer only records BCS,
+//! because it does not know the layouts of the values it sees, so their types are resolved here,
+//! against the packages the transaction loaded.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use mgo_json::MgoJsonValue;
+use mgo_package_resolver::error::Error;
+use mgo_package_resolver::{Package, PackageStore, Resolver, Result};
+use mgo_types::base_types::SequenceNumber;
+use mgo_types::execution_trace::{ExecutionTrace, FrameTrace, TracedValue};
+use mgo_types::inner_temporary_store::InnerTemporaryStore;
+use mgo_types::object::Object;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::TypeTag;
+
+/// Fills in the `json` of the values in `trace` whose types can be resolved, from the packages the
+/// transaction loaded or wrote, as recorded in `inner_temporary_store`. Values whose types cannot
+/// be resolved are left as BCS only.
+pub async fn decode_execution_trace(
+    trace: &mut ExecutionTrace,
+    inner_temporary_store: &InnerTemporaryStore,
+) {
+    let resolver = Resolver::new(TracePackageStore::new(inner_temporary_store));
+
+    for command in &mut trace.commands {
+        let mut frames: Vec<&mut FrameTrace> = command.calls.iter_mut().collect();
+        while let Some(frame) = frames.pop() {
+            decode_frame(&resolver, frame).await;
+            frames.extend(frame.calls.iter_mut());
+        }
+
+        for event in &mut command.events {
+            let tag = TypeTag::Struct(Box::new(event.type_.clone()));
+            decode_value(&resolver, Ok(tag), &mut event.contents).await;
+        }
+    }
+
+    for access in &mut trace.objects {
+        let tag = TypeTag::Struct(Box::new(access.type_.clone()));
+        for object in [&mut access.before, &mut access.after]
+            .into_iter()
+            .flatten()
+        {
+            decode_value(&resolver, Ok(tag.clone()), &mut object.contents).await;
+        }
+    }
+}
+
+async fn decode_frame(resolver: &Resolver<TracePackageStore>, frame: &mut FrameTrace) {
+    let Some(package) = resolver.package_store().by_runtime_id(frame.package.into()) else {
+        return;
+    };
+
+    let Ok(Some(function)) = package
+        .module(&frame.module)
+        .and_then(|module| module.function_def(&frame.function))
+    else {
+        return;
+    };
+
+    // References are traced as the value they refer to, so only the body of the signature matters.
+    let values = frame.arguments.iter_mut().zip(&function.parameters);
+    let returns = frame.return_values.iter_mut().zip(&function.return_);
+    for (value, signature) in values.chain(returns) {
+        let tag = signature.body.instantiate(&package, &frame.type_arguments);
+        decode_value(resolver, tag, value).await;
+    }
+}
+
+async fn decode_value(
+    resolver: &Resolver<TracePackageStore>,
+    tag: Result<TypeTag>,
+    value: &mut TracedValue,
+) {
+    let Ok(tag) = tag else {
+        return;
+    };
+
+    let Ok(layout) = resolver.type_layout(tag).await else {
+        return;
+    };
+
+    value.json = MgoJsonValue::from_bcs_bytes(Some(&layout), &value.bcs)
+        .ok()
+        .map(|json| json.to_json_value());
+}
+
+/// The packages a transaction loaded or wrote, by storage ID, and by runtime ID (at the latest
+/// version the transaction saw).
+struct TracePackageStore {
+    packages: BTreeMap<AccountAddress, (SequenceNumber, Arc<Package>)>,
+    runtime_packages: BTreeMap<AccountAddress, Arc<Package>>,
+}
+
+impl TracePackageStore {
+    fn new(inner_temporary_store: &InnerTemporaryStore) -> Self {
+        let objects = inner_temporary_store
+            .runtime_packages_loaded_from_db
+            .values()
+            .map(|package| package.object())
+            .chain(inner_temporary_store.written.values());
+
+        let mut packages = BTreeMap::new();
+        let mut runtime_packages: BTreeMap<AccountAddress, (SequenceNumber, Arc<Package>)> =
+            BTreeMap::new();
+
+        for object in objects {
+            let Some(runtime_id) = runtime_id(object) else {
+                continue;
+            };
+
+            let Ok(package) = Package::read(object) else {
+                continue;
+            };
+
+            let version = object.version();
+            let package = Arc::new(package);
+            packages.insert(object.id().into(), (version, package.clone()));
+
+            match runtime_packages.get(&runtime_id) {
+                Some((latest, _)) if *latest >= version => {}
+                _ => {
+                    runtime_packages.insert(runtime_id, (version, package));
+                }
+            }
+        }
+
+        Self {
+            packages,
+            runtime_packages: runtime_packages
+                .into_iter()
+                .map(|(id, (_, package))| (id, package))
+                .collect(),
+        }
+    }
+
+    fn by_runtime_id(&self, runtime_id: AccountAddress) -> Option<Arc<Package>> {
+        self.runtime_packages.get(&runtime_id).cloned()
+    }
+}
+
+fn runtime_id(object: &Object) -> Option<AccountAddress> {
+    let package = object.data.try_as_package()?;
+    Some(package.original_package_id().into())
+}
+
+#[async_trait]
+impl PackageStore for TracePackageStore {
+    async fn version(&self, id: AccountAddress) -> Result<SequenceNumber> {
+        let Some((version, _)) = self.packages.get(&id) else {
+            return Err(Error::PackageNotFound(id));
+        };
+
+        Ok(*version)
+    }
+
+    async fn fetch(&self, id: AccountAddress) -> Result<Arc<Package>> {
+        let Some((_, package)) = self.packages.get(&id) else {
+            return Err(Error::PackageNotFound(id));
+        };
+
+        Ok(package.clone())
+    }
+}
@@ -4,6 +4,7 @@
 
 extern crate core;
 
+pub mod admission_control;
 pub mod authority;
 pub mod authority_aggregator;
 pub mod authority_client;
@@ -18,6 +19,7 @@ pub mod consensus_validator;
 pub mod db_checkpoint_handler;
 pub mod epoch;
 mod execution_driver;
+pub mod execution_trace;
 pub mod in_mem_execution_cache;
 pub mod metrics;
 pub mod module_cache_metrics;
@@ -5,40 +5,168 @@ use crate::consensus_handler::{
     SequencedConsensusTransactionKind, VerifiedSequencedConsensusTransaction,
 };
 use mango_metrics::monitored_scope;
-use mgo_protocol_config::ConsensusTransactionOrdering;
+use mgo_protocol_config::{ConsensusTransactionOrdering, ProtocolConfig};
+use mgo_types::base_types::{MgoAddress, ObjectID};
 use mgo_types::messages_consensus::{ConsensusTransaction, ConsensusTransactionKind};
+use mgo_types::transaction::CertifiedTransaction;
+use std::collections::{BTreeMap, HashMap};
 
 pub struct PostConsensusTxReorder {}
 
 impl PostConsensusTxReorder {
+    /// Applies the orderings enabled by the protocol config. Senders are interleaved first, and
+    /// the configured ordering is applied on top of that. Every ordering is stable, so
+    /// transactions it does not move apart keep their round-robin order.
+    pub fn reorder_for_protocol(
+        transactions: &mut [VerifiedSequencedConsensusTransaction],
+        protocol_config: &ProtocolConfig,
+    ) {
+        if protocol_config.consensus_sender_round_robin() {
+            Self::reorder(
+                transactions,
+                ConsensusTransactionOrdering::BySenderRoundRobin,
+            );
+        }
+        Self::reorder(
+            transactions,
+            protocol_config.consensus_transaction_ordering(),
+        );
+    }
+
     pub fn reorder(
         transactions: &mut [VerifiedSequencedConsensusTransaction],
         kind: ConsensusTransactionOrdering,
     ) {
-        // TODO: make the reordering algorithm richer and depend on object hotness as well.
-        // Order transactions based on their gas prices. System transactions without gas price
-        // are put to the beginning of the sequenced_transactions vector.
+        // Every strategy must be deterministic, since all validators have to schedule the
+        // transactions of a commit in the same order.
         match kind {
             ConsensusTransactionOrdering::ByGasPrice => Self::order_by_gas_price(transactions),
+            ConsensusTransactionOrdering::BySenderRoundRobin => {
+                Self::order_by_sender_round_robin(transactions)
+            }
+            ConsensusTransactionOrdering::ByGasPricePerSharedObject => {
+                Self::order_by_gas_price_per_shared_object(transactions)
+            }
             ConsensusTransactionOrdering::None => (),
         }
     }
 
     fn order_by_gas_price(transactions: &mut [VerifiedSequencedConsensusTransaction]) {
         let _scope = monitored_scope("HandleConsensusOutput::order_by_gas_price");
-        transactions.sort_by_key(|txn| {
-            // Reverse order, so that transactions with higher gas price are put to the beginning.
-            std::cmp::Reverse({
-                match &txn.0.transaction {
-                    SequencedConsensusTransactionKind::External(ConsensusTransaction {
-                        tracking_id: _,
-                        kind: ConsensusTransactionKind::UserTransaction(cert),
-                    }) => cert.gas_price(),
-                    // Non-user transactions are considered to have gas price of MAX u64 and are
-                    // put to the beginning.
-                    _ => u64::MAX,
-                }
+        // Reverse order, so that transactions with higher gas price are put to the beginning.
+        transactions.sort_by_key(|txn| std::cmp::Reverse(gas_price(txn)))
+    }
+
+    /// Gives every sender a turn before any sender gets a second one, so a single sender cannot
+    /// crowd the beginning of the commit. Non-user transactions are put to the beginning.
+    fn order_by_sender_round_robin(transactions: &mut [VerifiedSequencedConsensusTransaction]) {
+        let _scope = monitored_scope("HandleConsensusOutput::order_by_sender_round_robin");
+        // Senders are ranked by their first transaction in the sequenced order.
+        let mut senders: HashMap<MgoAddress, (usize, usize)> = HashMap::new();
+        let keys: Vec<_> = transactions
+            .iter()
+            .map(|txn| {
+                let cert = user_transaction(txn)?;
+                let next_rank = senders.len();
+                let (rank, count) = senders
+                    .entry(cert.sender_address())
+                    .or_insert((next_rank, 0));
+                let turn = *count;
+                *count += 1;
+                Some((turn, *rank))
             })
-        })
+            .collect();
+        // None sorts first, and the stable sort keeps the order of transactions with equal keys.
+        let mut order: Vec<usize> = (0..transactions.len()).collect();
+        order.sort_by_key(|i| keys[*i]);
+        apply_permutation(transactions, order);
+    }
+
+    /// Transactions touching a common shared object, directly or through other transactions, form
+    /// a conflict set. Within each conflict set transactions are ordered by gas price, highest
+    /// first, reusing the positions the set already occupies. All other transactions keep their
+    /// positions.
+    fn order_by_gas_price_per_shared_object(
+        transactions: &mut [VerifiedSequencedConsensusTransaction],
+    ) {
+        let _scope = monitored_scope("HandleConsensusOutput::order_by_gas_price_per_shared_object");
+        // Union-find over transaction indices, joining transactions that touch the same object.
+        let mut parents: Vec<usize> = (0..transactions.len()).collect();
+        let mut object_owners: HashMap<ObjectID, usize> = HashMap::new();
+        let mut shared_object_txns = vec![];
+        for (i, txn) in transactions.iter().enumerate() {
+            let Some(cert) = user_transaction(txn) else {
+                continue;
+            };
+            let mut shared_objects = cert.shared_input_objects().peekable();
+            if shared_objects.peek().is_none() {
+                continue;
+            }
+            shared_object_txns.push(i);
+            for object in shared_objects {
+                let owner = *object_owners.entry(object.id()).or_insert(i);
+                let (a, b) = (find(&mut parents, owner), find(&mut parents, i));
+                // Keep the smallest index as root, so the result does not depend on hashing.
+                parents[a.max(b)] = a.min(b);
+            }
+        }
+
+        let mut conflict_sets: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for i in shared_object_txns {
+            let root = find(&mut parents, i);
+            conflict_sets.entry(root).or_default().push(i);
+        }
+
+        let mut order: Vec<usize> = (0..transactions.len()).collect();
+        for positions in conflict_sets.into_values() {
+            let mut sorted = positions.clone();
+            sorted.sort_by_key(|i| std::cmp::Reverse(gas_price(&transactions[*i])));
+            for (position, source) in positions.into_iter().zip(sorted) {
+                order[position] = source;
+            }
+        }
+        apply_permutation(transactions, order);
+    }
+}
+
+fn user_transaction(txn: &VerifiedSequencedConsensusTransaction) -> Option<&CertifiedTransaction> {
+    match &txn.0.transaction {
+        SequencedConsensusTransactionKind::External(ConsensusTransaction {
+            tracking_id: _,
+            kind: ConsensusTransactionKind::UserTransaction(cert),
+        }) => Some(cert),
+        _ => None,
+    }
+}
+
+fn gas_price(txn: &VerifiedSequencedConsensusTransaction) -> u64 {
+    // Non-user transactions are considered to have gas price of MAX u64 and are put to the
+    // beginning.
+    user_transaction(txn).map_or(u64::MAX, |cert| cert.gas_price())
+}
+
+fn find(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+/// Moves `transactions[order[i]]` to position `i`, following each cycle of the permutation with
+/// swaps so that transactions do not need to be cloned.
+fn apply_permutation(
+    transactions: &mut [VerifiedSequencedConsensusTransaction],
+    mut order: Vec<usize>,
+) {
+    for start in 0..order.len() {
+        let mut current = start;
+        while order[current] != start {
+            let next = order[current];
+            transactions.swap(current, next);
+            order[current] = current;
+            current = next;
+        }
+        order[current] = current;
     }
 }
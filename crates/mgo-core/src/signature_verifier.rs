@@ -25,6 +25,8 @@ use mgo_types::{
     error::{MgoError, MgoResult},
     message_envelope::{AuthenticatedMessage, Message},
     messages_checkpoint::SignedCheckpointSummary,
+    multisig::{NestedMultiSigLimits, MAX_MULTISIG_NESTING_DEPTH},
+    passkey_authenticator::PasskeyRelyingParties,
     signature::VerifyParams,
     transaction::{CertifiedTransaction, VerifiedCertificate},
 };
@@ -105,6 +107,12 @@ pub struct SignatureVerifier {
     /// Params that contains a list of supported providers for ZKLogin and the environment (prod/test) the code runs in.
     zk_login_params: ZkLoginParams,
 
+    /// Limits for verifying multisigs with multisig members, which are rejected if not set.
+    nested_multisig_limits: Option<NestedMultiSigLimits>,
+
+    /// The relying parties and origins passkey assertions are accepted for.
+    passkey_relying_parties: PasskeyRelyingParties,
+
     queue: Mutex<CertBuffer>,
     pub metrics: Arc<SignatureVerifierMetrics>,
 }
@@ -129,6 +137,8 @@ impl SignatureVerifier {
         env: ZkLoginEnv,
         verify_legacy_zklogin_address: bool,
         accept_zklogin_in_multisig: bool,
+        nested_multisig_limits: Option<NestedMultiSigLimits>,
+        passkey_relying_parties: PasskeyRelyingParties,
     ) -> Self {
         Self {
             committee,
@@ -156,6 +166,8 @@ impl SignatureVerifier {
                 verify_legacy_zklogin_address,
                 accept_zklogin_in_multisig,
             },
+            nested_multisig_limits,
+            passkey_relying_parties,
         }
     }
 
@@ -166,6 +178,8 @@ impl SignatureVerifier {
         zklogin_env: ZkLoginEnv,
         verify_legacy_zklogin_address: bool,
         accept_zklogin_in_multisig: bool,
+        nested_multisig_limits: Option<NestedMultiSigLimits>,
+        passkey_relying_parties: PasskeyRelyingParties,
     ) -> Self {
         Self::new_with_batch_size(
             committee,
@@ -175,6 +189,8 @@ impl SignatureVerifier {
             zklogin_env,
             verify_legacy_zklogin_address,
             accept_zklogin_in_multisig,
+            nested_multisig_limits,
+            passkey_relying_parties,
         )
     }
 
@@ -354,13 +370,17 @@ impl SignatureVerifier {
             || {
                 signed_tx.verify_epoch(self.committee.epoch())?;
                 let jwks = self.jwks.read().clone();
-                let verify_params = VerifyParams::new(
-                    jwks,
-                    self.zk_login_params.supported_providers.clone(),
-                    self.zk_login_params.env.clone(),
-                    self.zk_login_params.verify_legacy_zklogin_address,
-                    self.zk_login_params.accept_zklogin_in_multisig,
-                );
+                let verify_params = VerifyParams {
+                    nested_multisig_limits: self.nested_multisig_limits,
+                    passkey_relying_parties: Some(self.passkey_relying_parties.clone()),
+                    ..VerifyParams::new(
+                        jwks,
+                        self.zk_login_params.supported_providers.clone(),
+                        self.zk_login_params.env.clone(),
+                        self.zk_login_params.verify_legacy_zklogin_address,
+                        self.zk_login_params.accept_zklogin_in_multisig,
+                    )
+                };
                 signed_tx.verify_message_signature(&verify_params)
             },
             || Ok(()),
@@ -503,13 +523,19 @@ pub fn batch_verify_certificates(
     certs: &[CertifiedTransaction],
 ) -> Vec<MgoResult> {
     // certs.data() is assumed to be verified already by the caller.
-    let verify_params = VerifyParams::new(
-        Default::default(),
-        Vec::new(),
-        Default::default(),
-        true,
-        true,
-    );
+    let verify_params = VerifyParams {
+        nested_multisig_limits: Some(NestedMultiSigLimits {
+            max_depth: MAX_MULTISIG_NESTING_DEPTH,
+            max_signatures: u32::MAX,
+        }),
+        ..VerifyParams::new(
+            Default::default(),
+            Vec::new(),
+            Default::default(),
+            true,
+            true,
+        )
+    };
     match batch_verify(committee, certs, &[]) {
         Ok(_) => vec![Ok(()); certs.len()],
 
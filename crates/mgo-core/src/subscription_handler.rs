@@ -1,21 +1,29 @@
 // Copyright (c) MangoNet Labs Ltd.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::HashSet;
+use std::future::Future;
+use std::hash::Hash;
 use std::sync::Arc;
 
+use futures::StreamExt;
+use mango_metrics::spawn_monitored_task;
 use prometheus::{
     register_int_counter_vec_with_registry, register_int_gauge_vec_with_registry, IntCounterVec,
     IntGaugeVec, Registry,
 };
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::Stream;
 use tracing::{error, instrument, trace};
 
 use crate::streamer::Streamer;
 use mgo_json_rpc_types::{
     EffectsWithInput, EventFilter, MgoTransactionBlockEffects, MgoTransactionBlockEvents,
-    TransactionFilter,
+    SubscriptionGap, SubscriptionItem, TransactionFilter,
 };
 use mgo_json_rpc_types::{MgoEvent, MgoTransactionBlockEffectsAPI};
+use mgo_types::base_types::TxSequenceNumber;
 use mgo_types::error::MgoResult;
 use mgo_types::transaction::TransactionData;
 
@@ -25,6 +33,20 @@ mod subscription_handler_tests;
 
 pub const EVENT_DISPATCH_BUFFER_SIZE: usize = 1000;
 
+/// A position in the event and transaction indexes, as a (transaction sequence number, event
+/// sequence number) pair.
+pub type BackfillPosition = (TxSequenceNumber, usize);
+
+/// A page of items backfilled from the indexes for a resumable subscription.
+#[derive(Debug)]
+pub struct BackfillPage<T> {
+    pub items: Vec<SubscriptionItem<T>>,
+    /// Where the next page starts.
+    pub next: BackfillPosition,
+    /// Whether the page reached the end of the indexes.
+    pub caught_up: bool,
+}
+
 pub struct SubscriptionMetrics {
     pub streaming_success: IntCounterVec,
     pub streaming_failure: IntCounterVec,
@@ -119,3 +141,82 @@ impl SubscriptionHandler {
         self.transaction_streamer.subscribe(filter)
     }
 }
+
+/// Streams the items of a resumable subscription: first the items backfilled from the indexes
+/// from `start` on, then the live items of the stream returned by `subscribe`.
+///
+/// The live stream is only subscribed to once the backfill has caught up with the indexes, so that
+/// it doesn't overflow while the backfill runs. The indexes are then read once more to pick up the
+/// items committed in between, and live items already sent by that last backfill are skipped.
+pub fn backfill_then_subscribe<T, K, E, S, Start, Fetch, Fut, Subscribe>(
+    start: Start,
+    mut fetch_page: Fetch,
+    subscribe: Subscribe,
+    key: fn(&T) -> K,
+) -> impl Stream<Item = Result<SubscriptionItem<T>, E>>
+where
+    T: Send + 'static,
+    K: Eq + Hash + Send,
+    E: Send + 'static,
+    S: Stream<Item = T> + Unpin + Send,
+    Start: Future<Output = Result<(BackfillPosition, Option<SubscriptionGap>), E>> + Send + 'static,
+    Fetch: FnMut(BackfillPosition) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<BackfillPage<T>, E>> + Send,
+    Subscribe: FnOnce() -> S + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(EVENT_DISPATCH_BUFFER_SIZE);
+    spawn_monitored_task!(async move {
+        let (mut position, gap) = match start.await {
+            Ok(start) => start,
+            Err(e) => {
+                let _ = tx.send(Err(e)).await;
+                return;
+            }
+        };
+        if let Some(gap) = gap {
+            if tx.send(Ok(SubscriptionItem::Gap(gap))).await.is_err() {
+                return;
+            }
+        }
+
+        let mut live = None;
+        let mut sent = HashSet::new();
+        loop {
+            let page = match fetch_page(position).await {
+                Ok(page) => page,
+                Err(e) => {
+                    let _ = tx.send(Err(e)).await;
+                    return;
+                }
+            };
+            for item in page.items {
+                if let (Some(_), SubscriptionItem::Data(data)) = (&live, &item) {
+                    sent.insert(key(data));
+                }
+                if tx.send(Ok(item)).await.is_err() {
+                    return;
+                }
+            }
+            position = page.next;
+            if page.caught_up {
+                if live.is_some() {
+                    break;
+                }
+                live = Some(subscribe());
+            }
+        }
+
+        let Some(mut live) = live else {
+            return;
+        };
+        while let Some(data) = live.next().await {
+            if sent.remove(&key(&data)) {
+                continue;
+            }
+            if tx.send(Ok(SubscriptionItem::Data(data))).await.is_err() {
+                return;
+            }
+        }
+    });
+    ReceiverStream::new(rx)
+}
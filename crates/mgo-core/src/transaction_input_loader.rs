@@ -9,12 +9,14 @@ use std::sync::Arc;
 use mgo_protocol_config::ProtocolConfig;
 use mgo_types::{
     base_types::{EpochId, ObjectID, ObjectRef, SequenceNumber, TransactionDigest},
+    clock::Clock,
     error::{MgoError, MgoResult, UserInputError},
     storage::{GetSharedLocks, ObjectKey},
     transaction::{
         InputObjectKind, InputObjects, ObjectReadResult, ObjectReadResultKind,
         ReceivingObjectReadResult, ReceivingObjectReadResultKind, ReceivingObjects,
     },
+    MGO_CLOCK_OBJECT_ID,
 };
 use tracing::instrument;
 
@@ -29,6 +31,25 @@ impl TransactionInputLoader {
 }
 
 impl TransactionInputLoader {
+    /// Read the consensus time, i.e. the timestamp of the clock object as of the last consensus
+    /// commit executed by this validator. Used to check timestamp based transaction expirations
+    /// when signing.
+    pub fn read_consensus_time_ms(&self) -> MgoResult<u64> {
+        let Some(clock) = self.cache.get_object(&MGO_CLOCK_OBJECT_ID)? else {
+            return Err(UserInputError::ObjectNotFound {
+                object_id: MGO_CLOCK_OBJECT_ID,
+                version: None,
+            }
+            .into());
+        };
+        let clock: Clock = clock
+            .to_rust()
+            .ok_or_else(|| MgoError::ObjectDeserializationError {
+                error: "Unable to deserialize the clock object".to_string(),
+            })?;
+        Ok(clock.timestamp_ms())
+    }
+
     /// Read the inputs for a transaction that the validator was asked to sign.
     ///
     /// tx_digest is provided so that the inputs can be cached with the tx_digest and returned with
@@ -37,11 +37,13 @@ use mgo_types::effects::TransactionEffects;
 use mgo_types::epoch_data::EpochData;
 use mgo_types::error::UserInputError;
 use mgo_types::execution_status::{ExecutionFailureStatus, ExecutionStatus};
-use mgo_types::gas_coin::GasCoin;
+use mgo_types::execution_trace::ObjectAccessKind;
+use mgo_types::gas_coin::{GasCoin, GAS};
 use mgo_types::messages_consensus::{ConsensusCommitPrologue, ConsensusCommitPrologueV2};
 use mgo_types::object::Data;
 use mgo_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use mgo_types::randomness_state::get_randomness_state_obj_initial_shared_version;
+use mgo_types::state_override::{ObjectOverride, PackageOverride};
 use mgo_types::storage::GetSharedLocks;
 use mgo_types::mgo_system_state::MgoSystemStateWrapper;
 use mgo_types::utils::{
@@ -295,6 +297,323 @@ async fn test_dry_run_no_gas_big_transfer() {
     assert_eq!(*dry_run_res.effects.status(), MgoExecutionStatus::Success);
 }
 
+#[tokio::test]
+async fn test_dry_run_with_state_overrides() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let recipient = dbg_addr(2);
+    let gas_object_id = ObjectID::random();
+    let (_, fullnode, _) =
+        init_state_with_ids_and_object_basics_with_fullnode(vec![(sender, gas_object_id)]).await;
+
+    // More than the gas coin holds.
+    let amount = 100_000_000_000_000_000u64;
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.transfer_mgo(recipient, Some(amount));
+    let rgp = fullnode.reference_gas_price_for_testing().unwrap();
+    let data = TransactionData::new_programmable(
+        sender,
+        vec![fullnode
+            .get_object(&gas_object_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .compute_object_reference()],
+        builder.finish(),
+        TEST_ONLY_GAS_UNIT_FOR_TRANSFER * rgp,
+        rgp,
+    );
+    let signed = to_sender_signed_transaction(data, &sender_key);
+    let transaction = signed.data().intent_message().value.clone();
+
+    let (response, _, _, _) = fullnode
+        .dry_exec_transaction(transaction.clone(), *signed.digest())
+        .await
+        .unwrap();
+    assert_ne!(*response.effects.status(), MgoExecutionStatus::Success);
+    assert!(response.state_overrides.is_none());
+
+    let state_overrides = StateOverrides {
+        objects: BTreeMap::from([(gas_object_id, ObjectOverride::Balance(amount * 2))]),
+        timestamp_ms: Some(42),
+        ..Default::default()
+    };
+    let (response, written_objects, _, _, overridden_objects) = fullnode
+        .dry_exec_transaction_with_overrides(transaction, *signed.digest(), state_overrides)
+        .await
+        .unwrap();
+    assert_eq!(*response.effects.status(), MgoExecutionStatus::Success);
+    let applied = response.state_overrides.unwrap();
+    assert_eq!(applied.touched_objects, vec![gas_object_id]);
+    assert_eq!(applied.timestamp_ms, Some(42));
+    assert!(overridden_objects.contains_key(&gas_object_id));
+    assert!(overridden_objects.contains_key(&MGO_CLOCK_OBJECT_ID));
+
+    // The gas coin's change is relative to the overridden balance.
+    let (_, gas_object, _) = &written_objects[&gas_object_id];
+    let gas_coin = GasCoin::try_from(gas_object).unwrap();
+    assert!(gas_coin.value() < amount);
+    assert!(gas_coin.value() > amount - TEST_ONLY_GAS_UNIT_FOR_TRANSFER * rgp);
+
+    // Nothing is committed.
+    let gas_object = fullnode.get_object(&gas_object_id).await.unwrap().unwrap();
+    assert!(GasCoin::try_from(&gas_object).unwrap().value() < amount);
+}
+
+#[tokio::test]
+async fn test_dry_run_with_sender_override() {
+    let (sender, _): (_, AccountKeyPair) = get_key_pair();
+    let impersonated = dbg_addr(2);
+    let gas_object_id = ObjectID::random();
+    let (_, fullnode, _) =
+        init_state_with_ids_and_object_basics_with_fullnode(vec![(sender, gas_object_id)]).await;
+
+    // `impersonated` transfers a coin it does not own to itself, paying with mock gas.
+    let coin_ref = fullnode
+        .get_object(&gas_object_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .compute_object_reference();
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.transfer_object(impersonated, coin_ref).unwrap();
+    let rgp = fullnode.reference_gas_price_for_testing().unwrap();
+    let transaction = TransactionData::new_programmable(
+        impersonated,
+        vec![],
+        builder.finish(),
+        TEST_ONLY_GAS_UNIT_FOR_TRANSFER * rgp,
+        rgp,
+    );
+    let digest = TransactionDigest::random();
+
+    assert!(fullnode
+        .dry_exec_transaction(transaction.clone(), digest)
+        .await
+        .is_err());
+
+    let state_overrides = StateOverrides {
+        sender: Some(impersonated),
+        ..Default::default()
+    };
+    let (response, written_objects, _, mock_gas, _) = fullnode
+        .dry_exec_transaction_with_overrides(transaction, digest, state_overrides)
+        .await
+        .unwrap();
+    assert_eq!(*response.effects.status(), MgoExecutionStatus::Success);
+    assert!(mock_gas.is_some());
+    let (_, coin, _) = &written_objects[&gas_object_id];
+    assert_eq!(coin.owner, Owner::AddressOwner(impersonated));
+}
+
+/// A package override with a single module at a fresh address. The module of an `invalid`
+/// package declares a `key` struct without an `id` field, which the Mgo verifier rejects.
+fn package_override_for_testing(
+    protocol_config: &ProtocolConfig,
+    invalid: bool,
+) -> (ObjectID, PackageOverride) {
+    let package_id = ObjectID::random();
+    let mut module = file_format::empty_module();
+    module.identifiers[0] = Identifier::new("m").unwrap();
+    module.address_identifiers[0] = AccountAddress::from(package_id);
+    if invalid {
+        module.identifiers.push(Identifier::new("S").unwrap());
+        module.identifiers.push(Identifier::new("value").unwrap());
+        module.struct_handles.push(file_format::StructHandle {
+            module: file_format::ModuleHandleIndex(0),
+            name: IdentifierIndex(1),
+            abilities: file_format::AbilitySet::singleton(file_format::Ability::Key),
+            type_parameters: vec![],
+        });
+        module.struct_defs.push(file_format::StructDefinition {
+            struct_handle: file_format::StructHandleIndex(0),
+            field_information: file_format::StructFieldInformation::Declared(vec![
+                file_format::FieldDefinition {
+                    name: IdentifierIndex(2),
+                    signature: file_format::TypeSignature(file_format::SignatureToken::U64),
+                },
+            ]),
+        });
+    }
+
+    let mut bytes = vec![];
+    module
+        .serialize_for_version(
+            Some(protocol_config.move_binary_format_version()),
+            &mut bytes,
+        )
+        .unwrap();
+    let package = PackageOverride {
+        modules: vec![bytes],
+        dependencies: vec![],
+    };
+    (package_id, package)
+}
+
+#[tokio::test]
+async fn test_dry_run_with_package_override() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas_object_id = ObjectID::random();
+    let (_, fullnode, _) =
+        init_state_with_ids_and_object_basics_with_fullnode(vec![(sender, gas_object_id)]).await;
+
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.transfer_mgo(dbg_addr(2), Some(1));
+    let rgp = fullnode.reference_gas_price_for_testing().unwrap();
+    let data = TransactionData::new_programmable(
+        sender,
+        vec![fullnode
+            .get_object(&gas_object_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .compute_object_reference()],
+        builder.finish(),
+        TEST_ONLY_GAS_UNIT_FOR_TRANSFER * rgp,
+        rgp,
+    );
+    let signed = to_sender_signed_transaction(data, &sender_key);
+    let transaction = signed.data().intent_message().value.clone();
+    let protocol_config = fullnode.epoch_store_for_testing().protocol_config().clone();
+
+    let (package_id, package) = package_override_for_testing(&protocol_config, false);
+    let state_overrides = StateOverrides {
+        packages: vec![package],
+        ..Default::default()
+    };
+    let (response, _, _, _, overridden_objects) = fullnode
+        .dry_exec_transaction_with_overrides(transaction.clone(), *signed.digest(), state_overrides)
+        .await
+        .unwrap();
+    assert_eq!(*response.effects.status(), MgoExecutionStatus::Success);
+    assert!(overridden_objects[&package_id].is_package());
+
+    // Packages that would fail verification when published are rejected.
+    let (package_id, package) = package_override_for_testing(&protocol_config, true);
+    let state_overrides = StateOverrides {
+        packages: vec![package],
+        ..Default::default()
+    };
+    let result = fullnode
+        .dry_exec_transaction_with_overrides(transaction, *signed.digest(), state_overrides)
+        .await;
+    assert!(matches!(
+        result,
+        Err(MgoError::UserInputError {
+            error: UserInputError::InvalidStateOverride { object_id, .. }
+        }) if object_id == package_id
+    ));
+}
+
+#[tokio::test]
+async fn test_dry_run_with_contents_override() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas_object_id = ObjectID::random();
+    let (_, fullnode, _) =
+        init_state_with_ids_and_object_basics_with_fullnode(vec![(sender, gas_object_id)]).await;
+
+    // More than the gas coin holds.
+    let amount = 100_000_000_000_000_000u64;
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.transfer_mgo(dbg_addr(2), Some(amount));
+    let rgp = fullnode.reference_gas_price_for_testing().unwrap();
+    let data = TransactionData::new_programmable(
+        sender,
+        vec![fullnode
+            .get_object(&gas_object_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .compute_object_reference()],
+        builder.finish(),
+        TEST_ONLY_GAS_UNIT_FOR_TRANSFER * rgp,
+        rgp,
+    );
+    let signed = to_sender_signed_transaction(data, &sender_key);
+    let transaction = signed.data().intent_message().value.clone();
+
+    let contents = bcs::to_bytes(&(gas_object_id, amount * 2)).unwrap();
+    let state_overrides = StateOverrides {
+        objects: BTreeMap::from([(gas_object_id, ObjectOverride::Contents(contents))]),
+        ..Default::default()
+    };
+    let (response, _, _, _, _) = fullnode
+        .dry_exec_transaction_with_overrides(transaction.clone(), *signed.digest(), state_overrides)
+        .await
+        .unwrap();
+    assert_eq!(*response.effects.status(), MgoExecutionStatus::Success);
+
+    // Contents that do not deserialize to the object's type are rejected.
+    let contents = bcs::to_bytes(&(gas_object_id, amount * 2, true)).unwrap();
+    let state_overrides = StateOverrides {
+        objects: BTreeMap::from([(gas_object_id, ObjectOverride::Contents(contents))]),
+        ..Default::default()
+    };
+    let result = fullnode
+        .dry_exec_transaction_with_overrides(transaction, *signed.digest(), state_overrides)
+        .await;
+    assert!(matches!(
+        result,
+        Err(MgoError::UserInputError {
+            error: UserInputError::InvalidStateOverride { object_id, .. }
+        }) if object_id == gas_object_id
+    ));
+}
+
+#[tokio::test]
+async fn test_dev_inspect_with_state_overrides() {
+    let (sender, _): (_, AccountKeyPair) = get_key_pair();
+    let gas_object_id = ObjectID::random();
+    let (_, fullnode, _) =
+        init_state_with_ids_and_object_basics_with_fullnode(vec![(sender, gas_object_id)]).await;
+
+    let coin_ref = fullnode
+        .get_object(&gas_object_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .compute_object_reference();
+    let mut builder = ProgrammableTransactionBuilder::new();
+    let coin = builder.obj(ObjectArg::ImmOrOwnedObject(coin_ref)).unwrap();
+    builder.programmable_move_call(
+        MGO_FRAMEWORK_PACKAGE_ID,
+        ident_str!("coin").to_owned(),
+        ident_str!("value").to_owned(),
+        vec![GAS::type_tag()],
+        vec![coin],
+    );
+    let kind = TransactionKind::programmable(builder.finish());
+    let rgp = fullnode.reference_gas_price_for_testing().unwrap();
+
+    let state_overrides = StateOverrides {
+        objects: BTreeMap::from([(gas_object_id, ObjectOverride::Balance(5_000))]),
+        ..Default::default()
+    };
+    let DevInspectResults {
+        effects, results, ..
+    } = fullnode
+        .dev_inspect_transaction_block(
+            sender,
+            kind,
+            Some(rgp),
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(state_overrides),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(*effects.status(), MgoExecutionStatus::Success);
+
+    // The coin's value is read from the overridden balance.
+    let mut results = results.unwrap();
+    assert_eq!(results.len(), 1);
+    let (value, _) = results.pop().unwrap().return_values.pop().unwrap();
+    assert_eq!(bcs::from_bytes::<u64>(&value).unwrap(), 5_000);
+}
+
 #[tokio::test]
 async fn test_dev_inspect_object_by_bytes() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
@@ -575,7 +894,7 @@ async fn test_dev_inspect_dynamic_field() {
     };
     let kind = TransactionKind::programmable(pt);
     let DevInspectResults { error, .. } = fullnode
-        .dev_inspect_transaction_block(sender, kind, None, None, None, None, None, None)
+        .dev_inspect_transaction_block(sender, kind, None, None, None, None, None, None, None, None)
         .await
         .unwrap();
     // produces an error
@@ -817,7 +1136,7 @@ async fn test_dev_inspect_gas_coin_argument() {
     };
     let kind = TransactionKind::programmable(pt);
     let results = fullnode
-        .dev_inspect_transaction_block(sender, kind, None, None, None, None, None, None)
+        .dev_inspect_transaction_block(sender, kind, None, None, None, None, None, None, None, None)
         .await
         .unwrap()
         .results
@@ -851,6 +1170,98 @@ async fn test_dev_inspect_gas_coin_argument() {
     assert!(return_values.is_empty());
 }
 
+#[tokio::test]
+async fn test_dev_inspect_trace() {
+    let (sender, _sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas_object_id = ObjectID::random();
+    let (_validator, fullnode, object_basics) =
+        init_state_with_ids_and_object_basics_with_fullnode(vec![(sender, gas_object_id)]).await;
+
+    let pt = {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        builder
+            .move_call(
+                object_basics.0,
+                Identifier::new("object_basics").unwrap(),
+                Identifier::new("create").unwrap(),
+                vec![],
+                vec![
+                    CallArg::Pure(bcs::to_bytes(&(16_u64)).unwrap()),
+                    CallArg::Pure(bcs::to_bytes(&sender).unwrap()),
+                ],
+            )
+            .unwrap();
+        builder.finish()
+    };
+    let kind = TransactionKind::programmable(pt);
+
+    // No trace unless one is requested.
+    let DevInspectResults { trace, .. } = fullnode
+        .dev_inspect_transaction_block(
+            sender,
+            kind.clone(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(trace.is_none());
+
+    let DevInspectResults { effects, trace, .. } = fullnode
+        .dev_inspect_transaction_block(
+            sender,
+            kind,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(true),
+        )
+        .await
+        .unwrap();
+    let trace = trace.unwrap();
+    assert_eq!(trace.commands.len(), 1);
+
+    let command = &trace.commands[0];
+    assert_eq!(command.kind, "MoveCall");
+    assert!(command.error.is_none());
+
+    // The entry function, with its arguments decoded against its signature.
+    let root = &command.calls[0];
+    assert_eq!(root.package, object_basics.0);
+    assert_eq!(root.module, "object_basics");
+    assert_eq!(root.function, "create");
+    assert_eq!(root.arguments[0].json, Some(json!("16")));
+    assert_eq!(root.arguments[1].json, Some(json!(sender.to_string())));
+    assert!(root.gas_used > 0 && !root.aborted);
+
+    // ...and the functions it called.
+    let callees: Vec<_> = root.calls.iter().map(|f| f.function.as_str()).collect();
+    assert!(callees.contains(&"new"));
+    assert!(callees.contains(&"public_transfer"));
+
+    // The created object is traced with its contents after the transaction.
+    let created = effects.created()[0].object_id();
+    let access = trace
+        .objects
+        .iter()
+        .find(|access| access.object_id == created)
+        .unwrap();
+    assert_eq!(access.kind, ObjectAccessKind::Created);
+    assert!(access.before.is_none());
+    let after = access.after.as_ref().unwrap();
+    assert_eq!(after.contents.json.as_ref().unwrap()["value"], json!("16"));
+}
+
 #[tokio::test]
 async fn test_dev_inspect_gas_price() {
     let (_, fullnode, _object_basics) =
@@ -866,7 +1277,18 @@ async fn test_dev_inspect_gas_price() {
     };
     let kind = TransactionKind::programmable(pt);
     let error = fullnode
-        .dev_inspect_transaction_block(sender, kind.clone(), Some(1), None, None, None, None, None)
+        .dev_inspect_transaction_block(
+            sender,
+            kind.clone(),
+            Some(1),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
         .await
         .unwrap_err();
     assert!(
@@ -889,6 +1311,8 @@ async fn test_dev_inspect_gas_price() {
             None,
             None,
             None,
+            None,
+            None,
         )
         .await
         .unwrap_err();
@@ -943,6 +1367,8 @@ async fn test_dev_inspect_uses_unbound_object() {
             None,
             None,
             None,
+            None,
+            None,
         )
         .await;
     let Err(err) = result else { panic!() };
@@ -1084,7 +1510,18 @@ async fn test_dry_run_dev_inspect_dynamic_field_too_new() {
     let rgp = fullnode.reference_gas_price_for_testing().unwrap();
     // dev inspect
     let DevInspectResults { effects, .. } = fullnode
-        .dev_inspect_transaction_block(sender, kind, Some(rgp), None, None, None, None, None)
+        .dev_inspect_transaction_block(
+            sender,
+            kind,
+            Some(rgp),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
         .await
         .unwrap();
     assert_eq!(effects.deleted().len(), 0);
@@ -1137,7 +1574,18 @@ async fn test_dry_run_dev_inspect_max_gas_version() {
     let kind = TransactionKind::programmable(pt.clone());
     // dev inspect
     let DevInspectResults { effects, .. } = fullnode
-        .dev_inspect_transaction_block(sender, kind, Some(rgp + 100), None, None, None, None, None)
+        .dev_inspect_transaction_block(
+            sender,
+            kind,
+            Some(rgp + 100),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
         .await
         .unwrap();
     assert_eq!(effects.status(), &MgoExecutionStatus::Success);
@@ -4607,7 +5055,18 @@ pub async fn call_dev_inspect(
     let kind = TransactionKind::programmable(builder.finish());
     let rgp = authority.reference_gas_price_for_testing().unwrap();
     authority
-        .dev_inspect_transaction_block(*sender, kind, Some(rgp), None, None, None, None, None)
+        .dev_inspect_transaction_block(
+            *sender,
+            kind,
+            Some(rgp),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
         .await
 }
 
@@ -5284,6 +5743,8 @@ async fn test_for_inc_201_dev_inspect() {
             None,
             None,
             None,
+            None,
+            None,
         )
         .await
         .unwrap();
@@ -15,6 +15,7 @@ use mgo_types::gas::GasCostSummary;
 use mgo_types::messages_checkpoint::{
     CheckpointContents, CheckpointSummary, SignedCheckpointSummary,
 };
+use mgo_types::passkey_authenticator::PasskeyRelyingParties;
 use mgo_types::transaction::CertifiedTransaction;
 
 // TODO consolidate with `gen_certs` in batch_verification_bench.rs
@@ -121,6 +122,8 @@ async fn test_async_verifier() {
         ZkLoginEnv::Test,
         true,
         true,
+        None,
+        PasskeyRelyingParties::default(),
     ));
 
     let tasks: Vec<_> = (0..32)
@@ -1,6 +1,9 @@
 // Copyright (c) MangoNet Labs Ltd.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::sync::{Arc, Mutex};
+
+use futures::StreamExt;
 use move_core_types::account_address::AccountAddress;
 use move_core_types::identifier::Identifier;
 
@@ -13,12 +16,15 @@ use move_core_types::{
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::json;
-use mgo_json_rpc_types::MgoMoveStruct;
+use mgo_json_rpc_types::{MgoMoveStruct, SubscriptionGap, SubscriptionItem};
 
 use mgo_types::base_types::ObjectID;
+use mgo_types::digests::TransactionDigest;
 use mgo_types::gas_coin::GasCoin;
 use mgo_types::{MOVE_STDLIB_ADDRESS, MGO_FRAMEWORK_ADDRESS};
 
+use super::{backfill_then_subscribe, BackfillPage};
+
 #[test]
 fn test_to_json_value() {
     let move_event = TestEvent {
@@ -63,6 +69,88 @@ fn test_to_json_value() {
     assert_eq!(Some(&json!("test_event")), json_value.pointer("/name"));
 }
 
+#[tokio::test]
+async fn test_backfill_then_subscribe() {
+    use SubscriptionItem::{Data, Gap};
+
+    let pruned = Gap(SubscriptionGap::Transactions(vec![
+        TransactionDigest::random(),
+    ]));
+    let index = Arc::new(Mutex::new(vec![
+        Data(0),
+        Data(1),
+        pruned.clone(),
+        Data(3),
+        Data(4),
+    ]));
+    let fetch_page = {
+        let index = index.clone();
+        move |(position, _): (u64, usize)| {
+            let items: Vec<_> = index
+                .lock()
+                .unwrap()
+                .iter()
+                .skip(position as usize)
+                .take(2)
+                .cloned()
+                .collect();
+            let page = BackfillPage {
+                next: (position + items.len() as u64, 0),
+                caught_up: items.len() < 2,
+                items,
+            };
+            async move { Ok::<_, String>(page) }
+        }
+    };
+    // 5 and 6 are committed as the live stream is subscribed to, so they are both backfilled and
+    // streamed live.
+    let subscribe = move || {
+        index.lock().unwrap().extend([Data(5), Data(6)]);
+        futures::stream::iter([5, 6, 7])
+    };
+    let checkpoints_gap = SubscriptionGap::Checkpoints { start: 0, end: 9 };
+    let start = {
+        let checkpoints_gap = checkpoints_gap.clone();
+        async move { Ok(((0, 0), Some(checkpoints_gap))) }
+    };
+
+    let items: Vec<_> = backfill_then_subscribe(start, fetch_page, subscribe, |n: &u64| *n)
+        .map(Result::unwrap)
+        .collect()
+        .await;
+    assert_eq!(
+        items,
+        vec![
+            Gap(checkpoints_gap),
+            Data(0),
+            Data(1),
+            pruned,
+            Data(3),
+            Data(4),
+            Data(5),
+            Data(6),
+            Data(7),
+        ]
+    );
+
+    // An invalid cursor fails the subscription.
+    let items: Vec<_> = backfill_then_subscribe(
+        async { Err("invalid cursor".to_string()) },
+        |_| async {
+            Ok(BackfillPage {
+                items: vec![],
+                next: (0, 0),
+                caught_up: true,
+            })
+        },
+        || futures::stream::iter([0u64]),
+        |n: &u64| *n,
+    )
+    .collect()
+    .await;
+    assert_eq!(items, vec![Err("invalid cursor".to_string())]);
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TestEvent {
     creator: AccountAddress,
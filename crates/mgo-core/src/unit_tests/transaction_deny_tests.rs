@@ -13,7 +13,10 @@ use move_core_types::ident_str;
 use std::path::PathBuf;
 use std::sync::Arc;
 use mgo_config::certificate_deny_config::CertificateDenyConfigBuilder;
-use mgo_config::transaction_deny_config::{TransactionDenyConfig, TransactionDenyConfigBuilder};
+use mgo_config::transaction_deny_config::{
+    TransactionDenyConfig, TransactionDenyConfigBuilder, TransactionDenyPredicate,
+    TransactionDenyRule,
+};
 use mgo_swarm_config::genesis_config::{AccountConfig, DEFAULT_GAS_AMOUNT};
 use mgo_swarm_config::network_config::NetworkConfig;
 use mgo_test_transaction_builder::TestTransactionBuilder;
@@ -21,15 +24,19 @@ use mgo_types::base_types::{ObjectID, ObjectRef, MgoAddress};
 use mgo_types::effects::TransactionEffectsAPI;
 use mgo_types::error::{MgoError, MgoResult, UserInputError};
 use mgo_types::execution_status::{ExecutionFailureStatus, ExecutionStatus};
+use mgo_types::gas_coin::GAS;
 use mgo_types::messages_grpc::HandleTransactionResponse;
+use mgo_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use mgo_types::transaction::{
-    CallArg, CertifiedTransaction, Transaction, TransactionData, VerifiedCertificate,
-    VerifiedTransaction, TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
+    Argument, CallArg, CertifiedTransaction, Command, ObjectArg, ProgrammableTransaction,
+    Transaction, TransactionData, VerifiedCertificate, VerifiedTransaction,
+    TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
 };
 use mgo_types::utils::get_zklogin_user_address;
 use mgo_types::utils::{
     make_zklogin_tx, to_sender_signed_transaction, to_sender_signed_transaction_with_multi_signers,
 };
+use mgo_types::{MGO_FRAMEWORK_PACKAGE_ID, MGO_SYSTEM_PACKAGE_ID};
 
 const ACCOUNT_NUM: usize = 5;
 const GAS_OBJECT_COUNT: usize = 15;
@@ -158,6 +165,36 @@ async fn handle_move_call_transaction(
     state.handle_transaction(&epoch_store, tx).await
 }
 
+async fn transfer_mgo_with_account(
+    account: &Account,
+    gas_payment_index: usize,
+    amount: Option<u64>,
+    state: &Arc<AuthorityState>,
+) -> MgoResult<HandleTransactionResponse> {
+    let rgp = state.reference_gas_price_for_testing().unwrap();
+    let tx = TestTransactionBuilder::new(account.0, account.2[gas_payment_index], rgp)
+        .transfer_mgo(amount, account.0)
+        .build_and_sign(&account.1);
+    let epoch_store = state.epoch_store_for_testing();
+    let tx = epoch_store.verify_transaction(tx).unwrap();
+    state.handle_transaction(&epoch_store, tx).await
+}
+
+async fn execute_ptb_with_account(
+    account: &Account,
+    gas_payment_index: usize,
+    pt: ProgrammableTransaction,
+    state: &Arc<AuthorityState>,
+) -> MgoResult<HandleTransactionResponse> {
+    let rgp = state.reference_gas_price_for_testing().unwrap();
+    let tx = TestTransactionBuilder::new(account.0, account.2[gas_payment_index], rgp)
+        .programmable(pt)
+        .build_and_sign(&account.1);
+    let epoch_store = state.epoch_store_for_testing();
+    let tx = epoch_store.verify_transaction(tx).unwrap();
+    state.handle_transaction(&epoch_store, tx).await
+}
+
 fn assert_denied<T: std::fmt::Debug>(result: &MgoResult<T>) {
     assert!(matches!(
         result.as_ref().unwrap_err(),
@@ -475,3 +512,248 @@ async fn test_certificate_deny() {
         }
     ));
 }
+
+#[tokio::test]
+async fn test_move_function_denied() {
+    let (network_config, state) = setup_test(
+        TransactionDenyConfigBuilder::new()
+            .add_rule(TransactionDenyRule::new(
+                TransactionDenyPredicate::MoveFunction {
+                    package: MGO_SYSTEM_PACKAGE_ID,
+                    module: "mgo_system".to_string(),
+                    function: "request_add_stake".to_string(),
+                },
+            ))
+            .build(),
+    )
+    .await;
+    let accounts = get_accounts_and_coins(&network_config, &state);
+    let gas_price = state.reference_gas_price_for_testing().unwrap();
+    let account = &accounts[0];
+    let tx = TestTransactionBuilder::new(account.0, account.2[0], gas_price)
+        .call_staking(account.2[1], MgoAddress::default())
+        .build_and_sign(&account.1);
+    let epoch_store = state.epoch_store_for_testing();
+    let tx = epoch_store.verify_transaction(tx).unwrap();
+    assert_denied(&state.handle_transaction(&epoch_store, tx).await);
+
+    // Other calls are still allowed.
+    assert!(transfer_with_account(&accounts[1], &accounts[1], &state)
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_input_object_type_denied() {
+    let coin_rule = TransactionDenyRule::new(TransactionDenyPredicate::InputObjectType {
+        object_type: "0x2::coin::Coin".parse().unwrap(),
+    });
+    let (network_config, state) = setup_test(
+        TransactionDenyConfigBuilder::new()
+            .add_rule(coin_rule.clone())
+            .build(),
+    )
+    .await;
+    let accounts = get_accounts_and_coins(&network_config, &state);
+    assert_denied(&transfer_with_account(&accounts[0], &accounts[0], &state).await);
+
+    // Expired rules no longer apply.
+    let state = reload_state_with_new_deny_config(
+        &network_config,
+        state,
+        TransactionDenyConfigBuilder::new()
+            .add_rule(coin_rule.expiring_at_ms(1))
+            .build(),
+    )
+    .await;
+    assert!(transfer_with_account(&accounts[0], &accounts[0], &state)
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_coin_transfer_amount_denied() {
+    let (network_config, state) = setup_test(
+        TransactionDenyConfigBuilder::new()
+            .add_rule(TransactionDenyRule::new(
+                TransactionDenyPredicate::CoinTransferAmount {
+                    coin_type: "0x2::mgo::MGO".parse().unwrap(),
+                    max_amount: DEFAULT_GAS_AMOUNT / 2,
+                },
+            ))
+            .build(),
+    )
+    .await;
+    let accounts = get_accounts_and_coins(&network_config, &state);
+    let account = &accounts[0];
+
+    // Transferring the whole gas coin.
+    assert_denied(&transfer_mgo_with_account(account, 0, None, &state).await);
+    // Splitting off an amount from the gas coin.
+    assert_denied(
+        &transfer_mgo_with_account(account, 0, Some(DEFAULT_GAS_AMOUNT / 2 + 1), &state).await,
+    );
+    assert!(
+        transfer_mgo_with_account(account, 0, Some(DEFAULT_GAS_AMOUNT / 2), &state)
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn test_coin_transfer_amount_denied_across_commands() {
+    let (network_config, state) = setup_test(
+        TransactionDenyConfigBuilder::new()
+            .add_rule(TransactionDenyRule::new(
+                TransactionDenyPredicate::CoinTransferAmount {
+                    coin_type: "0x2::mgo::MGO".parse().unwrap(),
+                    max_amount: DEFAULT_GAS_AMOUNT * 3 / 2,
+                },
+            ))
+            .build(),
+    )
+    .await;
+    let accounts = get_accounts_and_coins(&network_config, &state);
+    let account = &accounts[0];
+    let recipient = accounts[1].0;
+
+    // Amounts split off in separate commands add up.
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.transfer_mgo(recipient, Some(DEFAULT_GAS_AMOUNT / 2));
+    builder.transfer_mgo(recipient, Some(DEFAULT_GAS_AMOUNT / 2));
+    assert!(
+        execute_ptb_with_account(account, 0, builder.finish(), &state)
+            .await
+            .is_ok()
+    );
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.transfer_mgo(recipient, Some(DEFAULT_GAS_AMOUNT / 2));
+    builder.transfer_mgo(recipient, Some(DEFAULT_GAS_AMOUNT / 2));
+    builder.transfer_mgo(recipient, Some(DEFAULT_GAS_AMOUNT / 2 + 1));
+    assert_denied(&execute_ptb_with_account(account, 1, builder.finish(), &state).await);
+
+    // So do whole coins transferred in separate commands.
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.transfer_object(recipient, account.2[3]).unwrap();
+    assert!(
+        execute_ptb_with_account(account, 2, builder.finish(), &state)
+            .await
+            .is_ok()
+    );
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.transfer_object(recipient, account.2[4]).unwrap();
+    builder.transfer_object(recipient, account.2[5]).unwrap();
+    assert_denied(&execute_ptb_with_account(account, 6, builder.finish(), &state).await);
+
+    // A whole coin carries the value of the coins merged into it.
+    let mut builder = ProgrammableTransactionBuilder::new();
+    let coin = builder
+        .obj(ObjectArg::ImmOrOwnedObject(account.2[4]))
+        .unwrap();
+    let merged = builder
+        .obj(ObjectArg::ImmOrOwnedObject(account.2[5]))
+        .unwrap();
+    builder.command(Command::MergeCoins(coin, vec![merged]));
+    builder.transfer_arg(recipient, coin);
+    assert_denied(&execute_ptb_with_account(account, 7, builder.finish(), &state).await);
+
+    // Amounts that cannot be decoded are not known before execution.
+    let mut builder = ProgrammableTransactionBuilder::new();
+    let amount = builder.pure(true).unwrap();
+    let coin = builder.command(Command::SplitCoins(Argument::GasCoin, vec![amount]));
+    builder.transfer_arg(recipient, coin);
+    assert_denied(&execute_ptb_with_account(account, 8, builder.finish(), &state).await);
+}
+
+#[tokio::test]
+async fn test_coin_transfer_amount_denied_through_move_calls() {
+    let (network_config, state) = setup_test(
+        TransactionDenyConfigBuilder::new()
+            .add_rule(TransactionDenyRule::new(
+                TransactionDenyPredicate::CoinTransferAmount {
+                    coin_type: "0x2::mgo::MGO".parse().unwrap(),
+                    max_amount: DEFAULT_GAS_AMOUNT / 2,
+                },
+            ))
+            .build(),
+    )
+    .await;
+    let accounts = get_accounts_and_coins(&network_config, &state);
+    let account = &accounts[0];
+    let recipient = accounts[1].0;
+
+    // A coin passed to a Move call by reference counts as transferred as a whole, since the
+    // call can take any amount out of it.
+    let mut builder = ProgrammableTransactionBuilder::new();
+    let coin = builder
+        .obj(ObjectArg::ImmOrOwnedObject(account.2[3]))
+        .unwrap();
+    let amount = builder.pure(1u64).unwrap();
+    let recipient_arg = builder.pure(recipient).unwrap();
+    builder.programmable_move_call(
+        MGO_FRAMEWORK_PACKAGE_ID,
+        ident_str!("pay").to_owned(),
+        ident_str!("split_and_transfer").to_owned(),
+        vec![GAS::type_tag()],
+        vec![coin, amount, recipient_arg],
+    );
+    assert_denied(&execute_ptb_with_account(account, 0, builder.finish(), &state).await);
+
+    // So does a coin put into a vector, which only Move calls can use.
+    let mut builder = ProgrammableTransactionBuilder::new();
+    let coin = builder
+        .obj(ObjectArg::ImmOrOwnedObject(account.2[3]))
+        .unwrap();
+    let coins = builder.command(Command::MakeMoveVec(None, vec![coin]));
+    let recipient_arg = builder.pure(recipient).unwrap();
+    builder.programmable_move_call(
+        MGO_FRAMEWORK_PACKAGE_ID,
+        ident_str!("pay").to_owned(),
+        ident_str!("join_vec_and_transfer").to_owned(),
+        vec![GAS::type_tag()],
+        vec![coins, recipient_arg],
+    );
+    assert_denied(&execute_ptb_with_account(account, 0, builder.finish(), &state).await);
+
+    // Amounts split off before are not counted again when the new coin is passed on.
+    let mut builder = ProgrammableTransactionBuilder::new();
+    let amount = builder.pure(DEFAULT_GAS_AMOUNT / 2).unwrap();
+    let coin = builder.command(Command::SplitCoins(Argument::GasCoin, vec![amount]));
+    let coins = builder.command(Command::MakeMoveVec(None, vec![coin]));
+    let recipient_arg = builder.pure(recipient).unwrap();
+    builder.programmable_move_call(
+        MGO_FRAMEWORK_PACKAGE_ID,
+        ident_str!("pay").to_owned(),
+        ident_str!("join_vec_and_transfer").to_owned(),
+        vec![GAS::type_tag()],
+        vec![coins, recipient_arg],
+    );
+    assert!(
+        execute_ptb_with_account(account, 1, builder.finish(), &state)
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn test_reload_transaction_deny_config() {
+    let (network_config, state) = setup_test(TransactionDenyConfig::default()).await;
+    let accounts = get_accounts_and_coins(&network_config, &state);
+
+    let changes = state.reload_transaction_deny_config(
+        TransactionDenyConfigBuilder::new()
+            .add_denied_address(accounts[0].0)
+            .build(),
+    );
+    assert_eq!(changes.len(), 1);
+    assert_denied(&transfer_with_account(&accounts[0], &accounts[0], &state).await);
+    assert!(transfer_with_account(&accounts[1], &accounts[1], &state)
+        .await
+        .is_ok());
+
+    let changes = state.reload_transaction_deny_config(TransactionDenyConfig::default());
+    assert_eq!(changes.len(), 1);
+    assert!(transfer_with_account(&accounts[0], &accounts[0], &state)
+        .await
+        .is_ok());
+}
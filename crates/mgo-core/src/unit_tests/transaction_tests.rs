@@ -5,6 +5,7 @@ use fastcrypto::ed25519::Ed25519KeyPair;
 use fastcrypto::traits::KeyPair;
 use fastcrypto_zkp::bn254::zk_login::{parse_jwks, OIDCProvider, ZkLoginInputs};
 use mango_network::Multiaddr;
+use mgo_config::node::{AdmissionControlConfig, TokenBucketConfig};
 use rand::{rngs::StdRng, SeedableRng};
 use shared_crypto::intent::{Intent, IntentMessage};
 use std::ops::Deref;
@@ -1159,3 +1160,199 @@ async fn test_handle_certificate_errors() {
 
     assert_matches!(err, MgoError::SignerSignatureAbsent { .. });
 }
+
+#[tokio::test]
+async fn test_handle_certificate_admission_control() {
+    telemetry_subscribers::init_for_testing();
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let recipient = dbg_addr(2);
+    let object_ids = [ObjectID::random(), ObjectID::random()];
+    let gas_object_ids = [ObjectID::random(), ObjectID::random()];
+    let authority_state = init_state_with_ids(
+        object_ids
+            .iter()
+            .chain(&gas_object_ids)
+            .map(|id| (sender, *id))
+            .collect::<Vec<_>>(),
+    )
+    .await;
+    let rgp = authority_state.reference_gas_price_for_testing().unwrap();
+    let epoch_store = authority_state.epoch_store_for_testing();
+    let committee = epoch_store.committee().deref().clone();
+
+    let mut certs = vec![];
+    for (object_id, gas_object_id) in object_ids.iter().zip(&gas_object_ids) {
+        let object = authority_state
+            .get_object(object_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let gas_object = authority_state
+            .get_object(gas_object_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let transfer_transaction = init_transfer_transaction(
+            |_| {},
+            sender,
+            &sender_key,
+            recipient,
+            object.compute_object_reference(),
+            gas_object.compute_object_reference(),
+            rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
+            rgp,
+        );
+        let signed_transaction = VerifiedSignedTransaction::new(
+            epoch_store.epoch(),
+            VerifiedTransaction::new_unchecked(transfer_transaction.clone()),
+            authority_state.name,
+            &*authority_state.secret,
+        );
+        certs.push(
+            CertifiedTransaction::new(
+                transfer_transaction.into_data(),
+                vec![signed_transaction.auth_sig().clone()],
+                &committee,
+            )
+            .unwrap(),
+        );
+    }
+
+    // The client IP limit admits nothing, so that only certificates, which are not charged to
+    // the client IP, can get through.
+    let server = AuthorityServer::new_for_test(
+        "/ip4/127.0.0.1/tcp/0/http".parse().unwrap(),
+        authority_state.clone(),
+        "/ip4/127.0.0.1/tcp/0/http".parse().unwrap(),
+    )
+    .with_admission_control_for_test(AdmissionControlConfig {
+        sender_limit: Some(TokenBucketConfig {
+            capacity: 1,
+            refill_rate_per_sec: 0,
+        }),
+        client_ip_limit: Some(TokenBucketConfig {
+            capacity: 0,
+            refill_rate_per_sec: 0,
+        }),
+        ..Default::default()
+    });
+    let server_handle = server.spawn_for_test().await.unwrap();
+    let client = NetworkAuthorityClient::connect(server_handle.address())
+        .await
+        .unwrap();
+
+    client
+        .handle_certificate_v2(certs[0].clone())
+        .await
+        .unwrap();
+    // Resubmitting a processed certificate is not charged to the sender again.
+    client
+        .handle_certificate_v2(certs[0].clone())
+        .await
+        .unwrap();
+
+    let err = client
+        .handle_certificate_v2(certs[1].clone())
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        MgoError::TooManyRequests { key, .. } if key == format!("sender {sender}")
+    );
+}
+
+#[tokio::test]
+async fn test_handle_transaction_admission_control() {
+    telemetry_subscribers::init_for_testing();
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let (other_sender, other_sender_key): (_, AccountKeyPair) = get_key_pair();
+    let (_unknown_address, unknown_key): (_, AccountKeyPair) = get_key_pair();
+    let authority_state = init_state_with_ids(vec![]).await;
+    let rgp = authority_state.reference_gas_price_for_testing().unwrap();
+    let shared_object = ObjectID::random();
+
+    // The shared object does not exist, so admitted transactions fail later on, but only after
+    // being charged to their sender and shared objects.
+    let transaction = |sender, key: &AccountKeyPair, shared_objects: &[ObjectID]| {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        for id in shared_objects {
+            let arg = builder
+                .obj(ObjectArg::SharedObject {
+                    id: *id,
+                    initial_shared_version: OBJECT_START_VERSION,
+                    mutable: true,
+                })
+                .unwrap();
+            builder.transfer_arg(dbg_addr(2), arg);
+        }
+        let data = TransactionData::new_programmable(
+            sender,
+            vec![random_object_ref()],
+            builder.finish(),
+            rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
+            rgp,
+        );
+        to_sender_signed_transaction(data, key)
+    };
+
+    let server = AuthorityServer::new_for_test(
+        "/ip4/127.0.0.1/tcp/0/http".parse().unwrap(),
+        authority_state.clone(),
+        "/ip4/127.0.0.1/tcp/0/http".parse().unwrap(),
+    )
+    .with_admission_control_for_test(AdmissionControlConfig {
+        sender_limit: Some(TokenBucketConfig {
+            capacity: 1,
+            refill_rate_per_sec: 0,
+        }),
+        shared_object_limit: Some(TokenBucketConfig {
+            capacity: 1,
+            refill_rate_per_sec: 0,
+        }),
+        ..Default::default()
+    });
+    let server_handle = server.spawn_for_test().await.unwrap();
+    let client = NetworkAuthorityClient::connect(server_handle.address())
+        .await
+        .unwrap();
+
+    // Transactions that fail signature verification are not charged to the sender or the shared
+    // object they claim.
+    for _ in 0..3 {
+        let err = client
+            .handle_transaction(transaction(sender, &unknown_key, &[shared_object]))
+            .await
+            .unwrap_err();
+        assert_matches!(err, MgoError::SignerSignatureAbsent { .. });
+    }
+
+    let err = client
+        .handle_transaction(transaction(sender, &sender_key, &[shared_object]))
+        .await
+        .unwrap_err();
+    assert!(
+        !matches!(err, MgoError::TooManyRequests { .. }),
+        "unexpected error: {err:?}"
+    );
+
+    let err = client
+        .handle_transaction(transaction(sender, &sender_key, &[]))
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        MgoError::TooManyRequests { key, .. } if key == format!("sender {sender}")
+    );
+    let err = client
+        .handle_transaction(transaction(
+            other_sender,
+            &other_sender_key,
+            &[shared_object],
+        ))
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        MgoError::TooManyRequests { key, .. } if key == format!("shared object {shared_object}")
+    );
+}
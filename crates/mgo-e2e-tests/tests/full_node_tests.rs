@@ -588,6 +588,7 @@ async fn do_test_full_node_sync_flood() {
                             * context.get_reference_gas_price().await.unwrap(),
                         serialize_unsigned_transaction: false,
                         serialize_signed_transaction: false,
+                        expiration: Default::default(),
                     }
                     .execute(context)
                     .await
@@ -8,7 +8,7 @@ use uuid::Uuid;
 
 mod simple_faucet;
 mod write_ahead_log;
-pub use self::simple_faucet::SimpleFaucet;
+pub use self::simple_faucet::{build_pay_mgo_txn, SimpleFaucet};
 use clap::Parser;
 use std::{net::Ipv4Addr, path::PathBuf};
 
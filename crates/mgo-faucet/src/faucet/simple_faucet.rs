@@ -663,19 +663,7 @@ impl SimpleFaucet {
         amounts: &[u64],
         budget: u64,
     ) -> Result<TransactionData, anyhow::Error> {
-        let recipients = vec![recipient; amounts.len()];
-        let client = self.wallet.get_client().await?;
-        client
-            .transaction_builder()
-            .pay_mgo(signer, vec![coin_id], recipients, amounts.to_vec(), budget)
-            .await
-            .map_err(|e| {
-                anyhow::anyhow!(
-                    "Failed to build PayMgo transaction for coin {:?}, with err {:?}",
-                    coin_id,
-                    e
-                )
-            })
+        build_pay_mgo_txn(&self.wallet, coin_id, signer, recipient, amounts, budget).await
     }
 
     async fn check_and_map_transfer_gas_result(
@@ -946,6 +934,31 @@ impl Faucet for SimpleFaucet {
     }
 }
 
+/// Build a PayMgo transaction paying `amounts` out of `coin_id`, which also pays for gas, to
+/// `recipient`. When `recipient` is `signer`, this splits `coin_id` into coins of `amounts`.
+pub async fn build_pay_mgo_txn(
+    wallet: &WalletContext,
+    coin_id: ObjectID,
+    signer: MgoAddress,
+    recipient: MgoAddress,
+    amounts: &[u64],
+    budget: u64,
+) -> Result<TransactionData, anyhow::Error> {
+    let recipients = vec![recipient; amounts.len()];
+    let client = wallet.get_client().await?;
+    client
+        .transaction_builder()
+        .pay_mgo(signer, vec![coin_id], recipients, amounts.to_vec(), budget)
+        .await
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to build PayMgo transaction for coin {:?}, with err {:?}",
+                coin_id,
+                e
+            )
+        })
+}
+
 pub async fn batch_gather(
     request_consumer: &mut Receiver<(Uuid, MgoAddress, Vec<u64>)>,
     requests: &mut Vec<(Uuid, MgoAddress, Vec<u64>)>,
@@ -1116,6 +1129,7 @@ mod tests {
             count: Some(10),
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            expiration: Default::default(),
         }
         .execute(&mut context)
         .await
@@ -1244,6 +1258,7 @@ mod tests {
             count: Some(10),
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            expiration: Default::default(),
         }
         .execute(&mut context)
         .await
@@ -1401,6 +1416,7 @@ mod tests {
             gas_budget: 2_000_000,
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            expiration: Default::default(),
         }
         .execute(faucet.wallet_mut())
         .await
@@ -1526,6 +1542,7 @@ mod tests {
             count: None,
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            expiration: Default::default(),
         }
         .execute(&mut context)
         .await;
@@ -1611,6 +1628,7 @@ mod tests {
             count: None,
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            expiration: Default::default(),
         }
         .execute(&mut context)
         .await
@@ -1626,6 +1644,7 @@ mod tests {
                 amount: None,
                 serialize_unsigned_transaction: false,
                 serialize_signed_transaction: false,
+                expiration: Default::default(),
             }
             .execute(&mut context)
             .await
@@ -1684,6 +1703,7 @@ mod tests {
             count: None,
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            expiration: Default::default(),
         }
         .execute(&mut context)
         .await;
@@ -1699,6 +1719,7 @@ mod tests {
                 amount: None,
                 serialize_unsigned_transaction: false,
                 serialize_signed_transaction: false,
+                expiration: Default::default(),
             }
             .execute(&mut context)
             .await
@@ -1818,6 +1839,7 @@ mod tests {
             count: Some(10),
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            expiration: Default::default(),
         }
         .execute(&mut context)
         .await
@@ -0,0 +1,110 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::MoveFunctionId;
+use clap::Parser;
+use mgo_types::base_types::ObjectID;
+use std::{net::Ipv4Addr, path::PathBuf};
+
+pub const DEFAULT_MAX_GAS_BUDGET: u64 = 1_000_000_000;
+pub const DEFAULT_NUM_COINS: usize = 100;
+
+#[derive(Parser, Clone, Debug)]
+#[clap(
+    name = "Mgo Gas Station",
+    about = "Sponsors gas for transactions on Mgo",
+    rename_all = "kebab-case"
+)]
+pub struct GasStationConfig {
+    #[clap(long, default_value_t = 9527)]
+    pub port: u16,
+
+    #[clap(long, default_value = "127.0.0.1")]
+    pub host_ip: Ipv4Addr,
+
+    #[clap(long, default_value_t = 60)]
+    pub wallet_client_timeout_secs: u64,
+
+    #[clap(long)]
+    pub write_ahead_log: PathBuf,
+
+    /// Number of gas coins in the pool. Coins of the sponsor are split into this many coins on
+    /// start up if there are not enough.
+    #[clap(long, default_value_t = DEFAULT_NUM_COINS)]
+    pub num_coins: usize,
+
+    /// The maximum gas budget of a sponsored transaction, which is also the balance coins of the
+    /// pool are split to. Coins that can no longer pay for the budget of a reservation are merged
+    /// and split again into coins of this balance.
+    #[clap(long, default_value_t = DEFAULT_MAX_GAS_BUDGET)]
+    pub max_gas_budget: u64,
+
+    /// The total gas budget a sender can be sponsored for in every budget window. Unlimited if not
+    /// set.
+    #[clap(long)]
+    pub per_user_budget: Option<u64>,
+
+    #[clap(long, default_value_t = 86400)]
+    pub budget_window_secs: u64,
+
+    /// The maximum number of gas coins a client can hold reserved at a time. Reservations are not
+    /// authenticated, so this keeps a single client from reserving the whole pool.
+    #[clap(long, default_value_t = 10)]
+    pub max_reservations_per_client: usize,
+
+    #[clap(long, default_value_t = 60)]
+    pub default_reservation_secs: u64,
+
+    #[clap(long, default_value_t = 600)]
+    pub max_reservation_secs: u64,
+
+    /// How often expired reservations are released, and depleted coins are merged back into the
+    /// pool.
+    #[clap(long, default_value_t = 10)]
+    pub release_interval_secs: u64,
+
+    /// How long after its reservation expired a coin paying for a transaction whose outcome is
+    /// unknown stays reserved, if the transaction is not found. Coins of transactions that are
+    /// found are released once their effects are known.
+    #[clap(long, default_value_t = 60)]
+    pub in_flight_timeout_secs: u64,
+
+    /// Packages whose functions can be called by sponsored transactions.
+    #[clap(long = "allowed-package")]
+    pub allowed_packages: Vec<ObjectID>,
+
+    /// Functions that can be called by sponsored transactions, as `<package>::<module>::<function>`.
+    /// If no packages or functions are allowed, every function can be called.
+    #[clap(long = "allowed-function")]
+    pub allowed_functions: Vec<MoveFunctionId>,
+
+    #[clap(long, default_value_t = 10)]
+    pub request_buffer_size: usize,
+
+    #[clap(long, default_value_t = 10)]
+    pub max_request_per_second: u64,
+}
+
+impl Default for GasStationConfig {
+    fn default() -> Self {
+        Self {
+            port: 9527,
+            host_ip: Ipv4Addr::new(127, 0, 0, 1),
+            wallet_client_timeout_secs: 60,
+            write_ahead_log: Default::default(),
+            num_coins: DEFAULT_NUM_COINS,
+            max_gas_budget: DEFAULT_MAX_GAS_BUDGET,
+            per_user_budget: None,
+            budget_window_secs: 86400,
+            max_reservations_per_client: 10,
+            default_reservation_secs: 60,
+            max_reservation_secs: 600,
+            release_interval_secs: 10,
+            in_flight_timeout_secs: 60,
+            allowed_packages: vec![],
+            allowed_functions: vec![],
+            request_buffer_size: 10,
+            max_request_per_second: 10,
+        }
+    }
+}
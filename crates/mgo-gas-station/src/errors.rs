@@ -0,0 +1,54 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use mgo_types::base_types::{MgoAddress, ObjectID};
+use std::net::IpAddr;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum GasStationError {
+    #[error("Gas station cannot read objects from fullnode: {0}")]
+    FullnodeReadingError(String),
+
+    #[error("No gas coin is available, please try again later")]
+    NoGasCoinAvailable,
+
+    #[error("Transaction does not use a gas coin reserved by the gas station")]
+    ReservationNotFound,
+
+    #[error("Reservation of gas coin `{0}` has expired")]
+    ReservationExpired(ObjectID),
+
+    #[error("Gas coin `{0}` is already used by a transaction in flight")]
+    ReservationInFlight(ObjectID),
+
+    #[error("Transaction does not match its gas reservation: {0}")]
+    ReservationMismatch(String),
+
+    #[error("Transaction is not sponsored: {0}")]
+    PolicyViolation(String),
+
+    #[error("Gas budget of sender `{0}` is exhausted, please try again later")]
+    UserBudgetExceeded(MgoAddress),
+
+    #[error("Too many gas coins are reserved by client `{0}`, please try again later")]
+    TooManyReservations(IpAddr),
+
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    #[error("Wallet Error: `{0}`")]
+    Wallet(String),
+
+    #[error("Transaction execution failed: {0}")]
+    Execution(String),
+
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+impl GasStationError {
+    pub(crate) fn internal(e: impl ToString) -> Self {
+        GasStationError::Internal(e.to_string())
+    }
+}
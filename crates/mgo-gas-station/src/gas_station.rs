@@ -0,0 +1,1011 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics::GasStationMetrics;
+use crate::policy::{SponsorPolicy, UserBudgets};
+use crate::write_ahead_log::{Reservation, WriteAheadLog};
+use crate::{GasStationConfig, GasStationError, ReservedGas};
+use mgo_faucet::build_pay_mgo_txn;
+use mgo_json_rpc_types::{
+    MgoExecutionStatus, MgoObjectData, MgoObjectDataOptions, MgoTransactionBlockEffects,
+    MgoTransactionBlockEffectsAPI, MgoTransactionBlockResponseOptions,
+};
+use mgo_keys::keystore::AccountKeystore;
+use mgo_sdk::wallet_context::WalletContext;
+use mgo_types::base_types::{MgoAddress, ObjectID, ObjectRef};
+use mgo_types::gas_coin::GasCoin;
+use mgo_types::object::Owner;
+use mgo_types::quorum_driver_types::ExecuteTransactionRequestType;
+use mgo_types::signature::GenericSignature;
+use mgo_types::transaction::{Transaction, TransactionData, TransactionDataAPI};
+use parking_lot::Mutex;
+use prometheus::Registry;
+use shared_crypto::intent::Intent;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+use typed_store::Map;
+
+/// Maximum number of coins created by a single transaction splitting the sponsor's coins.
+const MAX_COINS_PER_SPLIT: usize = 200;
+
+/// Maximum number of depleted coins merged by a single transaction refilling the pool.
+const MAX_COINS_PER_MERGE: usize = 200;
+
+/// Sponsors transactions out of a pool of gas coins owned by the active address of its wallet.
+///
+/// A client first reserves a gas coin for a transaction, builds the transaction with the
+/// sponsor as gas owner and the reserved coin as gas payment, signs it, and asks the gas station
+/// to execute it. The gas station co-signs and executes the transaction if it complies with its
+/// [SponsorPolicy] and the reservation, and returns the coin to the pool once the effects of the
+/// transaction are known. Reservations that are not used before they expire are released by
+/// [GasStation::release_expired_reservations], and coins of transactions whose outcome was not
+/// known when they were executed by [GasStation::resolve_in_flight_reservations].
+///
+/// Coins that can no longer pay for a reservation are set aside, and merged and split back into
+/// the pool by [GasStation::refill_pool].
+pub struct GasStation {
+    wallet: WalletContext,
+    sponsor: MgoAddress,
+    config: GasStationConfig,
+    pool: Mutex<VecDeque<ObjectID>>,
+    depleted: Mutex<Vec<ObjectID>>,
+    wal: Mutex<WriteAheadLog>,
+    /// The number of coins reserved by every client.
+    client_reservations: Mutex<HashMap<IpAddr, usize>>,
+    policy: SponsorPolicy,
+    budgets: Mutex<UserBudgets>,
+    metrics: GasStationMetrics,
+}
+
+/// We do not just derive(Debug) because WalletContext and the WriteAheadLog do not implement Debug
+impl fmt::Debug for GasStation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GasStation")
+            .field("sponsor", &self.sponsor)
+            .field("pool", &self.pool)
+            .field("depleted", &self.depleted)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl GasStation {
+    pub async fn new(
+        mut wallet: WalletContext,
+        prometheus_registry: &Registry,
+        config: GasStationConfig,
+    ) -> Result<Arc<Self>, GasStationError> {
+        let sponsor = wallet
+            .active_address()
+            .map_err(|err| GasStationError::Wallet(err.to_string()))?;
+        info!("GasStation::new with sponsor address: {sponsor}");
+
+        split_coins(&wallet, sponsor, &config).await?;
+        let coins = wallet
+            .gas_objects(sponsor)
+            .await
+            .map_err(|e| GasStationError::Wallet(e.to_string()))?;
+        let metrics = GasStationMetrics::new(prometheus_registry);
+
+        // Reservations that are still valid stay reserved, and so do coins in flight when the gas
+        // station went down, until the outcome of their transaction is known.
+        let mut wal = WriteAheadLog::open(&config.write_ahead_log);
+        let now_ms = now_ms();
+        let mut reserved = HashSet::new();
+        let mut client_reservations = HashMap::new();
+        for item in wal.log.safe_iter() {
+            let (coin_id, reservation) = item.map_err(GasStationError::internal)?;
+            if reservation.in_flight.is_none() && reservation.expiration_ms < now_ms {
+                wal.commit(coin_id).map_err(GasStationError::internal)?;
+                continue;
+            }
+            reserved.insert(coin_id);
+            if let Some(client) = reservation.client {
+                *client_reservations.entry(client).or_insert(0) += 1;
+            }
+        }
+        metrics.total_reserved_coins.set(reserved.len() as i64);
+
+        let (pool, depleted): (VecDeque<_>, Vec<_>) = coins
+            .into_iter()
+            .filter(|(_, o)| !reserved.contains(&o.object_id))
+            .partition(|(balance, _)| *balance >= config.max_gas_budget);
+        let pool: VecDeque<_> = pool.into_iter().map(|(_, o)| o.object_id).collect();
+        let depleted: Vec<_> = depleted.into_iter().map(|(_, o)| o.object_id).collect();
+        info!(
+            "Adding {} coins to gas pool, {} depleted coins are left to merge",
+            pool.len(),
+            depleted.len()
+        );
+        metrics.total_available_coins.set(pool.len() as i64);
+        metrics.total_depleted_coins.set(depleted.len() as i64);
+
+        Ok(Arc::new(Self {
+            wallet,
+            sponsor,
+            policy: SponsorPolicy::new(sponsor, &config),
+            budgets: Mutex::new(UserBudgets::new(
+                config.per_user_budget,
+                Duration::from_secs(config.budget_window_secs),
+            )),
+            config,
+            pool: Mutex::new(pool),
+            depleted: Mutex::new(depleted),
+            wal: Mutex::new(wal),
+            client_reservations: Mutex::new(client_reservations),
+            metrics,
+        }))
+    }
+
+    pub fn sponsor(&self) -> MgoAddress {
+        self.sponsor
+    }
+
+    /// Reserves a gas coin that pays up to `gas_budget` for a transaction sent by `sender`, for
+    /// `duration_secs` or the default reservation duration. The budget is charged to the sender
+    /// right away, and what the transaction does not use is refunded when the reservation ends.
+    pub async fn reserve_gas(
+        &self,
+        sender: MgoAddress,
+        client: Option<IpAddr>,
+        gas_budget: u64,
+        duration_secs: Option<u64>,
+    ) -> Result<ReservedGas, GasStationError> {
+        self.policy.check_reservation(sender, gas_budget)?;
+        let duration_secs = duration_secs
+            .unwrap_or(self.config.default_reservation_secs)
+            .min(self.config.max_reservation_secs);
+
+        if let Some(client) = client {
+            let mut client_reservations = self.client_reservations.lock();
+            let reservations = client_reservations.entry(client).or_insert(0);
+            if *reservations >= self.config.max_reservations_per_client {
+                return Err(GasStationError::TooManyReservations(client));
+            }
+            *reservations += 1;
+        }
+        if let Err(err) = self
+            .budgets
+            .lock()
+            .charge(sender, gas_budget, Instant::now())
+        {
+            self.free_client_reservation(client);
+            return Err(err);
+        }
+
+        let result = self
+            .reserve_gas_coin(sender, client, gas_budget, duration_secs)
+            .await;
+        if result.is_err() {
+            self.budgets.lock().refund(sender, gas_budget);
+            self.free_client_reservation(client);
+        }
+        result
+    }
+
+    async fn reserve_gas_coin(
+        &self,
+        sender: MgoAddress,
+        client: Option<IpAddr>,
+        gas_budget: u64,
+        duration_secs: u64,
+    ) -> Result<ReservedGas, GasStationError> {
+        let gas_coin = loop {
+            let Some(coin_id) = self.pool.lock().pop_front() else {
+                return Err(GasStationError::NoGasCoinAvailable);
+            };
+            self.metrics.total_available_coins.dec();
+            match self.get_gas_coin_and_check_sponsor_owner(coin_id).await {
+                Ok(Some((gas_coin, balance))) if balance >= gas_budget => break gas_coin,
+                Ok(Some(_)) => {
+                    info!(?coin_id, "Setting aside depleted gas coin.");
+                    self.set_aside_depleted([coin_id]);
+                }
+                Ok(None) => {
+                    warn!(
+                        ?coin_id,
+                        "Discarding gas coin no longer owned by the sponsor."
+                    );
+                    self.metrics.total_discarded_coins.inc();
+                }
+                Err(err) => {
+                    self.recycle_gas_coin(coin_id);
+                    return Err(GasStationError::FullnodeReadingError(err.to_string()));
+                }
+            }
+        };
+
+        let coin_id = gas_coin.0;
+        let expiration_ms = now_ms() + duration_secs * 1000;
+        if let Err(err) =
+            self.wal
+                .lock()
+                .reserve(coin_id, sender, client, gas_budget, expiration_ms)
+        {
+            self.recycle_gas_coin(coin_id);
+            return Err(GasStationError::internal(err));
+        }
+        self.metrics.total_reserved_coins.inc();
+        info!(
+            ?coin_id,
+            ?sender,
+            ?client,
+            gas_budget,
+            expiration_ms,
+            "Reserved gas coin."
+        );
+
+        Ok(ReservedGas {
+            sponsor: self.sponsor,
+            gas_coins: vec![gas_coin],
+            expiration_ms,
+        })
+    }
+
+    /// Co-signs and executes a transaction signed by its sender with `user_sig`, paid for by a
+    /// gas coin reserved for the sender. The reservation ends once the effects of the transaction
+    /// are known. If execution fails, the transaction may still have been executed, so the coin
+    /// stays reserved until [GasStation::resolve_in_flight_reservations] finds out.
+    pub async fn execute_transaction(
+        &self,
+        tx_data: TransactionData,
+        user_sig: GenericSignature,
+    ) -> Result<MgoTransactionBlockEffects, GasStationError> {
+        let sender = tx_data.sender();
+        let [(coin_id, _, _)] = tx_data.gas() else {
+            return Err(GasStationError::ReservationMismatch(
+                "transaction must be paid for by a single reserved gas coin".to_string(),
+            ));
+        };
+        let coin_id = *coin_id;
+        let client = self
+            .wallet
+            .get_client()
+            .await
+            .map_err(|e| GasStationError::Wallet(format!("Unable to get client: {e:?}")))?;
+
+        let (tx, reservation) = {
+            let mut wal = self.wal.lock();
+            let reservation = wal
+                .get(coin_id)
+                .map_err(GasStationError::internal)?
+                .ok_or(GasStationError::ReservationNotFound)?;
+            self.check_reservation(&tx_data, coin_id, &reservation)?;
+            if let Err(err) = self.policy.check_transaction(&tx_data) {
+                self.metrics.total_rejected_transactions.inc();
+                return Err(err);
+            }
+            let tx = self.sign_transaction(tx_data, user_sig)?;
+            wal.set_in_flight(coin_id, *tx.digest())
+                .map_err(GasStationError::internal)?;
+            (tx, reservation)
+        };
+
+        let tx_digest = *tx.digest();
+        info!(?tx_digest, "Executing sponsored transaction.");
+        let response = client
+            .quorum_driver_api()
+            .execute_transaction_block(
+                tx,
+                MgoTransactionBlockResponseOptions::new().with_effects(),
+                Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+            )
+            .await
+            .map_err(|e| GasStationError::Execution(e.to_string()));
+        let effects = response.and_then(|response| {
+            response.effects.ok_or_else(|| {
+                GasStationError::Execution(format!("effects field missing for txn {tx_digest}"))
+            })
+        });
+        let effects = match effects {
+            Ok(effects) => effects,
+            Err(err) => {
+                warn!(
+                    ?coin_id,
+                    ?tx_digest,
+                    "Outcome of sponsored transaction is unknown, keeping gas coin reserved: {err}"
+                );
+                return Err(err);
+            }
+        };
+
+        let gas_used = gas_used(&effects);
+        self.end_reservation(coin_id, &reservation, gas_used)?;
+        self.metrics.total_sponsored_transactions.inc();
+        self.metrics.total_sponsored_gas.inc_by(gas_used);
+        if let MgoExecutionStatus::Failure { error } = effects.status() {
+            warn!(?coin_id, ?sender, "Sponsored transaction failed: {error}");
+        }
+        Ok(effects)
+    }
+
+    /// Returns the coins of reservations that expired without paying for a transaction to the
+    /// pool.
+    pub fn release_expired_reservations(&self) -> Result<(), GasStationError> {
+        let now_ms = now_ms();
+        let expired: Vec<_> = self
+            .reservations()
+            .into_iter()
+            .filter(|(_, reservation)| {
+                reservation.in_flight.is_none() && reservation.expiration_ms < now_ms
+            })
+            .collect();
+
+        info!("Releasing {} expired reservations", expired.len());
+        for (coin_id, reservation) in expired {
+            self.end_reservation(coin_id, &reservation, 0)?;
+            self.metrics.total_expired_reservations.inc();
+        }
+        self.budgets.lock().prune(Instant::now());
+        Ok(())
+    }
+
+    /// Ends the reservations of coins paying for transactions whose outcome was not known when
+    /// they were executed, once their effects are found. Coins of transactions that are still not
+    /// found `in_flight_timeout_secs` after their reservation expired are returned to the pool:
+    /// the sponsor's signature never leaves the gas station, so the transaction cannot be
+    /// submitted again by anyone else.
+    pub async fn resolve_in_flight_reservations(&self) -> Result<(), GasStationError> {
+        let in_flight: Vec<_> = self
+            .reservations()
+            .into_iter()
+            .filter_map(|(coin_id, reservation)| {
+                Some((coin_id, reservation.in_flight?, reservation))
+            })
+            .collect();
+        if in_flight.is_empty() {
+            return Ok(());
+        }
+
+        info!("Resolving {} reservations in flight", in_flight.len());
+        let client = self
+            .wallet
+            .get_client()
+            .await
+            .map_err(|e| GasStationError::Wallet(format!("Unable to get client: {e:?}")))?;
+        for (coin_id, tx_digest, reservation) in in_flight {
+            let effects = client
+                .read_api()
+                .get_transaction_with_options(
+                    tx_digest,
+                    MgoTransactionBlockResponseOptions::new().with_effects(),
+                )
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|response| {
+                    response
+                        .effects
+                        .ok_or_else(|| format!("effects field missing for txn {tx_digest}"))
+                });
+            match effects {
+                Ok(effects) => {
+                    let gas_used = gas_used(&effects);
+                    self.end_reservation(coin_id, &reservation, gas_used)?;
+                    self.metrics.total_sponsored_transactions.inc();
+                    self.metrics.total_sponsored_gas.inc_by(gas_used);
+                }
+                Err(err)
+                    if reservation.expiration_ms + self.config.in_flight_timeout_secs * 1000
+                        < now_ms() =>
+                {
+                    warn!(
+                        ?coin_id,
+                        ?tx_digest,
+                        "Releasing gas coin of sponsored transaction that was not found: {err}"
+                    );
+                    self.end_reservation(coin_id, &reservation, 0)?;
+                }
+                Err(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Merges the depleted coins and splits them back into coins of the maximum gas budget,
+    /// which are added to the pool.
+    pub async fn refill_pool(&self) -> Result<(), GasStationError> {
+        let coin_ids = {
+            let mut depleted = self.depleted.lock();
+            let len = depleted.len();
+            depleted.split_off(len.saturating_sub(MAX_COINS_PER_MERGE))
+        };
+        if coin_ids.is_empty() {
+            return Ok(());
+        }
+        self.metrics.total_depleted_coins.sub(coin_ids.len() as i64);
+
+        let coins = match self.get_gas_coins(&coin_ids).await {
+            Ok(coins) => coins,
+            Err(err) => {
+                self.set_aside_depleted(coin_ids);
+                return Err(err);
+            }
+        };
+        let discarded = coin_ids.len() - coins.len();
+        if discarded > 0 {
+            warn!("Discarding {discarded} depleted gas coins no longer owned by the sponsor.");
+            self.metrics.total_discarded_coins.add(discarded as i64);
+        }
+
+        match self.merge_and_split(&coins).await {
+            Ok(Some(refilled)) => {
+                info!(
+                    "Refilled gas pool with {} coins merged from {} depleted coins",
+                    refilled.len(),
+                    coins.len()
+                );
+                for coin_id in refilled {
+                    self.recycle_gas_coin(coin_id);
+                }
+                Ok(())
+            }
+            // Not enough balance to split a coin yet, try again once more coins are depleted.
+            Ok(None) => {
+                self.set_aside_depleted(coins.iter().map(|((coin_id, _, _), _)| *coin_id));
+                Ok(())
+            }
+            Err(err) => {
+                self.set_aside_depleted(coins.iter().map(|((coin_id, _, _), _)| *coin_id));
+                Err(err)
+            }
+        }
+    }
+
+    /// Merges `coins` and splits as many coins of the maximum gas budget out of them as they can
+    /// pay for. Returns the coins split and the merged coin, or `None` if the coins cannot pay for
+    /// a single coin.
+    async fn merge_and_split(
+        &self,
+        coins: &[(ObjectRef, u64)],
+    ) -> Result<Option<Vec<ObjectID>>, GasStationError> {
+        let budget = self.config.max_gas_budget;
+        // The merged coin also pays for the transaction.
+        let balance: u64 = coins.iter().map(|(_, balance)| balance).sum();
+        let num_coins =
+            ((balance.saturating_sub(budget) / budget) as usize).min(MAX_COINS_PER_SPLIT);
+        if num_coins == 0 {
+            return Ok(None);
+        }
+        let mut coin_refs: Vec<_> = coins.iter().map(|(coin_ref, _)| *coin_ref).collect();
+        let merged = coin_refs.remove(0);
+
+        let gas_price = self
+            .wallet
+            .get_reference_gas_price()
+            .await
+            .map_err(|e| GasStationError::Wallet(e.to_string()))?;
+        let tx_data = TransactionData::new_pay_mgo(
+            self.sponsor,
+            coin_refs,
+            vec![self.sponsor; num_coins],
+            vec![budget; num_coins],
+            merged,
+            budget,
+            gas_price,
+        )
+        .map_err(GasStationError::internal)?;
+        let signature = self
+            .wallet
+            .config
+            .keystore
+            .sign_secure(&self.sponsor, &tx_data, Intent::mgo_transaction())
+            .map_err(GasStationError::internal)?;
+        let response = self
+            .wallet
+            .execute_transaction_may_fail(Transaction::from_data(tx_data, vec![signature]))
+            .await
+            .map_err(|e| GasStationError::Execution(e.to_string()))?;
+        let effects = response.effects.ok_or_else(|| {
+            GasStationError::Execution(format!("effects field missing for txn {}", response.digest))
+        })?;
+        if let MgoExecutionStatus::Failure { error } = effects.status() {
+            return Err(GasStationError::Execution(format!(
+                "Failed to merge depleted gas coins: {error}"
+            )));
+        }
+        Ok(Some(
+            effects
+                .created()
+                .iter()
+                .map(|coin| coin.reference.object_id)
+                .chain([merged.0])
+                .collect(),
+        ))
+    }
+
+    fn set_aside_depleted(&self, coin_ids: impl IntoIterator<Item = ObjectID>) {
+        let mut depleted = self.depleted.lock();
+        let len = depleted.len();
+        depleted.extend(coin_ids);
+        self.metrics
+            .total_depleted_coins
+            .add((depleted.len() - len) as i64);
+    }
+
+    /// Returns all the reservations in the WAL.
+    fn reservations(&self) -> Vec<(ObjectID, Reservation)> {
+        // Safe unwrap as we are the only ones that ever add to the WAL.
+        self.wal
+            .lock()
+            .log
+            .safe_iter()
+            .map(|item| item.unwrap())
+            .collect()
+    }
+
+    fn check_reservation(
+        &self,
+        tx_data: &TransactionData,
+        coin_id: ObjectID,
+        reservation: &Reservation,
+    ) -> Result<(), GasStationError> {
+        if reservation.in_flight.is_some() {
+            return Err(GasStationError::ReservationInFlight(coin_id));
+        }
+        if reservation.expiration_ms < now_ms() {
+            return Err(GasStationError::ReservationExpired(coin_id));
+        }
+        if tx_data.gas_owner() != self.sponsor {
+            return Err(GasStationError::ReservationMismatch(format!(
+                "gas owner must be the sponsor {}",
+                self.sponsor
+            )));
+        }
+        if tx_data.sender() != reservation.sender {
+            return Err(GasStationError::ReservationMismatch(format!(
+                "gas coin {coin_id} is reserved for sender {}",
+                reservation.sender
+            )));
+        }
+        if tx_data.gas_budget() > reservation.gas_budget {
+            return Err(GasStationError::ReservationMismatch(format!(
+                "gas budget {} exceeds the reserved budget of {}",
+                tx_data.gas_budget(),
+                reservation.gas_budget
+            )));
+        }
+        Ok(())
+    }
+
+    fn sign_transaction(
+        &self,
+        tx_data: TransactionData,
+        user_sig: GenericSignature,
+    ) -> Result<Transaction, GasStationError> {
+        let sponsor_sig = self
+            .wallet
+            .config
+            .keystore
+            .sign_secure(&self.sponsor, &tx_data, Intent::mgo_transaction())
+            .map_err(GasStationError::internal)?;
+        Ok(Transaction::from_generic_sig_data(
+            tx_data,
+            vec![user_sig, GenericSignature::Signature(sponsor_sig)],
+        ))
+    }
+
+    /// Ends the reservation of `coin_id`, whose transaction used `gas_used`, and returns the coin
+    /// to the pool. Does nothing if the reservation already ended.
+    fn end_reservation(
+        &self,
+        coin_id: ObjectID,
+        reservation: &Reservation,
+        gas_used: u64,
+    ) -> Result<(), GasStationError> {
+        {
+            let mut wal = self.wal.lock();
+            if wal
+                .get(coin_id)
+                .map_err(GasStationError::internal)?
+                .is_none()
+            {
+                return Ok(());
+            }
+            wal.commit(coin_id).map_err(GasStationError::internal)?;
+        }
+        self.metrics.total_reserved_coins.dec();
+        self.budgets.lock().refund(
+            reservation.sender,
+            reservation.gas_budget.saturating_sub(gas_used),
+        );
+        self.free_client_reservation(reservation.client);
+        self.recycle_gas_coin(coin_id);
+        Ok(())
+    }
+
+    fn free_client_reservation(&self, client: Option<IpAddr>) {
+        let Some(client) = client else {
+            return;
+        };
+        let mut client_reservations = self.client_reservations.lock();
+        if let Some(reservations) = client_reservations.get_mut(&client) {
+            *reservations = reservations.saturating_sub(1);
+            if *reservations == 0 {
+                client_reservations.remove(&client);
+            }
+        }
+    }
+
+    fn recycle_gas_coin(&self, coin_id: ObjectID) {
+        self.pool.lock().push_back(coin_id);
+        self.metrics.total_available_coins.inc();
+    }
+
+    /// Returns the latest reference and the balance of `coin_id` if it is a gas coin owned by the
+    /// sponsor.
+    async fn get_gas_coin_and_check_sponsor_owner(
+        &self,
+        coin_id: ObjectID,
+    ) -> anyhow::Result<Option<(ObjectRef, u64)>> {
+        let client = self.wallet.get_client().await?;
+        let Some(o) = client
+            .read_api()
+            .get_object_with_options(
+                coin_id,
+                MgoObjectDataOptions::new()
+                    .with_type()
+                    .with_owner()
+                    .with_content(),
+            )
+            .await?
+            .data
+        else {
+            return Ok(None);
+        };
+        Ok(self.sponsor_gas_coin(&o))
+    }
+
+    /// Returns the latest references and balances of the coins of `coin_ids` that are gas coins
+    /// owned by the sponsor.
+    async fn get_gas_coins(
+        &self,
+        coin_ids: &[ObjectID],
+    ) -> Result<Vec<(ObjectRef, u64)>, GasStationError> {
+        let client = self
+            .wallet
+            .get_client()
+            .await
+            .map_err(|e| GasStationError::Wallet(format!("Unable to get client: {e:?}")))?;
+        let objects = client
+            .read_api()
+            .multi_get_object_with_options(
+                coin_ids.to_vec(),
+                MgoObjectDataOptions::new()
+                    .with_type()
+                    .with_owner()
+                    .with_content(),
+            )
+            .await
+            .map_err(|e| GasStationError::FullnodeReadingError(e.to_string()))?;
+        Ok(objects
+            .iter()
+            .filter_map(|response| self.sponsor_gas_coin(response.data.as_ref()?))
+            .collect())
+    }
+
+    fn sponsor_gas_coin(&self, o: &MgoObjectData) -> Option<(ObjectRef, u64)> {
+        let coin = GasCoin::try_from(o).ok()?;
+        match o.owner {
+            Some(Owner::AddressOwner(owner)) if owner == self.sponsor => {
+                Some((o.object_ref(), coin.value()))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn gas_used(effects: &MgoTransactionBlockEffects) -> u64 {
+    effects.gas_cost_summary().net_gas_usage().max(0) as u64
+}
+
+/// Splits the sponsor's largest coin until the sponsor has `num_coins` coins that can pay for the
+/// maximum gas budget, or the largest coin cannot be split any further.
+async fn split_coins(
+    wallet: &WalletContext,
+    sponsor: MgoAddress,
+    config: &GasStationConfig,
+) -> Result<(), GasStationError> {
+    let budget = config.max_gas_budget;
+    loop {
+        let coins = wallet
+            .gas_objects(sponsor)
+            .await
+            .map_err(|e| GasStationError::Wallet(e.to_string()))?;
+        let usable = coins
+            .iter()
+            .filter(|(balance, _)| *balance >= budget)
+            .count();
+        let Some((balance, largest)) = coins.iter().max_by_key(|(balance, _)| *balance) else {
+            return Err(GasStationError::Wallet(format!(
+                "Sponsor {sponsor} does not own any gas coins"
+            )));
+        };
+        // The largest coin also pays for splitting itself, and is usable unless it is split
+        // entirely.
+        let splittable = (balance.saturating_sub(budget) / budget) as usize;
+        let missing = config.num_coins.saturating_sub(usable);
+        let num_coins = missing.min(splittable).min(MAX_COINS_PER_SPLIT);
+        if num_coins == 0 {
+            if missing > 0 {
+                warn!("Sponsor {sponsor} only has {usable} usable gas coins");
+            }
+            return Ok(());
+        }
+
+        info!(coin_id = ?largest.object_id, "Splitting gas coin into {num_coins} coins.");
+        let tx_data = build_pay_mgo_txn(
+            wallet,
+            largest.object_id,
+            sponsor,
+            sponsor,
+            &vec![budget; num_coins],
+            budget,
+        )
+        .await
+        .map_err(|e| GasStationError::Wallet(e.to_string()))?;
+        let signature = wallet
+            .config
+            .keystore
+            .sign_secure(&sponsor, &tx_data, Intent::mgo_transaction())
+            .map_err(GasStationError::internal)?;
+        let response = wallet
+            .execute_transaction_may_fail(Transaction::from_data(tx_data, vec![signature]))
+            .await
+            .map_err(|e| GasStationError::Execution(e.to_string()))?;
+        if let Some(MgoExecutionStatus::Failure { error }) =
+            response.effects.as_ref().map(|effects| effects.status())
+        {
+            return Err(GasStationError::Execution(format!(
+                "Failed to split gas coin {}: {error}",
+                largest.object_id
+            )));
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Current time should be after the Unix epoch")
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mgo_types::digests::TransactionDigest;
+    use mgo_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+    use mgo_types::{Identifier, MGO_FRAMEWORK_PACKAGE_ID};
+    use test_cluster::TestClusterBuilder;
+
+    const BUDGET: u64 = 100_000_000;
+
+    fn config(tmp: &tempfile::TempDir) -> GasStationConfig {
+        GasStationConfig {
+            write_ahead_log: tmp.path().join("wal"),
+            num_coins: 5,
+            max_gas_budget: BUDGET,
+            ..Default::default()
+        }
+    }
+
+    fn sender(gas_station: &GasStation) -> MgoAddress {
+        gas_station
+            .wallet
+            .get_addresses()
+            .into_iter()
+            .find(|address| *address != gas_station.sponsor)
+            .unwrap()
+    }
+
+    /// A transaction by `sender` that only reads its own address, paid for by `gas`.
+    async fn sponsored_transaction(
+        gas_station: &GasStation,
+        sender: MgoAddress,
+        gas: &ReservedGas,
+    ) -> (TransactionData, GenericSignature) {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        builder.programmable_move_call(
+            MGO_FRAMEWORK_PACKAGE_ID,
+            Identifier::new("tx_context").unwrap(),
+            Identifier::new("sender").unwrap(),
+            vec![],
+            vec![],
+        );
+        let tx_data = TransactionData::new_programmable_allow_sponsor(
+            sender,
+            gas.gas_coins.clone(),
+            builder.finish(),
+            BUDGET,
+            gas_station.wallet.get_reference_gas_price().await.unwrap(),
+            gas.sponsor,
+        );
+        let signature = gas_station
+            .wallet
+            .config
+            .keystore
+            .sign_secure(&sender, &tx_data, Intent::mgo_transaction())
+            .unwrap();
+        (tx_data, GenericSignature::Signature(signature))
+    }
+
+    #[tokio::test]
+    async fn test_reserve_and_execute() {
+        let test_cluster = TestClusterBuilder::new().build().await;
+        let tmp = tempfile::tempdir().unwrap();
+        let gas_station = GasStation::new(test_cluster.wallet, &Registry::new(), config(&tmp))
+            .await
+            .unwrap();
+        let sender = sender(&gas_station);
+        let available = gas_station.metrics.total_available_coins.get();
+
+        let gas = gas_station
+            .reserve_gas(sender, None, BUDGET, None)
+            .await
+            .unwrap();
+        assert_eq!(gas.sponsor, gas_station.sponsor());
+        assert_eq!(gas_station.metrics.total_reserved_coins.get(), 1);
+        assert_eq!(
+            gas_station.metrics.total_available_coins.get(),
+            available - 1
+        );
+
+        let (tx_data, user_sig) = sponsored_transaction(&gas_station, sender, &gas).await;
+        let effects = gas_station
+            .execute_transaction(tx_data.clone(), user_sig.clone())
+            .await
+            .unwrap();
+        assert_eq!(*effects.status(), MgoExecutionStatus::Success);
+        assert_eq!(effects.gas_object().reference.object_id, gas.gas_coins[0].0);
+
+        // The coin is back in the pool, and the reservation cannot be used again.
+        assert_eq!(gas_station.metrics.total_reserved_coins.get(), 0);
+        assert_eq!(gas_station.metrics.total_available_coins.get(), available);
+        assert_eq!(gas_station.metrics.total_sponsored_transactions.get(), 1);
+        assert!(matches!(
+            gas_station.execute_transaction(tx_data, user_sig).await,
+            Err(GasStationError::ReservationNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reservation_limits() {
+        let test_cluster = TestClusterBuilder::new().build().await;
+        let tmp = tempfile::tempdir().unwrap();
+        let config = GasStationConfig {
+            max_reservations_per_client: 2,
+            per_user_budget: Some(3 * BUDGET),
+            ..config(&tmp)
+        };
+        let gas_station = GasStation::new(test_cluster.wallet, &Registry::new(), config)
+            .await
+            .unwrap();
+        let sender = sender(&gas_station);
+        let client: IpAddr = "10.0.0.1".parse().unwrap();
+        let other_client: IpAddr = "10.0.0.2".parse().unwrap();
+
+        for _ in 0..2 {
+            gas_station
+                .reserve_gas(sender, Some(client), BUDGET, Some(0))
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            gas_station
+                .reserve_gas(sender, Some(client), BUDGET, Some(0))
+                .await,
+            Err(GasStationError::TooManyReservations(client))
+        );
+
+        // The budget of the sender is charged as soon as gas is reserved.
+        gas_station
+            .reserve_gas(sender, Some(other_client), BUDGET, Some(0))
+            .await
+            .unwrap();
+        assert_eq!(
+            gas_station
+                .reserve_gas(sender, Some(other_client), BUDGET, Some(0))
+                .await,
+            Err(GasStationError::UserBudgetExceeded(sender))
+        );
+        assert_eq!(gas_station.metrics.total_reserved_coins.get(), 3);
+
+        // Releasing the reservations refunds the budget and frees the client's reservations.
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        gas_station.release_expired_reservations().unwrap();
+        assert_eq!(gas_station.metrics.total_reserved_coins.get(), 0);
+        assert_eq!(gas_station.metrics.total_expired_reservations.get(), 3);
+        for _ in 0..2 {
+            gas_station
+                .reserve_gas(sender, Some(client), BUDGET, None)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refill_depleted_coins() {
+        let test_cluster = TestClusterBuilder::new().build().await;
+        let tmp = tempfile::tempdir().unwrap();
+        let mut wallet = test_cluster.wallet;
+        let sponsor = wallet.active_address().unwrap();
+
+        // Leave the sponsor with coins that cannot pay for the maximum gas budget.
+        let (_, coin) = wallet.gas_objects(sponsor).await.unwrap().remove(0);
+        let tx_data = build_pay_mgo_txn(
+            &wallet,
+            coin.object_id,
+            sponsor,
+            sponsor,
+            &[BUDGET * 3 / 4; 4],
+            BUDGET,
+        )
+        .await
+        .unwrap();
+        wallet
+            .execute_transaction_must_succeed(wallet.sign_transaction(&tx_data))
+            .await;
+
+        let gas_station = GasStation::new(wallet, &Registry::new(), config(&tmp))
+            .await
+            .unwrap();
+        assert_eq!(gas_station.metrics.total_depleted_coins.get(), 4);
+        let available = gas_station.metrics.total_available_coins.get();
+
+        // The depleted coins are merged into one coin, out of which two coins are split, as the
+        // merged coin pays for the transaction.
+        gas_station.refill_pool().await.unwrap();
+        assert_eq!(gas_station.metrics.total_depleted_coins.get(), 0);
+        assert_eq!(
+            gas_station.metrics.total_available_coins.get(),
+            available + 3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_in_flight_reservations() {
+        let test_cluster = TestClusterBuilder::new().build().await;
+        let tmp = tempfile::tempdir().unwrap();
+        let config = GasStationConfig {
+            in_flight_timeout_secs: 0,
+            ..config(&tmp)
+        };
+        let gas_station = GasStation::new(test_cluster.wallet, &Registry::new(), config)
+            .await
+            .unwrap();
+        let sender = sender(&gas_station);
+
+        // A transaction that was submitted without its outcome being known.
+        let expired = gas_station
+            .reserve_gas(sender, None, BUDGET, Some(0))
+            .await
+            .unwrap();
+        let valid = gas_station
+            .reserve_gas(sender, None, BUDGET, None)
+            .await
+            .unwrap();
+        for gas in [&expired, &valid] {
+            gas_station
+                .wal
+                .lock()
+                .set_in_flight(gas.gas_coins[0].0, TransactionDigest::random())
+                .unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // Coins in flight are not released when their reservation expires.
+        gas_station.release_expired_reservations().unwrap();
+        assert_eq!(gas_station.metrics.total_reserved_coins.get(), 2);
+
+        // Only the coin of the expired reservation is released, as its transaction is not found.
+        gas_station.resolve_in_flight_reservations().await.unwrap();
+        assert_eq!(gas_station.metrics.total_reserved_coins.get(), 1);
+        let wal = gas_station.wal.lock();
+        assert!(wal.get(expired.gas_coins[0].0).unwrap().is_none());
+        assert!(wal.get(valid.gas_coins[0].0).unwrap().is_some());
+    }
+}
@@ -0,0 +1,18 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+mod config;
+mod errors;
+mod gas_station;
+mod metrics;
+mod policy;
+mod requests;
+mod responses;
+mod write_ahead_log;
+
+pub use config::*;
+pub use errors::GasStationError;
+pub use gas_station::*;
+pub use policy::*;
+pub use requests::*;
+pub use responses::*;
@@ -0,0 +1,237 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use axum::{
+    error_handling::HandleErrorLayer,
+    extract::ConnectInfo,
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    BoxError, Extension, Json, Router,
+};
+use clap::Parser;
+use fastcrypto::encoding::{Base64, Encoding};
+use fastcrypto::traits::ToFromBytes;
+use http::Method;
+use mango_metrics::spawn_monitored_task;
+use mgo_config::{mgo_config_dir, MGO_CLIENT_CONFIG};
+use mgo_gas_station::{
+    ExecuteTransactionRequest, ExecuteTransactionResponse, GasStation, GasStationConfig,
+    GasStationError, ReserveGasRequest, ReserveGasResponse,
+};
+use mgo_sdk::wallet_context::WalletContext;
+use mgo_types::signature::GenericSignature;
+use mgo_types::transaction::TransactionData;
+use std::{
+    borrow::Cow,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+use tower::{limit::RateLimitLayer, ServiceBuilder};
+use tower_http::cors::{Any, CorsLayer};
+use tracing::{error, info, warn};
+
+const CONCURRENCY_LIMIT: usize = 30;
+
+const PROM_PORT_ADDR: &str = "0.0.0.0:9184";
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    // initialize tracing
+    let _guard = telemetry_subscribers::TelemetryConfig::new()
+        .with_env()
+        .init();
+
+    let config = GasStationConfig::parse();
+    let GasStationConfig {
+        port,
+        host_ip,
+        request_buffer_size,
+        max_request_per_second,
+        wallet_client_timeout_secs,
+        release_interval_secs,
+        ..
+    } = config;
+
+    let context = create_wallet_context(wallet_client_timeout_secs).await?;
+
+    let prom_binding = PROM_PORT_ADDR.parse().unwrap();
+    info!("Starting Prometheus HTTP endpoint at {}", prom_binding);
+    let registry_service = mango_metrics::start_prometheus_server(prom_binding);
+    let prometheus_registry = registry_service.default_registry();
+    let gas_station = GasStation::new(context, &prometheus_registry, config).await?;
+
+    let cors = CorsLayer::new()
+        .allow_methods(vec![Method::GET, Method::POST])
+        .allow_headers(Any)
+        .allow_origin(Any);
+
+    let app = Router::new()
+        .route("/", get(health))
+        .route("/v1/reserve_gas", post(reserve_gas))
+        .route("/v1/execute_tx", post(execute_tx))
+        .layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(handle_error))
+                .layer(cors)
+                .load_shed()
+                .buffer(request_buffer_size)
+                .layer(RateLimitLayer::new(
+                    max_request_per_second,
+                    Duration::from_secs(1),
+                ))
+                .concurrency_limit(CONCURRENCY_LIMIT)
+                .layer(Extension(gas_station.clone()))
+                .into_inner(),
+        );
+
+    spawn_monitored_task!(async move {
+        info!("Starting task to release expired reservations and refill the gas pool.");
+        loop {
+            tokio::time::sleep(Duration::from_secs(release_interval_secs)).await;
+            if let Err(err) = gas_station.release_expired_reservations() {
+                error!("Failed to release expired reservations: {err:?}");
+            }
+            if let Err(err) = gas_station.resolve_in_flight_reservations().await {
+                error!("Failed to resolve reservations in flight: {err:?}");
+            }
+            if let Err(err) = gas_station.refill_pool().await {
+                error!("Failed to refill the gas pool: {err:?}");
+            }
+        }
+    });
+
+    let addr = SocketAddr::new(IpAddr::V4(host_ip), port);
+    info!("listening on {}", addr);
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+        .await?;
+    Ok(())
+}
+
+/// basic handler that responds with a static string
+async fn health() -> &'static str {
+    "OK"
+}
+
+/// handler for reserve_gas requests
+async fn reserve_gas(
+    Extension(gas_station): Extension<Arc<GasStation>>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    Json(request): Json<ReserveGasRequest>,
+) -> impl IntoResponse {
+    info!(sender = ?request.sender, ?client, "Got new gas reservation request.");
+    // We spawn a tokio task for this such that connection drop will not interrupt it and leave
+    // the coin out of both the pool and the WAL
+    let result = spawn_monitored_task!(async move {
+        gas_station
+            .reserve_gas(
+                request.sender,
+                Some(client.ip()),
+                request.gas_budget,
+                request.reserve_duration_secs,
+            )
+            .await
+    })
+    .await
+    .unwrap();
+
+    match result {
+        Ok(v) => (StatusCode::OK, Json(ReserveGasResponse::from(v))),
+        Err(e) => {
+            warn!("Failed to reserve gas: {:?}", e);
+            (error_status(&e), Json(ReserveGasResponse::from(e)))
+        }
+    }
+}
+
+/// handler for execute_tx requests
+async fn execute_tx(
+    Extension(gas_station): Extension<Arc<GasStation>>,
+    Json(request): Json<ExecuteTransactionRequest>,
+) -> impl IntoResponse {
+    let (tx_data, user_sig) = match parse_transaction(&request) {
+        Ok(v) => v,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ExecuteTransactionResponse::from(e)),
+            )
+        }
+    };
+    // We spawn a tokio task for this such that connection drop will not interrupt it and leave
+    // the reservation in flight
+    let result =
+        spawn_monitored_task!(
+            async move { gas_station.execute_transaction(tx_data, user_sig).await }
+        )
+        .await
+        .unwrap();
+
+    match result {
+        Ok(v) => (StatusCode::OK, Json(ExecuteTransactionResponse::from(v))),
+        Err(e) => {
+            warn!("Failed to execute sponsored transaction: {:?}", e);
+            (error_status(&e), Json(ExecuteTransactionResponse::from(e)))
+        }
+    }
+}
+
+fn parse_transaction(
+    request: &ExecuteTransactionRequest,
+) -> Result<(TransactionData, GenericSignature), GasStationError> {
+    let tx_bytes = Base64::decode(&request.tx_bytes)
+        .map_err(|e| GasStationError::InvalidRequest(format!("Invalid tx_bytes: {e}")))?;
+    let tx_data = bcs::from_bytes(&tx_bytes)
+        .map_err(|e| GasStationError::InvalidRequest(format!("Invalid transaction: {e}")))?;
+    let user_sig = Base64::decode(&request.user_sig)
+        .map_err(|e| GasStationError::InvalidRequest(format!("Invalid user_sig: {e}")))?;
+    let user_sig = GenericSignature::from_bytes(&user_sig)
+        .map_err(|e| GasStationError::InvalidRequest(format!("Invalid signature: {e}")))?;
+    Ok((tx_data, user_sig))
+}
+
+fn error_status(error: &GasStationError) -> StatusCode {
+    match error {
+        GasStationError::NoGasCoinAvailable => StatusCode::SERVICE_UNAVAILABLE,
+        GasStationError::UserBudgetExceeded(_) | GasStationError::TooManyReservations(_) => {
+            StatusCode::TOO_MANY_REQUESTS
+        }
+        GasStationError::ReservationNotFound => StatusCode::NOT_FOUND,
+        GasStationError::ReservationExpired(_)
+        | GasStationError::ReservationInFlight(_)
+        | GasStationError::ReservationMismatch(_)
+        | GasStationError::PolicyViolation(_)
+        | GasStationError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        GasStationError::FullnodeReadingError(_)
+        | GasStationError::Wallet(_)
+        | GasStationError::Execution(_)
+        | GasStationError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+async fn create_wallet_context(timeout_secs: u64) -> Result<WalletContext, anyhow::Error> {
+    let wallet_conf = mgo_config_dir()?.join(MGO_CLIENT_CONFIG);
+    info!("Initialize wallet from config path: {:?}", wallet_conf);
+    WalletContext::new(
+        &wallet_conf,
+        Some(Duration::from_secs(timeout_secs)),
+        Some(1000),
+    )
+    .await
+}
+
+async fn handle_error(error: BoxError) -> impl IntoResponse {
+    if error.is::<tower::load_shed::error::Overloaded>() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Cow::from("service is overloaded, please try again later"),
+        );
+    }
+
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Cow::from(format!("Unhandled internal error: {}", error)),
+    )
+}
@@ -0,0 +1,75 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use prometheus::{
+    register_int_counter_with_registry, register_int_gauge_with_registry, IntCounter, IntGauge,
+    Registry,
+};
+
+/// Metrics relevant to the running of the gas station
+#[derive(Clone, Debug)]
+pub struct GasStationMetrics {
+    pub(crate) total_available_coins: IntGauge,
+    pub(crate) total_reserved_coins: IntGauge,
+    pub(crate) total_discarded_coins: IntGauge,
+    pub(crate) total_depleted_coins: IntGauge,
+    pub(crate) total_expired_reservations: IntCounter,
+    pub(crate) total_rejected_transactions: IntCounter,
+    pub(crate) total_sponsored_transactions: IntCounter,
+    pub(crate) total_sponsored_gas: IntCounter,
+}
+
+impl GasStationMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            total_available_coins: register_int_gauge_with_registry!(
+                "total_available_coins",
+                "Total number of available gas coins in the gas station",
+                registry,
+            )
+            .unwrap(),
+            total_reserved_coins: register_int_gauge_with_registry!(
+                "total_reserved_coins",
+                "Total number of gas coins currently reserved",
+                registry,
+            )
+            .unwrap(),
+            total_discarded_coins: register_int_gauge_with_registry!(
+                "total_discarded_coins",
+                "Total number of discarded gas coins",
+                registry,
+            )
+            .unwrap(),
+            total_depleted_coins: register_int_gauge_with_registry!(
+                "total_depleted_coins",
+                "Total number of gas coins waiting to be merged back into the pool",
+                registry,
+            )
+            .unwrap(),
+            total_expired_reservations: register_int_counter_with_registry!(
+                "total_expired_reservations",
+                "Total number of reservations released after they expired",
+                registry,
+            )
+            .unwrap(),
+            total_rejected_transactions: register_int_counter_with_registry!(
+                "total_rejected_transactions",
+                "Total number of transactions the gas station refused to sponsor",
+                registry,
+            )
+            .unwrap(),
+            total_sponsored_transactions: register_int_counter_with_registry!(
+                "total_sponsored_transactions",
+                "Total number of sponsored transactions executed",
+                registry,
+            )
+            .unwrap(),
+            total_sponsored_gas: register_int_counter_with_registry!(
+                "total_sponsored_gas",
+                "Total gas paid for sponsored transactions",
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+}
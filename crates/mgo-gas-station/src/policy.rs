@@ -0,0 +1,312 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{GasStationConfig, GasStationError};
+use anyhow::bail;
+use mgo_types::base_types::{MgoAddress, ObjectID};
+use mgo_types::transaction::{
+    Argument, Command, TransactionData, TransactionDataAPI, TransactionKind,
+};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// A Move function, written as `<package>::<module>::<function>`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MoveFunctionId {
+    pub package: ObjectID,
+    pub module: String,
+    pub function: String,
+}
+
+impl FromStr for MoveFunctionId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split("::").collect();
+        let [package, module, function] = parts[..] else {
+            bail!("Expected <package>::<module>::<function>, got {s}");
+        };
+        Ok(Self {
+            package: ObjectID::from_str(package)?,
+            module: module.to_string(),
+            function: function.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for MoveFunctionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}::{}", self.package, self.module, self.function)
+    }
+}
+
+/// Decides which transactions the gas station sponsors.
+#[derive(Debug)]
+pub struct SponsorPolicy {
+    sponsor: MgoAddress,
+    max_gas_budget: u64,
+    allowed_packages: BTreeSet<ObjectID>,
+    allowed_functions: BTreeSet<MoveFunctionId>,
+}
+
+impl SponsorPolicy {
+    pub fn new(sponsor: MgoAddress, config: &GasStationConfig) -> Self {
+        Self {
+            sponsor,
+            max_gas_budget: config.max_gas_budget,
+            allowed_packages: config.allowed_packages.iter().copied().collect(),
+            allowed_functions: config.allowed_functions.iter().cloned().collect(),
+        }
+    }
+
+    /// Checks a request by `sender` to reserve `gas_budget`.
+    pub fn check_reservation(
+        &self,
+        sender: MgoAddress,
+        gas_budget: u64,
+    ) -> Result<(), GasStationError> {
+        if sender == self.sponsor {
+            return Err(GasStationError::PolicyViolation(
+                "the sponsor cannot sponsor its own transactions".to_string(),
+            ));
+        }
+        if gas_budget > self.max_gas_budget {
+            return Err(GasStationError::PolicyViolation(format!(
+                "gas budget {gas_budget} exceeds the maximum of {}",
+                self.max_gas_budget
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks a transaction before the sponsor signs it. Sponsored transactions can only call
+    /// allowed Move functions, and must not use the sponsor's gas coin other than to pay for gas.
+    /// Without any allowed packages or functions, every Move function can be called.
+    pub fn check_transaction(&self, tx_data: &TransactionData) -> Result<(), GasStationError> {
+        let TransactionKind::ProgrammableTransaction(pt) = tx_data.kind() else {
+            return Err(GasStationError::PolicyViolation(
+                "only programmable transactions can be sponsored".to_string(),
+            ));
+        };
+        for command in &pt.commands {
+            let arguments: Vec<&Argument> = match command {
+                Command::MoveCall(call) => {
+                    let function = MoveFunctionId {
+                        package: call.package,
+                        module: call.module.to_string(),
+                        function: call.function.to_string(),
+                    };
+                    if !self.is_allowed(&function) {
+                        return Err(GasStationError::PolicyViolation(format!(
+                            "calls to {function} are not sponsored"
+                        )));
+                    }
+                    call.arguments.iter().collect()
+                }
+                Command::TransferObjects(objects, recipient) => {
+                    objects.iter().chain([recipient]).collect()
+                }
+                Command::SplitCoins(coin, amounts) => [coin].into_iter().chain(amounts).collect(),
+                Command::MergeCoins(coin, coins) => [coin].into_iter().chain(coins).collect(),
+                Command::MakeMoveVec(_, elements) => elements.iter().collect(),
+                Command::Publish(..) | Command::Upgrade(..) => {
+                    return Err(GasStationError::PolicyViolation(
+                        "package publishing and upgrades are not sponsored".to_string(),
+                    ));
+                }
+            };
+            if arguments.contains(&&Argument::GasCoin) {
+                return Err(GasStationError::PolicyViolation(
+                    "sponsored transactions cannot use the gas coin".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn is_allowed(&self, function: &MoveFunctionId) -> bool {
+        (self.allowed_packages.is_empty() && self.allowed_functions.is_empty())
+            || self.allowed_packages.contains(&function.package)
+            || self.allowed_functions.contains(function)
+    }
+}
+
+/// Tracks the gas budget every sender was sponsored for in the current window, and enforces the
+/// per-user budget if one is configured.
+pub struct UserBudgets {
+    limit: Option<u64>,
+    window: Duration,
+    spent: HashMap<MgoAddress, (Instant, u64)>,
+}
+
+impl UserBudgets {
+    pub fn new(limit: Option<u64>, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            spent: HashMap::new(),
+        }
+    }
+
+    /// Charges `amount` to `sender`, failing if that exceeds the sender's budget.
+    pub fn charge(
+        &mut self,
+        sender: MgoAddress,
+        amount: u64,
+        now: Instant,
+    ) -> Result<(), GasStationError> {
+        let Some(limit) = self.limit else {
+            return Ok(());
+        };
+        let (window_start, spent) = self.spent.entry(sender).or_insert((now, 0));
+        if now.saturating_duration_since(*window_start) >= self.window {
+            *window_start = now;
+            *spent = 0;
+        }
+        if spent.saturating_add(amount) > limit {
+            return Err(GasStationError::UserBudgetExceeded(sender));
+        }
+        *spent += amount;
+        Ok(())
+    }
+
+    /// Returns `amount` previously charged to `sender`, e.g. when a transaction used less gas
+    /// than its budget.
+    pub fn refund(&mut self, sender: MgoAddress, amount: u64) {
+        if let Some((_, spent)) = self.spent.get_mut(&sender) {
+            *spent = spent.saturating_sub(amount);
+        }
+    }
+
+    /// Forgets senders whose window has ended.
+    pub fn prune(&mut self, now: Instant) {
+        let window = self.window;
+        self.spent
+            .retain(|_, (window_start, _)| now.saturating_duration_since(*window_start) < window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mgo_types::base_types::random_object_ref;
+    use mgo_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+    use mgo_types::transaction::ProgrammableMoveCall;
+    use mgo_types::{Identifier, MGO_FRAMEWORK_PACKAGE_ID};
+
+    fn move_call(package: ObjectID, module: &str, function: &str) -> Command {
+        Command::MoveCall(Box::new(ProgrammableMoveCall {
+            package,
+            module: Identifier::new(module).unwrap(),
+            function: Identifier::new(function).unwrap(),
+            type_arguments: vec![],
+            arguments: vec![],
+        }))
+    }
+
+    fn transaction(
+        sender: MgoAddress,
+        sponsor: MgoAddress,
+        commands: Vec<Command>,
+    ) -> TransactionData {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        for command in commands {
+            builder.command(command);
+        }
+        TransactionData::new_programmable_allow_sponsor(
+            sender,
+            vec![random_object_ref()],
+            builder.finish(),
+            1_000_000,
+            1000,
+            sponsor,
+        )
+    }
+
+    #[test]
+    fn test_move_function_id() {
+        let function: MoveFunctionId = "0x2::coin::split".parse().unwrap();
+        assert_eq!(function.package, MGO_FRAMEWORK_PACKAGE_ID);
+        assert_eq!(function.module, "coin");
+        assert_eq!(function.function, "split");
+        assert!("0x2::coin".parse::<MoveFunctionId>().is_err());
+        assert!("0x2::coin::split::extra".parse::<MoveFunctionId>().is_err());
+        assert!("not_an_id::coin::split".parse::<MoveFunctionId>().is_err());
+    }
+
+    #[test]
+    fn test_sponsor_policy() {
+        let sponsor = MgoAddress::random_for_testing_only();
+        let sender = MgoAddress::random_for_testing_only();
+        let (allowed_package, other_package) = (ObjectID::random(), ObjectID::random());
+        let policy = SponsorPolicy::new(
+            sponsor,
+            &GasStationConfig {
+                max_gas_budget: 1_000,
+                allowed_packages: vec![allowed_package],
+                allowed_functions: vec![MoveFunctionId {
+                    package: other_package,
+                    module: "game".to_string(),
+                    function: "play".to_string(),
+                }],
+                ..Default::default()
+            },
+        );
+
+        assert!(policy.check_reservation(sender, 1_000).is_ok());
+        assert!(policy.check_reservation(sender, 1_001).is_err());
+        assert!(policy.check_reservation(sponsor, 1).is_err());
+
+        let check = |commands| policy.check_transaction(&transaction(sender, sponsor, commands));
+        assert!(check(vec![move_call(allowed_package, "any", "function")]).is_ok());
+        assert!(check(vec![move_call(other_package, "game", "play")]).is_ok());
+        assert!(check(vec![move_call(other_package, "game", "cheat")]).is_err());
+        assert!(check(vec![Command::SplitCoins(Argument::Input(0), vec![])]).is_ok());
+        // The sponsor's gas coin must not be spent or transferred.
+        assert!(check(vec![Command::SplitCoins(Argument::GasCoin, vec![])]).is_err());
+        assert!(check(vec![Command::TransferObjects(
+            vec![Argument::GasCoin],
+            Argument::Input(0)
+        )])
+        .is_err());
+        assert!(check(vec![Command::Publish(vec![], vec![])]).is_err());
+
+        // Without an allowlist every function can be called.
+        let policy = SponsorPolicy::new(sponsor, &GasStationConfig::default());
+        assert!(policy
+            .check_transaction(&transaction(
+                sender,
+                sponsor,
+                vec![move_call(other_package, "game", "cheat")]
+            ))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_user_budgets() {
+        let sender = MgoAddress::random_for_testing_only();
+        let other_sender = MgoAddress::random_for_testing_only();
+        let mut budgets = UserBudgets::new(Some(100), Duration::from_secs(60));
+        let now = Instant::now();
+
+        assert!(budgets.charge(sender, 60, now).is_ok());
+        assert!(budgets.charge(sender, 60, now).is_err());
+        assert!(budgets.charge(other_sender, 100, now).is_ok());
+        budgets.refund(sender, 20);
+        assert!(budgets.charge(sender, 60, now).is_ok());
+        assert!(budgets.charge(sender, 1, now).is_err());
+
+        // The budget is restored once the window ends.
+        let now = now + Duration::from_secs(60);
+        assert!(budgets.charge(sender, 100, now).is_ok());
+        budgets.prune(now + Duration::from_secs(59));
+        assert_eq!(budgets.spent.len(), 1);
+
+        // Without a limit, senders are never charged.
+        let mut budgets = UserBudgets::new(None, Duration::from_secs(60));
+        assert!(budgets.charge(sender, u64::MAX, now).is_ok());
+        assert!(budgets.charge(sender, u64::MAX, now).is_ok());
+    }
+}
@@ -0,0 +1,25 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use mgo_types::base_types::MgoAddress;
+use serde::{Deserialize, Serialize};
+
+/// Reserves a gas coin paying up to `gas_budget` for a transaction sent by `sender`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReserveGasRequest {
+    pub sender: MgoAddress,
+    pub gas_budget: u64,
+    /// How long the gas coin is reserved for. Defaults to `default-reservation-secs`.
+    pub reserve_duration_secs: Option<u64>,
+}
+
+/// Executes a transaction paid for by a reserved gas coin.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteTransactionRequest {
+    /// Base64 encoded BCS bytes of the `TransactionData`.
+    pub tx_bytes: String,
+    /// Base64 encoded signature of the sender.
+    pub user_sig: String,
+}
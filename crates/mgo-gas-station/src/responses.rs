@@ -0,0 +1,66 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::*;
+use mgo_json_rpc_types::MgoTransactionBlockEffects;
+use mgo_types::base_types::{MgoAddress, ObjectRef};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReservedGas {
+    pub sponsor: MgoAddress,
+    pub gas_coins: Vec<ObjectRef>,
+    /// Unix timestamp after which the reservation is released.
+    pub expiration_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReserveGasResponse {
+    pub result: Option<ReservedGas>,
+    pub error: Option<String>,
+}
+
+impl From<GasStationError> for ReserveGasResponse {
+    fn from(e: GasStationError) -> Self {
+        Self {
+            result: None,
+            error: Some(e.to_string()),
+        }
+    }
+}
+
+impl From<ReservedGas> for ReserveGasResponse {
+    fn from(v: ReservedGas) -> Self {
+        Self {
+            result: Some(v),
+            error: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteTransactionResponse {
+    pub effects: Option<MgoTransactionBlockEffects>,
+    pub error: Option<String>,
+}
+
+impl From<GasStationError> for ExecuteTransactionResponse {
+    fn from(e: GasStationError) -> Self {
+        Self {
+            effects: None,
+            error: Some(e.to_string()),
+        }
+    }
+}
+
+impl From<MgoTransactionBlockEffects> for ExecuteTransactionResponse {
+    fn from(v: MgoTransactionBlockEffects) -> Self {
+        Self {
+            effects: Some(v),
+            error: None,
+        }
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use mgo_types::base_types::{MgoAddress, ObjectID};
+use mgo_types::digests::TransactionDigest;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::Path;
+use tracing::info;
+use typed_store::rocks::DBMap;
+use typed_store::traits::{TableSummary, TypedStoreDebug};
+use typed_store::{Map, TypedStoreError};
+use typed_store_derive::DBMapUtils;
+
+/// Persistent log of gas coins reserved by the gas station, keyed by the reserved coin.
+/// Reservations are written to the log before they are handed out, and removed once the effects
+/// of the sponsored transaction are known or the reservation expired, before the coin goes back
+/// to the pool.
+///
+/// This allows the gas station to go down and back up without handing out coins that are still
+/// reserved, or that pay for a transaction in flight.
+#[derive(DBMapUtils, Clone)]
+pub struct WriteAheadLog {
+    pub log: DBMap<ObjectID, Reservation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct Reservation {
+    pub sender: MgoAddress,
+    /// The client the coin was reserved by, if known.
+    pub client: Option<IpAddr>,
+    pub gas_budget: u64,
+    pub expiration_ms: u64,
+    /// The sponsored transaction paying with the coin, once it was submitted.
+    pub in_flight: Option<TransactionDigest>,
+}
+
+impl WriteAheadLog {
+    pub(crate) fn open(path: &Path) -> Self {
+        Self::open_tables_read_write(
+            path.to_path_buf(),
+            typed_store::rocks::MetricConf::new("gas_station_write_ahead_log"),
+            None,
+            None,
+        )
+    }
+
+    /// Mark `coin` as reserved by `client` for a transaction by `sender` until `expiration_ms`.
+    /// Fails if `coin` is already reserved.
+    pub(crate) fn reserve(
+        &mut self,
+        coin: ObjectID,
+        sender: MgoAddress,
+        client: Option<IpAddr>,
+        gas_budget: u64,
+        expiration_ms: u64,
+    ) -> Result<(), TypedStoreError> {
+        if self.log.contains_key(&coin)? {
+            // Don't permit multiple reservations of the same coin
+            return Err(TypedStoreError::SerializationError(format!(
+                "Duplicate WAL entry for coin {coin:?}",
+            )));
+        }
+
+        self.log.insert(
+            &coin,
+            &Reservation {
+                sender,
+                client,
+                gas_budget,
+                expiration_ms,
+                in_flight: None,
+            },
+        )
+    }
+
+    /// Returns the reservation of `coin`, if any.
+    pub(crate) fn get(&self, coin: ObjectID) -> Result<Option<Reservation>, TypedStoreError> {
+        self.log.get(&coin)
+    }
+
+    /// Indicate that the reservation of `coin` is over, and the entry in the WAL can be removed.
+    pub(crate) fn commit(&mut self, coin: ObjectID) -> Result<(), TypedStoreError> {
+        self.log.remove(&coin)
+    }
+
+    /// Records that `coin` pays for the transaction `tx_digest`, which was submitted.
+    pub(crate) fn set_in_flight(
+        &mut self,
+        coin: ObjectID,
+        tx_digest: TransactionDigest,
+    ) -> Result<(), TypedStoreError> {
+        if let Some(mut entry) = self.log.get(&coin)? {
+            entry.in_flight = Some(tx_digest);
+            self.log.insert(&coin, &entry)?;
+        } else {
+            info!(
+                ?coin,
+                "Attempted to set inflight a coin that was not in the WAL."
+            );
+
+            return Err(TypedStoreError::RocksDBError(format!(
+                "Coin object {coin:?} not found in WAL."
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reserve_get_commit() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut wal = WriteAheadLog::open(&tmp.path().join("wal"));
+
+        let coin = ObjectID::random();
+        let sender = MgoAddress::random_for_testing_only();
+        wal.reserve(coin, sender, None, 1000, 42).unwrap();
+
+        let Some(entry) = wal.get(coin).unwrap() else {
+            panic!("Entry not found for {}", coin);
+        };
+        assert_eq!(
+            entry,
+            Reservation {
+                sender,
+                client: None,
+                gas_budget: 1000,
+                expiration_ms: 42,
+                in_flight: None,
+            }
+        );
+
+        wal.commit(coin).unwrap();
+        assert_eq!(Ok(None), wal.get(coin));
+    }
+
+    #[tokio::test]
+    async fn reserve_reserve() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut wal = WriteAheadLog::open(&tmp.path().join("wal"));
+
+        let coin = ObjectID::random();
+        let sender = MgoAddress::random_for_testing_only();
+        wal.reserve(coin, sender, None, 1000, 42).unwrap();
+
+        // Second reservation fails because the coin is already reserved
+        assert!(matches!(
+            wal.reserve(coin, sender, None, 1000, 42),
+            Err(TypedStoreError::SerializationError(_)),
+        ));
+
+        // Once the reservation is over, the coin can be reserved again
+        wal.commit(coin).unwrap();
+        wal.reserve(coin, sender, None, 1000, 42).unwrap();
+    }
+
+    #[tokio::test]
+    async fn set_in_flight() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut wal = WriteAheadLog::open(&tmp.path().join("wal"));
+
+        let coin = ObjectID::random();
+        let tx_digest = TransactionDigest::random();
+        assert!(wal.set_in_flight(coin, tx_digest).is_err());
+
+        let client = Some("10.0.0.1".parse().unwrap());
+        wal.reserve(
+            coin,
+            MgoAddress::random_for_testing_only(),
+            client,
+            1000,
+            42,
+        )
+        .unwrap();
+        assert_eq!(wal.get(coin).unwrap().unwrap().in_flight, None);
+        wal.set_in_flight(coin, tx_digest).unwrap();
+        let entry = wal.get(coin).unwrap().unwrap();
+        assert_eq!(entry.in_flight, Some(tx_digest));
+        assert_eq!(entry.client, client);
+    }
+}
@@ -5,10 +5,19 @@ use async_graphql::*;
 
 use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
 use move_core_types::annotated_value::{MoveStruct, MoveValue};
+use move_core_types::language_storage::StructTag;
 use mgo_indexer::{models_v2::display::StoredDisplay, schema_v2::display};
+use mgo_package_resolver::Resolver;
+use mgo_types::base_types::ObjectID;
+use mgo_types::display_template::{
+    render_display_fields, DisplayGrammar, DisplayStore, DISPLAY_GRAMMAR_KEY,
+};
 use mgo_types::TypeTag;
 
+use super::coin_metadata::CoinMetadata;
+use super::object::{deserialize_move_struct, Object, ObjectLookupKey};
 use crate::{
+    context_data::package_cache::PackageCache,
     data::{Db, DbConnection, QueryExecutor},
     error::Error,
 };
@@ -18,6 +27,14 @@ pub(crate) struct Display {
     pub stored: StoredDisplay,
 }
 
+/// Loads the objects and coin metadata that version 2 display templates refer to, as of the
+/// checkpoint that the displayed object was viewed at.
+pub(crate) struct DisplayObjectStore<'a> {
+    pub db: &'a Db,
+    pub resolver: &'a Resolver<PackageCache>,
+    pub checkpoint_viewed_at: Option<u64>,
+}
+
 /// The set of named templates defined on-chain for the type of this object,
 /// to be handled off-chain. The server substitutes data from the object
 /// into these templates to generate a display string per template.
@@ -63,15 +80,45 @@ impl Display {
         Ok(stored.map(|stored| Display { stored }))
     }
 
-    /// Render the fields defined by this `Display` from the contents of `struct_`.
-    pub(crate) fn render(&self, struct_: &MoveStruct) -> Result<Vec<DisplayEntry>, Error> {
+    /// Render the fields defined by this `Display` from the contents of `struct_`. Templates
+    /// using grammar version 2 may load further objects and coin metadata from `store`.
+    pub(crate) async fn render(
+        &self,
+        struct_: &MoveStruct,
+        store: &DisplayObjectStore<'_>,
+    ) -> Result<Vec<DisplayEntry>, Error> {
         let event = self
             .stored
             .to_display_update_event()
             .map_err(|e| Error::Internal(e.to_string()))?;
 
+        match DisplayGrammar::from_fields(&event.fields) {
+            Ok(DisplayGrammar::V1) => {}
+            Ok(DisplayGrammar::V2) => {
+                let rendered = render_display_fields(&event.fields, struct_, store).await;
+                return Ok(rendered
+                    .into_iter()
+                    .map(|(key, value)| match value {
+                        Ok(v) => DisplayEntry::create_value(key, v),
+                        Err(e) => DisplayEntry::create_error(key, e.to_string()),
+                    })
+                    .collect());
+            }
+            Err(e) => {
+                return Ok(event
+                    .fields
+                    .contents
+                    .into_iter()
+                    .map(|entry| DisplayEntry::create_error(entry.key, e.to_string()))
+                    .collect());
+            }
+        }
+
         let mut rendered = vec![];
         for entry in event.fields.contents {
+            if entry.key == DISPLAY_GRAMMAR_KEY {
+                continue;
+            }
             rendered.push(match parse_template(&entry.value, struct_) {
                 Ok(v) => DisplayEntry::create_value(entry.key, v),
                 Err(e) => DisplayEntry::create_error(entry.key, e.to_string()),
@@ -82,6 +129,32 @@ impl Display {
     }
 }
 
+#[async_trait::async_trait]
+impl DisplayStore for DisplayObjectStore<'_> {
+    async fn get_object(&self, id: ObjectID) -> anyhow::Result<Option<MoveStruct>> {
+        let key = match self.checkpoint_viewed_at {
+            Some(checkpoint) => ObjectLookupKey::LatestAt(checkpoint),
+            None => ObjectLookupKey::Latest,
+        };
+        let Some(object) = Object::query(self.db, id.into(), key).await? else {
+            return Ok(None);
+        };
+        let Some(move_object) = object
+            .native_impl()
+            .and_then(|native| native.data.try_as_move())
+        else {
+            return Ok(None);
+        };
+        let (_, move_struct) = deserialize_move_struct(move_object, self.resolver).await?;
+        Ok(Some(move_struct))
+    }
+
+    async fn get_coin_decimals(&self, coin_type: &StructTag) -> anyhow::Result<Option<u8>> {
+        let metadata = CoinMetadata::query(self.db, coin_type.clone().into()).await?;
+        Ok(metadata.map(|metadata| metadata.native.decimals))
+    }
+}
+
 impl DisplayEntry {
     pub(crate) fn create_value(key: String, value: String) -> Self {
         Self {
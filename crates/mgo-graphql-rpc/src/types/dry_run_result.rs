@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::base64::Base64;
+use super::mgo_address::MgoAddress;
 use super::move_type::MoveType;
 use super::transaction_block::{TransactionBlock, TransactionBlockInner};
 use super::transaction_block_kind::programmable::TransactionArgument;
@@ -20,6 +21,10 @@ pub(crate) struct DryRunResult {
     pub results: Option<Vec<DryRunEffect>>,
     /// The transaction block representing the dry run execution.
     pub transaction: Option<TransactionBlock>,
+    /// The overridden objects and injected packages that the dry run read or modified, if it was
+    /// run with state overrides. Changes to these objects are relative to the overridden state
+    /// rather than the live state.
+    pub touched_overridden_objects: Option<Vec<MgoAddress>>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, SimpleObject)]
@@ -122,6 +127,9 @@ impl TryFrom<DevInspectResults> for DryRunResult {
             error: results.error,
             results: Some(execution_results),
             transaction,
+            touched_overridden_objects: results
+                .state_overrides
+                .map(|o| o.touched_objects.into_iter().map(|id| id.into()).collect()),
         })
     }
 }
@@ -11,7 +11,7 @@ use super::coin::Coin;
 use super::coin_metadata::CoinMetadata;
 use super::cursor::{self, Page, Paginated, RawPaginated, Target};
 use super::digest::Digest;
-use super::display::{Display, DisplayEntry};
+use super::display::{Display, DisplayEntry, DisplayObjectStore};
 use super::dynamic_field::{DynamicField, DynamicFieldName};
 use super::move_object::MoveObject;
 use super::move_package::MovePackage;
@@ -661,7 +661,13 @@ impl ObjectImpl<'_> {
             return Ok(None);
         };
 
-        Ok(Some(display.render(&move_struct).extend()?))
+        let store = DisplayObjectStore {
+            db: ctx.data_unchecked(),
+            resolver: ctx.data_unchecked(),
+            checkpoint_viewed_at: self.0.checkpoint_viewed_at,
+        };
+
+        Ok(Some(display.render(&move_struct, &store).await.extend()?))
     }
 }
 
@@ -32,6 +32,7 @@ use super::{
     protocol_config::ProtocolConfigs,
     mgo_address::MgoAddress,
     mgons_registration::Domain,
+    state_overrides::StateOverrides,
     transaction_block::{self, TransactionBlock, TransactionBlockFilter},
     transaction_metadata::TransactionMetadata,
     type_filter::ExactTypeFilter,
@@ -90,12 +91,18 @@ impl Query {
     ///     checks that prevent access to objects that are owned by
     ///     addresses other than the sender, and calling non-public,
     ///     non-entry functions, and some other checks.  Defaults to false.
+    ///
+    /// `stateOverrides` optional hypothetical state to run the transaction
+    ///     against instead of the live state: replacement object contents
+    ///     or coin balances, unpublished packages, a simulated sender, and
+    ///     an overridden clock timestamp and epoch.
     async fn dry_run_transaction_block(
         &self,
         ctx: &Context<'_>,
         tx_bytes: String,
         tx_meta: Option<TransactionMetadata>,
         skip_checks: Option<bool>,
+        state_overrides: Option<StateOverrides>,
     ) -> Result<DryRunResult> {
         let skip_checks = skip_checks.unwrap_or(false);
 
@@ -159,6 +166,8 @@ impl Query {
             gas_objects,
             show_raw_txn_data_and_effects: Some(true),
             skip_checks: Some(skip_checks),
+            state_overrides: state_overrides.map(|o| o.into()),
+            trace: None,
         };
 
         let res = mgo_sdk_client
@@ -0,0 +1,77 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::base64::Base64;
+use super::mgo_address::MgoAddress;
+use async_graphql::*;
+use mgo_json_rpc_types::{MgoObjectOverride, MgoPackageOverride, MgoStateOverrides};
+
+/// Hypothetical state to dry run a transaction against, instead of the live state. The effects
+/// of the dry run on overridden objects are relative to the overridden state.
+#[derive(Clone, Debug, PartialEq, Eq, InputObject)]
+pub(crate) struct StateOverrides {
+    /// Replacement contents or balances for existing objects.
+    pub objects: Option<Vec<ObjectOverride>>,
+    /// Packages to treat as published, at the address their modules were compiled against.
+    pub packages: Option<Vec<PackageOverride>>,
+    /// Execute the transaction on behalf of this sender, without checking that it owns the
+    /// transaction's owned inputs.
+    pub sender: Option<MgoAddress>,
+    /// The timestamp of the `Clock` object, in milliseconds.
+    pub timestamp_ms: Option<u64>,
+    /// The epoch the transaction executes in.
+    pub epoch: Option<u64>,
+}
+
+/// A replacement for the state of an existing object. Exactly one of `contents` and `balance`
+/// must be set.
+#[derive(Clone, Debug, PartialEq, Eq, InputObject)]
+pub(crate) struct ObjectOverride {
+    pub address: MgoAddress,
+    /// BCS serialized contents of the Move object, starting with its ID.
+    pub contents: Option<Base64>,
+    /// The balance of a `Coin<T>` object.
+    pub balance: Option<u64>,
+}
+
+/// A package that has not been published yet.
+#[derive(Clone, Debug, PartialEq, Eq, InputObject)]
+pub(crate) struct PackageOverride {
+    /// BCS serialized modules of the package.
+    pub modules: Vec<Base64>,
+    /// The addresses of all packages the modules depend on, transitively.
+    pub dependencies: Vec<MgoAddress>,
+}
+
+impl From<StateOverrides> for MgoStateOverrides {
+    fn from(overrides: StateOverrides) -> Self {
+        let objects = overrides
+            .objects
+            .unwrap_or_default()
+            .into_iter()
+            .map(|o| MgoObjectOverride {
+                object_id: o.address.into(),
+                contents: o.contents.map(|c| c.0),
+                balance: o.balance.map(|b| b.into()),
+            })
+            .collect();
+
+        let packages = overrides
+            .packages
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| MgoPackageOverride {
+                modules: p.modules.into_iter().map(|m| m.0).collect(),
+                dependencies: p.dependencies.into_iter().map(|d| d.into()).collect(),
+            })
+            .collect();
+
+        MgoStateOverrides {
+            objects,
+            packages,
+            sender: overrides.sender.map(|s| s.into()),
+            timestamp_ms: overrides.timestamp_ms.map(|t| t.into()),
+            epoch: overrides.epoch.map(|e| e.into()),
+        }
+    }
+}
@@ -15,7 +15,7 @@ use mgo_json_rpc_api::{cap_page_limit, IndexerApiClient, IndexerApiServer};
 use mgo_json_rpc_types::{
     DynamicFieldPage, EventFilter, EventPage, ObjectsPage, Page, MgoObjectDataFilter,
     MgoObjectResponse, MgoObjectResponseQuery, MgoTransactionBlockResponseQuery,
-    TransactionBlocksPage, TransactionFilter,
+    SubscriptionCursor, TransactionBlocksPage, TransactionFilter,
 };
 use mgo_open_rpc::Module;
 use mgo_types::base_types::{ObjectID, MgoAddress};
@@ -455,6 +455,24 @@ where
         Ok(())
     }
 
+    fn subscribe_event_from(
+        &self,
+        _sink: SubscriptionSink,
+        _filter: EventFilter,
+        _cursor: SubscriptionCursor,
+    ) -> SubscriptionResult {
+        Ok(())
+    }
+
+    fn subscribe_transaction_from(
+        &self,
+        _sink: SubscriptionSink,
+        _filter: TransactionFilter,
+        _cursor: SubscriptionCursor,
+    ) -> SubscriptionResult {
+        Ok(())
+    }
+
     async fn resolve_name_service_address(&self, name: String) -> RpcResult<Option<MgoAddress>> {
         self.fullnode.resolve_name_service_address(name).await
     }
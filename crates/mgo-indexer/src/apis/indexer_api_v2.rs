@@ -13,8 +13,8 @@ use mgo_json_rpc::MgoRpcModule;
 use mgo_json_rpc_api::{cap_page_limit, IndexerApiServer};
 use mgo_json_rpc_types::{
     DynamicFieldPage, EventFilter, EventPage, ObjectsPage, Page, MgoObjectResponse,
-    MgoObjectResponseQuery, MgoTransactionBlockResponseQuery, TransactionBlocksPage,
-    TransactionFilter,
+    MgoObjectResponseQuery, MgoTransactionBlockResponseQuery, SubscriptionCursor,
+    TransactionBlocksPage, TransactionFilter,
 };
 use mgo_open_rpc::Module;
 use mgo_types::base_types::{ObjectID, MgoAddress};
@@ -293,6 +293,24 @@ impl IndexerApiServer for IndexerApiV2 {
         Err(SubscriptionEmptyError)
     }
 
+    fn subscribe_event_from(
+        &self,
+        _sink: SubscriptionSink,
+        _filter: EventFilter,
+        _cursor: SubscriptionCursor,
+    ) -> SubscriptionResult {
+        Err(SubscriptionEmptyError)
+    }
+
+    fn subscribe_transaction_from(
+        &self,
+        _sink: SubscriptionSink,
+        _filter: TransactionFilter,
+        _cursor: SubscriptionCursor,
+    ) -> SubscriptionResult {
+        Err(SubscriptionEmptyError)
+    }
+
     async fn resolve_name_service_address(&self, name: String) -> RpcResult<Option<MgoAddress>> {
         let domain = name.parse::<Domain>().map_err(|e| {
             IndexerError::InvalidArgumentError(format!(
@@ -10,8 +10,8 @@ use jsonrpsee::RpcModule;
 use mgo_json_rpc::MgoRpcModule;
 use mgo_json_rpc_api::{WriteApiClient, WriteApiServer};
 use mgo_json_rpc_types::{
-    DevInspectArgs, DevInspectResults, DryRunTransactionBlockResponse, MgoTransactionBlockResponse,
-    MgoTransactionBlockResponseOptions,
+    DevInspectArgs, DevInspectResults, DryRunTransactionBlockResponse, MgoStateOverrides,
+    MgoTransactionBlockResponse, MgoTransactionBlockResponseOptions,
 };
 use mgo_open_rpc::Module;
 use mgo_types::base_types::MgoAddress;
@@ -76,8 +76,11 @@ impl WriteApiServer for WriteApi {
     async fn dry_run_transaction_block(
         &self,
         tx_bytes: Base64,
+        state_overrides: Option<MgoStateOverrides>,
     ) -> RpcResult<DryRunTransactionBlockResponse> {
-        self.fullnode.dry_run_transaction_block(tx_bytes).await
+        self.fullnode
+            .dry_run_transaction_block(tx_bytes, state_overrides)
+            .await
     }
 }
 
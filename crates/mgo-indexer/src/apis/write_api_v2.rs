@@ -14,8 +14,8 @@ use jsonrpsee::RpcModule;
 use mgo_json_rpc::MgoRpcModule;
 use mgo_json_rpc_api::WriteApiServer;
 use mgo_json_rpc_types::{
-    DevInspectArgs, DevInspectResults, DryRunTransactionBlockResponse, MgoTransactionBlockResponse,
-    MgoTransactionBlockResponseOptions,
+    DevInspectArgs, DevInspectResults, DryRunTransactionBlockResponse, MgoStateOverrides,
+    MgoTransactionBlockResponse, MgoTransactionBlockResponseOptions,
 };
 use mgo_open_rpc::Module;
 use mgo_types::base_types::MgoAddress;
@@ -58,6 +58,7 @@ impl WriteApiServer for WriteApiV2 {
     async fn dry_run_transaction_block(
         &self,
         tx_bytes: Base64,
+        state_overrides: Option<MgoStateOverrides>,
     ) -> RpcResult<DryRunTransactionBlockResponse> {
         unimplemented!()
     }
@@ -1640,8 +1640,13 @@ impl IndexerReader {
         };
 
         if let Some(display_object) = self.get_display_object_by_type(&object_type).await? {
-            return mgo_json_rpc::read_api::get_rendered_fields(display_object.fields, &layout)
-                .map_err(|e| IndexerError::GenericError(e.to_string()));
+            return mgo_json_rpc::read_api::get_rendered_fields(
+                display_object.fields,
+                &layout,
+                self,
+            )
+            .await
+            .map_err(|e| IndexerError::GenericError(e.to_string()));
         }
         Ok(DisplayFieldsResponse {
             data: None,
@@ -1776,6 +1781,28 @@ impl mgo_types::storage::ObjectStore for IndexerReader {
     }
 }
 
+#[async_trait::async_trait]
+impl mgo_types::display_template::DisplayStore for IndexerReader {
+    async fn get_object(
+        &self,
+        id: ObjectID,
+    ) -> Result<Option<move_core_types::annotated_value::MoveStruct>> {
+        let ObjectRead::Exists(_, object, layout) = self.get_object_read_in_blocking_task(id).await?
+        else {
+            return Ok(None);
+        };
+        let move_struct = mgo_json_rpc::read_api::get_object_type_and_struct(&object, &layout)?;
+        Ok(move_struct.map(|(_, move_struct)| move_struct))
+    }
+
+    async fn get_coin_decimals(&self, coin_type: &StructTag) -> Result<Option<u8>> {
+        Ok(self
+            .get_coin_metadata_in_blocking_task(coin_type.clone())
+            .await?
+            .map(|metadata| metadata.decimals))
+    }
+}
+
 impl move_bytecode_utils::module_cache::GetModule for IndexerReader {
     type Error = IndexerError;
     type Item = move_binary_format::CompiledModule;
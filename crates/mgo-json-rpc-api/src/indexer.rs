@@ -6,9 +6,9 @@ use jsonrpsee::proc_macros::rpc;
 
 use mgo_json_rpc_types::MgoTransactionBlockEffects;
 use mgo_json_rpc_types::{
-    DynamicFieldPage, EventFilter, EventPage, ObjectsPage, Page, MgoEvent, MgoObjectResponse,
-    MgoObjectResponseQuery, MgoTransactionBlockResponseQuery, TransactionBlocksPage,
-    TransactionFilter,
+    DynamicFieldPage, EventFilter, EventPage, EventSubscriptionItem, MgoEvent, MgoObjectResponse,
+    MgoObjectResponseQuery, MgoTransactionBlockResponseQuery, ObjectsPage, Page,
+    SubscriptionCursor, TransactionBlocksPage, TransactionFilter, TransactionSubscriptionItem,
 };
 use mgo_open_rpc_macros::open_rpc;
 use mgo_types::base_types::{ObjectID, MgoAddress};
@@ -77,6 +77,29 @@ pub trait IndexerApi {
     #[subscription(name = "subscribeTransaction", item = MgoTransactionBlockEffects)]
     fn subscribe_transaction(&self, filter: TransactionFilter);
 
+    /// Subscribe to a stream of Mgo events, resuming from a cursor. The events emitted since the
+    /// cursor are backfilled from the fullnode's indexes before live events are streamed. Events
+    /// the fullnode has pruned are reported as a gap.
+    #[subscription(name = "subscribeEventFrom", item = EventSubscriptionItem)]
+    fn subscribe_event_from(
+        &self,
+        /// The filter criteria of the event stream. See [Event filter](https://docs.mangonetwork.io/build/event_api#event-filters) documentation for examples.
+        filter: EventFilter,
+        /// Where to resume the stream: a checkpoint, or the last event or transaction received.
+        cursor: SubscriptionCursor,
+    );
+
+    /// Subscribe to a stream of Mgo transaction effects, resuming from a cursor. The transactions
+    /// executed since the cursor are backfilled from the fullnode's indexes before live
+    /// transactions are streamed. Transactions the fullnode has pruned are reported as a gap.
+    #[subscription(name = "subscribeTransactionFrom", item = TransactionSubscriptionItem)]
+    fn subscribe_transaction_from(
+        &self,
+        filter: TransactionFilter,
+        /// Where to resume the stream: a checkpoint, or the last transaction received.
+        cursor: SubscriptionCursor,
+    );
+
     /// Return the list of dynamic field objects owned by an object.
     #[method(name = "getDynamicFields")]
     async fn get_dynamic_fields(
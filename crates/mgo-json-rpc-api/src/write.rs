@@ -6,8 +6,8 @@ use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
 
 use mgo_json_rpc_types::{
-    DevInspectArgs, DevInspectResults, DryRunTransactionBlockResponse, MgoTransactionBlockResponse,
-    MgoTransactionBlockResponseOptions,
+    DevInspectArgs, DevInspectResults, DryRunTransactionBlockResponse, MgoStateOverrides,
+    MgoTransactionBlockResponse, MgoTransactionBlockResponseOptions,
 };
 use mgo_open_rpc_macros::open_rpc;
 use mgo_types::base_types::MgoAddress;
@@ -63,5 +63,9 @@ pub trait WriteApi {
     async fn dry_run_transaction_block(
         &self,
         tx_bytes: Base64,
+        /// Hypothetical state to dry run the transaction against instead of the live state:
+        /// replacement object contents or coin balances, unpublished packages, a simulated sender,
+        /// and an overridden clock timestamp and epoch.
+        state_overrides: Option<MgoStateOverrides>,
     ) -> RpcResult<DryRunTransactionBlockResponse>;
 }
@@ -14,6 +14,7 @@ pub use mgo_governance::*;
 pub use mgo_move::*;
 pub use mgo_object::*;
 pub use mgo_protocol::*;
+pub use mgo_subscription::*;
 pub use mgo_transaction::*;
 use mgo_types::base_types::ObjectID;
 use mgo_types::dynamic_field::DynamicFieldInfo;
@@ -33,6 +34,7 @@ mod mgo_governance;
 mod mgo_move;
 mod mgo_object;
 mod mgo_protocol;
+mod mgo_subscription;
 mod mgo_transaction;
 
 pub type DynamicFieldPage = Page<DynamicFieldInfo, ObjectID>;
@@ -0,0 +1,57 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use mgo_types::base_types::TransactionDigest;
+use mgo_types::event::EventID;
+use mgo_types::messages_checkpoint::CheckpointSequenceNumber;
+use mgo_types::mgo_serde::BigInt;
+
+use crate::{MgoEvent, MgoTransactionBlockEffects};
+
+pub type EventSubscriptionItem = SubscriptionItem<MgoEvent>;
+pub type TransactionSubscriptionItem = SubscriptionItem<MgoTransactionBlockEffects>;
+
+/// Where a resumable subscription starts. Items committed since the cursor are backfilled from
+/// the fullnode's indexes before live items are streamed.
+#[serde_as]
+#[derive(Clone, Debug, JsonSchema, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SubscriptionCursor {
+    /// Start with the first transaction of the checkpoint.
+    Checkpoint(
+        #[schemars(with = "BigInt<u64>")]
+        #[serde_as(as = "BigInt<u64>")]
+        CheckpointSequenceNumber,
+    ),
+    /// Start after the event. Only event subscriptions can be resumed from an event.
+    Event(EventID),
+    /// Start after the transaction.
+    Transaction(TransactionDigest),
+}
+
+/// An item of a resumable subscription.
+#[derive(Clone, Debug, JsonSchema, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SubscriptionItem<T> {
+    Data(T),
+    /// Items that could not be backfilled because the fullnode pruned them.
+    Gap(SubscriptionGap),
+}
+
+#[serde_as]
+#[derive(Clone, Debug, JsonSchema, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SubscriptionGap {
+    /// The checkpoints from `start` to `end` (inclusive) were pruned.
+    Checkpoints {
+        #[schemars(with = "BigInt<u64>")]
+        #[serde_as(as = "BigInt<u64>")]
+        start: CheckpointSequenceNumber,
+        #[schemars(with = "BigInt<u64>")]
+        #[serde_as(as = "BigInt<u64>")]
+        end: CheckpointSequenceNumber,
+    },
+    /// The data of these transactions was pruned.
+    Transactions(Vec<TransactionDigest>),
+}
@@ -18,6 +18,7 @@ use mango_metrics::monitored_scope;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
+use std::collections::BTreeMap;
 use std::fmt::{self, Display, Formatter, Write};
 use mgo_json::{primitive_type, MgoJsonValue};
 use mgo_types::authenticator_state::ActiveJwk;
@@ -25,16 +26,20 @@ use mgo_types::base_types::{
     EpochId, ObjectID, ObjectRef, SequenceNumber, MgoAddress, TransactionDigest,
 };
 use mgo_types::crypto::MgoSignature;
-use mgo_types::digests::{ConsensusCommitDigest, ObjectDigest, TransactionEventsDigest};
+use mgo_types::digests::{
+    ChainIdentifier, ConsensusCommitDigest, ObjectDigest, TransactionEventsDigest,
+};
 use mgo_types::effects::{TransactionEffects, TransactionEffectsAPI, TransactionEvents};
-use mgo_types::error::{ExecutionError, MgoError, MgoResult};
+use mgo_types::error::{ExecutionError, MgoError, MgoResult, UserInputError};
 use mgo_types::execution_status::ExecutionStatus;
+use mgo_types::execution_trace::ExecutionTrace;
 use mgo_types::gas::GasCostSummary;
 use mgo_types::messages_checkpoint::CheckpointSequenceNumber;
 use mgo_types::object::{MoveObject, Owner};
 use mgo_types::parse_mgo_type_tag;
 use mgo_types::quorum_driver_types::ExecuteTransactionRequestType;
 use mgo_types::signature::GenericSignature;
+use mgo_types::state_override::{ObjectOverride, PackageOverride, StateOverrides};
 use mgo_types::storage::{DeleteKind, WriteKind};
 use mgo_types::mgo_serde::Readable;
 use mgo_types::mgo_serde::{
@@ -43,7 +48,8 @@ use mgo_types::mgo_serde::{
 use mgo_types::transaction::{
     Argument, CallArg, ChangeEpoch, Command, EndOfEpochTransactionKind, GenesisObject,
     InputObjectKind, ObjectArg, ProgrammableMoveCall, ProgrammableTransaction, SenderSignedData,
-    TransactionData, TransactionDataAPI, TransactionKind, VersionedProtocolMessage,
+    TransactionData, TransactionDataAPI, TransactionExpiration, TransactionKind,
+    VersionedProtocolMessage,
 };
 use mgo_types::type_resolver::LayoutResolver;
 use mgo_types::MGO_FRAMEWORK_ADDRESS;
@@ -963,6 +969,9 @@ pub struct DryRunTransactionBlockResponse {
     pub object_changes: Vec<ObjectChange>,
     pub balance_changes: Vec<BalanceChange>,
     pub input: MgoTransactionBlockData,
+    /// The state overrides the transaction was dry run against, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_overrides: Option<MgoAppliedStateOverrides>,
 }
 
 #[derive(Eq, PartialEq, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -1052,6 +1061,142 @@ pub struct DevInspectArgs {
     pub skip_checks: Option<bool>,
     /// Whether to return the raw transaction data and effects.
     pub show_raw_txn_data_and_effects: Option<bool>,
+    /// Hypothetical state to execute the transaction against instead of the live state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_overrides: Option<MgoStateOverrides>,
+    /// Whether to return a trace of the Move execution of the transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<bool>,
+}
+
+/// Hypothetical state that a transaction is dry run against instead of the live state.
+#[derive(Eq, PartialEq, Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename = "StateOverrides", rename_all = "camelCase")]
+pub struct MgoStateOverrides {
+    /// Replacement contents or balances for existing objects.
+    #[serde(default)]
+    pub objects: Vec<MgoObjectOverride>,
+    /// Packages to treat as published, at the address their modules were compiled against.
+    #[serde(default)]
+    pub packages: Vec<MgoPackageOverride>,
+    /// Execute the transaction on behalf of this sender, without checking that it owns the
+    /// transaction's owned inputs.
+    pub sender: Option<MgoAddress>,
+    /// The timestamp of the `Clock` object.
+    pub timestamp_ms: Option<BigInt<u64>>,
+    /// The epoch the transaction executes in.
+    pub epoch: Option<BigInt<u64>>,
+}
+
+/// A replacement for the state of an existing object: exactly one of `contents` and `balance`
+/// must be set.
+#[serde_as]
+#[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename = "ObjectOverride", rename_all = "camelCase")]
+pub struct MgoObjectOverride {
+    pub object_id: ObjectID,
+    /// BCS serialized contents of the Move object, starting with its ID.
+    #[serde_as(as = "Option<Base64>")]
+    #[schemars(with = "Option<Base64>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contents: Option<Vec<u8>>,
+    /// The balance of a `Coin<T>` object.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<BigInt<u64>>,
+}
+
+/// A package that has not been published yet.
+#[serde_as]
+#[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename = "PackageOverride", rename_all = "camelCase")]
+pub struct MgoPackageOverride {
+    /// The serialized modules of the package.
+    #[serde_as(as = "Vec<Base64>")]
+    #[schemars(with = "Vec<Base64>")]
+    pub modules: Vec<Vec<u8>>,
+    /// The IDs of all packages the modules depend on, transitively.
+    pub dependencies: Vec<ObjectID>,
+}
+
+impl TryFrom<MgoStateOverrides> for StateOverrides {
+    type Error = UserInputError;
+
+    fn try_from(overrides: MgoStateOverrides) -> Result<Self, Self::Error> {
+        let mut objects = BTreeMap::new();
+        for MgoObjectOverride {
+            object_id,
+            contents,
+            balance,
+        } in overrides.objects
+        {
+            let object_override = match (contents, balance) {
+                (Some(contents), None) => ObjectOverride::Contents(contents),
+                (None, Some(balance)) => ObjectOverride::Balance(*balance),
+                _ => {
+                    return Err(UserInputError::InvalidStateOverride {
+                        object_id,
+                        error: "exactly one of contents and balance must be set".to_string(),
+                    })
+                }
+            };
+            if objects.insert(object_id, object_override).is_some() {
+                return Err(UserInputError::InvalidStateOverride {
+                    object_id,
+                    error: "object is overridden more than once".to_string(),
+                });
+            }
+        }
+
+        Ok(StateOverrides {
+            objects,
+            packages: overrides
+                .packages
+                .into_iter()
+                .map(|p| PackageOverride {
+                    modules: p.modules,
+                    dependencies: p.dependencies,
+                })
+                .collect(),
+            sender: overrides.sender,
+            timestamp_ms: overrides.timestamp_ms.map(|t| *t),
+            epoch: overrides.epoch.map(|e| *e),
+        })
+    }
+}
+
+/// The state overrides a transaction was dry run against.
+#[serde_as]
+#[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename = "AppliedStateOverrides", rename_all = "camelCase")]
+pub struct MgoAppliedStateOverrides {
+    /// Overridden objects and injected packages that the transaction read or modified. Effects,
+    /// object changes and balance changes for these objects are relative to the overridden state
+    /// rather than the live state.
+    pub touched_objects: Vec<ObjectID>,
+    /// The simulated sender, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender: Option<MgoAddress>,
+    /// The overridden timestamp of the `Clock` object, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<BigInt<u64>>")]
+    #[serde_as(as = "Option<BigInt<u64>>")]
+    pub timestamp_ms: Option<u64>,
+    /// The overridden epoch, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<BigInt<u64>>")]
+    #[serde_as(as = "Option<BigInt<u64>>")]
+    pub epoch: Option<EpochId>,
+}
+
+impl MgoAppliedStateOverrides {
+    pub fn new(overrides: &StateOverrides, touched_objects: Vec<ObjectID>) -> Self {
+        Self {
+            touched_objects,
+            sender: overrides.sender,
+            timestamp_ms: overrides.timestamp_ms,
+            epoch: overrides.epoch,
+        }
+    }
 }
 
 /// The response from processing a dev inspect transaction
@@ -1076,6 +1221,13 @@ pub struct DevInspectResults {
     /// The raw effects of the transaction that was dev inspected.
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub raw_effects: Vec<u8>,
+    /// The state overrides the transaction was executed against, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_overrides: Option<MgoAppliedStateOverrides>,
+    /// The calls, arguments, return values, events and object accesses of the Move execution of
+    /// the transaction, if a trace was requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<ExecutionTrace>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -1138,6 +1290,8 @@ impl DevInspectResults {
             error,
             raw_txn_data,
             raw_effects,
+            state_overrides: None,
+            trace: None,
         })
     }
 }
@@ -1217,6 +1371,89 @@ pub struct MgoGasData {
     pub budget: u64,
 }
 
+#[serde_as]
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename = "TransactionExpiration", tag = "kind")]
+pub enum MgoTransactionExpiration {
+    /// The transaction has no expiration
+    #[default]
+    None,
+    /// Validators wont sign the transaction after this epoch
+    Epoch {
+        #[schemars(with = "BigInt<u64>")]
+        #[serde_as(as = "BigInt<u64>")]
+        epoch: EpochId,
+    },
+    /// Validators only sign the transaction while the consensus time is within the window, and
+    /// if `chain` is set, only on that chain
+    #[serde(rename_all = "camelCase")]
+    ValidDuring {
+        #[schemars(with = "Option<BigInt<u64>>")]
+        #[serde_as(as = "Option<BigInt<u64>>")]
+        min_timestamp_ms: Option<u64>,
+        #[schemars(with = "Option<BigInt<u64>>")]
+        #[serde_as(as = "Option<BigInt<u64>>")]
+        max_timestamp_ms: Option<u64>,
+        chain: Option<ChainIdentifier>,
+        nonce: u32,
+    },
+}
+
+impl MgoTransactionExpiration {
+    pub fn is_none(&self) -> bool {
+        matches!(self, MgoTransactionExpiration::None)
+    }
+}
+
+impl From<TransactionExpiration> for MgoTransactionExpiration {
+    fn from(expiration: TransactionExpiration) -> Self {
+        match expiration {
+            TransactionExpiration::None => MgoTransactionExpiration::None,
+            TransactionExpiration::Epoch(epoch) => MgoTransactionExpiration::Epoch { epoch },
+            TransactionExpiration::ValidDuring {
+                min_timestamp_ms,
+                max_timestamp_ms,
+                chain,
+                nonce,
+            } => MgoTransactionExpiration::ValidDuring {
+                min_timestamp_ms,
+                max_timestamp_ms,
+                chain,
+                nonce,
+            },
+        }
+    }
+}
+
+impl Display for MgoTransactionExpiration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MgoTransactionExpiration::None => write!(f, "None"),
+            MgoTransactionExpiration::Epoch { epoch } => write!(f, "Epoch {epoch}"),
+            MgoTransactionExpiration::ValidDuring {
+                min_timestamp_ms,
+                max_timestamp_ms,
+                chain,
+                nonce,
+            } => {
+                let bound = |timestamp_ms: &Option<u64>| {
+                    timestamp_ms.map_or_else(|| "-".to_string(), |ms| ms.to_string())
+                };
+                write!(
+                    f,
+                    "Valid from {} ms to {} ms",
+                    bound(min_timestamp_ms),
+                    bound(max_timestamp_ms)
+                )?;
+                if let Some(chain) = chain {
+                    write!(f, " on chain {chain}")?;
+                }
+                write!(f, " (nonce {nonce})")
+            }
+        }
+    }
+}
+
 impl Display for MgoGasData {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         writeln!(f, "Gas Owner: {}", self.owner)?;
@@ -1254,6 +1491,8 @@ pub struct MgoTransactionBlockDataV1 {
     pub transaction: MgoTransactionBlockKind,
     pub sender: MgoAddress,
     pub gas_data: MgoGasData,
+    #[serde(default, skip_serializing_if = "MgoTransactionExpiration::is_none")]
+    pub expiration: MgoTransactionExpiration,
 }
 
 impl MgoTransactionBlockDataAPI for MgoTransactionBlockDataV1 {
@@ -1292,6 +1531,9 @@ impl Display for MgoTransactionBlockData {
             Self::V1(data) => {
                 writeln!(f, "Sender: {}", data.sender)?;
                 writeln!(f, "{}", self.gas_data())?;
+                if !data.expiration.is_none() {
+                    writeln!(f, "Expiration: {}", data.expiration)?;
+                }
                 writeln!(f, "{}", data.transaction)
             }
         }
@@ -1317,12 +1559,14 @@ impl MgoTransactionBlockData {
             price: data.gas_price(),
             budget: data.gas_budget(),
         };
+        let expiration = (*data.expiration()).into();
         let transaction = MgoTransactionBlockKind::try_from(data.into_kind(), module_cache)?;
         match message_version {
             1 => Ok(MgoTransactionBlockData::V1(MgoTransactionBlockDataV1 {
                 transaction,
                 sender,
                 gas_data,
+                expiration,
             })),
             _ => Err(anyhow::anyhow!(
                 "Support for TransactionData version {} not implemented",
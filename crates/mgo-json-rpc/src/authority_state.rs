@@ -11,10 +11,11 @@ use std::sync::Arc;
 use mgo_core::authority::authority_per_epoch_store::AuthorityPerEpochStore;
 use mgo_core::authority::AuthorityState;
 use mgo_core::in_mem_execution_cache::ExecutionCacheRead;
-use mgo_core::subscription_handler::SubscriptionHandler;
+use mgo_core::subscription_handler::{BackfillPage, BackfillPosition, SubscriptionHandler};
 use mgo_json_rpc_types::{
     Coin as MgoCoin, DevInspectResults, DryRunTransactionBlockResponse, EventFilter, MgoEvent,
-    MgoObjectDataFilter, TransactionFilter,
+    MgoObjectDataFilter, MgoTransactionBlockEffects, SubscriptionCursor, SubscriptionGap,
+    TransactionFilter,
 };
 use mgo_storage::indexes::TotalBalance;
 use mgo_storage::key_value_store::{
@@ -36,6 +37,7 @@ use mgo_types::messages_checkpoint::{
     VerifiedCheckpoint,
 };
 use mgo_types::object::{Object, ObjectRead, PastObjectRead};
+use mgo_types::state_override::StateOverrides;
 use mgo_types::storage::{BackingPackageStore, ObjectStore, WriteKind};
 use mgo_types::mgo_serde::BigInt;
 use mgo_types::mgo_system_state::MgoSystemState;
@@ -124,6 +126,19 @@ pub trait StateRead: Send + Sync {
         Option<ObjectID>,
     )>;
 
+    async fn dry_exec_transaction_with_overrides(
+        &self,
+        transaction: TransactionData,
+        transaction_digest: TransactionDigest,
+        state_overrides: StateOverrides,
+    ) -> StateReadResult<(
+        DryRunTransactionBlockResponse,
+        BTreeMap<ObjectID, (ObjectRef, Object, WriteKind)>,
+        TransactionEffects,
+        Option<ObjectID>,
+        BTreeMap<ObjectID, Object>,
+    )>;
+
     async fn dev_inspect_transaction_block(
         &self,
         sender: MgoAddress,
@@ -134,11 +149,35 @@ pub trait StateRead: Send + Sync {
         gas_objects: Option<Vec<ObjectRef>>,
         show_raw_txn_data_and_effects: Option<bool>,
         skip_checks: Option<bool>,
+        state_overrides: Option<StateOverrides>,
+        trace: Option<bool>,
     ) -> StateReadResult<DevInspectResults>;
 
     // indexer_api
     fn get_subscription_handler(&self) -> Arc<SubscriptionHandler>;
 
+    async fn resolve_subscription_cursor(
+        &self,
+        kv_store: &Arc<TransactionKeyValueStore>,
+        cursor: SubscriptionCursor,
+    ) -> StateReadResult<(BackfillPosition, Option<SubscriptionGap>)>;
+
+    async fn backfill_events(
+        &self,
+        kv_store: &Arc<TransactionKeyValueStore>,
+        filter: &EventFilter,
+        start: BackfillPosition,
+        limit: usize,
+    ) -> StateReadResult<BackfillPage<MgoEvent>>;
+
+    async fn backfill_transactions(
+        &self,
+        kv_store: &Arc<TransactionKeyValueStore>,
+        filter: &TransactionFilter,
+        start: BackfillPosition,
+        limit: usize,
+    ) -> StateReadResult<BackfillPage<MgoTransactionBlockEffects>>;
+
     fn get_owner_objects_with_limit(
         &self,
         owner: MgoAddress,
@@ -360,6 +399,23 @@ impl StateRead for AuthorityState {
             .await?)
     }
 
+    async fn dry_exec_transaction_with_overrides(
+        &self,
+        transaction: TransactionData,
+        transaction_digest: TransactionDigest,
+        state_overrides: StateOverrides,
+    ) -> StateReadResult<(
+        DryRunTransactionBlockResponse,
+        BTreeMap<ObjectID, (ObjectRef, Object, WriteKind)>,
+        TransactionEffects,
+        Option<ObjectID>,
+        BTreeMap<ObjectID, Object>,
+    )> {
+        Ok(self
+            .dry_exec_transaction_with_overrides(transaction, transaction_digest, state_overrides)
+            .await?)
+    }
+
     async fn dev_inspect_transaction_block(
         &self,
         sender: MgoAddress,
@@ -370,6 +426,8 @@ impl StateRead for AuthorityState {
         gas_objects: Option<Vec<ObjectRef>>,
         show_raw_txn_data_and_effects: Option<bool>,
         skip_checks: Option<bool>,
+        state_overrides: Option<StateOverrides>,
+        trace: Option<bool>,
     ) -> StateReadResult<DevInspectResults> {
         Ok(self
             .dev_inspect_transaction_block(
@@ -381,6 +439,8 @@ impl StateRead for AuthorityState {
                 gas_objects,
                 show_raw_txn_data_and_effects,
                 skip_checks,
+                state_overrides,
+                trace,
             )
             .await?)
     }
@@ -389,6 +449,36 @@ impl StateRead for AuthorityState {
         self.subscription_handler.clone()
     }
 
+    async fn resolve_subscription_cursor(
+        &self,
+        kv_store: &Arc<TransactionKeyValueStore>,
+        cursor: SubscriptionCursor,
+    ) -> StateReadResult<(BackfillPosition, Option<SubscriptionGap>)> {
+        Ok(self.resolve_subscription_cursor(kv_store, cursor).await?)
+    }
+
+    async fn backfill_events(
+        &self,
+        kv_store: &Arc<TransactionKeyValueStore>,
+        filter: &EventFilter,
+        start: BackfillPosition,
+        limit: usize,
+    ) -> StateReadResult<BackfillPage<MgoEvent>> {
+        Ok(self.backfill_events(kv_store, filter, start, limit).await?)
+    }
+
+    async fn backfill_transactions(
+        &self,
+        kv_store: &Arc<TransactionKeyValueStore>,
+        filter: &TransactionFilter,
+        start: BackfillPosition,
+        limit: usize,
+    ) -> StateReadResult<BackfillPage<MgoTransactionBlockEffects>> {
+        Ok(self
+            .backfill_transactions(kv_store, filter, start, limit)
+            .await?)
+    }
+
     fn get_owner_objects_with_limit(
         &self,
         owner: MgoAddress,
@@ -211,6 +211,19 @@ impl<P> ObjectProviderCache<P> {
             provider,
         }
     }
+
+    /// Serves `overridden_objects` in place of the provider's objects at the same versions, so
+    /// that changes are computed relative to the state a transaction was dry run against.
+    pub fn with_overridden_objects(
+        mut self,
+        overridden_objects: BTreeMap<ObjectID, Object>,
+    ) -> Self {
+        let object_cache = self.object_cache.get_mut();
+        for (object_id, object) in overridden_objects {
+            object_cache.insert((object_id, object.version()), object);
+        }
+        self
+    }
 }
 
 #[async_trait]
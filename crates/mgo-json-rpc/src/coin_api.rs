@@ -246,7 +246,7 @@ impl CoinReadApiServer for CoinReadApi {
     convert = r#"{ format!("{}{}", package_id, object_struct_tag) }"#,
     result = true
 )]
-async fn find_package_object_id(
+pub(crate) async fn find_package_object_id(
     state: Arc<dyn StateRead>,
     package_id: ObjectID,
     object_struct_tag: StructTag,
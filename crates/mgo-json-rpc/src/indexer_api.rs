@@ -3,7 +3,7 @@
 
 use anyhow::bail;
 use async_trait::async_trait;
-use futures::Stream;
+use futures::{Stream, StreamExt, TryStream};
 use jsonrpsee::{
     core::{error::SubscriptionClosed, RpcResult},
     types::SubscriptionResult,
@@ -13,18 +13,22 @@ use move_bytecode_utils::layout::TypeLayoutBuilder;
 use move_core_types::language_storage::TypeTag;
 use mango_metrics::spawn_monitored_task;
 use serde::Serialize;
+use std::convert::Infallible;
+use std::fmt::Display;
 use std::str::FromStr;
 use std::sync::Arc;
 use mgo_core::authority::AuthorityState;
+use mgo_core::subscription_handler::backfill_then_subscribe;
 use mgo_json::MgoJsonValue;
 use mgo_json_rpc_api::{
     cap_page_limit, validate_limit, IndexerApiOpenRpc, IndexerApiServer, JsonRpcMetrics,
     ReadApiServer, QUERY_MAX_RESULT_LIMIT,
 };
 use mgo_json_rpc_types::{
-    DynamicFieldPage, EventFilter, EventPage, ObjectsPage, Page, MgoObjectDataOptions,
-    MgoObjectResponse, MgoObjectResponseQuery, MgoTransactionBlockResponse,
-    MgoTransactionBlockResponseQuery, TransactionBlocksPage, TransactionFilter,
+    DynamicFieldPage, EventFilter, EventPage, ObjectsPage, Page, MgoEvent, MgoObjectDataOptions,
+    MgoObjectResponse, MgoObjectResponseQuery, MgoTransactionBlockEffects,
+    MgoTransactionBlockEffectsAPI, MgoTransactionBlockResponse, MgoTransactionBlockResponseQuery,
+    SubscriptionCursor, TransactionBlocksPage, TransactionFilter,
 };
 use mgo_open_rpc::Module;
 use mgo_storage::key_value_store::TransactionKeyValueStore;
@@ -32,7 +36,7 @@ use mgo_types::{
     base_types::{ObjectID, MgoAddress},
     digests::TransactionDigest,
     dynamic_field::{DynamicFieldName, Field},
-    error::MgoObjectResponseError,
+    error::{MgoObjectResponseError, UserInputError},
     event::EventID,
 };
 use tokio::sync::{OwnedSemaphorePermit, Semaphore};
@@ -52,10 +56,23 @@ pub fn spawn_subscription<S, T>(
 ) where
     S: Stream<Item = T> + Unpin + Send + 'static,
     T: Serialize,
+{
+    spawn_try_subscription(sink, rx.map(Ok::<T, Infallible>), permit)
+}
+
+/// Like `spawn_subscription`, but closes the subscription with the first error of the stream.
+pub fn spawn_try_subscription<S, T, E>(
+    mut sink: SubscriptionSink,
+    rx: S,
+    permit: Option<OwnedSemaphorePermit>,
+) where
+    S: TryStream<Ok = T, Error = E> + Unpin + Send + 'static,
+    T: Serialize,
+    E: Display,
 {
     spawn_monitored_task!(async move {
         let _permit = permit;
-        match sink.pipe_from_stream(rx).await {
+        match sink.pipe_from_try_stream(rx).await {
             SubscriptionClosed::Success => {
                 debug!("Subscription completed.");
                 sink.close(SubscriptionClosed::Success);
@@ -72,6 +89,7 @@ pub fn spawn_subscription<S, T>(
     });
 }
 const DEFAULT_MAX_SUBSCRIPTIONS: usize = 100;
+const SUBSCRIPTION_BACKFILL_PAGE_SIZE: usize = 100;
 
 pub struct IndexerApi<R> {
     state: Arc<dyn StateRead>,
@@ -309,6 +327,99 @@ impl<R: ReadApiServer> IndexerApiServer for IndexerApi<R> {
         Ok(())
     }
 
+    #[instrument(skip(self))]
+    fn subscribe_event_from(
+        &self,
+        sink: SubscriptionSink,
+        filter: EventFilter,
+        cursor: SubscriptionCursor,
+    ) -> SubscriptionResult {
+        let permit = self.acquire_subscribe_permit()?;
+        let (state, kv_store) = (self.state.clone(), self.transaction_kv_store.clone());
+        let start = {
+            let (state, kv_store) = (state.clone(), kv_store.clone());
+            async move { state.resolve_subscription_cursor(&kv_store, cursor).await }
+        };
+        let fetch_page = {
+            let filter = filter.clone();
+            move |position| {
+                let (state, kv_store, filter) = (state.clone(), kv_store.clone(), filter.clone());
+                async move {
+                    state
+                        .backfill_events(
+                            &kv_store,
+                            &filter,
+                            position,
+                            SUBSCRIPTION_BACKFILL_PAGE_SIZE,
+                        )
+                        .await
+                }
+            }
+        };
+        let subscription_handler = self.state.get_subscription_handler();
+        spawn_try_subscription(
+            sink,
+            backfill_then_subscribe(
+                start,
+                fetch_page,
+                move || subscription_handler.subscribe_events(filter),
+                |event: &MgoEvent| event.id,
+            ),
+            Some(permit),
+        );
+        Ok(())
+    }
+
+    fn subscribe_transaction_from(
+        &self,
+        sink: SubscriptionSink,
+        filter: TransactionFilter,
+        cursor: SubscriptionCursor,
+    ) -> SubscriptionResult {
+        let permit = self.acquire_subscribe_permit()?;
+        let (state, kv_store) = (self.state.clone(), self.transaction_kv_store.clone());
+        let start = {
+            let (state, kv_store) = (state.clone(), kv_store.clone());
+            async move {
+                if let SubscriptionCursor::Event(_) = cursor {
+                    return Err(UserInputError::Unsupported(
+                        "Transaction subscriptions cannot be resumed from an event".to_string(),
+                    )
+                    .into());
+                }
+                state.resolve_subscription_cursor(&kv_store, cursor).await
+            }
+        };
+        let fetch_page = {
+            let filter = filter.clone();
+            move |position| {
+                let (state, kv_store, filter) = (state.clone(), kv_store.clone(), filter.clone());
+                async move {
+                    state
+                        .backfill_transactions(
+                            &kv_store,
+                            &filter,
+                            position,
+                            SUBSCRIPTION_BACKFILL_PAGE_SIZE,
+                        )
+                        .await
+                }
+            }
+        };
+        let subscription_handler = self.state.get_subscription_handler();
+        spawn_try_subscription(
+            sink,
+            backfill_then_subscribe(
+                start,
+                fetch_page,
+                move || subscription_handler.subscribe_transactions(filter),
+                |effects: &MgoTransactionBlockEffects| *effects.transaction_digest(),
+            ),
+            Some(permit),
+        );
+        Ok(())
+    }
+
     #[instrument(skip(self))]
     async fn get_dynamic_fields(
         &self,
@@ -35,10 +35,14 @@ use mgo_open_rpc::Module;
 use mgo_protocol_config::{ProtocolConfig, ProtocolVersion};
 use mgo_storage::key_value_store::TransactionKeyValueStore;
 use mgo_types::base_types::{ObjectID, SequenceNumber, TransactionDigest};
+use mgo_types::coin::CoinMetadata;
 use mgo_types::collection_types::VecMap;
 use mgo_types::crypto::AggregateAuthoritySignature;
 use mgo_types::digests::TransactionEventsDigest;
 use mgo_types::display::DisplayVersionUpdatedEvent;
+use mgo_types::display_template::{
+    render_display_fields, DisplayGrammar, DisplayStore, DISPLAY_GRAMMAR_KEY,
+};
 use mgo_types::effects::{TransactionEffects, TransactionEffectsAPI, TransactionEvents};
 use mgo_types::error::{MgoError, MgoObjectResponseError};
 use mgo_types::messages_checkpoint::{
@@ -51,6 +55,7 @@ use mgo_types::transaction::Transaction;
 use mgo_types::transaction::TransactionDataAPI;
 
 use crate::authority_state::{StateRead, StateReadError, StateReadResult};
+use crate::coin_api::find_package_object_id;
 use crate::error::{Error, RpcInterimResult, MgoRpcInputError};
 use crate::with_tracing;
 use crate::{
@@ -1119,7 +1124,11 @@ async fn get_display_fields(
     if let Some(display_object) =
         get_display_object_by_type(kv_store, fullnode_api, &object_type).await?
     {
-        return get_rendered_fields(display_object.fields, &layout);
+        let store = ReadApiDisplayStore {
+            fullnode_api,
+            kv_store,
+        };
+        return get_rendered_fields(display_object.fields, &layout, &store).await;
     }
     Ok(DisplayFieldsResponse {
         data: None,
@@ -1177,44 +1186,116 @@ fn get_move_struct(
         .to_move_struct(layout)?)
 }
 
-pub fn get_rendered_fields(
+/// Loads the objects and coin metadata referenced by version 2 display templates from the
+/// fullnode state.
+struct ReadApiDisplayStore<'a> {
+    fullnode_api: &'a ReadApi,
+    kv_store: &'a Arc<TransactionKeyValueStore>,
+}
+
+#[async_trait]
+impl DisplayStore for ReadApiDisplayStore<'_> {
+    async fn get_object(&self, id: ObjectID) -> anyhow::Result<Option<MoveStruct>> {
+        match self.get_object_read(id).await? {
+            ObjectRead::Exists(_, object, layout) => Ok(Some(get_move_struct(&object, &layout)?)),
+            ObjectRead::Deleted(_) | ObjectRead::NotExists(_) => Ok(None),
+        }
+    }
+
+    async fn get_coin_decimals(&self, coin_type: &StructTag) -> anyhow::Result<Option<u8>> {
+        let metadata_id = match find_package_object_id(
+            self.fullnode_api.state.clone(),
+            coin_type.address.into(),
+            CoinMetadata::type_(coin_type.clone()),
+            self.kv_store.clone(),
+        )
+        .await
+        {
+            Ok(metadata_id) => metadata_id,
+            // The package of the coin did not create its metadata.
+            Err(Error::MgoRpcInputError(MgoRpcInputError::GenericNotFound(_))) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        match self.get_object_read(metadata_id).await? {
+            ObjectRead::Exists(_, object, _) => Ok(Some(CoinMetadata::try_from(object)?.decimals)),
+            ObjectRead::Deleted(_) | ObjectRead::NotExists(_) => Ok(None),
+        }
+    }
+}
+
+impl ReadApiDisplayStore<'_> {
+    /// Reads the object from the database on a blocking thread, not to stall the runtime.
+    async fn get_object_read(&self, id: ObjectID) -> anyhow::Result<ObjectRead> {
+        let state = self.fullnode_api.state.clone();
+        Ok(tokio::task::spawn_blocking(move || state.get_object_read(&id)).await??)
+    }
+}
+
+pub async fn get_rendered_fields(
     fields: VecMap<String, String>,
     move_struct: &MoveStruct,
+    store: &dyn DisplayStore,
 ) -> Result<DisplayFieldsResponse, ObjectDisplayError> {
+    match DisplayGrammar::from_fields(&fields) {
+        Ok(DisplayGrammar::V1) => {}
+        Ok(DisplayGrammar::V2) => {
+            let rendered = render_display_fields(&fields, move_struct, store).await;
+            return Ok(to_display_fields_response(
+                rendered
+                    .into_iter()
+                    .map(|(key, value)| value.map(|value| (key, value))),
+            ));
+        }
+        Err(e) => {
+            return Ok(DisplayFieldsResponse {
+                data: None,
+                error: Some(MgoObjectResponseError::DisplayError {
+                    error: e.to_string(),
+                }),
+            })
+        }
+    }
+
     let mgo_move_value: MgoMoveValue = MoveValue::Struct(move_struct.clone()).into();
     if let MgoMoveValue::Struct(move_struct) = mgo_move_value {
-        let fields =
-            fields
-                .contents
-                .iter()
-                .map(|entry| match parse_template(&entry.value, &move_struct) {
-                    Ok(value) => Ok((entry.key.clone(), value)),
-                    Err(e) => Err(e),
-                });
-        let (oks, errs): (Vec<_>, Vec<_>) = fields.partition(Result::is_ok);
-        let success = oks.into_iter().filter_map(Result::ok).collect();
-        let errors: Vec<_> = errs.into_iter().filter_map(Result::err).collect();
-        let error_string = errors
+        let fields = fields
+            .contents
             .iter()
-            .map(|e| e.to_string())
-            .collect::<Vec<String>>()
-            .join("; ");
-        let error = if !error_string.is_empty() {
-            Some(MgoObjectResponseError::DisplayError {
-                error: anyhow!("{error_string}").to_string(),
-            })
-        } else {
-            None
-        };
-
-        return Ok(DisplayFieldsResponse {
-            data: Some(success),
-            error,
-        });
+            .filter(|entry| entry.key != DISPLAY_GRAMMAR_KEY)
+            .map(|entry| match parse_template(&entry.value, &move_struct) {
+                Ok(value) => Ok((entry.key.clone(), value)),
+                Err(e) => Err(e),
+            });
+        return Ok(to_display_fields_response(fields));
     }
     Err(ObjectDisplayError::NotMoveStruct)?
 }
 
+fn to_display_fields_response<E: std::fmt::Display>(
+    fields: impl Iterator<Item = Result<(String, String), E>>,
+) -> DisplayFieldsResponse {
+    let (oks, errs): (Vec<_>, Vec<_>) = fields.partition(Result::is_ok);
+    let success = oks.into_iter().filter_map(Result::ok).collect();
+    let errors: Vec<_> = errs.into_iter().filter_map(Result::err).collect();
+    let error_string = errors
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<String>>()
+        .join("; ");
+    let error = if !error_string.is_empty() {
+        Some(MgoObjectResponseError::DisplayError {
+            error: anyhow!("{error_string}").to_string(),
+        })
+    } else {
+        None
+    };
+
+    DisplayFieldsResponse {
+        data: Some(success),
+        error,
+    }
+}
+
 fn parse_template(template: &str, move_struct: &MgoMoveStruct) -> Result<String, Error> {
     let mut output = template.to_string();
     let mut var_name = String::new();
@@ -1400,6 +1481,61 @@ fn calculate_checkpoint_numbers(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mgo_types::collection_types::Entry;
+    use mgo_types::parse_mgo_struct_tag;
+    use move_core_types::identifier::Identifier;
+
+    struct EmptyDisplayStore;
+
+    #[async_trait]
+    impl DisplayStore for EmptyDisplayStore {
+        async fn get_object(&self, _id: ObjectID) -> anyhow::Result<Option<MoveStruct>> {
+            Ok(None)
+        }
+
+        async fn get_coin_decimals(&self, _coin_type: &StructTag) -> anyhow::Result<Option<u8>> {
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rendered_fields_skip_grammar() {
+        let name = MoveValue::Struct(MoveStruct::new(
+            parse_mgo_struct_tag("0x1::string::String").unwrap(),
+            vec![(
+                Identifier::new("bytes").unwrap(),
+                MoveValue::Vector(b"Alice".iter().copied().map(MoveValue::U8).collect()),
+            )],
+        ));
+        let move_struct = MoveStruct::new(
+            parse_mgo_struct_tag("0x42::hero::Hero").unwrap(),
+            vec![(Identifier::new("name").unwrap(), name)],
+        );
+
+        // Both grammars render the same fields, without the grammar field itself.
+        for grammar in ["1", "2"] {
+            let fields = VecMap {
+                contents: vec![
+                    Entry {
+                        key: DISPLAY_GRAMMAR_KEY.to_string(),
+                        value: grammar.to_string(),
+                    },
+                    Entry {
+                        key: "name".to_string(),
+                        value: "{name}".to_string(),
+                    },
+                ],
+            };
+            let rendered = get_rendered_fields(fields, &move_struct, &EmptyDisplayStore)
+                .await
+                .unwrap();
+            assert_eq!(rendered.error, None);
+            assert_eq!(
+                rendered.data,
+                Some([("name".to_string(), "Alice".to_string())].into())
+            );
+        }
+    }
 
     #[test]
     fn test_calculate_checkpoint_numbers() {
@@ -17,8 +17,9 @@ use mgo_core::authority_client::NetworkAuthorityClient;
 use mgo_core::transaction_orchestrator::TransactiondOrchestrator;
 use mgo_json_rpc_api::{JsonRpcMetrics, WriteApiOpenRpc, WriteApiServer};
 use mgo_json_rpc_types::{
-    DevInspectArgs, DevInspectResults, DryRunTransactionBlockResponse, MgoTransactionBlock,
-    MgoTransactionBlockEvents, MgoTransactionBlockResponse, MgoTransactionBlockResponseOptions,
+    DevInspectArgs, DevInspectResults, DryRunTransactionBlockResponse, MgoStateOverrides,
+    MgoTransactionBlock, MgoTransactionBlockEvents, MgoTransactionBlockResponse,
+    MgoTransactionBlockResponseOptions,
 };
 use mgo_open_rpc::Module;
 use mgo_types::base_types::MgoAddress;
@@ -29,6 +30,7 @@ use mgo_types::quorum_driver_types::{
     ExecuteTransactionRequest, ExecuteTransactionRequestType, ExecuteTransactionResponse,
 };
 use mgo_types::signature::GenericSignature;
+use mgo_types::state_override::StateOverrides;
 use mgo_types::mgo_serde::BigInt;
 use mgo_types::transaction::{
     InputObjectKind, Transaction, TransactionData, TransactionDataAPI, TransactionKind,
@@ -237,15 +239,18 @@ impl TransactionExecutionApi {
     async fn dry_run_transaction_block(
         &self,
         tx_bytes: Base64,
+        state_overrides: Option<MgoStateOverrides>,
     ) -> Result<DryRunTransactionBlockResponse, Error> {
         let (txn_data, txn_digest, input_objs) =
             self.prepare_dry_run_transaction_block(tx_bytes)?;
-        let sender = txn_data.sender();
-        let (resp, written_objects, transaction_effects, mock_gas) = self
+        let state_overrides: StateOverrides = state_overrides.unwrap_or_default().try_into()?;
+        let sender = state_overrides.sender.unwrap_or(txn_data.sender());
+        let (resp, written_objects, transaction_effects, mock_gas, overridden_objects) = self
             .state
-            .dry_exec_transaction(txn_data.clone(), txn_digest)
+            .dry_exec_transaction_with_overrides(txn_data.clone(), txn_digest, state_overrides)
             .await?;
-        let object_cache = ObjectProviderCache::new_with_cache(self.state.clone(), written_objects);
+        let object_cache = ObjectProviderCache::new_with_cache(self.state.clone(), written_objects)
+            .with_overridden_objects(overridden_objects);
         let balance_changes = get_balance_changes_from_effect(
             &object_cache,
             &transaction_effects,
@@ -268,6 +273,7 @@ impl TransactionExecutionApi {
             object_changes,
             balance_changes,
             input: resp.input,
+            state_overrides: resp.state_overrides,
         })
     }
 }
@@ -304,8 +310,11 @@ impl WriteApiServer for TransactionExecutionApi {
                 gas_objects,
                 show_raw_txn_data_and_effects,
                 skip_checks,
+                state_overrides,
+                trace,
             } = additional_args.unwrap_or_default();
             let tx_kind: TransactionKind = self.convert_bytes(tx_bytes)?;
+            let state_overrides = state_overrides.map(StateOverrides::try_from).transpose()?;
             self.state
                 .dev_inspect_transaction_block(
                     sender_address,
@@ -316,6 +325,8 @@ impl WriteApiServer for TransactionExecutionApi {
                     gas_objects,
                     show_raw_txn_data_and_effects,
                     skip_checks,
+                    state_overrides,
+                    trace,
                 )
                 .await
                 .map_err(Error::from)
@@ -326,8 +337,12 @@ impl WriteApiServer for TransactionExecutionApi {
     async fn dry_run_transaction_block(
         &self,
         tx_bytes: Base64,
+        state_overrides: Option<MgoStateOverrides>,
     ) -> RpcResult<DryRunTransactionBlockResponse> {
-        with_tracing!(async move { self.dry_run_transaction_block(tx_bytes).await })
+        with_tracing!(async move {
+            self.dry_run_transaction_block(tx_bytes, state_overrides)
+                .await
+        })
     }
 }
 
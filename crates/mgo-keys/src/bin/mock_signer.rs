@@ -0,0 +1,35 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::anyhow;
+use mgo_keys::external_keystore::MockSigner;
+use mgo_types::crypto::{EncodeDecodeBase64, MgoKeyPair};
+
+// An external signer for tests, which holds the keys it is given and signs whatever it is asked
+// to. Command line options are:
+// --socket <path> - listen on a unix socket at the path, instead of the standard input and output
+// <key>... - the keys to sign with, as Base64 encoded `flag || privkey`
+fn main() -> Result<(), anyhow::Error> {
+    let mut args = std::env::args().skip(1).peekable();
+    let socket = if args.peek().is_some_and(|arg| arg == "--socket") {
+        args.next();
+        let path = args
+            .next()
+            .ok_or_else(|| anyhow!("Missing the path of --socket"))?;
+        Some(path)
+    } else {
+        None
+    };
+    let keys = args
+        .map(|key| MgoKeyPair::decode_base64(&key).map_err(|e| anyhow!("Invalid key: {e}")))
+        .collect::<Result<Vec<_>, _>>()?;
+    let signer = MockSigner::new(keys);
+
+    match socket {
+        #[cfg(unix)]
+        Some(path) => signer.serve_socket(std::os::unix::net::UnixListener::bind(path)?),
+        #[cfg(not(unix))]
+        Some(_) => anyhow::bail!("Unix sockets are not supported on this platform"),
+        None => signer.serve(std::io::stdin().lock(), std::io::stdout().lock()),
+    }
+}
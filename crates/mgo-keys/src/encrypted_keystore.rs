@@ -0,0 +1,730 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::keystore::{load_aliases, save_aliases, validate_alias, AccountKeystore, Alias};
+use crate::random_names::random_name;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, bail, ensure, Context};
+use fastcrypto::encoding::{Base64, Encoding};
+use mgo_types::base_types::MgoAddress;
+use mgo_types::crypto::{EncodeDecodeBase64, MgoKeyPair, PublicKey, Signature};
+use rand::RngCore;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use shared_crypto::intent::{Intent, IntentMessage};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zeroize::Zeroizing;
+
+/// The password of an encrypted keystore is read from this environment variable when it is set,
+/// instead of being prompted for.
+pub const MGO_KEYSTORE_PASSWORD: &str = "MGO_KEYSTORE_PASSWORD";
+/// The new password of an encrypted keystore is read from this environment variable when it is
+/// set, instead of being prompted for, when the password is changed.
+pub const MGO_KEYSTORE_NEW_PASSWORD: &str = "MGO_KEYSTORE_NEW_PASSWORD";
+/// The token of an unlock session is read from this environment variable. Without it, the
+/// session file next to the keystore cannot be decrypted.
+pub const MGO_KEYSTORE_SESSION: &str = "MGO_KEYSTORE_SESSION";
+
+const KEYSTORE_VERSION: u32 = 1;
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+const SALT_LENGTH: usize = 32;
+/// Encrypted with the keystore's key, to tell a wrong password apart from a corrupted key.
+const PASSWORD_CHECK: &[u8] = b"mgo encrypted keystore";
+/// The cheapest scrypt parameters a keystore may use, so that a copy of the keystore file cannot
+/// be edited into one whose password is quick to guess.
+const MIN_KDF_LOG_N: u8 = 14;
+const MIN_KDF_R: u32 = 8;
+
+/// Parameters of scrypt, which derives the encryption key of a keystore from its password.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            log_n: 17,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    /// The cheapest parameters that are accepted, so that tests do not spend seconds deriving
+    /// keys.
+    pub fn insecure_for_tests() -> Self {
+        Self {
+            log_n: MIN_KDF_LOG_N,
+            r: MIN_KDF_R,
+            p: 1,
+        }
+    }
+
+    /// Fails if the parameters are cheaper than the minimum accepted for a keystore.
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        ensure!(
+            self.log_n >= MIN_KDF_LOG_N && self.r >= MIN_KDF_R && self.p >= 1,
+            "The key derivation parameters of the keystore are too weak, the minimum is \
+             log_n = {MIN_KDF_LOG_N}, r = {MIN_KDF_R} and p = 1"
+        );
+        Ok(())
+    }
+}
+
+/// The contents of an encrypted keystore file. Public keys are kept in the clear, so that
+/// addresses can be listed without the password, and each private key is encrypted separately
+/// with AES-256-GCM, authenticated together with its public key.
+#[derive(Serialize, Deserialize)]
+struct EncryptedKeystoreFile {
+    version: u32,
+    kdf: KdfParams,
+    salt: String,
+    check: Ciphertext,
+    keys: Vec<EncryptedKeyFile>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Ciphertext {
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedKeyFile {
+    public_key: String,
+    #[serde(flatten)]
+    ciphertext: Ciphertext,
+}
+
+/// Written next to the keystore by `unlock` with a timeout, so that later commands can use the
+/// keystore without asking for the password again until the session expires. The keystore's key
+/// is encrypted with the session token, authenticated together with the expiry time.
+#[derive(Serialize, Deserialize)]
+struct UnlockSession {
+    expires_at_ms: u64,
+    key: Ciphertext,
+}
+
+/// An unlock session started by [EncryptedFileKeystore::unlock]. Later commands use the unlocked
+/// keystore while `token` is set in `MGO_KEYSTORE_SESSION`, and the token is never written to
+/// disk.
+pub struct SessionToken {
+    pub expires_at: SystemTime,
+    pub token: Zeroizing<String>,
+}
+
+struct EncryptedKey {
+    public_key: PublicKey,
+    ciphertext: Ciphertext,
+}
+
+struct Unlocked {
+    key: Zeroizing<[u8; KEY_LENGTH]>,
+    keys: BTreeMap<MgoAddress, MgoKeyPair>,
+}
+
+/// A keystore whose private keys are encrypted at rest with a key derived from a password. The
+/// keystore is unlocked the first time a private key is needed, from an unlock session whose
+/// token is in `MGO_KEYSTORE_SESSION`, the `MGO_KEYSTORE_PASSWORD` environment variable, or by
+/// prompting for the password.
+pub struct EncryptedFileKeystore {
+    path: PathBuf,
+    kdf: KdfParams,
+    salt: Vec<u8>,
+    check: Ciphertext,
+    keys: BTreeMap<MgoAddress, EncryptedKey>,
+    aliases: BTreeMap<MgoAddress, Alias>,
+    unlocked: OnceLock<Unlocked>,
+}
+
+impl Serialize for EncryptedFileKeystore {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.path.to_str().unwrap_or(""))
+    }
+}
+
+impl<'de> Deserialize<'de> for EncryptedFileKeystore {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+        EncryptedFileKeystore::new(&PathBuf::from(String::deserialize(deserializer)?))
+            .map_err(D::Error::custom)
+    }
+}
+
+impl AccountKeystore for EncryptedFileKeystore {
+    fn sign_hashed(&self, address: &MgoAddress, msg: &[u8]) -> Result<Signature, signature::Error> {
+        Ok(Signature::new_hashed(
+            msg,
+            self.get_key(address)
+                .map_err(|e| signature::Error::from_source(e.to_string()))?,
+        ))
+    }
+
+    fn sign_secure<T>(
+        &self,
+        address: &MgoAddress,
+        msg: &T,
+        intent: Intent,
+    ) -> Result<Signature, signature::Error>
+    where
+        T: Serialize,
+    {
+        Ok(Signature::new_secure(
+            &IntentMessage::new(intent, msg),
+            self.get_key(address)
+                .map_err(|e| signature::Error::from_source(e.to_string()))?,
+        ))
+    }
+
+    fn add_key(&mut self, alias: Option<String>, keypair: MgoKeyPair) -> Result<(), anyhow::Error> {
+        let public_key = keypair.public();
+        let address: MgoAddress = (&public_key).into();
+        let alias = self.create_alias(alias)?;
+
+        self.unlocked()?;
+        let unlocked = self.unlocked.get_mut().expect("Keystore was just unlocked");
+        let ciphertext = encrypt_key(&unlocked.key, &keypair)?;
+        unlocked.keys.insert(address, keypair);
+        self.aliases.insert(
+            address,
+            Alias {
+                alias,
+                public_key_base64: public_key.encode_base64(),
+            },
+        );
+        self.keys.insert(
+            address,
+            EncryptedKey {
+                public_key,
+                ciphertext,
+            },
+        );
+        self.save()?;
+        Ok(())
+    }
+
+    /// Return an array of `Alias`, consisting of every alias and its corresponding public key.
+    fn aliases(&self) -> Vec<&Alias> {
+        self.aliases.values().collect()
+    }
+
+    fn addresses_with_alias(&self) -> Vec<(&MgoAddress, &Alias)> {
+        self.aliases.iter().collect::<Vec<_>>()
+    }
+
+    /// Return an array of `Alias`, consisting of every alias and its corresponding public key.
+    fn aliases_mut(&mut self) -> Vec<&mut Alias> {
+        self.aliases.values_mut().collect()
+    }
+
+    /// The public keys are stored in the clear, so they are available while the keystore is
+    /// locked.
+    fn keys(&self) -> Vec<PublicKey> {
+        self.keys
+            .values()
+            .map(|key| key.public_key.clone())
+            .collect()
+    }
+
+    /// This function returns an error if the provided alias already exists. If the alias
+    /// has not already been used, then it returns the alias.
+    /// If no alias has been passed, it will generate a new alias.
+    fn create_alias(&self, alias: Option<String>) -> Result<String, anyhow::Error> {
+        match alias {
+            Some(a) if self.alias_exists(&a) => {
+                bail!("Alias {a} already exists. Please choose another alias.")
+            }
+            Some(a) => validate_alias(&a),
+            None => Ok(random_name(
+                &self
+                    .alias_names()
+                    .into_iter()
+                    .map(|x| x.to_string())
+                    .collect::<HashSet<_>>(),
+            )),
+        }
+    }
+
+    /// Get the address by its alias
+    fn get_address_by_alias(&self, alias: String) -> Result<&MgoAddress, anyhow::Error> {
+        self.addresses_with_alias()
+            .iter()
+            .find(|x| x.1.alias == alias)
+            .ok_or_else(|| anyhow!("Cannot resolve alias {alias} to an address"))
+            .map(|x| x.0)
+    }
+
+    /// Get the alias if it exists, or return an error if it does not exist.
+    fn get_alias_by_address(&self, address: &MgoAddress) -> Result<String, anyhow::Error> {
+        match self.aliases.get(address) {
+            Some(alias) => Ok(alias.alias.clone()),
+            None => bail!("Cannot find alias for address {address}"),
+        }
+    }
+
+    /// Unlocks the keystore if it is not unlocked yet.
+    fn get_key(&self, address: &MgoAddress) -> Result<&MgoKeyPair, anyhow::Error> {
+        if !self.keys.contains_key(address) {
+            bail!("Cannot find key for address: [{address}]");
+        }
+        match self.unlocked()?.keys.get(address) {
+            Some(key) => Ok(key),
+            None => Err(anyhow!("Cannot find key for address: [{address}]")),
+        }
+    }
+
+    /// Updates an old alias to the new alias and saves it to the alias file.
+    /// If the new_alias is None, it will generate a new random alias.
+    fn update_alias(
+        &mut self,
+        old_alias: &str,
+        new_alias: Option<&str>,
+    ) -> Result<String, anyhow::Error> {
+        let new_alias_name = self.update_alias_value(old_alias, new_alias)?;
+        save_aliases(&self.path, &self.aliases)?;
+        Ok(new_alias_name)
+    }
+}
+
+impl EncryptedFileKeystore {
+    /// Opens the encrypted keystore at `path`, without unlocking it.
+    pub fn new(path: &PathBuf) -> Result<Self, anyhow::Error> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Cannot open the keystore file: {}", path.display()))?;
+        let file: EncryptedKeystoreFile = serde_json::from_str(&contents)
+            .with_context(|| format!("Cannot deserialize the keystore file: {}", path.display()))?;
+        ensure!(
+            file.version == KEYSTORE_VERSION,
+            "Unsupported version {} of encrypted keystore file: {}",
+            file.version,
+            path.display()
+        );
+        file.kdf
+            .validate()
+            .with_context(|| format!("Cannot open the keystore file: {}", path.display()))?;
+
+        let keys = file
+            .keys
+            .into_iter()
+            .map(|key| {
+                let public_key = PublicKey::decode_base64(&key.public_key)
+                    .map_err(|e| anyhow!("Invalid keystore file: {}. {}", path.display(), e))?;
+                Ok((
+                    MgoAddress::from(&public_key),
+                    EncryptedKey {
+                        public_key,
+                        ciphertext: key.ciphertext,
+                    },
+                ))
+            })
+            .collect::<Result<BTreeMap<_, _>, anyhow::Error>>()?;
+        let public_keys = keys
+            .iter()
+            .map(|(address, key)| (*address, key.public_key.clone()))
+            .collect();
+        let aliases = load_aliases(path, &public_keys)?;
+
+        Ok(Self {
+            path: path.clone(),
+            kdf: file.kdf,
+            salt: decode(&file.salt)?,
+            check: file.check,
+            keys,
+            aliases,
+            unlocked: OnceLock::new(),
+        })
+    }
+
+    /// Creates an empty keystore at `path`, encrypted with `password`.
+    pub fn create(path: &Path, password: &str, kdf: KdfParams) -> Result<Self, anyhow::Error> {
+        Self::from_keys(path, password, kdf, BTreeMap::new(), BTreeMap::new())
+    }
+
+    pub(crate) fn from_keys(
+        path: &Path,
+        password: &str,
+        kdf: KdfParams,
+        keys: BTreeMap<MgoAddress, MgoKeyPair>,
+        aliases: BTreeMap<MgoAddress, Alias>,
+    ) -> Result<Self, anyhow::Error> {
+        ensure!(!password.is_empty(), "The password cannot be empty");
+        kdf.validate()?;
+        let mut keystore = Self {
+            path: path.to_path_buf(),
+            kdf,
+            salt: vec![],
+            check: Ciphertext {
+                nonce: String::new(),
+                ciphertext: String::new(),
+            },
+            keys: BTreeMap::new(),
+            aliases,
+            unlocked: OnceLock::new(),
+        };
+        keystore.reencrypt(password, keys)?;
+        keystore.save()?;
+        Ok(keystore)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file at `path` is an encrypted keystore, as opposed to a plaintext one.
+    pub fn is_encrypted(path: &Path) -> bool {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+            .is_some_and(|value| value.is_object())
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.unlocked.get().is_some()
+    }
+
+    /// Unlocks the keystore with `password`. With a `timeout`, the keystore stays unlocked for
+    /// later commands that are given the returned session token, until the timeout expires or
+    /// the keystore is locked again.
+    pub fn unlock(
+        &mut self,
+        password: &str,
+        timeout: Option<Duration>,
+    ) -> Result<Option<SessionToken>, anyhow::Error> {
+        let unlocked = self.open(&self.derive_key(password)?)?;
+        let session = match timeout {
+            Some(timeout) => Some(self.write_session(&unlocked.key, timeout)?),
+            None => None,
+        };
+        self.unlocked = OnceLock::from(unlocked);
+        Ok(session)
+    }
+
+    /// Ends the unlock session of the keystore, if any, and forgets its decrypted keys.
+    pub fn lock(&mut self) -> Result<(), anyhow::Error> {
+        let session_path = self.session_path();
+        if session_path.exists() {
+            fs::remove_file(&session_path).with_context(|| {
+                format!("Cannot remove keystore session: {}", session_path.display())
+            })?;
+        }
+        self.unlocked = OnceLock::new();
+        Ok(())
+    }
+
+    /// Re-encrypts every key with a key derived from `new_password`, and ends any unlock
+    /// session, which was made with the old key.
+    pub fn change_password(
+        &mut self,
+        old_password: &str,
+        new_password: &str,
+    ) -> Result<(), anyhow::Error> {
+        ensure!(!new_password.is_empty(), "The password cannot be empty");
+        let unlocked = self.open(&self.derive_key(old_password)?)?;
+        self.lock()?;
+        self.reencrypt(new_password, unlocked.keys)?;
+        self.save()
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        save_aliases(&self.path, &self.aliases)?;
+        self.save_keystore()
+    }
+
+    fn save_keystore(&self) -> Result<(), anyhow::Error> {
+        let file = EncryptedKeystoreFile {
+            version: KEYSTORE_VERSION,
+            kdf: self.kdf,
+            salt: Base64::encode(&self.salt),
+            check: self.check.clone(),
+            keys: self
+                .keys
+                .values()
+                .map(|key| EncryptedKeyFile {
+                    public_key: key.public_key.encode_base64(),
+                    ciphertext: key.ciphertext.clone(),
+                })
+                .collect(),
+        };
+        let store = serde_json::to_string_pretty(&file).with_context(|| {
+            format!("Cannot serialize keystore to file: {}", self.path.display())
+        })?;
+
+        // Replace the file in one step, so that an interrupted write cannot lose any keys.
+        let mut tmp_path = self.path.clone();
+        tmp_path.set_extension("keystore.tmp");
+        fs::write(&tmp_path, store)?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Encrypts `keys` with a new salt and a key derived from `password`, and leaves the keystore
+    /// unlocked.
+    fn reencrypt(
+        &mut self,
+        password: &str,
+        keys: BTreeMap<MgoAddress, MgoKeyPair>,
+    ) -> Result<(), anyhow::Error> {
+        let mut salt = vec![0; SALT_LENGTH];
+        rand::thread_rng().fill_bytes(&mut salt);
+        self.salt = salt;
+        let key = self.derive_key(password)?;
+
+        self.check = encrypt(&key, PASSWORD_CHECK, &[])?;
+        self.keys = keys
+            .iter()
+            .map(|(address, keypair)| {
+                Ok((
+                    *address,
+                    EncryptedKey {
+                        public_key: keypair.public(),
+                        ciphertext: encrypt_key(&key, keypair)?,
+                    },
+                ))
+            })
+            .collect::<Result<_, anyhow::Error>>()?;
+        self.unlocked = OnceLock::from(Unlocked { key, keys });
+        Ok(())
+    }
+
+    /// The decrypted keys, unlocking the keystore first if needed.
+    fn unlocked(&self) -> Result<&Unlocked, anyhow::Error> {
+        if let Some(unlocked) = self.unlocked.get() {
+            return Ok(unlocked);
+        }
+        let unlocked = match self.read_session()? {
+            Some(key) => self.open(&key)?,
+            None => {
+                let password = read_password(&format!(
+                    "Enter the password of keystore {}: ",
+                    self.path.display()
+                ))?;
+                self.open(&self.derive_key(&password)?)?
+            }
+        };
+        Ok(self.unlocked.get_or_init(|| unlocked))
+    }
+
+    /// Decrypts every key with `key`, failing if it was not derived from the right password.
+    fn open(&self, key: &[u8; KEY_LENGTH]) -> Result<Unlocked, anyhow::Error> {
+        ensure!(
+            decrypt(key, &self.check, &[]).is_ok(),
+            "Incorrect password for keystore {}",
+            self.path.display()
+        );
+        let keys = self
+            .keys
+            .iter()
+            .map(|(address, encrypted)| {
+                let keypair = decrypt_key(key, encrypted)
+                    .with_context(|| format!("Cannot decrypt the key for address {address}"))?;
+                Ok((*address, keypair))
+            })
+            .collect::<Result<_, anyhow::Error>>()?;
+        Ok(Unlocked {
+            key: Zeroizing::new(*key),
+            keys,
+        })
+    }
+
+    fn derive_key(&self, password: &str) -> Result<Zeroizing<[u8; KEY_LENGTH]>, anyhow::Error> {
+        let params = scrypt::Params::new(self.kdf.log_n, self.kdf.r, self.kdf.p)
+            .map_err(|_| anyhow!("Invalid key derivation parameters in keystore"))?;
+        let mut key = Zeroizing::new([0; KEY_LENGTH]);
+        scrypt::scrypt(password.as_bytes(), &self.salt, &params, key.as_mut())
+            .map_err(|_| anyhow!("Cannot derive the keystore key from the password"))?;
+        Ok(key)
+    }
+
+    fn session_path(&self) -> PathBuf {
+        let mut session_path = self.path.clone();
+        session_path.set_extension("session");
+        session_path
+    }
+
+    /// Writes the keystore's `key` to the session file, encrypted with a new random token that
+    /// is only returned to the caller.
+    fn write_session(
+        &self,
+        key: &[u8; KEY_LENGTH],
+        timeout: Duration,
+    ) -> Result<SessionToken, anyhow::Error> {
+        let expires_at = SystemTime::now() + timeout;
+        let expires_at_ms = expires_at.duration_since(UNIX_EPOCH)?.as_millis() as u64;
+        let mut token = Zeroizing::new([0; KEY_LENGTH]);
+        rand::thread_rng().fill_bytes(token.as_mut());
+        let session = UnlockSession {
+            expires_at_ms,
+            key: encrypt(&token, key, &expires_at_ms.to_le_bytes())?,
+        };
+        let session_path = self.session_path();
+        write_private_file(&session_path, &serde_json::to_string(&session)?).with_context(
+            || format!("Cannot write keystore session: {}", session_path.display()),
+        )?;
+        Ok(SessionToken {
+            expires_at,
+            token: Zeroizing::new(Base64::encode(token.as_ref())),
+        })
+    }
+
+    /// The key of the current unlock session, if there is one that has not expired and its token
+    /// is set in `MGO_KEYSTORE_SESSION`. Sessions that have expired, or were made before the
+    /// password was changed, are removed.
+    fn read_session(&self) -> Result<Option<Zeroizing<[u8; KEY_LENGTH]>>, anyhow::Error> {
+        let session_path = self.session_path();
+        let Ok(contents) = fs::read_to_string(&session_path) else {
+            return Ok(None);
+        };
+        let now_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+        let Some(session) = serde_json::from_str::<UnlockSession>(&contents)
+            .ok()
+            .filter(|session| session.expires_at_ms > now_ms)
+        else {
+            fs::remove_file(&session_path).with_context(|| {
+                format!("Cannot remove keystore session: {}", session_path.display())
+            })?;
+            return Ok(None);
+        };
+        let Some(token) = std::env::var(MGO_KEYSTORE_SESSION)
+            .ok()
+            .and_then(|token| Base64::decode(&token).ok())
+            .and_then(|token| <[u8; KEY_LENGTH]>::try_from(token).ok())
+            .map(Zeroizing::new)
+        else {
+            return Ok(None);
+        };
+        // A token from another session cannot decrypt the key, and is ignored.
+        let Some(key) = decrypt(&token, &session.key, &session.expires_at_ms.to_le_bytes())
+            .ok()
+            .and_then(|key| <[u8; KEY_LENGTH]>::try_from(key.as_slice()).ok())
+            .map(Zeroizing::new)
+        else {
+            return Ok(None);
+        };
+        if decrypt(&key, &self.check, &[]).is_err() {
+            fs::remove_file(&session_path).with_context(|| {
+                format!("Cannot remove keystore session: {}", session_path.display())
+            })?;
+            return Ok(None);
+        }
+        Ok(Some(key))
+    }
+}
+
+/// Reads the password of a keystore from `MGO_KEYSTORE_PASSWORD` if it is set, or prompts for
+/// it otherwise.
+pub fn read_password(prompt: &str) -> Result<String, anyhow::Error> {
+    read_password_from_env(MGO_KEYSTORE_PASSWORD, prompt)
+}
+
+/// Reads a password from the environment variable `var` if it is set, or prompts for it if the
+/// standard input is a terminal.
+pub fn read_password_from_env(var: &str, prompt: &str) -> Result<String, anyhow::Error> {
+    if let Ok(password) = std::env::var(var) {
+        return Ok(password);
+    }
+    ensure!(
+        std::io::stdin().is_terminal(),
+        "The keystore is locked. Unlock it with `mgo keytool unlock`, or set {var}"
+    );
+    Ok(rpassword::prompt_password(prompt)?)
+}
+
+/// Reads a new password from the environment variable `var` if it is set, or prompts for it
+/// twice otherwise, so that a mistyped password does not lock the keys away.
+pub fn read_new_password(var: &str) -> Result<String, anyhow::Error> {
+    if let Ok(password) = std::env::var(var) {
+        return Ok(password);
+    }
+    ensure!(
+        std::io::stdin().is_terminal(),
+        "Cannot prompt for the new password, set {var}"
+    );
+    let password = rpassword::prompt_password("Enter the new password: ")?;
+    let confirmation = rpassword::prompt_password("Confirm the new password: ")?;
+    ensure!(password == confirmation, "The passwords do not match");
+    Ok(password)
+}
+
+fn encrypt_key(key: &[u8; KEY_LENGTH], keypair: &MgoKeyPair) -> Result<Ciphertext, anyhow::Error> {
+    let plaintext = Zeroizing::new(keypair.encode_base64());
+    let aad = keypair.public().encode_base64();
+    encrypt(key, plaintext.as_bytes(), aad.as_bytes())
+}
+
+fn decrypt_key(
+    key: &[u8; KEY_LENGTH],
+    encrypted: &EncryptedKey,
+) -> Result<MgoKeyPair, anyhow::Error> {
+    let aad = encrypted.public_key.encode_base64();
+    let plaintext = decrypt(key, &encrypted.ciphertext, aad.as_bytes())?;
+    let keypair = MgoKeyPair::decode_base64(std::str::from_utf8(&plaintext)?)
+        .map_err(|e| anyhow!("Invalid key: {e}"))?;
+    ensure!(
+        keypair.public() == encrypted.public_key,
+        "The key does not match its public key"
+    );
+    Ok(keypair)
+}
+
+fn encrypt(key: &[u8; KEY_LENGTH], msg: &[u8], aad: &[u8]) -> Result<Ciphertext, anyhow::Error> {
+    let mut nonce = [0; NONCE_LENGTH];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg, aad })
+        .map_err(|_| anyhow!("Cannot encrypt key"))?;
+    Ok(Ciphertext {
+        nonce: Base64::encode(nonce),
+        ciphertext: Base64::encode(ciphertext),
+    })
+}
+
+fn decrypt(
+    key: &[u8; KEY_LENGTH],
+    ciphertext: &Ciphertext,
+    aad: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, anyhow::Error> {
+    let nonce = decode(&ciphertext.nonce)?;
+    ensure!(nonce.len() == NONCE_LENGTH, "Invalid nonce length");
+    let msg = decode(&ciphertext.ciphertext)?;
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+        .decrypt(Nonce::from_slice(&nonce), Payload { msg: &msg, aad })
+        .map(Zeroizing::new)
+        .map_err(|_| anyhow!("Decryption failed"))
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, anyhow::Error> {
+    Base64::decode(value).map_err(|e| anyhow!("Invalid Base64 in keystore: {e}"))
+}
+
+/// Writes `contents` to a file that only the current user can read.
+fn write_private_file(path: &Path, contents: &str) -> Result<(), anyhow::Error> {
+    use std::io::Write;
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents.as_bytes())?;
+    Ok(())
+}
@@ -0,0 +1,700 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A keystore whose private keys are held by an external signer, such as an HSM bridge, a remote
+//! signing service or a policy engine, which the keystore talks to over a JSON protocol.
+//!
+//! # Protocol
+//!
+//! Requests and responses are JSON objects, one per line. The signer is reached either as a
+//! process started by the keystore, which reads requests from its standard input and writes
+//! responses to its standard output, or on a unix socket, with a new connection for every
+//! request. A request carries an `id` and a `method`, and the response to it echoes the `id`
+//! along with either a `result` or an `error` message:
+//!
+//! ```text
+//! {"id":1,"method":"list_keys"}
+//! {"id":1,"result":{"public_keys":["AJ7a...", "AQJx..."]}}
+//!
+//! {"id":2,"method":"get_public_key","address":"0x5c8f..."}
+//! {"id":2,"result":{"public_key":"AJ7a..."}}
+//!
+//! {"id":3,"method":"sign","address":"0x5c8f...","intent_message":"AAAA..."}
+//! {"id":3,"result":{"signature":"AMe4..."}}
+//!
+//! {"id":4,"method":"sign","address":"0x0000...","intent_message":"AAAA..."}
+//! {"id":4,"error":"Unknown address 0x0000..."}
+//! ```
+//!
+//! Public keys are Base64 encoded `flag || pk`, and signatures are Base64 encoded
+//! `flag || sig || pk`. The intent message is the Base64 encoded BCS bytes of an
+//! [struct IntentMessage], so that the signer can inspect the intent and the transaction before
+//! signing them. The signature commits to the Blake2b hash of those bytes, as with any other
+//! key, and is verified by the keystore before it is used.
+//!
+//! A signer that does not answer a request within the configured timeout, 30 seconds by
+//! default, fails the request, and a signer process is started again for the next one.
+//!
+//! [MockSigner] is a reference implementation of the signer side of the protocol.
+//!
+//! # Configuration
+//!
+//! The keystore is set in the `keystore` entry of `client.yaml`. Opening the config does not
+//! reach the signer: its keys are listed the first time they are needed, and the public keys
+//! cached in the aliases file are used instead if the signer cannot be reached then.
+//!
+//! ```yaml
+//! keystore:
+//!   External:
+//!     transport:
+//!       command:
+//!         command: /usr/local/bin/hsm-signer
+//!         args: ["--slot", "0"]
+//!     aliases_path: /home/me/.mgo/mgo_config/hsm.aliases
+//!     timeout_secs: 120
+//! ```
+//!
+//! A signer listening on a unix socket is configured with `socket: { path: /run/signer.sock }`
+//! as its transport instead.
+
+use crate::keystore::{load_aliases, save_aliases, validate_alias, AccountKeystore, Alias};
+use crate::random_names::{random_name, random_names};
+use anyhow::{anyhow, bail, ensure, Context};
+use fastcrypto::encoding::{Base64, Encoding};
+use fastcrypto::hash::HashFunction;
+use mgo_types::base_types::MgoAddress;
+use mgo_types::crypto::{
+    DefaultHash, EncodeDecodeBase64, MgoKeyPair, MgoSignature, PublicKey, Signature,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use shared_crypto::intent::{Intent, IntentMessage};
+use std::collections::{BTreeMap, HashSet};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// How long a signer has to answer a request, unless its config sets another timeout.
+const DEFAULT_SIGNER_TIMEOUT_SECS: u64 = 30;
+
+/// How the keystore reaches its external signer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignerTransport {
+    /// A process that is started the first time the signer is needed, and kept running for as
+    /// long as the keystore is open.
+    Command {
+        command: PathBuf,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+    },
+    /// A signer listening on a unix socket.
+    Socket { path: PathBuf },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalSignerConfig {
+    pub transport: SignerTransport,
+    /// The file that the aliases of the signer's keys are kept in. Without one, aliases are
+    /// generated every time the keystore is opened.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aliases_path: Option<PathBuf>,
+    /// How long the signer has to answer a request, in seconds. Signers that wait for a person
+    /// to approve a request need a longer timeout than the default of 30 seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+impl ExternalSignerConfig {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.unwrap_or(DEFAULT_SIGNER_TIMEOUT_SECS))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignerRequest {
+    pub id: u64,
+    #[serde(flatten)]
+    pub method: SignerMethod,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum SignerMethod {
+    /// List the public keys held by the signer.
+    ListKeys,
+    /// Get the public key of an address.
+    GetPublicKey { address: MgoAddress },
+    /// Sign the Base64 encoded BCS bytes of an intent message with the key of an address.
+    Sign {
+        address: MgoAddress,
+        intent_message: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignerResponse {
+    pub id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListKeysResult {
+    pub public_keys: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetPublicKeyResult {
+    pub public_key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignResult {
+    pub signature: Signature,
+}
+
+/// A keystore that signs with the keys of an external signer. The private keys never leave the
+/// signer, so they cannot be exported, and keys cannot be added to it through the keystore.
+pub struct ExternalKeystore {
+    config: ExternalSignerConfig,
+    signer: SignerConnection,
+    /// Listed from the signer the first time they are needed.
+    known: OnceLock<KnownKeys>,
+}
+
+#[derive(Default)]
+struct KnownKeys {
+    keys: BTreeMap<MgoAddress, PublicKey>,
+    aliases: BTreeMap<MgoAddress, Alias>,
+}
+
+impl Serialize for ExternalKeystore {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.config.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ExternalKeystore {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(ExternalKeystore::open(ExternalSignerConfig::deserialize(
+            deserializer,
+        )?))
+    }
+}
+
+impl AccountKeystore for ExternalKeystore {
+    fn sign_hashed(&self, _: &MgoAddress, _: &[u8]) -> Result<Signature, signature::Error> {
+        Err(signature::Error::from_source(
+            "An external signer only signs intent messages",
+        ))
+    }
+
+    fn sign_secure<T>(
+        &self,
+        address: &MgoAddress,
+        msg: &T,
+        intent: Intent,
+    ) -> Result<Signature, signature::Error>
+    where
+        T: Serialize,
+    {
+        self.sign_intent_message(address, &IntentMessage::new(intent, msg))
+            .map_err(|e| signature::Error::from_source(e.to_string()))
+    }
+
+    fn add_key(&mut self, _: Option<String>, keypair: MgoKeyPair) -> Result<(), anyhow::Error> {
+        bail!(
+            "Cannot add key for address {} to an external signer, add it to the signer instead",
+            MgoAddress::from(&keypair.public())
+        )
+    }
+
+    /// Return an array of `Alias`, consisting of every alias and its corresponding public key.
+    fn aliases(&self) -> Vec<&Alias> {
+        self.known().aliases.values().collect()
+    }
+
+    fn addresses_with_alias(&self) -> Vec<(&MgoAddress, &Alias)> {
+        self.known().aliases.iter().collect::<Vec<_>>()
+    }
+
+    /// Return an array of `Alias`, consisting of every alias and its corresponding public key.
+    fn aliases_mut(&mut self) -> Vec<&mut Alias> {
+        self.known_mut().aliases.values_mut().collect()
+    }
+
+    fn keys(&self) -> Vec<PublicKey> {
+        self.known().keys.values().cloned().collect()
+    }
+
+    /// This function returns an error if the provided alias already exists. If the alias
+    /// has not already been used, then it returns the alias.
+    /// If no alias has been passed, it will generate a new alias.
+    fn create_alias(&self, alias: Option<String>) -> Result<String, anyhow::Error> {
+        match alias {
+            Some(a) if self.alias_exists(&a) => {
+                bail!("Alias {a} already exists. Please choose another alias.")
+            }
+            Some(a) => validate_alias(&a),
+            None => Ok(random_name(
+                &self
+                    .alias_names()
+                    .into_iter()
+                    .map(|x| x.to_string())
+                    .collect::<HashSet<_>>(),
+            )),
+        }
+    }
+
+    /// Get the address by its alias
+    fn get_address_by_alias(&self, alias: String) -> Result<&MgoAddress, anyhow::Error> {
+        self.addresses_with_alias()
+            .iter()
+            .find(|x| x.1.alias == alias)
+            .ok_or_else(|| anyhow!("Cannot resolve alias {alias} to an address"))
+            .map(|x| x.0)
+    }
+
+    /// Get the alias if it exists, or return an error if it does not exist.
+    fn get_alias_by_address(&self, address: &MgoAddress) -> Result<String, anyhow::Error> {
+        match self.known().aliases.get(address) {
+            Some(alias) => Ok(alias.alias.clone()),
+            None => bail!("Cannot find alias for address {address}"),
+        }
+    }
+
+    /// The private keys are held by the signer, so this always fails.
+    fn get_key(&self, address: &MgoAddress) -> Result<&MgoKeyPair, anyhow::Error> {
+        bail!("The private key for address [{address}] is held by an external signer")
+    }
+
+    /// Updates an old alias to the new alias, and saves it to the alias file if there is one.
+    /// If the new_alias is None, it will generate a new random alias.
+    fn update_alias(
+        &mut self,
+        old_alias: &str,
+        new_alias: Option<&str>,
+    ) -> Result<String, anyhow::Error> {
+        let new_alias_name = self.update_alias_value(old_alias, new_alias)?;
+        if let Some(path) = &self.config.aliases_path {
+            save_aliases(path, &self.known().aliases)?;
+        }
+        Ok(new_alias_name)
+    }
+}
+
+impl ExternalKeystore {
+    /// Connects to the signer described by `config` and lists its keys.
+    pub fn new(config: ExternalSignerConfig) -> Result<Self, anyhow::Error> {
+        let keystore = Self::open(config);
+        let known = keystore.list_keys()?;
+        let _ = keystore.known.set(known);
+        Ok(keystore)
+    }
+
+    /// Opens the keystore without reaching the signer, which is only connected to once its keys
+    /// are needed.
+    pub fn open(config: ExternalSignerConfig) -> Self {
+        let signer = SignerConnection::new(config.transport.clone(), config.timeout());
+        Self {
+            config,
+            signer,
+            known: OnceLock::new(),
+        }
+    }
+
+    pub fn config(&self) -> &ExternalSignerConfig {
+        &self.config
+    }
+
+    /// The keys of the signer, or the keys cached in the aliases file if the signer cannot be
+    /// reached.
+    fn known(&self) -> &KnownKeys {
+        self.known.get_or_init(|| {
+            self.list_keys()
+                .or_else(|_| self.cached_keys())
+                .unwrap_or_default()
+        })
+    }
+
+    fn known_mut(&mut self) -> &mut KnownKeys {
+        self.known();
+        self.known.get_mut().expect("The keys were listed")
+    }
+
+    /// Lists the keys of the signer, and names the ones that have no alias yet.
+    fn list_keys(&self) -> Result<KnownKeys, anyhow::Error> {
+        let ListKeysResult { public_keys } = self.signer.call(SignerMethod::ListKeys)?;
+        let keys = public_keys
+            .iter()
+            .map(|key| {
+                let public_key = decode_public_key(key)?;
+                Ok((MgoAddress::from(&public_key), public_key))
+            })
+            .collect::<Result<BTreeMap<_, _>, anyhow::Error>>()?;
+
+        let mut aliases = match &self.config.aliases_path {
+            Some(path) => load_aliases(path, &keys)?,
+            None => BTreeMap::new(),
+        };
+        aliases.retain(|address, _| keys.contains_key(address));
+        // The signer may hold keys that were added to it after the aliases were saved.
+        let unnamed = keys
+            .iter()
+            .filter(|(address, _)| !aliases.contains_key(address))
+            .collect::<Vec<_>>();
+        if !unnamed.is_empty() {
+            let taken = aliases.values().map(|a| a.alias.clone()).collect();
+            let names = random_names(taken, unnamed.len());
+            for ((address, public_key), alias) in unnamed.into_iter().zip(names) {
+                aliases.insert(
+                    *address,
+                    Alias {
+                        alias,
+                        public_key_base64: public_key.encode_base64(),
+                    },
+                );
+            }
+            if let Some(path) = &self.config.aliases_path {
+                save_aliases(path, &aliases)?;
+            }
+        }
+        Ok(KnownKeys { keys, aliases })
+    }
+
+    /// The keys whose public keys were saved in the aliases file when the signer was last
+    /// reached.
+    fn cached_keys(&self) -> Result<KnownKeys, anyhow::Error> {
+        let Some(path) = &self.config.aliases_path else {
+            return Ok(KnownKeys::default());
+        };
+        let aliases = load_aliases(path, &BTreeMap::new())?;
+        let keys = aliases
+            .iter()
+            .map(|(address, alias)| {
+                let public_key = PublicKey::decode_base64(&alias.public_key_base64)
+                    .map_err(|e| anyhow!("Invalid public key in aliases file: {e}"))?;
+                Ok((*address, public_key))
+            })
+            .collect::<Result<_, anyhow::Error>>()?;
+        Ok(KnownKeys { keys, aliases })
+    }
+
+    /// Asks the signer for the public key of `address`.
+    pub fn get_public_key(&self, address: &MgoAddress) -> Result<PublicKey, anyhow::Error> {
+        let GetPublicKeyResult { public_key } = self
+            .signer
+            .call(SignerMethod::GetPublicKey { address: *address })?;
+        let public_key = decode_public_key(&public_key)?;
+        ensure!(
+            MgoAddress::from(&public_key) == *address,
+            "The external signer returned a public key that does not belong to address {address}"
+        );
+        Ok(public_key)
+    }
+
+    fn sign_intent_message<T>(
+        &self,
+        address: &MgoAddress,
+        intent_msg: &IntentMessage<T>,
+    ) -> Result<Signature, anyhow::Error>
+    where
+        T: Serialize,
+    {
+        let public_key = match self.known().keys.get(address) {
+            Some(public_key) => public_key.clone(),
+            // The key may have been added to the signer after the keystore was opened.
+            None => self.get_public_key(address)?,
+        };
+        let SignResult { signature } = self.signer.call(SignerMethod::Sign {
+            address: *address,
+            intent_message: Base64::encode(bcs::to_bytes(intent_msg)?),
+        })?;
+        signature
+            .verify_secure(intent_msg, *address, public_key.scheme())
+            .map_err(|e| anyhow!("The external signer returned an invalid signature: {e}"))?;
+        Ok(signature)
+    }
+}
+
+/// The connection to a signer, which is used by one request at a time.
+struct SignerConnection {
+    transport: SignerTransport,
+    timeout: Duration,
+    process: Mutex<Option<SignerProcess>>,
+    next_id: AtomicU64,
+}
+
+/// A signer process, whose standard input and output are written and read on their own threads
+/// so that a signer that stops answering cannot block the keystore past its timeout.
+struct SignerProcess {
+    child: Child,
+    requests: Sender<String>,
+    responses: Receiver<std::io::Result<String>>,
+}
+
+impl Drop for SignerProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl SignerConnection {
+    fn new(transport: SignerTransport, timeout: Duration) -> Self {
+        Self {
+            transport,
+            timeout,
+            process: Mutex::new(None),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    fn call<R: DeserializeOwned>(&self, method: SignerMethod) -> Result<R, anyhow::Error> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = serde_json::to_string(&SignerRequest { id, method })?;
+        let response = match &self.transport {
+            SignerTransport::Command { command, args } => {
+                let mut process = self.process.lock().unwrap();
+                if process.is_none() {
+                    *process = Some(spawn_signer(command, args)?);
+                }
+                let signer = process.as_ref().unwrap();
+                let response = signer.exchange(&request, self.timeout);
+                if response.is_err() {
+                    // Start the signer again for the next request.
+                    *process = None;
+                }
+                response
+            }
+            SignerTransport::Socket { path } => connect_socket(path, self.timeout)
+                .and_then(|stream| exchange(&request, &mut &stream, &mut BufReader::new(&stream))),
+        };
+        let response = response.context("Cannot reach the external signer")?;
+
+        let response: SignerResponse = serde_json::from_str(&response)
+            .with_context(|| format!("Invalid response from the external signer: {response}"))?;
+        ensure!(
+            response.id == id,
+            "The external signer answered request {} instead of request {id}",
+            response.id
+        );
+        if let Some(error) = response.error {
+            bail!("The external signer returned an error: {error}");
+        }
+        let result = response
+            .result
+            .ok_or_else(|| anyhow!("The external signer returned no result"))?;
+        serde_json::from_value(result)
+            .map_err(|e| anyhow!("Invalid result from the external signer: {e}"))
+    }
+}
+
+fn spawn_signer(command: &Path, args: &[String]) -> Result<SignerProcess, anyhow::Error> {
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Cannot start the external signer {}", command.display()))?;
+    let mut stdin = child.stdin.take().expect("The standard input is piped");
+    let stdout = BufReader::new(child.stdout.take().expect("The standard output is piped"));
+
+    // Both threads end once the process is killed, when the signer process is dropped.
+    let (requests, pending) = mpsc::channel::<String>();
+    std::thread::spawn(move || {
+        for request in pending {
+            if writeln!(stdin, "{request}")
+                .and_then(|_| stdin.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+    let (answered, responses) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in stdout.lines() {
+            if answered.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(SignerProcess {
+        child,
+        requests,
+        responses,
+    })
+}
+
+impl SignerProcess {
+    /// Sends the `request` line and waits up to `timeout` for the response line.
+    fn exchange(&self, request: &str, timeout: Duration) -> Result<String, anyhow::Error> {
+        self.requests
+            .send(request.to_string())
+            .map_err(|_| anyhow!("The external signer closed the connection"))?;
+        match self.responses.recv_timeout(timeout) {
+            Ok(response) => Ok(response?),
+            Err(RecvTimeoutError::Timeout) => bail!(
+                "The external signer did not answer within {} seconds",
+                timeout.as_secs()
+            ),
+            Err(RecvTimeoutError::Disconnected) => {
+                bail!("The external signer closed the connection")
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn connect_socket(
+    path: &Path,
+    timeout: Duration,
+) -> Result<std::os::unix::net::UnixStream, anyhow::Error> {
+    let stream = std::os::unix::net::UnixStream::connect(path)
+        .with_context(|| format!("Cannot connect to {}", path.display()))?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    Ok(stream)
+}
+
+#[cfg(not(unix))]
+fn connect_socket(_: &Path, _: Duration) -> Result<std::fs::File, anyhow::Error> {
+    bail!("Unix sockets are not supported on this platform")
+}
+
+/// Writes the `request` line and reads the response line, on a stream whose reads and writes
+/// time out.
+fn exchange(
+    request: &str,
+    writer: &mut impl Write,
+    reader: &mut impl BufRead,
+) -> Result<String, anyhow::Error> {
+    writeln!(writer, "{request}")?;
+    writer.flush()?;
+    let mut response = String::new();
+    ensure!(
+        reader.read_line(&mut response)? > 0,
+        "The external signer closed the connection"
+    );
+    Ok(response)
+}
+
+fn decode_public_key(key: &str) -> Result<PublicKey, anyhow::Error> {
+    PublicKey::decode_base64(key)
+        .map_err(|e| anyhow!("Invalid public key {key} from the external signer: {e}"))
+}
+
+/// A signer that holds its keys in memory and signs whatever it is asked to. It is the reference
+/// implementation of the signer side of the protocol, and is meant for tests.
+pub struct MockSigner {
+    keys: BTreeMap<MgoAddress, MgoKeyPair>,
+}
+
+impl MockSigner {
+    pub fn new(keys: Vec<MgoKeyPair>) -> Self {
+        Self {
+            keys: keys
+                .into_iter()
+                .map(|key| ((&key.public()).into(), key))
+                .collect(),
+        }
+    }
+
+    pub fn handle(&self, request: SignerRequest) -> SignerResponse {
+        match self.result(request.method) {
+            Ok(result) => SignerResponse {
+                id: request.id,
+                result: Some(result),
+                error: None,
+            },
+            Err(e) => SignerResponse {
+                id: request.id,
+                result: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+
+    /// Answers the requests read from `reader` on `writer`, until `reader` is closed.
+    pub fn serve(&self, reader: impl BufRead, mut writer: impl Write) -> Result<(), anyhow::Error> {
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str::<SignerRequest>(&line) {
+                Ok(request) => self.handle(request),
+                Err(e) => SignerResponse {
+                    id: 0,
+                    result: None,
+                    error: Some(format!("Invalid request: {e}")),
+                },
+            };
+            writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Answers the requests of every connection made to `listener`.
+    #[cfg(unix)]
+    pub fn serve_socket(
+        &self,
+        listener: std::os::unix::net::UnixListener,
+    ) -> Result<(), anyhow::Error> {
+        for stream in listener.incoming() {
+            let stream = stream?;
+            // A client that goes away does not stop the signer.
+            let _ = self.serve(BufReader::new(&stream), &stream);
+        }
+        Ok(())
+    }
+
+    fn result(&self, method: SignerMethod) -> Result<serde_json::Value, anyhow::Error> {
+        Ok(match method {
+            SignerMethod::ListKeys => serde_json::to_value(ListKeysResult {
+                public_keys: self
+                    .keys
+                    .values()
+                    .map(|key| key.public().encode_base64())
+                    .collect(),
+            })?,
+            SignerMethod::GetPublicKey { address } => serde_json::to_value(GetPublicKeyResult {
+                public_key: self.key(&address)?.public().encode_base64(),
+            })?,
+            SignerMethod::Sign {
+                address,
+                intent_message,
+            } => {
+                let key = self.key(&address)?;
+                let intent_message = Base64::decode(&intent_message)
+                    .map_err(|e| anyhow!("Invalid intent message: {e}"))?;
+                let mut hasher = DefaultHash::default();
+                hasher.update(&intent_message);
+                serde_json::to_value(SignResult {
+                    signature: Signature::new_hashed(&hasher.finalize().digest, key),
+                })?
+            }
+        })
+    }
+
+    fn key(&self, address: &MgoAddress) -> Result<&MgoKeyPair, anyhow::Error> {
+        self.keys
+            .get(address)
+            .ok_or_else(|| anyhow!("Unknown address {address}"))
+    }
+}
@@ -0,0 +1,364 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::key_derive::derive_key_pair_from_path;
+use anyhow::{anyhow, bail, ensure};
+use bip32::DerivationPath;
+use bip39::{Language, Mnemonic, Seed};
+use fastcrypto::encoding::{Base64, Encoding};
+use fastcrypto::hash::HashFunction;
+use mgo_types::crypto::{DefaultHash, EncodeDecodeBase64, MgoKeyPair, PublicKey, SignatureScheme};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+const KEY_SHARE_VERSION: u8 = 1;
+const CHECKSUM_LENGTH: usize = 4;
+
+/// What a set of key shares splits.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SharedSecret {
+    /// The private key `flag || privkey` of a keypair.
+    PrivateKey,
+    /// The entropy of a mnemonic phrase, from which the key is derived with the scheme of its
+    /// public key and the derivation path, or the default path of the scheme.
+    Mnemonic { derivation_path: Option<String> },
+}
+
+/// One of the shares that a private key or mnemonic is split into with Shamir's secret sharing
+/// over GF(256). Any `threshold` shares of a secret recombine into it, and fewer reveal nothing
+/// about it. Every share carries the public key of the key it recombines into, so that
+/// recombination can be verified, and so that a share can stand in for the public key.
+///
+/// A share is written as the Base64 encoding of its BCS bytes followed by a 4-byte checksum.
+#[derive(Clone)]
+pub struct KeyShare {
+    /// Random, and the same for every share of a secret, so that shares of different secrets
+    /// are not combined.
+    pub identifier: u16,
+    pub threshold: u8,
+    /// The x coordinate of the share, from 1 to the number of shares.
+    pub index: u8,
+    pub secret: SharedSecret,
+    pub public_key: PublicKey,
+    value: Zeroizing<Vec<u8>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncodedKeyShare {
+    version: u8,
+    identifier: u16,
+    threshold: u8,
+    index: u8,
+    secret: SharedSecret,
+    public_key: String,
+    value: Vec<u8>,
+}
+
+impl KeyShare {
+    pub fn encode(&self) -> String {
+        let encoded = EncodedKeyShare {
+            version: KEY_SHARE_VERSION,
+            identifier: self.identifier,
+            threshold: self.threshold,
+            index: self.index,
+            secret: self.secret.clone(),
+            public_key: self.public_key.encode_base64(),
+            value: self.value.to_vec(),
+        };
+        let mut bytes = bcs::to_bytes(&encoded).expect("Serialization should not fail");
+        bytes.extend_from_slice(&checksum(&bytes));
+        Base64::encode(bytes)
+    }
+
+    pub fn decode(value: &str) -> Result<Self, anyhow::Error> {
+        let bytes = Base64::decode(value.trim()).map_err(|e| anyhow!("Invalid key share: {e}"))?;
+        ensure!(bytes.len() > CHECKSUM_LENGTH, "Invalid key share length");
+        let (bytes, expected) = bytes.split_at(bytes.len() - CHECKSUM_LENGTH);
+        ensure!(
+            checksum(bytes).as_slice() == expected,
+            "Invalid key share checksum, the share may have been mistyped"
+        );
+        let encoded: EncodedKeyShare =
+            bcs::from_bytes(bytes).map_err(|e| anyhow!("Invalid key share: {e}"))?;
+        let value = Zeroizing::new(encoded.value);
+        ensure!(
+            encoded.version == KEY_SHARE_VERSION,
+            "Unsupported key share version {}",
+            encoded.version
+        );
+        ensure!(
+            encoded.index > 0 && encoded.threshold >= 2,
+            "Invalid key share index or threshold"
+        );
+        Ok(Self {
+            identifier: encoded.identifier,
+            threshold: encoded.threshold,
+            index: encoded.index,
+            secret: encoded.secret,
+            public_key: PublicKey::decode_base64(&encoded.public_key)
+                .map_err(|e| anyhow!("Invalid public key in key share: {e}"))?,
+            value,
+        })
+    }
+}
+
+impl Debug for KeyShare {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyShare")
+            .field("identifier", &self.identifier)
+            .field("threshold", &self.threshold)
+            .field("index", &self.index)
+            .field("secret", &self.secret)
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FromStr for KeyShare {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::decode(s)
+    }
+}
+
+impl Display for KeyShare {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.encode())
+    }
+}
+
+/// Splits the private key of `keypair` into `count` shares, any `threshold` of which recombine
+/// into it.
+pub fn split_private_key(
+    keypair: &MgoKeyPair,
+    threshold: u8,
+    count: u8,
+) -> Result<Vec<KeyShare>, anyhow::Error> {
+    split(
+        &Zeroizing::new(keypair.to_bytes()),
+        SharedSecret::PrivateKey,
+        keypair.public(),
+        threshold,
+        count,
+    )
+}
+
+/// Splits a mnemonic phrase into `count` shares, any `threshold` of which recombine into it. The
+/// key scheme and derivation path are kept with the shares, to derive the key after recombining.
+pub fn split_mnemonic(
+    phrase: &str,
+    key_scheme: SignatureScheme,
+    derivation_path: Option<DerivationPath>,
+    threshold: u8,
+    count: u8,
+) -> Result<Vec<KeyShare>, anyhow::Error> {
+    let mnemonic = Mnemonic::from_phrase(phrase, Language::English)
+        .map_err(|e| anyhow!("Invalid mnemonic phrase: {:?}", e))?;
+    let (_, keypair) = derive_key_pair_from_path(
+        Seed::new(&mnemonic, "").as_bytes(),
+        derivation_path.clone(),
+        &key_scheme,
+    )
+    .map_err(|e| anyhow!("Cannot derive key from mnemonic phrase: {e}"))?;
+    split(
+        &Zeroizing::new(mnemonic.entropy().to_vec()),
+        SharedSecret::Mnemonic {
+            derivation_path: derivation_path.map(|path| path.to_string()),
+        },
+        keypair.public(),
+        threshold,
+        count,
+    )
+}
+
+/// Recombines key shares into the keypair they were split from, and checks that it is the key
+/// whose public key the shares carry. Shares beyond the threshold must agree with the ones the
+/// key is recombined from.
+pub fn combine_shares(shares: &[KeyShare]) -> Result<MgoKeyPair, anyhow::Error> {
+    let Some(first) = shares.first() else {
+        bail!("No key shares to combine");
+    };
+    for share in shares {
+        ensure!(
+            share.identifier == first.identifier
+                && share.threshold == first.threshold
+                && share.secret == first.secret
+                && share.public_key == first.public_key,
+            "Key share {} is not a share of the same key as key share {}",
+            share.index,
+            first.index
+        );
+    }
+    let indices = shares.iter().map(|s| s.index).collect::<HashSet<_>>();
+    ensure!(
+        indices.len() == shares.len(),
+        "Each key share can only be given once"
+    );
+    ensure!(
+        shares.len() >= first.threshold as usize,
+        "{} key shares are needed to recombine the key, but {} were given",
+        first.threshold,
+        shares.len()
+    );
+
+    let (points, extra) = shares.split_at(first.threshold as usize);
+    for share in extra {
+        ensure!(
+            interpolate(points, share.index)?.as_slice() == share.value.as_slice(),
+            "Key share {} does not agree with the other key shares, one of them is corrupted",
+            share.index
+        );
+    }
+    let secret = interpolate(points, 0)?;
+    let keypair = match &first.secret {
+        SharedSecret::PrivateKey => MgoKeyPair::from_bytes(&secret)
+            .map_err(|_| anyhow!("The key shares do not recombine into a valid key"))?,
+        SharedSecret::Mnemonic { derivation_path } => {
+            let mnemonic = Mnemonic::from_entropy(&secret, Language::English)
+                .map_err(|_| anyhow!("The key shares do not recombine into a valid mnemonic"))?;
+            let derivation_path = derivation_path
+                .as_deref()
+                .map(DerivationPath::from_str)
+                .transpose()
+                .map_err(|e| anyhow!("Invalid derivation path in key share: {e}"))?;
+            derive_key_pair_from_path(
+                Seed::new(&mnemonic, "").as_bytes(),
+                derivation_path,
+                &first.public_key.scheme(),
+            )
+            .map_err(|e| anyhow!("Cannot derive key from the recombined mnemonic: {e}"))?
+            .1
+        }
+    };
+    ensure!(
+        keypair.public() == first.public_key,
+        "The key shares recombine into a different key than the one they were split from"
+    );
+    Ok(keypair)
+}
+
+fn split(
+    secret: &[u8],
+    kind: SharedSecret,
+    public_key: PublicKey,
+    threshold: u8,
+    count: u8,
+) -> Result<Vec<KeyShare>, anyhow::Error> {
+    ensure!(
+        threshold >= 2 && threshold <= count,
+        "The threshold must be between 2 and the number of shares {count}, a single share \
+         would hold the secret itself"
+    );
+    let mut rng = rand::thread_rng();
+    let identifier = rng.next_u32() as u16;
+
+    // Each byte of the secret is the constant term of its own random polynomial of degree
+    // threshold - 1, and share x holds the value of every polynomial at x.
+    let mut coefficients = Zeroizing::new(vec![0u8; secret.len() * (threshold as usize - 1)]);
+    rng.fill_bytes(&mut coefficients);
+    Ok((1..=count)
+        .map(|index| {
+            let value = secret
+                .iter()
+                .enumerate()
+                .map(|(i, byte)| {
+                    let higher =
+                        &coefficients[i * (threshold as usize - 1)..][..threshold as usize - 1];
+                    // Horner's method, from the highest coefficient down to the secret.
+                    higher
+                        .iter()
+                        .rev()
+                        .chain(std::iter::once(byte))
+                        .fold(0, |acc, c| gf_mul(acc, index) ^ c)
+                })
+                .collect();
+            KeyShare {
+                identifier,
+                threshold,
+                index,
+                secret: kind.clone(),
+                public_key: public_key.clone(),
+                value: Zeroizing::new(value),
+            }
+        })
+        .collect())
+}
+
+/// The value at `x` of the polynomials that pass through the shares, by Lagrange interpolation.
+/// The secret is the value at 0.
+fn interpolate(shares: &[KeyShare], x: u8) -> Result<Zeroizing<Vec<u8>>, anyhow::Error> {
+    let length = shares[0].value.len();
+    ensure!(
+        shares.iter().all(|s| s.value.len() == length),
+        "The key shares have different lengths"
+    );
+    let basis = shares
+        .iter()
+        .map(|share| {
+            shares
+                .iter()
+                .filter(|other| other.index != share.index)
+                .fold(1, |acc, other| {
+                    gf_mul(
+                        acc,
+                        gf_mul(x ^ other.index, gf_inv(other.index ^ share.index)),
+                    )
+                })
+        })
+        .collect::<Vec<_>>();
+    Ok(Zeroizing::new(
+        (0..length)
+            .map(|i| {
+                shares
+                    .iter()
+                    .zip(&basis)
+                    .fold(0, |acc, (share, b)| acc ^ gf_mul(share.value[i], *b))
+            })
+            .collect(),
+    ))
+}
+
+/// Multiplication in GF(256) with the AES polynomial x^8 + x^4 + x^3 + x + 1.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// The multiplicative inverse in GF(256), as a^254.
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1;
+    let mut base = a;
+    let mut exponent = 254u8;
+    while exponent != 0 {
+        if exponent & 1 != 0 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_LENGTH] {
+    let mut hasher = DefaultHash::default();
+    hasher.update(bytes);
+    let digest = hasher.finalize().digest;
+    let mut checksum = [0; CHECKSUM_LENGTH];
+    checksum.copy_from_slice(&digest[..CHECKSUM_LENGTH]);
+    checksum
+}
@@ -1,6 +1,8 @@
 // Copyright (c) MangoNet Labs Ltd.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::encrypted_keystore::{EncryptedFileKeystore, KdfParams};
+use crate::external_keystore::{ExternalKeystore, ExternalSignerConfig};
 use crate::key_derive::{derive_key_pair_from_path, generate_new_key};
 use crate::random_names::{random_name, random_names};
 use anyhow::{anyhow, bail, ensure, Context};
@@ -23,11 +25,13 @@ use mgo_types::crypto::{
     enum_dispatch, EncodeDecodeBase64, PublicKey, Signature, SignatureScheme, MgoKeyPair,
 };
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize)]
 #[enum_dispatch(AccountKeystore)]
 pub enum Keystore {
     File(FileBasedKeystore),
     InMem(InMemKeystore),
+    Encrypted(EncryptedFileKeystore),
+    External(ExternalKeystore),
 }
 #[enum_dispatch]
 pub trait AccountKeystore: Send + Sync {
@@ -136,6 +140,47 @@ pub trait AccountKeystore: Send + Sync {
     }
 }
 
+impl Keystore {
+    /// Loads the keystore at `path`, whether its keys are stored in plaintext or encrypted.
+    pub fn load(path: &PathBuf) -> Result<Self, anyhow::Error> {
+        if EncryptedFileKeystore::is_encrypted(path) {
+            Ok(Keystore::Encrypted(EncryptedFileKeystore::new(path)?))
+        } else {
+            Ok(Keystore::File(FileBasedKeystore::new(path)?))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Keystore {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        /// A `File` keystore may have been encrypted in place since the config was written, so
+        /// it is loaded as whichever kind of keystore its file now holds.
+        #[derive(Deserialize)]
+        enum KeystoreConfig {
+            File(PathBuf),
+            InMem(InMemKeystore),
+            Encrypted(PathBuf),
+            External(ExternalSignerConfig),
+        }
+
+        match KeystoreConfig::deserialize(deserializer)? {
+            KeystoreConfig::File(path) => Keystore::load(&path).map_err(D::Error::custom),
+            KeystoreConfig::InMem(keystore) => Ok(Keystore::InMem(keystore)),
+            KeystoreConfig::Encrypted(path) => EncryptedFileKeystore::new(&path)
+                .map(Keystore::Encrypted)
+                .map_err(D::Error::custom),
+            KeystoreConfig::External(config) => {
+                Ok(Keystore::External(ExternalKeystore::open(config)))
+            }
+        }
+    }
+}
+
 impl Display for Keystore {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut writer = String::new();
@@ -149,6 +194,16 @@ impl Display for Keystore {
                 writeln!(writer, "Keystore Type : InMem")?;
                 write!(f, "{}", writer)
             }
+            Keystore::Encrypted(encrypted) => {
+                writeln!(writer, "Keystore Type : Encrypted")?;
+                write!(writer, "Keystore Path : {:?}", encrypted.path())?;
+                write!(f, "{}", writer)
+            }
+            Keystore::External(external) => {
+                writeln!(writer, "Keystore Type : External")?;
+                write!(writer, "Signer        : {:?}", external.config().transport)?;
+                write!(f, "{}", writer)
+            }
         }
     }
 }
@@ -329,67 +384,11 @@ impl FileBasedKeystore {
             BTreeMap::new()
         };
 
-        // check aliases
-        let mut aliases_path = path.clone();
-        aliases_path.set_extension("aliases");
-
-        let aliases = if aliases_path.exists() {
-            let reader = BufReader::new(File::open(&aliases_path).with_context(|| {
-                format!(
-                    "Cannot open aliases file in keystore: {}",
-                    aliases_path.display()
-                )
-            })?);
-
-            let aliases: Vec<Alias> = serde_json::from_reader(reader).with_context(|| {
-                format!(
-                    "Cannot deserialize aliases file in keystore: {}",
-                    aliases_path.display(),
-                )
-            })?;
-
-            aliases
-                .into_iter()
-                .map(|alias| {
-                    let key = PublicKey::decode_base64(&alias.public_key_base64);
-                    key.map(|k| (Into::<MgoAddress>::into(&k), alias))
-                })
-                .collect::<Result<BTreeMap<_, _>, _>>()
-                .map_err(|e| {
-                    anyhow!(
-                        "Invalid aliases file in keystore: {}. {}",
-                        aliases_path.display(),
-                        e
-                    )
-                })?
-        } else if keys.is_empty() {
-            BTreeMap::new()
-        } else {
-            let names: Vec<String> = random_names(HashSet::new(), keys.len());
-            let aliases = keys
-                .iter()
-                .zip(names)
-                .map(|((mgo_address, skp), alias)| {
-                    let public_key_base64 = skp.public().encode_base64();
-                    (
-                        *mgo_address,
-                        Alias {
-                            alias,
-                            public_key_base64,
-                        },
-                    )
-                })
-                .collect::<BTreeMap<_, _>>();
-            let aliases_store = serde_json::to_string_pretty(&aliases.values().collect::<Vec<_>>())
-                .with_context(|| {
-                    format!(
-                        "Cannot serialize aliases to file in keystore: {}",
-                        aliases_path.display()
-                    )
-                })?;
-            fs::write(aliases_path, aliases_store)?;
-            aliases
-        };
+        let public_keys = keys
+            .iter()
+            .map(|(address, skp)| (*address, skp.public()))
+            .collect();
+        let aliases = load_aliases(path, &public_keys)?;
 
         Ok(Self {
             keys,
@@ -398,24 +397,17 @@ impl FileBasedKeystore {
         })
     }
 
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
     pub fn set_path(&mut self, path: &Path) {
         self.path = Some(path.to_path_buf());
     }
 
     pub fn save_aliases(&self) -> Result<(), anyhow::Error> {
         if let Some(path) = &self.path {
-            let aliases_store =
-                serde_json::to_string_pretty(&self.aliases.values().collect::<Vec<_>>())
-                    .with_context(|| {
-                        format!(
-                            "Cannot serialize aliases to file in keystore: {}",
-                            path.display()
-                        )
-                    })?;
-
-            let mut aliases_path = path.clone();
-            aliases_path.set_extension("aliases");
-            fs::write(aliases_path, aliases_store)?
+            save_aliases(path, &self.aliases)?;
         }
         Ok(())
     }
@@ -450,6 +442,19 @@ impl FileBasedKeystore {
     pub fn key_pairs(&self) -> Vec<&MgoKeyPair> {
         self.keys.values().collect()
     }
+
+    /// Encrypts the keys with `password`, replacing the plaintext keystore file with an encrypted
+    /// one. The aliases are kept as they are.
+    pub fn encrypt(
+        self,
+        password: &str,
+        kdf: KdfParams,
+    ) -> Result<EncryptedFileKeystore, anyhow::Error> {
+        let path = self
+            .path
+            .ok_or_else(|| anyhow!("Cannot encrypt a keystore that is not saved to a file"))?;
+        EncryptedFileKeystore::from_keys(&path, password, kdf, self.keys, self.aliases)
+    }
 }
 
 #[derive(Default, Serialize, Deserialize)]
@@ -604,7 +609,94 @@ impl InMemKeystore {
     }
 }
 
-fn validate_alias(alias: &str) -> Result<String, anyhow::Error> {
+/// Reads the aliases of the keystore at `path` from its aliases file. If there is no aliases file,
+/// random aliases are generated for `public_keys` and saved.
+pub(crate) fn load_aliases(
+    path: &Path,
+    public_keys: &BTreeMap<MgoAddress, PublicKey>,
+) -> Result<BTreeMap<MgoAddress, Alias>, anyhow::Error> {
+    let mut aliases_path = path.to_path_buf();
+    aliases_path.set_extension("aliases");
+
+    let aliases = if aliases_path.exists() {
+        let reader = BufReader::new(File::open(&aliases_path).with_context(|| {
+            format!(
+                "Cannot open aliases file in keystore: {}",
+                aliases_path.display()
+            )
+        })?);
+
+        let aliases: Vec<Alias> = serde_json::from_reader(reader).with_context(|| {
+            format!(
+                "Cannot deserialize aliases file in keystore: {}",
+                aliases_path.display(),
+            )
+        })?;
+
+        aliases
+            .into_iter()
+            .map(|alias| {
+                let key = PublicKey::decode_base64(&alias.public_key_base64);
+                key.map(|k| (Into::<MgoAddress>::into(&k), alias))
+            })
+            .collect::<Result<BTreeMap<_, _>, _>>()
+            .map_err(|e| {
+                anyhow!(
+                    "Invalid aliases file in keystore: {}. {}",
+                    aliases_path.display(),
+                    e
+                )
+            })?
+    } else if public_keys.is_empty() {
+        BTreeMap::new()
+    } else {
+        let names: Vec<String> = random_names(HashSet::new(), public_keys.len());
+        let aliases = public_keys
+            .iter()
+            .zip(names)
+            .map(|((mgo_address, public_key), alias)| {
+                let public_key_base64 = public_key.encode_base64();
+                (
+                    *mgo_address,
+                    Alias {
+                        alias,
+                        public_key_base64,
+                    },
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        let aliases_store = serde_json::to_string_pretty(&aliases.values().collect::<Vec<_>>())
+            .with_context(|| {
+                format!(
+                    "Cannot serialize aliases to file in keystore: {}",
+                    aliases_path.display()
+                )
+            })?;
+        fs::write(aliases_path, aliases_store)?;
+        aliases
+    };
+    Ok(aliases)
+}
+
+pub(crate) fn save_aliases(
+    path: &Path,
+    aliases: &BTreeMap<MgoAddress, Alias>,
+) -> Result<(), anyhow::Error> {
+    let aliases_store = serde_json::to_string_pretty(&aliases.values().collect::<Vec<_>>())
+        .with_context(|| {
+            format!(
+                "Cannot serialize aliases to file in keystore: {}",
+                path.display()
+            )
+        })?;
+
+    let mut aliases_path = path.to_path_buf();
+    aliases_path.set_extension("aliases");
+    fs::write(aliases_path, aliases_store)?;
+    Ok(())
+}
+
+pub(crate) fn validate_alias(alias: &str) -> Result<String, anyhow::Error> {
     let re = Regex::new(r"^[A-Za-z][A-Za-z0-9-_\.]*$")
         .map_err(|_| anyhow!("Cannot build the regex needed to validate the alias naming"))?;
     let alias = alias.trim();
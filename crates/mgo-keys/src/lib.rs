@@ -1,7 +1,10 @@
 // Copyright (c) MangoNet Labs Ltd.
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod encrypted_keystore;
+pub mod external_keystore;
 pub mod key_derive;
+pub mod key_shares;
 pub mod keypair_file;
 pub mod keystore;
 pub mod random_names;
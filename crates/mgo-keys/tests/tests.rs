@@ -3,17 +3,24 @@
 
 use std::fs;
 use std::str::FromStr;
+use std::time::Duration;
 
 use fastcrypto::hash::HashFunction;
 use fastcrypto::traits::EncodeDecodeBase64;
+use mgo_keys::encrypted_keystore::{EncryptedFileKeystore, KdfParams, MGO_KEYSTORE_SESSION};
+use mgo_keys::external_keystore::{
+    ExternalKeystore, ExternalSignerConfig, MockSigner, SignerTransport,
+};
 use mgo_keys::key_derive::generate_new_key;
+use mgo_keys::key_shares::{combine_shares, split_mnemonic, split_private_key, KeyShare};
+use shared_crypto::intent::{Intent, IntentMessage, PersonalMessage};
 use tempfile::TempDir;
 
 use mgo_keys::keystore::{AccountKeystore, FileBasedKeystore, InMemKeystore, Keystore};
 use mgo_types::crypto::{DefaultHash, SignatureScheme, MgoSignatureInner};
 use mgo_types::{
     base_types::{MgoAddress, MGO_ADDRESS_LENGTH},
-    crypto::Ed25519MgoSignature,
+    crypto::{Ed25519MgoSignature, MgoSignature},
 };
 
 #[test]
@@ -266,3 +273,361 @@ fn get_alias_by_address_test() {
     let address = generate_new_key(SignatureScheme::ED25519, None, None).unwrap();
     assert!(keystore.get_alias_by_address(&address.0).is_err())
 }
+
+#[test]
+fn encrypted_keystore_test() {
+    let temp_dir = TempDir::new().unwrap();
+    let keystore_path = temp_dir.path().join("mgo.keystore");
+    let keystore =
+        EncryptedFileKeystore::create(&keystore_path, "password", KdfParams::insecure_for_tests())
+            .unwrap();
+    let mut keystore = Keystore::from(keystore);
+    let (address, _, _) = keystore
+        .generate_and_add_new_key(
+            SignatureScheme::ED25519,
+            Some("my_alias".into()),
+            None,
+            None,
+        )
+        .unwrap();
+    let exported = keystore.get_key(&address).unwrap().encode_base64();
+    assert!(!fs::read_to_string(&keystore_path)
+        .unwrap()
+        .contains(&exported));
+
+    // The public keys and aliases are available without the password.
+    let mut keystore = EncryptedFileKeystore::new(&keystore_path).unwrap();
+    assert!(!keystore.is_unlocked());
+    assert_eq!(vec![address], keystore.addresses());
+    assert_eq!("my_alias", keystore.get_alias_by_address(&address).unwrap());
+
+    let err = keystore.unlock("wrong password", None).unwrap_err();
+    assert!(err.to_string().contains("Incorrect password"));
+    assert!(!keystore.is_unlocked());
+
+    keystore.unlock("password", None).unwrap();
+    assert_eq!(
+        exported,
+        keystore.get_key(&address).unwrap().encode_base64()
+    );
+    let msg = PersonalMessage {
+        message: b"hello".to_vec(),
+    };
+    keystore
+        .sign_secure(&address, &msg, Intent::personal_message())
+        .unwrap();
+}
+
+#[test]
+fn encrypt_file_keystore_test() {
+    let temp_dir = TempDir::new().unwrap();
+    let keystore_path = temp_dir.path().join("mgo.keystore");
+    let mut keystore = FileBasedKeystore::new(&keystore_path).unwrap();
+    let (address, _, _) = keystore
+        .generate_and_add_new_key(
+            SignatureScheme::ED25519,
+            Some("my_alias".into()),
+            None,
+            None,
+        )
+        .unwrap();
+    let exported = keystore.get_key(&address).unwrap().encode_base64();
+
+    keystore
+        .encrypt("password", KdfParams::insecure_for_tests())
+        .unwrap();
+    assert!(EncryptedFileKeystore::is_encrypted(&keystore_path));
+    assert!(FileBasedKeystore::new(&keystore_path).is_err());
+
+    let Keystore::Encrypted(mut keystore) = Keystore::load(&keystore_path).unwrap() else {
+        panic!("The keystore was not loaded as an encrypted keystore");
+    };
+    assert_eq!("my_alias", keystore.get_alias_by_address(&address).unwrap());
+    keystore.unlock("password", None).unwrap();
+    assert_eq!(
+        exported,
+        keystore.get_key(&address).unwrap().encode_base64()
+    );
+
+    // A config written before the keystore was encrypted still refers to it as a file.
+    let config = serde_json::json!({ "File": keystore_path });
+    let keystore: Keystore = serde_json::from_value(config).unwrap();
+    assert!(matches!(keystore, Keystore::Encrypted(_)));
+    assert_eq!(
+        serde_json::json!({ "Encrypted": keystore_path }),
+        serde_json::to_value(&keystore).unwrap()
+    );
+}
+
+#[test]
+fn change_keystore_password_test() {
+    let temp_dir = TempDir::new().unwrap();
+    let keystore_path = temp_dir.path().join("mgo.keystore");
+    let keystore =
+        EncryptedFileKeystore::create(&keystore_path, "password", KdfParams::insecure_for_tests())
+            .unwrap();
+    let mut keystore = Keystore::from(keystore);
+    let (address, _, _) = keystore
+        .generate_and_add_new_key(SignatureScheme::ED25519, None, None, None)
+        .unwrap();
+    let exported = keystore.get_key(&address).unwrap().encode_base64();
+
+    let mut keystore = EncryptedFileKeystore::new(&keystore_path).unwrap();
+    assert!(keystore
+        .change_password("wrong password", "new password")
+        .is_err());
+    keystore
+        .change_password("password", "new password")
+        .unwrap();
+
+    let mut keystore = EncryptedFileKeystore::new(&keystore_path).unwrap();
+    assert!(keystore.unlock("password", None).is_err());
+    keystore.unlock("new password", None).unwrap();
+    assert_eq!(
+        exported,
+        keystore.get_key(&address).unwrap().encode_base64()
+    );
+}
+
+#[test]
+fn keystore_unlock_session_test() {
+    let temp_dir = TempDir::new().unwrap();
+    let keystore_path = temp_dir.path().join("mgo.keystore");
+    let session_path = temp_dir.path().join("mgo.session");
+    let keystore =
+        EncryptedFileKeystore::create(&keystore_path, "password", KdfParams::insecure_for_tests())
+            .unwrap();
+    let mut keystore = Keystore::from(keystore);
+    let (address, _, _) = keystore
+        .generate_and_add_new_key(SignatureScheme::ED25519, None, None, None)
+        .unwrap();
+
+    let mut keystore = EncryptedFileKeystore::new(&keystore_path).unwrap();
+    let session = keystore
+        .unlock("password", Some(Duration::from_secs(600)))
+        .unwrap()
+        .unwrap();
+    assert!(session_path.exists());
+    // The session file only holds the keystore's key encrypted with the token.
+    assert!(!fs::read_to_string(&session_path)
+        .unwrap()
+        .contains(session.token.as_str()));
+
+    // Without the token, the session cannot be used.
+    std::env::remove_var(MGO_KEYSTORE_SESSION);
+    let keystore = EncryptedFileKeystore::new(&keystore_path).unwrap();
+    assert!(keystore.get_key(&address).is_err());
+    assert!(!keystore.is_unlocked());
+
+    // Another process given the token can use the keystore without the password until the
+    // session ends.
+    std::env::set_var(MGO_KEYSTORE_SESSION, session.token.as_str());
+    let keystore = EncryptedFileKeystore::new(&keystore_path).unwrap();
+    assert!(!keystore.is_unlocked());
+    keystore.get_key(&address).unwrap();
+    assert!(keystore.is_unlocked());
+
+    let mut keystore = EncryptedFileKeystore::new(&keystore_path).unwrap();
+    keystore.lock().unwrap();
+    assert!(!session_path.exists());
+    std::env::remove_var(MGO_KEYSTORE_SESSION);
+}
+
+#[test]
+fn keystore_weak_kdf_test() {
+    let temp_dir = TempDir::new().unwrap();
+    let keystore_path = temp_dir.path().join("mgo.keystore");
+    let weak = KdfParams {
+        log_n: 4,
+        r: 8,
+        p: 1,
+    };
+    let err = EncryptedFileKeystore::create(&keystore_path, "password", weak)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("too weak"));
+
+    // A keystore file edited to use cheaper parameters is rejected when it is loaded.
+    EncryptedFileKeystore::create(&keystore_path, "password", KdfParams::insecure_for_tests())
+        .unwrap();
+    let mut file: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&keystore_path).unwrap()).unwrap();
+    file["kdf"]["log_n"] = 4.into();
+    fs::write(&keystore_path, file.to_string()).unwrap();
+    let err = EncryptedFileKeystore::new(&keystore_path).err().unwrap();
+    assert!(format!("{err:#}").contains("too weak"));
+}
+
+#[cfg(unix)]
+#[test]
+fn external_keystore_socket_test() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("signer.sock");
+    let (address, keypair, _, _) = generate_new_key(SignatureScheme::ED25519, None, None).unwrap();
+    let public_key = keypair.public();
+    let listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+    std::thread::spawn(move || MockSigner::new(vec![keypair]).serve_socket(listener));
+
+    let config = ExternalSignerConfig {
+        transport: SignerTransport::Socket { path: socket_path },
+        aliases_path: Some(temp_dir.path().join("signer.aliases")),
+        timeout_secs: None,
+    };
+    let mut keystore = Keystore::from(ExternalKeystore::new(config.clone()).unwrap());
+    assert_eq!(vec![public_key], keystore.keys());
+    let alias = keystore.get_alias_by_address(&address).unwrap();
+
+    let msg = PersonalMessage {
+        message: b"hello".to_vec(),
+    };
+    let signature = keystore
+        .sign_secure(&address, &msg, Intent::personal_message())
+        .unwrap();
+    signature
+        .verify_secure(
+            &IntentMessage::new(Intent::personal_message(), &msg),
+            address,
+            SignatureScheme::ED25519,
+        )
+        .unwrap();
+
+    // The signer only signs with the keys it holds, which cannot be read or added to.
+    let err = keystore
+        .sign_secure(
+            &MgoAddress::random_for_testing_only(),
+            &msg,
+            Intent::personal_message(),
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("Unknown address"));
+    assert!(keystore.get_key(&address).is_err());
+    assert!(keystore
+        .generate_and_add_new_key(SignatureScheme::ED25519, None, None, None)
+        .is_err());
+
+    // The aliases are kept across sessions, and the keystore is saved as its config.
+    let keystore = ExternalKeystore::new(config.clone()).unwrap();
+    assert_eq!(alias, keystore.get_alias_by_address(&address).unwrap());
+    assert_eq!(
+        serde_json::json!({ "External": config }),
+        serde_json::to_value(Keystore::from(keystore)).unwrap()
+    );
+
+    // Without the signer, the config still loads, with the keys cached in the aliases file.
+    let offline = ExternalSignerConfig {
+        transport: SignerTransport::Socket {
+            path: temp_dir.path().join("missing.sock"),
+        },
+        ..config
+    };
+    let keystore: Keystore =
+        serde_json::from_value(serde_json::json!({ "External": offline })).unwrap();
+    assert_eq!(vec![address], keystore.addresses());
+    assert_eq!(alias, keystore.get_alias_by_address(&address).unwrap());
+    let err = keystore
+        .sign_secure(&address, &msg, Intent::personal_message())
+        .unwrap_err();
+    assert!(err.to_string().contains("Cannot reach the external signer"));
+}
+
+#[cfg(unix)]
+#[test]
+fn external_keystore_timeout_test() {
+    let config = ExternalSignerConfig {
+        transport: SignerTransport::Command {
+            command: "sh".into(),
+            args: vec!["-c".into(), "sleep 60".into()],
+        },
+        aliases_path: None,
+        timeout_secs: Some(1),
+    };
+    let err = ExternalKeystore::new(config).err().unwrap();
+    assert!(format!("{err:#}").contains("did not answer within 1 seconds"));
+}
+
+#[test]
+fn external_keystore_command_test() {
+    let (address, keypair, _, _) = generate_new_key(SignatureScheme::ED25519, None, None).unwrap();
+    let config = serde_json::json!({
+        "External": {
+            "transport": {
+                "command": {
+                    "command": env!("CARGO_BIN_EXE_mock_signer"),
+                    "args": [keypair.encode_base64()],
+                }
+            }
+        }
+    });
+    let keystore: Keystore = serde_json::from_value(config).unwrap();
+    assert_eq!(vec![address], keystore.addresses());
+
+    // The signer process is kept running between requests.
+    for message in [b"hello".to_vec(), b"world".to_vec()] {
+        let msg = PersonalMessage { message };
+        let signature = keystore
+            .sign_secure(&address, &msg, Intent::personal_message())
+            .unwrap();
+        signature
+            .verify_secure(
+                &IntentMessage::new(Intent::personal_message(), &msg),
+                address,
+                SignatureScheme::ED25519,
+            )
+            .unwrap();
+    }
+}
+
+#[test]
+fn key_shares_test() {
+    let (address, keypair, _, _) =
+        generate_new_key(SignatureScheme::Secp256k1, None, None).unwrap();
+    let shares = split_private_key(&keypair, 3, 5).unwrap();
+    assert_eq!(5, shares.len());
+
+    // Any three of the shares recombine into the key, in any order.
+    for indices in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+        let subset = indices.map(|i| shares[i].clone());
+        assert_eq!(keypair, combine_shares(&subset).unwrap());
+    }
+    let err = combine_shares(&shares[..2]).unwrap_err();
+    assert!(err.to_string().contains("3 key shares are needed"));
+    let err = combine_shares(&[shares[0].clone(), shares[0].clone(), shares[1].clone()]);
+    assert!(err.is_err());
+
+    // Shares beyond the threshold are checked against the others.
+    assert_eq!(keypair, combine_shares(&shares).unwrap());
+    let mut corrupted = shares[..4].to_vec();
+    corrupted[3].index = 5;
+    let err = combine_shares(&corrupted).unwrap_err();
+    assert!(err.to_string().contains("does not agree"));
+
+    // A single share would hold the key itself.
+    let err = split_private_key(&keypair, 1, 3).unwrap_err();
+    assert!(err.to_string().contains("between 2 and"));
+
+    // Shares survive their encoding, and a mistyped share is caught by its checksum.
+    let encoded = shares[3].encode();
+    let decoded = KeyShare::decode(&encoded).unwrap();
+    assert_eq!(4, decoded.index);
+    assert_eq!(address, MgoAddress::from(&decoded.public_key));
+    let mut mistyped = encoded.into_bytes();
+    mistyped[10] = if mistyped[10] == b'A' { b'B' } else { b'A' };
+    let err = KeyShare::decode(std::str::from_utf8(&mistyped).unwrap()).unwrap_err();
+    assert!(err.to_string().contains("checksum"));
+
+    // Shares of different keys do not mix.
+    let (_, other, _, _) = generate_new_key(SignatureScheme::ED25519, None, None).unwrap();
+    let other_shares = split_private_key(&other, 3, 5).unwrap();
+    let mixed = [&shares[..2], &other_shares[2..3]].concat();
+    assert!(combine_shares(&mixed).is_err());
+}
+
+#[test]
+fn mnemonic_shares_test() {
+    let (address, keypair, _, phrase) =
+        generate_new_key(SignatureScheme::ED25519, None, Some("word24".to_string())).unwrap();
+    let shares = split_mnemonic(&phrase, SignatureScheme::ED25519, None, 2, 3).unwrap();
+    let restored = combine_shares(&[shares[2].clone(), shares[1].clone()]).unwrap();
+    assert_eq!(keypair, restored);
+    assert_eq!(address, MgoAddress::from(&restored.public()));
+}
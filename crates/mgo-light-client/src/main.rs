@@ -8,14 +8,17 @@ use mgo_json_rpc_types::MgoTransactionBlockResponseOptions;
 
 use mgo_rest_api::{CheckpointData, Client};
 use mgo_types::{
-    base_types::{ObjectID, SequenceNumber},
+    base_types::{EpochId, ObjectID, SequenceNumber},
     committee::Committee,
     crypto::AuthorityQuorumSignInfo,
     digests::TransactionDigest,
-    effects::{TransactionEffects, TransactionEffectsAPI, TransactionEvents},
+    effects::{TransactionEffects, TransactionEvents},
     message_envelope::Envelope,
-    messages_checkpoint::{CertifiedCheckpointSummary, CheckpointSummary, EndOfEpochData},
+    messages_checkpoint::{
+        CertifiedCheckpointSummary, CheckpointSequenceNumber, CheckpointSummary, EndOfEpochData,
+    },
     object::{Data, Object},
+    proof::{InclusionProof, ProofTarget},
 };
 
 use mgo_config::genesis::Genesis;
@@ -335,39 +338,21 @@ fn extract_verified_effects_and_events(
     committee: &Committee,
     tid: TransactionDigest,
 ) -> anyhow::Result<(TransactionEffects, Option<TransactionEvents>)> {
-    let summary = &checkpoint.checkpoint_summary;
-
-    // Verify the checkpoint summary using the committee
-    summary.verify_with_contents(committee, Some(&checkpoint.checkpoint_contents))?;
-
-    // Check the validity of the transaction
-    let contents = &checkpoint.checkpoint_contents;
-    let (matching_tx, _) = checkpoint
-        .transactions
-        .iter()
-        .zip(contents.iter())
-        // Note that we get the digest of the effects to ensure this is
-        // indeed the correct effects that are authenticated in the contents.
-        .find(|(tx, digest)| {
-            tx.effects.execution_digests() == **digest && digest.transaction == tid
-        })
+    let proof = InclusionProof::new(checkpoint, ProofTarget::Transaction(tid))
         .ok_or(anyhow!("Transaction not found in checkpoint contents"))?;
 
-    // Check the events are all correct.
-    let events_digest = matching_tx.events.as_ref().map(|events| events.digest());
-    anyhow::ensure!(
-        events_digest.as_ref() == matching_tx.effects.events_digest(),
-        "Events digest does not match"
-    );
+    // Verifies the checkpoint summary using the committee, and that the effects and events are
+    // the ones authenticated in the contents.
+    proof.verify(committee)?;
 
     // Since we do not check objects we do not return them
-    Ok((matching_tx.effects.clone(), matching_tx.events.clone()))
+    Ok((proof.effects, proof.events))
 }
 
-async fn get_verified_effects_and_events(
+async fn get_transaction_checkpoint(
     config: &Config,
     tid: TransactionDigest,
-) -> anyhow::Result<(TransactionEffects, Option<TransactionEvents>)> {
+) -> anyhow::Result<CheckpointSequenceNumber> {
     let mgo_mainnet: Arc<mgo_sdk::MgoClient> = Arc::new(
         MgoClientBuilder::default()
             .build(config.full_node_url.as_str())
@@ -378,15 +363,18 @@ async fn get_verified_effects_and_events(
 
     // Lookup the transaction id and get the checkpoint sequence number
     let options = MgoTransactionBlockResponseOptions::new();
-    let seq = read_api
+    read_api
         .get_transaction_with_options(tid, options)
         .await?
         .checkpoint
-        .ok_or(anyhow!("Transaction not found"))?;
-
-    // Download the full checkpoint for this sequence number
-    let full_check_point = get_full_checkpoint(config, seq).await?;
+        .ok_or(anyhow!("Transaction not found"))
+}
 
+fn get_verified_committee(
+    config: &Config,
+    seq: CheckpointSequenceNumber,
+    epoch: EpochId,
+) -> anyhow::Result<Committee> {
     // Load the list of stored checkpoints
     let checkpoints_list: CheckpointsList = read_checkpoint_list(config)?;
 
@@ -403,7 +391,7 @@ async fn get_verified_effects_and_events(
 
         // Check we have the right checkpoint
         anyhow::ensure!(
-            prev_ckp.epoch().checked_add(1).unwrap() == full_check_point.checkpoint_summary.epoch(),
+            prev_ckp.epoch().checked_add(1).unwrap() == epoch,
             "Checkpoint sequence number does not match. Need to Sync."
         );
 
@@ -428,6 +416,21 @@ async fn get_verified_effects_and_events(
         Genesis::load(&genesis_path)?.committee()?
     };
 
+    Ok(committee)
+}
+
+async fn get_verified_effects_and_events(
+    config: &Config,
+    tid: TransactionDigest,
+) -> anyhow::Result<(TransactionEffects, Option<TransactionEvents>)> {
+    let seq = get_transaction_checkpoint(config, tid).await?;
+
+    // Download the full checkpoint for this sequence number
+    let full_check_point = get_full_checkpoint(config, seq).await?;
+
+    let committee =
+        get_verified_committee(config, seq, full_check_point.checkpoint_summary.epoch())?;
+
     extract_verified_effects_and_events(&full_check_point, &committee, tid)
 }
 
@@ -435,15 +438,17 @@ async fn get_verified_object(config: &Config, id: ObjectID) -> anyhow::Result<Ob
     let client: Client = Client::new(config.rest_url());
     let object = client.get_object(id).await?;
 
-    // Need to authenticate this object
-    let (effects, _) = get_verified_effects_and_events(config, object.previous_transaction).await?;
+    // Need to authenticate this object, with a proof that the transaction that wrote this
+    // object ID, version and hash is in a certified checkpoint
+    let seq = get_transaction_checkpoint(config, object.previous_transaction).await?;
+    let proof = client.get_object_proof(seq, id, object.version()).await?;
+    anyhow::ensure!(
+        proof.target == ProofTarget::Object(object.compute_object_reference()),
+        "Object not found"
+    );
 
-    // check that this object ID, version and hash is in the effects
-    effects
-        .all_changed_objects()
-        .iter()
-        .find(|object_ref| object_ref.0 == object.compute_object_reference())
-        .ok_or(anyhow!("Object not found"))?;
+    let committee = get_verified_committee(config, seq, proof.checkpoint_summary.epoch())?;
+    proof.verify(&committee)?;
 
     Ok(object)
 }
@@ -1,20 +1,29 @@
 // Copyright (c) MangoNet Labs Ltd.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::transaction_deny::parse as parse_transaction_deny_config;
 use crate::MgoNode;
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    headers::{authorization::Bearer, Authorization},
+    http::{Request, StatusCode},
+    middleware::{self, Next},
+    response::Response,
     routing::{get, post},
-    Router,
+    Router, TypedHeader,
 };
 use humantime::parse_duration;
 use serde::Deserialize;
+use std::fs;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use mgo_config::node::AuthorityStorePruningConfig;
+use mgo_types::base_types::ObjectID;
+use mgo_types::digests::TransactionDigest;
 use mgo_types::error::MgoError;
 use telemetry_subscribers::TracingHandle;
-use tracing::info;
+use tracing::{error, info};
 
 // Example commands:
 //
@@ -47,6 +56,63 @@ use tracing::info;
 // Reset tracing to the TRACE_FILTER env var.
 //
 //   $ curl -X POST 'http://127.0.0.1:1337/reset-tracing'
+//
+// View the transaction deny config in use:
+//
+//   $ curl 'http://127.0.0.1:1337/transaction-deny-config'
+//
+// The endpoints below inspect or change the runtime state of the node, and require the token in
+// the file at admin-interface-auth-token-path:
+//
+//   $ TOKEN=$(cat /path/to/admin-token)
+//
+// Replace the transaction deny config until the next reload (changes are recorded in the audit
+// log):
+//
+//   $ curl -X POST -H "Authorization: Bearer $TOKEN" 'http://127.0.0.1:1337/transaction-deny-config' --data-binary @deny.yaml
+//
+// Reload the transaction deny config from transaction-deny-config-path:
+//
+//   $ curl -X POST -H "Authorization: Bearer $TOKEN" 'http://127.0.0.1:1337/reload-transaction-deny-config'
+//
+// View the certificates pending execution or consensus in the current epoch:
+//
+//   $ curl -H "Authorization: Bearer $TOKEN" 'http://127.0.0.1:1337/pending-certificates'
+//
+// View the transactions deferred to later consensus rounds:
+//
+//   $ curl -H "Authorization: Bearer $TOKEN" 'http://127.0.0.1:1337/deferred-transactions'
+//
+// View the shared object versions assigned to up to 100 certificates, starting from a digest:
+//
+//   $ curl -H "Authorization: Bearer $TOKEN" 'http://127.0.0.1:1337/shared-object-versions?limit=100&start=<digest>'
+//
+// View the latest version of an object in the execution cache:
+//
+//   $ curl -H "Authorization: Bearer $TOKEN" 'http://127.0.0.1:1337/cached-object?object_id=0x5'
+//
+// View the effects of an executed transaction in the execution cache:
+//
+//   $ curl -H "Authorization: Bearer $TOKEN" 'http://127.0.0.1:1337/cached-effects?digest=<digest>'
+//
+// Pause and resume the execution of synced checkpoints:
+//
+//   $ curl -X POST -H "Authorization: Bearer $TOKEN" 'http://127.0.0.1:1337/pause-checkpoint-execution'
+//   $ curl -X POST -H "Authorization: Bearer $TOKEN" 'http://127.0.0.1:1337/resume-checkpoint-execution'
+//
+// Take a checkpoint of the dbs, written to the manual_db_checkpoints dir of the db path:
+//
+//   $ curl -X POST -H "Authorization: Bearer $TOKEN" 'http://127.0.0.1:1337/db-checkpoint'
+//
+// Dump the input objects of a transaction executed in the current epoch as a NodeStateDump, written
+// to the state debug dump dir:
+//
+//   $ curl -X POST -H "Authorization: Bearer $TOKEN" 'http://127.0.0.1:1337/dump-transaction-state?digest=<digest>'
+//
+// View and change the pruning config (changes are lost when the node restarts):
+//
+//   $ curl -H "Authorization: Bearer $TOKEN" 'http://127.0.0.1:1337/pruning-config' > pruning.yaml
+//   $ curl -X POST -H "Authorization: Bearer $TOKEN" 'http://127.0.0.1:1337/pruning-config' --data-binary @pruning.yaml
 
 const LOGGING_ROUTE: &str = "/logging";
 const TRACING_ROUTE: &str = "/enable-tracing";
@@ -56,6 +122,20 @@ const CLEAR_BUFFER_STAKE_ROUTE: &str = "/clear-override-buffer-stake";
 const FORCE_CLOSE_EPOCH: &str = "/force-close-epoch";
 const CAPABILITIES: &str = "/capabilities";
 const NODE_CONFIG: &str = "/node-config";
+const TRANSACTION_DENY_CONFIG: &str = "/transaction-deny-config";
+const RELOAD_TRANSACTION_DENY_CONFIG: &str = "/reload-transaction-deny-config";
+const PENDING_CERTIFICATES: &str = "/pending-certificates";
+const DEFERRED_TRANSACTIONS: &str = "/deferred-transactions";
+const SHARED_OBJECT_VERSIONS: &str = "/shared-object-versions";
+const CACHED_OBJECT: &str = "/cached-object";
+const CACHED_EFFECTS: &str = "/cached-effects";
+const PAUSE_CHECKPOINT_EXECUTION: &str = "/pause-checkpoint-execution";
+const RESUME_CHECKPOINT_EXECUTION: &str = "/resume-checkpoint-execution";
+const DB_CHECKPOINT: &str = "/db-checkpoint";
+const DUMP_TRANSACTION_STATE: &str = "/dump-transaction-state";
+const PRUNING_CONFIG: &str = "/pruning-config";
+
+const DEFAULT_SHARED_OBJECT_VERSIONS_LIMIT: usize = 100;
 
 struct AppState {
     node: Arc<MgoNode>,
@@ -65,10 +145,56 @@ struct AppState {
 pub async fn run_admin_server(node: Arc<MgoNode>, port: u16, tracing_handle: TracingHandle) {
     let filter = tracing_handle.get_log().unwrap();
 
-    let app_state = AppState {
+    let auth_token = node
+        .config
+        .admin_interface_auth_token_path
+        .as_ref()
+        .and_then(|path| match fs::read_to_string(path) {
+            Ok(token) if !token.trim().is_empty() => Some(token.trim().to_string()),
+            Ok(_) => {
+                error!("Admin auth token file {} is empty", path.display());
+                None
+            }
+            Err(err) => {
+                error!(
+                    "Unable to read admin auth token file {}: {err:?}",
+                    path.display()
+                );
+                None
+            }
+        });
+
+    let app_state = Arc::new(AppState {
         node,
         tracing_handle,
-    };
+    });
+
+    let authenticated = Router::new()
+        .route(TRANSACTION_DENY_CONFIG, post(set_transaction_deny_config))
+        .route(
+            RELOAD_TRANSACTION_DENY_CONFIG,
+            post(reload_transaction_deny_config),
+        )
+        .route(PENDING_CERTIFICATES, get(pending_certificates))
+        .route(DEFERRED_TRANSACTIONS, get(deferred_transactions))
+        .route(SHARED_OBJECT_VERSIONS, get(shared_object_versions))
+        .route(CACHED_OBJECT, get(cached_object))
+        .route(CACHED_EFFECTS, get(cached_effects))
+        .route(PAUSE_CHECKPOINT_EXECUTION, post(pause_checkpoint_execution))
+        .route(
+            RESUME_CHECKPOINT_EXECUTION,
+            post(resume_checkpoint_execution),
+        )
+        .route(DB_CHECKPOINT, post(db_checkpoint))
+        .route(DUMP_TRANSACTION_STATE, post(dump_transaction_state))
+        .route(PRUNING_CONFIG, get(get_pruning_config))
+        .route(PRUNING_CONFIG, post(set_pruning_config))
+        // Token required by the endpoints that inspect or change the node's runtime state. These
+        // endpoints are disabled if not set.
+        .route_layer(middleware::from_fn_with_state(
+            auth_token,
+            require_auth_token,
+        ));
 
     let app = Router::new()
         .route(LOGGING_ROUTE, get(get_filter))
@@ -86,7 +212,9 @@ pub async fn run_admin_server(node: Arc<MgoNode>, port: u16, tracing_handle: Tra
         .route(FORCE_CLOSE_EPOCH, post(force_close_epoch))
         .route(TRACING_ROUTE, post(enable_tracing))
         .route(TRACING_RESET_ROUTE, post(reset_tracing))
-        .with_state(Arc::new(app_state));
+        .route(TRANSACTION_DENY_CONFIG, get(get_transaction_deny_config))
+        .merge(authenticated)
+        .with_state(app_state);
 
     let socket_address = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
     info!(
@@ -216,6 +344,43 @@ async fn node_config(State(state): State<Arc<AppState>>) -> (StatusCode, String)
     (StatusCode::OK, format!("{:#?}\n", node_config))
 }
 
+async fn get_transaction_deny_config(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    let config = state.node.state().transaction_deny_config();
+    match serde_yaml::to_string(config.as_ref()) {
+        Ok(config) => (StatusCode::OK, config),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+async fn set_transaction_deny_config(
+    State(state): State<Arc<AppState>>,
+    config: String,
+) -> (StatusCode, String) {
+    match parse_transaction_deny_config(&config) {
+        Ok(config) => {
+            let changes = state.node.set_transaction_deny_config(config, "admin");
+            (StatusCode::OK, describe_changes(changes))
+        }
+        Err(err) => (StatusCode::BAD_REQUEST, format!("{:#}\n", err)),
+    }
+}
+
+async fn reload_transaction_deny_config(
+    State(state): State<Arc<AppState>>,
+) -> (StatusCode, String) {
+    match state.node.reload_transaction_deny_config("admin reload") {
+        Ok(changes) => (StatusCode::OK, describe_changes(changes)),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}\n", err)),
+    }
+}
+
+fn describe_changes(changes: Vec<String>) -> String {
+    if changes.is_empty() {
+        return "transaction deny config unchanged\n".to_string();
+    }
+    changes.into_iter().map(|change| change + "\n").collect()
+}
+
 #[derive(Deserialize)]
 struct Epoch {
     epoch: u64,
@@ -288,3 +453,303 @@ async fn force_close_epoch(
         Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
     }
 }
+
+async fn require_auth_token<B>(
+    State(auth_token): State<Option<String>>,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Result<Response, (StatusCode, String)> {
+    let Some(auth_token) = auth_token else {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "admin-interface-auth-token-path is not configured\n".to_string(),
+        ));
+    };
+    match authorization {
+        // Compared in constant time, so that the token cannot be guessed from response times.
+        Some(TypedHeader(Authorization(bearer)))
+            if bool::from(bearer.token().as_bytes().ct_eq(auth_token.as_bytes())) =>
+        {
+            Ok(next.run(request).await)
+        }
+        _ => Err((
+            StatusCode::UNAUTHORIZED,
+            "missing or invalid bearer token\n".to_string(),
+        )),
+    }
+}
+
+async fn pending_certificates(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    let epoch_store = state.node.state().load_epoch_store_one_call_per_task();
+    let pending_execution = match epoch_store.all_pending_execution() {
+        Ok(pending_execution) => pending_execution,
+        Err(err) => return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    };
+
+    let mut output = format!("pending execution ({}):\n", pending_execution.len());
+    for certificate in &pending_execution {
+        output.push_str(&format!("{}\n", certificate.digest()));
+    }
+    let pending_consensus = epoch_store.pending_consensus_certificates();
+    output.push_str(&format!(
+        "pending consensus ({}):\n",
+        pending_consensus.len()
+    ));
+    for digest in &pending_consensus {
+        output.push_str(&format!("{}\n", digest));
+    }
+
+    (StatusCode::OK, output)
+}
+
+async fn deferred_transactions(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    let epoch_store = state.node.state().load_epoch_store_one_call_per_task();
+    let deferred_transactions = match epoch_store.get_all_deferred_transactions() {
+        Ok(deferred_transactions) => deferred_transactions,
+        Err(err) => return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    };
+
+    let mut output = String::new();
+    for (key, transactions) in &deferred_transactions {
+        output.push_str(&format!("{:?}:\n", key));
+        for transaction in transactions {
+            output.push_str(&format!("  {:?}\n", transaction.0.key()));
+        }
+    }
+
+    (StatusCode::OK, output)
+}
+
+#[derive(Deserialize)]
+struct SharedObjectVersions {
+    start: Option<TransactionDigest>,
+    limit: Option<usize>,
+}
+
+async fn shared_object_versions(
+    State(state): State<Arc<AppState>>,
+    query: Query<SharedObjectVersions>,
+) -> (StatusCode, String) {
+    let Query(SharedObjectVersions { start, limit }) = query;
+    let epoch_store = state.node.state().load_epoch_store_one_call_per_task();
+    let assigned_versions = match epoch_store.get_assigned_shared_object_versions(
+        start,
+        limit.unwrap_or(DEFAULT_SHARED_OBJECT_VERSIONS_LIMIT),
+    ) {
+        Ok(assigned_versions) => assigned_versions,
+        Err(err) => return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    };
+
+    let mut output = String::new();
+    for (digest, versions) in &assigned_versions {
+        output.push_str(&format!("{}:\n", digest));
+        for (object_id, version) in versions {
+            output.push_str(&format!("  {} {}\n", object_id, version));
+        }
+    }
+
+    (StatusCode::OK, output)
+}
+
+#[derive(Deserialize)]
+struct CachedObject {
+    object_id: ObjectID,
+}
+
+async fn cached_object(
+    State(state): State<Arc<AppState>>,
+    query: Query<CachedObject>,
+) -> (StatusCode, String) {
+    let Query(CachedObject { object_id }) = query;
+    let cache_reader = state.node.state().get_cache_reader();
+
+    let object_ref = match cache_reader.get_latest_object_ref_or_tombstone(object_id) {
+        Ok(Some(object_ref)) => object_ref,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                format!("object {object_id} not found\n"),
+            )
+        }
+        Err(err) => return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    };
+    match cache_reader.get_object(&object_id) {
+        Ok(Some(object)) => (StatusCode::OK, format!("{:?}\n{:#?}\n", object_ref, object)),
+        // The latest version is a tombstone
+        Ok(None) => (
+            StatusCode::OK,
+            format!("{:?} (deleted or wrapped)\n", object_ref),
+        ),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+#[derive(Deserialize)]
+struct Digest {
+    digest: TransactionDigest,
+}
+
+async fn cached_effects(
+    State(state): State<Arc<AppState>>,
+    query: Query<Digest>,
+) -> (StatusCode, String) {
+    let Query(Digest { digest }) = query;
+
+    match state
+        .node
+        .state()
+        .get_cache_reader()
+        .get_executed_effects(&digest)
+    {
+        Ok(Some(effects)) => (StatusCode::OK, format!("{:#?}\n", effects)),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            format!("transaction {digest} not executed\n"),
+        ),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+async fn pause_checkpoint_execution(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    if state.node.pause_checkpoint_execution() {
+        (
+            StatusCode::OK,
+            "checkpoint execution already paused\n".to_string(),
+        )
+    } else {
+        info!("Checkpoint execution paused through the admin interface");
+        (StatusCode::OK, "checkpoint execution paused\n".to_string())
+    }
+}
+
+async fn resume_checkpoint_execution(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    if state.node.resume_checkpoint_execution() {
+        info!("Checkpoint execution resumed through the admin interface");
+        (StatusCode::OK, "checkpoint execution resumed\n".to_string())
+    } else {
+        (
+            StatusCode::OK,
+            "checkpoint execution was not paused\n".to_string(),
+        )
+    }
+}
+
+async fn db_checkpoint(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    match state.node.checkpoint_dbs().await {
+        Ok(path) => (
+            StatusCode::OK,
+            format!("db checkpoint written to {}\n", path.display()),
+        ),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}\n", err)),
+    }
+}
+
+async fn dump_transaction_state(
+    State(state): State<Arc<AppState>>,
+    query: Query<Digest>,
+) -> (StatusCode, String) {
+    let Query(Digest { digest }) = query;
+
+    match state.node.state().dump_transaction_state(&digest) {
+        Ok(path) => (
+            StatusCode::OK,
+            format!("transaction state dumped to {}\n", path.display()),
+        ),
+        Err(err @ MgoError::TransactionNotFound { .. }) => {
+            (StatusCode::NOT_FOUND, format!("{}\n", err))
+        }
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{}\n", err)),
+    }
+}
+
+async fn get_pruning_config(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    match serde_yaml::to_string(&state.node.state().pruning_config()) {
+        Ok(config) => (StatusCode::OK, config),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+async fn set_pruning_config(
+    State(state): State<Arc<AppState>>,
+    config: String,
+) -> (StatusCode, String) {
+    let config: AuthorityStorePruningConfig = match serde_yaml::from_str(&config) {
+        Ok(config) => config,
+        Err(err) => return (StatusCode::BAD_REQUEST, format!("{}\n", err)),
+    };
+
+    let config = state.node.state().update_pruning_config(config);
+    info!(
+        ?config,
+        "Pruning config updated through the admin interface"
+    );
+    match serde_yaml::to_string(&config) {
+        Ok(config) => (StatusCode::OK, config),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    const TOKEN: &str = "admin-token";
+
+    fn router(auth_token: Option<&str>) -> Router {
+        Router::new()
+            .route("/", post(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(
+                auth_token.map(str::to_string),
+                require_auth_token,
+            ))
+    }
+
+    async fn status(router: Router, authorization: Option<&str>) -> StatusCode {
+        let mut request = Request::builder().method("POST").uri("/");
+        if let Some(authorization) = authorization {
+            request = request.header("Authorization", authorization);
+        }
+        router
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_require_auth_token() {
+        let bearer = format!("Bearer {TOKEN}");
+        assert_eq!(
+            status(router(Some(TOKEN)), Some(&bearer)).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(router(Some(TOKEN)), None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        for invalid in ["Bearer admin", "Bearer admin-token2", "Bearer ADMIN-TOKEN"] {
+            assert_eq!(
+                status(router(Some(TOKEN)), Some(invalid)).await,
+                StatusCode::UNAUTHORIZED
+            );
+        }
+        // The token is only sent as a bearer token.
+        assert_eq!(
+            status(router(Some(TOKEN)), Some(TOKEN)).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_require_auth_token_not_configured() {
+        let bearer = format!("Bearer {TOKEN}");
+        assert_eq!(
+            status(router(None), Some(&bearer)).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(status(router(None), None).await, StatusCode::FORBIDDEN);
+    }
+}
@@ -50,7 +50,9 @@ use mgo_archival::writer::ArchiveWriter;
 use mgo_config::node::{ConsensusProtocol, DBCheckpointConfig, RunWithRange};
 use mgo_config::node_config_metrics::NodeConfigMetrics;
 use mgo_config::object_storage_config::{ObjectStoreConfig, ObjectStoreType};
+use mgo_config::transaction_deny_config::TransactionDenyConfig;
 use mgo_config::{ConsensusConfig, NodeConfig};
+use mgo_core::admission_control::AdmissionController;
 use mgo_core::authority::authority_per_epoch_store::AuthorityPerEpochStore;
 use mgo_core::authority::authority_store_tables::AuthorityPerpetualTables;
 use mgo_core::authority::epoch_start_configuration::EpochStartConfigTrait;
@@ -117,6 +119,7 @@ use mgo_types::quorum_driver_types::QuorumDriverEffectsQueueResult;
 use mgo_types::mgo_system_state::epoch_start_mgo_system_state::EpochStartSystemState;
 use mgo_types::mgo_system_state::epoch_start_mgo_system_state::EpochStartSystemStateTrait;
 use mgo_types::mgo_system_state::MgoSystemStateTrait;
+use transaction_deny::TransactionDenyConfigReloader;
 use typed_store::rocks::default_db_options;
 use typed_store::DBMetrics;
 
@@ -125,6 +128,7 @@ use crate::metrics::{GrpcMetrics, MgoNodeMetrics};
 pub mod admin;
 mod handle;
 pub mod metrics;
+pub mod transaction_deny;
 
 pub struct ValidatorComponents {
     validator_server_handle: JoinHandle<Result<()>>,
@@ -230,6 +234,11 @@ pub struct MgoNode {
     _state_snapshot_uploader_handle: Option<broadcast::Sender<()>>,
     // Channel to allow signaling upstream to shutdown mgo-node
     shutdown_channel_tx: broadcast::Sender<Option<RunWithRange>>,
+
+    transaction_deny_config_reloader: Arc<TransactionDenyConfigReloader>,
+
+    /// Set to pause the execution of synced checkpoints.
+    checkpoint_execution_paused: watch::Sender<bool>,
 }
 
 impl fmt::Debug for MgoNode {
@@ -391,7 +400,8 @@ impl MgoNode {
         registry_service: RegistryService,
         custom_rpc_runtime: Option<Handle>,
     ) -> Result<Arc<MgoNode>> {
-        NodeConfigMetrics::new(&registry_service.default_registry()).record_metrics(config);
+        let node_config_metrics = NodeConfigMetrics::new(&registry_service.default_registry());
+        node_config_metrics.record_metrics(config);
         let mut config = config.clone();
         if config.supported_protocol_versions.is_none() {
             info!(
@@ -575,6 +585,8 @@ impl MgoNode {
             pruning_config.set_killswitch_tombstone_pruning(true);
         }
 
+        let transaction_deny_config = TransactionDenyConfigReloader::initial_config(&config)?;
+        node_config_metrics.record_transaction_deny_config(&transaction_deny_config);
         let state = AuthorityState::new(
             config.protocol_public_key(),
             secret,
@@ -590,7 +602,7 @@ impl MgoNode {
             genesis.objects(),
             &db_checkpoint_config,
             config.expensive_safety_check_config.clone(),
-            config.transaction_deny_config.clone(),
+            transaction_deny_config,
             config.certificate_deny_config.clone(),
             config.indirect_objects_threshold,
             config.state_debug_dump_config.clone(),
@@ -703,6 +715,15 @@ impl MgoNode {
         // setup shutdown channel
         let (shutdown_channel, _) = broadcast::channel::<Option<RunWithRange>>(1);
 
+        let transaction_deny_config_reloader = Arc::new(TransactionDenyConfigReloader::new(
+            state.clone(),
+            &config,
+            node_config_metrics,
+        ));
+        spawn_monitored_task!(transaction_deny_config_reloader.clone().run());
+
+        let (checkpoint_execution_paused, _) = watch::channel(false);
+
         let node = Self {
             config,
             validator_components: Mutex::new(validator_components),
@@ -728,6 +749,8 @@ impl MgoNode {
             _state_archive_handle: state_archive_handle,
             _state_snapshot_uploader_handle: state_snapshot_handle,
             shutdown_channel_tx: shutdown_channel,
+            transaction_deny_config_reloader,
+            checkpoint_execution_paused,
         };
 
         info!("MgoNode started!");
@@ -781,6 +804,56 @@ impl MgoNode {
             .set_override_protocol_upgrade_buffer_stake(epoch, buffer_stake_bps)
     }
 
+    pub fn set_transaction_deny_config(
+        &self,
+        config: TransactionDenyConfig,
+        source: &str,
+    ) -> Vec<String> {
+        self.transaction_deny_config_reloader.set(config, source)
+    }
+
+    pub fn reload_transaction_deny_config(&self, source: &str) -> Result<Vec<String>> {
+        self.transaction_deny_config_reloader.reload_from_file(source)
+    }
+
+    /// Stops scheduling synced checkpoints for execution, until execution is resumed. Checkpoints
+    /// already scheduled still complete, and state sync keeps running. Returns whether execution
+    /// was already paused.
+    pub fn pause_checkpoint_execution(&self) -> bool {
+        self.checkpoint_execution_paused.send_replace(true)
+    }
+
+    /// Returns whether execution was paused.
+    pub fn resume_checkpoint_execution(&self) -> bool {
+        self.checkpoint_execution_paused.send_replace(false)
+    }
+
+    pub fn is_checkpoint_execution_paused(&self) -> bool {
+        *self.checkpoint_execution_paused.borrow()
+    }
+
+    /// Takes a checkpoint of the dbs under `manual_db_checkpoint_path`, and returns its path.
+    pub async fn checkpoint_dbs(&self) -> Result<PathBuf> {
+        let epoch_store = self.state.load_epoch_store_one_call_per_task().clone();
+        let checkpoint_path = self.config.manual_db_checkpoint_path().join(format!(
+            "epoch_{}_{}",
+            epoch_store.epoch(),
+            AuthorityState::unixtime_now_ms()
+        ));
+        let checkpoint_indexes = self
+            .config
+            .db_checkpoint_config
+            .perform_index_db_checkpoints_at_epoch_end
+            .unwrap_or(false);
+        let state = self.state.clone();
+        let path = checkpoint_path.clone();
+        tokio::task::spawn_blocking(move || {
+            state.checkpoint_all_dbs(&path, &epoch_store, checkpoint_indexes)
+        })
+        .await??;
+        Ok(checkpoint_path)
+    }
+
     // Testing-only API to start epoch close process.
     // For production code, please use the non-testing version.
     pub async fn close_epoch_for_testing(&self) -> MgoResult {
@@ -885,6 +958,7 @@ impl MgoNode {
                     config.authority_store_pruning_config,
                     prometheus_registry,
                     state_snapshot_enabled,
+                    db_checkpoint_config.incremental_upload.unwrap_or(false),
                 )?;
                 Ok((
                     db_checkpoint_config,
@@ -1280,10 +1354,15 @@ impl MgoNode {
         consensus_adapter: Arc<ConsensusAdapter>,
         prometheus_registry: &Registry,
     ) -> Result<tokio::task::JoinHandle<Result<()>>> {
+        let admission_controller = config
+            .admission_control_config
+            .clone()
+            .map(|config| Arc::new(AdmissionController::new(config)));
         let validator_service = ValidatorService::new(
             state.clone(),
             consensus_adapter,
             Arc::new(ValidatorServiceMetrics::new(prometheus_registry)),
+            admission_controller,
         );
 
         let mut server_conf = mango_network::config::Config::new();
@@ -1366,6 +1445,7 @@ impl MgoNode {
             self.state.clone(),
             self.accumulator.clone(),
             self.config.checkpoint_executor_config.clone(),
+            self.checkpoint_execution_paused.subscribe(),
             &self.registry_service.default_registry(),
         );
 
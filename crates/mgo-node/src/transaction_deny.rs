@@ -0,0 +1,138 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, Context, Result};
+use mgo_config::node_config_metrics::NodeConfigMetrics;
+use mgo_config::transaction_deny_config::TransactionDenyConfig;
+use mgo_config::NodeConfig;
+use mgo_core::authority::AuthorityState;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{error, info};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Applies changes of the transaction deny config while the node is running, either from the
+/// file at `transaction-deny-config-path` or through the admin server, and records every change
+/// in the audit log.
+pub struct TransactionDenyConfigReloader {
+    state: Arc<AuthorityState>,
+    config_path: Option<PathBuf>,
+    audit_log_path: Option<PathBuf>,
+    metrics: Arc<NodeConfigMetrics>,
+    // Contents of the config file that were last applied, so that polling only reloads changes.
+    last_file_contents: Mutex<Option<String>>,
+}
+
+impl TransactionDenyConfigReloader {
+    pub fn new(
+        state: Arc<AuthorityState>,
+        config: &NodeConfig,
+        metrics: Arc<NodeConfigMetrics>,
+    ) -> Self {
+        Self {
+            state,
+            config_path: config.transaction_deny_config_path.clone(),
+            audit_log_path: config.transaction_deny_audit_log_path.clone(),
+            metrics,
+            last_file_contents: Mutex::new(None),
+        }
+    }
+
+    /// The deny config to start the node with.
+    pub fn initial_config(config: &NodeConfig) -> Result<TransactionDenyConfig> {
+        match &config.transaction_deny_config_path {
+            Some(path) => parse(&read(path)?),
+            None => Ok(config.transaction_deny_config.clone()),
+        }
+    }
+
+    /// Replaces the deny config with `config`. `source` identifies who made the change in the
+    /// audit log. Returns the changes made.
+    pub fn set(&self, config: TransactionDenyConfig, source: &str) -> Vec<String> {
+        self.metrics.record_transaction_deny_config(&config);
+        let changes = self.state.reload_transaction_deny_config(config);
+        self.audit(source, &changes);
+        changes
+    }
+
+    /// Reloads the deny config from `transaction-deny-config-path`.
+    pub fn reload_from_file(&self, source: &str) -> Result<Vec<String>> {
+        let path = self
+            .config_path
+            .as_ref()
+            .ok_or_else(|| anyhow!("transaction-deny-config-path is not set"))?;
+        let contents = read(path)?;
+        let config = parse(&contents)?;
+        *self.last_file_contents.lock().unwrap() = Some(contents);
+        Ok(self.set(config, source))
+    }
+
+    /// Reloads the deny config whenever the file at `transaction-deny-config-path` changes.
+    /// Invalid configs are logged and ignored, keeping the config in use.
+    pub async fn run(self: Arc<Self>) {
+        let Some(path) = self.config_path.clone() else {
+            return;
+        };
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            let contents = match read(&path) {
+                Ok(contents) => contents,
+                Err(err) => {
+                    error!("Failed to read transaction deny config: {err:?}");
+                    continue;
+                }
+            };
+            if self.last_file_contents.lock().unwrap().as_ref() == Some(&contents) {
+                continue;
+            }
+            match parse(&contents) {
+                Ok(config) => {
+                    self.set(config, &format!("file {}", path.display()));
+                }
+                Err(err) => error!("Ignoring invalid transaction deny config: {err:?}"),
+            }
+            *self.last_file_contents.lock().unwrap() = Some(contents);
+        }
+    }
+
+    fn audit(&self, source: &str, changes: &[String]) {
+        for change in changes {
+            info!(target: "transaction_deny_audit", source, "{change}");
+        }
+        let Some(path) = &self.audit_log_path else {
+            return;
+        };
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis());
+        let result = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| {
+                changes
+                    .iter()
+                    .try_for_each(|change| writeln!(file, "{timestamp_ms} [{source}] {change}"))
+            });
+        if let Err(err) = result {
+            error!(
+                "Failed to write transaction deny audit log {}: {err:?}",
+                path.display()
+            );
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path)
+        .with_context(|| format!("Unable to read transaction deny config {}", path.display()))
+}
+
+pub fn parse(contents: &str) -> Result<TransactionDenyConfig> {
+    serde_yaml::from_str(contents).context("Invalid transaction deny config")
+}
@@ -289,6 +289,8 @@ impl RpcExampleProvider {
             error: None,
             raw_txn_data: vec![],
             raw_effects: vec![],
+            state_overrides: None,
+            trace: None,
         };
 
         Examples::new(
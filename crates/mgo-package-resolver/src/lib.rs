@@ -638,6 +638,43 @@ impl OpenSignatureBody {
             ),
         })
     }
+
+    /// Substitute `type_params` for the type parameters in this signature, to produce a type tag.
+    /// `package` is the package the signature was read from: Datatypes in the signature are
+    /// referred to by runtime ID, and are translated into storage IDs using its linkage table, so
+    /// that the resulting tag can be passed to [`Resolver::type_layout`].
+    pub fn instantiate(&self, package: &Package, type_params: &[TypeTag]) -> Result<TypeTag> {
+        use OpenSignatureBody as O;
+        use TypeTag as T;
+
+        Ok(match self {
+            O::Address => T::Address,
+            O::Bool => T::Bool,
+            O::U8 => T::U8,
+            O::U16 => T::U16,
+            O::U32 => T::U32,
+            O::U64 => T::U64,
+            O::U128 => T::U128,
+            O::U256 => T::U256,
+
+            O::Vector(sig) => T::Vector(Box::new(sig.instantiate(package, type_params)?)),
+
+            O::TypeParameter(ix) => type_params
+                .get(*ix as usize)
+                .cloned()
+                .ok_or_else(|| Error::TypeParamOOB(*ix, type_params.len()))?,
+
+            O::Datatype(key, params) => T::Struct(Box::new(StructTag {
+                address: package.relocate(key.package)?,
+                module: ident(&key.module)?,
+                name: ident(&key.name)?,
+                type_params: params
+                    .iter()
+                    .map(|sig| sig.instantiate(package, type_params))
+                    .collect::<Result<_>>()?,
+            })),
+        })
+    }
 }
 
 impl<'m, 'n> DatatypeRef<'m, 'n> {
@@ -1573,6 +1610,44 @@ mod tests {
         ));
     }
 
+    /// Instantiating a function's signature produces type tags that refer to the packages in its
+    /// linkage table by storage ID.
+    #[tokio::test]
+    async fn test_instantiate_function_signature() {
+        let (_, cache) = package_cache([
+            (1, build_package("a0"), a0_types()),
+            (2, build_package("a1"), a1_types()),
+            (1, build_package("b0"), b0_types()),
+            (1, build_package("c0"), c0_types()),
+        ]);
+
+        let c0 = cache.fetch(addr("0xc0")).await.unwrap();
+        let bar = c0
+            .module("m")
+            .unwrap()
+            .function_def("bar")
+            .unwrap()
+            .unwrap();
+
+        let params = bar
+            .parameters
+            .iter()
+            .map(|sig| sig.body.instantiate(&c0, &[]).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(params, vec![type_("0xc0::m::T0"), type_("0xa1::n::T1")]);
+
+        let generic = OpenSignatureBody::Vector(Box::new(OpenSignatureBody::TypeParameter(1)));
+        assert_eq!(
+            generic
+                .instantiate(&c0, &[type_("u8"), type_("0x1::string::String")])
+                .unwrap(),
+            type_("vector<0x1::string::String>"),
+        );
+
+        let err = generic.instantiate(&c0, &[type_("u8")]).unwrap_err();
+        assert!(matches!(err, Error::TypeParamOOB(1, 1)));
+    }
+
     /// Primitive types should have the expected primitive abilities
     #[tokio::test]
     async fn test_primitive_abilities() {
@@ -12,11 +12,19 @@ use tracing::{info, warn};
 
 /// The minimum and maximum protocol versions supported by this build.
 const MIN_PROTOCOL_VERSION: u64 = 1;
-const MAX_PROTOCOL_VERSION: u64 = 2;
+const MAX_PROTOCOL_VERSION: u64 = 8;
 
 // Record history of protocol version allocations here:
 //
 // Version 1: Original version.
+// Version 2: Coin deny list, shared object deletion and consensus transaction size limits.
+// Version 3: Order consensus transactions by gas price within shared object conflict sets, and
+//            cap the number of transactions touching one shared object per commit in devnet.
+// Version 4: Enable passkey auth in devnet.
+// Version 5: Enable timestamp based transaction expiration in devnet.
+// Version 6: Enable nested multisig in devnet.
+// Version 7: Order consensus transactions round-robin by sender in devnet.
+// Version 8: Restrict passkey assertions to the mangonetwork.io relying party in devnet.
 #[derive(Copy, Clone, Debug, Hash, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ProtocolVersion(u64);
 
@@ -291,6 +299,30 @@ struct FeatureFlags {
     // Enable native functions for group operations.
     #[serde(skip_serializing_if = "is_false")]
     enable_group_ops_native_functions: bool,
+
+    // How we limit the number of transactions touching a hot shared object in one consensus commit.
+    #[serde(skip_serializing_if = "PerObjectCongestionControlMode::is_none")]
+    per_object_congestion_control_mode: PerObjectCongestionControlMode,
+
+    // Enable passkey auth
+    #[serde(skip_serializing_if = "is_false")]
+    passkey_auth: bool,
+
+    // Enable transactions that expire based on consensus time instead of epochs
+    #[serde(skip_serializing_if = "is_false")]
+    timestamp_expiration: bool,
+
+    // Interleave the transactions of different senders before applying
+    // consensus_transaction_ordering, so that one sender cannot crowd the beginning of a commit.
+    #[serde(skip_serializing_if = "is_false")]
+    consensus_sender_round_robin: bool,
+
+    // The WebAuthn relying party IDs passkey assertions can be scoped to, and the origins they
+    // can be made from. Passkey assertions are rejected if either list is empty.
+    #[serde(skip_serializing_if = "is_empty")]
+    passkey_rp_ids: BTreeSet<String>,
+    #[serde(skip_serializing_if = "is_empty")]
+    passkey_origins: BTreeSet<String>,
 }
 
 fn is_false(b: &bool) -> bool {
@@ -309,6 +341,12 @@ pub enum ConsensusTransactionOrdering {
     None,
     /// Order transactions by gas price, highest first.
     ByGasPrice,
+    /// Interleave user transactions of different senders, taking one transaction per sender in
+    /// turn. Transactions of the same sender keep their sequenced order.
+    BySenderRoundRobin,
+    /// Order transactions by gas price, highest first, among transactions sharing a shared object
+    /// (directly or transitively). Transactions that do not conflict keep their relative positions.
+    ByGasPricePerSharedObject,
 }
 
 impl ConsensusTransactionOrdering {
@@ -317,6 +355,23 @@ impl ConsensusTransactionOrdering {
     }
 }
 
+/// Congestion control for shared objects touched by many transactions in one consensus commit.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Serialize, Debug)]
+pub enum PerObjectCongestionControlMode {
+    /// No limit on the number of transactions touching one shared object per commit.
+    #[default]
+    None,
+    /// At most `max_txns_per_shared_object_per_commit` transactions touching one shared object are
+    /// scheduled per commit. The rest are deferred to the next commit.
+    TransactionCount,
+}
+
+impl PerObjectCongestionControlMode {
+    pub fn is_none(&self) -> bool {
+        matches!(self, PerObjectCongestionControlMode::None)
+    }
+}
+
 /// Constants that change the behavior of the protocol.
 ///
 /// The value of each constant here must be fixed for a given protocol version. To change the value
@@ -837,6 +892,16 @@ pub struct ProtocolConfig {
     consensus_max_transaction_size_bytes: Option<u64>,
     /// The maximum size of transactions included in a consensus proposed block
     consensus_max_transactions_in_block_bytes: Option<u64>,
+
+    /// The maximum number of transactions touching one shared object that are scheduled per
+    /// consensus commit, when `per_object_congestion_control_mode` is `TransactionCount`.
+    max_txns_per_shared_object_per_commit: Option<u64>,
+
+    /// The maximum number of multisig levels below the top level multisig of a transaction
+    /// signature. Multisigs cannot have multisig members if not set.
+    max_multisig_nesting_depth: Option<u32>,
+    /// The maximum number of signatures verified across all levels of a nested multisig.
+    max_multisig_signatures: Option<u32>,
 }
 
 // feature flags
@@ -947,6 +1012,10 @@ impl ProtocolConfig {
         self.feature_flags.consensus_transaction_ordering
     }
 
+    pub fn per_object_congestion_control_mode(&self) -> PerObjectCongestionControlMode {
+        self.feature_flags.per_object_congestion_control_mode
+    }
+
     pub fn simplified_unwrap_then_delete(&self) -> bool {
         self.feature_flags.simplified_unwrap_then_delete
     }
@@ -1059,6 +1128,26 @@ impl ProtocolConfig {
     pub fn enable_group_ops_native_functions(&self) -> bool {
         self.feature_flags.enable_group_ops_native_functions
     }
+
+    pub fn passkey_auth(&self) -> bool {
+        self.feature_flags.passkey_auth
+    }
+
+    pub fn timestamp_expiration(&self) -> bool {
+        self.feature_flags.timestamp_expiration
+    }
+
+    pub fn consensus_sender_round_robin(&self) -> bool {
+        self.feature_flags.consensus_sender_round_robin
+    }
+
+    pub fn passkey_rp_ids(&self) -> &BTreeSet<String> {
+        &self.feature_flags.passkey_rp_ids
+    }
+
+    pub fn passkey_origins(&self) -> &BTreeSet<String> {
+        &self.feature_flags.passkey_origins
+    }
 }
 
 #[cfg(not(msim))]
@@ -1473,6 +1562,11 @@ impl ProtocolConfig {
             consensus_max_transaction_size_bytes: None,
 
             consensus_max_transactions_in_block_bytes: None,
+
+            max_txns_per_shared_object_per_commit: None,
+
+            max_multisig_nesting_depth: None,
+            max_multisig_signatures: None,
             // When adding a new constant, set it to None in the earliest version, like this:
             // new_constant: None,
         };
@@ -1649,6 +1743,44 @@ impl ProtocolConfig {
                     cfg.consensus_max_transaction_size_bytes = Some(256 * 1024); // 256KB
                     cfg.consensus_max_transactions_in_block_bytes = Some(6 * 1_024 * 1024);
                 }
+                3 => {
+                    if chain != Chain::Mainnet && chain != Chain::Testnet {
+                        cfg.feature_flags.consensus_transaction_ordering =
+                            ConsensusTransactionOrdering::ByGasPricePerSharedObject;
+                        cfg.feature_flags.per_object_congestion_control_mode =
+                            PerObjectCongestionControlMode::TransactionCount;
+                        cfg.max_txns_per_shared_object_per_commit = Some(100);
+                    }
+                }
+                4 => {
+                    if chain != Chain::Mainnet && chain != Chain::Testnet {
+                        cfg.feature_flags.passkey_auth = true;
+                    }
+                }
+                5 => {
+                    if chain != Chain::Mainnet && chain != Chain::Testnet {
+                        cfg.feature_flags.timestamp_expiration = true;
+                    }
+                }
+                6 => {
+                    if chain != Chain::Mainnet && chain != Chain::Testnet {
+                        cfg.max_multisig_nesting_depth = Some(2);
+                        cfg.max_multisig_signatures = Some(30);
+                    }
+                }
+                7 => {
+                    if chain != Chain::Mainnet && chain != Chain::Testnet {
+                        cfg.feature_flags.consensus_sender_round_robin = true;
+                    }
+                }
+                8 => {
+                    if chain != Chain::Mainnet && chain != Chain::Testnet {
+                        cfg.feature_flags.passkey_rp_ids =
+                            BTreeSet::from(["mangonetwork.io".to_string()]);
+                        cfg.feature_flags.passkey_origins =
+                            BTreeSet::from(["https://wallet.mangonetwork.io".to_string()]);
+                    }
+                }
                 _ => panic!("unsupported version {:?}", version),
             }
         }
@@ -1685,6 +1817,15 @@ impl ProtocolConfig {
     pub fn set_zklogin_auth_for_testing(&mut self, val: bool) {
         self.feature_flags.zklogin_auth = val
     }
+    pub fn set_passkey_auth_for_testing(&mut self, val: bool) {
+        self.feature_flags.passkey_auth = val
+    }
+    pub fn set_timestamp_expiration_for_testing(&mut self, val: bool) {
+        self.feature_flags.timestamp_expiration = val
+    }
+    pub fn set_consensus_sender_round_robin_for_testing(&mut self, val: bool) {
+        self.feature_flags.consensus_sender_round_robin = val
+    }
     pub fn set_enable_jwk_consensus_updates_for_testing(&mut self, val: bool) {
         self.feature_flags.enable_jwk_consensus_updates = val
     }
@@ -1710,6 +1851,20 @@ impl ProtocolConfig {
         self.feature_flags.narwhal_new_leader_election_schedule = val;
     }
 
+    pub fn set_consensus_transaction_ordering_for_testing(
+        &mut self,
+        val: ConsensusTransactionOrdering,
+    ) {
+        self.feature_flags.consensus_transaction_ordering = val;
+    }
+
+    pub fn set_per_object_congestion_control_mode_for_testing(
+        &mut self,
+        val: PerObjectCongestionControlMode,
+    ) {
+        self.feature_flags.per_object_congestion_control_mode = val;
+    }
+
     pub fn set_consensus_bad_nodes_stake_threshold(&mut self, val: u64) {
         self.consensus_bad_nodes_stake_threshold = Some(val);
     }
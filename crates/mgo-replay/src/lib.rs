@@ -95,6 +95,9 @@ pub enum ReplayToolCommand {
         /// Optional protocol version to use, if not specified defaults to the one originally used for the transaction.
         #[arg(long, short, allow_hyphen_values = true)]
         protocol_version: Option<i64>,
+        /// Optional output filepath for a JSON trace of the Move execution of the transaction: the functions it called, with their arguments, return values and gas, the events it emitted, and the objects it read and wrote.
+        #[arg(long, allow_hyphen_values = true)]
+        trace_output: Option<PathBuf>,
     },
 
     /// Replay transactions listed in a file
@@ -199,6 +202,7 @@ pub async fn execute_replay_command(
                 None,
                 None,
                 None,
+                false,
             )
             .await?;
 
@@ -282,6 +286,7 @@ pub async fn execute_replay_command(
                             None,
                             None,
                             None,
+                            false,
                         )
                         .await?;
 
@@ -384,6 +389,7 @@ pub async fn execute_replay_command(
                 executor_version,
                 protocol_version,
                 output_path,
+                false,
             )
             .await?;
 
@@ -397,6 +403,7 @@ pub async fn execute_replay_command(
             diag,
             executor_version,
             protocol_version,
+            trace_output,
         } => {
             let tx_digest = TransactionDigest::from_str(&tx_digest)?;
             info!("Executing tx: {}", tx_digest);
@@ -409,9 +416,15 @@ pub async fn execute_replay_command(
                 executor_version,
                 protocol_version,
                 None,
+                trace_output.is_some(),
             )
             .await?;
 
+            if let (Some(path), Some(trace)) = (&trace_output, &sandbox_state.execution_trace) {
+                std::fs::write(path, serde_json::to_string_pretty(trace)?)?;
+                println!("Execution trace written to {}", path.display());
+            }
+
             if diag {
                 println!("{:#?}", sandbox_state.pre_exec_diag);
             }
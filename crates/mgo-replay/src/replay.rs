@@ -36,6 +36,7 @@ use mgo_core::{
         test_authority_builder::TestAuthorityBuilder, AuthorityState, NodeStateDump,
     },
     epoch::epoch_metrics::EpochMetrics,
+    execution_trace::decode_execution_trace,
     module_cache_metrics::ResolverMetrics,
     signature_verifier::SignatureVerifierMetrics,
 };
@@ -52,6 +53,7 @@ use mgo_types::{
     digests::{ChainIdentifier, CheckpointDigest, ObjectDigest, TransactionDigest},
     error::{ExecutionError, MgoError, MgoResult},
     executable_transaction::VerifiedExecutableTransaction,
+    execution_trace::{ExecutionTrace, ExecutionTracer},
     gas::MgoGasStatus,
     inner_temporary_store::InnerTemporaryStore,
     metrics::LimitsMetrics,
@@ -85,6 +87,9 @@ pub struct ExecutionSandboxState {
     pub local_exec_status: Option<Result<(), ExecutionError>>,
     /// Pre exec diag info
     pub pre_exec_diag: DiagInfo,
+    /// Trace of the Move execution of the transaction, if tracing was enabled
+    #[serde(skip)]
+    pub execution_trace: Option<ExecutionTrace>,
 }
 
 impl ExecutionSandboxState {
@@ -239,6 +244,8 @@ pub struct LocalExec {
     // Whether or not to enable the gas profiler, the PathBuf contains either a user specified
     // filepath or the default current directory and name format for the profile output
     pub enable_profiler: Option<PathBuf>,
+    // Whether or not to record a trace of the Move execution of the transaction
+    pub enable_tracer: bool,
     // Retry policies due to RPC errors
     pub num_retries_for_timeout: u32,
     pub sleep_period_for_timeout: std::time::Duration,
@@ -323,6 +330,7 @@ impl LocalExec {
         executor_version: Option<i64>,
         protocol_version: Option<i64>,
         enable_profiler: Option<PathBuf>,
+        enable_tracer: bool,
     ) -> Result<ExecutionSandboxState, ReplayEngineError> {
         async fn inner_exec(
             rpc_url: String,
@@ -332,6 +340,7 @@ impl LocalExec {
             executor_version: Option<i64>,
             protocol_version: Option<i64>,
             enable_profiler: Option<PathBuf>,
+            enable_tracer: bool,
         ) -> Result<ExecutionSandboxState, ReplayEngineError> {
             LocalExec::new_from_fn_url(&rpc_url)
                 .await?
@@ -344,6 +353,7 @@ impl LocalExec {
                     executor_version,
                     protocol_version,
                     enable_profiler,
+                    enable_tracer,
                 )
                 .await
         }
@@ -358,6 +368,7 @@ impl LocalExec {
                 executor_version,
                 protocol_version,
                 enable_profiler,
+                enable_tracer,
             )
             .await
             {
@@ -385,6 +396,7 @@ impl LocalExec {
                 executor_version,
                 protocol_version,
                 enable_profiler.clone(),
+                enable_tracer,
             )
             .await
             {
@@ -445,6 +457,7 @@ impl LocalExec {
             executor_version: None,
             protocol_version: None,
             enable_profiler: None,
+            enable_tracer: false,
         })
     }
 
@@ -488,6 +501,7 @@ impl LocalExec {
             executor_version: None,
             protocol_version: None,
             enable_profiler: None,
+            enable_tracer: false,
         })
     }
 
@@ -671,6 +685,7 @@ impl LocalExec {
                     None,
                     None,
                     None,
+                    false,
                 )
                 .await
                 .map(|q| q.check_effects())
@@ -714,6 +729,7 @@ impl LocalExec {
                 local_exec_effects: effects,
                 local_exec_status: Some(Ok(())),
                 pre_exec_diag: self.diag.clone(),
+                execution_trace: None,
             });
         }
         // Initialize the state necessary for execution
@@ -754,28 +770,57 @@ impl LocalExec {
         let expensive_checks = true;
         let transaction_kind = override_transaction_kind.unwrap_or(tx_info.kind.clone());
         let certificate_deny_set = HashSet::new();
+        let mut tracer = self.enable_tracer.then(ExecutionTracer::new);
         let (inner_store, gas_status, effects, result) = if let Ok(gas_status) =
             MgoGasStatus::new(tx_info.gas_budget, tx_info.gas_price, rgp, protocol_config)
         {
-            executor.execute_transaction_to_effects(
-                &self,
-                protocol_config,
-                metrics,
-                expensive_checks,
-                &certificate_deny_set,
-                &tx_info.executed_epoch,
-                epoch_start_timestamp,
-                CheckedInputObjects::new_for_replay(input_objects),
-                tx_info.gas.clone(),
-                gas_status,
-                transaction_kind.clone(),
-                tx_info.sender,
-                *tx_digest,
-            )
+            if let Some(tracer) = &mut tracer {
+                executor.execute_transaction_to_effects_with_tracer(
+                    &self,
+                    protocol_config,
+                    metrics,
+                    expensive_checks,
+                    &certificate_deny_set,
+                    &tx_info.executed_epoch,
+                    epoch_start_timestamp,
+                    CheckedInputObjects::new_for_replay(input_objects),
+                    tx_info.gas.clone(),
+                    gas_status,
+                    transaction_kind.clone(),
+                    tx_info.sender,
+                    *tx_digest,
+                    tracer,
+                )?
+            } else {
+                executor.execute_transaction_to_effects(
+                    &self,
+                    protocol_config,
+                    metrics,
+                    expensive_checks,
+                    &certificate_deny_set,
+                    &tx_info.executed_epoch,
+                    epoch_start_timestamp,
+                    CheckedInputObjects::new_for_replay(input_objects),
+                    tx_info.gas.clone(),
+                    gas_status,
+                    transaction_kind.clone(),
+                    tx_info.sender,
+                    *tx_digest,
+                )
+            }
         } else {
             unreachable!("Transaction was valid so gas status must be valid");
         };
 
+        let execution_trace = match tracer {
+            Some(tracer) => {
+                let mut trace = tracer.finish();
+                decode_execution_trace(&mut trace, &inner_store).await;
+                Some(trace)
+            }
+            None => None,
+        };
+
         trace!(target: "replay_gas_info", "{}", Pretty(&gas_status));
 
         if let ProgrammableTransaction(pt) = transaction_kind {
@@ -793,6 +838,7 @@ impl LocalExec {
             local_exec_effects: effects,
             local_exec_status: Some(result),
             pre_exec_diag: self.diag.clone(),
+            execution_trace,
         })
     }
 
@@ -947,6 +993,8 @@ impl LocalExec {
             local_exec_effects: effects,
             local_exec_status: Some(exec_res),
             pre_exec_diag: pre_exec_diag.clone(),
+            // The trace is recorded by the execution engine run that produced `pre_run_sandbox`
+            execution_trace: pre_run_sandbox.execution_trace.clone(),
         })
     }
 
@@ -1004,10 +1052,12 @@ impl LocalExec {
         executor_version: Option<i64>,
         protocol_version: Option<i64>,
         enable_profiler: Option<PathBuf>,
+        enable_tracer: bool,
     ) -> Result<ExecutionSandboxState, ReplayEngineError> {
         self.executor_version = executor_version;
         self.protocol_version = protocol_version;
         self.enable_profiler = enable_profiler;
+        self.enable_tracer = enable_tracer;
         if use_authority {
             self.certificate_execute(tx_digest, expensive_safety_check_config.clone())
                 .await
@@ -148,6 +148,7 @@ async fn execute_replay(url: &str, tx: &TransactionDigest) -> Result<(), ReplayE
             None,
             None,
             None,
+            false,
         )
         .await?
         .check_effects()?;
@@ -163,6 +164,7 @@ async fn execute_replay(url: &str, tx: &TransactionDigest) -> Result<(), ReplayE
             None,
             None,
             None,
+            false,
         )
         .await?
         .check_effects()?;
@@ -3,9 +3,11 @@
 
 use anyhow::Result;
 use mgo_types::base_types::{ObjectID, SequenceNumber};
+use mgo_types::digests::TransactionDigest;
 use mgo_types::full_checkpoint_content::CheckpointData;
 use mgo_types::messages_checkpoint::{CertifiedCheckpointSummary, CheckpointSequenceNumber};
 use mgo_types::object::Object;
+use mgo_types::proof::InclusionProof;
 
 #[derive(Clone)]
 pub struct Client {
@@ -99,6 +101,47 @@ impl Client {
         self.bcs(response).await
     }
 
+    pub async fn get_transaction_proof(
+        &self,
+        checkpoint_sequence_number: CheckpointSequenceNumber,
+        transaction_digest: TransactionDigest,
+    ) -> Result<InclusionProof> {
+        let url = format!(
+            "{}/checkpoints/{checkpoint_sequence_number}/proofs/transactions/{transaction_digest}",
+            self.base_url
+        );
+
+        let response = self
+            .inner
+            .get(url)
+            .header(reqwest::header::ACCEPT, crate::APPLICATION_BCS)
+            .send()
+            .await?;
+
+        self.bcs(response).await
+    }
+
+    pub async fn get_object_proof(
+        &self,
+        checkpoint_sequence_number: CheckpointSequenceNumber,
+        object_id: ObjectID,
+        version: SequenceNumber,
+    ) -> Result<InclusionProof> {
+        let url = format!(
+            "{}/checkpoints/{checkpoint_sequence_number}/proofs/objects/{object_id}/version/{version}",
+            self.base_url
+        );
+
+        let response = self
+            .inner
+            .get(url)
+            .header(reqwest::header::ACCEPT, crate::APPLICATION_BCS)
+            .send()
+            .await?;
+
+        self.bcs(response).await
+    }
+
     fn check_response(&self, response: reqwest::Response) -> Result<reqwest::Response> {
         if !response.status().is_success() {
             let status = response.status();
@@ -7,6 +7,7 @@ mod checkpoints;
 mod client;
 pub mod headers;
 mod objects;
+mod proofs;
 
 pub use client::Client;
 pub use mgo_types::full_checkpoint_content::{CheckpointData, CheckpointTransaction};
@@ -72,6 +73,14 @@ where
             objects::GET_OBJECT_WITH_VERSION_PATH,
             get(objects::get_object_with_version::<S>),
         )
+        .route(
+            proofs::GET_TRANSACTION_PROOF_PATH,
+            get(proofs::get_transaction_proof::<S>),
+        )
+        .route(
+            proofs::GET_OBJECT_PROOF_PATH,
+            get(proofs::get_object_proof::<S>),
+        )
         .with_state(state)
 }
 
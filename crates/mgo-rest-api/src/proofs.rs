@@ -0,0 +1,69 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use axum::extract::{Path, State};
+use mgo_types::{
+    base_types::{ObjectID, SequenceNumber},
+    digests::TransactionDigest,
+    effects::TransactionEffectsAPI,
+    full_checkpoint_content::CheckpointData,
+    messages_checkpoint::CheckpointSequenceNumber,
+    proof::{InclusionProof, ProofTarget},
+    storage::ReadStore,
+};
+
+use crate::{AppError, Bcs};
+
+pub const GET_TRANSACTION_PROOF_PATH: &str =
+    "/checkpoints/:checkpoint/proofs/transactions/:transaction";
+
+pub async fn get_transaction_proof<S: ReadStore>(
+    Path((checkpoint_id, transaction_digest)): Path<(CheckpointSequenceNumber, TransactionDigest)>,
+    State(state): State<S>,
+) -> Result<Bcs<InclusionProof>, AppError> {
+    let checkpoint = get_checkpoint_data(&state, checkpoint_id)?;
+    let proof = InclusionProof::new(&checkpoint, ProofTarget::Transaction(transaction_digest))
+        .ok_or_else(|| anyhow::anyhow!("transaction not found in checkpoint"))?;
+
+    Ok(Bcs(proof))
+}
+
+pub const GET_OBJECT_PROOF_PATH: &str =
+    "/checkpoints/:checkpoint/proofs/objects/:object_id/version/:version";
+
+pub async fn get_object_proof<S: ReadStore>(
+    Path((checkpoint_id, object_id, version)): Path<(
+        CheckpointSequenceNumber,
+        ObjectID,
+        SequenceNumber,
+    )>,
+    State(state): State<S>,
+) -> Result<Bcs<InclusionProof>, AppError> {
+    let checkpoint = get_checkpoint_data(&state, checkpoint_id)?;
+    // The object may have been pruned since, so its digest is read from the effects that wrote it
+    let object_ref = checkpoint
+        .transactions
+        .iter()
+        .flat_map(|transaction| transaction.effects.all_changed_objects())
+        .map(|(object_ref, _, _)| object_ref)
+        .find(|(id, object_version, _)| *id == object_id && *object_version == version)
+        .ok_or_else(|| anyhow::anyhow!("object not written in checkpoint"))?;
+    let proof = InclusionProof::new(&checkpoint, ProofTarget::Object(object_ref))
+        .ok_or_else(|| anyhow::anyhow!("object overwritten later in checkpoint"))?;
+
+    Ok(Bcs(proof))
+}
+
+fn get_checkpoint_data<S: ReadStore>(
+    state: &S,
+    checkpoint_id: CheckpointSequenceNumber,
+) -> anyhow::Result<CheckpointData> {
+    let verified_summary = state
+        .get_checkpoint_by_sequence_number(checkpoint_id)?
+        .ok_or_else(|| anyhow::anyhow!("missing checkpoint"))?;
+    let checkpoint_contents = state
+        .get_checkpoint_contents_by_digest(&verified_summary.content_digest)?
+        .ok_or_else(|| anyhow::anyhow!("missing checkpoint contents"))?;
+
+    Ok(state.get_checkpoint_data(verified_summary, checkpoint_contents)?)
+}
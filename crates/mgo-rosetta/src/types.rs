@@ -341,6 +341,14 @@ impl From<MgoPublicKey> for PublicKey {
                 hex_bytes: Hex::from_bytes(&k.0),
                 curve_type: CurveType::ZkLogin, // inaccurate but added for completeness.
             },
+            MgoPublicKey::Passkey(k) => PublicKey {
+                hex_bytes: Hex::from_bytes(&k.0),
+                curve_type: CurveType::Passkey, // inaccurate but added for completeness.
+            },
+            MgoPublicKey::MultiSig(k) => PublicKey {
+                hex_bytes: Hex::from_bytes(k.0.as_ref()),
+                curve_type: CurveType::MultiSig, // inaccurate but added for completeness.
+            },
         }
     }
 }
@@ -362,6 +370,8 @@ pub enum CurveType {
     Edwards25519,
     Secp256r1,
     ZkLogin,
+    Passkey,
+    MultiSig,
 }
 
 impl From<CurveType> for SignatureScheme {
@@ -371,6 +381,8 @@ impl From<CurveType> for SignatureScheme {
             CurveType::Edwards25519 => SignatureScheme::ED25519,
             CurveType::Secp256r1 => SignatureScheme::Secp256r1,
             CurveType::ZkLogin => SignatureScheme::ZkLoginAuthenticator,
+            CurveType::Passkey => SignatureScheme::PasskeyAuthenticator,
+            CurveType::MultiSig => SignatureScheme::MultiSig,
         }
     }
 }
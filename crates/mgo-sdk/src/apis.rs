@@ -21,16 +21,18 @@ use mgo_json_rpc_api::{
 };
 use mgo_json_rpc_types::{
     Balance, Checkpoint, CheckpointId, Coin, CoinPage, DelegatedStake, DevInspectResults,
-    DryRunTransactionBlockResponse, DynamicFieldPage, EventFilter, EventPage, ObjectsPage,
-    ProtocolConfigResponse, MgoCoinMetadata, MgoCommittee, MgoEvent, MgoGetPastObjectRequest,
+    DryRunTransactionBlockResponse, DynamicFieldPage, EventFilter, EventPage,
+    EventSubscriptionItem, MgoCoinMetadata, MgoCommittee, MgoEvent, MgoGetPastObjectRequest,
     MgoMoveNormalizedModule, MgoObjectDataOptions, MgoObjectResponse, MgoObjectResponseQuery,
-    MgoPastObjectResponse, MgoTransactionBlockEffects, MgoTransactionBlockResponse,
-    MgoTransactionBlockResponseOptions, MgoTransactionBlockResponseQuery, TransactionBlocksPage,
-    TransactionFilter,
+    MgoPastObjectResponse, MgoStateOverrides, MgoTransactionBlockEffects,
+    MgoTransactionBlockResponse, MgoTransactionBlockResponseOptions,
+    MgoTransactionBlockResponseQuery, ObjectsPage, ProtocolConfigResponse, SubscriptionCursor,
+    TransactionBlocksPage, TransactionFilter, TransactionSubscriptionItem,
 };
 use mgo_json_rpc_types::{CheckpointPage, MgoLoadedChildObjectsResponse};
 use mgo_types::balance::Supply;
 use mgo_types::base_types::{ObjectID, SequenceNumber, MgoAddress, TransactionDigest};
+use mgo_types::digests::ChainIdentifier;
 use mgo_types::dynamic_field::DynamicFieldName;
 use mgo_types::event::EventID;
 use mgo_types::messages_checkpoint::CheckpointSequenceNumber;
@@ -526,6 +528,15 @@ impl ReadApi {
         Ok(self.api.http.get_chain_identifier().await?)
     }
 
+    /// Return the full chain identifier, i.e. the digest of the genesis checkpoint, or an error
+    /// upon failure. Use it to bind a [TransactionExpiration::ValidDuring] expiration to this chain.
+    ///
+    /// [TransactionExpiration::ValidDuring]: mgo_types::transaction::TransactionExpiration::ValidDuring
+    pub async fn get_genesis_chain_identifier(&self) -> MgoRpcResult<ChainIdentifier> {
+        let genesis = self.get_checkpoint(CheckpointId::SequenceNumber(0)).await?;
+        Ok(ChainIdentifier::from(genesis.digest))
+    }
+
     /// Return a checkpoint, or an error upon failure.
     ///
     /// A Mgo checkpoint is a sequence of transaction sets that a quorum of validators
@@ -609,6 +620,26 @@ impl ReadApi {
         Ok(subscription.map(|item| Ok(item?)))
     }
 
+    /// Subscribe to a stream of transactions, resuming from a cursor. The transactions executed
+    /// since the cursor are sent first, and transactions the fullnode has pruned are reported as
+    /// a [SubscriptionItem::Gap](mgo_json_rpc_types::SubscriptionItem::Gap).
+    ///
+    /// This is only available through WebSockets.
+    pub async fn subscribe_transaction_from(
+        &self,
+        filter: TransactionFilter,
+        cursor: SubscriptionCursor,
+    ) -> MgoRpcResult<impl Stream<Item = MgoRpcResult<TransactionSubscriptionItem>>> {
+        let Some(c) = &self.api.ws else {
+            return Err(Error::Subscription(
+                "Subscription only supported by WebSocket client.".to_string(),
+            ));
+        };
+        let subscription: Subscription<TransactionSubscriptionItem> =
+            c.subscribe_transaction_from(filter, cursor).await?;
+        Ok(subscription.map(|item| Ok(item?)))
+    }
+
     /// Return a map consisting of the move package name and the normalized module, or an error upon failure.
     pub async fn get_normalized_move_modules_by_package(
         &self,
@@ -639,7 +670,29 @@ impl ReadApi {
         Ok(self
             .api
             .http
-            .dry_run_transaction_block(Base64::from_bytes(&bcs::to_bytes(&tx)?))
+            .dry_run_transaction_block(Base64::from_bytes(&bcs::to_bytes(&tx)?), None)
+            .await?)
+    }
+
+    /// Dry run a transaction block against hypothetical state instead of the live state. Returns
+    /// an error upon failure.
+    ///
+    /// The overrides can replace the contents or balances of existing objects, inject packages
+    /// that have not been published yet, execute on behalf of another sender, and override the
+    /// clock timestamp and epoch. The response lists the overridden objects the transaction
+    /// touched, whose changes are relative to the overridden state.
+    pub async fn dry_run_transaction_block_with_overrides(
+        &self,
+        tx: TransactionData,
+        state_overrides: MgoStateOverrides,
+    ) -> MgoRpcResult<DryRunTransactionBlockResponse> {
+        Ok(self
+            .api
+            .http
+            .dry_run_transaction_block(
+                Base64::from_bytes(&bcs::to_bytes(&tx)?),
+                Some(state_overrides),
+            )
             .await?)
     }
 
@@ -661,6 +714,9 @@ impl ReadApi {
     ///
     /// Dev inspect's output includes a breakdown of results returned by every transaction
     /// in the block, as well as the transaction's effects.
+    /// Setting `trace` in `additional_args` also returns a trace of the Move execution: the
+    /// functions every command called, with their arguments, return values and gas, and the
+    /// objects the transaction read and wrote.
     ///
     /// To run an accurate simulation of a transaction and understand whether
     /// it will successfully validate and run,
@@ -1052,6 +1108,54 @@ impl EventApi {
         }
     }
 
+    /// Return a stream of events resuming from a cursor, or an error upon failure.
+    ///
+    /// The events emitted since the cursor are sent first, and events the fullnode has pruned
+    /// are reported as a [SubscriptionItem::Gap](mgo_json_rpc_types::SubscriptionItem::Gap).
+    /// Keeping the ID of the last event received allows resuming the stream after a disconnect.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// use futures::StreamExt;
+    /// use mgo_json_rpc_types::{EventFilter, SubscriptionCursor, SubscriptionItem};
+    /// use mgo_sdk::MgoClientBuilder;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), anyhow::Error> {
+    ///     let mgo = MgoClientBuilder::default()
+    ///         .ws_url("wss://rpc.mainnet.mangonetwork.io:443")
+    ///         .build("https://fullnode.mainnet.mangonetwork.io:443")
+    ///         .await?;
+    ///     let mut subscription = mgo
+    ///         .event_api()
+    ///         .subscribe_event_from(EventFilter::All(vec![]), SubscriptionCursor::Checkpoint(0))
+    ///         .await?;
+    ///     while let Some(item) = subscription.next().await {
+    ///         match item? {
+    ///             SubscriptionItem::Data(event) => println!("{:?}", event.id),
+    ///             SubscriptionItem::Gap(gap) => println!("Missed {:?}", gap),
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn subscribe_event_from(
+        &self,
+        filter: EventFilter,
+        cursor: SubscriptionCursor,
+    ) -> MgoRpcResult<impl Stream<Item = MgoRpcResult<EventSubscriptionItem>>> {
+        match &self.api.ws {
+            Some(c) => {
+                let subscription: Subscription<EventSubscriptionItem> =
+                    c.subscribe_event_from(filter, cursor).await?;
+                Ok(subscription.map(|item| Ok(item?)))
+            }
+            _ => Err(Error::Subscription(
+                "Subscription only supported by WebSocket client.".to_string(),
+            )),
+        }
+    }
+
     /// Return a list of events for the given transaction digest, or an error upon failure.
     pub async fn get_events(&self, digest: TransactionDigest) -> MgoRpcResult<Vec<MgoEvent>> {
         Ok(self.api.http.get_events(digest).await?)
@@ -75,6 +75,7 @@ impl SingleValidator {
             validator,
             consensus_adapter,
             Arc::new(ValidatorServiceMetrics::new_for_tests()),
+            None,
         ));
         Self {
             validator_service,
@@ -183,6 +183,7 @@ async fn run_publish(
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -213,6 +214,7 @@ async fn run_upgrade(
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
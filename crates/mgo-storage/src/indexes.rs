@@ -923,6 +923,21 @@ impl IndexStore {
         Ok(self.tables.transactions_seq.get(digest)?)
     }
 
+    /// Returns up to `limit` transactions in execution order, starting with `tx_seq`.
+    pub fn transactions_from(
+        &self,
+        tx_seq: TxSequenceNumber,
+        limit: usize,
+    ) -> MgoResult<Vec<(TxSequenceNumber, TransactionDigest)>> {
+        Ok(self
+            .tables
+            .transaction_order
+            .unbounded_iter()
+            .skip_to(&tx_seq)?
+            .take(limit)
+            .collect())
+    }
+
     pub fn all_events(
         &self,
         tx_seq: TxSequenceNumber,
@@ -0,0 +1,515 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::object_store::util::{delete_files, exists, find_all_dirs_with_epoch_prefix, get, put};
+use crate::object_store::ObjectStoreListExt;
+use anyhow::{anyhow, Context, Result};
+use bytes::Bytes;
+use fastcrypto::encoding::{Encoding, Hex};
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use futures::{StreamExt, TryStreamExt};
+use object_store::path::Path;
+use object_store::DynObjectStore;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use tracing::info;
+
+/// Name of the manifest file written to `epoch_N/` for every incremental db checkpoint.
+pub const DB_CHECKPOINT_MANIFEST_FILENAME: &str = "DB_CHECKPOINT_MANIFEST";
+/// Directory holding the content addressed files shared by all incremental db checkpoints.
+pub const DB_CHECKPOINT_FILES_DIR: &str = "files";
+
+/// Lists the files which make up the incremental db checkpoint of an epoch. The contents of a
+/// file are stored once under `files/<digest>` and shared by every checkpoint that contains it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DBCheckpointManifest {
+    pub epoch: u64,
+    pub files: Vec<DBCheckpointFile>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DBCheckpointFile {
+    /// Path of the file relative to the db checkpoint directory
+    pub path: String,
+    pub size: u64,
+    /// Hex encoded Blake2b256 digest of the file contents
+    pub digest: String,
+}
+
+impl DBCheckpointFile {
+    fn object_path(&self) -> Path {
+        file_object_path(&self.digest)
+    }
+}
+
+fn file_object_path(digest: &str) -> Path {
+    Path::from(DB_CHECKPOINT_FILES_DIR).child(digest)
+}
+
+pub fn db_checkpoint_manifest_path(epoch: u64) -> Path {
+    Path::from(format!("epoch_{}", epoch)).child(DB_CHECKPOINT_MANIFEST_FILENAME)
+}
+
+fn digest(bytes: &[u8]) -> String {
+    Hex::encode(Blake2b256::digest(bytes).digest)
+}
+
+pub async fn read_db_checkpoint_manifest(
+    store: &Arc<DynObjectStore>,
+    epoch: u64,
+) -> Result<DBCheckpointManifest> {
+    let bytes = store
+        .get(&db_checkpoint_manifest_path(epoch))
+        .await?
+        .bytes()
+        .await?;
+    let manifest: DBCheckpointManifest = serde_json::from_slice(&bytes)?;
+    if manifest.epoch != epoch {
+        return Err(anyhow!(
+            "Manifest of epoch: {} is for epoch: {}",
+            epoch,
+            manifest.epoch
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Returns the manifest of the latest incremental db checkpoint in the store before `epoch`, if any
+pub async fn find_previous_db_checkpoint_manifest(
+    store: &Arc<DynObjectStore>,
+    epoch: u64,
+) -> Result<Option<DBCheckpointManifest>> {
+    let epochs = find_all_dirs_with_epoch_prefix(store, None).await?;
+    for prev_epoch in epochs.range(..epoch).map(|(e, _)| *e).rev() {
+        if let Ok(manifest) = read_db_checkpoint_manifest(store, prev_epoch).await {
+            return Ok(Some(manifest));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the latest epoch whose incremental db checkpoint was fully uploaded to the store, i.e.
+/// which has both a manifest and the `success_marker` written once the upload completed, if any
+pub async fn find_latest_completed_db_checkpoint_epoch(
+    store: &Arc<DynObjectStore>,
+    success_marker: &str,
+) -> Result<Option<u64>> {
+    let epochs = find_all_dirs_with_epoch_prefix(store, None).await?;
+    for (epoch, dir) in epochs.iter().rev() {
+        if exists(store, &dir.child(success_marker)).await
+            && read_db_checkpoint_manifest(store, *epoch).await.is_ok()
+        {
+            return Ok(Some(*epoch));
+        }
+    }
+    Ok(None)
+}
+
+/// Uploads the db checkpoint at `dir` in `local_store` as the incremental db checkpoint of `epoch`.
+/// RocksDB never modifies an SST file once written, so SST files which are in `previous` with the
+/// same size are not read or uploaded again. Files starting with `_` are local markers and are
+/// skipped. The manifest is written only after all its files are uploaded.
+pub async fn upload_incremental_db_checkpoint(
+    dir: &Path,
+    epoch: u64,
+    local_store: &Arc<DynObjectStore>,
+    remote_store: &Arc<DynObjectStore>,
+    previous: Option<&DBCheckpointManifest>,
+    concurrency: NonZeroUsize,
+) -> Result<DBCheckpointManifest> {
+    let previous_files: BTreeMap<&str, &DBCheckpointFile> = previous
+        .map(|m| m.files.iter().map(|f| (f.path.as_str(), f)).collect())
+        .unwrap_or_default();
+    let previous_digests: HashSet<&str> =
+        previous_files.values().map(|f| f.digest.as_str()).collect();
+
+    let mut local_files = vec![];
+    let mut paths = local_store.list_objects(Some(dir)).await?;
+    while let Some(res) = paths.next().await {
+        let object_metadata = res?;
+        let relative_path = object_metadata
+            .location
+            .prefix_match(dir)
+            .ok_or_else(|| anyhow!("{} is not in {}", object_metadata.location, dir))?
+            .map(|part| part.as_ref().to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        if relative_path.is_empty() || relative_path.starts_with('_') {
+            continue;
+        }
+        local_files.push((
+            object_metadata.location,
+            relative_path,
+            object_metadata.size as u64,
+        ));
+    }
+
+    let mut reused = 0;
+    let mut files = vec![];
+    let mut to_upload = vec![];
+    for (location, path, size) in local_files {
+        match previous_files.get(path.as_str()) {
+            Some(file) if path.ends_with(".sst") && file.size == size => {
+                reused += 1;
+                files.push((*file).clone());
+            }
+            _ => to_upload.push((location, path, size)),
+        }
+    }
+
+    let uploaded: Vec<(DBCheckpointFile, bool)> = futures::stream::iter(to_upload)
+        .map(|(location, path, size)| {
+            let previous_digests = &previous_digests;
+            async move {
+                let bytes = get(local_store, &location).await?;
+                let file = DBCheckpointFile {
+                    path,
+                    size,
+                    digest: digest(&bytes),
+                };
+                // Empty files are recorded in the manifest only and created on restore
+                let upload = !bytes.is_empty() && !previous_digests.contains(file.digest.as_str());
+                if upload {
+                    put(remote_store, &file.object_path(), bytes).await?;
+                }
+                Ok::<_, anyhow::Error>((file, upload))
+            }
+        })
+        .boxed()
+        .buffer_unordered(concurrency.get())
+        .try_collect()
+        .await?;
+    let num_uploaded = uploaded.iter().filter(|(_, upload)| *upload).count();
+    files.extend(uploaded.into_iter().map(|(file, _)| file));
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let manifest = DBCheckpointManifest { epoch, files };
+    let bytes = serde_json::to_vec_pretty(&manifest)?;
+    put(
+        remote_store,
+        &db_checkpoint_manifest_path(epoch),
+        Bytes::from(bytes),
+    )
+    .await?;
+    info!(
+        "Uploaded incremental db checkpoint for epoch: {epoch}, files uploaded: {num_uploaded}, reused: {reused}"
+    );
+    Ok(manifest)
+}
+
+/// Deletes the files which are not referenced by any incremental db checkpoint manifest in the
+/// store, e.g. after old checkpoints were garbage collected or an upload was interrupted. Must not
+/// run concurrently with an upload as its files are unreferenced until its manifest is written.
+pub async fn garbage_collect_db_checkpoint_files(
+    store: &Arc<DynObjectStore>,
+    concurrency: NonZeroUsize,
+) -> Result<Vec<Path>> {
+    let mut referenced = HashSet::new();
+    for epoch in find_all_dirs_with_epoch_prefix(store, None).await?.keys() {
+        match read_db_checkpoint_manifest(store, *epoch).await {
+            Ok(manifest) => referenced.extend(manifest.files.into_iter().map(|f| f.digest)),
+            Err(e)
+                if matches!(
+                    e.downcast_ref::<object_store::Error>(),
+                    Some(object_store::Error::NotFound { .. })
+                ) => {}
+            // Never delete files when a manifest can't be read, they may still be referenced
+            Err(e) => return Err(e.context(format!("Failed to read manifest of epoch: {epoch}"))),
+        }
+    }
+
+    let mut unreferenced = vec![];
+    let mut paths = store
+        .list_objects(Some(&Path::from(DB_CHECKPOINT_FILES_DIR)))
+        .await?;
+    while let Some(res) = paths.next().await {
+        let object_metadata = res?;
+        let is_referenced = object_metadata
+            .location
+            .filename()
+            .map_or(false, |digest| referenced.contains(digest));
+        if !is_referenced {
+            unreferenced.push(object_metadata.location);
+        }
+    }
+    delete_files(&unreferenced, store, concurrency).await?;
+    info!(
+        "Garbage collected {} unreferenced db checkpoint files",
+        unreferenced.len()
+    );
+    Ok(unreferenced)
+}
+
+/// Downloads all files of the incremental db checkpoint of `epoch` into `path`, verifying the
+/// digest of each of them.
+pub async fn restore_incremental_db_checkpoint(
+    store: &Arc<DynObjectStore>,
+    epoch: u64,
+    path: &std::path::Path,
+    concurrency: NonZeroUsize,
+) -> Result<DBCheckpointManifest> {
+    let manifest = read_db_checkpoint_manifest(store, epoch)
+        .await
+        .with_context(|| format!("Failed to read db checkpoint manifest of epoch: {epoch}"))?;
+    futures::stream::iter(manifest.files.iter())
+        .map(|file| async move {
+            let bytes = if file.size == 0 {
+                Bytes::new()
+            } else {
+                get(store, &file.object_path()).await?
+            };
+            if bytes.len() as u64 != file.size || digest(&bytes) != file.digest {
+                return Err(anyhow!(
+                    "Digest mismatch for file: {} in db checkpoint of epoch: {}",
+                    file.path,
+                    epoch
+                ));
+            }
+            let file_path = path.join(&file.path);
+            if let Some(parent) = file_path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&file_path, bytes).await?;
+            Ok(())
+        })
+        .boxed()
+        .buffer_unordered(concurrency.get())
+        .try_collect::<Vec<()>>()
+        .await?;
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::object_store::db_checkpoint::{
+        find_latest_completed_db_checkpoint_epoch, find_previous_db_checkpoint_manifest,
+        garbage_collect_db_checkpoint_files, restore_incremental_db_checkpoint,
+        upload_incremental_db_checkpoint, DB_CHECKPOINT_FILES_DIR,
+    };
+    use crate::object_store::util::{delete_recursively, put};
+    use bytes::Bytes;
+    use mgo_config::object_storage_config::{ObjectStoreConfig, ObjectStoreType};
+    use object_store::path::Path;
+    use std::fs;
+    use std::num::NonZeroUsize;
+    use tempfile::TempDir;
+
+    fn num_remote_files(remote_path: &std::path::Path) -> usize {
+        fs::read_dir(remote_path.join(DB_CHECKPOINT_FILES_DIR))
+            .unwrap()
+            .count()
+    }
+
+    #[tokio::test]
+    pub async fn test_incremental_upload_and_restore() -> anyhow::Result<()> {
+        let local = TempDir::new()?;
+        let local_path = local.path();
+        let remote = TempDir::new()?;
+        let remote_path = remote.path();
+        let concurrency = NonZeroUsize::new(2).unwrap();
+
+        let local_store = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(local_path.to_path_buf()),
+            ..Default::default()
+        }
+        .make()?;
+        let remote_store = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(remote_path.to_path_buf()),
+            ..Default::default()
+        }
+        .make()?;
+
+        let epoch_0 = local_path.join("epoch_0");
+        fs::create_dir_all(epoch_0.join("store"))?;
+        fs::write(epoch_0.join("store").join("000001.sst"), b"sst 1")?;
+        fs::write(epoch_0.join("store").join("000002.sst"), b"sst 2")?;
+        fs::write(epoch_0.join("store").join("CURRENT"), b"MANIFEST-000001")?;
+        fs::write(epoch_0.join("store").join("LOCK"), b"")?;
+        fs::write(epoch_0.join("_UPLOAD_COMPLETED"), b"")?;
+
+        let manifest_0 = upload_incremental_db_checkpoint(
+            &Path::from("epoch_0"),
+            0,
+            &local_store,
+            &remote_store,
+            None,
+            concurrency,
+        )
+        .await?;
+        assert_eq!(manifest_0.files.len(), 4);
+        // The empty LOCK file is not uploaded
+        assert_eq!(num_remote_files(remote_path), 3);
+
+        // An SST file was compacted away and a new one was added
+        let epoch_1 = local_path.join("epoch_1");
+        fs::create_dir_all(epoch_1.join("store"))?;
+        fs::write(epoch_1.join("store").join("000002.sst"), b"sst 2")?;
+        fs::write(epoch_1.join("store").join("000003.sst"), b"sst 3")?;
+        fs::write(epoch_1.join("store").join("CURRENT"), b"MANIFEST-000002")?;
+        fs::write(epoch_1.join("store").join("LOCK"), b"")?;
+
+        let previous = find_previous_db_checkpoint_manifest(&remote_store, 1).await?;
+        assert_eq!(previous.as_ref(), Some(&manifest_0));
+        let manifest_1 = upload_incremental_db_checkpoint(
+            &Path::from("epoch_1"),
+            1,
+            &local_store,
+            &remote_store,
+            previous.as_ref(),
+            concurrency,
+        )
+        .await?;
+        assert_eq!(manifest_1.files.len(), 4);
+        // Only the new SST file and the changed CURRENT file are uploaded
+        assert_eq!(num_remote_files(remote_path), 5);
+
+        let restored = TempDir::new()?;
+        restore_incremental_db_checkpoint(&remote_store, 1, restored.path(), concurrency).await?;
+        let restored_store = restored.path().join("store");
+        assert_eq!(fs::read(restored_store.join("000002.sst"))?, b"sst 2");
+        assert_eq!(fs::read(restored_store.join("000003.sst"))?, b"sst 3");
+        assert_eq!(
+            fs::read(restored_store.join("CURRENT"))?,
+            b"MANIFEST-000002"
+        );
+        assert!(restored_store.join("LOCK").exists());
+        assert!(!restored_store.join("000001.sst").exists());
+
+        // Nothing is unreferenced while both checkpoints exist
+        let deleted = garbage_collect_db_checkpoint_files(&remote_store, concurrency).await?;
+        assert!(deleted.is_empty());
+
+        // Dropping epoch 0 leaves its compacted SST file and old CURRENT file unreferenced
+        delete_recursively(&Path::from("epoch_0"), &remote_store, concurrency).await?;
+        let deleted = garbage_collect_db_checkpoint_files(&remote_store, concurrency).await?;
+        assert_eq!(deleted.len(), 2);
+        assert_eq!(num_remote_files(remote_path), 3);
+
+        let restored = TempDir::new()?;
+        restore_incremental_db_checkpoint(&remote_store, 1, restored.path(), concurrency).await?;
+        assert_eq!(
+            fs::read(restored.path().join("store").join("000003.sst"))?,
+            b"sst 3"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    pub async fn test_restore_detects_corruption() -> anyhow::Result<()> {
+        let local = TempDir::new()?;
+        let remote = TempDir::new()?;
+        let concurrency = NonZeroUsize::new(1).unwrap();
+        let local_store = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(local.path().to_path_buf()),
+            ..Default::default()
+        }
+        .make()?;
+        let remote_store = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(remote.path().to_path_buf()),
+            ..Default::default()
+        }
+        .make()?;
+
+        fs::create_dir_all(local.path().join("epoch_0"))?;
+        fs::write(local.path().join("epoch_0").join("000001.sst"), b"sst 1")?;
+        let manifest = upload_incremental_db_checkpoint(
+            &Path::from("epoch_0"),
+            0,
+            &local_store,
+            &remote_store,
+            None,
+            concurrency,
+        )
+        .await?;
+        fs::write(
+            remote
+                .path()
+                .join(DB_CHECKPOINT_FILES_DIR)
+                .join(&manifest.files[0].digest),
+            b"sst 2",
+        )?;
+
+        let restored = TempDir::new()?;
+        assert!(
+            restore_incremental_db_checkpoint(&remote_store, 0, restored.path(), concurrency)
+                .await
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    pub async fn test_find_latest_completed_db_checkpoint_epoch() -> anyhow::Result<()> {
+        let local = TempDir::new()?;
+        let remote = TempDir::new()?;
+        let concurrency = NonZeroUsize::new(1).unwrap();
+        let local_store = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(local.path().to_path_buf()),
+            ..Default::default()
+        }
+        .make()?;
+        let remote_store = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(remote.path().to_path_buf()),
+            ..Default::default()
+        }
+        .make()?;
+        let success_marker = "_SUCCESS";
+        assert_eq!(
+            find_latest_completed_db_checkpoint_epoch(&remote_store, success_marker).await?,
+            None
+        );
+
+        for epoch in 0..2 {
+            let dir = format!("epoch_{epoch}");
+            fs::create_dir_all(local.path().join(&dir))?;
+            fs::write(local.path().join(&dir).join("000001.sst"), b"sst 1")?;
+            upload_incremental_db_checkpoint(
+                &Path::from(dir.as_str()),
+                epoch,
+                &local_store,
+                &remote_store,
+                None,
+                concurrency,
+            )
+            .await?;
+        }
+        put(
+            &remote_store,
+            &Path::from("epoch_0").child(success_marker),
+            Bytes::new(),
+        )
+        .await?;
+        // Epoch 2 has a success marker but no manifest
+        put(
+            &remote_store,
+            &Path::from("epoch_2").child(success_marker),
+            Bytes::new(),
+        )
+        .await?;
+
+        // The upload of epoch 1 has not completed yet
+        assert_eq!(
+            find_latest_completed_db_checkpoint_epoch(&remote_store, success_marker).await?,
+            Some(0)
+        );
+        put(
+            &remote_store,
+            &Path::from("epoch_1").child(success_marker),
+            Bytes::new(),
+        )
+        .await?;
+        assert_eq!(
+            find_latest_completed_db_checkpoint_epoch(&remote_store, success_marker).await?,
+            Some(1)
+        );
+        Ok(())
+    }
+}
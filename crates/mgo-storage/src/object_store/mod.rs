@@ -9,6 +9,7 @@ use object_store::path::Path;
 use object_store::{DynObjectStore, ObjectMeta};
 use std::sync::Arc;
 
+pub mod db_checkpoint;
 pub mod http;
 pub mod util;
 
@@ -150,6 +150,7 @@ impl ValidatorConfigBuilder {
             network_address,
             metrics_address: validator.metrics_address,
             admin_interface_port: local_ip_utils::get_available_port(&localhost),
+            admin_interface_auth_token_path: None,
             json_rpc_address: local_ip_utils::new_tcp_address_for_testing(&localhost)
                 .to_socket_addr()
                 .unwrap(),
@@ -174,6 +175,8 @@ impl ValidatorConfigBuilder {
             name_service_registry_id: None,
             name_service_reverse_registry_id: None,
             transaction_deny_config: Default::default(),
+            transaction_deny_config_path: None,
+            transaction_deny_audit_log_path: None,
             certificate_deny_config: Default::default(),
             state_debug_dump_config: Default::default(),
             state_archive_write_config: StateArchiveConfig::default(),
@@ -189,6 +192,7 @@ impl ValidatorConfigBuilder {
                 .unwrap_or(3600),
             zklogin_oauth_providers: default_zklogin_oauth_providers(),
             overload_threshold_config: self.overload_threshold_config.unwrap_or_default(),
+            admission_control_config: None,
             run_with_range: None,
         }
     }
@@ -397,6 +401,7 @@ impl FullnodeConfigBuilder {
             admin_interface_port: self
                 .admin_interface_port
                 .unwrap_or(local_ip_utils::get_available_port(&localhost)),
+            admin_interface_auth_token_path: None,
             json_rpc_address: self.json_rpc_address.unwrap_or(json_rpc_address),
             consensus_config: None,
             enable_event_processing: true, // This is unused.
@@ -422,6 +427,8 @@ impl FullnodeConfigBuilder {
             name_service_registry_id: None,
             name_service_reverse_registry_id: None,
             transaction_deny_config: Default::default(),
+            transaction_deny_config_path: None,
+            transaction_deny_audit_log_path: None,
             certificate_deny_config: Default::default(),
             state_debug_dump_config: Default::default(),
             state_archive_write_config: StateArchiveConfig::default(),
@@ -435,6 +442,7 @@ impl FullnodeConfigBuilder {
             jwk_fetch_interval_seconds: 3600,
             zklogin_oauth_providers: default_zklogin_oauth_providers(),
             overload_threshold_config: Default::default(),
+            admission_control_config: None,
             run_with_range: self.run_with_range,
         }
     }
@@ -6,8 +6,9 @@ use crate::{
     db_tool::{execute_db_tool_command, print_db_all_tables, DbToolCommand},
     download_db_snapshot, download_formal_snapshot, dump_checkpoints_from_archive,
     get_latest_available_epoch, get_object, get_transaction_block, make_clients, pkg_dump,
-    restore_from_db_checkpoint, state_sync_from_archive, verify_archive,
-    verify_archive_by_checksum, ConciseObjectOutput, GroupedObjectOutput, VerboseObjectOutput,
+    restore_from_db_checkpoint, restore_from_incremental_db_checkpoint, state_sync_from_archive,
+    verify_archive, verify_archive_by_checksum, ConciseObjectOutput, GroupedObjectOutput,
+    VerboseObjectOutput,
 };
 use anyhow::Result;
 use std::env;
@@ -246,12 +247,22 @@ pub enum ToolCommand {
         args: anemo_cli::Args,
     },
 
+    /// Restores the node db from a local db checkpoint directory, or from an incremental
+    /// db checkpoint in the object store if `--db-checkpoint-path` is not set
     #[command(name = "restore-db")]
     RestoreFromDBCheckpoint {
         #[arg(long = "config-path")]
         config_path: PathBuf,
         #[arg(long = "db-checkpoint-path")]
-        db_checkpoint_path: PathBuf,
+        db_checkpoint_path: Option<PathBuf>,
+        /// Epoch of the incremental db checkpoint to restore. Defaults to the latest
+        /// epoch whose db checkpoint was fully uploaded to the object store.
+        #[arg(long = "epoch")]
+        epoch: Option<u64>,
+        #[command(flatten)]
+        object_store_config: ObjectStoreConfig,
+        #[arg(long = "num-parallel-downloads", default_value_t = 20)]
+        num_parallel_downloads: usize,
     },
 
     #[clap(
@@ -584,9 +595,22 @@ impl ToolCommand {
             ToolCommand::RestoreFromDBCheckpoint {
                 config_path,
                 db_checkpoint_path,
+                epoch,
+                object_store_config,
+                num_parallel_downloads,
             } => {
                 let config = mgo_config::NodeConfig::load(config_path)?;
-                restore_from_db_checkpoint(&config, &db_checkpoint_path).await?;
+                if let Some(db_checkpoint_path) = db_checkpoint_path {
+                    restore_from_db_checkpoint(&config, &db_checkpoint_path).await?;
+                } else {
+                    restore_from_incremental_db_checkpoint(
+                        &config,
+                        &object_store_config,
+                        epoch,
+                        num_parallel_downloads,
+                    )
+                    .await?;
+                }
             }
             ToolCommand::DownloadFormalSnapshot {
                 epoch,
@@ -48,10 +48,14 @@ use mgo_config::object_storage_config::{ObjectStoreConfig, ObjectStoreType};
 use mgo_core::authority::authority_store_tables::AuthorityPerpetualTables;
 use mgo_core::authority::AuthorityStore;
 use mgo_core::checkpoints::CheckpointStore;
+use mgo_core::db_checkpoint_handler::SUCCESS_MARKER;
 use mgo_core::epoch::committee_store::CommitteeStore;
 use mgo_core::storage::RocksDbStore;
 use mgo_snapshot::reader::StateSnapshotReaderV1;
 use mgo_snapshot::setup_db_state;
+use mgo_storage::object_store::db_checkpoint::{
+    find_latest_completed_db_checkpoint_epoch, restore_incremental_db_checkpoint,
+};
 use mgo_storage::object_store::util::{copy_file, exists, get_path};
 use mgo_storage::object_store::ObjectStoreGetExt;
 use mgo_storage::verify_checkpoint_range;
@@ -640,6 +644,35 @@ pub async fn restore_from_db_checkpoint(
     Ok(())
 }
 
+pub async fn restore_from_incremental_db_checkpoint(
+    config: &NodeConfig,
+    object_store_config: &ObjectStoreConfig,
+    epoch: Option<u64>,
+    num_parallel_downloads: usize,
+) -> Result<(), anyhow::Error> {
+    let store = object_store_config.make()?;
+    let epoch = match epoch {
+        Some(epoch) => epoch,
+        None => find_latest_completed_db_checkpoint_epoch(&store, SUCCESS_MARKER)
+            .await?
+            .ok_or_else(|| anyhow!("No fully uploaded db checkpoints found in object store"))?,
+    };
+    info!("Restoring incremental db checkpoint for epoch: {epoch}");
+    let manifest = restore_incremental_db_checkpoint(
+        &store,
+        epoch,
+        &config.db_path(),
+        NonZeroUsize::new(num_parallel_downloads)
+            .ok_or_else(|| anyhow!("Number of parallel downloads must be positive"))?,
+    )
+    .await?;
+    info!(
+        "Restored {} files of db checkpoint for epoch: {epoch}",
+        manifest.files.len()
+    );
+    Ok(())
+}
+
 fn start_summary_sync(
     perpetual_db: Arc<AuthorityPerpetualTables>,
     committee_store: Arc<CommitteeStore>,
@@ -30,7 +30,8 @@ use mgo_types::object::{Object, Owner};
 use mgo_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use mgo_types::mgo_system_state::MGO_SYSTEM_MODULE_NAME;
 use mgo_types::transaction::{
-    Argument, CallArg, Command, InputObjectKind, ObjectArg, TransactionData, TransactionKind,
+    Argument, CallArg, Command, InputObjectKind, ObjectArg, ProgrammableTransaction,
+    TransactionData, TransactionKind,
 };
 use mgo_types::{coin, fp_ensure, MGO_FRAMEWORK_PACKAGE_ID, MGO_SYSTEM_PACKAGE_ID};
 
@@ -270,7 +271,19 @@ impl TransactionBuilder {
             call_args,
         )
         .await?;
-        let pt = builder.finish();
+        self.programmable(signer, builder.finish(), gas, gas_budget)
+            .await
+    }
+
+    /// Pays for the programmable transaction `pt` with `gas`, or if it is not provided, with a gas
+    /// object of `signer` that is not one of the transaction's inputs.
+    pub async fn programmable(
+        &self,
+        signer: MgoAddress,
+        pt: ProgrammableTransaction,
+        gas: Option<ObjectID>,
+        gas_budget: u64,
+    ) -> anyhow::Result<TransactionData> {
         let input_objects = pt
             .input_objects()?
             .iter()
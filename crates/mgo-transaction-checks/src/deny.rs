@@ -2,14 +2,22 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use fastcrypto_zkp::bn254::zk_login::OIDCProvider;
-use mgo_config::transaction_deny_config::TransactionDenyConfig;
+use mgo_config::transaction_deny_config::{TransactionDenyConfig, TransactionDenyPredicate};
 use mgo_types::{
-    base_types::ObjectRef,
+    base_types::{ObjectID, ObjectRef},
     error::{MgoError, MgoResult, UserInputError},
+    gas_coin::GAS,
+    object::Object,
     signature::GenericSignature,
     storage::BackingPackageStore,
-    transaction::{Command, InputObjectKind, TransactionData, TransactionDataAPI},
+    transaction::{
+        Argument, CallArg, Command, InputObjectKind, InputObjects, ObjectArg, TransactionData,
+        TransactionDataAPI, TransactionKind,
+    },
+    TypeTag,
 };
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
 macro_rules! deny_if_true {
     ($cond:expr, $msg:expr) => {
         if ($cond) {
@@ -42,9 +50,36 @@ pub fn check_transaction_for_signing(
 
     check_receiving_objects(filter_config, receiving_objects)?;
 
+    check_move_functions(filter_config, tx_data)?;
+
+    Ok(())
+}
+
+/// Check the rules of the deny config that depend on the contents of the input objects, once
+/// they are loaded.
+pub fn check_input_objects_for_signing(
+    tx_data: &TransactionData,
+    input_objects: &InputObjects,
+    filter_config: &TransactionDenyConfig,
+) -> MgoResult {
+    let now_ms = now_ms();
+    if filter_config.active_rules(now_ms).next().is_none() {
+        return Ok(());
+    }
+
+    check_input_object_types(filter_config, input_objects, now_ms)?;
+
+    check_coin_transfer_amounts(filter_config, tx_data, input_objects, now_ms)?;
+
     Ok(())
 }
 
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
 fn check_receiving_objects(
     filter_config: &TransactionDenyConfig,
     receiving_objects: &[ObjectRef],
@@ -209,3 +244,229 @@ fn check_package_dependencies(
     }
     Ok(())
 }
+
+fn check_move_functions(
+    filter_config: &TransactionDenyConfig,
+    tx_data: &TransactionData,
+) -> MgoResult {
+    let now_ms = now_ms();
+    for predicate in filter_config.active_rules(now_ms) {
+        let TransactionDenyPredicate::MoveFunction {
+            package,
+            module,
+            function,
+        } = predicate
+        else {
+            continue;
+        };
+        for command in tx_data.kind().iter_commands() {
+            if let Command::MoveCall(call) = command {
+                deny_if_true!(
+                    call.package == *package
+                        && call.module.as_str() == module.as_str()
+                        && call.function.as_str() == function.as_str(),
+                    format!(
+                        "Calls to {}::{}::{} are temporarily disabled",
+                        package, module, function
+                    )
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_input_object_types(
+    filter_config: &TransactionDenyConfig,
+    input_objects: &InputObjects,
+    now_ms: u64,
+) -> MgoResult {
+    for predicate in filter_config.active_rules(now_ms) {
+        let TransactionDenyPredicate::InputObjectType { object_type } = predicate else {
+            continue;
+        };
+        for object in input_objects.iter_objects() {
+            let Some(tag) = object.struct_tag() else {
+                continue;
+            };
+            deny_if_true!(
+                TransactionDenyPredicate::object_type_matches(object_type, &tag),
+                format!(
+                    "Access to objects of type {} is temporarily disabled",
+                    object_type
+                )
+            );
+        }
+    }
+    Ok(())
+}
+
+fn check_coin_transfer_amounts(
+    filter_config: &TransactionDenyConfig,
+    tx_data: &TransactionData,
+    input_objects: &InputObjects,
+    now_ms: u64,
+) -> MgoResult {
+    let limits: Vec<_> = filter_config
+        .active_rules(now_ms)
+        .filter_map(|predicate| match predicate {
+            TransactionDenyPredicate::CoinTransferAmount {
+                coin_type,
+                max_amount,
+            } => Some((coin_type, *max_amount)),
+            _ => None,
+        })
+        .collect();
+    if limits.is_empty() {
+        return Ok(());
+    }
+    let TransactionKind::ProgrammableTransaction(pt) = tx_data.kind() else {
+        return Ok(());
+    };
+    let objects: HashMap<ObjectID, &Object> = input_objects
+        .iter_objects()
+        .map(|object| (object.id(), object))
+        .collect();
+    let gas_coin_value: u64 = tx_data
+        .gas()
+        .iter()
+        .filter_map(|(id, _, _)| objects.get(id))
+        .filter(|object| object.is_gas_coin())
+        .map(|object| object.get_coin_value_unsafe())
+        .sum();
+    let input_coin = |arg: &Argument| -> Option<(TypeTag, u64)> {
+        match arg {
+            Argument::GasCoin => Some((GAS::type_tag(), gas_coin_value)),
+            Argument::Input(i) => match pt.inputs.get(*i as usize)? {
+                CallArg::Object(ObjectArg::ImmOrOwnedObject((id, _, _))) => {
+                    let object = objects.get(id)?;
+                    Some((object.coin_type_maybe()?, object.get_coin_value_unsafe()))
+                }
+                _ => None,
+            },
+            // Results are only known once the transaction is executed.
+            Argument::Result(_) | Argument::NestedResult(..) => None,
+        }
+    };
+    let is_limited = |coin_type: &TypeTag| limits.iter().any(|(limited, _)| *limited == coin_type);
+
+    // The type of each known coin, and the part of its value not counted as transferred yet.
+    // Amounts split off a coin count right away, since the new coins can also be transferred by
+    // Move calls, and merging a coin into another moves its uncounted value along.
+    let mut uncounted: HashMap<Argument, (TypeTag, u64)> = HashMap::new();
+    // Amounts transferred by the whole transaction, per coin type.
+    let mut transferred: HashMap<TypeTag, u64> = HashMap::new();
+    for (i, command) in pt.commands.iter().enumerate() {
+        match command {
+            Command::SplitCoins(coin, amounts) => {
+                let Some((coin_type, value)) =
+                    uncounted.get(coin).cloned().or_else(|| input_coin(coin))
+                else {
+                    continue;
+                };
+                let mut split = 0u64;
+                for (j, arg) in amounts.iter().enumerate() {
+                    let amount = match arg {
+                        Argument::Input(k) => match pt.inputs.get(*k as usize) {
+                            Some(CallArg::Pure(bytes)) => bcs::from_bytes::<u64>(bytes).ok(),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    let Some(amount) = amount else {
+                        deny_if_true!(
+                            is_limited(&coin_type),
+                            format!(
+                                "Transfers of unknown amounts of {} are temporarily disabled",
+                                coin_type
+                            )
+                        );
+                        continue;
+                    };
+                    split = split.saturating_add(amount);
+                    uncounted.insert(
+                        Argument::NestedResult(i as u16, j as u16),
+                        (coin_type.clone(), 0),
+                    );
+                }
+                let total = transferred.entry(coin_type.clone()).or_default();
+                *total = total.saturating_add(split);
+                uncounted.insert(*coin, (coin_type, value.saturating_sub(split)));
+            }
+            Command::MergeCoins(target, sources) => {
+                // All the coins merged have the type of the coin merged into.
+                let mut merged: Option<(TypeTag, u64)> = None;
+                for source in sources {
+                    if let Some((coin_type, value)) =
+                        uncounted.remove(source).or_else(|| input_coin(source))
+                    {
+                        let total = merged.get_or_insert((coin_type.clone(), 0));
+                        total.1 = total.1.saturating_add(value);
+                        // A coin merged away has nothing left to transfer.
+                        uncounted.insert(*source, (coin_type, 0));
+                    }
+                }
+                let Some((coin_type, merged)) = merged else {
+                    continue;
+                };
+                match uncounted
+                    .get(target)
+                    .cloned()
+                    .or_else(|| input_coin(target))
+                {
+                    Some((_, value)) => {
+                        uncounted.insert(*target, (coin_type, value.saturating_add(merged)));
+                    }
+                    // The coin merged into is only known once the transaction is executed, and
+                    // can be transferred later on, so the merged coins count as transferred.
+                    None => {
+                        let total = transferred.entry(coin_type).or_default();
+                        *total = total.saturating_add(merged);
+                    }
+                }
+            }
+            // Move calls can transfer any coin they are passed, also by reference, and vectors
+            // of coins can only be used by Move calls, so the whole value of these coins counts
+            // as transferred.
+            Command::TransferObjects(coins, _) | Command::MakeMoveVec(_, coins) => {
+                count_transferred(coins, &input_coin, &mut uncounted, &mut transferred)
+            }
+            Command::MoveCall(call) => count_transferred(
+                &call.arguments,
+                &input_coin,
+                &mut uncounted,
+                &mut transferred,
+            ),
+            Command::Publish(..) | Command::Upgrade(..) => continue,
+        }
+    }
+
+    for (coin_type, max_amount) in &limits {
+        deny_if_true!(
+            transferred
+                .get(*coin_type)
+                .is_some_and(|amount| amount > max_amount),
+            format!(
+                "Transfers of more than {} {} are temporarily disabled",
+                max_amount, coin_type
+            )
+        );
+    }
+    Ok(())
+}
+
+/// Counts the uncounted value of the known coins among `args` as transferred.
+fn count_transferred(
+    args: &[Argument],
+    input_coin: impl Fn(&Argument) -> Option<(TypeTag, u64)>,
+    uncounted: &mut HashMap<Argument, (TypeTag, u64)>,
+    transferred: &mut HashMap<TypeTag, u64>,
+) {
+    for arg in args {
+        if let Some((coin_type, value)) = uncounted.remove(arg).or_else(|| input_coin(arg)) {
+            let total = transferred.entry(coin_type.clone()).or_default();
+            *total = total.saturating_add(value);
+            uncounted.insert(*arg, (coin_type, 0));
+        }
+    }
+}
@@ -135,6 +135,8 @@ impl TransactionalAdapter for ValidatorWithFullnode {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await
     }
@@ -646,7 +646,12 @@ impl<T: MgoPublicKey> From<&T> for MgoAddress {
 }
 
 impl From<&PublicKey> for MgoAddress {
+    /// The address of a multisig member of a multisig is the address of the member multisig
+    /// itself, see [struct MultiSigPublicIdentifier].
     fn from(pk: &PublicKey) -> Self {
+        if let PublicKey::MultiSig(id) = pk {
+            return id.0;
+        }
         let mut hasher = DefaultHash::default();
         hasher.update([pk.flag()]);
         hasher.update(pk);
@@ -663,7 +668,8 @@ impl From<&MultiSigPublicKey> for MgoAddress {
     /// || ... || flag_n || pk_n || weight_n`.
     ///
     /// When flag_i is ZkLogin, pk_i refers to [struct ZkLoginPublicIdentifier]
-    /// derived from padded address seed in bytes and iss.
+    /// derived from padded address seed in bytes and iss. When flag_i is
+    /// MultiSig, pk_i is the address of the member multisig.
     fn from(multisig_pk: &MultiSigPublicKey) -> Self {
         let mut hasher = DefaultHash::default();
         hasher.update([SignatureScheme::MultiSig.flag()]);
@@ -710,6 +716,7 @@ impl TryFrom<&GenericSignature> for MgoAddress {
             GenericSignature::ZkLoginAuthenticator(zklogin) => {
                 MgoAddress::try_from_unpadded(&zklogin.inputs)
             }
+            GenericSignature::PasskeyAuthenticator(passkey) => Ok((&passkey.get_pk()?).into()),
         }
     }
 }
@@ -44,6 +44,7 @@ use strum::EnumString;
 use crate::base_types::{AuthorityName, ConciseableName, MgoAddress};
 use crate::committee::{Committee, EpochId, StakeUnit};
 use crate::error::{MgoError, MgoResult};
+use crate::multisig::MultiSigPublicKey;
 use crate::signature::GenericSignature;
 use crate::mgo_serde::{Readable, MgoBitmap};
 pub use enum_dispatch::enum_dispatch;
@@ -251,6 +252,8 @@ pub enum PublicKey {
     Secp256k1(Secp256k1PublicKeyAsBytes),
     Secp256r1(Secp256r1PublicKeyAsBytes),
     ZkLogin(ZkLoginPublicIdentifier),
+    Passkey(Secp256r1PublicKeyAsBytes),
+    MultiSig(MultiSigPublicIdentifier),
 }
 
 /// A wrapper struct to retrofit in [enum PublicKey] for zkLogin.
@@ -277,6 +280,19 @@ impl ZkLoginPublicIdentifier {
         Ok(Self(bytes))
     }
 }
+
+/// A wrapper struct to retrofit in [enum PublicKey] for a multisig that is a member of another
+/// multisig. It is the address of the member multisig, whose public key is only known once it
+/// signs. Useful to construct [struct MultiSigPublicKey].
+#[derive(Clone, Debug, PartialEq, Eq, JsonSchema, Serialize, Deserialize)]
+pub struct MultiSigPublicIdentifier(pub MgoAddress);
+
+impl From<&MultiSigPublicKey> for MultiSigPublicIdentifier {
+    fn from(multisig_pk: &MultiSigPublicKey) -> Self {
+        Self(multisig_pk.into())
+    }
+}
+
 impl AsRef<[u8]> for PublicKey {
     fn as_ref(&self) -> &[u8] {
         match self {
@@ -284,6 +300,8 @@ impl AsRef<[u8]> for PublicKey {
             PublicKey::Secp256k1(pk) => &pk.0,
             PublicKey::Secp256r1(pk) => &pk.0,
             PublicKey::ZkLogin(z) => &z.0,
+            PublicKey::Passkey(pk) => &pk.0,
+            PublicKey::MultiSig(id) => id.0.as_ref(),
         }
     }
 }
@@ -315,6 +333,16 @@ impl EncodeDecodeBase64 for PublicKey {
                         bytes.get(1..).ok_or_else(|| eyre!("Invalid length"))?,
                     )?;
                     Ok(PublicKey::Secp256r1((&pk).into()))
+                } else if x == &SignatureScheme::PasskeyAuthenticator.flag() {
+                    let pk = Secp256r1PublicKey::from_bytes(
+                        bytes.get(1..).ok_or_else(|| eyre!("Invalid length"))?,
+                    )?;
+                    Ok(PublicKey::Passkey((&pk).into()))
+                } else if x == &SignatureScheme::MultiSig.flag() {
+                    let address = MgoAddress::from_bytes(
+                        bytes.get(1..).ok_or_else(|| eyre!("Invalid length"))?,
+                    )?;
+                    Ok(PublicKey::MultiSig(MultiSigPublicIdentifier(address)))
                 } else {
                     Err(eyre!("Invalid flag byte"))
                 }
@@ -343,6 +371,9 @@ impl PublicKey {
             SignatureScheme::Secp256r1 => Ok(PublicKey::Secp256r1(
                 (&Secp256r1PublicKey::from_bytes(key_bytes)?).into(),
             )),
+            SignatureScheme::PasskeyAuthenticator => Ok(PublicKey::Passkey(
+                (&Secp256r1PublicKey::from_bytes(key_bytes)?).into(),
+            )),
             _ => Err(eyre!("Unsupported curve")),
         }
     }
@@ -353,6 +384,8 @@ impl PublicKey {
             PublicKey::Secp256k1(_) => Secp256k1MgoSignature::SCHEME,
             PublicKey::Secp256r1(_) => Secp256r1MgoSignature::SCHEME,
             PublicKey::ZkLogin(_) => SignatureScheme::ZkLoginAuthenticator,
+            PublicKey::Passkey(_) => SignatureScheme::PasskeyAuthenticator,
+            PublicKey::MultiSig(_) => SignatureScheme::MultiSig,
         }
     }
 
@@ -1629,6 +1662,7 @@ pub enum SignatureScheme {
     BLS12381, // This is currently not supported for user Mgo Address.
     MultiSig,
     ZkLoginAuthenticator,
+    PasskeyAuthenticator,
 }
 
 impl SignatureScheme {
@@ -1640,6 +1674,7 @@ impl SignatureScheme {
             SignatureScheme::MultiSig => 0x03,
             SignatureScheme::BLS12381 => 0x04, // This is currently not supported for user Mgo Address.
             SignatureScheme::ZkLoginAuthenticator => 0x05,
+            SignatureScheme::PasskeyAuthenticator => 0x06,
         }
     }
 
@@ -1658,6 +1693,7 @@ impl SignatureScheme {
             0x03 => Ok(SignatureScheme::MultiSig),
             0x04 => Ok(SignatureScheme::BLS12381),
             0x05 => Ok(SignatureScheme::ZkLoginAuthenticator),
+            0x06 => Ok(SignatureScheme::PasskeyAuthenticator),
             _ => Err(MgoError::KeyConversionError(
                 "Invalid key scheme".to_string(),
             )),
@@ -1671,9 +1707,12 @@ pub enum CompressedSignature {
     Secp256k1(Secp256k1SignatureAsBytes),
     Secp256r1(Secp256r1SignatureAsBytes),
     ZkLogin(ZkLoginAuthenticatorAsBytes),
+    MultiSig(MultiSigAsBytes),
 }
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 pub struct ZkLoginAuthenticatorAsBytes(#[schemars(with = "Base64")] pub Vec<u8>);
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct MultiSigAsBytes(#[schemars(with = "Base64")] pub Vec<u8>);
 
 impl AsRef<[u8]> for CompressedSignature {
     fn as_ref(&self) -> &[u8] {
@@ -1682,6 +1721,7 @@ impl AsRef<[u8]> for CompressedSignature {
             CompressedSignature::Secp256k1(sig) => &sig.0,
             CompressedSignature::Secp256r1(sig) => &sig.0,
             CompressedSignature::ZkLogin(sig) => &sig.0,
+            CompressedSignature::MultiSig(sig) => &sig.0,
         }
     }
 }
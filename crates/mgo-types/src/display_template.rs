@@ -0,0 +1,973 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::future::Future;
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::annotated_value::{MoveStruct, MoveValue};
+use move_core_types::ident_str;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use move_core_types::u256::U256;
+
+use crate::base_types::{MgoAddress, ObjectID};
+use crate::collection_types::VecMap;
+use crate::dynamic_field::{derive_dynamic_field_id, DynamicFieldInfo};
+use crate::{parse_mgo_struct_tag, MGO_FRAMEWORK_ADDRESS, MOVE_STDLIB_ADDRESS};
+
+#[cfg(test)]
+#[path = "unit_tests/display_template_tests.rs"]
+mod display_template_tests;
+
+/// Display field selecting the template grammar of a `Display`. Displays without this field use
+/// grammar version 1, which only supports `{field.path}` substitution, so that existing templates
+/// keep rendering identically. Its value is `"2"` to opt into the grammar of `DisplayTemplate`.
+pub const DISPLAY_GRAMMAR_KEY: &str = "$grammar";
+/// Maximum number of fields, indices and dynamic fields in a template path.
+pub const MAX_DISPLAY_PATH_DEPTH: usize = 10;
+/// Maximum number of objects loaded through dynamic fields while rendering a template.
+pub const MAX_DISPLAY_OBJECT_LOADS: usize = 16;
+/// Maximum nesting of conditionals in a template.
+pub const MAX_DISPLAY_CONDITIONAL_DEPTH: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisplayGrammar {
+    V1,
+    V2,
+}
+
+impl DisplayGrammar {
+    pub fn from_fields(fields: &VecMap<String, String>) -> Result<Self, DisplayRenderError> {
+        let Some(entry) = fields
+            .contents
+            .iter()
+            .find(|entry| entry.key == DISPLAY_GRAMMAR_KEY)
+        else {
+            return Ok(Self::V1);
+        };
+        match entry.value.as_str() {
+            "1" => Ok(Self::V1),
+            "2" => Ok(Self::V2),
+            version => Err(DisplayRenderError::UnsupportedGrammar(version.to_string())),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum DisplayRenderError {
+    #[error("Unsupported display grammar version: {0}")]
+    UnsupportedGrammar(String),
+    #[error("Invalid display template at position {position}: {reason}")]
+    Parse { position: usize, reason: String },
+    #[error(
+        "Display template path {0} exceeds maximum depth of {}",
+        MAX_DISPLAY_PATH_DEPTH
+    )]
+    ExceedsPathDepth(String),
+    #[error(
+        "Display template exceeds the limit of {} object loads",
+        MAX_DISPLAY_OBJECT_LOADS
+    )]
+    ExceedsObjectLoads,
+    #[error("Field '{0}' not found")]
+    FieldNotFound(String),
+    #[error("Cannot access {0} of a value of type {1}")]
+    InvalidAccess(String, String),
+    #[error("Value of type {0} is not supported as a Display value")]
+    NotDisplayable(String),
+    #[error("Value of type {0} cannot be formatted as {1}")]
+    InvalidFormat(String, String),
+    #[error("CoinMetadata of {0} not found")]
+    CoinMetadataNotFound(String),
+    #[error("Failed to load display data: {0}")]
+    Store(String),
+}
+
+/// Data outside of the displayed object which templates can refer to.
+#[async_trait]
+pub trait DisplayStore: Send + Sync {
+    /// Returns the contents of the object, if it exists.
+    async fn get_object(&self, id: ObjectID) -> anyhow::Result<Option<MoveStruct>>;
+
+    /// Returns the decimals of the `CoinMetadata` of the coin type, if it exists.
+    async fn get_coin_decimals(&self, coin_type: &StructTag) -> anyhow::Result<Option<u8>>;
+}
+
+/// A template of grammar version 2, defined as:
+///
+/// ```text
+/// TEMPLATE ::= PART*
+/// PART     ::= TEXT | '{' EXPR '}' | '{#if ' EXPR '}' TEMPLATE ('{#else}' TEMPLATE)? '{/if}'
+/// TEXT     ::= '\{' | '\}' | '\\' | [:utf8: except '{', '}' and '\']
+/// EXPR     ::= TERM ('|' TERM)*
+/// TERM     ::= PATH (':' FORMAT)? | LITERAL
+/// PATH     ::= IDENT ('.' IDENT | '[' [0-9]+ ']' | '->[' LITERAL ']' | '=>[' LITERAL ']')*
+/// LITERAL  ::= STRING | NUMBER | 'true' | 'false' | '@' ADDRESS
+/// STRING   ::= "'" ("\'" | '\\' | [:utf8: except "'" and '\'])* "'"
+/// NUMBER   ::= [0-9]+ ('u8' | 'u16' | 'u32' | 'u64' | 'u128' | 'u256')?
+/// FORMAT   ::= 'decimals(' [0-9]+ ')' | 'coin' | 'coin(' TYPE ')' | 'grouped'
+/// ```
+///
+/// Options are transparent in paths. A path has no value if it reaches `option::none`, a vector
+/// index out of bounds or a missing dynamic field (`->`) or dynamic object field (`=>`), in which
+/// case the next term of the expression is tried, and nothing is rendered if no term has a value.
+/// A conditional renders its first branch if its expression has a value other than `false`.
+///
+/// `decimals(n)` renders an integer shifted by `n` decimal places, `coin` does the same with the
+/// decimals of the `CoinMetadata` of a `Balance<T>` or `Coin<T>` (or of `TYPE` for integers) and
+/// `grouped` renders an integer with thousands separators.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisplayTemplate {
+    parts: Vec<Part>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Expr(Expr),
+    If {
+        condition: Expr,
+        then: Vec<Part>,
+        otherwise: Vec<Part>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Expr {
+    terms: Vec<Term>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Term {
+    Path(Path, Option<Format>),
+    Literal(Literal),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Path {
+    /// The path as written in the template, for error messages
+    text: String,
+    root: String,
+    accessors: Vec<Accessor>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Accessor {
+    Field(String),
+    Index(usize),
+    DynamicField(Literal),
+    DynamicObjectField(Literal),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Literal {
+    String(String),
+    Bool(bool),
+    Address(AccountAddress),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    U256(U256),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Format {
+    Decimals(u8),
+    Coin(Option<StructTag>),
+    Grouped,
+}
+
+enum Terminator {
+    End,
+    Else,
+    EndIf,
+}
+
+impl DisplayTemplate {
+    pub fn parse(template: &str) -> Result<Self, DisplayRenderError> {
+        let mut parser = Parser {
+            chars: template.chars().collect(),
+            pos: 0,
+        };
+        let (parts, _) = parser.parse_parts(0)?;
+        Ok(Self { parts })
+    }
+
+    pub async fn render(
+        &self,
+        move_struct: &MoveStruct,
+        store: &dyn DisplayStore,
+    ) -> Result<String, DisplayRenderError> {
+        let mut renderer = Renderer {
+            root: move_struct,
+            store,
+            object_loads: 0,
+        };
+        let mut output = String::new();
+        renderer.render_parts(&self.parts, &mut output).await?;
+        Ok(output)
+    }
+}
+
+/// Renders the fields of a `Display` of grammar version 2, except for the grammar field itself.
+pub async fn render_display_fields(
+    fields: &VecMap<String, String>,
+    move_struct: &MoveStruct,
+    store: &dyn DisplayStore,
+) -> Vec<(String, Result<String, DisplayRenderError>)> {
+    let mut rendered = vec![];
+    for entry in &fields.contents {
+        if entry.key == DISPLAY_GRAMMAR_KEY {
+            continue;
+        }
+        let value = match DisplayTemplate::parse(&entry.value) {
+            Ok(template) => template.render(move_struct, store).await,
+            Err(e) => Err(e),
+        };
+        rendered.push((entry.key.clone(), value));
+    }
+    rendered
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn error<T>(&self, reason: impl Into<String>) -> Result<T, DisplayRenderError> {
+        Err(DisplayRenderError::Parse {
+            position: self.pos,
+            reason: reason.into(),
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        let mut pos = self.pos;
+        for c in s.chars() {
+            if self.chars.get(pos) != Some(&c) {
+                return false;
+            }
+            pos += 1;
+        }
+        true
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        let matches = self.starts_with(s);
+        if matches {
+            self.pos += s.chars().count();
+        }
+        matches
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), DisplayRenderError> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            self.error(format!("expected '{s}'"))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn parse_parts(&mut self, depth: usize) -> Result<(Vec<Part>, Terminator), DisplayRenderError> {
+        let mut parts = vec![];
+        let mut text = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                if depth > 0 {
+                    return self.error("missing '{/if}'");
+                }
+                break;
+            };
+            match c {
+                '\\' => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some(c @ ('{' | '}' | '\\')) => text.push(c),
+                        _ => return self.error("invalid escape sequence"),
+                    }
+                    self.pos += 1;
+                }
+                '}' => return self.error("unexpected '}'"),
+                '{' => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    if self.eat("{#else}") {
+                        if depth == 0 {
+                            return self.error("'{#else}' outside of '{#if}'");
+                        }
+                        return Ok((parts, Terminator::Else));
+                    }
+                    if self.eat("{/if}") {
+                        if depth == 0 {
+                            return self.error("'{/if}' outside of '{#if}'");
+                        }
+                        return Ok((parts, Terminator::EndIf));
+                    }
+                    if self.eat("{#if ") {
+                        if depth == MAX_DISPLAY_CONDITIONAL_DEPTH {
+                            return self.error(format!(
+                                "conditionals nested deeper than {MAX_DISPLAY_CONDITIONAL_DEPTH}"
+                            ));
+                        }
+                        let condition = self.parse_expr()?;
+                        self.expect("}")?;
+                        let (then, terminator) = self.parse_parts(depth + 1)?;
+                        let otherwise = match terminator {
+                            Terminator::Else => match self.parse_parts(depth + 1)? {
+                                (otherwise, Terminator::EndIf) => otherwise,
+                                _ => return self.error("expected '{/if}'"),
+                            },
+                            _ => vec![],
+                        };
+                        parts.push(Part::If {
+                            condition,
+                            then,
+                            otherwise,
+                        });
+                    } else {
+                        self.pos += 1;
+                        let expr = self.parse_expr()?;
+                        self.expect("}")?;
+                        parts.push(Part::Expr(expr));
+                    }
+                }
+                c => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok((parts, Terminator::End))
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, DisplayRenderError> {
+        let mut terms = vec![];
+        loop {
+            self.skip_whitespace();
+            terms.push(self.parse_term()?);
+            self.skip_whitespace();
+            if !self.eat("|") {
+                return Ok(Expr { terms });
+            }
+        }
+    }
+
+    fn parse_term(&mut self) -> Result<Term, DisplayRenderError> {
+        if let Some(literal) = self.parse_literal()? {
+            return Ok(Term::Literal(literal));
+        }
+        let path = self.parse_path()?;
+        self.skip_whitespace();
+        let format = if self.eat(":") {
+            self.skip_whitespace();
+            Some(self.parse_format()?)
+        } else {
+            None
+        };
+        Ok(Term::Path(path, format))
+    }
+
+    fn parse_ident(&mut self) -> Result<String, DisplayRenderError> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_') {
+            self.pos += 1;
+        }
+        let ident: String = self.chars[start..self.pos].iter().collect();
+        if !ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            self.pos = start;
+            return self.error("expected an identifier");
+        }
+        Ok(ident)
+    }
+
+    fn parse_path(&mut self) -> Result<Path, DisplayRenderError> {
+        let start = self.pos;
+        let root = self.parse_ident()?;
+        let mut accessors = vec![];
+        loop {
+            if self.eat(".") {
+                accessors.push(Accessor::Field(self.parse_ident()?));
+            } else if self.eat("[") {
+                self.skip_whitespace();
+                let digits = self.parse_digits()?;
+                let index = digits
+                    .parse()
+                    .or_else(|_| self.error("vector index out of range"))?;
+                self.skip_whitespace();
+                self.expect("]")?;
+                accessors.push(Accessor::Index(index));
+            } else if self.eat("->[") {
+                accessors.push(Accessor::DynamicField(self.parse_key()?));
+            } else if self.eat("=>[") {
+                accessors.push(Accessor::DynamicObjectField(self.parse_key()?));
+            } else {
+                break;
+            }
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        if accessors.len() + 1 > MAX_DISPLAY_PATH_DEPTH {
+            return Err(DisplayRenderError::ExceedsPathDepth(text));
+        }
+        Ok(Path {
+            text,
+            root,
+            accessors,
+        })
+    }
+
+    fn parse_key(&mut self) -> Result<Literal, DisplayRenderError> {
+        self.skip_whitespace();
+        let Some(key) = self.parse_literal()? else {
+            return self.error("expected a literal dynamic field name");
+        };
+        self.skip_whitespace();
+        self.expect("]")?;
+        Ok(key)
+    }
+
+    fn parse_digits(&mut self) -> Result<String, DisplayRenderError> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return self.error("expected a number");
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    /// Parses a literal, or returns None without consuming any input if there is none.
+    fn parse_literal(&mut self) -> Result<Option<Literal>, DisplayRenderError> {
+        match self.peek() {
+            Some('\'') => {
+                self.pos += 1;
+                let mut value = String::new();
+                loop {
+                    match self.peek() {
+                        None => return self.error("unterminated string literal"),
+                        Some('\'') => break,
+                        Some('\\') => {
+                            self.pos += 1;
+                            match self.peek() {
+                                Some(c @ ('\'' | '\\')) => value.push(c),
+                                _ => return self.error("invalid escape sequence"),
+                            }
+                        }
+                        Some(c) => value.push(c),
+                    }
+                    self.pos += 1;
+                }
+                self.pos += 1;
+                Ok(Some(Literal::String(value)))
+            }
+            Some('@') => {
+                self.pos += 1;
+                let start = self.pos;
+                while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric()) {
+                    self.pos += 1;
+                }
+                let address: String = self.chars[start..self.pos].iter().collect();
+                match AccountAddress::from_hex_literal(&address) {
+                    Ok(address) => Ok(Some(Literal::Address(address))),
+                    Err(_) => {
+                        self.pos = start;
+                        self.error("invalid address literal")
+                    }
+                }
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let digits = self.parse_digits()?;
+                let literal = if self.eat("u8") {
+                    digits.parse().map(Literal::U8).ok()
+                } else if self.eat("u16") {
+                    digits.parse().map(Literal::U16).ok()
+                } else if self.eat("u32") {
+                    digits.parse().map(Literal::U32).ok()
+                } else if self.eat("u64") {
+                    digits.parse().map(Literal::U64).ok()
+                } else if self.eat("u128") {
+                    digits.parse().map(Literal::U128).ok()
+                } else if self.eat("u256") {
+                    digits.parse().map(Literal::U256).ok()
+                } else {
+                    digits.parse().map(Literal::U64).ok()
+                };
+                match literal {
+                    Some(literal) => Ok(Some(literal)),
+                    None => self.error("number literal out of range"),
+                }
+            }
+            _ => {
+                for (keyword, value) in [("true", true), ("false", false)] {
+                    let is_keyword = self.starts_with(keyword)
+                        && !matches!(
+                            self.chars.get(self.pos + keyword.len()),
+                            Some(c) if c.is_ascii_alphanumeric() || *c == '_'
+                        );
+                    if is_keyword {
+                        self.pos += keyword.len();
+                        return Ok(Some(Literal::Bool(value)));
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    fn parse_format(&mut self) -> Result<Format, DisplayRenderError> {
+        match self.parse_ident()?.as_str() {
+            "decimals" => {
+                self.expect("(")?;
+                let digits = self.parse_digits()?;
+                let decimals = digits
+                    .parse()
+                    .or_else(|_| self.error("decimals out of range"))?;
+                self.expect(")")?;
+                Ok(Format::Decimals(decimals))
+            }
+            "coin" => {
+                if !self.eat("(") {
+                    return Ok(Format::Coin(None));
+                }
+                let start = self.pos;
+                while !matches!(self.peek(), None | Some(')')) {
+                    self.pos += 1;
+                }
+                let type_: String = self.chars[start..self.pos].iter().collect();
+                let coin_type = parse_mgo_struct_tag(type_.trim())
+                    .or_else(|_| self.error(format!("invalid coin type '{type_}'")))?;
+                self.expect(")")?;
+                Ok(Format::Coin(Some(coin_type)))
+            }
+            "grouped" => Ok(Format::Grouped),
+            format => self.error(format!("unknown format '{format}'")),
+        }
+    }
+}
+
+impl Literal {
+    fn type_tag(&self) -> TypeTag {
+        match self {
+            Literal::String(_) => TypeTag::Struct(Box::new(StructTag {
+                address: MOVE_STDLIB_ADDRESS,
+                module: ident_str!("string").to_owned(),
+                name: ident_str!("String").to_owned(),
+                type_params: vec![],
+            })),
+            Literal::Bool(_) => TypeTag::Bool,
+            Literal::Address(_) => TypeTag::Address,
+            Literal::U8(_) => TypeTag::U8,
+            Literal::U16(_) => TypeTag::U16,
+            Literal::U32(_) => TypeTag::U32,
+            Literal::U64(_) => TypeTag::U64,
+            Literal::U128(_) => TypeTag::U128,
+            Literal::U256(_) => TypeTag::U256,
+        }
+    }
+
+    fn to_bcs(&self) -> Vec<u8> {
+        match self {
+            Literal::String(v) => bcs::to_bytes(v),
+            Literal::Bool(v) => bcs::to_bytes(v),
+            Literal::Address(v) => bcs::to_bytes(v),
+            Literal::U8(v) => bcs::to_bytes(v),
+            Literal::U16(v) => bcs::to_bytes(v),
+            Literal::U32(v) => bcs::to_bytes(v),
+            Literal::U64(v) => bcs::to_bytes(v),
+            Literal::U128(v) => bcs::to_bytes(v),
+            Literal::U256(v) => bcs::to_bytes(v),
+        }
+        .expect("Serializing a literal cannot fail")
+    }
+
+    fn render(&self) -> String {
+        match self {
+            Literal::String(v) => v.clone(),
+            Literal::Bool(v) => v.to_string(),
+            Literal::Address(v) => MgoAddress::from(ObjectID::from(*v)).to_string(),
+            Literal::U8(v) => v.to_string(),
+            Literal::U16(v) => v.to_string(),
+            Literal::U32(v) => v.to_string(),
+            Literal::U64(v) => v.to_string(),
+            Literal::U128(v) => v.to_string(),
+            Literal::U256(v) => v.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Format::Decimals(decimals) => write!(f, "decimals({decimals})"),
+            Format::Coin(None) => write!(f, "coin"),
+            Format::Coin(Some(coin_type)) => write!(f, "coin({coin_type})"),
+            Format::Grouped => write!(f, "grouped"),
+        }
+    }
+}
+
+type RenderFuture<'a> = Pin<Box<dyn Future<Output = Result<(), DisplayRenderError>> + Send + 'a>>;
+
+struct Renderer<'a> {
+    root: &'a MoveStruct,
+    store: &'a dyn DisplayStore,
+    object_loads: usize,
+}
+
+impl<'a> Renderer<'a> {
+    fn render_parts<'b>(
+        &'b mut self,
+        parts: &'b [Part],
+        output: &'b mut String,
+    ) -> RenderFuture<'b> {
+        Box::pin(async move {
+            for part in parts {
+                match part {
+                    Part::Text(text) => output.push_str(text),
+                    Part::Expr(expr) => {
+                        if let Some(value) = self.render_expr(expr).await? {
+                            output.push_str(&value);
+                        }
+                    }
+                    Part::If {
+                        condition,
+                        then,
+                        otherwise,
+                    } => {
+                        if self.eval_condition(condition).await? {
+                            self.render_parts(then, output).await?;
+                        } else {
+                            self.render_parts(otherwise, output).await?;
+                        }
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    async fn render_expr(&mut self, expr: &Expr) -> Result<Option<String>, DisplayRenderError> {
+        for term in &expr.terms {
+            match term {
+                Term::Literal(literal) => return Ok(Some(literal.render())),
+                Term::Path(path, format) => {
+                    if let Some(value) = self.eval_path(path).await? {
+                        let rendered = match format {
+                            Some(format) => self.format_value(&value, format).await?,
+                            None => render_value(&value)?,
+                        };
+                        return Ok(Some(rendered));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    async fn eval_condition(&mut self, expr: &Expr) -> Result<bool, DisplayRenderError> {
+        for term in &expr.terms {
+            match term {
+                Term::Literal(literal) => return Ok(literal != &Literal::Bool(false)),
+                Term::Path(path, _) => {
+                    if let Some(value) = self.eval_path(path).await? {
+                        return Ok(value != MoveValue::Bool(false));
+                    }
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    async fn eval_path(&mut self, path: &Path) -> Result<Option<MoveValue>, DisplayRenderError> {
+        let mut value = field(self.root, &path.root)?.clone();
+        for accessor in &path.accessors {
+            let Some(current) = unwrap_option(value) else {
+                return Ok(None);
+            };
+            value = match (accessor, current) {
+                (Accessor::Field(name), MoveValue::Struct(s)) => field(&s, name)?.clone(),
+                (Accessor::Index(index), MoveValue::Vector(mut values)) => {
+                    if *index >= values.len() {
+                        return Ok(None);
+                    }
+                    values.swap_remove(*index)
+                }
+                (Accessor::DynamicField(key), current) => {
+                    let parent = object_id(&current).ok_or_else(|| {
+                        DisplayRenderError::InvalidAccess(path.text.clone(), type_name(&current))
+                    })?;
+                    match self.load_dynamic_field(parent, key.type_tag(), key).await? {
+                        Some(value) => value,
+                        None => return Ok(None),
+                    }
+                }
+                (Accessor::DynamicObjectField(key), current) => {
+                    let parent = object_id(&current).ok_or_else(|| {
+                        DisplayRenderError::InvalidAccess(path.text.clone(), type_name(&current))
+                    })?;
+                    let wrapper = DynamicFieldInfo::dynamic_object_field_wrapper(key.type_tag());
+                    let Some(id) = self.load_dynamic_field(parent, wrapper.into(), key).await?
+                    else {
+                        return Ok(None);
+                    };
+                    let id = object_id(&id).ok_or_else(|| {
+                        DisplayRenderError::Store("dynamic object field is not an ID".to_string())
+                    })?;
+                    match self.load_object(id).await? {
+                        Some(object) => MoveValue::Struct(object),
+                        None => return Ok(None),
+                    }
+                }
+                (_, current) => {
+                    return Err(DisplayRenderError::InvalidAccess(
+                        path.text.clone(),
+                        type_name(&current),
+                    ))
+                }
+            };
+        }
+        Ok(unwrap_option(value))
+    }
+
+    async fn load_object(
+        &mut self,
+        id: ObjectID,
+    ) -> Result<Option<MoveStruct>, DisplayRenderError> {
+        if self.object_loads == MAX_DISPLAY_OBJECT_LOADS {
+            return Err(DisplayRenderError::ExceedsObjectLoads);
+        }
+        self.object_loads += 1;
+        self.store
+            .get_object(id)
+            .await
+            .map_err(|e| DisplayRenderError::Store(e.to_string()))
+    }
+
+    /// Returns the value of the dynamic field of `parent` with the name `key` of type `key_type`.
+    async fn load_dynamic_field(
+        &mut self,
+        parent: ObjectID,
+        key_type: TypeTag,
+        key: &Literal,
+    ) -> Result<Option<MoveValue>, DisplayRenderError> {
+        let id = derive_dynamic_field_id(parent, &key_type, &key.to_bcs())
+            .map_err(|e| DisplayRenderError::Store(e.to_string()))?;
+        let Some(field_object) = self.load_object(id).await? else {
+            return Ok(None);
+        };
+        Ok(Some(field(&field_object, "value")?.clone()))
+    }
+
+    async fn format_value(
+        &mut self,
+        value: &MoveValue,
+        format: &Format,
+    ) -> Result<String, DisplayRenderError> {
+        let invalid_format =
+            || DisplayRenderError::InvalidFormat(type_name(value), format.to_string());
+        let (amount, decimals) = match format {
+            Format::Grouped => {
+                let amount = integer(value).ok_or_else(invalid_format)?;
+                return Ok(group_thousands(&amount));
+            }
+            Format::Decimals(decimals) => (integer(value).ok_or_else(invalid_format)?, *decimals),
+            Format::Coin(coin_type) => {
+                let coin_type = match (coin_type, value) {
+                    (Some(coin_type), _) => coin_type.clone(),
+                    (None, MoveValue::Struct(s)) => {
+                        coin_type_param(&s.type_).ok_or_else(invalid_format)?
+                    }
+                    (None, _) => return Err(invalid_format()),
+                };
+                let amount = integer(value).ok_or_else(invalid_format)?;
+                let decimals = self
+                    .store
+                    .get_coin_decimals(&coin_type)
+                    .await
+                    .map_err(|e| DisplayRenderError::Store(e.to_string()))?
+                    .ok_or_else(|| {
+                        DisplayRenderError::CoinMetadataNotFound(
+                            coin_type.to_canonical_string(/* with_prefix */ true),
+                        )
+                    })?;
+                (amount, decimals)
+            }
+        };
+        Ok(shift_decimals(&amount, decimals))
+    }
+}
+
+fn field<'s>(move_struct: &'s MoveStruct, name: &str) -> Result<&'s MoveValue, DisplayRenderError> {
+    move_struct
+        .fields
+        .iter()
+        .find_map(|(id, value)| (id.as_str() == name).then_some(value))
+        .ok_or_else(|| DisplayRenderError::FieldNotFound(name.to_string()))
+}
+
+fn is_type(tag: &StructTag, address: AccountAddress, module: &str, name: &str) -> bool {
+    tag.address == address && tag.module.as_str() == module && tag.name.as_str() == name
+}
+
+/// Returns the value of an `option::Option`, or the value itself if it is not an option.
+fn unwrap_option(value: MoveValue) -> Option<MoveValue> {
+    match value {
+        MoveValue::Struct(s) if is_type(&s.type_, MOVE_STDLIB_ADDRESS, "option", "Option") => {
+            s.fields.into_iter().find_map(|(id, value)| match value {
+                MoveValue::Vector(values) if id.as_str() == "vec" => values.into_iter().next(),
+                _ => None,
+            })
+        }
+        value => Some(value),
+    }
+}
+
+/// Returns the ID of a `UID`, an `ID`, an address or an object with an `id` field.
+fn object_id(value: &MoveValue) -> Option<ObjectID> {
+    match value {
+        MoveValue::Address(address) => Some(ObjectID::from(*address)),
+        MoveValue::Struct(s) if is_type(&s.type_, MGO_FRAMEWORK_ADDRESS, "object", "ID") => {
+            object_id(field(s, "bytes").ok()?)
+        }
+        MoveValue::Struct(s) => object_id(field(s, "id").ok()?),
+        _ => None,
+    }
+}
+
+/// Returns `T` of a `Balance<T>` or `Coin<T>`.
+fn coin_type_param(tag: &StructTag) -> Option<StructTag> {
+    let is_coin = is_type(tag, MGO_FRAMEWORK_ADDRESS, "balance", "Balance")
+        || is_type(tag, MGO_FRAMEWORK_ADDRESS, "coin", "Coin");
+    match tag.type_params.as_slice() {
+        [TypeTag::Struct(coin_type)] if is_coin => Some(coin_type.as_ref().clone()),
+        _ => None,
+    }
+}
+
+/// Returns the decimal digits of an unsigned integer, `Balance` or `Coin`.
+fn integer(value: &MoveValue) -> Option<String> {
+    match value {
+        MoveValue::U8(v) => Some(v.to_string()),
+        MoveValue::U16(v) => Some(v.to_string()),
+        MoveValue::U32(v) => Some(v.to_string()),
+        MoveValue::U64(v) => Some(v.to_string()),
+        MoveValue::U128(v) => Some(v.to_string()),
+        MoveValue::U256(v) => Some(v.to_string()),
+        MoveValue::Struct(s) if is_type(&s.type_, MGO_FRAMEWORK_ADDRESS, "balance", "Balance") => {
+            integer(field(s, "value").ok()?)
+        }
+        MoveValue::Struct(s) if is_type(&s.type_, MGO_FRAMEWORK_ADDRESS, "coin", "Coin") => {
+            integer(field(s, "balance").ok()?)
+        }
+        _ => None,
+    }
+}
+
+fn shift_decimals(digits: &str, decimals: u8) -> String {
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits.to_string();
+    }
+    let padded = format!("{digits:0>width$}", width = decimals + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{whole}.{fraction}")
+    }
+}
+
+fn group_thousands(digits: &str) -> String {
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+fn type_name(value: &MoveValue) -> String {
+    match value {
+        MoveValue::U8(_) => "u8".to_string(),
+        MoveValue::U16(_) => "u16".to_string(),
+        MoveValue::U32(_) => "u32".to_string(),
+        MoveValue::U64(_) => "u64".to_string(),
+        MoveValue::U128(_) => "u128".to_string(),
+        MoveValue::U256(_) => "u256".to_string(),
+        MoveValue::Bool(_) => "bool".to_string(),
+        MoveValue::Address(_) => "address".to_string(),
+        MoveValue::Signer(_) => "signer".to_string(),
+        MoveValue::Vector(_) => "vector".to_string(),
+        MoveValue::Struct(s) => s.type_.to_canonical_string(/* with_prefix */ true),
+    }
+}
+
+/// Renders a value the way grammar version 1 does for the types it supports.
+fn render_value(value: &MoveValue) -> Result<String, DisplayRenderError> {
+    let not_displayable = || DisplayRenderError::NotDisplayable(type_name(value));
+    match value {
+        MoveValue::U8(v) => Ok(v.to_string()),
+        MoveValue::U16(v) => Ok(v.to_string()),
+        MoveValue::U32(v) => Ok(v.to_string()),
+        MoveValue::U64(v) => Ok(v.to_string()),
+        MoveValue::U128(v) => Ok(v.to_string()),
+        MoveValue::U256(v) => Ok(v.to_string()),
+        MoveValue::Bool(v) => Ok(v.to_string()),
+        MoveValue::Address(v) | MoveValue::Signer(v) => {
+            Ok(MgoAddress::from(ObjectID::from(*v)).to_string())
+        }
+        MoveValue::Vector(_) => Err(not_displayable()),
+        MoveValue::Struct(s) => {
+            let tag = &s.type_;
+            if is_type(tag, MOVE_STDLIB_ADDRESS, "string", "String")
+                || is_type(tag, MOVE_STDLIB_ADDRESS, "ascii", "String")
+            {
+                let MoveValue::Vector(bytes) = field(s, "bytes")? else {
+                    return Err(not_displayable());
+                };
+                let bytes = bytes
+                    .iter()
+                    .map(|b| match b {
+                        MoveValue::U8(b) => Some(*b),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(not_displayable)?;
+                String::from_utf8(bytes).map_err(|_| not_displayable())
+            } else if is_type(tag, MGO_FRAMEWORK_ADDRESS, "url", "Url") {
+                render_value(field(s, "url")?)
+            } else if is_type(tag, MGO_FRAMEWORK_ADDRESS, "object", "UID")
+                || is_type(tag, MGO_FRAMEWORK_ADDRESS, "object", "ID")
+            {
+                object_id(value)
+                    .map(|id| id.to_string())
+                    .ok_or_else(not_displayable)
+            } else if is_type(tag, MGO_FRAMEWORK_ADDRESS, "balance", "Balance") {
+                render_value(field(s, "value")?)
+            } else {
+                Err(not_displayable())
+            }
+        }
+    }
+}
@@ -254,6 +254,15 @@ pub enum UserInputError {
         address: MgoAddress,
         coin_type: String,
     },
+
+    #[error("Transaction is for chain {provided}, but this is chain {expected}")]
+    InvalidChainId { provided: String, expected: String },
+
+    #[error("Invalid transaction expiration: {error}")]
+    InvalidTransactionExpiration { error: String },
+
+    #[error("Invalid state override for {object_id}: {error}")]
+    InvalidStateOverride { object_id: ObjectID, error: String },
 }
 
 #[derive(
@@ -612,6 +621,21 @@ pub enum MgoError {
 
     #[error("Storage error: {0}")]
     Storage(String),
+
+    // Appended to keep the serialized indices of existing variants unchanged.
+    #[error("Too many requests for {key}, retry after {retry_after_ms} ms")]
+    TooManyRequests { key: String, retry_after_ms: u64 },
+
+    #[error("Invalid inclusion proof: {error}")]
+    InvalidInclusionProof { error: String },
+
+    #[error(
+        "Transaction is not valid before {min_timestamp_ms} ms, consensus time is {current_timestamp_ms} ms"
+    )]
+    TransactionNotYetValid {
+        min_timestamp_ms: u64,
+        current_timestamp_ms: u64,
+    },
 }
 
 #[repr(u64)]
@@ -773,6 +797,10 @@ impl MgoError {
             MgoError::TooManyTransactionsPendingOnObject { .. } => (true, true),
             MgoError::TooOldTransactionPendingOnObject { .. } => (true, true),
             MgoError::TooManyTransactionsPendingConsensus => (true, true),
+            MgoError::TooManyRequests { .. } => (true, true),
+
+            // The transaction becomes valid once consensus time reaches its validity window
+            MgoError::TransactionNotYetValid { .. } => (true, true),
 
             // Non retryable error
             MgoError::ExecutionError(..) => (false, true),
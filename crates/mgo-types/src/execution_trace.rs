@@ -0,0 +1,425 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Structured traces of the Move execution of a programmable transaction: the call stack of every
+//! command, with the arguments, return values and gas of each frame, the events each command
+//! emitted, and the objects the transaction read and wrote. Traces are recorded on request (for
+//! dev-inspect and replay) and serialized as JSON.
+
+use std::collections::BTreeSet;
+
+use fastcrypto::encoding::Base64;
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::serde_as;
+
+use crate::base_types::{ObjectID, ObjectRef, SequenceNumber};
+use crate::effects::{TransactionEffects, TransactionEffectsAPI};
+use crate::inner_temporary_store::InnerTemporaryStore;
+use crate::mgo_serde::{BigInt, MgoStructTag, MgoTypeTag, SequenceNumber as AsSequenceNumber};
+use crate::object::Object;
+use crate::storage::ObjectStore;
+
+#[cfg(test)]
+#[path = "unit_tests/execution_trace_tests.rs"]
+mod execution_trace_tests;
+
+/// The version of the JSON trace format. Changes that remove or re-interpret fields bump it,
+/// additions do not.
+pub const EXECUTION_TRACE_FORMAT_VERSION: u32 = 1;
+
+/// The most frames a trace records. Frames entered after the limit is reached are dropped.
+pub const MAX_TRACED_FRAMES: usize = 10_000;
+
+/// The most bytes of BCS values (arguments, return values, events and object contents) a trace
+/// records. Values recorded after the limit is reached are dropped.
+pub const MAX_TRACED_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionTrace {
+    /// The version of the format this trace is in, see [`EXECUTION_TRACE_FORMAT_VERSION`].
+    pub version: u32,
+    /// The commands of the transaction, in execution order. Execution stops at the first command
+    /// that fails.
+    pub commands: Vec<CommandTrace>,
+    /// The Move objects the transaction read or wrote, ordered by ID.
+    pub objects: Vec<ObjectAccess>,
+    /// Whether the trace reached [`MAX_TRACED_FRAMES`] or [`MAX_TRACED_BYTES`], in which case
+    /// the frames, values, events and objects after that point are missing from it.
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandTrace {
+    pub index: u64,
+    /// The kind of command, e.g. `MoveCall` or `SplitCoins`.
+    pub kind: String,
+    /// The Move functions the command called directly: the function of a `MoveCall`, or the
+    /// module initializers of a `Publish`.
+    pub calls: Vec<FrameTrace>,
+    /// The events emitted by the command, in order.
+    pub events: Vec<EventTrace>,
+    /// Why the command failed, if it did.
+    pub error: Option<String>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameTrace {
+    /// The runtime ID of the package the function is defined in.
+    pub package: ObjectID,
+    pub module: String,
+    pub function: String,
+    #[schemars(with = "Vec<String>")]
+    #[serde_as(as = "Vec<MgoTypeTag>")]
+    pub type_arguments: Vec<TypeTag>,
+    /// Arguments passed by reference are traced as the value they refer to.
+    pub arguments: Vec<TracedValue>,
+    /// Empty if the frame aborted.
+    pub return_values: Vec<TracedValue>,
+    /// Gas used by this frame, including the frames it called, in Move VM internal gas units.
+    #[schemars(with = "BigInt<u64>")]
+    #[serde_as(as = "BigInt<u64>")]
+    pub gas_used: u64,
+    pub is_native: bool,
+    /// Whether the frame aborted (or ran out of gas) rather than returning.
+    pub aborted: bool,
+    /// The frames this function called, in order.
+    pub calls: Vec<FrameTrace>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EventTrace {
+    #[schemars(with = "String")]
+    #[serde_as(as = "MgoStructTag")]
+    #[serde(rename = "type")]
+    pub type_: StructTag,
+    pub contents: TracedValue,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectAccess {
+    pub object_id: ObjectID,
+    #[schemars(with = "String")]
+    #[serde_as(as = "MgoStructTag")]
+    #[serde(rename = "type")]
+    pub type_: StructTag,
+    pub kind: ObjectAccessKind,
+    /// The object as the transaction found it, if it existed before the transaction.
+    pub before: Option<TracedObject>,
+    /// The object as the transaction left it, if it still exists afterwards.
+    pub after: Option<TracedObject>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ObjectAccessKind {
+    Read,
+    Created,
+    Mutated,
+    Unwrapped,
+    Deleted,
+    Wrapped,
+    UnwrappedThenDeleted,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TracedObject {
+    #[schemars(with = "AsSequenceNumber")]
+    #[serde_as(as = "AsSequenceNumber")]
+    pub version: SequenceNumber,
+    pub contents: TracedValue,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TracedValue {
+    /// The BCS serialized value.
+    #[schemars(with = "Base64")]
+    #[serde_as(as = "Base64")]
+    pub bcs: Vec<u8>,
+    /// The value as JSON, if its type could be resolved.
+    pub json: Option<Value>,
+}
+
+/// Builds an [`ExecutionTrace`] as the execution layer reports the frames it enters and leaves,
+/// and the events emitted while a command runs.
+#[derive(Debug)]
+pub struct ExecutionTracer {
+    commands: Vec<CommandTrace>,
+    /// Frames that have been entered but not left yet, innermost last, alongside the gas that was
+    /// remaining when they were entered.
+    open_frames: Vec<(FrameTrace, u64)>,
+    /// Frames that have been entered but not left yet, and that are not recorded because the
+    /// trace was truncated. They are all nested inside the `open_frames`.
+    dropped_frames: usize,
+    objects: Vec<ObjectAccess>,
+    max_frames: usize,
+    max_bytes: usize,
+    frames: usize,
+    bytes: usize,
+    truncated: bool,
+}
+
+impl Default for ExecutionTracer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExecutionTracer {
+    pub fn new() -> Self {
+        Self::with_limits(MAX_TRACED_FRAMES, MAX_TRACED_BYTES)
+    }
+
+    /// A tracer that records at most `max_frames` frames and `max_bytes` bytes of values.
+    pub fn with_limits(max_frames: usize, max_bytes: usize) -> Self {
+        Self {
+            commands: vec![],
+            open_frames: vec![],
+            dropped_frames: 0,
+            objects: vec![],
+            max_frames,
+            max_bytes,
+            frames: 0,
+            bytes: 0,
+            truncated: false,
+        }
+    }
+
+    /// Whether the tracer reached its limits and stopped recording.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Accounts for `bytes` more bytes of recorded values. Returns false, and marks the trace as
+    /// truncated, if they do not fit in the limit or the trace is already truncated.
+    fn reserve_bytes(&mut self, bytes: usize) -> bool {
+        if self.truncated || self.bytes.saturating_add(bytes) > self.max_bytes {
+            self.truncated = true;
+            return false;
+        }
+        self.bytes += bytes;
+        true
+    }
+
+    pub fn start_command(&mut self, index: usize, kind: &str) {
+        self.commands.push(CommandTrace {
+            index: index as u64,
+            kind: kind.to_string(),
+            calls: vec![],
+            events: vec![],
+            error: None,
+        });
+    }
+
+    /// Finishes the current command. Frames that are still open at this point did not return, so
+    /// they are closed as aborted.
+    pub fn end_command(&mut self, error: Option<String>, remaining_gas: u64) {
+        self.dropped_frames = 0;
+        while !self.open_frames.is_empty() {
+            self.close_frame(None, false, remaining_gas);
+        }
+
+        if let Some(command) = self.commands.last_mut() {
+            command.error = error;
+        }
+    }
+
+    pub fn open_frame(
+        &mut self,
+        module: &ModuleId,
+        function: &str,
+        type_arguments: Vec<TypeTag>,
+        arguments: Vec<Vec<u8>>,
+        remaining_gas: u64,
+    ) {
+        let size = arguments.iter().map(Vec::len).sum();
+        if self.truncated || self.frames >= self.max_frames || !self.reserve_bytes(size) {
+            self.truncated = true;
+            self.dropped_frames += 1;
+            return;
+        }
+        self.frames += 1;
+
+        let frame = FrameTrace {
+            package: ObjectID::from(*module.address()),
+            module: module.name().to_string(),
+            function: function.to_string(),
+            type_arguments,
+            arguments: arguments.into_iter().map(TracedValue::new).collect(),
+            return_values: vec![],
+            gas_used: 0,
+            is_native: false,
+            aborted: false,
+            calls: vec![],
+        };
+
+        self.open_frames.push((frame, remaining_gas));
+    }
+
+    /// Leaves the innermost open frame, which returned `return_values`, or aborted if they are
+    /// `None`.
+    pub fn close_frame(
+        &mut self,
+        return_values: Option<Vec<Vec<u8>>>,
+        is_native: bool,
+        remaining_gas: u64,
+    ) {
+        if self.dropped_frames > 0 {
+            self.dropped_frames -= 1;
+            return;
+        }
+        let Some((mut frame, gas_on_entry)) = self.open_frames.pop() else {
+            return;
+        };
+
+        frame.gas_used = gas_on_entry.saturating_sub(remaining_gas);
+        frame.is_native = is_native;
+        match return_values {
+            Some(values) => {
+                if self.reserve_bytes(values.iter().map(Vec::len).sum()) {
+                    frame.return_values = values.into_iter().map(TracedValue::new).collect();
+                }
+            }
+            None => frame.aborted = true,
+        }
+
+        if let Some((parent, _)) = self.open_frames.last_mut() {
+            parent.calls.push(frame);
+        } else if let Some(command) = self.commands.last_mut() {
+            command.calls.push(frame);
+        }
+    }
+
+    pub fn record_event(&mut self, type_: StructTag, contents: Vec<u8>) {
+        if !self.reserve_bytes(contents.len()) {
+            return;
+        }
+        if let Some(command) = self.commands.last_mut() {
+            command.events.push(EventTrace {
+                type_,
+                contents: TracedValue::new(contents),
+            });
+        }
+    }
+
+    /// Records the Move objects the transaction read or wrote, with their contents before and
+    /// after the transaction. Objects loaded dynamically (e.g. dynamic fields) are read from
+    /// `store` at the version the transaction loaded them at.
+    pub fn record_objects(
+        &mut self,
+        store: &dyn ObjectStore,
+        inner_temporary_store: &InnerTemporaryStore,
+        effects: &TransactionEffects,
+    ) {
+        let ids = |refs: Vec<ObjectRef>| -> BTreeSet<ObjectID> {
+            refs.into_iter().map(|(id, _, _)| id).collect()
+        };
+        let unwrapped = ids(effects.unwrapped().into_iter().map(|(r, _)| r).collect());
+        let deleted = ids(effects.deleted());
+        let wrapped = ids(effects.wrapped());
+        let unwrapped_then_deleted = ids(effects.unwrapped_then_deleted());
+
+        let InnerTemporaryStore {
+            input_objects,
+            written,
+            loaded_runtime_objects,
+            ..
+        } = inner_temporary_store;
+
+        let accessed: BTreeSet<ObjectID> = input_objects
+            .keys()
+            .chain(loaded_runtime_objects.keys())
+            .chain(written.keys())
+            .chain(&unwrapped_then_deleted)
+            .copied()
+            .collect();
+
+        for object_id in accessed {
+            let before = input_objects.get(&object_id).cloned().or_else(|| {
+                let metadata = loaded_runtime_objects.get(&object_id)?;
+                store
+                    .get_object_by_key(&object_id, metadata.version)
+                    .ok()
+                    .flatten()
+            });
+            let after = written.get(&object_id);
+
+            let kind = match (&before, after) {
+                (Some(_), Some(_)) => ObjectAccessKind::Mutated,
+                (None, Some(_)) if unwrapped.contains(&object_id) => ObjectAccessKind::Unwrapped,
+                (None, Some(_)) => ObjectAccessKind::Created,
+                _ if wrapped.contains(&object_id) => ObjectAccessKind::Wrapped,
+                _ if deleted.contains(&object_id) => ObjectAccessKind::Deleted,
+                _ if unwrapped_then_deleted.contains(&object_id) => {
+                    ObjectAccessKind::UnwrappedThenDeleted
+                }
+                _ => ObjectAccessKind::Read,
+            };
+
+            // Packages are not traced, and neither are objects that were unwrapped and deleted in
+            // the same transaction, because their contents are never observed.
+            let Some(type_) = before.as_ref().or(after).and_then(Object::struct_tag) else {
+                continue;
+            };
+
+            let before = before.as_ref().and_then(TracedObject::new);
+            let after = after.and_then(TracedObject::new);
+            let size = [&before, &after]
+                .into_iter()
+                .flatten()
+                .map(|object| object.contents.bcs.len())
+                .sum();
+            if !self.reserve_bytes(size) {
+                break;
+            }
+
+            self.objects.push(ObjectAccess {
+                object_id,
+                type_,
+                kind,
+                before,
+                after,
+            });
+        }
+    }
+
+    pub fn finish(self) -> ExecutionTrace {
+        ExecutionTrace {
+            version: EXECUTION_TRACE_FORMAT_VERSION,
+            commands: self.commands,
+            objects: self.objects,
+            truncated: self.truncated,
+        }
+    }
+}
+
+impl TracedObject {
+    fn new(object: &Object) -> Option<Self> {
+        let contents = object.data.try_as_move()?.contents().to_vec();
+        Some(Self {
+            version: object.version(),
+            contents: TracedValue::new(contents),
+        })
+    }
+}
+
+impl TracedValue {
+    pub fn new(bcs: Vec<u8>) -> Self {
+        Self { bcs, json: None }
+    }
+}
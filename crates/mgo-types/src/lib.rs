@@ -38,6 +38,7 @@ pub mod crypto;
 pub mod deny_list;
 pub mod digests;
 pub mod display;
+pub mod display_template;
 pub mod dynamic_field;
 pub mod effects;
 pub mod epoch_data;
@@ -46,6 +47,7 @@ pub mod executable_transaction;
 pub mod execution;
 pub mod execution_mode;
 pub mod execution_status;
+pub mod execution_trace;
 pub mod full_checkpoint_content;
 pub mod gas;
 pub mod gas_coin;
@@ -64,11 +66,15 @@ pub mod mock_checkpoint_builder;
 pub mod move_package;
 pub mod multisig;
 pub mod multisig_legacy;
+pub mod multisig_policy;
 pub mod object;
+pub mod passkey_authenticator;
 pub mod programmable_transaction_builder;
+pub mod proof;
 pub mod quorum_driver_types;
 pub mod randomness_state;
 pub mod signature;
+pub mod state_override;
 pub mod storage;
 pub mod mgo_serde;
 pub mod mgo_system_state;
@@ -41,6 +41,18 @@ pub type ThresholdUnit = u16;
 pub type BitmapUnit = u16;
 pub const MAX_SIGNER_IN_MULTISIG: usize = 10;
 pub const MAX_BITMAP_VALUE: BitmapUnit = 0b1111111111;
+/// The hard limit on how deeply multisigs can be nested, regardless of protocol config.
+pub const MAX_MULTISIG_NESTING_DEPTH: u32 = 4;
+
+/// The limits for verifying a MultiSig that has other MultiSigs as members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NestedMultiSigLimits {
+    /// The number of multisig levels allowed below the top level multisig.
+    pub max_depth: u32,
+    /// The maximum number of signatures across all levels.
+    pub max_signatures: u32,
+}
+
 /// The struct that contains signatures and public keys necessary for authenticating a MultiSig.
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
@@ -101,6 +113,42 @@ impl AuthenticatorTrait for MultiSig {
         multisig_address: MgoAddress,
         verify_params: &VerifyParams,
     ) -> Result<(), MgoError>
+    where
+        T: Serialize,
+    {
+        let message = bcs::to_bytes(&value).expect("Message serialization should not fail");
+        let mut hasher = DefaultHash::default();
+        hasher.update(message);
+        let digest = hasher.finalize().digest;
+        let mut signatures_left = verify_params
+            .nested_multisig_limits
+            .map_or(MAX_SIGNER_IN_MULTISIG as u32, |limits| {
+                limits.max_signatures
+            });
+        self.verify_claims_at_depth(
+            value,
+            &digest,
+            multisig_address,
+            verify_params,
+            0,
+            &mut signatures_left,
+        )
+    }
+}
+
+impl MultiSig {
+    /// Verifies a MultiSig that is `depth` levels below the top level multisig, against the
+    /// digest of the top level intent message. `signatures_left` is the number of signatures
+    /// that can still be verified across all levels.
+    fn verify_claims_at_depth<T>(
+        &self,
+        value: &IntentMessage<T>,
+        digest: &[u8],
+        multisig_address: MgoAddress,
+        verify_params: &VerifyParams,
+        depth: u32,
+        signatures_left: &mut u32,
+    ) -> Result<(), MgoError>
     where
         T: Serialize,
     {
@@ -116,17 +164,27 @@ impl AuthenticatorTrait for MultiSig {
             });
         }
 
-        if !self.get_zklogin_sigs()?.is_empty() && !verify_params.accept_zklogin_in_multisig {
+        if depth == 0
+            && !self.get_zklogin_sigs()?.is_empty()
+            && !verify_params.accept_zklogin_in_multisig
+        {
             return Err(MgoError::InvalidSignature {
                 error: "zkLogin sig not supported inside multisig".to_string(),
             });
         }
 
+        if self.has_multisig_members() {
+            match verify_params.nested_multisig_limits {
+                Some(limits) if depth < limits.max_depth => (),
+                _ => {
+                    return Err(MgoError::InvalidSignature {
+                        error: "Nested multisig not supported or nested too deep".to_string(),
+                    })
+                }
+            }
+        }
+
         let mut weight_sum: u16 = 0;
-        let message = bcs::to_bytes(&value).expect("Message serialization should not fail");
-        let mut hasher = DefaultHash::default();
-        hasher.update(message);
-        let digest = hasher.finalize().digest;
         // Verify each signature against its corresponding signature scheme and public key.
         // TODO: further optimization can be done because multiple Ed25519 signatures can be batch verified.
         for (sig, i) in self.sigs.iter().zip(as_indices(self.bitmap)?) {
@@ -137,6 +195,21 @@ impl AuthenticatorTrait for MultiSig {
                     .ok_or(MgoError::InvalidSignature {
                         error: "Invalid public keys index".to_string(),
                     })?;
+            if matches!(subsig_pubkey, PublicKey::MultiSig(_))
+                != matches!(sig, CompressedSignature::MultiSig(_))
+            {
+                return Err(MgoError::InvalidSignature {
+                    error: "Signature scheme does not match public key".to_string(),
+                });
+            }
+            if !matches!(sig, CompressedSignature::MultiSig(_)) {
+                *signatures_left =
+                    signatures_left
+                        .checked_sub(1)
+                        .ok_or(MgoError::InvalidSignature {
+                            error: "Too many signatures in nested multisig".to_string(),
+                        })?;
+            }
             let res =
                 match sig {
                     CompressedSignature::Ed25519(s) => {
@@ -147,7 +220,7 @@ impl AuthenticatorTrait for MultiSig {
                                 }
                             })?;
                         pk.verify(
-                            &digest,
+                            digest,
                             &s.try_into().map_err(|_| MgoError::InvalidSignature {
                                 error: "Invalid ed25519 signature bytes".to_string(),
                             })?,
@@ -160,7 +233,7 @@ impl AuthenticatorTrait for MultiSig {
                             },
                         )?;
                         pk.verify(
-                            &digest,
+                            digest,
                             &s.try_into().map_err(|_| MgoError::InvalidSignature {
                                 error: "Invalid k1 signature bytes".to_string(),
                             })?,
@@ -173,7 +246,7 @@ impl AuthenticatorTrait for MultiSig {
                             },
                         )?;
                         pk.verify(
-                            &digest,
+                            digest,
                             &s.try_into().map_err(|_| MgoError::InvalidSignature {
                                 error: "Invalid r1 signature bytes".to_string(),
                             })?,
@@ -186,6 +259,20 @@ impl AuthenticatorTrait for MultiSig {
                             .verify_claims(value, MgoAddress::from(subsig_pubkey), verify_params)
                             .map_err(|e| FastCryptoError::GeneralError(e.to_string()))
                     }
+                    CompressedSignature::MultiSig(m) => {
+                        let multisig = MultiSig::from_bytes(&m.0)
+                            .map_err(|_| MgoError::InvalidAuthenticator)?;
+                        multisig
+                            .verify_claims_at_depth(
+                                value,
+                                digest,
+                                MgoAddress::from(subsig_pubkey),
+                                verify_params,
+                                depth + 1,
+                                signatures_left,
+                            )
+                            .map_err(|e| FastCryptoError::GeneralError(e.to_string()))
+                    }
                 };
             if res.is_ok() {
                 weight_sum += *weight as u16;
@@ -305,21 +392,44 @@ impl MultiSig {
         &self.sigs
     }
 
+    /// Returns the zkLogin signatures of this MultiSig, including those of its multisig members.
     pub fn get_zklogin_sigs(&self) -> Result<Vec<ZkLoginAuthenticator>, MgoError> {
-        let authenticator_as_bytes: Vec<_> = self
-            .sigs
-            .iter()
-            .filter_map(|s| match s {
-                CompressedSignature::ZkLogin(z) => Some(z),
-                _ => None,
-            })
-            .collect();
-        authenticator_as_bytes
+        let mut zklogin_sigs = Vec::new();
+        self.collect_zklogin_sigs(0, &mut zklogin_sigs)?;
+        Ok(zklogin_sigs)
+    }
+
+    fn collect_zklogin_sigs(
+        &self,
+        depth: u32,
+        zklogin_sigs: &mut Vec<ZkLoginAuthenticator>,
+    ) -> Result<(), MgoError> {
+        if depth > MAX_MULTISIG_NESTING_DEPTH {
+            return Err(MgoError::InvalidSignature {
+                error: "Multisig nested too deep".to_string(),
+            });
+        }
+        for sig in &self.sigs {
+            match sig {
+                CompressedSignature::ZkLogin(z) => zklogin_sigs.push(
+                    ZkLoginAuthenticator::from_bytes(&z.0)
+                        .map_err(|_| MgoError::InvalidAuthenticator)?,
+                ),
+                CompressedSignature::MultiSig(m) => MultiSig::from_bytes(&m.0)
+                    .map_err(|_| MgoError::InvalidAuthenticator)?
+                    .collect_zklogin_sigs(depth + 1, zklogin_sigs)?,
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether any member of this MultiSig is itself a MultiSig.
+    pub fn has_multisig_members(&self) -> bool {
+        self.multisig_pk
+            .pk_map
             .iter()
-            .map(|z| {
-                ZkLoginAuthenticator::from_bytes(&z.0).map_err(|_| MgoError::InvalidAuthenticator)
-            })
-            .collect()
+            .any(|(pk, _weight)| matches!(pk, PublicKey::MultiSig(_)))
     }
 
     pub fn get_indices(&self) -> Result<Vec<u8>, MgoError> {
@@ -406,6 +516,8 @@ impl MultiSigPublicKey {
                 .iter()
                 .enumerate()
                 .any(|(i, pk)| pks.iter().skip(i + 1).any(|other_pk| *pk == *other_pk))
+            // Passkey signatures cannot be combined into a multisig.
+            || pks.iter().any(|pk| matches!(pk, PublicKey::Passkey(_)))
         {
             return Err(MgoError::InvalidSignature {
                 error: "Invalid multisig public key construction".to_string(),
@@ -447,6 +559,9 @@ impl MultiSigPublicKey {
                     .skip(i + 1)
                     .any(|(other_pk, _weight)| *pk == *other_pk)
             })
+            || pk_map
+                .iter()
+                .any(|(pk, _weight)| matches!(pk, PublicKey::Passkey(_)))
         {
             return Err(FastCryptoError::InvalidInput);
         }
@@ -0,0 +1,184 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    base_types::MgoAddress,
+    crypto::{PublicKey, ZkLoginPublicIdentifier},
+    error::{MgoError, MgoResult},
+    multisig::{
+        MultiSig, MultiSigPublicKey, ThresholdUnit, WeightUnit, MAX_MULTISIG_NESTING_DEPTH,
+    },
+    signature::GenericSignature,
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+#[path = "unit_tests/multisig_policy_tests.rs"]
+mod multisig_policy_tests;
+
+/// A description of a MultiSig whose members can be public keys, zkLogin accounts or other
+/// MultiSigs. It is shared by the members of a MultiSig to derive its address and to combine
+/// their partial signatures. For example, a treasury that needs 2 of its team multisig, the CFO's
+/// zkLogin account and a cold key is described as:
+///
+/// ```yaml
+/// threshold: 2
+/// members:
+///   - weight: 1
+///     multisig:
+///       threshold: 2
+///       members:
+///         - weight: 1
+///           public_key: AIA3UiQ5cLnz6pu8aFCSH+2w8J6xH8i6Bp44vTsSjCKZ
+///         - weight: 1
+///           public_key: AQIrSeiWsl/C2aALwrNBJCpZI81XSv92SggU21snPjSKGw==
+///   - weight: 1
+///     zklogin:
+///       iss: https://accounts.google.com
+///       address_seed: "2455937816256448139867920733574432108874399013957449003014574786810155574022"
+///   - weight: 1
+///     public_key: AgLSq0Q5UqPEt9kzsSE6M8pDfpu5wT/8N8lHmJbyaZWC2A==
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiSigPolicy {
+    pub threshold: ThresholdUnit,
+    pub members: Vec<MultiSigPolicyMember>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiSigPolicyMember {
+    pub weight: WeightUnit,
+    #[serde(flatten)]
+    pub signer: MultiSigPolicySigner,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MultiSigPolicySigner {
+    /// A Base64 encoded `flag || pk`, as printed by `mgo keytool list`.
+    PublicKey(#[serde(with = "public_key_base64")] PublicKey),
+    #[serde(rename = "zklogin")]
+    ZkLogin { iss: String, address_seed: String },
+    #[serde(rename = "multisig")]
+    MultiSig(MultiSigPolicy),
+}
+
+impl MultiSigPolicy {
+    /// The public key of the top level MultiSig of this policy.
+    pub fn multisig_pk(&self) -> MgoResult<MultiSigPublicKey> {
+        self.multisig_pk_at_depth(0)
+    }
+
+    pub fn address(&self) -> MgoResult<MgoAddress> {
+        Ok((&self.multisig_pk()?).into())
+    }
+
+    /// Combines the signatures of members at any level of this policy into a MultiSig for its
+    /// address. A nested MultiSig is combined from the signatures of its own members if they meet
+    /// its threshold, or can be passed in already combined.
+    pub fn combine(&self, sigs: &[GenericSignature]) -> MgoResult<MultiSig> {
+        let mut signers = Vec::new();
+        self.collect_signers(0, &mut signers)?;
+        for sig in sigs {
+            let pk = sig.to_public_key()?;
+            if !signers.contains(&pk) {
+                return Err(MgoError::IncorrectSigner {
+                    error: format!("pk is not a member of the policy: {:?}", pk),
+                });
+            }
+        }
+        self.combine_at_depth(sigs, 0)?
+            .ok_or(MgoError::InvalidSignature {
+                error: "Insufficient weight to meet the policy threshold".to_string(),
+            })
+    }
+
+    fn multisig_pk_at_depth(&self, depth: u32) -> MgoResult<MultiSigPublicKey> {
+        if depth > MAX_MULTISIG_NESTING_DEPTH {
+            return Err(MgoError::InvalidSignature {
+                error: "Multisig policy nested too deep".to_string(),
+            });
+        }
+        let pks = self
+            .members
+            .iter()
+            .map(|member| member.signer.public_key(depth))
+            .collect::<MgoResult<Vec<_>>>()?;
+        let weights = self.members.iter().map(|member| member.weight).collect();
+        MultiSigPublicKey::new(pks, weights, self.threshold)
+    }
+
+    /// Collects the public keys of the members at every level of this policy.
+    fn collect_signers(&self, depth: u32, signers: &mut Vec<PublicKey>) -> MgoResult {
+        for member in &self.members {
+            if let MultiSigPolicySigner::MultiSig(policy) = &member.signer {
+                policy.collect_signers(depth + 1, signers)?;
+            }
+            signers.push(member.signer.public_key(depth)?);
+        }
+        Ok(())
+    }
+
+    /// Returns `None` if the signatures do not meet the threshold of this policy.
+    fn combine_at_depth(
+        &self,
+        sigs: &[GenericSignature],
+        depth: u32,
+    ) -> MgoResult<Option<MultiSig>> {
+        let multisig_pk = self.multisig_pk_at_depth(depth)?;
+        let mut member_sigs = Vec::new();
+        let mut weight_sum: ThresholdUnit = 0;
+        for (member, (pk, weight)) in self.members.iter().zip(multisig_pk.pubkeys()) {
+            let combined = match &member.signer {
+                MultiSigPolicySigner::MultiSig(policy) => policy
+                    .combine_at_depth(sigs, depth + 1)?
+                    .map(GenericSignature::MultiSig),
+                _ => None,
+            };
+            let sig = match combined {
+                Some(sig) => Some(sig),
+                None => sigs
+                    .iter()
+                    .find(|sig| sig.to_public_key().is_ok_and(|sig_pk| sig_pk == *pk))
+                    .cloned(),
+            };
+            if let Some(sig) = sig {
+                weight_sum += *weight as ThresholdUnit;
+                member_sigs.push(sig);
+            }
+        }
+        if weight_sum < self.threshold {
+            return Ok(None);
+        }
+        MultiSig::combine(member_sigs, multisig_pk).map(Some)
+    }
+}
+
+impl MultiSigPolicySigner {
+    fn public_key(&self, depth: u32) -> MgoResult<PublicKey> {
+        Ok(match self {
+            MultiSigPolicySigner::PublicKey(pk) => pk.clone(),
+            MultiSigPolicySigner::ZkLogin { iss, address_seed } => {
+                PublicKey::ZkLogin(ZkLoginPublicIdentifier::new(iss, address_seed)?)
+            }
+            MultiSigPolicySigner::MultiSig(policy) => {
+                PublicKey::MultiSig((&policy.multisig_pk_at_depth(depth + 1)?).into())
+            }
+        })
+    }
+}
+
+mod public_key_base64 {
+    use crate::crypto::PublicKey;
+    use fastcrypto::traits::EncodeDecodeBase64;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(pk: &PublicKey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&pk.encode_base64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PublicKey, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        PublicKey::decode_base64(&s).map_err(|e| Error::custom(e.to_string()))
+    }
+}
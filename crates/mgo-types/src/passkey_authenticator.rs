@@ -0,0 +1,314 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Passkey authenticator for transactions signed with a WebAuthn credential.
+//!
+//! A passkey does not sign the intent message directly. The authenticator signs
+//! `authenticatorData || sha256(clientDataJSON)`, where `clientDataJSON` is produced by the browser
+//! and carries the challenge passed to `navigator.credentials.get()`. The challenge is the unpadded
+//! base64url encoding of the Blake2b256 digest of the BCS serialized intent message, i.e. the same
+//! digest an Ed25519, Secp256k1 or Secp256r1 user signature is computed over.
+
+use crate::crypto::{DefaultHash, MgoSignature, PublicKey, Signature, SignatureScheme};
+use crate::{
+    base_types::{EpochId, MgoAddress},
+    error::{MgoError, MgoResult},
+    signature::{AuthenticatorTrait, VerifyParams},
+};
+use fastcrypto::hash::{HashFunction, Sha256};
+use fastcrypto::secp256r1::{Secp256r1PublicKey, Secp256r1Signature};
+use fastcrypto::{
+    error::FastCryptoError,
+    traits::{ToFromBytes, VerifyingKey},
+};
+use once_cell::sync::OnceCell;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use shared_crypto::intent::IntentMessage;
+use std::collections::BTreeSet;
+use std::hash::Hash;
+use std::hash::Hasher;
+use url::{Host, Url};
+
+#[cfg(test)]
+#[path = "unit_tests/passkey_authenticator_test.rs"]
+mod passkey_authenticator_test;
+
+/// The only client data type of a WebAuthn assertion.
+const WEBAUTHN_GET_TYPE: &str = "webauthn.get";
+
+/// Length of rpIdHash (32 bytes), flags (1 byte) and signCount (4 bytes) at the beginning of
+/// authenticator data.
+const MIN_AUTHENTICATOR_DATA_LENGTH: usize = 37;
+
+/// The User Present (UP) flag in authenticator data.
+const USER_PRESENT_FLAG: u8 = 0x01;
+
+/// The relying parties passkey assertions can be scoped to, and the origins they can be made
+/// from. Validators take both from the protocol config, so that they all accept the same
+/// assertions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PasskeyRelyingParties {
+    pub rp_ids: BTreeSet<String>,
+    pub origins: BTreeSet<String>,
+}
+
+/// An authenticator for a WebAuthn assertion signed by a secp256r1 passkey.
+#[derive(Debug, Clone, JsonSchema, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyAuthenticator {
+    /// The authenticator data returned by the authenticator, see
+    /// <https://www.w3.org/TR/webauthn-2/#sctn-authenticator-data>.
+    authenticator_data: Vec<u8>,
+    /// The client data JSON serialized by the client, see
+    /// <https://www.w3.org/TR/webauthn-2/#dictdef-collectedclientdata>.
+    client_data_json: String,
+    /// A Secp256r1 signature `flag || sig || pk` over `authenticatorData || sha256(clientDataJSON)`.
+    /// The signature is in its 64 byte compact form.
+    user_signature: Signature,
+    #[serde(skip)]
+    pub bytes: OnceCell<Vec<u8>>,
+}
+
+/// The fields of the client data JSON that are verified.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CollectedClientData {
+    #[serde(rename = "type")]
+    type_: String,
+    challenge: String,
+    origin: String,
+    #[serde(default)]
+    cross_origin: bool,
+}
+
+impl PasskeyAuthenticator {
+    /// Create a new [struct PasskeyAuthenticator] from a WebAuthn assertion. Fails if
+    /// `user_signature` is not a Secp256r1 signature.
+    pub fn new(
+        authenticator_data: Vec<u8>,
+        client_data_json: String,
+        user_signature: Signature,
+    ) -> MgoResult<Self> {
+        if !matches!(user_signature.scheme(), SignatureScheme::Secp256r1) {
+            return Err(MgoError::InvalidSignature {
+                error: "Passkey signature must be a Secp256r1 signature".to_string(),
+            });
+        }
+        Ok(Self {
+            authenticator_data,
+            client_data_json,
+            user_signature,
+            bytes: OnceCell::new(),
+        })
+    }
+
+    pub fn authenticator_data(&self) -> &[u8] {
+        &self.authenticator_data
+    }
+
+    pub fn client_data_json(&self) -> &str {
+        &self.client_data_json
+    }
+
+    /// The public key of the passkey. Its address is derived from `passkey_flag || pk`.
+    pub fn get_pk(&self) -> MgoResult<PublicKey> {
+        PublicKey::try_from_bytes(
+            SignatureScheme::PasskeyAuthenticator,
+            self.user_signature.public_key_bytes(),
+        )
+        .map_err(|_| MgoError::KeyConversionError("Cannot parse pk".to_string()))
+    }
+
+    /// The challenge a passkey must sign to authorize `intent_msg`.
+    pub fn challenge<T: Serialize>(intent_msg: &IntentMessage<T>) -> String {
+        let mut hasher = DefaultHash::default();
+        hasher.update(bcs::to_bytes(intent_msg).expect("Message serialization should not fail"));
+        base64_url::encode(&hasher.finalize().digest)
+    }
+
+    /// The message signed by the passkey, `authenticatorData || sha256(clientDataJSON)`.
+    fn signed_message(&self) -> Vec<u8> {
+        let mut message = self.authenticator_data.clone();
+        message.extend_from_slice(&Sha256::digest(self.client_data_json.as_bytes()).digest);
+        message
+    }
+
+    fn verify_client_data<T: Serialize>(
+        &self,
+        intent_msg: &IntentMessage<T>,
+        relying_parties: Option<&PasskeyRelyingParties>,
+    ) -> MgoResult {
+        let client_data: CollectedClientData = serde_json::from_str(&self.client_data_json)
+            .map_err(|e| MgoError::InvalidSignature {
+                error: format!("Invalid client data JSON: {e}"),
+            })?;
+        if client_data.type_ != WEBAUTHN_GET_TYPE {
+            return Err(MgoError::InvalidSignature {
+                error: format!("Invalid client data type {}", client_data.type_),
+            });
+        }
+        if client_data.challenge != Self::challenge(intent_msg) {
+            return Err(MgoError::InvalidSignature {
+                error: "Passkey challenge does not match the intent message".to_string(),
+            });
+        }
+        if client_data.cross_origin {
+            return Err(MgoError::InvalidSignature {
+                error: "Cross origin passkey assertions are not supported".to_string(),
+            });
+        }
+        self.verify_origin(&client_data.origin, relying_parties)
+    }
+
+    /// Checks that `origin` is a secure origin, and that its host is the relying party the
+    /// passkey is scoped to: the rpIdHash of the authenticator data must be the sha256 of the host
+    /// or of one of its parent domains. If `relying_parties` is set, the origin and the relying
+    /// party must also be among them.
+    fn verify_origin(
+        &self,
+        origin: &str,
+        relying_parties: Option<&PasskeyRelyingParties>,
+    ) -> MgoResult {
+        let invalid_origin = |reason: &str| MgoError::InvalidSignature {
+            error: format!("Invalid passkey origin {origin}: {reason}"),
+        };
+        let url = Url::parse(origin).map_err(|e| invalid_origin(&e.to_string()))?;
+        let Some(Host::Domain(host)) = url.host() else {
+            return Err(invalid_origin("origin must have a domain"));
+        };
+        match url.scheme() {
+            "https" => (),
+            "http" if host == "localhost" => (),
+            _ => return Err(invalid_origin("origin must be https")),
+        }
+
+        if let Some(relying_parties) = relying_parties {
+            if !relying_parties.origins.contains(origin) {
+                return Err(invalid_origin("origin is not allowed"));
+            }
+        }
+
+        let rp_id_hash = &self.authenticator_data[..32];
+        let mut rp_id = Some(host);
+        while let Some(domain) = rp_id {
+            if rp_id_hash == Sha256::digest(domain.as_bytes()).digest.as_slice() {
+                return match relying_parties {
+                    Some(relying_parties) if !relying_parties.rp_ids.contains(domain) => Err(
+                        invalid_origin(&format!("relying party {domain} is not allowed")),
+                    ),
+                    _ => Ok(()),
+                };
+            }
+            rp_id = domain.split_once('.').map(|(_, parent)| parent);
+        }
+        Err(invalid_origin("rpIdHash does not match the origin"))
+    }
+}
+
+/// Necessary trait for [struct SenderSignedData].
+impl PartialEq for PasskeyAuthenticator {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+/// Necessary trait for [struct SenderSignedData].
+impl Eq for PasskeyAuthenticator {}
+
+/// Necessary trait for [struct SenderSignedData].
+impl Hash for PasskeyAuthenticator {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state);
+    }
+}
+
+impl AuthenticatorTrait for PasskeyAuthenticator {
+    fn verify_user_authenticator_epoch(&self, _: EpochId) -> MgoResult {
+        Ok(())
+    }
+
+    fn verify_uncached_checks<T>(
+        &self,
+        _intent_msg: &IntentMessage<T>,
+        _author: MgoAddress,
+        _aux_verify_data: &VerifyParams,
+    ) -> MgoResult
+    where
+        T: Serialize,
+    {
+        Ok(())
+    }
+
+    /// Verify an intent message of a transaction with a passkey authenticator.
+    fn verify_claims<T>(
+        &self,
+        intent_msg: &IntentMessage<T>,
+        author: MgoAddress,
+        aux_verify_data: &VerifyParams,
+    ) -> MgoResult
+    where
+        T: Serialize,
+    {
+        let address = MgoAddress::from(&self.get_pk()?);
+        if author != address {
+            return Err(MgoError::IncorrectSigner {
+                error: format!("Incorrect signer, expected {:?}, got {:?}", author, address),
+            });
+        }
+
+        if self.authenticator_data.len() < MIN_AUTHENTICATOR_DATA_LENGTH {
+            return Err(MgoError::InvalidSignature {
+                error: "Invalid authenticator data".to_string(),
+            });
+        }
+        if self.authenticator_data[32] & USER_PRESENT_FLAG == 0 {
+            return Err(MgoError::InvalidSignature {
+                error: "User presence flag is not set in authenticator data".to_string(),
+            });
+        }
+        self.verify_client_data(intent_msg, aux_verify_data.passkey_relying_parties.as_ref())?;
+
+        let pk = Secp256r1PublicKey::from_bytes(self.user_signature.public_key_bytes())
+            .map_err(|_| MgoError::KeyConversionError("Cannot parse pk".to_string()))?;
+        let sig = Secp256r1Signature::from_bytes(self.user_signature.signature_bytes()).map_err(
+            |_| MgoError::InvalidSignature {
+                error: "Cannot parse sig".to_string(),
+            },
+        )?;
+        pk.verify(&self.signed_message(), &sig)
+            .map_err(|e| MgoError::InvalidSignature {
+                error: format!("Fail to verify passkey sig {}", e),
+            })
+    }
+}
+
+impl ToFromBytes for PasskeyAuthenticator {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, FastCryptoError> {
+        if bytes.first().ok_or(FastCryptoError::InvalidInput)?
+            != &SignatureScheme::PasskeyAuthenticator.flag()
+        {
+            return Err(FastCryptoError::InvalidInput);
+        }
+        let passkey: PasskeyAuthenticator =
+            bcs::from_bytes(&bytes[1..]).map_err(|_| FastCryptoError::InvalidSignature)?;
+        if !matches!(passkey.user_signature.scheme(), SignatureScheme::Secp256r1) {
+            return Err(FastCryptoError::InvalidSignature);
+        }
+        Ok(passkey)
+    }
+}
+
+impl AsRef<[u8]> for PasskeyAuthenticator {
+    fn as_ref(&self) -> &[u8] {
+        self.bytes
+            .get_or_try_init::<_, eyre::Report>(|| {
+                let as_bytes = bcs::to_bytes(self).expect("BCS serialization should not fail");
+                let mut bytes = Vec::with_capacity(1 + as_bytes.len());
+                bytes.push(SignatureScheme::PasskeyAuthenticator.flag());
+                bytes.extend_from_slice(as_bytes.as_slice());
+                Ok(bytes)
+            })
+            .expect("OnceCell invariant violated")
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Proofs that an object version, or the effects and events of a transaction, are included under
+//! a certified checkpoint summary. A proof is verified with the committee of the epoch of the
+//! checkpoint alone, without trusting whoever built it.
+//!
+//! The state accumulator of a checkpoint does not support membership proofs, so an object version
+//! is proven through the transaction that wrote it, together with the effects of all transactions
+//! executed after it in the same checkpoint, none of which may mutate, wrap or delete the object.
+//! This proves that the version was the live version of the object at the end of the checkpoint.
+//! It says nothing about later checkpoints, where the object may have changed since.
+
+use crate::base_types::ObjectRef;
+use crate::committee::Committee;
+use crate::digests::TransactionDigest;
+use crate::effects::{TransactionEffects, TransactionEffectsAPI, TransactionEvents};
+use crate::error::{fp_ensure, MgoError, MgoResult};
+use crate::full_checkpoint_content::CheckpointData;
+use crate::messages_checkpoint::{CertifiedCheckpointSummary, CheckpointContents};
+use crate::transaction::Transaction;
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+#[path = "unit_tests/proof_tests.rs"]
+mod proof_tests;
+
+/// What an `InclusionProof` proves.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofTarget {
+    /// The object version was written by a transaction of the checkpoint, and was still the live
+    /// version of the object at the end of the checkpoint.
+    Object(ObjectRef),
+    /// The transaction was executed in the checkpoint, with the effects and events of the proof.
+    Transaction(TransactionDigest),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub target: ProofTarget,
+    pub checkpoint_summary: CertifiedCheckpointSummary,
+    pub checkpoint_contents: CheckpointContents,
+    /// The transaction of the checkpoint that `target` is proven through.
+    pub transaction: Transaction,
+    pub effects: TransactionEffects,
+    pub events: Option<TransactionEvents>,
+    /// For an object target, the effects of the transactions following `transaction` in the
+    /// checkpoint, showing that none of them overwrote the object.
+    pub later_effects: Vec<TransactionEffects>,
+}
+
+impl InclusionProof {
+    /// Builds the proof of `target` from the checkpoint that includes it. Returns None if no
+    /// transaction of the checkpoint wrote the object or has the digest of `target`, or if the
+    /// object was overwritten later in the checkpoint.
+    pub fn new(checkpoint: &CheckpointData, target: ProofTarget) -> Option<Self> {
+        let index = checkpoint
+            .transactions
+            .iter()
+            .position(|transaction| Self::proves(&target, &transaction.effects))?;
+        let transaction = &checkpoint.transactions[index];
+
+        let later_effects: Vec<_> = match &target {
+            ProofTarget::Object(object_ref) => {
+                let later_effects: Vec<_> = checkpoint.transactions[index + 1..]
+                    .iter()
+                    .map(|transaction| transaction.effects.clone())
+                    .collect();
+                if later_effects
+                    .iter()
+                    .any(|effects| Self::overwrites(effects, object_ref))
+                {
+                    return None;
+                }
+                later_effects
+            }
+            ProofTarget::Transaction(_) => vec![],
+        };
+
+        Some(Self {
+            target,
+            checkpoint_summary: checkpoint.checkpoint_summary.clone(),
+            checkpoint_contents: checkpoint.checkpoint_contents.clone(),
+            transaction: transaction.transaction.clone(),
+            effects: transaction.effects.clone(),
+            events: transaction.events.clone(),
+            later_effects,
+        })
+    }
+
+    /// Verifies that the checkpoint summary is certified by `committee`, and that `target` is
+    /// included under it.
+    pub fn verify(&self, committee: &Committee) -> MgoResult {
+        // Also checks that the contents are the ones committed to by the summary.
+        self.checkpoint_summary
+            .verify_with_contents(committee, Some(&self.checkpoint_contents))?;
+
+        let execution_digests = self.effects.execution_digests();
+        let Some(index) = self
+            .checkpoint_contents
+            .iter()
+            .position(|digests| *digests == execution_digests)
+        else {
+            return Err(invalid_proof(format!(
+                "transaction {} with effects {} is not included in checkpoint {}",
+                execution_digests.transaction,
+                execution_digests.effects,
+                self.checkpoint_summary.sequence_number
+            )));
+        };
+        fp_ensure!(
+            *self.transaction.digest() == execution_digests.transaction,
+            invalid_proof(format!(
+                "transaction {} does not match effects of transaction {}",
+                self.transaction.digest(),
+                execution_digests.transaction
+            ))
+        );
+        fp_ensure!(
+            self.events.as_ref().map(|events| events.digest()).as_ref()
+                == self.effects.events_digest(),
+            invalid_proof("events do not match the events digest of the effects".to_string())
+        );
+        fp_ensure!(
+            Self::proves(&self.target, &self.effects),
+            invalid_proof(format!(
+                "{:?} is not proven by transaction {}",
+                self.target, execution_digests.transaction
+            ))
+        );
+
+        if let ProofTarget::Object(object_ref) = &self.target {
+            // The later effects must be exactly those of the rest of the checkpoint, so that
+            // none of the transactions that could have overwritten the object is left out.
+            let later_digests: Vec<_> = self
+                .later_effects
+                .iter()
+                .map(|effects| effects.execution_digests())
+                .collect();
+            fp_ensure!(
+                self.checkpoint_contents
+                    .iter()
+                    .skip(index + 1)
+                    .eq(later_digests.iter()),
+                invalid_proof(format!(
+                    "later effects do not match the transactions following {} in checkpoint {}",
+                    execution_digests.transaction, self.checkpoint_summary.sequence_number
+                ))
+            );
+            if let Some(effects) = self
+                .later_effects
+                .iter()
+                .find(|effects| Self::overwrites(effects, object_ref))
+            {
+                return Err(invalid_proof(format!(
+                    "object {} was overwritten by transaction {} later in checkpoint {}",
+                    object_ref.0,
+                    effects.transaction_digest(),
+                    self.checkpoint_summary.sequence_number
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn proves(target: &ProofTarget, effects: &TransactionEffects) -> bool {
+        match target {
+            ProofTarget::Object(object_ref) => effects
+                .all_changed_objects()
+                .iter()
+                .any(|(written, _, _)| written == object_ref),
+            ProofTarget::Transaction(digest) => effects.transaction_digest() == digest,
+        }
+    }
+
+    /// Whether the transaction with `effects` mutated, wrapped or deleted the object.
+    fn overwrites(effects: &TransactionEffects, object_ref: &ObjectRef) -> bool {
+        effects
+            .modified_at_versions()
+            .iter()
+            .any(|(id, _)| *id == object_ref.0)
+    }
+}
+
+fn invalid_proof(error: String) -> MgoError {
+    MgoError::InvalidInclusionProof { error }
+}
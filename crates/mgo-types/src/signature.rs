@@ -3,12 +3,19 @@
 
 use crate::committee::EpochId;
 use crate::crypto::{
-    CompressedSignature, PublicKey, SignatureScheme, MgoSignature, ZkLoginAuthenticatorAsBytes,
+    CompressedSignature, MultiSigAsBytes, MultiSigPublicIdentifier, PublicKey, SignatureScheme,
+    MgoSignature, ZkLoginAuthenticatorAsBytes,
 };
 use crate::error::MgoError;
 use crate::multisig_legacy::MultiSigLegacy;
+use crate::passkey_authenticator::{PasskeyAuthenticator, PasskeyRelyingParties};
 use crate::zk_login_authenticator::ZkLoginAuthenticator;
-use crate::{base_types::MgoAddress, crypto::Signature, error::MgoResult, multisig::MultiSig};
+use crate::{
+    base_types::MgoAddress,
+    crypto::Signature,
+    error::MgoResult,
+    multisig::{MultiSig, NestedMultiSigLimits},
+};
 pub use enum_dispatch::enum_dispatch;
 use fastcrypto::ed25519::{Ed25519PublicKey, Ed25519Signature};
 use fastcrypto::secp256k1::{Secp256k1PublicKey, Secp256k1Signature};
@@ -32,6 +39,10 @@ pub struct VerifyParams {
     pub zk_login_env: ZkLoginEnv,
     pub verify_legacy_zklogin_address: bool,
     pub accept_zklogin_in_multisig: bool,
+    // Multisigs with multisig members are rejected if not set.
+    pub nested_multisig_limits: Option<NestedMultiSigLimits>,
+    // Passkey assertions are only checked against the relying party they claim if not set.
+    pub passkey_relying_parties: Option<PasskeyRelyingParties>,
 }
 
 impl VerifyParams {
@@ -48,6 +59,8 @@ impl VerifyParams {
             zk_login_env,
             verify_legacy_zklogin_address,
             accept_zklogin_in_multisig,
+            nested_multisig_limits: None,
+            passkey_relying_parties: None,
         }
     }
 }
@@ -103,6 +116,7 @@ pub enum GenericSignature {
     MultiSigLegacy,
     Signature,
     ZkLoginAuthenticator,
+    PasskeyAuthenticator,
 }
 
 impl GenericSignature {
@@ -110,10 +124,18 @@ impl GenericSignature {
         matches!(self, GenericSignature::ZkLoginAuthenticator(_))
     }
 
+    pub fn is_passkey(&self) -> bool {
+        matches!(self, GenericSignature::PasskeyAuthenticator(_))
+    }
+
     pub fn is_upgraded_multisig(&self) -> bool {
         matches!(self, GenericSignature::MultiSig(_))
     }
 
+    pub fn is_nested_multisig(&self) -> bool {
+        matches!(self, GenericSignature::MultiSig(s) if s.has_multisig_members())
+    }
+
     /// Parse [enum CompressedSignature] from trait MgoSignature `flag || sig || pk`.
     /// This is useful for the MultiSig to combine partial signature into a MultiSig public key.
     pub fn to_compressed(&self) -> Result<CompressedSignature, MgoError> {
@@ -153,6 +175,9 @@ impl GenericSignature {
             GenericSignature::ZkLoginAuthenticator(s) => Ok(CompressedSignature::ZkLogin(
                 ZkLoginAuthenticatorAsBytes(s.as_ref().to_vec()),
             )),
+            GenericSignature::MultiSig(s) => Ok(CompressedSignature::MultiSig(MultiSigAsBytes(
+                s.as_ref().to_vec(),
+            ))),
             _ => Err(MgoError::UnsupportedFeatureError {
                 error: "Unsupported signature scheme".to_string(),
             }),
@@ -190,6 +215,10 @@ impl GenericSignature {
                 }
             }
             GenericSignature::ZkLoginAuthenticator(s) => s.get_pk(),
+            GenericSignature::PasskeyAuthenticator(s) => s.get_pk(),
+            GenericSignature::MultiSig(s) => Ok(PublicKey::MultiSig(
+                MultiSigPublicIdentifier::from(s.get_pk()),
+            )),
             _ => Err(MgoError::UnsupportedFeatureError {
                 error: "Unsupported signature scheme".to_string(),
             }),
@@ -202,6 +231,8 @@ impl GenericSignature {
 /// of [struct MultiSigLegacy] i.e. `flag || bcs_bytes(MultiSigLegacy)`.
 /// [struct Multisig] is encodede as the MultiSig flag (0x03) concat with the bcs serializedbytes
 /// of [struct Multisig] i.e. `flag || bcs_bytes(Multisig)`.
+/// [struct PasskeyAuthenticator] is encoded as the passkey flag (0x06) concat with the bcs
+/// serialized bytes of [struct PasskeyAuthenticator] i.e. `flag || bcs_bytes(PasskeyAuthenticator)`.
 impl ToFromBytes for GenericSignature {
     fn from_bytes(bytes: &[u8]) -> Result<Self, FastCryptoError> {
         match SignatureScheme::from_flag_byte(
@@ -224,6 +255,10 @@ impl ToFromBytes for GenericSignature {
                     let zk_login = ZkLoginAuthenticator::from_bytes(bytes)?;
                     Ok(GenericSignature::ZkLoginAuthenticator(zk_login))
                 }
+                SignatureScheme::PasskeyAuthenticator => {
+                    let passkey = PasskeyAuthenticator::from_bytes(bytes)?;
+                    Ok(GenericSignature::PasskeyAuthenticator(passkey))
+                }
                 _ => Err(FastCryptoError::InvalidInput),
             },
             Err(_) => Err(FastCryptoError::InvalidInput),
@@ -239,6 +274,7 @@ impl AsRef<[u8]> for GenericSignature {
             GenericSignature::MultiSigLegacy(s) => s.as_ref(),
             GenericSignature::Signature(s) => s.as_ref(),
             GenericSignature::ZkLoginAuthenticator(s) => s.as_ref(),
+            GenericSignature::PasskeyAuthenticator(s) => s.as_ref(),
         }
     }
 }
@@ -0,0 +1,278 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hypothetical ("what-if") state that a dry run can be executed against instead of the live
+//! state: replaced object contents or coin balances, packages that have not been published yet,
+//! a simulated sender and an overridden clock timestamp and epoch.
+
+use std::collections::BTreeMap;
+
+use mgo_protocol_config::ProtocolConfig;
+use move_binary_format::CompiledModule;
+use move_core_types::annotated_value::MoveStruct;
+
+use crate::base_types::{MgoAddress, ObjectID, ObjectRef, SequenceNumber, VersionNumber};
+use crate::committee::EpochId;
+use crate::digests::TransactionDigest;
+use crate::error::{MgoResult, UserInputError, UserInputResult};
+use crate::move_package::MovePackage;
+use crate::object::{Object, Owner};
+use crate::storage::{
+    BackingPackageStore, ChildObjectResolver, ObjectStore, PackageObject, ParentSync,
+};
+use crate::type_resolver::LayoutResolver;
+
+#[cfg(test)]
+#[path = "unit_tests/state_override_tests.rs"]
+mod state_override_tests;
+
+/// A replacement for the state of an existing object. The object keeps its ID, type, owner and
+/// version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ObjectOverride {
+    /// The BCS serialized contents of the Move object, starting with its ID.
+    Contents(Vec<u8>),
+    /// The balance of a `Coin<T>` object.
+    Balance(u64),
+}
+
+/// A package that is treated as published at the address of its modules.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PackageOverride {
+    /// The serialized modules of the package.
+    pub modules: Vec<Vec<u8>>,
+    /// The IDs of all packages the modules depend on, transitively.
+    pub dependencies: Vec<ObjectID>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateOverrides {
+    pub objects: BTreeMap<ObjectID, ObjectOverride>,
+    pub packages: Vec<PackageOverride>,
+    /// Execute the transaction on behalf of this sender, without checking that it owns the
+    /// transaction's owned inputs.
+    pub sender: Option<MgoAddress>,
+    /// The timestamp of the `Clock` object.
+    pub timestamp_ms: Option<u64>,
+    /// The epoch the transaction executes in.
+    pub epoch: Option<EpochId>,
+}
+
+impl StateOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+            && self.packages.is_empty()
+            && self.sender.is_none()
+            && self.timestamp_ms.is_none()
+            && self.epoch.is_none()
+    }
+}
+
+impl ObjectOverride {
+    /// Returns a copy of `object` with this override applied. Overridden contents must
+    /// deserialize to the object's type, as laid out by `layout_resolver`.
+    pub fn apply(
+        &self,
+        object: &Object,
+        layout_resolver: &mut dyn LayoutResolver,
+        protocol_config: &ProtocolConfig,
+    ) -> UserInputResult<Object> {
+        let object_id = object.id();
+        let invalid = |error: String| UserInputError::InvalidStateOverride { object_id, error };
+
+        let mut inner = object.clone().into_inner();
+        let Some(move_object) = inner.data.try_as_move_mut() else {
+            return Err(invalid("only Move objects can be overridden".to_string()));
+        };
+        match self {
+            ObjectOverride::Contents(contents) => {
+                if contents.get(..ObjectID::LENGTH) != Some(object_id.as_ref()) {
+                    return Err(invalid(
+                        "contents must start with the object's ID".to_string(),
+                    ));
+                }
+                let layout = layout_resolver
+                    .get_annotated_layout(&move_object.type_().clone().into())
+                    .map_err(|e| invalid(e.to_string()))?;
+                MoveStruct::simple_deserialize(contents, &layout).map_err(|e| {
+                    invalid(format!("contents do not match the object's type: {e}"))
+                })?;
+                move_object
+                    .update_contents(contents.clone(), protocol_config)
+                    .map_err(|e| invalid(e.to_string()))?;
+            }
+            ObjectOverride::Balance(balance) => {
+                if !move_object.is_coin() {
+                    return Err(invalid(
+                        "only the balance of a coin can be overridden".to_string(),
+                    ));
+                }
+                move_object.set_coin_value_unsafe(*balance);
+            }
+        }
+        Ok(inner.into())
+    }
+}
+
+impl PackageOverride {
+    /// Builds the package object, linking it against `dependencies`. The modules must pass
+    /// `verify`, which should run the same verifiers as publishing the package would.
+    pub fn to_package_object<'p>(
+        &self,
+        protocol_config: &ProtocolConfig,
+        dependencies: impl IntoIterator<Item = &'p MovePackage>,
+        verify: impl FnOnce(&[CompiledModule]) -> MgoResult<()>,
+    ) -> UserInputResult<Object> {
+        let modules = self
+            .modules
+            .iter()
+            .map(|bytes| {
+                CompiledModule::deserialize_with_config(
+                    bytes,
+                    protocol_config.move_binary_format_version(),
+                    protocol_config.no_extraneous_module_bytes(),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| UserInputError::InvalidStateOverride {
+                object_id: ObjectID::ZERO,
+                error: format!("Failed to deserialize package module: {e}"),
+            })?;
+
+        let Some(package_id) = modules.first().map(|m| ObjectID::from(*m.address())) else {
+            return Err(UserInputError::InvalidStateOverride {
+                object_id: ObjectID::ZERO,
+                error: "package overrides must contain at least one module".to_string(),
+            });
+        };
+        let invalid = |error: String| UserInputError::InvalidStateOverride {
+            object_id: package_id,
+            error,
+        };
+        if package_id == ObjectID::ZERO {
+            return Err(invalid(
+                "package modules must be compiled against the package's address".to_string(),
+            ));
+        }
+        if modules
+            .iter()
+            .any(|m| ObjectID::from(*m.address()) != package_id)
+        {
+            return Err(invalid(
+                "all package modules must share an address".to_string(),
+            ));
+        }
+        verify(&modules).map_err(|e| invalid(e.to_string()))?;
+
+        Object::new_package(
+            &modules,
+            TransactionDigest::genesis_marker(),
+            protocol_config.max_move_package_size(),
+            dependencies,
+        )
+        .map_err(|e| invalid(e.to_string()))
+    }
+}
+
+/// Serves the objects in `overrides` in place of the corresponding objects in `fallback`, so
+/// that execution, including dynamic field accesses and package loads, observes them.
+pub struct StateOverrideStore<'a, S: ?Sized> {
+    overrides: &'a BTreeMap<ObjectID, Object>,
+    fallback: &'a S,
+}
+
+impl<'a, S: ?Sized> StateOverrideStore<'a, S> {
+    pub fn new(overrides: &'a BTreeMap<ObjectID, Object>, fallback: &'a S) -> Self {
+        Self {
+            overrides,
+            fallback,
+        }
+    }
+}
+
+impl<S: ?Sized + BackingPackageStore> BackingPackageStore for StateOverrideStore<'_, S> {
+    fn get_package_object(&self, package_id: &ObjectID) -> MgoResult<Option<PackageObject>> {
+        match self.overrides.get(package_id) {
+            Some(object) if object.is_package() => Ok(Some(PackageObject::new(object.clone()))),
+            _ => self.fallback.get_package_object(package_id),
+        }
+    }
+}
+
+impl<S: ?Sized + ChildObjectResolver> ChildObjectResolver for StateOverrideStore<'_, S> {
+    fn read_child_object(
+        &self,
+        parent: &ObjectID,
+        child: &ObjectID,
+        child_version_upper_bound: SequenceNumber,
+    ) -> MgoResult<Option<Object>> {
+        match self.overrides.get(child) {
+            Some(object)
+                if object.owner == Owner::ObjectOwner((*parent).into())
+                    && object.version() <= child_version_upper_bound =>
+            {
+                Ok(Some(object.clone()))
+            }
+            _ => self
+                .fallback
+                .read_child_object(parent, child, child_version_upper_bound),
+        }
+    }
+
+    fn get_object_received_at_version(
+        &self,
+        owner: &ObjectID,
+        receiving_object_id: &ObjectID,
+        receive_object_at_version: SequenceNumber,
+        epoch_id: EpochId,
+    ) -> MgoResult<Option<Object>> {
+        match self.overrides.get(receiving_object_id) {
+            Some(object)
+                if object.owner == Owner::AddressOwner((*owner).into())
+                    && object.version() == receive_object_at_version =>
+            {
+                Ok(Some(object.clone()))
+            }
+            _ => self.fallback.get_object_received_at_version(
+                owner,
+                receiving_object_id,
+                receive_object_at_version,
+                epoch_id,
+            ),
+        }
+    }
+}
+
+impl<S: ?Sized + ObjectStore> ObjectStore for StateOverrideStore<'_, S> {
+    fn get_object(&self, object_id: &ObjectID) -> crate::storage::error::Result<Option<Object>> {
+        match self.overrides.get(object_id) {
+            Some(object) => Ok(Some(object.clone())),
+            None => self.fallback.get_object(object_id),
+        }
+    }
+
+    fn get_object_by_key(
+        &self,
+        object_id: &ObjectID,
+        version: VersionNumber,
+    ) -> crate::storage::error::Result<Option<Object>> {
+        match self.overrides.get(object_id) {
+            Some(object) if object.version() == version => Ok(Some(object.clone())),
+            _ => self.fallback.get_object_by_key(object_id, version),
+        }
+    }
+}
+
+impl<S: ?Sized + ParentSync> ParentSync for StateOverrideStore<'_, S> {
+    fn get_latest_parent_entry_ref_deprecated(
+        &self,
+        object_id: ObjectID,
+    ) -> MgoResult<Option<ObjectRef>> {
+        match self.overrides.get(&object_id) {
+            Some(object) => Ok(Some(object.compute_object_reference())),
+            None => self
+                .fallback
+                .get_latest_parent_entry_ref_deprecated(object_id),
+        }
+    }
+}
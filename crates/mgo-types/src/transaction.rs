@@ -11,7 +11,7 @@ use crate::crypto::{
     ToFromBytes,
 };
 use crate::digests::ConsensusCommitDigest;
-use crate::digests::{CertificateDigest, SenderSignedDataDigest};
+use crate::digests::{CertificateDigest, ChainIdentifier, SenderSignedDataDigest};
 use crate::execution::SharedInput;
 use crate::message_envelope::{
     AuthenticatedMessage, Envelope, Message, TrustedEnvelope, VerifiedEnvelope,
@@ -1398,6 +1398,91 @@ pub enum TransactionExpiration {
     /// Validators wont sign a transaction unless the expiration Epoch
     /// is greater than or equal to the current epoch
     Epoch(EpochId),
+    /// Validators wont sign a transaction unless the consensus time is within
+    /// `[min_timestamp_ms, max_timestamp_ms]` and, if `chain` is set, the transaction
+    /// is submitted to that chain. `nonce` distinguishes otherwise identical transactions.
+    ValidDuring {
+        min_timestamp_ms: Option<u64>,
+        max_timestamp_ms: Option<u64>,
+        chain: Option<ChainIdentifier>,
+        nonce: u32,
+    },
+}
+
+impl TransactionExpiration {
+    pub fn is_timestamp_based(&self) -> bool {
+        matches!(self, TransactionExpiration::ValidDuring { .. })
+    }
+
+    /// Checks that a transaction with this expiration can be signed in `epoch` on `chain`.
+    /// `consensus_time_ms` is only called for timestamp based expirations.
+    pub fn check(
+        &self,
+        epoch: EpochId,
+        chain: ChainIdentifier,
+        consensus_time_ms: impl FnOnce() -> MgoResult<u64>,
+    ) -> MgoResult {
+        match self {
+            TransactionExpiration::None => Ok(()),
+            TransactionExpiration::Epoch(expiration_epoch) => {
+                fp_ensure!(*expiration_epoch >= epoch, MgoError::TransactionExpired);
+                Ok(())
+            }
+            TransactionExpiration::ValidDuring {
+                min_timestamp_ms,
+                max_timestamp_ms,
+                chain: expected_chain,
+                nonce: _,
+            } => {
+                if let Some(expected_chain) = expected_chain {
+                    fp_ensure!(
+                        *expected_chain == chain,
+                        UserInputError::InvalidChainId {
+                            provided: expected_chain.to_string(),
+                            expected: chain.to_string(),
+                        }
+                        .into()
+                    );
+                }
+                let current_timestamp_ms = consensus_time_ms()?;
+                if let Some(max_timestamp_ms) = max_timestamp_ms {
+                    fp_ensure!(
+                        current_timestamp_ms <= *max_timestamp_ms,
+                        MgoError::TransactionExpired
+                    );
+                }
+                if let Some(min_timestamp_ms) = min_timestamp_ms {
+                    fp_ensure!(
+                        current_timestamp_ms >= *min_timestamp_ms,
+                        MgoError::TransactionNotYetValid {
+                            min_timestamp_ms: *min_timestamp_ms,
+                            current_timestamp_ms,
+                        }
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn validity_check(&self) -> UserInputResult {
+        if let TransactionExpiration::ValidDuring {
+            min_timestamp_ms: Some(min_timestamp_ms),
+            max_timestamp_ms: Some(max_timestamp_ms),
+            ..
+        } = self
+        {
+            fp_ensure!(
+                min_timestamp_ms <= max_timestamp_ms,
+                UserInputError::InvalidTransactionExpiration {
+                    error: format!(
+                        "min_timestamp_ms {min_timestamp_ms} is greater than max_timestamp_ms {max_timestamp_ms}"
+                    ),
+                }
+            );
+        }
+        Ok(())
+    }
 }
 
 #[enum_dispatch(TransactionDataAPI)]
@@ -1434,6 +1519,12 @@ impl VersionedProtocolMessage for TransactionData {
             });
         }
 
+        if self.expiration().is_timestamp_based() && !protocol_config.timestamp_expiration() {
+            return Err(MgoError::UnsupportedFeatureError {
+                error: "timestamp based transaction expiration is not enabled".to_string(),
+            });
+        }
+
         // Now check interior versioned data
         self.kind().check_version_supported(protocol_config)?;
 
@@ -1467,6 +1558,15 @@ impl TransactionData {
         })
     }
 
+    /// Sets the expiration of the transaction, e.g. to limit how long a pre-signed transaction
+    /// remains valid.
+    pub fn with_expiration(mut self, expiration: TransactionExpiration) -> Self {
+        match &mut self {
+            TransactionData::V1(data) => data.expiration = expiration,
+        }
+        self
+    }
+
     pub fn new(
         kind: TransactionKind,
         sender: MgoAddress,
@@ -1981,6 +2081,7 @@ impl TransactionDataAPI for TransactionDataV1 {
     // may not be provided and created "on the fly"
     fn validity_check_no_gas_check(&self, config: &ProtocolConfig) -> UserInputResult {
         self.kind().validity_check(config)?;
+        self.expiration.validity_check()?;
         self.check_sponsorship()
     }
 
@@ -2126,6 +2227,10 @@ impl SenderSignedData {
         self.tx_signatures().iter().any(|sig| sig.is_zklogin())
     }
 
+    pub fn has_passkey_sig(&self) -> bool {
+        self.tx_signatures().iter().any(|sig| sig.is_passkey())
+    }
+
     pub fn has_upgraded_multisig(&self) -> bool {
         self.tx_signatures()
             .iter()
@@ -2211,6 +2316,22 @@ impl VersionedProtocolMessage for SenderSignedData {
                             error: "multisig format not enabled on this network".to_string(),
                         });
                     }
+                    if sig.is_nested_multisig()
+                        && protocol_config
+                            .max_multisig_nesting_depth_as_option()
+                            .is_none()
+                    {
+                        return Err(MgoError::UnsupportedFeatureError {
+                            error: "nested multisig is not enabled on this network".to_string(),
+                        });
+                    }
+                }
+                GenericSignature::PasskeyAuthenticator(_) => {
+                    if !protocol_config.passkey_auth() {
+                        return Err(MgoError::UnsupportedFeatureError {
+                            error: "passkey is not enabled on this network".to_string(),
+                        });
+                    }
                 }
                 GenericSignature::Signature(_)
                 | GenericSignature::MultiSigLegacy(_)
@@ -0,0 +1,297 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use move_core_types::annotated_value::{MoveStruct, MoveValue};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag};
+
+use super::{
+    DisplayGrammar, DisplayRenderError, DisplayStore, DisplayTemplate, DISPLAY_GRAMMAR_KEY,
+};
+use crate::base_types::ObjectID;
+use crate::collection_types::{Entry, VecMap};
+use crate::dynamic_field::{derive_dynamic_field_id, DynamicFieldInfo};
+use crate::parse_mgo_struct_tag;
+
+#[derive(Default)]
+struct TestStore {
+    objects: BTreeMap<ObjectID, MoveStruct>,
+    coin_decimals: BTreeMap<String, u8>,
+}
+
+#[async_trait]
+impl DisplayStore for TestStore {
+    async fn get_object(&self, id: ObjectID) -> anyhow::Result<Option<MoveStruct>> {
+        Ok(self.objects.get(&id).cloned())
+    }
+
+    async fn get_coin_decimals(&self, coin_type: &StructTag) -> anyhow::Result<Option<u8>> {
+        Ok(self
+            .coin_decimals
+            .get(&coin_type.to_canonical_string(/* with_prefix */ true))
+            .copied())
+    }
+}
+
+fn tag(s: &str) -> StructTag {
+    parse_mgo_struct_tag(s).unwrap()
+}
+
+fn new_struct(type_: &str, fields: Vec<(&str, MoveValue)>) -> MoveStruct {
+    MoveStruct::new(
+        tag(type_),
+        fields
+            .into_iter()
+            .map(|(name, value)| (Identifier::new(name).unwrap(), value))
+            .collect(),
+    )
+}
+
+fn string(s: &str) -> MoveValue {
+    MoveValue::Struct(new_struct(
+        "0x1::string::String",
+        vec![(
+            "bytes",
+            MoveValue::Vector(s.bytes().map(MoveValue::U8).collect()),
+        )],
+    ))
+}
+
+fn option(value: Option<MoveValue>) -> MoveValue {
+    MoveValue::Struct(new_struct(
+        "0x1::option::Option<0x1::string::String>",
+        vec![("vec", MoveValue::Vector(value.into_iter().collect()))],
+    ))
+}
+
+fn id(id: ObjectID) -> MoveValue {
+    MoveValue::Struct(new_struct(
+        "0x2::object::ID",
+        vec![("bytes", MoveValue::Address(id.into()))],
+    ))
+}
+
+fn uid(object_id: ObjectID) -> MoveValue {
+    MoveValue::Struct(new_struct("0x2::object::UID", vec![("id", id(object_id))]))
+}
+
+fn hero_id() -> ObjectID {
+    ObjectID::from_single_byte(0x42)
+}
+
+fn hero() -> MoveStruct {
+    new_struct(
+        "0x42::hero::Hero",
+        vec![
+            ("id", uid(hero_id())),
+            ("name", string("Mango")),
+            ("level", MoveValue::U64(1234567)),
+            (
+                "tags",
+                MoveValue::Vector(vec![string("brave"), string("swift")]),
+            ),
+            ("nickname", option(None)),
+            ("title", option(Some(string("Sir")))),
+            ("legendary", MoveValue::Bool(true)),
+            (
+                "reward",
+                MoveValue::Struct(new_struct(
+                    "0x2::balance::Balance<0x2::mgo::MGO>",
+                    vec![("value", MoveValue::U64(1_500_000_000))],
+                )),
+            ),
+        ],
+    )
+}
+
+fn store() -> TestStore {
+    let string_type: TypeTag = tag("0x1::string::String").into();
+    let mut store = TestStore::default();
+
+    let power_id =
+        derive_dynamic_field_id(hero_id(), &string_type, &bcs::to_bytes("power").unwrap()).unwrap();
+    store.objects.insert(
+        power_id,
+        new_struct(
+            "0x2::dynamic_field::Field<0x1::string::String, u64>",
+            vec![
+                ("id", uid(power_id)),
+                ("name", string("power")),
+                ("value", MoveValue::U64(9001)),
+            ],
+        ),
+    );
+
+    let sword_id = ObjectID::from_single_byte(0x43);
+    store.objects.insert(
+        sword_id,
+        new_struct(
+            "0x42::hero::Sword",
+            vec![("id", uid(sword_id)), ("name", string("Excalibur"))],
+        ),
+    );
+    let wrapper_type = DynamicFieldInfo::dynamic_object_field_wrapper(string_type).into();
+    let sword_field_id =
+        derive_dynamic_field_id(hero_id(), &wrapper_type, &bcs::to_bytes("sword").unwrap())
+            .unwrap();
+    store.objects.insert(
+        sword_field_id,
+        new_struct(
+            "0x2::dynamic_field::Field<0x2::dynamic_object_field::Wrapper<0x1::string::String>, 0x2::object::ID>",
+            vec![
+                ("id", uid(sword_field_id)),
+                ("name", string("sword")),
+                ("value", id(sword_id)),
+            ],
+        ),
+    );
+
+    store
+        .coin_decimals
+        .insert(tag("0x2::mgo::MGO").to_canonical_string(true), 9);
+    store
+}
+
+async fn render(template: &str) -> Result<String, DisplayRenderError> {
+    DisplayTemplate::parse(template)?
+        .render(&hero(), &store())
+        .await
+}
+
+#[test]
+fn test_grammar_version() {
+    let fields = |entries: &[(&str, &str)]| VecMap {
+        contents: entries
+            .iter()
+            .map(|(key, value)| Entry {
+                key: key.to_string(),
+                value: value.to_string(),
+            })
+            .collect(),
+    };
+    assert_eq!(
+        DisplayGrammar::from_fields(&fields(&[("name", "{name}")])),
+        Ok(DisplayGrammar::V1)
+    );
+    assert_eq!(
+        DisplayGrammar::from_fields(&fields(&[(DISPLAY_GRAMMAR_KEY, "2"), ("name", "{name}")])),
+        Ok(DisplayGrammar::V2)
+    );
+    assert_eq!(
+        DisplayGrammar::from_fields(&fields(&[(DISPLAY_GRAMMAR_KEY, "3")])),
+        Err(DisplayRenderError::UnsupportedGrammar("3".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn test_fields_and_escapes() {
+    assert_eq!(
+        render("Hero {name} ({title})").await.unwrap(),
+        "Hero Mango (Sir)"
+    );
+    assert_eq!(render("{id}").await.unwrap(), hero_id().to_string());
+    assert_eq!(render("{ level }").await.unwrap(), "1234567");
+    assert_eq!(render("\\{name\\} \\\\").await.unwrap(), "{name} \\");
+    // Options which are none render nothing, like in grammar version 1
+    assert_eq!(render("[{nickname}]").await.unwrap(), "[]");
+}
+
+#[tokio::test]
+async fn test_indexing_and_fallbacks() {
+    assert_eq!(render("{tags[1]}").await.unwrap(), "swift");
+    assert_eq!(render("{tags[2] | 'untagged'}").await.unwrap(), "untagged");
+    assert_eq!(render("{nickname | title | name}").await.unwrap(), "Sir");
+    assert_eq!(
+        render("{nickname | 'It\\'s {me}'}").await.unwrap(),
+        "It's {me}"
+    );
+    assert_eq!(render("{nickname | 42}").await.unwrap(), "42");
+}
+
+#[tokio::test]
+async fn test_formats() {
+    assert_eq!(render("{level:grouped}").await.unwrap(), "1,234,567");
+    assert_eq!(render("{level:decimals(2)}").await.unwrap(), "12345.67");
+    assert_eq!(render("{level:decimals(9)}").await.unwrap(), "0.001234567");
+    assert_eq!(render("{reward:coin} MGO").await.unwrap(), "1.5 MGO");
+    assert_eq!(
+        render("{level:coin(0x2::mgo::MGO)}").await.unwrap(),
+        "0.001234567"
+    );
+    assert_eq!(
+        render("{level:coin(0x42::hero::GEM)}").await,
+        Err(DisplayRenderError::CoinMetadataNotFound(
+            tag("0x42::hero::GEM").to_canonical_string(true)
+        ))
+    );
+    assert!(matches!(
+        render("{name:grouped}").await,
+        Err(DisplayRenderError::InvalidFormat(..))
+    ));
+}
+
+#[tokio::test]
+async fn test_conditionals() {
+    assert_eq!(
+        render("{#if legendary}Legendary {#else}Common {/if}{name}")
+            .await
+            .unwrap(),
+        "Legendary Mango"
+    );
+    assert_eq!(
+        render("{#if nickname}{nickname}{#else}{#if title}{title} {/if}{name}{/if}")
+            .await
+            .unwrap(),
+        "Sir Mango"
+    );
+    assert_eq!(render("{#if false}hidden{/if}").await.unwrap(), "");
+}
+
+#[tokio::test]
+async fn test_dynamic_fields() {
+    assert_eq!(render("{id->['power']}").await.unwrap(), "9001");
+    assert_eq!(render("{id->['speed'] | 0}").await.unwrap(), "0");
+    assert_eq!(render("{id=>['sword'].name}").await.unwrap(), "Excalibur");
+    assert_eq!(render("{#if id=>['shield']}shield{/if}").await.unwrap(), "");
+}
+
+#[tokio::test]
+async fn test_errors() {
+    assert_eq!(
+        render("{nmae}").await,
+        Err(DisplayRenderError::FieldNotFound("nmae".to_string()))
+    );
+    assert!(matches!(
+        render("{tags}").await,
+        Err(DisplayRenderError::NotDisplayable(_))
+    ));
+    assert!(matches!(
+        render("{level.first}").await,
+        Err(DisplayRenderError::InvalidAccess(..))
+    ));
+    for template in [
+        "{name",
+        "name}",
+        "{}",
+        "{#if legendary}unterminated",
+        "{/if}",
+        "{name:unknown}",
+        "{tags['0']}",
+        "\\n",
+    ] {
+        assert!(
+            matches!(
+                DisplayTemplate::parse(template),
+                Err(DisplayRenderError::Parse { .. })
+            ),
+            "{template}"
+        );
+    }
+    assert!(matches!(
+        DisplayTemplate::parse("{a.b.c.d.e.f.g.h.i.j.k}"),
+        Err(DisplayRenderError::ExceedsPathDepth(_))
+    ));
+}
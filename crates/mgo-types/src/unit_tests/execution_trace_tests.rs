@@ -0,0 +1,144 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
+use serde_json::json;
+
+use super::{ExecutionTracer, EXECUTION_TRACE_FORMAT_VERSION};
+use crate::MGO_FRAMEWORK_ADDRESS;
+
+fn module(name: &str) -> ModuleId {
+    ModuleId::new(MGO_FRAMEWORK_ADDRESS, name.parse().unwrap())
+}
+
+#[test]
+fn test_nested_frames() {
+    let mut tracer = ExecutionTracer::new();
+    tracer.start_command(0, "MoveCall");
+    tracer.open_frame(
+        &module("coin"),
+        "value",
+        vec![TypeTag::from_str("0x2::mgo::MGO").unwrap()],
+        vec![vec![1]],
+        1_000,
+    );
+    tracer.open_frame(&module("balance"), "value", vec![], vec![vec![2]], 900);
+    tracer.close_frame(Some(vec![vec![3]]), false, 850);
+    tracer.close_frame(Some(vec![vec![4]]), false, 800);
+    tracer.end_command(None, 800);
+
+    let trace = tracer.finish();
+    assert_eq!(trace.version, EXECUTION_TRACE_FORMAT_VERSION);
+    assert_eq!(trace.commands.len(), 1);
+
+    let root = &trace.commands[0].calls[0];
+    assert_eq!(root.function, "value");
+    assert_eq!(root.gas_used, 200);
+    assert_eq!(root.return_values[0].bcs, vec![4]);
+    assert!(!root.aborted);
+
+    let callee = &root.calls[0];
+    assert_eq!(callee.module, "balance");
+    assert_eq!(callee.arguments[0].bcs, vec![2]);
+    assert_eq!(callee.gas_used, 50);
+}
+
+#[test]
+fn test_unfinished_frames_abort() {
+    let mut tracer = ExecutionTracer::new();
+    tracer.start_command(0, "MoveCall");
+    tracer.open_frame(&module("m"), "outer", vec![], vec![], 1_000);
+    tracer.open_frame(&module("m"), "native", vec![], vec![], 900);
+    tracer.close_frame(None, true, 700);
+    tracer.end_command(Some("MoveAbort".to_string()), 600);
+
+    let trace = tracer.finish();
+    let command = &trace.commands[0];
+    assert_eq!(command.error.as_deref(), Some("MoveAbort"));
+
+    let outer = &command.calls[0];
+    assert!(outer.aborted);
+    assert_eq!(outer.gas_used, 400);
+
+    let native = &outer.calls[0];
+    assert!(native.aborted && native.is_native);
+    assert!(native.return_values.is_empty());
+}
+
+#[test]
+fn test_json_format() {
+    let mut tracer = ExecutionTracer::new();
+    tracer.start_command(0, "MoveCall");
+    tracer.open_frame(&module("event"), "emit", vec![], vec![vec![7]], 10);
+    tracer.record_event(StructTag::from_str("0x2::m::E").unwrap(), vec![7]);
+    tracer.close_frame(Some(vec![]), true, 5);
+    tracer.end_command(None, 5);
+
+    let json = serde_json::to_value(tracer.finish()).unwrap();
+    assert_eq!(
+        json,
+        json!({
+            "version": EXECUTION_TRACE_FORMAT_VERSION,
+            "commands": [{
+                "index": 0,
+                "kind": "MoveCall",
+                "calls": [{
+                    "package": "0x0000000000000000000000000000000000000000000000000000000000000002",
+                    "module": "event",
+                    "function": "emit",
+                    "typeArguments": [],
+                    "arguments": [{ "bcs": "Bw==", "json": null }],
+                    "returnValues": [],
+                    "gasUsed": "5",
+                    "isNative": true,
+                    "aborted": false,
+                    "calls": [],
+                }],
+                "events": [{
+                    "type": "0x2::m::E",
+                    "contents": { "bcs": "Bw==", "json": null },
+                }],
+                "error": null,
+            }],
+            "objects": [],
+            "truncated": false,
+        })
+    );
+}
+
+#[test]
+fn test_truncated_trace() {
+    let mut tracer = ExecutionTracer::with_limits(2, 3);
+    tracer.start_command(0, "MoveCall");
+    tracer.open_frame(&module("m"), "a", vec![], vec![vec![1]], 100);
+    tracer.open_frame(&module("m"), "b", vec![], vec![vec![2]], 90);
+    // Frames past the limit are dropped, along with the frames they call.
+    tracer.open_frame(&module("m"), "c", vec![], vec![vec![3]], 80);
+    tracer.open_frame(&module("m"), "d", vec![], vec![], 70);
+    tracer.close_frame(Some(vec![]), false, 60);
+    tracer.close_frame(Some(vec![]), false, 50);
+    tracer.close_frame(Some(vec![vec![4]]), false, 40);
+    tracer.record_event(StructTag::from_str("0x2::m::E").unwrap(), vec![5]);
+    tracer.close_frame(Some(vec![vec![6]]), false, 30);
+    tracer.end_command(None, 30);
+    assert!(tracer.is_truncated());
+
+    let trace = tracer.finish();
+    assert!(trace.truncated);
+    let command = &trace.commands[0];
+    assert!(command.events.is_empty());
+
+    let a = &command.calls[0];
+    assert_eq!(a.function, "a");
+    assert_eq!(a.gas_used, 70);
+    assert!(a.return_values.is_empty());
+    assert_eq!(a.calls.len(), 1);
+
+    let b = &a.calls[0];
+    assert_eq!(b.function, "b");
+    assert_eq!(b.gas_used, 50);
+    assert!(b.calls.is_empty());
+    assert!(!b.aborted);
+}
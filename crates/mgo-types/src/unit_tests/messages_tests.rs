@@ -15,11 +15,12 @@ use crate::crypto::{
     get_key_pair, AccountKeyPair, AuthorityKeyPair, AuthorityPublicKeyBytes,
     AuthoritySignInfoTrait, MgoAuthoritySignature,
 };
-use crate::digests::TransactionEventsDigest;
+use crate::digests::{CheckpointDigest, TransactionEventsDigest};
 use crate::effects::{SignedTransactionEffects, TransactionEffects, TransactionEffectsAPI};
 use crate::execution_status::ExecutionStatus;
 use crate::gas::GasCostSummary;
 use crate::object::Owner;
+use crate::utils::make_transaction_data;
 use fastcrypto::traits::AggregateAuthenticator;
 use fastcrypto::traits::KeyPair;
 use move_core_types::language_storage::StructTag;
@@ -1383,3 +1384,88 @@ fn check_approx_effects_components_size() {
         "Update APPROX_SIZE_OF_EXECUTION_STATUS constant"
     );
 }
+
+#[test]
+fn test_timestamp_expiration() {
+    let chain = ChainIdentifier::from(CheckpointDigest::new([1; 32]));
+    let other_chain = ChainIdentifier::from(CheckpointDigest::new([2; 32]));
+    let expiration = TransactionExpiration::ValidDuring {
+        min_timestamp_ms: Some(1_000),
+        max_timestamp_ms: Some(2_000),
+        chain: Some(chain),
+        nonce: 0,
+    };
+
+    assert!(expiration.check(0, chain, || Ok(1_000)).is_ok());
+    assert!(expiration.check(0, chain, || Ok(2_000)).is_ok());
+    assert_eq!(
+        expiration.check(0, chain, || Ok(2_001)),
+        Err(MgoError::TransactionExpired)
+    );
+    assert_eq!(
+        expiration.check(0, chain, || Ok(999)),
+        Err(MgoError::TransactionNotYetValid {
+            min_timestamp_ms: 1_000,
+            current_timestamp_ms: 999,
+        })
+    );
+    assert!(matches!(
+        expiration.check(0, other_chain, || Ok(1_500)),
+        Err(MgoError::UserInputError {
+            error: UserInputError::InvalidChainId { .. }
+        })
+    ));
+
+    // Open ended windows on any chain.
+    let expiration = TransactionExpiration::ValidDuring {
+        min_timestamp_ms: None,
+        max_timestamp_ms: Some(2_000),
+        chain: None,
+        nonce: 1,
+    };
+    assert!(expiration.check(0, other_chain, || Ok(0)).is_ok());
+
+    // The consensus time is only read for timestamp based expirations.
+    let no_time = || -> MgoResult<u64> { panic!("consensus time should not be read") };
+    assert!(TransactionExpiration::None.check(5, chain, no_time).is_ok());
+    assert!(TransactionExpiration::Epoch(5)
+        .check(5, chain, no_time)
+        .is_ok());
+    assert_eq!(
+        TransactionExpiration::Epoch(4).check(5, chain, no_time),
+        Err(MgoError::TransactionExpired)
+    );
+}
+
+#[test]
+fn test_timestamp_expiration_validity() {
+    let (sender, _): (_, AccountKeyPair) = get_key_pair();
+    let mut config = ProtocolConfig::get_for_max_version_UNSAFE();
+    config.set_timestamp_expiration_for_testing(true);
+
+    let data = make_transaction_data(sender).with_expiration(TransactionExpiration::ValidDuring {
+        min_timestamp_ms: Some(2_000),
+        max_timestamp_ms: Some(1_000),
+        chain: None,
+        nonce: 0,
+    });
+    assert!(data.check_version_supported(&config).is_ok());
+    assert!(matches!(
+        data.validity_check_no_gas_check(&config),
+        Err(UserInputError::InvalidTransactionExpiration { .. })
+    ));
+
+    let data = data.with_expiration(TransactionExpiration::ValidDuring {
+        min_timestamp_ms: Some(1_000),
+        max_timestamp_ms: Some(2_000),
+        chain: None,
+        nonce: 0,
+    });
+    assert!(data.validity_check_no_gas_check(&config).is_ok());
+
+    config.set_timestamp_expiration_for_testing(false);
+    assert!(matches!(
+        data.check_version_supported(&config),
+        Err(MgoError::UnsupportedFeatureError { .. })
+    ));
+}
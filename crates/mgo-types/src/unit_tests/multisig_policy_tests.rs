@@ -0,0 +1,185 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{MultiSigPolicy, MultiSigPolicyMember, MultiSigPolicySigner};
+use crate::{
+    base_types::MgoAddress,
+    crypto::{get_key_pair, MgoKeyPair, PublicKey, Signature, ZkLoginPublicIdentifier},
+    multisig::{MultiSig, MultiSigPublicKey, NestedMultiSigLimits},
+    signature::{AuthenticatorTrait, GenericSignature, VerifyParams},
+    utils::{keys, make_zklogin_tx, DEFAULT_ADDRESS_SEED},
+    zk_login_util::DEFAULT_JWK_BYTES,
+};
+use fastcrypto::{ed25519::Ed25519KeyPair, traits::EncodeDecodeBase64};
+use fastcrypto_zkp::bn254::zk_login::{parse_jwks, JwkId, OIDCProvider, JWK};
+use fastcrypto_zkp::bn254::zk_login_api::ZkLoginEnv;
+use im::hashmap::HashMap as ImHashMap;
+use shared_crypto::intent::{Intent, IntentMessage};
+
+fn treasury_policy() -> MultiSigPolicy {
+    let keys = keys();
+    serde_yaml::from_str(&format!(
+        r#"
+threshold: 2
+members:
+  - weight: 1
+    multisig:
+      threshold: 2
+      members:
+        - weight: 1
+          public_key: "{}"
+        - weight: 1
+          public_key: "{}"
+  - weight: 1
+    zklogin:
+      iss: "{}"
+      address_seed: "{}"
+  - weight: 1
+    public_key: "{}"
+"#,
+        keys[0].public().encode_base64(),
+        keys[1].public().encode_base64(),
+        OIDCProvider::Twitch.get_config().iss,
+        DEFAULT_ADDRESS_SEED,
+        keys[2].public().encode_base64(),
+    ))
+    .unwrap()
+}
+
+#[test]
+fn test_policy_address() {
+    let keys = keys();
+    let policy = treasury_policy();
+    assert_eq!(
+        policy.members[2],
+        MultiSigPolicyMember {
+            weight: 1,
+            signer: MultiSigPolicySigner::PublicKey(keys[2].public()),
+        }
+    );
+
+    let team_pk =
+        MultiSigPublicKey::new(vec![keys[0].public(), keys[1].public()], vec![1, 1], 2).unwrap();
+    let zklogin_pk = PublicKey::ZkLogin(
+        ZkLoginPublicIdentifier::new(&OIDCProvider::Twitch.get_config().iss, DEFAULT_ADDRESS_SEED)
+            .unwrap(),
+    );
+    let treasury_pk = MultiSigPublicKey::new(
+        vec![
+            PublicKey::MultiSig((&team_pk).into()),
+            zklogin_pk,
+            keys[2].public(),
+        ],
+        vec![1, 1, 1],
+        2,
+    )
+    .unwrap();
+    assert_eq!(policy.multisig_pk().unwrap(), treasury_pk);
+    assert_eq!(policy.address().unwrap(), MgoAddress::from(&treasury_pk));
+
+    // The policy round trips through its file format.
+    let yaml = serde_yaml::to_string(&policy).unwrap();
+    assert_eq!(
+        serde_yaml::from_str::<MultiSigPolicy>(&yaml).unwrap(),
+        policy
+    );
+
+    // A policy whose threshold cannot be met is invalid.
+    let mut invalid_policy = policy;
+    invalid_policy.threshold = 4;
+    assert!(invalid_policy.address().is_err());
+}
+
+#[test]
+fn test_policy_combine() {
+    let keys = keys();
+    let policy = treasury_policy();
+    let address = policy.address().unwrap();
+
+    let (_, envelope, zklogin_sig) = make_zklogin_tx(address, false);
+    let intent_msg = IntentMessage::new(
+        Intent::mgo_transaction(),
+        envelope.into_data().transaction_data().clone(),
+    );
+    let [team_sig1, team_sig2, cold_sig] =
+        [0, 1, 2].map(|i| GenericSignature::from(Signature::new_secure(&intent_msg, &keys[i])));
+
+    let jwks: ImHashMap<JwkId, JWK> = parse_jwks(DEFAULT_JWK_BYTES, &OIDCProvider::Twitch)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let verify_params = VerifyParams {
+        nested_multisig_limits: Some(NestedMultiSigLimits {
+            max_depth: 1,
+            max_signatures: 3,
+        }),
+        ..VerifyParams::new(jwks, vec![], ZkLoginEnv::Test, true, true)
+    };
+    let verify = |multisig: MultiSig, verify_params: &VerifyParams| {
+        GenericSignature::MultiSig(multisig).verify_authenticator(
+            &intent_msg,
+            address,
+            None,
+            verify_params,
+        )
+    };
+
+    // The team multisig and the CFO sign.
+    let multisig = policy
+        .combine(&[team_sig1.clone(), team_sig2.clone(), zklogin_sig.clone()])
+        .unwrap();
+    assert!(multisig.has_multisig_members());
+    assert!(verify(multisig.clone(), &verify_params).is_ok());
+
+    // Nested multisigs are rejected without limits, or beyond them.
+    let no_limits = VerifyParams {
+        nested_multisig_limits: None,
+        ..verify_params.clone()
+    };
+    assert!(verify(multisig.clone(), &no_limits).is_err());
+    let too_shallow = VerifyParams {
+        nested_multisig_limits: Some(NestedMultiSigLimits {
+            max_depth: 0,
+            max_signatures: 3,
+        }),
+        ..verify_params.clone()
+    };
+    assert!(verify(multisig.clone(), &too_shallow).is_err());
+    let too_few_signatures = VerifyParams {
+        nested_multisig_limits: Some(NestedMultiSigLimits {
+            max_depth: 1,
+            max_signatures: 2,
+        }),
+        ..verify_params.clone()
+    };
+    assert!(verify(multisig, &too_few_signatures).is_err());
+
+    // The CFO and the cold key sign, in any order.
+    let multisig = policy
+        .combine(&[zklogin_sig.clone(), cold_sig.clone()])
+        .unwrap();
+    assert!(verify(multisig, &verify_params).is_ok());
+
+    // An already combined team multisig can be passed in.
+    let team_policy = match &policy.members[0].signer {
+        MultiSigPolicySigner::MultiSig(team_policy) => team_policy,
+        _ => panic!("Expected the team multisig"),
+    };
+    let team_sig = team_policy
+        .combine(&[team_sig1.clone(), team_sig2])
+        .unwrap();
+    let multisig = policy
+        .combine(&[GenericSignature::MultiSig(team_sig), cold_sig.clone()])
+        .unwrap();
+    assert!(verify(multisig, &verify_params).is_ok());
+
+    // A single team member does not meet the team threshold.
+    assert!(policy.combine(&[team_sig1.clone(), cold_sig]).is_err());
+
+    // Signatures of keys outside the policy are rejected.
+    let outsider = MgoKeyPair::Ed25519(get_key_pair::<Ed25519KeyPair>().1);
+    let outsider_sig = GenericSignature::from(Signature::new_secure(&intent_msg, &outsider));
+    assert!(policy
+        .combine(&[team_sig1, zklogin_sig, outsider_sig])
+        .is_err());
+}
@@ -9,7 +9,7 @@ use crate::{
         PublicKey, Signature, MgoKeyPair, MgoSignatureInner, ZkLoginAuthenticatorAsBytes,
         ZkLoginPublicIdentifier,
     },
-    multisig::{as_indices, MultiSig, MAX_SIGNER_IN_MULTISIG},
+    multisig::{as_indices, MultiSig, NestedMultiSigLimits, MAX_SIGNER_IN_MULTISIG},
     multisig_legacy::{bitmap_to_u16, MultiSigLegacy, MultiSigPublicKeyLegacy},
     signature::{AuthenticatorTrait, GenericSignature, VerifyParams},
     utils::{
@@ -24,7 +24,7 @@ use fastcrypto::{
     encoding::{Base64, Encoding},
     hash::HashFunction,
     secp256k1::{Secp256k1KeyPair, Secp256k1PrivateKey},
-    traits::ToFromBytes,
+    traits::{EncodeDecodeBase64, ToFromBytes},
 };
 use fastcrypto_zkp::bn254::zk_login_api::ZkLoginEnv;
 use fastcrypto_zkp::bn254::{
@@ -1194,3 +1194,72 @@ fn test_derive_multisig_address() {
             .unwrap()
     );
 }
+
+#[test]
+fn nested_multisig_scenarios() {
+    let keys = keys();
+    let inner_pk =
+        MultiSigPublicKey::new(vec![keys[0].public(), keys[1].public()], vec![1, 1], 1).unwrap();
+    let inner_member = PublicKey::MultiSig((&inner_pk).into());
+    assert_eq!(MgoAddress::from(&inner_member), MgoAddress::from(&inner_pk));
+    assert_eq!(
+        PublicKey::decode_base64(&inner_member.encode_base64()).unwrap(),
+        inner_member
+    );
+
+    let outer_pk =
+        MultiSigPublicKey::new(vec![inner_member, keys[2].public()], vec![1, 1], 2).unwrap();
+    let addr = MgoAddress::from(&outer_pk);
+    let msg = IntentMessage::new(
+        Intent::mgo_transaction(),
+        PersonalMessage {
+            message: "Hello".as_bytes().to_vec(),
+        },
+    );
+    let sig1: GenericSignature = Signature::new_secure(&msg, &keys[0]).into();
+    let sig3: GenericSignature = Signature::new_secure(&msg, &keys[2]).into();
+    let inner_sig: GenericSignature = MultiSig::combine(vec![sig1.clone()], inner_pk)
+        .unwrap()
+        .into();
+    let multisig = MultiSig::combine(vec![inner_sig, sig3.clone()], outer_pk.clone()).unwrap();
+    assert!(multisig.has_multisig_members());
+
+    // The nested multisig round trips through its bytes.
+    let generic_sig = GenericSignature::MultiSig(multisig.clone());
+    assert!(generic_sig.is_nested_multisig());
+    assert_eq!(
+        GenericSignature::from_bytes(generic_sig.as_ref()).unwrap(),
+        generic_sig
+    );
+
+    let limits = |max_depth, max_signatures| VerifyParams {
+        nested_multisig_limits: Some(NestedMultiSigLimits {
+            max_depth,
+            max_signatures,
+        }),
+        ..Default::default()
+    };
+    assert!(multisig
+        .verify_authenticator(&msg, addr, None, &limits(1, 2))
+        .is_ok());
+    // Nested multisigs are rejected if not enabled, nested too deep, or with too many signatures.
+    assert!(multisig
+        .verify_authenticator(&msg, addr, None, &VerifyParams::default())
+        .is_err());
+    assert!(multisig
+        .verify_authenticator(&msg, addr, None, &limits(0, 2))
+        .is_err());
+    assert!(multisig
+        .verify_authenticator(&msg, addr, None, &limits(1, 1))
+        .is_err());
+
+    // A plain signature cannot stand in for a multisig member.
+    let multisig = MultiSig::new(
+        vec![sig1.to_compressed().unwrap(), sig3.to_compressed().unwrap()],
+        0b11,
+        outer_pk,
+    );
+    assert!(multisig
+        .verify_authenticator(&msg, addr, None, &limits(1, 2))
+        .is_err());
+}
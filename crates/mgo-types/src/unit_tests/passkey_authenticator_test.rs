@@ -0,0 +1,241 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{PasskeyAuthenticator, PasskeyRelyingParties};
+use crate::base_types::MgoAddress;
+use crate::crypto::{get_key_pair_from_rng, MgoKeyPair, PublicKey, Signature, SignatureScheme};
+use crate::error::MgoError;
+use crate::multisig::MultiSigPublicKey;
+use crate::signature::{AuthenticatorTrait, GenericSignature, VerifyParams};
+use crate::transaction::TransactionData;
+use crate::utils::make_transaction_data;
+use fastcrypto::hash::{HashFunction, Sha256};
+use fastcrypto::secp256r1::{Secp256r1KeyPair, Secp256r1Signature};
+use fastcrypto::traits::{KeyPair, Signer, ToFromBytes};
+use rand::{rngs::StdRng, SeedableRng};
+use shared_crypto::intent::{Intent, IntentMessage};
+use std::collections::BTreeSet;
+
+const RP_ID: &str = "mangonet.io";
+const ORIGIN: &str = "https://wallet.mangonet.io";
+
+fn passkey_keypair() -> (MgoAddress, Secp256r1KeyPair) {
+    let kp: Secp256r1KeyPair = get_key_pair_from_rng(&mut StdRng::from_seed([0; 32])).1;
+    let pk = PublicKey::Passkey(kp.public().into());
+    ((&pk).into(), kp)
+}
+
+fn authenticator_data(rp_id: &str, flags: u8) -> Vec<u8> {
+    let mut data = Sha256::digest(rp_id.as_bytes()).digest.to_vec();
+    data.push(flags);
+    data.extend_from_slice(&[0, 0, 0, 1]);
+    data
+}
+
+fn client_data_json(type_: &str, challenge: &str, origin: &str) -> String {
+    format!(
+        r#"{{"type":"{type_}","challenge":"{challenge}","origin":"{origin}","crossOrigin":false}}"#
+    )
+}
+
+/// Signs an assertion like a WebAuthn authenticator does.
+fn sign_assertion(
+    kp: &Secp256r1KeyPair,
+    authenticator_data: Vec<u8>,
+    client_data_json: String,
+) -> PasskeyAuthenticator {
+    let mut message = authenticator_data.clone();
+    message.extend_from_slice(&Sha256::digest(client_data_json.as_bytes()).digest);
+    let sig: Secp256r1Signature = kp.sign(&message);
+
+    let mut bytes = vec![SignatureScheme::Secp256r1.flag()];
+    bytes.extend_from_slice(sig.as_ref());
+    bytes.extend_from_slice(kp.public().as_ref());
+    PasskeyAuthenticator::new(
+        authenticator_data,
+        client_data_json,
+        Signature::from_bytes(&bytes).unwrap(),
+    )
+    .unwrap()
+}
+
+fn intent_message(sender: MgoAddress) -> IntentMessage<TransactionData> {
+    IntentMessage::new(Intent::mgo_transaction(), make_transaction_data(sender))
+}
+
+#[test]
+fn passkey_authenticator() {
+    let (address, kp) = passkey_keypair();
+    let intent_msg = intent_message(address);
+    let challenge = PasskeyAuthenticator::challenge(&intent_msg);
+    let passkey = sign_assertion(
+        &kp,
+        authenticator_data(RP_ID, 0x05),
+        client_data_json("webauthn.get", &challenge, ORIGIN),
+    );
+
+    let verify_params = VerifyParams::default();
+    assert!(passkey
+        .verify_authenticator(&intent_msg, address, Some(0), &verify_params)
+        .is_ok());
+    assert_eq!(MgoAddress::from(&passkey.get_pk().unwrap()), address);
+
+    // The address is derived with the passkey flag, not the Secp256r1 one.
+    let secp256r1_address = MgoAddress::from(&PublicKey::Secp256r1(kp.public().into()));
+    assert_ne!(secp256r1_address, address);
+    assert!(matches!(
+        passkey.verify_authenticator(&intent_msg, secp256r1_address, None, &verify_params),
+        Err(MgoError::IncorrectSigner { .. })
+    ));
+
+    // Round trip through the generic signature encoding.
+    let sig = GenericSignature::PasskeyAuthenticator(passkey);
+    assert_eq!(
+        sig.as_ref()[0],
+        SignatureScheme::PasskeyAuthenticator.flag()
+    );
+    let decoded = GenericSignature::from_bytes(sig.as_ref()).unwrap();
+    assert!(decoded.is_passkey());
+    assert_eq!(decoded, sig);
+    assert_eq!(MgoAddress::try_from(&decoded).unwrap(), address);
+    assert!(decoded
+        .verify_authenticator(&intent_msg, address, None, &verify_params)
+        .is_ok());
+
+    // Passkeys cannot be part of a multisig.
+    assert!(MultiSigPublicKey::new(vec![decoded.to_public_key().unwrap()], vec![1], 1).is_err());
+}
+
+#[test]
+fn passkey_authenticator_invalid_assertions() {
+    let (address, kp) = passkey_keypair();
+    let intent_msg = intent_message(address);
+    let challenge = PasskeyAuthenticator::challenge(&intent_msg);
+    let verify_params = VerifyParams::default();
+    let verify = |authenticator_data: Vec<u8>, client_data_json: String| {
+        sign_assertion(&kp, authenticator_data, client_data_json).verify_authenticator(
+            &intent_msg,
+            address,
+            None,
+            &verify_params,
+        )
+    };
+
+    // The challenge is bound to the intent message.
+    let other_challenge = PasskeyAuthenticator::challenge(&intent_message(MgoAddress::ZERO));
+    assert!(verify(
+        authenticator_data(RP_ID, 0x05),
+        client_data_json("webauthn.get", &other_challenge, ORIGIN),
+    )
+    .is_err());
+    // Only assertions are accepted, not registrations.
+    assert!(verify(
+        authenticator_data(RP_ID, 0x05),
+        client_data_json("webauthn.create", &challenge, ORIGIN),
+    )
+    .is_err());
+    // The user must be present.
+    assert!(verify(
+        authenticator_data(RP_ID, 0x04),
+        client_data_json("webauthn.get", &challenge, ORIGIN),
+    )
+    .is_err());
+    // The authenticator data must at least have rpIdHash, flags and signCount.
+    assert!(verify(
+        authenticator_data(RP_ID, 0x05)[..36].to_vec(),
+        client_data_json("webauthn.get", &challenge, ORIGIN),
+    )
+    .is_err());
+    // The origin must be secure and belong to the relying party.
+    for origin in [
+        "http://wallet.mangonet.io",
+        "https://wallet.example.com",
+        "https://mangonet.io.example.com",
+        "https://127.0.0.1",
+        "not an origin",
+    ] {
+        assert!(verify(
+            authenticator_data(RP_ID, 0x05),
+            client_data_json("webauthn.get", &challenge, origin),
+        )
+        .is_err());
+    }
+    assert!(verify(
+        authenticator_data("localhost", 0x05),
+        client_data_json("webauthn.get", &challenge, "http://localhost:3000"),
+    )
+    .is_ok());
+
+    // The signature must cover both the authenticator data and the client data.
+    let mut passkey = sign_assertion(
+        &kp,
+        authenticator_data(RP_ID, 0x05),
+        client_data_json("webauthn.get", &challenge, ORIGIN),
+    );
+    passkey.authenticator_data[36] = 2;
+    passkey.bytes = Default::default();
+    assert!(passkey
+        .verify_authenticator(&intent_msg, address, None, &verify_params)
+        .is_err());
+}
+
+#[test]
+fn passkey_authenticator_relying_parties() {
+    let (address, kp) = passkey_keypair();
+    let intent_msg = intent_message(address);
+    let challenge = PasskeyAuthenticator::challenge(&intent_msg);
+    let verify_params = VerifyParams {
+        passkey_relying_parties: Some(PasskeyRelyingParties {
+            rp_ids: BTreeSet::from([RP_ID.to_string()]),
+            origins: BTreeSet::from([ORIGIN.to_string()]),
+        }),
+        ..Default::default()
+    };
+    let verify = |rp_id: &str, origin: &str| {
+        sign_assertion(
+            &kp,
+            authenticator_data(rp_id, 0x05),
+            client_data_json("webauthn.get", &challenge, origin),
+        )
+        .verify_authenticator(&intent_msg, address, None, &verify_params)
+    };
+
+    assert!(verify(RP_ID, ORIGIN).is_ok());
+    // Other origins of the relying party are rejected, even though their rpIdHash matches.
+    assert!(matches!(
+        verify(RP_ID, "https://phishing.mangonet.io"),
+        Err(MgoError::InvalidSignature { error }) if error.contains("origin is not allowed")
+    ));
+    // So are relying parties that are not allowed, even for an allowed origin.
+    assert!(matches!(
+        verify("wallet.mangonet.io", ORIGIN),
+        Err(MgoError::InvalidSignature { error })
+            if error.contains("relying party wallet.mangonet.io is not allowed")
+    ));
+
+    // Nothing is accepted without allowed relying parties.
+    let verify_params = VerifyParams {
+        passkey_relying_parties: Some(PasskeyRelyingParties::default()),
+        ..Default::default()
+    };
+    assert!(sign_assertion(
+        &kp,
+        authenticator_data(RP_ID, 0x05),
+        client_data_json("webauthn.get", &challenge, ORIGIN),
+    )
+    .verify_authenticator(&intent_msg, address, None, &verify_params)
+    .is_err());
+}
+
+#[test]
+fn passkey_authenticator_requires_secp256r1() {
+    let kp = MgoKeyPair::Ed25519(get_key_pair_from_rng(&mut StdRng::from_seed([0; 32])).1);
+    let intent_msg = intent_message(MgoAddress::ZERO);
+    let sig = Signature::new_secure(&intent_msg, &kp);
+    assert!(PasskeyAuthenticator::new(vec![0; 37], "{}".to_string(), sig.clone()).is_err());
+
+    // A passkey authenticator with an Ed25519 signature does not deserialize either.
+    let mut bytes = vec![SignatureScheme::PasskeyAuthenticator.flag()];
+    bytes.extend(bcs::to_bytes(&(vec![0u8; 37], "{}".to_string(), sig)).unwrap());
+    assert!(PasskeyAuthenticator::from_bytes(&bytes).is_err());
+}
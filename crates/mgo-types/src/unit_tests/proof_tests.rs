@@ -0,0 +1,195 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::base_types::random_object_ref;
+use crate::crypto::{AuthorityKeyPair, KeypairTraits};
+use crate::digests::ObjectDigest;
+use crate::full_checkpoint_content::CheckpointTransaction;
+use crate::gas::GasCostSummary;
+use crate::messages_checkpoint::{CheckpointSummary, SignedCheckpointSummary};
+use crate::object::Owner;
+use crate::transaction::TransactionDataAPI;
+use crate::utils::{create_fake_transaction, make_committee_key};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn checkpoint_data(committee: &Committee, keys: &[AuthorityKeyPair]) -> CheckpointData {
+    let transactions = (0..3)
+        .map(|_| {
+            let transaction = create_fake_transaction();
+            let effects = TransactionEffects::new_with_tx(transaction.data());
+            checkpoint_transaction(transaction, effects)
+        })
+        .collect();
+    checkpoint_with_transactions(committee, keys, transactions)
+}
+
+fn checkpoint_transaction(
+    transaction: Transaction,
+    effects: TransactionEffects,
+) -> CheckpointTransaction {
+    CheckpointTransaction {
+        transaction,
+        effects,
+        events: None,
+        input_objects: vec![],
+        output_objects: vec![],
+    }
+}
+
+fn checkpoint_with_transactions(
+    committee: &Committee,
+    keys: &[AuthorityKeyPair],
+    transactions: Vec<CheckpointTransaction>,
+) -> CheckpointData {
+    let checkpoint_contents = CheckpointContents::new_with_digests_only_for_tests(
+        transactions
+            .iter()
+            .map(|transaction| transaction.effects.execution_digests()),
+    );
+    let summary = CheckpointSummary::new(
+        committee.epoch,
+        1,
+        transactions.len() as u64,
+        &checkpoint_contents,
+        None,
+        GasCostSummary::default(),
+        None,
+        0,
+    );
+    let signatures: Vec<_> = keys
+        .iter()
+        .map(|key| {
+            SignedCheckpointSummary::sign(committee.epoch, &summary, key, key.public().into())
+        })
+        .collect();
+
+    CheckpointData {
+        checkpoint_summary: CertifiedCheckpointSummary::new(summary, signatures, committee)
+            .unwrap(),
+        checkpoint_contents,
+        transactions,
+    }
+}
+
+#[test]
+fn test_inclusion_proofs() {
+    let (keys, committee) = make_committee_key(&mut StdRng::from_seed([0; 32]));
+    let checkpoint = checkpoint_data(&committee, &keys);
+    let transaction = &checkpoint.transactions[1];
+
+    let digest = *transaction.transaction.digest();
+    let proof = InclusionProof::new(&checkpoint, ProofTarget::Transaction(digest)).unwrap();
+    proof.verify(&committee).unwrap();
+    assert_eq!(proof.effects, transaction.effects);
+
+    let (object_ref, _, _) = transaction.effects.all_changed_objects()[0];
+    let proof = InclusionProof::new(&checkpoint, ProofTarget::Object(object_ref)).unwrap();
+    proof.verify(&committee).unwrap();
+    assert_eq!(proof.transaction.digest(), transaction.transaction.digest());
+
+    // Objects and transactions not in the checkpoint cannot be proven.
+    assert!(InclusionProof::new(&checkpoint, ProofTarget::Object(random_object_ref())).is_none());
+    let other_transaction = create_fake_transaction();
+    assert!(InclusionProof::new(
+        &checkpoint,
+        ProofTarget::Transaction(*other_transaction.digest())
+    )
+    .is_none());
+}
+
+#[test]
+fn test_invalid_inclusion_proofs() {
+    let (keys, committee) = make_committee_key(&mut StdRng::from_seed([0; 32]));
+    let checkpoint = checkpoint_data(&committee, &keys);
+    let (object_ref, _, _) = checkpoint.transactions[0].effects.all_changed_objects()[0];
+    let proof = InclusionProof::new(&checkpoint, ProofTarget::Object(object_ref)).unwrap();
+
+    // Signed by another committee.
+    let (_, other_committee) = make_committee_key(&mut StdRng::from_seed([1; 32]));
+    assert!(proof.verify(&other_committee).is_err());
+
+    // Target not written by the transaction.
+    let mut invalid = proof.clone();
+    invalid.target = ProofTarget::Object(random_object_ref());
+    assert!(matches!(
+        invalid.verify(&committee),
+        Err(MgoError::InvalidInclusionProof { .. })
+    ));
+
+    // Effects not in the checkpoint.
+    let mut invalid = proof.clone();
+    let other_transaction = create_fake_transaction();
+    invalid.effects = TransactionEffects::new_with_tx(other_transaction.data());
+    invalid.transaction = other_transaction;
+    invalid.target = ProofTarget::Object(invalid.effects.all_changed_objects()[0].0);
+    assert!(matches!(
+        invalid.verify(&committee),
+        Err(MgoError::InvalidInclusionProof { .. })
+    ));
+
+    // Transaction not matching the effects.
+    let mut invalid = proof.clone();
+    invalid.transaction = checkpoint.transactions[1].transaction.clone();
+    assert!(matches!(
+        invalid.verify(&committee),
+        Err(MgoError::InvalidInclusionProof { .. })
+    ));
+
+    // Contents not matching the summary.
+    let mut invalid = proof;
+    invalid.checkpoint_contents = CheckpointContents::new_with_digests_only_for_tests([checkpoint
+        .transactions[0]
+        .effects
+        .execution_digests()]);
+    assert!(invalid.verify(&committee).is_err());
+}
+
+#[test]
+fn test_object_overwritten_in_checkpoint() {
+    let (keys, committee) = make_committee_key(&mut StdRng::from_seed([0; 32]));
+    let (object_id, version, digest) = random_object_ref();
+    let written = (object_id, version, digest);
+    let overwritten = (object_id, version.next(), ObjectDigest::random());
+    // Both transactions write the object as their gas object, the second one mutating the version
+    // written by the first.
+    let transactions = [written, overwritten]
+        .into_iter()
+        .map(|object_ref| {
+            let transaction = create_fake_transaction();
+            let owner = Owner::AddressOwner(transaction.data().transaction_data().sender());
+            let effects =
+                TransactionEffects::new_with_tx_and_gas(transaction.data(), (object_ref, owner));
+            checkpoint_transaction(transaction, effects)
+        })
+        .collect();
+    let checkpoint = checkpoint_with_transactions(&committee, &keys, transactions);
+
+    // Only the version live at the end of the checkpoint can be proven.
+    assert!(InclusionProof::new(&checkpoint, ProofTarget::Object(written)).is_none());
+    let proof = InclusionProof::new(&checkpoint, ProofTarget::Object(overwritten)).unwrap();
+    proof.verify(&committee).unwrap();
+    assert!(proof.later_effects.is_empty());
+
+    let mut forged = InclusionProof {
+        target: ProofTarget::Object(written),
+        checkpoint_summary: checkpoint.checkpoint_summary.clone(),
+        checkpoint_contents: checkpoint.checkpoint_contents.clone(),
+        transaction: checkpoint.transactions[0].transaction.clone(),
+        effects: checkpoint.transactions[0].effects.clone(),
+        events: None,
+        later_effects: vec![checkpoint.transactions[1].effects.clone()],
+    };
+    assert!(matches!(
+        forged.verify(&committee),
+        Err(MgoError::InvalidInclusionProof { .. })
+    ));
+
+    // Leaving out the transaction that overwrote the object does not help.
+    forged.later_effects.clear();
+    assert!(matches!(
+        forged.verify(&committee),
+        Err(MgoError::InvalidInclusionProof { .. })
+    ));
+}
@@ -0,0 +1,198 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+
+use mgo_protocol_config::ProtocolConfig;
+use move_binary_format::file_format::empty_module;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::annotated_value::MoveStructLayout;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::StructTag;
+
+use super::{ObjectOverride, PackageOverride, StateOverrideStore};
+use crate::balance::Supply;
+use crate::base_types::{MgoAddress, ObjectID};
+use crate::coin::TreasuryCap;
+use crate::error::{MgoError, UserInputError};
+use crate::gas_coin::{GasCoin, GAS};
+use crate::id::UID;
+use crate::in_memory_storage::InMemoryStorage;
+use crate::object::Object;
+use crate::storage::{BackingPackageStore, ObjectStore};
+use crate::type_resolver::LayoutResolver;
+
+/// Lays out gas coins, the only objects whose contents these tests override.
+struct GasCoinLayoutResolver;
+
+impl LayoutResolver for GasCoinLayoutResolver {
+    fn get_annotated_layout(
+        &mut self,
+        struct_tag: &StructTag,
+    ) -> Result<MoveStructLayout, MgoError> {
+        assert!(GasCoin::is_gas_coin(struct_tag));
+        Ok(GasCoin::layout())
+    }
+}
+
+/// A package with a single empty module, published at a random address.
+fn empty_package(config: &ProtocolConfig) -> (ObjectID, PackageOverride) {
+    let package_id = ObjectID::random();
+    let mut module = empty_module();
+    module.identifiers[0] = Identifier::new("m").unwrap();
+    module.address_identifiers[0] = AccountAddress::from(package_id);
+    let mut bytes = vec![];
+    module
+        .serialize_for_version(Some(config.move_binary_format_version()), &mut bytes)
+        .unwrap();
+    let package = PackageOverride {
+        modules: vec![bytes],
+        dependencies: vec![],
+    };
+    (package_id, package)
+}
+
+#[test]
+fn test_balance_override() {
+    let config = ProtocolConfig::get_for_max_version_UNSAFE();
+    let coin = Object::with_id_owner_gas_for_testing(ObjectID::random(), MgoAddress::ZERO, 100);
+
+    let overridden = ObjectOverride::Balance(5_000)
+        .apply(&coin, &mut GasCoinLayoutResolver, &config)
+        .unwrap();
+    let move_object = overridden.data.try_as_move().unwrap();
+    assert_eq!(move_object.get_coin_value_unsafe(), 5_000);
+    assert_eq!(overridden.id(), coin.id());
+    assert_eq!(overridden.version(), coin.version());
+    assert_eq!(overridden.owner, coin.owner);
+    assert_ne!(overridden.digest(), coin.digest());
+}
+
+#[test]
+fn test_contents_override() {
+    let config = ProtocolConfig::get_for_max_version_UNSAFE();
+    let id = ObjectID::random();
+    let coin = Object::with_id_owner_gas_for_testing(id, MgoAddress::ZERO, 100);
+
+    let contents = bcs::to_bytes(&(id, 42u64)).unwrap();
+    let overridden = ObjectOverride::Contents(contents)
+        .apply(&coin, &mut GasCoinLayoutResolver, &config)
+        .unwrap();
+    assert_eq!(
+        overridden
+            .data
+            .try_as_move()
+            .unwrap()
+            .get_coin_value_unsafe(),
+        42
+    );
+
+    // The contents must keep the object's ID.
+    let contents = bcs::to_bytes(&(ObjectID::random(), 42u64)).unwrap();
+    assert!(matches!(
+        ObjectOverride::Contents(contents).apply(&coin, &mut GasCoinLayoutResolver, &config),
+        Err(UserInputError::InvalidStateOverride { object_id, .. }) if object_id == id
+    ));
+
+    // The contents must match the object's type exactly.
+    for contents in [
+        bcs::to_bytes(&id).unwrap(),
+        bcs::to_bytes(&(id, 42u64, 1u8)).unwrap(),
+    ] {
+        assert!(matches!(
+            ObjectOverride::Contents(contents).apply(&coin, &mut GasCoinLayoutResolver, &config),
+            Err(UserInputError::InvalidStateOverride { object_id, .. }) if object_id == id
+        ));
+    }
+}
+
+#[test]
+fn test_invalid_overrides() {
+    let config = ProtocolConfig::get_for_max_version_UNSAFE();
+    let treasury_cap = Object::treasury_cap_for_testing(
+        GAS::type_(),
+        TreasuryCap {
+            id: UID::new(ObjectID::random()),
+            total_supply: Supply { value: 100 },
+        },
+    );
+    assert!(matches!(
+        ObjectOverride::Balance(1).apply(&treasury_cap, &mut GasCoinLayoutResolver, &config),
+        Err(UserInputError::InvalidStateOverride { .. })
+    ));
+
+    let empty_package = PackageOverride {
+        modules: vec![],
+        dependencies: vec![],
+    };
+    assert!(matches!(
+        empty_package.to_package_object(&config, [], |_| Ok(())),
+        Err(UserInputError::InvalidStateOverride { .. })
+    ));
+
+    let garbage_package = PackageOverride {
+        modules: vec![vec![0xde, 0xad]],
+        dependencies: vec![],
+    };
+    assert!(matches!(
+        garbage_package.to_package_object(&config, [], |_| Ok(())),
+        Err(UserInputError::InvalidStateOverride { .. })
+    ));
+}
+
+#[test]
+fn test_package_override() {
+    let config = ProtocolConfig::get_for_max_version_UNSAFE();
+    let (package_id, package) = empty_package(&config);
+
+    let object = package
+        .to_package_object(&config, [], |modules| {
+            assert_eq!(modules.len(), 1);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(object.id(), package_id);
+    assert!(object.is_package());
+
+    // Packages that fail to verify are rejected.
+    let result = package.to_package_object(&config, [], |_| {
+        Err(MgoError::ModuleVerificationFailure {
+            error: "invalid".to_string(),
+        })
+    });
+    assert!(matches!(
+        result,
+        Err(UserInputError::InvalidStateOverride { object_id, .. }) if object_id == package_id
+    ));
+}
+
+#[test]
+fn test_override_store() {
+    let config = ProtocolConfig::get_for_max_version_UNSAFE();
+    let live = Object::with_id_owner_gas_for_testing(ObjectID::random(), MgoAddress::ZERO, 100);
+    let untouched = Object::with_id_owner_gas_for_testing(ObjectID::random(), MgoAddress::ZERO, 7);
+    let store = InMemoryStorage::new(vec![live.clone(), untouched.clone()]);
+
+    let overridden = ObjectOverride::Balance(1)
+        .apply(&live, &mut GasCoinLayoutResolver, &config)
+        .unwrap();
+    let overrides = BTreeMap::from([(live.id(), overridden.clone())]);
+    let override_store = StateOverrideStore::new(&overrides, &store);
+
+    assert_eq!(
+        override_store.get_object(&live.id()).unwrap(),
+        Some(overridden.clone())
+    );
+    assert_eq!(
+        override_store
+            .get_object_by_key(&live.id(), live.version())
+            .unwrap(),
+        Some(overridden)
+    );
+    assert_eq!(
+        override_store.get_object(&untouched.id()).unwrap(),
+        Some(untouched)
+    );
+    // Overridden Move objects are not packages.
+    assert!(override_store.get_package_object(&live.id()).is_err());
+}
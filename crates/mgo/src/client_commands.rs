@@ -30,9 +30,9 @@ use shared_crypto::intent::Intent;
 use mgo_execution::verifier::VerifierOverrides;
 use mgo_json::MgoJsonValue;
 use mgo_json_rpc_types::{
-    DynamicFieldPage, MgoData, MgoObjectData, MgoObjectResponse, MgoObjectResponseQuery,
-    MgoParsedData, MgoRawData, MgoTransactionBlockEffectsAPI, MgoTransactionBlockResponse,
-    MgoTransactionBlockResponseOptions,
+    DevInspectArgs, DevInspectResults, DryRunTransactionBlockResponse, DynamicFieldPage, MgoData,
+    MgoObjectData, MgoObjectResponse, MgoObjectResponseQuery, MgoParsedData, MgoRawData,
+    MgoTransactionBlockEffectsAPI, MgoTransactionBlockResponse, MgoTransactionBlockResponseOptions,
 };
 use mgo_json_rpc_types::{MgoExecutionStatus, MgoObjectDataOptions};
 use mgo_keys::keystore::AccountKeystore;
@@ -59,7 +59,10 @@ use mgo_types::{
     object::Owner,
     parse_mgo_type_tag,
     signature::GenericSignature,
-    transaction::{SenderSignedData, Transaction, TransactionData, TransactionDataAPI},
+    transaction::{
+        SenderSignedData, Transaction, TransactionData, TransactionDataAPI, TransactionExpiration,
+        TransactionKind,
+    },
 };
 
 use tabled::{
@@ -72,28 +75,31 @@ use tabled::{
 
 use tracing::info;
 
+use crate::client_ptb::{self, PtbBuilder};
 use crate::key_identity::{get_identity_address, KeyIdentity};
+use crate::multisig_session::{MultiSigSessionCommand, MultiSigSessionCommandResult};
 
 #[path = "unit_tests/profiler_tests.rs"]
 #[cfg(test)]
 mod profiler_tests;
 
 macro_rules! serialize_or_execute {
-    ($tx_data:expr, $serialize_unsigned:expr, $serialize_signed:expr, $context:expr, $result_variant:ident) => {{
+    ($tx_data:expr, $expiration:expr, $serialize_unsigned:expr, $serialize_signed:expr, $context:expr, $result_variant:ident) => {{
         assert!(
             !$serialize_unsigned || !$serialize_signed,
             "Cannot specify both --serialize-unsigned-transaction and --serialize-signed-transaction"
         );
+        let tx_data = $expiration.apply($tx_data, $context).await?;
         if $serialize_unsigned {
-            MgoClientCommandResult::SerializedUnsignedTransaction($tx_data)
+            MgoClientCommandResult::SerializedUnsignedTransaction(tx_data)
         } else {
             let signature = $context.config.keystore.sign_secure(
-                &$tx_data.sender(),
-                &$tx_data,
+                &tx_data.sender(),
+                &tx_data,
                 Intent::mgo_transaction(),
             )?;
             let sender_signed_data = SenderSignedData::new_from_sender_signature(
-                $tx_data,
+                tx_data,
                 Intent::mgo_transaction(),
                 signature,
             );
@@ -117,6 +123,66 @@ macro_rules! serialize_or_execute {
     }};
 }
 
+/// Limits when a transaction can be signed by validators, e.g. to keep pre-signed transactions
+/// short-lived. Without any of these options the transaction does not expire.
+#[derive(Args, Debug, Default)]
+pub struct TransactionExpirationArgs {
+    /// Validators only sign the transaction once the consensus time, in milliseconds since the
+    /// unix epoch, reaches this timestamp
+    #[clap(long)]
+    pub valid_from_ms: Option<u64>,
+    /// Validators no longer sign the transaction once the consensus time, in milliseconds since
+    /// the unix epoch, is past this timestamp
+    #[clap(long)]
+    pub valid_until_ms: Option<u64>,
+    /// Only allow the transaction on the chain of the active environment
+    #[clap(long)]
+    pub bind_to_chain: bool,
+    /// Nonce distinguishing otherwise identical transactions with the same validity window
+    #[clap(long, default_value_t = 0)]
+    pub expiration_nonce: u32,
+}
+
+impl TransactionExpirationArgs {
+    fn is_set(&self) -> bool {
+        self.valid_from_ms.is_some()
+            || self.valid_until_ms.is_some()
+            || self.bind_to_chain
+            || self.expiration_nonce != 0
+    }
+
+    /// Sets the expiration of `tx_data`, if any option is set.
+    pub async fn apply(
+        self,
+        tx_data: TransactionData,
+        context: &WalletContext,
+    ) -> Result<TransactionData, anyhow::Error> {
+        if !self.is_set() {
+            return Ok(tx_data);
+        }
+        if let (Some(valid_from_ms), Some(valid_until_ms)) =
+            (self.valid_from_ms, self.valid_until_ms)
+        {
+            ensure!(
+                valid_from_ms <= valid_until_ms,
+                "--valid-from-ms must not be greater than --valid-until-ms"
+            );
+        }
+        let chain = if self.bind_to_chain {
+            let client = context.get_client().await?;
+            Some(client.read_api().get_genesis_chain_identifier().await?)
+        } else {
+            None
+        };
+        Ok(tx_data.with_expiration(TransactionExpiration::ValidDuring {
+            min_timestamp_ms: self.valid_from_ms,
+            max_timestamp_ms: self.valid_until_ms,
+            chain,
+            nonce: self.expiration_nonce,
+        }))
+    }
+}
+
 #[derive(Parser)]
 #[clap(rename_all = "kebab-case")]
 pub enum MgoClientCommands {
@@ -178,6 +244,20 @@ pub enum MgoClientCommands {
         /// <SIGNED_TX_BYTES>`.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        /// Instead of executing the transaction, dev-inspect it: run it against the current state
+        /// without committing its effects, and print its effects, events and return values.
+        #[clap(long, required = false)]
+        dev_inspect: bool,
+
+        /// Include a trace of the Move execution in the dev-inspect output, as JSON: the functions
+        /// called, with their arguments, return values and gas, the events emitted, and the objects
+        /// read and written.
+        #[clap(long, required = false, requires = "dev_inspect")]
+        trace: bool,
+
+        #[clap(flatten)]
+        expiration: TransactionExpirationArgs,
     },
 
     /// Query the chain identifier from the rpc endpoint.
@@ -268,6 +348,17 @@ pub enum MgoClientCommands {
         /// <SIGNED_TX_BYTES>`.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        expiration: TransactionExpirationArgs,
+    },
+
+    /// Collect the signatures of the members of a MultiSig for a transaction in a session file,
+    /// and execute it once the threshold is met. Members can sign the file offline.
+    #[clap(name = "multisig-session")]
+    MultiSigSession {
+        #[clap(subcommand)]
+        cmd: MultiSigSessionCommand,
     },
 
     /// Generate new address and keypair with keypair scheme flag {ed25519 | secp256k1 | secp256r1}
@@ -351,6 +442,9 @@ pub enum MgoClientCommands {
         /// <SIGNED_TX_BYTES>`.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        expiration: TransactionExpirationArgs,
     },
 
     /// Pay all residual MGO coins to the recipient with input coins, after deducting the gas cost.
@@ -380,6 +474,9 @@ pub enum MgoClientCommands {
         /// <SIGNED_TX_BYTES>`.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        expiration: TransactionExpirationArgs,
     },
 
     /// Pay MGO coins to recipients following following specified amounts, with input coins.
@@ -415,6 +512,47 @@ pub enum MgoClientCommands {
         /// <SIGNED_TX_BYTES>`.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        expiration: TransactionExpirationArgs,
+    },
+
+    /// Build and run a programmable transaction block from a sequence of commands, whose results
+    /// can be named and passed to later commands.
+    ///
+    /// The commands are:
+    ///
+    ///   --split-coins <coin> [<amount>, ...]
+    ///   --merge-coins <coin> [<coin>, ...]
+    ///   --transfer-objects [<object>, ...] <address>
+    ///   --make-move-vec <type> [<value>, ...]
+    ///   --move-call <package>::<module>::<function><type, ...> <argument> ...
+    ///   --publish <package path>
+    ///   --upgrade <package path> @<upgrade capability>
+    ///   --assign <name>: names the result of the previous command
+    ///
+    /// Values are numbers, `true` or `false`, strings ("..."), addresses or objects (@0x...),
+    /// vectors ([...]), options (`none` or `some(...)`), the gas coin (`gas`), or results (`<name>`
+    /// or `<name>.<index>`). Literals take the type expected by the command they are passed to.
+    ///
+    /// The transaction is configured with --gas-budget <amount> (required unless dev-inspecting),
+    /// --gas-coin @<coin>, and one of --dry-run, --dev-inspect, --serialize-unsigned-transaction or
+    /// --serialize-signed-transaction to not execute it. Its expiration is set with
+    /// --valid-from-ms <timestamp>, --valid-until-ms <timestamp>, --bind-to-chain and
+    /// --expiration-nonce <nonce>, as for the other transaction commands.
+    ///
+    /// For example: mgo client ptb --split-coins gas [1000] --assign coins --transfer-objects
+    /// [coins.0] @0x... --gas-budget 10000000
+    #[clap(name = "ptb", verbatim_doc_comment)]
+    Ptb {
+        /// The commands of the transaction, and its options
+        #[clap(
+            required = true,
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "COMMANDS"
+        )]
+        commands: Vec<String>,
     },
 
     /// Publish Move modules
@@ -458,6 +596,9 @@ pub enum MgoClientCommands {
         /// <SIGNED_TX_BYTES>`.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        expiration: TransactionExpirationArgs,
     },
 
     /// Split a coin object into multiple coins.
@@ -492,6 +633,9 @@ pub enum MgoClientCommands {
         /// <SIGNED_TX_BYTES>`.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        expiration: TransactionExpirationArgs,
     },
 
     /// Switch active address and network(e.g., devnet, local rpc server).
@@ -547,6 +691,9 @@ pub enum MgoClientCommands {
         /// <SIGNED_TX_BYTES>`.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        expiration: TransactionExpirationArgs,
     },
 
     /// Transfer MGO, and pay gas with the same MGO coin object.
@@ -582,6 +729,9 @@ pub enum MgoClientCommands {
         /// <SIGNED_TX_BYTES>`.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        expiration: TransactionExpirationArgs,
     },
 
     /// Upgrade Move modules
@@ -629,6 +779,9 @@ pub enum MgoClientCommands {
         /// <SIGNED_TX_BYTES>`.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        expiration: TransactionExpirationArgs,
     },
 
     /// Run the bytecode verifier on the package
@@ -695,6 +848,12 @@ pub enum MgoClientCommands {
         /// Log information about each programmable transaction command
         #[arg(long)]
         ptb_info: bool,
+
+        /// Write a JSON trace of the Move execution of the transaction to this file: the functions
+        /// it called, with their arguments, return values and gas, the events it emitted, and the
+        /// objects it read and wrote
+        #[arg(long)]
+        trace_output: Option<PathBuf>,
     },
 
     /// Replay transactions listed in a file.
@@ -764,6 +923,7 @@ impl MgoClientCommands {
                 tx_digest,
                 gas_info: _,
                 ptb_info: _,
+                trace_output,
             } => {
                 let cmd = ReplayToolCommand::ReplayTransaction {
                     tx_digest,
@@ -771,6 +931,7 @@ impl MgoClientCommands {
                     diag: false,
                     executor_version: None,
                     protocol_version: None,
+                    trace_output,
                 };
 
                 let rpc = context.config.get_active_env()?.rpc.clone();
@@ -849,6 +1010,7 @@ impl MgoClientCommands {
                 with_unpublished_dependencies,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                expiration,
             } => {
                 let sender = context.try_get_object_owner(&gas).await?;
                 let sender = sender.unwrap_or(context.active_address()?);
@@ -921,12 +1083,73 @@ impl MgoClientCommands {
                     .await?;
                 serialize_or_execute!(
                     data,
+                    expiration,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
                     context,
                     Upgrade
                 )
             }
+            MgoClientCommands::Ptb { commands } => {
+                let source = commands.join(" ");
+                let program = client_ptb::parse(&source).map_err(|e| anyhow!(e.render(&source)))?;
+                let options = &program.options;
+
+                let sender = context.try_get_object_owner(&options.gas_coin).await?;
+                let sender = sender.unwrap_or(context.active_address()?);
+
+                let client = context.get_client().await?;
+                let pt = PtbBuilder::new(&client)
+                    .build(&program)
+                    .await
+                    .map_err(|e| anyhow!(e.render(&source)))?;
+
+                if options.dev_inspect {
+                    let results = client
+                        .read_api()
+                        .dev_inspect_transaction_block(
+                            sender,
+                            TransactionKind::programmable(pt),
+                            None,
+                            None,
+                            Some(DevInspectArgs {
+                                gas_budget: options.gas_budget.map(Into::into),
+                                ..Default::default()
+                            }),
+                        )
+                        .await?;
+                    MgoClientCommandResult::DevInspect(results)
+                } else {
+                    let gas_budget = options
+                        .gas_budget
+                        .ok_or_else(|| anyhow!("Missing gas budget"))?;
+                    let tx_data = client
+                        .transaction_builder()
+                        .programmable(sender, pt, options.gas_coin, gas_budget)
+                        .await?;
+                    let expiration = TransactionExpirationArgs {
+                        valid_from_ms: options.valid_from_ms,
+                        valid_until_ms: options.valid_until_ms,
+                        bind_to_chain: options.bind_to_chain,
+                        expiration_nonce: options.expiration_nonce.unwrap_or_default(),
+                    };
+                    if options.dry_run {
+                        let tx_data = expiration.apply(tx_data, context).await?;
+                        let response = client.read_api().dry_run_transaction_block(tx_data).await?;
+                        MgoClientCommandResult::DryRun(response)
+                    } else {
+                        serialize_or_execute!(
+                            tx_data,
+                            expiration,
+                            options.serialize_unsigned_transaction,
+                            options.serialize_signed_transaction,
+                            context,
+                            Ptb
+                        )
+                    }
+                }
+            }
+
             MgoClientCommands::Publish {
                 package_path,
                 gas,
@@ -936,6 +1159,7 @@ impl MgoClientCommands {
                 with_unpublished_dependencies,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                expiration,
             } => {
                 if build_config.test_mode {
                     return Err(MgoError::ModulePublishFailure {
@@ -977,6 +1201,7 @@ impl MgoClientCommands {
                     .await?;
                 serialize_or_execute!(
                     data,
+                    expiration,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
                     context,
@@ -1064,18 +1289,43 @@ impl MgoClientCommands {
                 args,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                dev_inspect,
+                trace,
+                expiration,
             } => {
                 let tx_data = construct_move_call_transaction(
                     package, &module, &function, type_args, gas, gas_budget, args, context,
                 )
                 .await?;
-                serialize_or_execute!(
-                    tx_data,
-                    serialize_unsigned_transaction,
-                    serialize_signed_transaction,
-                    context,
-                    Call
-                )
+                if dev_inspect {
+                    let client = context.get_client().await?;
+                    let results = client
+                        .read_api()
+                        .dev_inspect_transaction_block(
+                            tx_data.sender(),
+                            tx_data.kind().clone(),
+                            Some(tx_data.gas_price().into()),
+                            None,
+                            Some(DevInspectArgs {
+                                gas_sponsor: Some(tx_data.gas_owner()),
+                                gas_budget: Some(tx_data.gas_budget().into()),
+                                gas_objects: Some(tx_data.gas().to_vec()),
+                                trace: Some(trace),
+                                ..Default::default()
+                            }),
+                        )
+                        .await?;
+                    MgoClientCommandResult::DevInspect(results)
+                } else {
+                    serialize_or_execute!(
+                        tx_data,
+                        expiration,
+                        serialize_unsigned_transaction,
+                        serialize_signed_transaction,
+                        context,
+                        Call
+                    )
+                }
             }
 
             MgoClientCommands::Transfer {
@@ -1085,6 +1335,7 @@ impl MgoClientCommands {
                 gas_budget,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                expiration,
             } => {
                 let from = context.get_object_owner(&object_id).await?;
                 let to = get_identity_address(Some(to), context)?;
@@ -1095,6 +1346,7 @@ impl MgoClientCommands {
                     .await?;
                 serialize_or_execute!(
                     data,
+                    expiration,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
                     context,
@@ -1109,6 +1361,7 @@ impl MgoClientCommands {
                 amount,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                expiration,
             } => {
                 let from = context.get_object_owner(&object_id).await?;
                 let to = get_identity_address(Some(to), context)?;
@@ -1119,6 +1372,7 @@ impl MgoClientCommands {
                     .await?;
                 serialize_or_execute!(
                     data,
+                    expiration,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
                     context,
@@ -1134,6 +1388,7 @@ impl MgoClientCommands {
                 gas_budget,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                expiration,
             } => {
                 ensure!(
                     !input_coins.is_empty(),
@@ -1164,6 +1419,7 @@ impl MgoClientCommands {
                     .await?;
                 serialize_or_execute!(
                     data,
+                    expiration,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
                     context,
@@ -1178,6 +1434,7 @@ impl MgoClientCommands {
                 gas_budget,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                expiration,
             } => {
                 ensure!(
                     !input_coins.is_empty(),
@@ -1208,6 +1465,7 @@ impl MgoClientCommands {
                     .await?;
                 serialize_or_execute!(
                     data,
+                    expiration,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
                     context,
@@ -1221,6 +1479,7 @@ impl MgoClientCommands {
                 gas_budget,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                expiration,
             } => {
                 ensure!(
                     !input_coins.is_empty(),
@@ -1236,6 +1495,7 @@ impl MgoClientCommands {
 
                 serialize_or_execute!(
                     data,
+                    expiration,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
                     context,
@@ -1347,6 +1607,7 @@ impl MgoClientCommands {
                 gas_budget,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                expiration,
             } => {
                 let signer = context.get_object_owner(&coin_id).await?;
                 let client = context.get_client().await?;
@@ -1372,6 +1633,7 @@ impl MgoClientCommands {
                 };
                 serialize_or_execute!(
                     data,
+                    expiration,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
                     context,
@@ -1385,6 +1647,7 @@ impl MgoClientCommands {
                 gas_budget,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                expiration,
             } => {
                 let client = context.get_client().await?;
                 let signer = context.get_object_owner(&primary_coin).await?;
@@ -1394,6 +1657,7 @@ impl MgoClientCommands {
                     .await?;
                 serialize_or_execute!(
                     data,
+                    expiration,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
                     context,
@@ -1467,6 +1731,9 @@ impl MgoClientCommands {
                 let response = context.execute_transaction_may_fail(transaction).await?;
                 MgoClientCommandResult::ExecuteSignedTx(response)
             }
+            MgoClientCommands::MultiSigSession { cmd } => {
+                MgoClientCommandResult::MultiSigSession(cmd.execute(context).await?)
+            }
             MgoClientCommands::NewEnv { alias, rpc, ws } => {
                 if context.config.envs.iter().any(|env| env.alias == alias) {
                     return Err(anyhow!(
@@ -1557,7 +1824,7 @@ fn compile_package_simple(
     )?)
 }
 
-async fn compile_package(
+pub(crate) async fn compile_package(
     client: &MgoClient,
     build_config: MoveBuildConfig,
     package_path: PathBuf,
@@ -1800,6 +2067,21 @@ impl Display for MgoClientCommandResult {
             MgoClientCommandResult::Call(response) => {
                 write!(writer, "{}", response)?;
             }
+            MgoClientCommandResult::DevInspect(results) => {
+                writeln!(writer, "{}", results.effects)?;
+                writeln!(writer, "{}", results.events)?;
+                if let Some(error) = &results.error {
+                    writeln!(writer, "Execution Error: {error}")?;
+                }
+                if let Some(trace) = &results.trace {
+                    let trace = serde_json::to_string_pretty(trace).map_err(|_| std::fmt::Error)?;
+                    writeln!(writer, "Execution Trace:\n{trace}")?;
+                }
+            }
+            MgoClientCommandResult::DryRun(response) => {
+                writeln!(writer, "{}", response.effects)?;
+                writeln!(writer, "{}", response.events)?;
+            }
             MgoClientCommandResult::SerializedUnsignedTransaction(tx_data) => {
                 writeln!(
                     writer,
@@ -1826,6 +2108,9 @@ impl Display for MgoClientCommandResult {
             MgoClientCommandResult::PayMgo(response) => {
                 write!(writer, "{}", response)?;
             }
+            MgoClientCommandResult::Ptb(response) => {
+                write!(writer, "{}", response)?;
+            }
             MgoClientCommandResult::PayAllMgo(response) => {
                 write!(writer, "{}", response)?;
             }
@@ -1853,6 +2138,9 @@ impl Display for MgoClientCommandResult {
             MgoClientCommandResult::ExecuteSignedTx(response) => {
                 write!(writer, "{}", response)?;
             }
+            MgoClientCommandResult::MultiSigSession(result) => {
+                write!(writer, "{}", result)?;
+            }
             MgoClientCommandResult::ActiveEnv(env) => {
                 write!(writer, "{}", env.as_deref().unwrap_or("None"))?;
             }
@@ -2019,7 +2307,8 @@ impl MgoClientCommandResult {
         match self {
             Upgrade(b) | Publish(b) | TransactionBlock(b) | Call(b) | Transfer(b)
             | TransferMgo(b) | Pay(b) | PayMgo(b) | PayAllMgo(b) | SplitCoin(b) | MergeCoin(b)
-            | ExecuteSignedTx(b) => Some(b),
+            | ExecuteSignedTx(b) | Ptb(b) => Some(b),
+            MultiSigSession(MultiSigSessionCommandResult::Execute(b)) => Some(b),
             _ => None,
         }
     }
@@ -2151,11 +2440,14 @@ pub enum MgoClientCommandResult {
     Addresses(AddressesOutput),
     Call(MgoTransactionBlockResponse),
     ChainIdentifier(String),
+    DevInspect(DevInspectResults),
+    DryRun(DryRunTransactionBlockResponse),
     DynamicFieldQuery(DynamicFieldPage),
     Envs(Vec<MgoEnv>, Option<String>),
     ExecuteSignedTx(MgoTransactionBlockResponse),
     Gas(Vec<GasCoin>),
     MergeCoin(MgoTransactionBlockResponse),
+    MultiSigSession(MultiSigSessionCommandResult),
     NewAddress(NewAddressOutput),
     NewEnv(MgoEnv),
     NoOutput,
@@ -2164,6 +2456,7 @@ pub enum MgoClientCommandResult {
     Pay(MgoTransactionBlockResponse),
     PayAllMgo(MgoTransactionBlockResponse),
     PayMgo(MgoTransactionBlockResponse),
+    Ptb(MgoTransactionBlockResponse),
     Publish(MgoTransactionBlockResponse),
     RawObject(MgoObjectResponse),
     SerializedSignedTransaction(SenderSignedData),
@@ -0,0 +1,143 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+
+use mgo_types::base_types::{MgoAddress, ObjectID};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::TypeTag;
+use move_core_types::u256::U256;
+
+use super::error::Spanned;
+
+/// A programmable transaction block, as parsed from the command line.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Program {
+    pub commands: Vec<Spanned<ParsedCommand>>,
+    pub options: ProgramOptions,
+}
+
+/// How the transaction is paid for, and what is done with it once it is built.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProgramOptions {
+    pub gas_budget: Option<u64>,
+    pub gas_coin: Option<ObjectID>,
+    pub dry_run: bool,
+    pub dev_inspect: bool,
+    pub serialize_unsigned_transaction: bool,
+    pub serialize_signed_transaction: bool,
+    /// The expiration of the transaction, as with the `--valid-from-ms`, `--valid-until-ms`,
+    /// `--bind-to-chain` and `--expiration-nonce` options of the other transaction commands.
+    pub valid_from_ms: Option<u64>,
+    pub valid_until_ms: Option<u64>,
+    pub bind_to_chain: bool,
+    pub expiration_nonce: Option<u32>,
+}
+
+/// A command in the transaction, and the name its result is bound to, if any.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParsedCommand {
+    pub kind: CommandKind,
+    pub assign: Option<Spanned<String>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommandKind {
+    /// `--split-coins <coin> [<amount>, ...]`
+    SplitCoins {
+        coin: Spanned<Value>,
+        amounts: Spanned<Value>,
+    },
+    /// `--merge-coins <into> [<coin>, ...]`
+    MergeCoins {
+        into: Spanned<Value>,
+        coins: Spanned<Value>,
+    },
+    /// `--transfer-objects [<object>, ...] <recipient>`
+    TransferObjects {
+        objects: Spanned<Value>,
+        recipient: Spanned<Value>,
+    },
+    /// `--make-move-vec <type> [<element>, ...]`
+    MakeMoveVec {
+        type_: Spanned<TypeTag>,
+        elements: Spanned<Value>,
+    },
+    /// `--move-call <package>::<module>::<function><type, ...> <argument> ...`
+    MoveCall {
+        function: Spanned<FunctionPath>,
+        type_args: Vec<Spanned<TypeTag>>,
+        args: Vec<Spanned<Value>>,
+    },
+    /// `--publish <package path>`
+    Publish { package_path: Spanned<PathBuf> },
+    /// `--upgrade <package path> <upgrade capability>`
+    Upgrade {
+        package_path: Spanned<PathBuf>,
+        upgrade_capability: Spanned<ObjectID>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionPath {
+    pub package: ObjectID,
+    pub module: Identifier,
+    pub function: Identifier,
+}
+
+/// An argument to a command. Literals are only given a type once the command they are passed to
+/// is known, e.g. `1` can be any integer type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Bool(bool),
+    Number(U256),
+    /// `@0x...`
+    Address(MgoAddress),
+    String(String),
+    Vector(Vec<Spanned<Value>>),
+    /// `none` or `some(<value>)`
+    Option(Option<Box<Spanned<Value>>>),
+    /// `gas`
+    Gas,
+    /// `<name>`, the result of an earlier command
+    Result(String),
+    /// `<name>.<index>`, one of the results of an earlier command
+    NestedResult(String, u16),
+}
+
+impl Value {
+    /// Whether the value is made only of literals, and so can be passed as a single pure input.
+    pub fn is_literal(&self) -> bool {
+        match self {
+            Value::Bool(_)
+            | Value::Number(_)
+            | Value::Address(_)
+            | Value::String(_)
+            | Value::Option(None) => true,
+            Value::Vector(values) => values.iter().all(|v| v.value.is_literal()),
+            Value::Option(Some(value)) => value.value.is_literal(),
+            Value::Gas | Value::Result(_) | Value::NestedResult(_, _) => false,
+        }
+    }
+
+    /// How the kind of value is described in errors.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Value::Bool(_) => "a bool",
+            Value::Number(_) => "a number",
+            Value::Address(_) => "an address",
+            Value::String(_) => "a string",
+            Value::Vector(_) => "a vector",
+            Value::Option(_) => "an option",
+            Value::Gas => "the gas coin",
+            Value::Result(_) | Value::NestedResult(_, _) => "a result",
+        }
+    }
+}
+
+impl Display for FunctionPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}::{}", self.package, self.module, self.function)
+    }
+}
@@ -0,0 +1,632 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use mgo_json_rpc_types::{
+    MgoData, MgoMoveNormalizedModule, MgoMoveNormalizedType, MgoObjectDataOptions,
+};
+use mgo_move_build::PublishedAtError;
+use mgo_sdk::MgoClient;
+use mgo_types::base_types::{
+    ObjectID, ObjectRef, RESOLVED_ASCII_STR, RESOLVED_STD_OPTION, RESOLVED_UTF8_STR,
+    TX_CONTEXT_MODULE_NAME, TX_CONTEXT_STRUCT_NAME,
+};
+use mgo_types::id::RESOLVED_MGO_ID;
+use mgo_types::move_package::UpgradeCap;
+use mgo_types::object::Owner;
+use mgo_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use mgo_types::transaction::{Argument, Command, ObjectArg, ProgrammableTransaction};
+use mgo_types::transfer::RESOLVED_RECEIVING_STRUCT;
+use mgo_types::{MGO_FRAMEWORK_ADDRESS, MGO_FRAMEWORK_PACKAGE_ID};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::ident_str;
+use move_core_types::identifier::{IdentStr, Identifier};
+use move_core_types::language_storage::{StructTag, TypeTag};
+use move_core_types::u256::U256;
+use move_package::BuildConfig as MoveBuildConfig;
+use serde::Serialize;
+
+use super::ast::{CommandKind, FunctionPath, Program, Value};
+use super::error::{PtbError, PtbResult, Span, Spanned};
+use crate::client_commands::compile_package;
+
+/// Compiles a parsed PTB into a [`ProgrammableTransaction`], resolving the objects and function
+/// signatures it refers to through `client`.
+pub struct PtbBuilder<'a> {
+    client: &'a MgoClient,
+    builder: ProgrammableTransactionBuilder,
+    /// The results of earlier commands, by the name they were assigned.
+    results: BTreeMap<String, Argument>,
+    /// The references and owners of the objects used so far, so each is fetched once.
+    objects: BTreeMap<ObjectID, (ObjectRef, Owner)>,
+    /// The normalized modules of the packages called so far, so each is fetched once.
+    packages: BTreeMap<ObjectID, BTreeMap<String, MgoMoveNormalizedModule>>,
+}
+
+/// What a command expects of one of its arguments, which decides how a literal is passed to it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ArgType {
+    /// A value passed as a pure input, serialized as this type.
+    Pure(TypeTag),
+    /// An object input, that the command mutates or takes by value if `mutable`.
+    Object { mutable: bool },
+    /// An object sent to another object, which is received by the command.
+    Receiving,
+    /// A vector of objects of this type, made with `MakeMoveVec`.
+    Objects(TypeTag),
+}
+
+impl<'a> PtbBuilder<'a> {
+    pub fn new(client: &'a MgoClient) -> Self {
+        Self {
+            client,
+            builder: ProgrammableTransactionBuilder::new(),
+            results: BTreeMap::new(),
+            objects: BTreeMap::new(),
+            packages: BTreeMap::new(),
+        }
+    }
+
+    pub async fn build(mut self, program: &Program) -> PtbResult<ProgrammableTransaction> {
+        for command in &program.commands {
+            let result = self.command(&command.value.kind, command.span).await?;
+            if let Some(name) = &command.value.assign {
+                self.results.insert(name.value.clone(), result);
+            }
+        }
+
+        Ok(self.builder.finish())
+    }
+
+    /// Adds the PTB commands for `kind`, returning the result of the last one.
+    async fn command(&mut self, kind: &CommandKind, span: Span) -> PtbResult<Argument> {
+        let object = ArgType::Object { mutable: true };
+        let command = match kind {
+            CommandKind::SplitCoins { coin, amounts } => {
+                let coin = self.arg(coin, &object).await?;
+                let amounts = self.args(amounts, &ArgType::Pure(TypeTag::U64)).await?;
+                Command::SplitCoins(coin, amounts)
+            }
+
+            CommandKind::MergeCoins { into, coins } => {
+                let into = self.arg(into, &object).await?;
+                let coins = self.args(coins, &object).await?;
+                Command::MergeCoins(into, coins)
+            }
+
+            CommandKind::TransferObjects { objects, recipient } => {
+                let objects = self.args(objects, &object).await?;
+                let recipient = self
+                    .arg(recipient, &ArgType::Pure(TypeTag::Address))
+                    .await?;
+                Command::TransferObjects(objects, recipient)
+            }
+
+            CommandKind::MakeMoveVec { type_, elements } => {
+                let elements = self
+                    .args(elements, &arg_type(type_.value.clone(), true))
+                    .await?;
+                Command::MakeMoveVec(Some(type_.value.clone()), elements)
+            }
+
+            CommandKind::MoveCall {
+                function,
+                type_args,
+                args,
+            } => {
+                let type_args: Vec<_> = type_args.iter().map(|t| t.value.clone()).collect();
+                let parameters = self.parameters(function, &type_args).await?;
+                if parameters.len() != args.len() {
+                    return Err(PtbError::new(
+                        span,
+                        format!(
+                            "{} expects {} argument(s), but {} were given",
+                            function.value,
+                            parameters.len(),
+                            args.len(),
+                        ),
+                    ));
+                }
+
+                let mut arguments = vec![];
+                for (arg, type_) in args.iter().zip(&parameters) {
+                    arguments.push(self.arg(arg, type_).await?);
+                }
+
+                let FunctionPath {
+                    package,
+                    module,
+                    function,
+                } = function.value.clone();
+                return Ok(self
+                    .builder
+                    .programmable_move_call(package, module, function, type_args, arguments));
+            }
+
+            CommandKind::Publish { package_path } => {
+                let (dependencies, modules, _, _) = self.compile(package_path).await?;
+                Command::Publish(modules, dependencies)
+            }
+
+            CommandKind::Upgrade {
+                package_path,
+                upgrade_capability,
+            } => return self.upgrade(package_path, upgrade_capability).await,
+        };
+
+        Ok(self.builder.command(command))
+    }
+
+    /// Authorizes, performs and commits an upgrade of the package at `package_path`, keeping the
+    /// upgrade policy of its capability.
+    async fn upgrade(
+        &mut self,
+        package_path: &Spanned<PathBuf>,
+        upgrade_capability: &Spanned<ObjectID>,
+    ) -> PtbResult<Argument> {
+        let (dependencies, modules, digest, package_id) = self.compile(package_path).await?;
+        let package_id = package_id.map_err(|e| {
+            let message = match e {
+                PublishedAtError::NotPresent => {
+                    "No 'published-at' field in manifest for package to be upgraded".to_string()
+                }
+                PublishedAtError::Invalid(v) => format!(
+                    "Invalid 'published-at' field in manifest of package to be upgraded. \
+                     Expected an on-chain address, but found: {v:?}"
+                ),
+            };
+            PtbError::new(package_path.span, message)
+        })?;
+
+        let (span, id) = (upgrade_capability.span, upgrade_capability.value);
+        let policy = self.upgrade_policy(id, span).await?;
+        let cap = self
+            .object(id, span, &ArgType::Object { mutable: true })
+            .await?;
+        let policy = self.pure(span, policy)?;
+        let digest = self.pure(span, digest)?;
+
+        let package = ident_str!("package").to_owned();
+        let ticket = self.builder.programmable_move_call(
+            MGO_FRAMEWORK_PACKAGE_ID,
+            package.clone(),
+            ident_str!("authorize_upgrade").to_owned(),
+            vec![],
+            vec![cap, policy, digest],
+        );
+        let receipt = self
+            .builder
+            .upgrade(package_id, ticket, dependencies, modules);
+        Ok(self.builder.programmable_move_call(
+            MGO_FRAMEWORK_PACKAGE_ID,
+            package,
+            ident_str!("commit_upgrade").to_owned(),
+            vec![],
+            vec![cap, receipt],
+        ))
+    }
+
+    /// Builds the package at `package_path`, returning the IDs of its dependencies, its modules,
+    /// its digest, and the ID it was published at, if any.
+    async fn compile(
+        &self,
+        package_path: &Spanned<PathBuf>,
+    ) -> PtbResult<(
+        Vec<ObjectID>,
+        Vec<Vec<u8>>,
+        Vec<u8>,
+        Result<ObjectID, PublishedAtError>,
+    )> {
+        let (dependencies, modules, package, package_id) = compile_package(
+            self.client,
+            MoveBuildConfig::default(),
+            package_path.value.clone(),
+            /* with_unpublished_dependencies */ false,
+            /* skip_dependency_verification */ false,
+        )
+        .await
+        .map_err(|e| PtbError::new(package_path.span, format!("Failed to build package: {e}")))?;
+
+        Ok((
+            dependencies.published.into_values().collect(),
+            modules,
+            package.get_package_digest(false).to_vec(),
+            package_id,
+        ))
+    }
+
+    /// The types of the parameters of `function` that are passed explicitly, i.e. all but the
+    /// `TxContext`, instantiated with `type_args`.
+    async fn parameters(
+        &mut self,
+        function: &Spanned<FunctionPath>,
+        type_args: &[TypeTag],
+    ) -> PtbResult<Vec<ArgType>> {
+        let Spanned { span, value: path } = function;
+        if !self.packages.contains_key(&path.package) {
+            let modules = self
+                .client
+                .read_api()
+                .get_normalized_move_modules_by_package(path.package)
+                .await
+                .map_err(|e| {
+                    PtbError::new(
+                        *span,
+                        format!("Failed to fetch package {}: {e}", path.package),
+                    )
+                })?;
+            self.packages.insert(path.package, modules);
+        }
+
+        let Some(module) = self.packages[&path.package].get(path.module.as_str()) else {
+            return Err(PtbError::new(
+                *span,
+                format!("No module '{}' in package {}", path.module, path.package),
+            ));
+        };
+
+        let Some(signature) = module.exposed_functions.get(path.function.as_str()) else {
+            return Err(PtbError::new(
+                *span,
+                format!("No public or entry function '{path}'"),
+            ));
+        };
+
+        if signature.type_parameters.len() != type_args.len() {
+            return Err(PtbError::new(
+                *span,
+                format!(
+                    "{path} expects {} type argument(s), but {} were given",
+                    signature.type_parameters.len(),
+                    type_args.len(),
+                ),
+            ));
+        }
+
+        signature
+            .parameters
+            .iter()
+            .filter(|parameter| !is_tx_context(parameter))
+            .map(|parameter| {
+                let (type_, mutable) = match parameter {
+                    MgoMoveNormalizedType::Reference(type_) => (type_.as_ref(), false),
+                    MgoMoveNormalizedType::MutableReference(type_) => (type_.as_ref(), true),
+                    type_ => (type_, true),
+                };
+                let tag = type_tag(type_, type_args).map_err(|e| {
+                    PtbError::new(*span, format!("In the signature of {path}: {e}"))
+                })?;
+                Ok(arg_type(tag, mutable))
+            })
+            .collect()
+    }
+
+    /// Each element of `values`, a vector, as an argument of type `type_`.
+    async fn args(&mut self, values: &Spanned<Value>, type_: &ArgType) -> PtbResult<Vec<Argument>> {
+        let Value::Vector(values) = &values.value else {
+            return Err(mismatch(values, "a vector"));
+        };
+
+        let mut args = vec![];
+        for value in values {
+            args.push(self.arg(value, type_).await?);
+        }
+        Ok(args)
+    }
+
+    async fn arg(&mut self, value: &Spanned<Value>, type_: &ArgType) -> PtbResult<Argument> {
+        // Vectors that are not made only of literals cannot be a single pure input, and vectors
+        // of objects never can, so they are made from their elements instead.
+        let element = match (&value.value, type_) {
+            (Value::Vector(_), ArgType::Objects(element)) => {
+                Some((element.clone(), arg_type(element.clone(), true)))
+            }
+            (Value::Vector(_), ArgType::Pure(TypeTag::Vector(element)))
+                if !value.value.is_literal() =>
+            {
+                Some((*element.clone(), ArgType::Pure(*element.clone())))
+            }
+            _ => None,
+        };
+
+        let Some((tag, element)) = element else {
+            return self.scalar(value, type_).await;
+        };
+
+        let Value::Vector(values) = &value.value else {
+            unreachable!("only vectors have element types");
+        };
+
+        let mut elements = vec![];
+        for value in values {
+            elements.push(self.scalar(value, &element).await?);
+        }
+        Ok(self
+            .builder
+            .command(Command::MakeMoveVec(Some(tag), elements)))
+    }
+
+    /// Like [`Self::arg`], for values that are not made into vectors with `MakeMoveVec`.
+    async fn scalar(&mut self, value: &Spanned<Value>, type_: &ArgType) -> PtbResult<Argument> {
+        match (&value.value, type_) {
+            (Value::Gas, _) => Ok(Argument::GasCoin),
+            (Value::Result(name), _) => Ok(self.results[name]),
+            (Value::NestedResult(name, index), _) => match self.results[name] {
+                Argument::Result(command) => Ok(Argument::NestedResult(command, *index)),
+                _ => Err(PtbError::new(
+                    value.span,
+                    format!("'{name}' is a single value, and cannot be indexed"),
+                )),
+            },
+
+            (_, ArgType::Pure(tag)) if value.value.is_literal() => {
+                let mut bytes = vec![];
+                serialize_pure(value, tag, &mut bytes)?;
+                Ok(self.builder.pure_bytes(bytes, /* force_separate */ false))
+            }
+
+            (Value::Address(address), ArgType::Object { .. } | ArgType::Receiving) => {
+                self.object((*address).into(), value.span, type_).await
+            }
+
+            (_, ArgType::Pure(tag)) => Err(mismatch(value, &format!("a value of type {tag}"))),
+            (_, ArgType::Object { .. }) => Err(mismatch(value, "an object, e.g. @0x...")),
+            (_, ArgType::Receiving) => Err(mismatch(value, "an object to receive, e.g. @0x...")),
+            (_, ArgType::Objects(tag)) => Err(mismatch(value, &format!("a vector of {tag}"))),
+        }
+    }
+
+    /// An input for the object `id`, which is shared or owned depending on its current owner.
+    async fn object(&mut self, id: ObjectID, span: Span, type_: &ArgType) -> PtbResult<Argument> {
+        let (object_ref, owner) = match self.objects.get(&id) {
+            Some(object) => *object,
+            None => {
+                let object = self
+                    .client
+                    .read_api()
+                    .get_object_with_options(id, MgoObjectDataOptions::new().with_owner())
+                    .await
+                    .map_err(|e| PtbError::new(span, format!("Failed to fetch object {id}: {e}")))?
+                    .into_object()
+                    .map_err(|e| PtbError::new(span, format!("Cannot use object {id}: {e}")))?;
+                let Some(owner) = object.owner else {
+                    return Err(PtbError::new(span, format!("Unknown owner of object {id}")));
+                };
+                self.objects.insert(id, (object.object_ref(), owner));
+                (object.object_ref(), owner)
+            }
+        };
+
+        let arg = match (owner, type_) {
+            (_, ArgType::Receiving) => ObjectArg::Receiving(object_ref),
+            (
+                Owner::Shared {
+                    initial_shared_version,
+                },
+                ArgType::Object { mutable },
+            ) => ObjectArg::SharedObject {
+                id,
+                initial_shared_version,
+                mutable: *mutable,
+            },
+            _ => ObjectArg::ImmOrOwnedObject(object_ref),
+        };
+
+        self.builder
+            .obj(arg)
+            .map_err(|e| PtbError::new(span, e.to_string()))
+    }
+
+    async fn upgrade_policy(&self, id: ObjectID, span: Span) -> PtbResult<u8> {
+        let object = self
+            .client
+            .read_api()
+            .get_object_with_options(id, MgoObjectDataOptions::new().with_bcs())
+            .await
+            .map_err(|e| PtbError::new(span, format!("Failed to fetch object {id}: {e}")))?
+            .into_object()
+            .map_err(|e| PtbError::new(span, format!("Cannot use object {id}: {e}")))?;
+
+        let upgrade_cap = object
+            .bcs
+            .as_ref()
+            .and_then(|bcs| bcs.try_as_move())
+            .and_then(|bcs| bcs.deserialize::<UpgradeCap>().ok())
+            .ok_or_else(|| PtbError::new(span, format!("{id} is not an upgrade capability")))?;
+
+        Ok(upgrade_cap.policy)
+    }
+
+    fn pure<T: Serialize>(&mut self, span: Span, value: T) -> PtbResult<Argument> {
+        self.builder
+            .pure(value)
+            .map_err(|e| PtbError::new(span, e.to_string()))
+    }
+}
+
+/// How a value of type `tag` is passed to a command that takes it by value, or by mutable
+/// reference if `mutable`.
+fn arg_type(tag: TypeTag, mutable: bool) -> ArgType {
+    if is_pure(&tag) {
+        return ArgType::Pure(tag);
+    }
+
+    match tag {
+        TypeTag::Vector(element) => ArgType::Objects(*element),
+        TypeTag::Struct(s) if resolved(&s) == RESOLVED_RECEIVING_STRUCT => ArgType::Receiving,
+        _ => ArgType::Object { mutable },
+    }
+}
+
+/// Whether values of type `tag` can be passed as pure inputs.
+fn is_pure(tag: &TypeTag) -> bool {
+    match tag {
+        TypeTag::Bool
+        | TypeTag::U8
+        | TypeTag::U16
+        | TypeTag::U32
+        | TypeTag::U64
+        | TypeTag::U128
+        | TypeTag::U256
+        | TypeTag::Address => true,
+        TypeTag::Signer => false,
+        TypeTag::Vector(element) => is_pure(element),
+        TypeTag::Struct(s) => match resolved(s) {
+            r if r == RESOLVED_UTF8_STR || r == RESOLVED_ASCII_STR || r == RESOLVED_MGO_ID => true,
+            r if r == RESOLVED_STD_OPTION => s.type_params.iter().all(is_pure),
+            _ => false,
+        },
+    }
+}
+
+fn resolved(s: &StructTag) -> (&AccountAddress, &IdentStr, &IdentStr) {
+    (&s.address, s.module.as_ident_str(), s.name.as_ident_str())
+}
+
+fn is_tx_context(parameter: &MgoMoveNormalizedType) -> bool {
+    let (MgoMoveNormalizedType::Reference(type_) | MgoMoveNormalizedType::MutableReference(type_)) =
+        parameter
+    else {
+        return false;
+    };
+
+    matches!(
+        type_tag(type_, &[]),
+        Ok(TypeTag::Struct(s)) if resolved(&s)
+            == (&MGO_FRAMEWORK_ADDRESS, TX_CONTEXT_MODULE_NAME, TX_CONTEXT_STRUCT_NAME)
+    )
+}
+
+/// The type tag of a parameter type, with its type parameters replaced by `type_args`.
+fn type_tag(type_: &MgoMoveNormalizedType, type_args: &[TypeTag]) -> Result<TypeTag, String> {
+    use MgoMoveNormalizedType as T;
+    Ok(match type_ {
+        T::Bool => TypeTag::Bool,
+        T::U8 => TypeTag::U8,
+        T::U16 => TypeTag::U16,
+        T::U32 => TypeTag::U32,
+        T::U64 => TypeTag::U64,
+        T::U128 => TypeTag::U128,
+        T::U256 => TypeTag::U256,
+        T::Address => TypeTag::Address,
+        T::Signer => TypeTag::Signer,
+        T::Vector(element) => TypeTag::Vector(Box::new(type_tag(element, type_args)?)),
+        T::TypeParameter(index) => type_args
+            .get(*index as usize)
+            .cloned()
+            .ok_or_else(|| format!("Unbound type parameter {index}"))?,
+        T::Struct {
+            address,
+            module,
+            name,
+            type_arguments,
+        } => TypeTag::Struct(Box::new(StructTag {
+            address: AccountAddress::from_hex_literal(address)
+                .map_err(|e| format!("Invalid address '{address}': {e}"))?,
+            module: Identifier::new(module.as_str())
+                .map_err(|e| format!("Invalid module name '{module}': {e}"))?,
+            name: Identifier::new(name.as_str())
+                .map_err(|e| format!("Invalid struct name '{name}': {e}"))?,
+            type_params: type_arguments
+                .iter()
+                .map(|t| type_tag(t, type_args))
+                .collect::<Result<_, _>>()?,
+        })),
+        T::Reference(_) | T::MutableReference(_) => {
+            return Err("Unexpected reference inside a type".to_string())
+        }
+    })
+}
+
+/// Appends the BCS of the literal `value`, as a value of type `tag`, to `bytes`.
+fn serialize_pure(value: &Spanned<Value>, tag: &TypeTag, bytes: &mut Vec<u8>) -> PtbResult<()> {
+    let span = value.span;
+    let struct_ = match tag {
+        TypeTag::Struct(s) => Some(resolved(s)),
+        _ => None,
+    };
+
+    match (&value.value, tag) {
+        (Value::Bool(b), TypeTag::Bool) => bytes.push(*b as u8),
+        (Value::Number(n), TypeTag::U8) => serialize_integer::<u8>(*n, span, tag, bytes)?,
+        (Value::Number(n), TypeTag::U16) => serialize_integer::<u16>(*n, span, tag, bytes)?,
+        (Value::Number(n), TypeTag::U32) => serialize_integer::<u32>(*n, span, tag, bytes)?,
+        (Value::Number(n), TypeTag::U64) => serialize_integer::<u64>(*n, span, tag, bytes)?,
+        (Value::Number(n), TypeTag::U128) => serialize_integer::<u128>(*n, span, tag, bytes)?,
+        (Value::Number(n), TypeTag::U256) => serialize_integer::<U256>(*n, span, tag, bytes)?,
+        (Value::Address(address), TypeTag::Address) => bytes.extend_from_slice(address.as_ref()),
+        (Value::Address(address), _) if struct_ == Some(RESOLVED_MGO_ID) => {
+            bytes.extend_from_slice(address.as_ref())
+        }
+
+        (Value::String(s), TypeTag::Vector(element)) if **element == TypeTag::U8 => {
+            serialize_bytes(s.as_bytes(), bytes)
+        }
+        (Value::String(s), _) if struct_ == Some(RESOLVED_UTF8_STR) => {
+            serialize_bytes(s.as_bytes(), bytes)
+        }
+        (Value::String(s), _) if struct_ == Some(RESOLVED_ASCII_STR) => {
+            if !s.is_ascii() {
+                return Err(PtbError::new(span, "Expected an ASCII string"));
+            }
+            serialize_bytes(s.as_bytes(), bytes)
+        }
+
+        (Value::Vector(values), TypeTag::Vector(element)) => {
+            serialize_length(values.len(), bytes);
+            for value in values {
+                serialize_pure(value, element, bytes)?;
+            }
+        }
+
+        // Options are vectors of at most one element.
+        (Value::Option(value), TypeTag::Struct(s)) if struct_ == Some(RESOLVED_STD_OPTION) => {
+            let Some(value) = value else {
+                bytes.push(0);
+                return Ok(());
+            };
+
+            bytes.push(1);
+            serialize_pure(value, &s.type_params[0], bytes)?;
+        }
+
+        _ => return Err(mismatch(value, &format!("a value of type {tag}"))),
+    }
+
+    Ok(())
+}
+
+fn serialize_integer<T>(n: U256, span: Span, tag: &TypeTag, bytes: &mut Vec<u8>) -> PtbResult<()>
+where
+    T: TryFrom<U256> + Serialize,
+{
+    let Ok(n) = T::try_from(n) else {
+        return Err(PtbError::new(span, format!("{n} does not fit in a {tag}")));
+    };
+
+    bytes.extend(bcs::to_bytes(&n).expect("Integers can always be serialized"));
+    Ok(())
+}
+
+fn serialize_bytes(value: &[u8], bytes: &mut Vec<u8>) {
+    serialize_length(value.len(), bytes);
+    bytes.extend_from_slice(value);
+}
+
+/// Appends `len` as the ULEB128 length prefix of a BCS sequence.
+fn serialize_length(mut len: usize, bytes: &mut Vec<u8>) {
+    while len >= 0x80 {
+        bytes.push((len as u8 & 0x7f) | 0x80);
+        len >>= 7;
+    }
+    bytes.push(len as u8);
+}
+
+fn mismatch(value: &Spanned<Value>, expected: &str) -> PtbError {
+    PtbError::new(
+        value.span,
+        format!("Expected {expected}, found {}", value.value.description()),
+    )
+}
@@ -0,0 +1,116 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::{self, Display, Formatter};
+
+use colored::Colorize;
+
+/// A range of bytes in the source of a PTB, which is the `mgo client ptb` arguments joined by
+/// spaces.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A value, along with where it was written in the source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Spanned<T> {
+    pub span: Span,
+    pub value: T,
+}
+
+/// An error in a PTB, pointing at the part of the source that caused it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PtbError {
+    pub span: Span,
+    pub message: String,
+    pub help: Option<String>,
+}
+
+pub type PtbResult<T> = Result<T, PtbError>;
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// The smallest span covering both `self` and `other`.
+    pub fn join(self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    pub fn wrap<T>(self, value: T) -> Spanned<T> {
+        Spanned { span: self, value }
+    }
+}
+
+impl<T> Spanned<T> {
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Spanned<U> {
+        Spanned {
+            span: self.span,
+            value: f(self.value),
+        }
+    }
+}
+
+impl PtbError {
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+            help: None,
+        }
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    /// Renders the error with the source it refers to, underlining the offending span.
+    pub fn render(&self, source: &str) -> String {
+        RenderedPtbError {
+            error: self,
+            source,
+        }
+        .to_string()
+    }
+}
+
+struct RenderedPtbError<'a> {
+    error: &'a PtbError,
+    source: &'a str,
+}
+
+impl Display for RenderedPtbError<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let PtbError {
+            span,
+            message,
+            help,
+        } = self.error;
+
+        // Spans at the end of the source (e.g. for a missing argument) still get one caret.
+        let start = span.start.min(self.source.len());
+        let end = span.end.clamp(start, self.source.len());
+        let offset = self.source[..start].chars().count();
+        let width = self.source[start..end].chars().count().max(1);
+
+        writeln!(f, "{}: {message}", "Error".red().bold())?;
+        writeln!(f, "  {}", self.source)?;
+        write!(
+            f,
+            "  {}{}",
+            " ".repeat(offset),
+            "^".repeat(width).red().bold()
+        )?;
+        if let Some(help) = help {
+            write!(f, "\n{}: {help}", "Help".bold())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+//! `mgo client ptb`: a small language for writing programmable transaction blocks on the command
+//! line. Its source is the arguments of the command joined by spaces, which is parsed into a
+//! [`Program`], and then built into a programmable transaction by a [`PtbBuilder`], which looks up
+//! the objects and functions the program uses.
+
+pub mod ast;
+mod builder;
+pub mod error;
+mod parser;
+
+pub use ast::{Program, ProgramOptions};
+pub use builder::PtbBuilder;
+pub use error::{PtbError, Span};
+pub use parser::parse;
+
+#[cfg(test)]
+#[path = "../unit_tests/client_ptb_tests.rs"]
+mod client_ptb_tests;
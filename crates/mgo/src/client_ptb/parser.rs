@@ -0,0 +1,617 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use mgo_types::base_types::{MgoAddress, ObjectID};
+use mgo_types::parse_mgo_type_tag;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::TypeTag;
+use move_core_types::u256::U256;
+
+use super::ast::{CommandKind, FunctionPath, ParsedCommand, Program, Value};
+use super::error::{PtbError, PtbResult, Span, Spanned};
+
+const COMMANDS: &str = "--split-coins, --merge-coins, --transfer-objects, --make-move-vec, \
+    --move-call, --publish, --upgrade, --assign, --gas-budget, --gas-coin, --dry-run, \
+    --dev-inspect, --serialize-unsigned-transaction, --serialize-signed-transaction, \
+    --valid-from-ms, --valid-until-ms, --bind-to-chain, --expiration-nonce";
+
+/// Words that cannot be used as names for results.
+const KEYWORDS: &[&str] = &["gas", "true", "false", "none", "some"];
+
+/// Parses the source of a PTB: the `mgo client ptb` arguments joined by spaces.
+pub fn parse(source: &str) -> PtbResult<Program> {
+    Parser::new(source).parse()
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+    /// Names bound by `--assign` so far, which later commands can refer to.
+    names: BTreeSet<String>,
+    /// Whether the last thing parsed was a command, so `--assign` can name its result.
+    assignable: bool,
+    /// The flag that set how the transaction is run, if any, to reject conflicting flags.
+    mode: Option<Spanned<&'static str>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            pos: 0,
+            names: BTreeSet::new(),
+            assignable: false,
+            mode: None,
+        }
+    }
+
+    fn parse(mut self) -> PtbResult<Program> {
+        let mut program = Program::default();
+
+        while let Some(flag) = self.flag()? {
+            let start = flag.span.start;
+            let kind = match flag.value {
+                "split-coins" => CommandKind::SplitCoins {
+                    coin: self.value()?,
+                    amounts: self.vector()?,
+                },
+                "merge-coins" => CommandKind::MergeCoins {
+                    into: self.value()?,
+                    coins: self.vector()?,
+                },
+                "transfer-objects" => CommandKind::TransferObjects {
+                    objects: self.vector()?,
+                    recipient: self.value()?,
+                },
+                "make-move-vec" => {
+                    let type_ = match self.type_args()? {
+                        Some(types) if types.value.len() == 1 => {
+                            types.value.into_iter().next().unwrap()
+                        }
+                        Some(types) => {
+                            return Err(PtbError::new(
+                                types.span,
+                                "Expected exactly one element type for --make-move-vec",
+                            ))
+                        }
+                        None => return Err(self.expected("an element type, e.g. <u64>")),
+                    };
+                    CommandKind::MakeMoveVec {
+                        type_,
+                        elements: self.vector()?,
+                    }
+                }
+                "move-call" => {
+                    let function = self.function_path()?;
+                    let type_args = self.type_args()?.map(|t| t.value).unwrap_or_default();
+                    let mut args = vec![];
+                    while !self.at_end() && !self.rest().starts_with("--") {
+                        args.push(self.value()?);
+                    }
+                    CommandKind::MoveCall {
+                        function,
+                        type_args,
+                        args,
+                    }
+                }
+                "publish" => CommandKind::Publish {
+                    package_path: self.path()?,
+                },
+                "upgrade" => CommandKind::Upgrade {
+                    package_path: self.path()?,
+                    upgrade_capability: self.object_id()?,
+                },
+                "assign" => {
+                    self.assign(&mut program, flag.span)?;
+                    continue;
+                }
+                _ => {
+                    self.option(&mut program, flag)?;
+                    self.assignable = false;
+                    continue;
+                }
+            };
+
+            let span = Span::new(start, self.pos);
+            program
+                .commands
+                .push(span.wrap(ParsedCommand { kind, assign: None }));
+            self.assignable = true;
+        }
+
+        let end = Span::new(self.source.len(), self.source.len());
+        if program.commands.is_empty() {
+            return Err(PtbError::new(end, "Expected at least one command")
+                .with_help(format!("The commands are: {COMMANDS}")));
+        }
+
+        if program.options.gas_budget.is_none() && !program.options.dev_inspect {
+            return Err(PtbError::new(end, "Missing gas budget")
+                .with_help("Set it with --gas-budget <amount>"));
+        }
+
+        Ok(program)
+    }
+
+    /// Binds the result of the previous command to a name.
+    fn assign(&mut self, program: &mut Program, flag: Span) -> PtbResult<()> {
+        let Some(command) = program.commands.last_mut().filter(|_| self.assignable) else {
+            return Err(PtbError::new(flag, "--assign must follow a command")
+                .with_help("It names the result of the command before it"));
+        };
+
+        let name = self.identifier("a name")?;
+        if KEYWORDS.contains(&name.value.as_str()) {
+            return Err(PtbError::new(
+                name.span,
+                format!("'{}' is reserved and cannot be used as a name", name.value),
+            ));
+        }
+
+        if !self.names.insert(name.value.clone()) {
+            return Err(PtbError::new(
+                name.span,
+                format!("'{}' is already the name of another result", name.value),
+            ));
+        }
+
+        command.span = command.span.join(name.span);
+        command.value.assign = Some(name);
+        self.assignable = false;
+        Ok(())
+    }
+
+    /// Parses the flags that set how the transaction is paid for and run.
+    fn option(&mut self, program: &mut Program, flag: Spanned<&'a str>) -> PtbResult<()> {
+        let options = &mut program.options;
+        match flag.value {
+            "gas-budget" => {
+                let budget = self.bounded_number("Gas budget", "u64")?;
+                set_once(&mut options.gas_budget, budget, flag.span)?;
+            }
+            "gas-coin" => {
+                let coin = self.object_id()?.value;
+                set_once(&mut options.gas_coin, coin, flag.span)?;
+            }
+            "dry-run" => {
+                self.mode(flag.span, "--dry-run")?;
+                options.dry_run = true;
+            }
+            "dev-inspect" => {
+                self.mode(flag.span, "--dev-inspect")?;
+                options.dev_inspect = true;
+            }
+            "serialize-unsigned-transaction" => {
+                self.mode(flag.span, "--serialize-unsigned-transaction")?;
+                options.serialize_unsigned_transaction = true;
+            }
+            "serialize-signed-transaction" => {
+                self.mode(flag.span, "--serialize-signed-transaction")?;
+                options.serialize_signed_transaction = true;
+            }
+            "valid-from-ms" => {
+                let timestamp = self.bounded_number("Timestamp", "u64")?;
+                set_once(&mut options.valid_from_ms, timestamp, flag.span)?;
+            }
+            "valid-until-ms" => {
+                let timestamp = self.bounded_number("Timestamp", "u64")?;
+                set_once(&mut options.valid_until_ms, timestamp, flag.span)?;
+            }
+            "bind-to-chain" => options.bind_to_chain = true,
+            "expiration-nonce" => {
+                let nonce = self.bounded_number("Expiration nonce", "u32")?;
+                set_once(&mut options.expiration_nonce, nonce, flag.span)?;
+            }
+            unknown => {
+                return Err(
+                    PtbError::new(flag.span, format!("Unknown command '--{unknown}'"))
+                        .with_help(format!("The commands are: {COMMANDS}")),
+                )
+            }
+        }
+        Ok(())
+    }
+
+    fn mode(&mut self, span: Span, flag: &'static str) -> PtbResult<()> {
+        if let Some(mode) = &self.mode {
+            return Err(PtbError::new(
+                span,
+                format!("{flag} cannot be used together with {}", mode.value),
+            ));
+        }
+
+        self.mode = Some(span.wrap(flag));
+        Ok(())
+    }
+
+    /// The next `--<flag>`, without its dashes, or `None` at the end of the source.
+    fn flag(&mut self) -> PtbResult<Option<Spanned<&'a str>>> {
+        self.skip_whitespace();
+        if self.at_end() {
+            return Ok(None);
+        }
+
+        let start = self.pos;
+        if !self.rest().starts_with("--") {
+            let word = self.word();
+            return Err(PtbError::new(
+                word.span,
+                format!("Expected a command, found '{}'", word.value),
+            )
+            .with_help(format!("The commands are: {COMMANDS}")));
+        }
+
+        self.pos += 2;
+        let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '-');
+        let span = Span::new(start, self.pos);
+        if name.is_empty() {
+            return Err(PtbError::new(span, "Expected a command after '--'"));
+        }
+
+        Ok(Some(span.wrap(name)))
+    }
+
+    fn value(&mut self) -> PtbResult<Spanned<Value>> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let Some(c) = self.peek() else {
+            return Err(self.expected("a value"));
+        };
+
+        let value = match c {
+            '[' => return self.vector(),
+            '@' => {
+                let id = self.object_id()?;
+                return Ok(id.map(|id| Value::Address(MgoAddress::from(id))));
+            }
+            '"' => Value::String(self.string()?.value),
+            '0'..='9' => Value::Number(self.number()?.value),
+            c if is_identifier_start(c) => {
+                let word = self.identifier("a value")?;
+                match word.value.as_str() {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    "gas" => Value::Gas,
+                    "none" => Value::Option(None),
+                    "some" => {
+                        self.expect('(')?;
+                        let value = self.value()?;
+                        self.skip_whitespace();
+                        self.expect(')')?;
+                        Value::Option(Some(Box::new(value)))
+                    }
+                    _ => self.result(word)?,
+                }
+            }
+            _ => {
+                let word = self.word();
+                return Err(PtbError::new(
+                    word.span,
+                    format!("Expected a value, found '{}'", word.value),
+                ));
+            }
+        };
+
+        Ok(Span::new(start, self.pos).wrap(value))
+    }
+
+    /// A reference to the result of an earlier command, `<name>` or `<name>.<index>`.
+    fn result(&mut self, name: Spanned<String>) -> PtbResult<Value> {
+        if !self.names.contains(&name.value) {
+            let help = if self.names.is_empty() {
+                "Name the result of a command with --assign <name> after it".to_string()
+            } else {
+                let names: Vec<_> = self.names.iter().map(String::as_str).collect();
+                format!("The results named so far are: {}", names.join(", "))
+            };
+            return Err(
+                PtbError::new(name.span, format!("Unknown name '{}'", name.value)).with_help(help),
+            );
+        }
+
+        if self.peek() != Some('.') {
+            return Ok(Value::Result(name.value));
+        }
+
+        self.pos += 1;
+        let index = self.number()?;
+        let Ok(index) = u16::try_from(index.value) else {
+            return Err(PtbError::new(
+                index.span,
+                "Result index does not fit in a u16",
+            ));
+        };
+
+        Ok(Value::NestedResult(name.value, index))
+    }
+
+    fn vector(&mut self) -> PtbResult<Spanned<Value>> {
+        self.skip_whitespace();
+        let start = self.pos;
+        if self.peek() != Some('[') {
+            return Err(self.expected("a vector, e.g. [1, 2]"));
+        }
+
+        self.pos += 1;
+        let mut values = vec![];
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(']') {
+                break;
+            }
+
+            values.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => break,
+                _ => return Err(self.expected("',' or ']'")),
+            }
+        }
+
+        self.pos += 1;
+        Ok(Span::new(start, self.pos).wrap(Value::Vector(values)))
+    }
+
+    /// A number that must fit in `T`, whose name is `type_name`.
+    fn bounded_number<T: TryFrom<U256>>(&mut self, what: &str, type_name: &str) -> PtbResult<T> {
+        let number = self.number()?;
+        T::try_from(number.value).map_err(|_| {
+            PtbError::new(number.span, format!("{what} does not fit in a {type_name}"))
+        })
+    }
+
+    fn number(&mut self) -> PtbResult<Spanned<U256>> {
+        self.skip_whitespace();
+        let word = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        let span = Span::new(self.pos - word.len(), self.pos);
+        if word.is_empty() {
+            return Err(self.expected("a number"));
+        }
+
+        let digits = word.replace('_', "");
+        let number = match digits.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16),
+            None => U256::from_str_radix(&digits, 10),
+        };
+
+        number
+            .map(|n| span.wrap(n))
+            .map_err(|_| PtbError::new(span, format!("Invalid number '{word}'")))
+    }
+
+    fn string(&mut self) -> PtbResult<Spanned<String>> {
+        let start = self.pos;
+        self.expect('"')?;
+
+        let mut string = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(PtbError::new(
+                    Span::new(start, self.pos),
+                    "Unterminated string",
+                ));
+            };
+
+            self.pos += c.len_utf8();
+            match c {
+                '"' => break,
+                '\\' => {
+                    let Some(escaped) = self.peek() else {
+                        continue;
+                    };
+                    self.pos += escaped.len_utf8();
+                    string.push(match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        c => c,
+                    });
+                }
+                c => string.push(c),
+            }
+        }
+
+        Ok(Span::new(start, self.pos).wrap(string))
+    }
+
+    /// An object ID or address, written `@0x...`.
+    fn object_id(&mut self) -> PtbResult<Spanned<ObjectID>> {
+        self.skip_whitespace();
+        let start = self.pos;
+        if self.peek() != Some('@') {
+            return Err(self.expected("an address, e.g. @0x2"));
+        }
+
+        self.pos += 1;
+        let hex = self.take_while(|c| c.is_ascii_alphanumeric());
+        let span = Span::new(start, self.pos);
+        ObjectID::from_hex_literal(hex)
+            .map(|id| span.wrap(id))
+            .map_err(|e| PtbError::new(span, format!("Invalid address '@{hex}': {e}")))
+    }
+
+    /// A function to call, `<package>::<module>::<function>`.
+    fn function_path(&mut self) -> PtbResult<Spanned<FunctionPath>> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let word = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':');
+        let span = Span::new(start, self.pos);
+        if word.is_empty() {
+            return Err(self.expected("a function, e.g. 0x2::coin::value"));
+        }
+
+        let invalid = |message: String| {
+            PtbError::new(span, message).with_help(
+                "Functions are written <package>::<module>::<function>, e.g. 0x2::coin::value",
+            )
+        };
+
+        let [package, module, function] = word.split("::").collect::<Vec<_>>()[..] else {
+            return Err(invalid(format!("Invalid function '{word}'")));
+        };
+
+        let package = ObjectID::from_hex_literal(package)
+            .map_err(|e| invalid(format!("Invalid package '{package}': {e}")))?;
+        let module = Identifier::new(module)
+            .map_err(|_| invalid(format!("Invalid module name '{module}'")))?;
+        let function = Identifier::new(function)
+            .map_err(|_| invalid(format!("Invalid function name '{function}'")))?;
+
+        Ok(span.wrap(FunctionPath {
+            package,
+            module,
+            function,
+        }))
+    }
+
+    /// Type arguments, `<type, ...>`, if there are any.
+    fn type_args(&mut self) -> PtbResult<Option<Spanned<Vec<Spanned<TypeTag>>>>> {
+        self.skip_whitespace();
+        let start = self.pos;
+        if self.peek() != Some('<') {
+            return Ok(None);
+        }
+
+        self.pos += 1;
+        let mut types = vec![];
+        let mut type_start = self.pos;
+        let mut depth = 0;
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(PtbError::new(
+                    Span::new(start, self.pos),
+                    "Unterminated type arguments",
+                ));
+            };
+
+            // Only a `>` at the outermost level closes the type arguments. Check before `depth`
+            // is decremented, so that the `>` closing a nested type does not end them too.
+            let closes = c == '>' && depth == 0;
+            match c {
+                '<' => depth += 1,
+                '>' if depth > 0 => depth -= 1,
+                ',' | '>' if depth == 0 => {
+                    types.push(self.type_tag(type_start, self.pos)?);
+                    type_start = self.pos + 1;
+                }
+                _ => {}
+            }
+
+            self.pos += c.len_utf8();
+            if closes {
+                break;
+            }
+        }
+
+        Ok(Some(Span::new(start, self.pos).wrap(types)))
+    }
+
+    fn type_tag(&self, start: usize, end: usize) -> PtbResult<Spanned<TypeTag>> {
+        let text = &self.source[start..end];
+        let trimmed_start = start + (text.len() - text.trim_start().len());
+        let span = Span::new(trimmed_start, trimmed_start + text.trim().len());
+        parse_mgo_type_tag(text.trim())
+            .map(|tag| span.wrap(tag))
+            .map_err(|e| PtbError::new(span, format!("Invalid type '{}': {e}", text.trim())))
+    }
+
+    /// The path to a package, either quoted or up to the next whitespace.
+    fn path(&mut self) -> PtbResult<Spanned<PathBuf>> {
+        self.skip_whitespace();
+        if self.peek() == Some('"') {
+            return Ok(self.string()?.map(PathBuf::from));
+        }
+
+        if self.at_end() || self.rest().starts_with("--") {
+            return Err(self.expected("a package path"));
+        }
+
+        Ok(self.word().map(PathBuf::from))
+    }
+
+    fn identifier(&mut self, expected: &str) -> PtbResult<Spanned<String>> {
+        self.skip_whitespace();
+        if !self.peek().is_some_and(is_identifier_start) {
+            return Err(self.expected(expected));
+        }
+
+        let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        Ok(Span::new(self.pos - name.len(), self.pos).wrap(name.to_string()))
+    }
+
+    fn expect(&mut self, c: char) -> PtbResult<()> {
+        if self.peek() != Some(c) {
+            return Err(self.expected(&format!("'{c}'")));
+        }
+
+        self.pos += c.len_utf8();
+        Ok(())
+    }
+
+    /// An error for something missing at the current position, pointing at what is there instead.
+    fn expected(&self, expected: &str) -> PtbError {
+        let rest = self.rest().trim_start();
+        let start = self.source.len() - rest.len();
+        let found = rest.split_whitespace().next().unwrap_or_default();
+        if found.is_empty() {
+            PtbError::new(
+                Span::new(start, start),
+                format!("Expected {expected}, found the end of the input"),
+            )
+        } else {
+            PtbError::new(
+                Span::new(start, start + found.len()),
+                format!("Expected {expected}, found '{found}'"),
+            )
+        }
+    }
+
+    /// Everything up to the next whitespace.
+    fn word(&mut self) -> Spanned<String> {
+        let word = self.take_while(|c| !c.is_whitespace());
+        Span::new(self.pos - word.len(), self.pos).wrap(word.to_string())
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let source = self.source;
+        let start = self.pos;
+        while let Some(c) = self.peek().filter(|c| f(*c)) {
+            self.pos += c.len_utf8();
+        }
+        &source[start..self.pos]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn rest(&self) -> &'a str {
+        let source = self.source;
+        &source[self.pos..]
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.pos == self.source.len()
+    }
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn set_once<T>(option: &mut Option<T>, value: T, span: Span) -> PtbResult<()> {
+    if option.is_some() {
+        return Err(PtbError::new(span, "This option can only be set once"));
+    }
+
+    *option = Some(value);
+    Ok(())
+}
@@ -28,7 +28,13 @@ use shared_crypto::intent::{Intent, IntentMessage, IntentScope, PersonalMessage}
 use std::fmt::{Debug, Display, Formatter};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+use mgo_keys::encrypted_keystore::{
+    read_new_password, read_password, EncryptedFileKeystore, KdfParams, MGO_KEYSTORE_NEW_PASSWORD,
+    MGO_KEYSTORE_PASSWORD, MGO_KEYSTORE_SESSION,
+};
 use mgo_keys::key_derive::generate_new_key;
+use mgo_keys::key_shares::{combine_shares, split_mnemonic, split_private_key, KeyShare};
 use mgo_keys::keypair_file::{
     read_authority_keypair_from_file, read_keypair_from_file, write_authority_keypair_to_file,
     write_keypair_to_file,
@@ -43,6 +49,8 @@ use mgo_types::crypto::{DefaultHash, PublicKey};
 use mgo_types::error::MgoResult;
 use mgo_types::multisig::{MultiSig, MultiSigPublicKey, ThresholdUnit, WeightUnit};
 use mgo_types::multisig_legacy::{MultiSigLegacy, MultiSigPublicKeyLegacy};
+use mgo_types::multisig_policy::MultiSigPolicy;
+use mgo_types::passkey_authenticator::PasskeyAuthenticator;
 use mgo_types::signature::{AuthenticatorTrait, GenericSignature, VerifyParams};
 use mgo_types::transaction::TransactionData;
 use mgo_types::zk_login_authenticator::ZkLoginAuthenticator;
@@ -67,6 +75,20 @@ pub enum KeyToolCommand {
         /// The alias must start with a letter and can contain only letters, digits, dots, hyphens (-), or underscores (_).
         new_alias: Option<String>,
     },
+    /// Change the password of an encrypted keystore. The current password is read from
+    /// MGO_KEYSTORE_PASSWORD and the new one from MGO_KEYSTORE_NEW_PASSWORD if they are set,
+    /// otherwise they are prompted for. Any unlock session of the keystore is ended.
+    ChangePassword,
+    /// Recombine shares made by `mgo keytool split-key` into their key, verify that it is the key
+    /// the shares were made from, and add it to Mgo CLI Keystore. Any `threshold` of the shares
+    /// can be passed in, in any order.
+    CombineKeyShares {
+        /// Sets an alias for this address. The alias must start with a letter and can contain only letters, digits, hyphens (-), or underscores (_).
+        #[clap(long)]
+        alias: Option<String>,
+        #[clap(long, num_args(1..))]
+        shares: Vec<KeyShare>,
+    },
     /// Convert private key in Hex or Base64 to new format (Bech32
     /// encoded 33 byte flag || private key starting with "mgoprivkey").
     /// Hex private key format import and export are both deprecated in
@@ -87,6 +109,11 @@ pub enum KeyToolCommand {
         #[clap(long)]
         tx_bytes: Option<String>,
     },
+    /// Encrypt the private keys of a plaintext keystore with a password, replacing the keystore
+    /// file in place. The password is read from MGO_KEYSTORE_PASSWORD if it is set, otherwise it
+    /// is prompted for twice. Commands that need a private key then ask for the password, unless
+    /// the keystore was unlocked with `mgo keytool unlock`.
+    EncryptKeystore,
     /// Generate a new keypair with key scheme flag {ed25519 | secp256k1 | secp256r1}
     /// with optional derivation path, default to m/44'/784'/0'/0'/0' for ed25519 or
     /// m/54'/784'/0'/0/0 for secp256k1 or m/74'/784'/0'/0/0 for secp256r1. Word
@@ -135,12 +162,15 @@ pub enum KeyToolCommand {
     /// (Base64 encoded `privkey`). This prints out the account keypair as Base64 encoded `flag || privkey`,
     /// the network keypair, worker keypair, protocol keypair as Base64 encoded `privkey`.
     LoadKeypair { file: PathBuf },
+    /// Lock an encrypted keystore, ending the session started by `mgo keytool unlock`.
+    Lock,
     /// To MultiSig Mgo Address. Pass in a list of all public keys `flag || pk` in Base64.
-    /// See `keytool list` for example public keys.
+    /// See `keytool list` for example public keys. A share made by `mgo keytool split-key`
+    /// can be passed in place of the public key of the key it is a share of.
     MultiSigAddress {
         #[clap(long)]
         threshold: ThresholdUnit,
-        #[clap(long, num_args(1..))]
+        #[clap(long, num_args(1..), value_parser = parse_public_key_or_share)]
         pks: Vec<PublicKey>,
         #[clap(long, num_args(1..))]
         weights: Vec<WeightUnit>,
@@ -153,11 +183,12 @@ pub enum KeyToolCommand {
     ///
     /// The order of `sigs` must be the same as the order of `pks`.
     /// e.g. for [pk1, pk2, pk3, pk4, pk5], [sig1, sig2, sig5] is valid, but
-    /// [sig2, sig1, sig5] is invalid.
+    /// [sig2, sig1, sig5] is invalid. As with `multi-sig-address`, a key share can be passed in
+    /// place of a public key.
     MultiSigCombinePartialSig {
         #[clap(long, num_args(1..))]
         sigs: Vec<GenericSignature>,
-        #[clap(long, num_args(1..))]
+        #[clap(long, num_args(1..), value_parser = parse_public_key_or_share)]
         pks: Vec<PublicKey>,
         #[clap(long, num_args(1..))]
         weights: Vec<WeightUnit>,
@@ -174,11 +205,50 @@ pub enum KeyToolCommand {
         #[clap(long)]
         threshold: ThresholdUnit,
     },
+    /// To MultiSig Mgo Address of a policy file in YAML or JSON. A policy describes a MultiSig
+    /// whose members can be public keys `flag || pk` in Base64, zkLogin accounts by their `iss`
+    /// and `address_seed`, or other MultiSigs described the same way.
+    MultiSigPolicyAddress {
+        #[clap(long)]
+        policy: PathBuf,
+    },
+    /// Provides a list of participating signatures (`flag || sig || pk` encoded in Base64) of
+    /// members at any level of a policy file. Returns a valid MultiSig signature and its sender
+    /// address. The signatures of the members of a nested MultiSig are combined into its
+    /// signature, which can also be passed in already combined. The signatures can be in any
+    /// order.
+    MultiSigCombinePolicySig {
+        #[clap(long)]
+        policy: PathBuf,
+        #[clap(long, num_args(1..))]
+        sigs: Vec<GenericSignature>,
+    },
 
     /// Read the content at the provided file path. The accepted format can be
     /// [enum MgoKeyPair] (Base64 encoded of 33-byte `flag || privkey`) or `type AuthorityKeyPair`
     /// (Base64 encoded `privkey`). It prints its Base64 encoded public key and the key scheme flag.
     Show { file: PathBuf },
+    /// Split the private key of the given key identity in Mgo CLI Keystore, or a mnemonic phrase,
+    /// into `shares` shares with Shamir's secret sharing, any `threshold` of which recombine into
+    /// the key with `mgo keytool combine-key-shares`, while fewer reveal nothing about it. Each
+    /// share carries a checksum, its index and the public key of the key. A mnemonic phrase is
+    /// split along with its key scheme and derivation path, default to m/44'/784'/0'/0'/0' for
+    /// ed25519 or m/54'/784'/0'/0/0 for secp256k1 or m/74'/784'/0'/0/0 for secp256r1.
+    SplitKey {
+        #[clap(long, required_unless_present = "mnemonic_phrase")]
+        key_identity: Option<KeyIdentity>,
+        #[clap(long, conflicts_with = "key_identity", requires = "key_scheme")]
+        mnemonic_phrase: Option<String>,
+        #[clap(long)]
+        key_scheme: Option<SignatureScheme>,
+        #[clap(long)]
+        derivation_path: Option<DerivationPath>,
+        /// How many shares recombine into the key, at least 2.
+        #[clap(long)]
+        threshold: u8,
+        #[clap(long)]
+        shares: u8,
+    },
     /// Create signature using the private key for for the given address (or its alias) in mgo keystore.
     /// Any signature commits to a [struct IntentMessage] consisting of the Base64 encoded
     /// of the BCS serialized transaction bytes itself and its intent. If intent is absent,
@@ -211,6 +281,16 @@ pub enum KeyToolCommand {
     /// outputs the keypair into a file at the current directory where the address is the filename,
     /// and prints out its Mgo address, Base64 encoded public key, the key scheme, and the key scheme flag.
     Unpack { keypair: String },
+    /// Unlock an encrypted keystore for a period of time, so that later commands can sign without
+    /// asking for the password. The password is read from MGO_KEYSTORE_PASSWORD if it is set,
+    /// otherwise it is prompted for. This prints a session token, which later commands read from
+    /// MGO_KEYSTORE_SESSION. The derived key is kept in a file next to the keystore, encrypted
+    /// with the token, until the session expires or the keystore is locked.
+    Unlock {
+        /// How long the keystore stays unlocked, e.g. 30s, 15m or 2h.
+        #[clap(long, default_value = "15m", value_parser = humantime::parse_duration)]
+        timeout: Duration,
+    },
 
     /// Given the max_epoch, generate an OAuth url, ask user to paste the redirect with id_token, call salt server, then call the prover server,
     /// create a test transaction, use the ephemeral key to sign and execute it by assembling to a serialized zkLogin signature.
@@ -269,6 +349,22 @@ pub enum KeyToolCommand {
         network: String,
     },
 
+    /// Given a passkey signature, parse it if valid. If `bytes` provided, parse it as either
+    /// TransactionData or PersonalMessage based on `intent_scope`, and verify the WebAuthn
+    /// assertion against it: the challenge, origin, authenticator data and secp256r1 signature.
+    /// Example request: mgo keytool passkey-sig-verify --sig $SERIALIZED_PASSKEY_SIG --bytes $BYTES --intent-scope 0
+    PasskeySigVerify {
+        /// The Base64 of the serialized passkey signature.
+        #[clap(long)]
+        sig: String,
+        /// The Base64 of the BCS encoded TransactionData or PersonalMessage.
+        #[clap(long)]
+        bytes: Option<String>,
+        /// Either 0 for TransactionData or 3 for PersonalMessage.
+        #[clap(long)]
+        intent_scope: u8,
+    },
+
     /// TESTING ONLY: Given a string of data, sign with the fixed dev-only ephemeral key
     /// and output a zkLogin signature with a fixed dev-only proof with fixed max epoch 10.
     ZkLoginInsecureSignPersonalMessage {
@@ -286,6 +382,28 @@ pub struct AliasUpdate {
     new_alias: String,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyShareOutput {
+    index: u8,
+    threshold: u8,
+    mgo_address: MgoAddress,
+    public_base64_key: String,
+    share: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeystoreStatus {
+    keystore_path: PathBuf,
+    encrypted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unlocked_until: Option<String>,
+    /// The command that hands the unlock session to later commands in the current shell.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session: Option<String>,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DecodedMultiSig {
@@ -418,6 +536,15 @@ pub struct ZkLoginSigVerifyResponse {
     res: Option<MgoResult>,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeySigVerifyResponse {
+    mgo_address: MgoAddress,
+    data: Option<String>,
+    parsed: String,
+    res: Option<MgoResult>,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ZkLoginInsecureSignPersonalMessage {
@@ -429,22 +556,29 @@ pub struct ZkLoginInsecureSignPersonalMessage {
 #[serde(untagged)]
 pub enum CommandOutput {
     Alias(AliasUpdate),
+    ChangePassword(KeystoreStatus),
+    CombineKeyShares(Key),
     Convert(ConvertOutput),
     DecodeMultiSig(DecodedMultiSigOutput),
     DecodeTxBytes(TransactionData),
+    EncryptKeystore(KeystoreStatus),
     Error(String),
     Generate(Key),
     Import(Key),
     Export(ExportedKey),
     List(Vec<Key>),
     LoadKeypair(KeypairData),
+    Lock(KeystoreStatus),
     MultiSigAddress(MultiSigAddress),
     MultiSigCombinePartialSig(MultiSigCombinePartialSig),
     MultiSigCombinePartialSigLegacy(MultiSigCombinePartialSigLegacyOutput),
+    PasskeySigVerify(PasskeySigVerifyResponse),
     PrivateKeyBase64(PrivateKeyBase64),
     Show(Key),
     Sign(SignData),
     SignKMS(SerializedSig),
+    SplitKey(Vec<KeyShareOutput>),
+    Unlock(KeystoreStatus),
     ZkLoginSignAndExecuteTx(ZkLoginSignAndExecuteTx),
     ZkLoginInsecureSignPersonalMessage(ZkLoginInsecureSignPersonalMessage),
     ZkLoginSigVerify(ZkLoginSigVerifyResponse),
@@ -463,6 +597,27 @@ impl KeyToolCommand {
                     new_alias,
                 })
             }
+            KeyToolCommand::ChangePassword => {
+                let keystore = encrypted_keystore(keystore)?;
+                let old_password = read_password(&format!(
+                    "Enter the current password of keystore {}: ",
+                    keystore.path().display()
+                ))?;
+                let new_password = read_new_password(MGO_KEYSTORE_NEW_PASSWORD)?;
+                keystore.change_password(&old_password, &new_password)?;
+                CommandOutput::ChangePassword(KeystoreStatus {
+                    keystore_path: keystore.path().to_path_buf(),
+                    encrypted: true,
+                    unlocked_until: None,
+                    session: None,
+                })
+            }
+            KeyToolCommand::CombineKeyShares { alias, shares } => {
+                let skp = combine_shares(&shares)?;
+                let key = Key::from(&skp);
+                keystore.add_key(alias, skp)?;
+                CommandOutput::CombineKeyShares(key)
+            }
             KeyToolCommand::Convert { value } => {
                 let result = convert_private_key_to_bech32(value)?;
                 CommandOutput::Convert(result)
@@ -528,6 +683,25 @@ impl KeyToolCommand {
                 CommandOutput::DecodeTxBytes(tx_data)
             }
 
+            KeyToolCommand::EncryptKeystore => {
+                let Keystore::File(file) = keystore else {
+                    return Err(anyhow!("Only a file-based keystore can be encrypted"));
+                };
+                let keystore_path = file
+                    .path()
+                    .ok_or_else(|| anyhow!("The keystore is not saved to a file"))?
+                    .to_path_buf();
+                let password = read_new_password(MGO_KEYSTORE_PASSWORD)?;
+                let encrypted = std::mem::take(file).encrypt(&password, KdfParams::default())?;
+                *keystore = Keystore::Encrypted(encrypted);
+                CommandOutput::EncryptKeystore(KeystoreStatus {
+                    keystore_path,
+                    encrypted: true,
+                    unlocked_until: None,
+                    session: None,
+                })
+            }
+
             KeyToolCommand::Generate {
                 key_scheme,
                 derivation_path,
@@ -658,6 +832,17 @@ impl KeyToolCommand {
                 CommandOutput::LoadKeypair(output)
             }
 
+            KeyToolCommand::Lock => {
+                let keystore = encrypted_keystore(keystore)?;
+                keystore.lock()?;
+                CommandOutput::Lock(KeystoreStatus {
+                    keystore_path: keystore.path().to_path_buf(),
+                    encrypted: true,
+                    unlocked_until: None,
+                    session: None,
+                })
+            }
+
             KeyToolCommand::MultiSigAddress {
                 threshold,
                 pks,
@@ -721,6 +906,37 @@ impl KeyToolCommand {
                 )
             }
 
+            KeyToolCommand::MultiSigPolicyAddress { policy } => {
+                let multisig_pk = read_multisig_policy(&policy)?.multisig_pk()?;
+                let address: MgoAddress = (&multisig_pk).into();
+                let mut output = MultiSigAddress {
+                    multisig_address: address.to_string(),
+                    multisig: vec![],
+                };
+
+                for (pk, w) in multisig_pk.pubkeys() {
+                    output.multisig.push(MultiSigOutput {
+                        address: pk.into(),
+                        public_base64_key: pk.encode_base64(),
+                        weight: *w,
+                    });
+                }
+                CommandOutput::MultiSigAddress(output)
+            }
+
+            KeyToolCommand::MultiSigCombinePolicySig { policy, sigs } => {
+                let policy = read_multisig_policy(&policy)?;
+                let address = policy.address()?;
+                let multisig = policy.combine(&sigs)?;
+                let generic_sig: GenericSignature = multisig.into();
+                let multisig_serialized = generic_sig.encode_base64();
+                CommandOutput::MultiSigCombinePartialSig(MultiSigCombinePartialSig {
+                    multisig_address: address,
+                    multisig_parsed: generic_sig,
+                    multisig_serialized,
+                })
+            }
+
             KeyToolCommand::Show { file } => {
                 let res = read_keypair_from_file(&file);
                 match res {
@@ -840,6 +1056,40 @@ impl KeyToolCommand {
                 })
             }
 
+            KeyToolCommand::SplitKey {
+                key_identity,
+                mnemonic_phrase,
+                key_scheme,
+                derivation_path,
+                threshold,
+                shares,
+            } => {
+                let key_shares = match (key_identity, mnemonic_phrase, key_scheme) {
+                    (_, Some(phrase), Some(key_scheme)) => {
+                        split_mnemonic(&phrase, key_scheme, derivation_path, threshold, shares)?
+                    }
+                    (Some(key_identity), None, _) => {
+                        let address = get_identity_address_from_keystore(key_identity, keystore)?;
+                        split_private_key(keystore.get_key(&address)?, threshold, shares)?
+                    }
+                    _ => {
+                        return Err(anyhow!(
+                            "Provide either a key identity, or a mnemonic phrase and its key scheme"
+                        ))
+                    }
+                };
+                let output = key_shares
+                    .iter()
+                    .map(|share| KeyShareOutput {
+                        index: share.index,
+                        threshold: share.threshold,
+                        mgo_address: MgoAddress::from(&share.public_key),
+                        public_base64_key: share.public_key.encode_base64(),
+                        share: share.encode(),
+                    })
+                    .collect();
+                CommandOutput::SplitKey(output)
+            }
             KeyToolCommand::Unpack { keypair } => {
                 let keypair = MgoKeyPair::decode_base64(&keypair)
                     .map_err(|_| anyhow!("Invalid Base64 encode keypair"))?;
@@ -856,6 +1106,24 @@ impl KeyToolCommand {
                 fs::write(path, out_str).unwrap();
                 CommandOutput::Show(key)
             }
+            KeyToolCommand::Unlock { timeout } => {
+                let keystore = encrypted_keystore(keystore)?;
+                let password = read_password(&format!(
+                    "Enter the password of keystore {}: ",
+                    keystore.path().display()
+                ))?;
+                let session = keystore.unlock(&password, Some(timeout))?;
+                CommandOutput::Unlock(KeystoreStatus {
+                    keystore_path: keystore.path().to_path_buf(),
+                    encrypted: true,
+                    unlocked_until: session.as_ref().map(|session| {
+                        humantime::format_rfc3339_seconds(session.expires_at).to_string()
+                    }),
+                    session: session.map(|session| {
+                        format!("export {MGO_KEYSTORE_SESSION}={}", session.token.as_str())
+                    }),
+                })
+            }
 
             KeyToolCommand::ZkLoginInsecureSignPersonalMessage { data } => {
                 let msg = PersonalMessage {
@@ -1098,6 +1366,45 @@ impl KeyToolCommand {
                     _ => CommandOutput::Error("Not a zkLogin signature".to_string()),
                 }
             }
+
+            KeyToolCommand::PasskeySigVerify {
+                sig,
+                bytes,
+                intent_scope,
+            } => {
+                match GenericSignature::from_bytes(
+                    &Base64::decode(&sig).map_err(|e| anyhow!("Invalid base64 sig: {:?}", e))?,
+                )? {
+                    GenericSignature::PasskeyAuthenticator(passkey) => {
+                        let mgo_address = MgoAddress::from(&passkey.get_pk()?);
+                        let parsed = serde_json::to_string(&passkey)?;
+                        let Some(bytes) = bytes else {
+                            return Ok(CommandOutput::PasskeySigVerify(PasskeySigVerifyResponse {
+                                mgo_address,
+                                data: None,
+                                parsed,
+                                res: None,
+                            }));
+                        };
+                        let bytes = Base64::decode(&bytes)
+                            .map_err(|e| anyhow!("Invalid base64 data: {:?}", e))?;
+                        let (serialized, res) = verify_passkey_sig(
+                            &passkey,
+                            mgo_address,
+                            &bytes,
+                            IntentScope::try_from(intent_scope)
+                                .map_err(|_| anyhow!("Invalid scope"))?,
+                        )?;
+                        CommandOutput::PasskeySigVerify(PasskeySigVerifyResponse {
+                            mgo_address,
+                            data: Some(serialized),
+                            parsed,
+                            res: Some(res),
+                        })
+                    }
+                    _ => CommandOutput::Error("Not a passkey signature".to_string()),
+                }
+            }
         });
 
         cmd_result
@@ -1204,6 +1511,70 @@ impl Debug for CommandOutput {
     }
 }
 
+/// Parses a Base64 encoded public key `flag || pk`, or a key share, which stands for the public key
+/// of the key it is a share of.
+fn parse_public_key_or_share(value: &str) -> Result<PublicKey, anyhow::Error> {
+    match PublicKey::decode_base64(value) {
+        Ok(pk) => Ok(pk),
+        Err(e) => KeyShare::decode(value)
+            .map(|share| share.public_key)
+            .map_err(|_| anyhow!("Invalid public key or key share: {e}")),
+    }
+}
+
+/// The encrypted keystore that the lock and password commands operate on.
+fn encrypted_keystore(
+    keystore: &mut Keystore,
+) -> Result<&mut EncryptedFileKeystore, anyhow::Error> {
+    match keystore {
+        Keystore::Encrypted(keystore) => Ok(keystore),
+        _ => Err(anyhow!(
+            "The keystore is not encrypted. Encrypt it with `mgo keytool encrypt-keystore`"
+        )),
+    }
+}
+
+/// Verifies a passkey signature by `mgo_address` over the BCS encoded TransactionData or
+/// PersonalMessage `bytes`. Returns the parsed data as JSON, and the verification result.
+fn verify_passkey_sig(
+    passkey: &PasskeyAuthenticator,
+    mgo_address: MgoAddress,
+    bytes: &[u8],
+    intent_scope: IntentScope,
+) -> Result<(String, MgoResult), anyhow::Error> {
+    let verify_params = VerifyParams::default();
+    Ok(match intent_scope {
+        IntentScope::TransactionData => {
+            let tx_data: TransactionData = bcs::from_bytes(bytes)?;
+            let res = passkey.verify_authenticator(
+                &IntentMessage::new(Intent::mgo_transaction(), tx_data.clone()),
+                mgo_address,
+                None,
+                &verify_params,
+            );
+            (serde_json::to_string(&tx_data)?, res)
+        }
+        IntentScope::PersonalMessage => {
+            let data: PersonalMessage = bcs::from_bytes(bytes)?;
+            let res = passkey.verify_authenticator(
+                &IntentMessage::new(Intent::personal_message(), data.clone()),
+                mgo_address,
+                None,
+                &verify_params,
+            );
+            (serde_json::to_string(&data)?, res)
+        }
+        _ => return Err(anyhow!("Invalid intent scope")),
+    })
+}
+
+/// Reads a [struct MultiSigPolicy] from a YAML or JSON file.
+fn read_multisig_policy(path: &Path) -> Result<MultiSigPolicy, anyhow::Error> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Cannot read policy file {}: {e}", path.display()))?;
+    serde_yaml::from_str(&contents).map_err(|e| anyhow!("Invalid policy file: {e}"))
+}
+
 /// Converts legacy formatted private key to 33 bytes bech32 encoded private key or vice versa.
 /// It can handle:
 /// 1) Hex encoded 32 byte private key (assumes scheme is Ed25519), this is the legacy wallet format
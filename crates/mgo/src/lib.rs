@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod client_commands;
+pub mod client_ptb;
 pub mod console;
 pub mod fire_drill;
 pub mod genesis_ceremony;
@@ -11,5 +12,6 @@ pub mod key_identity;
 pub mod keytool;
 pub mod shell;
 pub mod mgo_commands;
+pub mod multisig_session;
 pub mod validator_commands;
 pub mod zklogin_commands_util;
@@ -266,7 +266,7 @@ impl MgoCommand {
             } => {
                 let keystore_path =
                     keystore_path.unwrap_or(mgo_config_dir()?.join(MGO_KEYSTORE_FILENAME));
-                let mut keystore = Keystore::from(FileBasedKeystore::new(&keystore_path)?);
+                let mut keystore = Keystore::load(&keystore_path)?;
                 cmd.execute(&mut keystore).await?.print(!json);
                 Ok(())
             }
@@ -361,7 +361,7 @@ async fn genesis(
     if write_config.is_none() && !files.is_empty() {
         if force {
             // check old keystore and client.yaml is compatible
-            let is_compatible = FileBasedKeystore::new(&keystore_path).is_ok()
+            let is_compatible = Keystore::load(&keystore_path).is_ok()
                 && PersistedConfig::<MgoClientConfig>::read(&client_path).is_ok();
             // Keep keystore and client.yaml if they are compatible
             if is_compatible {
@@ -411,7 +411,7 @@ async fn genesis(
                 // Make a new genesis config from the provided ip addresses.
                 GenesisConfig::new_for_benchmarks(&ips)
             } else if keystore_path.exists() {
-                let existing_keys = FileBasedKeystore::new(&keystore_path)?.addresses();
+                let existing_keys = Keystore::load(&keystore_path)?.addresses();
                 GenesisConfig::for_local_testing_with_addresses(existing_keys)
             } else {
                 GenesisConfig::for_local_testing()
@@ -450,7 +450,7 @@ async fn genesis(
             .build()
     };
 
-    let mut keystore = FileBasedKeystore::new(&keystore_path)?;
+    let mut keystore = Keystore::load(&keystore_path)?;
     for key in &network_config.account_keys {
         keystore.add_key(None, MgoKeyPair::Ed25519(key.copy()))?;
     }
@@ -536,7 +536,7 @@ async fn genesis(
     let mut client_config = if client_path.exists() {
         PersistedConfig::read(&client_path)?
     } else {
-        MgoClientConfig::new(keystore.into())
+        MgoClientConfig::new(keystore)
     };
 
     if client_config.active_address.is_none() {
@@ -618,7 +618,7 @@ async fn prompt_if_no_config(
                 .parent()
                 .unwrap_or(&mgo_config_dir()?)
                 .join(MGO_KEYSTORE_FILENAME);
-            let mut keystore = Keystore::from(FileBasedKeystore::new(&keystore_path)?);
+            let mut keystore = Keystore::load(&keystore_path)?;
             let key_scheme = if accept_defaults {
                 SignatureScheme::ED25519
             } else {
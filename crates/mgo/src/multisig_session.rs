@@ -0,0 +1,587 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+//! File based signing sessions for MultiSig transactions.
+//!
+//! A session file holds an unsigned transaction sent from a MultiSig address, the
+//! [struct MultiSigPublicKey] of that address, and a summary of what the transaction does
+//! according to a dry run. Members add their signatures to the file one at a time, without
+//! needing a connection to the network, and the session is executed once the weight of the
+//! signatures reaches the threshold.
+//!
+//! Signatures only cover the transaction itself, so the dry run summary could have been edited
+//! by anyone who handled the file. Sessions are therefore always shown with the details decoded
+//! from the transaction, and the summary is labelled as unverified.
+
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, ensure};
+use clap::*;
+use fastcrypto::encoding::{Base64, Encoding};
+use mgo_json_rpc_types::{
+    BalanceChange, DryRunTransactionBlockResponse, MgoExecutionStatus,
+    MgoTransactionBlockEffectsAPI, MgoTransactionBlockResponse, ObjectChange,
+};
+use mgo_keys::keystore::AccountKeystore;
+use mgo_sdk::wallet_context::WalletContext;
+use mgo_types::base_types::{MgoAddress, ObjectID};
+use mgo_types::crypto::{EncodeDecodeBase64, MgoSignature, PublicKey};
+use mgo_types::digests::TransactionDigest;
+use mgo_types::gas::GasCostSummary;
+use mgo_types::multisig::{MultiSig, MultiSigPublicKey, ThresholdUnit, WeightUnit};
+use mgo_types::signature::GenericSignature;
+use mgo_types::transaction::{
+    Transaction, TransactionData, TransactionDataAPI, TransactionExpiration, TransactionKind,
+};
+use serde::{Deserialize, Serialize};
+use shared_crypto::intent::{Intent, IntentMessage};
+use tabled::{builder::Builder as TableBuilder, settings::Style as TableStyle};
+
+use crate::key_identity::{get_identity_address, KeyIdentity};
+
+#[cfg(test)]
+#[path = "unit_tests/multisig_session_tests.rs"]
+mod multisig_session_tests;
+
+/// A transaction waiting for the signatures of the members of the MultiSig that sends it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiSigSession {
+    /// Digest of the transaction, so that members can check what they sign out of band.
+    pub digest: TransactionDigest,
+    /// BCS serialized `TransactionData`, as Base64.
+    pub tx_bytes: String,
+    pub multisig_pk: MultiSigPublicKey,
+    pub summary: TransactionSummary,
+    pub signatures: Vec<SessionSignature>,
+}
+
+/// What the transaction does, according to the dry run it was checked with. The summary is not
+/// covered by the members' signatures.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionSummary {
+    pub sender: MgoAddress,
+    pub gas_budget: u64,
+    pub gas_cost: GasCostSummary,
+    pub balance_changes: Vec<BalanceChange>,
+    pub object_changes: Vec<ObjectChange>,
+}
+
+/// The signature of one member of the MultiSig.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSignature {
+    pub signer: MgoAddress,
+    /// `flag || signature || pubkey`, as Base64.
+    pub signature: String,
+}
+
+#[derive(Subcommand)]
+#[clap(rename_all = "kebab-case")]
+pub enum MultiSigSessionCommand {
+    /// Start a session for a transaction sent from a MultiSig address. The transaction is dry run,
+    /// and a summary of its effects is saved along with it, so that members can review what they
+    /// are signing.
+    Create {
+        /// BCS serialized transaction data bytes without its type tag, as base64 encoded string.
+        /// This is the output of mgo client commands using --serialize-unsigned-transaction.
+        #[clap(long)]
+        tx_bytes: String,
+        /// The public keys of the members of the MultiSig, `flag || pk` encoded in Base64.
+        #[clap(long, num_args(1..))]
+        pks: Vec<PublicKey>,
+        /// The weight of each member, in the same order as `pks`.
+        #[clap(long, num_args(1..))]
+        weights: Vec<WeightUnit>,
+        #[clap(long)]
+        threshold: ThresholdUnit,
+        /// Where to write the session file.
+        #[clap(long)]
+        session: PathBuf,
+    },
+    /// Add a member's signature to a session. This does not need access to the network. The
+    /// signature is checked against the transaction and the members of the MultiSig before the
+    /// session file is updated.
+    Sign {
+        #[clap(long)]
+        session: PathBuf,
+        /// Address (or its alias) of the key in the keystore to sign with. Defaults to the active
+        /// address.
+        #[clap(long, conflicts_with = "signature")]
+        #[arg(value_parser)]
+        address: Option<KeyIdentity>,
+        /// A signature produced elsewhere, e.g. by `mgo keytool sign`, as Base64 encoded
+        /// `flag || signature || pubkey`.
+        #[clap(long)]
+        signature: Option<GenericSignature>,
+    },
+    /// Show the transaction of a session, and who has signed it so far.
+    Status {
+        #[clap(long)]
+        session: PathBuf,
+    },
+    /// Combine the signatures of a session into a MultiSig signature, and execute the
+    /// transaction. Fails if the weight of the signatures has not reached the threshold.
+    Execute {
+        #[clap(long)]
+        session: PathBuf,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum MultiSigSessionCommandResult {
+    Status(MultiSigSessionStatus),
+    Execute(MgoTransactionBlockResponse),
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiSigSessionStatus {
+    pub session: PathBuf,
+    pub digest: TransactionDigest,
+    pub multisig_address: MgoAddress,
+    /// The transaction that members sign, decoded from the session.
+    pub transaction: TransactionDetails,
+    /// The dry run summary stored in the session, which the signatures do not cover.
+    pub unverified_summary: TransactionSummary,
+    pub members: Vec<MemberStatus>,
+    pub weight: ThresholdUnit,
+    pub threshold: ThresholdUnit,
+}
+
+/// The parts of a transaction that members review before signing it.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionDetails {
+    pub sender: MgoAddress,
+    pub gas_owner: MgoAddress,
+    pub gas_payment: Vec<ObjectID>,
+    pub gas_budget: u64,
+    pub gas_price: u64,
+    pub expiration: TransactionExpiration,
+    pub kind: TransactionKind,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberStatus {
+    pub address: MgoAddress,
+    pub public_key: String,
+    pub weight: WeightUnit,
+    pub signed: bool,
+}
+
+impl MultiSigSession {
+    /// Starts a session for `tx_data`, which must be sent from the address of `multisig_pk`.
+    pub fn new(
+        tx_data: &TransactionData,
+        multisig_pk: MultiSigPublicKey,
+        summary: TransactionSummary,
+    ) -> Result<Self, anyhow::Error> {
+        let multisig_address = MgoAddress::from(&multisig_pk);
+        ensure!(
+            tx_data.sender() == multisig_address,
+            "The transaction is sent from {}, not from the MultiSig address {multisig_address}",
+            tx_data.sender()
+        );
+        Ok(Self {
+            digest: tx_data.digest(),
+            tx_bytes: Base64::encode(bcs::to_bytes(tx_data)?),
+            multisig_pk,
+            summary,
+            signatures: vec![],
+        })
+    }
+
+    /// Reads a session from `path`, checking that it has not been tampered with since it was
+    /// written.
+    pub fn read(path: &Path) -> Result<Self, anyhow::Error> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Cannot read session file {}: {e}", path.display()))?;
+        let session: Self = serde_json::from_str(&contents)
+            .map_err(|e| anyhow!("Invalid session file {}: {e}", path.display()))?;
+        session.validate()?;
+        Ok(session)
+    }
+
+    /// Writes the session to `path`. The file is replaced in one step, so that an interrupted
+    /// write cannot lose the signatures collected so far.
+    pub fn write(&self, path: &Path) -> Result<(), anyhow::Error> {
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(self)?)
+            .map_err(|e| anyhow!("Cannot write session file {}: {e}", tmp_path.display()))?;
+        fs::rename(&tmp_path, path)
+            .map_err(|e| anyhow!("Cannot write session file {}: {e}", path.display()))?;
+        Ok(())
+    }
+
+    pub fn multisig_address(&self) -> MgoAddress {
+        MgoAddress::from(&self.multisig_pk)
+    }
+
+    pub fn tx_data(&self) -> Result<TransactionData, anyhow::Error> {
+        let bytes = Base64::decode(&self.tx_bytes)
+            .map_err(|_| anyhow!("Invalid Base64 encoding of the transaction"))?;
+        let tx_data: TransactionData = bcs::from_bytes(&bytes)
+            .map_err(|_| anyhow!("Failed to parse the transaction of the session"))?;
+        ensure!(
+            tx_data.digest() == self.digest,
+            "The transaction of the session does not match its digest {}",
+            self.digest
+        );
+        Ok(tx_data)
+    }
+
+    /// Checks the signature of a member over the transaction, and adds it to the session.
+    /// Returns the address of the member who signed.
+    pub fn add_signature(
+        &mut self,
+        signature: &GenericSignature,
+    ) -> Result<MgoAddress, anyhow::Error> {
+        let tx_data = self.tx_data()?;
+        let signer = self.verify_signature(&tx_data, signature)?;
+        ensure!(
+            !self.signatures.iter().any(|s| s.signer == signer),
+            "{signer} has already signed this transaction"
+        );
+        self.signatures.push(SessionSignature {
+            signer,
+            signature: signature.encode_base64(),
+        });
+        Ok(signer)
+    }
+
+    /// The total weight of the members who have signed so far.
+    pub fn weight(&self) -> ThresholdUnit {
+        self.multisig_pk
+            .pubkeys()
+            .iter()
+            .filter(|(pk, _)| {
+                let address = MgoAddress::from(pk);
+                self.signatures.iter().any(|s| s.signer == address)
+            })
+            .map(|(_, weight)| *weight as ThresholdUnit)
+            .sum()
+    }
+
+    pub fn threshold(&self) -> ThresholdUnit {
+        *self.multisig_pk.threshold()
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.weight() >= self.threshold()
+    }
+
+    /// Combines the signatures collected so far into a MultiSig signature.
+    pub fn combine(&self) -> Result<GenericSignature, anyhow::Error> {
+        ensure!(
+            self.is_ready(),
+            "The signatures have a weight of {}, which has not reached the threshold of {}",
+            self.weight(),
+            self.threshold()
+        );
+
+        // MultiSig::combine expects the signatures in the same order as the public keys.
+        let mut sigs = self
+            .signatures
+            .iter()
+            .map(|s| {
+                let sig = decode_signature(&s.signature)?;
+                let index = self
+                    .multisig_pk
+                    .get_index(&sig.to_public_key()?)
+                    .ok_or_else(|| anyhow!("{} is not a member of the MultiSig", s.signer))?;
+                Ok::<_, anyhow::Error>((index, sig))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        sigs.sort_by_key(|(index, _)| *index);
+
+        let multisig = MultiSig::combine(
+            sigs.into_iter().map(|(_, sig)| sig).collect(),
+            self.multisig_pk.clone(),
+        )?;
+        Ok(multisig.into())
+    }
+
+    pub fn status(&self, session: PathBuf) -> Result<MultiSigSessionStatus, anyhow::Error> {
+        let tx_data = self.tx_data()?;
+        let members = self
+            .multisig_pk
+            .pubkeys()
+            .iter()
+            .map(|(pk, weight)| {
+                let address = MgoAddress::from(pk);
+                MemberStatus {
+                    address,
+                    public_key: pk.encode_base64(),
+                    weight: *weight,
+                    signed: self.signatures.iter().any(|s| s.signer == address),
+                }
+            })
+            .collect();
+        Ok(MultiSigSessionStatus {
+            session,
+            digest: self.digest,
+            multisig_address: self.multisig_address(),
+            transaction: TransactionDetails {
+                sender: tx_data.sender(),
+                gas_owner: tx_data.gas_owner(),
+                gas_payment: tx_data.gas().iter().map(|(id, _, _)| *id).collect(),
+                gas_budget: tx_data.gas_budget(),
+                gas_price: tx_data.gas_price(),
+                expiration: tx_data.expiration().clone(),
+                kind: tx_data.into_kind(),
+            },
+            unverified_summary: self.summary.clone(),
+            members,
+            weight: self.weight(),
+            threshold: self.threshold(),
+        })
+    }
+
+    /// Checks everything that `new` and `add_signature` check, for a session read from a file.
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        self.multisig_pk
+            .validate()
+            .map_err(|_| anyhow!("Invalid MultiSig public key in the session"))?;
+        let tx_data = self.tx_data()?;
+        ensure!(
+            tx_data.sender() == self.multisig_address(),
+            "The transaction is sent from {}, not from the MultiSig address {}",
+            tx_data.sender(),
+            self.multisig_address()
+        );
+        ensure!(
+            self.summary.sender == tx_data.sender()
+                && self.summary.gas_budget == tx_data.gas_budget(),
+            "The summary of the session does not match its transaction"
+        );
+        for (i, s) in self.signatures.iter().enumerate() {
+            let signer = self.verify_signature(&tx_data, &decode_signature(&s.signature)?)?;
+            ensure!(
+                signer == s.signer,
+                "The signature recorded for {} was made by {signer}",
+                s.signer
+            );
+            ensure!(
+                !self.signatures[..i]
+                    .iter()
+                    .any(|other| other.signer == signer),
+                "{signer} has signed the session more than once"
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks that `signature` is a valid signature over `tx_data` by a member of the MultiSig,
+    /// and returns the member's address.
+    fn verify_signature(
+        &self,
+        tx_data: &TransactionData,
+        signature: &GenericSignature,
+    ) -> Result<MgoAddress, anyhow::Error> {
+        let GenericSignature::Signature(sig) = signature else {
+            bail!("Only Ed25519, Secp256k1 and Secp256r1 signatures can be added to a session");
+        };
+        let pk = signature.to_public_key()?;
+        let signer = MgoAddress::from(&pk);
+        ensure!(
+            self.multisig_pk.get_index(&pk).is_some(),
+            "{signer} is not a member of the MultiSig {}",
+            self.multisig_address()
+        );
+        sig.verify_secure(
+            &IntentMessage::new(Intent::mgo_transaction(), tx_data),
+            signer,
+            sig.scheme(),
+        )
+        .map_err(|e| {
+            anyhow!("The signature from {signer} is not valid for this transaction: {e}")
+        })?;
+        Ok(signer)
+    }
+}
+
+impl TransactionSummary {
+    /// Summarizes a successful dry run of `tx_data`.
+    pub fn new(
+        tx_data: &TransactionData,
+        response: DryRunTransactionBlockResponse,
+    ) -> Result<Self, anyhow::Error> {
+        if let MgoExecutionStatus::Failure { error } = response.effects.status() {
+            bail!("Dry run of the transaction failed: {error}");
+        }
+        Ok(Self {
+            sender: tx_data.sender(),
+            gas_budget: tx_data.gas_budget(),
+            gas_cost: response.effects.gas_cost_summary().clone(),
+            balance_changes: response.balance_changes,
+            object_changes: response.object_changes,
+        })
+    }
+}
+
+impl MultiSigSessionCommand {
+    pub async fn execute(
+        self,
+        context: &mut WalletContext,
+    ) -> Result<MultiSigSessionCommandResult, anyhow::Error> {
+        Ok(match self {
+            MultiSigSessionCommand::Create {
+                tx_bytes,
+                pks,
+                weights,
+                threshold,
+                session,
+            } => {
+                ensure!(
+                    !session.exists(),
+                    "Session file {} already exists",
+                    session.display()
+                );
+                let tx_data: TransactionData = bcs::from_bytes(
+                    &Base64::decode(&tx_bytes).map_err(|_| anyhow!("Invalid Base64 encoding"))?,
+                )
+                .map_err(|_| anyhow!("Failed to parse tx bytes, check if it matches the output of mgo client commands with --serialize-unsigned-transaction"))?;
+                let multisig_pk = MultiSigPublicKey::new(pks, weights, threshold)?;
+
+                let client = context.get_client().await?;
+                let response = client
+                    .read_api()
+                    .dry_run_transaction_block(tx_data.clone())
+                    .await?;
+                let summary = TransactionSummary::new(&tx_data, response)?;
+
+                let multisig_session = MultiSigSession::new(&tx_data, multisig_pk, summary)?;
+                multisig_session.write(&session)?;
+                MultiSigSessionCommandResult::Status(multisig_session.status(session)?)
+            }
+            MultiSigSessionCommand::Sign {
+                session,
+                address,
+                signature,
+            } => {
+                let mut multisig_session = MultiSigSession::read(&session)?;
+                let signature = match signature {
+                    Some(signature) => signature,
+                    None => {
+                        let address = get_identity_address(address, context)?;
+                        let tx_data = multisig_session.tx_data()?;
+                        context
+                            .config
+                            .keystore
+                            .sign_secure(&address, &tx_data, Intent::mgo_transaction())?
+                            .into()
+                    }
+                };
+                multisig_session.add_signature(&signature)?;
+                multisig_session.write(&session)?;
+                MultiSigSessionCommandResult::Status(multisig_session.status(session)?)
+            }
+            MultiSigSessionCommand::Status { session } => {
+                let multisig_session = MultiSigSession::read(&session)?;
+                MultiSigSessionCommandResult::Status(multisig_session.status(session)?)
+            }
+            MultiSigSessionCommand::Execute { session } => {
+                let multisig_session = MultiSigSession::read(&session)?;
+                let multisig = multisig_session.combine()?;
+                let transaction =
+                    Transaction::from_generic_sig_data(multisig_session.tx_data()?, vec![multisig]);
+                let response = context.execute_transaction_may_fail(transaction).await?;
+                MultiSigSessionCommandResult::Execute(response)
+            }
+        })
+    }
+}
+
+fn decode_signature(signature: &str) -> Result<GenericSignature, anyhow::Error> {
+    signature
+        .parse()
+        .map_err(|_| anyhow!("Invalid signature in the session: {signature}"))
+}
+
+impl Display for MultiSigSessionCommandResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MultiSigSessionCommandResult::Status(status) => write!(f, "{status}"),
+            MultiSigSessionCommandResult::Execute(response) => write!(f, "{response}"),
+        }
+    }
+}
+
+impl Display for MultiSigSessionStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let transaction = &self.transaction;
+        writeln!(f, "Session: {}", self.session.display())?;
+        writeln!(f, "Transaction Digest: {}", self.digest)?;
+        writeln!(f, "MultiSig Address: {}", self.multisig_address)?;
+        writeln!(f, "Gas Owner: {}", transaction.gas_owner)?;
+        let gas_payment: Vec<_> = transaction
+            .gas_payment
+            .iter()
+            .map(ObjectID::to_string)
+            .collect();
+        writeln!(f, "Gas Payment: {}", gas_payment.join(", "))?;
+        writeln!(f, "Gas Budget: {}", transaction.gas_budget)?;
+        writeln!(f, "Gas Price: {}", transaction.gas_price)?;
+        writeln!(f, "Expiration: {:?}", transaction.expiration)?;
+        write!(f, "{}", transaction.kind)?;
+
+        let summary = &self.unverified_summary;
+        writeln!(
+            f,
+            "Dry Run Summary (unverified: recorded when the session was created, and not covered \
+             by the signatures)"
+        )?;
+        writeln!(
+            f,
+            "Dry Run Gas Cost: {} (computation {}, storage {}, rebate {})",
+            summary.gas_cost.net_gas_usage(),
+            summary.gas_cost.computation_cost,
+            summary.gas_cost.storage_cost,
+            summary.gas_cost.storage_rebate,
+        )?;
+        writeln!(f, "Balance Changes:")?;
+        for change in &summary.balance_changes {
+            writeln!(f, "{change}")?;
+        }
+        writeln!(f, "Object Changes:")?;
+        for change in &summary.object_changes {
+            writeln!(f, "{change}")?;
+        }
+
+        let mut builder = TableBuilder::default();
+        builder.set_header(vec!["address", "publicBase64Key", "weight", "signed"]);
+        for member in &self.members {
+            builder.push_record([
+                member.address.to_string(),
+                member.public_key.clone(),
+                member.weight.to_string(),
+                if member.signed { "*" } else { "" }.to_string(),
+            ]);
+        }
+        let mut table = builder.build();
+        table.with(TableStyle::rounded());
+        writeln!(f, "{table}")?;
+
+        if self.weight >= self.threshold {
+            write!(
+                f,
+                "Signed with weight {} of threshold {}, ready to execute",
+                self.weight, self.threshold
+            )
+        } else {
+            write!(
+                f,
+                "Signed with weight {} of threshold {}, waiting for {} more",
+                self.weight,
+                self.threshold,
+                self.threshold - self.weight
+            )
+        }
+    }
+}
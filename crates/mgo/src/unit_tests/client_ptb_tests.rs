@@ -0,0 +1,238 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use mgo_types::base_types::{MgoAddress, ObjectID};
+use mgo_types::parse_mgo_type_tag;
+use move_core_types::language_storage::TypeTag;
+use move_core_types::u256::U256;
+
+use super::ast::{CommandKind, Value};
+use super::parse;
+
+fn values(value: &Value) -> Vec<Value> {
+    let Value::Vector(values) = value else {
+        panic!("Expected a vector, got {value:?}");
+    };
+    values.iter().map(|v| v.value.clone()).collect()
+}
+
+/// The text of `source` that the error from parsing it points at.
+fn error_at(source: &str) -> (String, String) {
+    let error = parse(source).unwrap_err();
+    (
+        source[error.span.start..error.span.end].to_string(),
+        error.message,
+    )
+}
+
+#[test]
+fn test_split_and_transfer() {
+    let program = parse(
+        "--split-coins gas [1000, 2_000] --assign coins \
+         --transfer-objects [coins.0, coins.1] @0x42 --gas-budget 5000000",
+    )
+    .unwrap();
+
+    assert_eq!(program.options.gas_budget, Some(5_000_000));
+    assert_eq!(program.commands.len(), 2);
+
+    let command = &program.commands[0].value;
+    assert_eq!(command.assign.as_ref().unwrap().value, "coins");
+    let CommandKind::SplitCoins { coin, amounts } = &command.kind else {
+        panic!("Expected split-coins, got {:?}", command.kind);
+    };
+    assert_eq!(coin.value, Value::Gas);
+    assert_eq!(
+        values(&amounts.value),
+        vec![
+            Value::Number(U256::from(1000u64)),
+            Value::Number(U256::from(2000u64))
+        ],
+    );
+
+    let command = &program.commands[1].value;
+    assert!(command.assign.is_none());
+    let CommandKind::TransferObjects { objects, recipient } = &command.kind else {
+        panic!("Expected transfer-objects, got {:?}", command.kind);
+    };
+    assert_eq!(
+        values(&objects.value),
+        vec![
+            Value::NestedResult("coins".to_string(), 0),
+            Value::NestedResult("coins".to_string(), 1)
+        ],
+    );
+    assert_eq!(
+        recipient.value,
+        Value::Address(MgoAddress::from(ObjectID::from_single_byte(0x42))),
+    );
+}
+
+#[test]
+fn test_move_call() {
+    let program = parse(
+        "--move-call 0x2::coin::zero<0x2::mgo::MGO> --assign zero \
+         --move-call 0x42::m::f<u64, vector<0x2::coin::Coin<0x2::mgo::MGO>>> \
+            zero \"hello \\\"world\\\"\" true some(@0x2) none [[1], []] 0xff \
+         --dev-inspect",
+    )
+    .unwrap();
+
+    assert!(program.options.dev_inspect);
+    assert_eq!(program.options.gas_budget, None);
+
+    let CommandKind::MoveCall {
+        function,
+        type_args,
+        args,
+    } = &program.commands[1].value.kind
+    else {
+        panic!("Expected move-call");
+    };
+
+    assert_eq!(function.value.package, ObjectID::from_single_byte(0x42));
+    assert_eq!(function.value.module.as_str(), "m");
+    assert_eq!(function.value.function.as_str(), "f");
+
+    let type_args: Vec<_> = type_args.iter().map(|t| t.value.clone()).collect();
+    assert_eq!(
+        type_args,
+        vec![
+            TypeTag::U64,
+            parse_mgo_type_tag("vector<0x2::coin::Coin<0x2::mgo::MGO>>").unwrap(),
+        ],
+    );
+
+    let args: Vec<_> = args.iter().map(|a| a.value.clone()).collect();
+    assert_eq!(args.len(), 7);
+    assert_eq!(args[0], Value::Result("zero".to_string()));
+    assert_eq!(args[1], Value::String("hello \"world\"".to_string()));
+    assert_eq!(args[2], Value::Bool(true));
+    let Value::Option(Some(address)) = &args[3] else {
+        panic!("Expected some(..), got {:?}", args[3]);
+    };
+    assert_eq!(
+        address.value,
+        Value::Address(MgoAddress::from(ObjectID::from_single_byte(2))),
+    );
+    assert_eq!(args[4], Value::Option(None));
+    assert_eq!(values(&args[5]).len(), 2);
+    assert_eq!(args[6], Value::Number(U256::from(255u64)));
+}
+
+#[test]
+fn test_publish_upgrade_and_make_move_vec() {
+    let program = parse(
+        "--publish ./my_package --assign cap \
+         --upgrade \"other package\" @0xcafe \
+         --make-move-vec <u8> [1, 2, 3] \
+         --gas-budget 100 --gas-coin @0x7 --serialize-unsigned-transaction",
+    )
+    .unwrap();
+
+    assert_eq!(program.commands.len(), 3);
+    assert_eq!(
+        program.options.gas_coin,
+        Some(ObjectID::from_single_byte(7))
+    );
+    assert!(program.options.serialize_unsigned_transaction);
+
+    let CommandKind::Publish { package_path } = &program.commands[0].value.kind else {
+        panic!("Expected publish");
+    };
+    assert_eq!(package_path.value.to_str(), Some("./my_package"));
+
+    let CommandKind::Upgrade {
+        package_path,
+        upgrade_capability,
+    } = &program.commands[1].value.kind
+    else {
+        panic!("Expected upgrade");
+    };
+    assert_eq!(package_path.value.to_str(), Some("other package"));
+    assert_eq!(
+        upgrade_capability.value,
+        ObjectID::from_hex_literal("0xcafe").unwrap(),
+    );
+
+    let CommandKind::MakeMoveVec { type_, elements } = &program.commands[2].value.kind else {
+        panic!("Expected make-move-vec");
+    };
+    assert_eq!(type_.value, TypeTag::U8);
+    assert_eq!(values(&elements.value).len(), 3);
+}
+
+#[test]
+fn test_error_spans() {
+    // Results can only be referred to once they have been named.
+    let (at, message) =
+        error_at("--transfer-objects [coins.0] @0x1 --split-coins gas [1] --assign coins");
+    assert_eq!(at, "coins");
+    assert_eq!(message, "Unknown name 'coins'");
+
+    let (at, message) = error_at("--split-coins gas [1] --frobnicate --gas-budget 1");
+    assert_eq!(at, "--frobnicate");
+    assert_eq!(message, "Unknown command '--frobnicate'");
+
+    let (at, _) = error_at("--split-coins gas [1, 2 --gas-budget 1");
+    assert_eq!(at, "--gas-budget");
+
+    let (at, _) = error_at("--move-call 0x2::coin --gas-budget 1");
+    assert_eq!(at, "0x2::coin");
+
+    let (at, _) = error_at("--move-call 0x2::coin::zero<0x2::mgo::> --gas-budget 1");
+    assert_eq!(at, "0x2::mgo::");
+
+    let (at, _) = error_at("--split-coins gas [1] --assign gas --gas-budget 1");
+    assert_eq!(at, "gas");
+
+    let (at, message) = error_at("--gas-budget 1 --assign coins");
+    assert_eq!(at, "--assign");
+    assert_eq!(message, "--assign must follow a command");
+
+    let (at, message) = error_at("--split-coins gas [1] --dry-run --dev-inspect");
+    assert_eq!(at, "--dev-inspect");
+    assert_eq!(
+        message,
+        "--dev-inspect cannot be used together with --dry-run"
+    );
+
+    let (at, message) = error_at("--split-coins gas [1]");
+    assert_eq!(at, "");
+    assert_eq!(message, "Missing gas budget");
+}
+
+#[test]
+fn test_expiration_options() {
+    let program = parse(
+        "--split-coins gas [1] --gas-budget 1 --valid-from-ms 1000 --valid-until-ms 2000 \
+         --bind-to-chain --expiration-nonce 7",
+    )
+    .unwrap();
+
+    let options = &program.options;
+    assert_eq!(options.valid_from_ms, Some(1_000));
+    assert_eq!(options.valid_until_ms, Some(2_000));
+    assert!(options.bind_to_chain);
+    assert_eq!(options.expiration_nonce, Some(7));
+
+    let (at, message) =
+        error_at("--split-coins gas [1] --gas-budget 1 --expiration-nonce 5000000000");
+    assert_eq!(at, "5000000000");
+    assert_eq!(message, "Expiration nonce does not fit in a u32");
+}
+
+#[test]
+fn test_render_error() {
+    let source = "--split-coins gas [1] --assign coins --transfer-objects [coin.0] @0x1";
+    colored::control::set_override(false);
+    let rendered = parse(source).unwrap_err().render(source);
+    assert_eq!(
+        rendered,
+        format!(
+            "Error: Unknown name 'coin'\n  {source}\n  {}^^^^\n\
+             Help: The results named so far are: coins",
+            " ".repeat(source.find("coin.0").unwrap()),
+        ),
+    );
+}
@@ -8,6 +8,7 @@ use crate::keytool::read_authority_keypair_from_file;
 use crate::keytool::read_keypair_from_file;
 use crate::keytool::CommandOutput;
 
+use super::parse_public_key_or_share;
 use super::write_keypair_to_file;
 use super::KeyToolCommand;
 use anyhow::Ok;
@@ -15,11 +16,15 @@ use fastcrypto::ed25519::Ed25519KeyPair;
 use fastcrypto::encoding::Base64;
 use fastcrypto::encoding::Encoding;
 use fastcrypto::encoding::Hex;
-use fastcrypto::traits::ToFromBytes;
+use fastcrypto::hash::{HashFunction, Sha256};
+use fastcrypto::secp256r1::{Secp256r1KeyPair, Secp256r1Signature};
+use fastcrypto::traits::{KeyPair, Signer, ToFromBytes};
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 use shared_crypto::intent::Intent;
+use shared_crypto::intent::IntentMessage;
 use shared_crypto::intent::IntentScope;
+use shared_crypto::intent::PersonalMessage;
 use mgo_keys::keystore::{AccountKeystore, FileBasedKeystore, InMemKeystore, Keystore};
 use mgo_types::base_types::ObjectDigest;
 use mgo_types::base_types::ObjectID;
@@ -36,6 +41,10 @@ use mgo_types::crypto::Signature;
 use mgo_types::crypto::SignatureScheme;
 use mgo_types::crypto::MgoKeyPair;
 use mgo_types::crypto::MgoSignatureInner;
+use mgo_types::crypto::PublicKey;
+use mgo_types::multisig::{MultiSigPublicKey, NestedMultiSigLimits};
+use mgo_types::passkey_authenticator::PasskeyAuthenticator;
+use mgo_types::signature::{AuthenticatorTrait, GenericSignature, VerifyParams};
 use mgo_types::transaction::TransactionData;
 use mgo_types::transaction::TEST_ONLY_GAS_UNIT_FOR_TRANSFER;
 use tempfile::TempDir;
@@ -565,3 +574,220 @@ async fn test_sign_command() -> Result<(), anyhow::Error> {
     .await?;
     Ok(())
 }
+
+#[test]
+async fn test_passkey_sig_verify() -> Result<(), anyhow::Error> {
+    let mut keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(0));
+    let kp: Secp256r1KeyPair = get_key_pair_from_rng(&mut StdRng::from_seed([0; 32])).1;
+    let personal_message = PersonalMessage {
+        message: b"hello passkey".to_vec(),
+    };
+    let intent_msg = IntentMessage::new(Intent::personal_message(), personal_message.clone());
+
+    // Sign the assertion like a WebAuthn authenticator for https://wallet.mangonet.io does.
+    let mut authenticator_data = Sha256::digest(b"mangonet.io").digest.to_vec();
+    authenticator_data.extend_from_slice(&[0x05, 0, 0, 0, 1]);
+    let client_data_json = format!(
+        r#"{{"type":"webauthn.get","challenge":"{}","origin":"https://wallet.mangonet.io"}}"#,
+        PasskeyAuthenticator::challenge(&intent_msg)
+    );
+    let mut message = authenticator_data.clone();
+    message.extend_from_slice(&Sha256::digest(client_data_json.as_bytes()).digest);
+    let sig: Secp256r1Signature = kp.sign(&message);
+    let user_signature = Signature::from_bytes(
+        &[
+            &[SignatureScheme::Secp256r1.flag()],
+            sig.as_ref(),
+            kp.public().as_ref(),
+        ]
+        .concat(),
+    )?;
+    let passkey = GenericSignature::PasskeyAuthenticator(PasskeyAuthenticator::new(
+        authenticator_data,
+        client_data_json,
+        user_signature,
+    )?);
+    let expected_address = MgoAddress::from(&PublicKey::Passkey(kp.public().into()));
+
+    let output = KeyToolCommand::PasskeySigVerify {
+        sig: Base64::encode(passkey.as_ref()),
+        bytes: Some(Base64::encode(bcs::to_bytes(&personal_message)?)),
+        intent_scope: IntentScope::PersonalMessage as u8,
+    }
+    .execute(&mut keystore)
+    .await?;
+    let CommandOutput::PasskeySigVerify(response) = output else {
+        panic!("unexpected output");
+    };
+    assert_eq!(response.mgo_address, expected_address);
+    assert_eq!(response.res, Some(Ok(())));
+
+    // The signature does not verify against other data.
+    let output = KeyToolCommand::PasskeySigVerify {
+        sig: Base64::encode(passkey.as_ref()),
+        bytes: Some(Base64::encode(bcs::to_bytes(&PersonalMessage {
+            message: b"other message".to_vec(),
+        })?)),
+        intent_scope: IntentScope::PersonalMessage as u8,
+    }
+    .execute(&mut keystore)
+    .await?;
+    let CommandOutput::PasskeySigVerify(response) = output else {
+        panic!("unexpected output");
+    };
+    assert!(matches!(response.res, Some(Err(_))));
+    Ok(())
+}
+
+#[test]
+async fn test_multisig_policy() -> Result<(), anyhow::Error> {
+    let mut keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(0));
+    let keys: Vec<MgoKeyPair> = (0..3)
+        .map(|_| MgoKeyPair::Ed25519(get_key_pair::<Ed25519KeyPair>().1))
+        .collect();
+    let team_pk = MultiSigPublicKey::new(vec![keys[0].public(), keys[1].public()], vec![1, 1], 2)?;
+    let treasury_pk = MultiSigPublicKey::new(
+        vec![PublicKey::MultiSig((&team_pk).into()), keys[2].public()],
+        vec![1, 1],
+        2,
+    )?;
+    let expected_address = MgoAddress::from(&treasury_pk);
+
+    let temp_dir = TempDir::new().unwrap();
+    let policy = temp_dir.path().join("policy.json");
+    std::fs::write(
+        &policy,
+        serde_json::json!({
+            "threshold": 2,
+            "members": [
+                {
+                    "weight": 1,
+                    "multisig": {
+                        "threshold": 2,
+                        "members": [
+                            { "weight": 1, "public_key": keys[0].public().encode_base64() },
+                            { "weight": 1, "public_key": keys[1].public().encode_base64() },
+                        ],
+                    },
+                },
+                { "weight": 1, "public_key": keys[2].public().encode_base64() },
+            ],
+        })
+        .to_string(),
+    )?;
+
+    let output = KeyToolCommand::MultiSigPolicyAddress {
+        policy: policy.clone(),
+    }
+    .execute(&mut keystore)
+    .await?;
+    let CommandOutput::MultiSigAddress(response) = output else {
+        panic!("unexpected output");
+    };
+    assert_eq!(response.multisig_address, expected_address.to_string());
+    assert_eq!(response.multisig[0].address, MgoAddress::from(&team_pk));
+
+    // All members sign, in any order.
+    let intent_msg = IntentMessage::new(
+        Intent::personal_message(),
+        PersonalMessage {
+            message: b"hello treasury".to_vec(),
+        },
+    );
+    let sigs = [2, 1, 0]
+        .map(|i| GenericSignature::from(Signature::new_secure(&intent_msg, &keys[i])))
+        .to_vec();
+    let output = KeyToolCommand::MultiSigCombinePolicySig { policy, sigs }
+        .execute(&mut keystore)
+        .await?;
+    let CommandOutput::MultiSigCombinePartialSig(response) = output else {
+        panic!("unexpected output");
+    };
+    assert_eq!(response.multisig_address, expected_address);
+    let verify_params = VerifyParams {
+        nested_multisig_limits: Some(NestedMultiSigLimits {
+            max_depth: 1,
+            max_signatures: 3,
+        }),
+        ..Default::default()
+    };
+    assert!(response
+        .multisig_parsed
+        .verify_authenticator(&intent_msg, expected_address, None, &verify_params)
+        .is_ok());
+    Ok(())
+}
+
+#[test]
+async fn test_split_and_combine_key_shares() -> Result<(), anyhow::Error> {
+    let mut keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(1));
+    let address = keystore.addresses()[0];
+    let public_key = keystore.get_key(&address)?.public();
+
+    let output = KeyToolCommand::SplitKey {
+        key_identity: Some(KeyIdentity::Address(address)),
+        mnemonic_phrase: None,
+        key_scheme: None,
+        derivation_path: None,
+        threshold: 2,
+        shares: 3,
+    }
+    .execute(&mut keystore)
+    .await?;
+    let CommandOutput::SplitKey(shares) = output else {
+        panic!("unexpected output");
+    };
+    assert_eq!(shares.len(), 3);
+    assert!(shares.iter().all(|share| share.mgo_address == address));
+
+    // Any two of the shares recombine into the key, in any order.
+    let mut restored = Keystore::from(InMemKeystore::new_insecure_for_tests(0));
+    let output = KeyToolCommand::CombineKeyShares {
+        alias: None,
+        shares: vec![shares[2].share.parse()?, shares[0].share.parse()?],
+    }
+    .execute(&mut restored)
+    .await?;
+    let CommandOutput::CombineKeyShares(key) = output else {
+        panic!("unexpected output");
+    };
+    assert_eq!(key.mgo_address, address);
+    assert_eq!(
+        restored.get_key(&address)?.encode_base64(),
+        keystore.get_key(&address)?.encode_base64()
+    );
+
+    // A share stands in for the public key of its key in a MultiSig.
+    assert_eq!(parse_public_key_or_share(&shares[1].share)?, public_key);
+    let other = MgoKeyPair::Ed25519(get_key_pair::<Ed25519KeyPair>().1).public();
+    let pks = vec![
+        parse_public_key_or_share(&shares[1].share)?,
+        parse_public_key_or_share(&other.encode_base64())?,
+    ];
+    let output = KeyToolCommand::MultiSigAddress {
+        threshold: 1,
+        pks,
+        weights: vec![1, 1],
+    }
+    .execute(&mut keystore)
+    .await?;
+    let CommandOutput::MultiSigAddress(response) = output else {
+        panic!("unexpected output");
+    };
+    let multisig_pk = MultiSigPublicKey::new(vec![public_key, other], vec![1, 1], 1)?;
+    assert_eq!(
+        response.multisig_address,
+        MgoAddress::from(&multisig_pk).to_string()
+    );
+
+    // Too few shares do not recombine.
+    let err = KeyToolCommand::CombineKeyShares {
+        alias: None,
+        shares: vec![shares[1].share.parse()?],
+    }
+    .execute(&mut restored)
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("2 key shares are needed"));
+    Ok(())
+}
@@ -0,0 +1,203 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use fastcrypto::ed25519::Ed25519KeyPair;
+use fastcrypto::encoding::{Base64, Encoding};
+use mgo_types::base_types::{random_object_ref, MgoAddress};
+use mgo_types::crypto::{get_key_pair, MgoKeyPair, Signature};
+use mgo_types::gas::GasCostSummary;
+use mgo_types::multisig::MultiSigPublicKey;
+use mgo_types::signature::{AuthenticatorTrait, GenericSignature, VerifyParams};
+use mgo_types::transaction::{TransactionData, TransactionDataAPI};
+use shared_crypto::intent::{Intent, IntentMessage};
+use tempfile::TempDir;
+
+use super::{MultiSigSession, TransactionSummary};
+
+struct Fixture {
+    keys: Vec<MgoKeyPair>,
+    multisig_pk: MultiSigPublicKey,
+    tx_data: TransactionData,
+}
+
+/// A MultiSig of three members with weights 1, 1 and 2 and a threshold of 3, and a transaction
+/// sent from it.
+fn fixture() -> Fixture {
+    let keys: Vec<MgoKeyPair> = (0..3)
+        .map(|_| MgoKeyPair::Ed25519(get_key_pair::<Ed25519KeyPair>().1))
+        .collect();
+    let multisig_pk =
+        MultiSigPublicKey::new(keys.iter().map(|k| k.public()).collect(), vec![1, 1, 2], 3)
+            .unwrap();
+    let tx_data = transfer_from(MgoAddress::from(&multisig_pk));
+    Fixture {
+        keys,
+        multisig_pk,
+        tx_data,
+    }
+}
+
+fn transfer_from(sender: MgoAddress) -> TransactionData {
+    TransactionData::new_transfer_mgo(
+        MgoAddress::random_for_testing_only(),
+        sender,
+        Some(1000),
+        random_object_ref(),
+        10_000_000,
+        1000,
+    )
+}
+
+fn summary(tx_data: &TransactionData) -> TransactionSummary {
+    TransactionSummary {
+        sender: tx_data.sender(),
+        gas_budget: tx_data.gas_budget(),
+        gas_cost: GasCostSummary::default(),
+        balance_changes: vec![],
+        object_changes: vec![],
+    }
+}
+
+fn sign(tx_data: &TransactionData, key: &MgoKeyPair) -> GenericSignature {
+    Signature::new_secure(&IntentMessage::new(Intent::mgo_transaction(), tx_data), key).into()
+}
+
+fn new_session(fixture: &Fixture) -> MultiSigSession {
+    MultiSigSession::new(
+        &fixture.tx_data,
+        fixture.multisig_pk.clone(),
+        summary(&fixture.tx_data),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_threshold_progress() {
+    let fixture = fixture();
+    let mut session = new_session(&fixture);
+    assert_eq!(session.weight(), 0);
+    assert_eq!(session.threshold(), 3);
+
+    // Members sign out of order, the signatures are sorted when they are combined.
+    let signer = session
+        .add_signature(&sign(&fixture.tx_data, &fixture.keys[2]))
+        .unwrap();
+    assert_eq!(signer, MgoAddress::from(&fixture.keys[2].public()));
+    assert_eq!(session.weight(), 2);
+    assert!(!session.is_ready());
+    assert!(session
+        .combine()
+        .unwrap_err()
+        .to_string()
+        .contains("has not reached the threshold of 3"));
+
+    session
+        .add_signature(&sign(&fixture.tx_data, &fixture.keys[0]))
+        .unwrap();
+    assert_eq!(session.weight(), 3);
+    assert!(session.is_ready());
+
+    let status = session.status("session.json".into()).unwrap();
+    assert_eq!(status.transaction.sender, session.multisig_address());
+    assert_eq!(status.transaction.gas_budget, fixture.tx_data.gas_budget());
+    assert_eq!(
+        status.transaction.gas_payment,
+        vec![fixture.tx_data.gas()[0].0]
+    );
+    let signed: Vec<_> = status.members.iter().map(|m| m.signed).collect();
+    assert_eq!(signed, vec![true, false, true]);
+
+    let multisig = session.combine().unwrap();
+    let intent_msg = IntentMessage::new(Intent::mgo_transaction(), fixture.tx_data.clone());
+    multisig
+        .verify_authenticator(
+            &intent_msg,
+            session.multisig_address(),
+            None,
+            &VerifyParams::default(),
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_rejected_signatures() {
+    let fixture = fixture();
+    let mut session = new_session(&fixture);
+    session
+        .add_signature(&sign(&fixture.tx_data, &fixture.keys[0]))
+        .unwrap();
+
+    let duplicate = session
+        .add_signature(&sign(&fixture.tx_data, &fixture.keys[0]))
+        .unwrap_err();
+    assert!(duplicate.to_string().contains("has already signed"));
+
+    let outsider = MgoKeyPair::Ed25519(get_key_pair::<Ed25519KeyPair>().1);
+    let not_member = session
+        .add_signature(&sign(&fixture.tx_data, &outsider))
+        .unwrap_err();
+    assert!(not_member
+        .to_string()
+        .contains("is not a member of the MultiSig"));
+
+    let other_tx = transfer_from(session.multisig_address());
+    let wrong_tx = session
+        .add_signature(&sign(&other_tx, &fixture.keys[1]))
+        .unwrap_err();
+    assert!(wrong_tx
+        .to_string()
+        .contains("is not valid for this transaction"));
+
+    // Nothing was added by the rejected signatures.
+    assert_eq!(session.signatures.len(), 1);
+    assert_eq!(session.weight(), 1);
+}
+
+#[test]
+fn test_sender_must_be_multisig() {
+    let fixture = fixture();
+    let tx_data = transfer_from(MgoAddress::random_for_testing_only());
+    let err = MultiSigSession::new(&tx_data, fixture.multisig_pk, summary(&tx_data)).unwrap_err();
+    assert!(err.to_string().contains("not from the MultiSig address"));
+}
+
+#[test]
+fn test_session_file() {
+    let fixture = fixture();
+    let mut session = new_session(&fixture);
+    session
+        .add_signature(&sign(&fixture.tx_data, &fixture.keys[1]))
+        .unwrap();
+
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("session.json");
+    session.write(&path).unwrap();
+    let read = MultiSigSession::read(&path).unwrap();
+    assert_eq!(read.digest, session.digest);
+    assert_eq!(read.signatures, session.signatures);
+    assert_eq!(read.weight(), 1);
+
+    // A signature recorded under another member's address.
+    let mut tampered = session.clone();
+    tampered.signatures[0].signer = MgoAddress::from(&fixture.keys[2].public());
+    tampered.write(&path).unwrap();
+    let err = MultiSigSession::read(&path).unwrap_err();
+    assert!(err.to_string().contains("was made by"));
+
+    // A transaction swapped out from under the signatures collected so far.
+    let mut tampered = session.clone();
+    tampered.tx_bytes =
+        Base64::encode(bcs::to_bytes(&transfer_from(session.multisig_address())).unwrap());
+    tampered.write(&path).unwrap();
+    let err = MultiSigSession::read(&path).unwrap_err();
+    assert!(err.to_string().contains("does not match its digest"));
+
+    // A dry run summary edited to hide what the transaction costs.
+    let mut tampered = session.clone();
+    tampered.summary.gas_budget = 1;
+    tampered.write(&path).unwrap();
+    let err = MultiSigSession::read(&path).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("summary of the session does not match its transaction"));
+}
@@ -8,10 +8,12 @@ use std::str::FromStr;
 use std::{fmt::Write, fs::read_dir, path::PathBuf, str, thread, time::Duration};
 
 use expect_test::expect;
+use fastcrypto::encoding::{Base64, Encoding};
 use move_package::BuildConfig as MoveBuildConfig;
 use serde_json::json;
 use mgo::key_identity::{get_identity_address, KeyIdentity};
-use mgo_test_transaction_builder::batch_make_transfer_transactions;
+use mgo_test_transaction_builder::{batch_make_transfer_transactions, TestTransactionBuilder};
+use mgo_types::multisig::MultiSigPublicKey;
 use mgo_types::object::Owner;
 use mgo_types::transaction::{
     TEST_ONLY_GAS_UNIT_FOR_GENERIC, TEST_ONLY_GAS_UNIT_FOR_OBJECT_BASICS,
@@ -24,6 +26,7 @@ use mgo::client_commands::SwitchResponse;
 use mgo::{
     client_commands::{MgoClientCommandResult, MgoClientCommands},
     mgo_commands::MgoCommand,
+    multisig_session::{MultiSigSessionCommand, MultiSigSessionCommandResult},
 };
 use mgo_config::{
     PersistedConfig, MGO_CLIENT_CONFIG, MGO_FULLNODE_CONFIG, MGO_GENESIS_FILENAME,
@@ -352,6 +355,7 @@ async fn test_gas_command() -> Result<(), anyhow::Error> {
         gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -403,6 +407,7 @@ async fn test_move_call_args_linter_command() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -473,6 +478,34 @@ async fn test_move_call_args_linter_command() -> Result<(), anyhow::Error> {
         MgoJsonValue::new(json!(address1))?,
     ];
 
+    // Dev-inspecting the call traces it, without executing it
+    let resp = MgoClientCommands::Call {
+        package,
+        module: "object_basics".to_string(),
+        function: "create".to_string(),
+        type_args: vec![],
+        args: args.clone(),
+        gas: None,
+        gas_budget: TEST_ONLY_GAS_UNIT_FOR_OBJECT_BASICS * rgp,
+        serialize_unsigned_transaction: false,
+        serialize_signed_transaction: false,
+        dev_inspect: true,
+        trace: true,
+        expiration: Default::default(),
+    }
+    .execute(context)
+    .await?;
+    resp.print(true);
+
+    let MgoClientCommandResult::DevInspect(results) = resp else {
+        panic!("Expected dev-inspect results, got {resp:?}");
+    };
+    assert_eq!(results.effects.created().len(), 1);
+    let trace = results.trace.unwrap();
+    let call = &trace.commands[0].calls[0];
+    assert_eq!(call.function, "create");
+    assert_eq!(call.arguments[0].json, Some(json!("123")));
+
     // Test case with no gas specified
     let resp = MgoClientCommands::Call {
         package,
@@ -484,6 +517,9 @@ async fn test_move_call_args_linter_command() -> Result<(), anyhow::Error> {
         gas_budget: TEST_ONLY_GAS_UNIT_FOR_OBJECT_BASICS * rgp,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        dev_inspect: false,
+        trace: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -523,6 +559,9 @@ async fn test_move_call_args_linter_command() -> Result<(), anyhow::Error> {
         gas_budget: TEST_ONLY_GAS_UNIT_FOR_OBJECT_BASICS * rgp,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        dev_inspect: false,
+        trace: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await;
@@ -549,6 +588,9 @@ async fn test_move_call_args_linter_command() -> Result<(), anyhow::Error> {
         gas_budget: TEST_ONLY_GAS_UNIT_FOR_OBJECT_BASICS * rgp,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        dev_inspect: false,
+        trace: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await;
@@ -577,6 +619,9 @@ async fn test_move_call_args_linter_command() -> Result<(), anyhow::Error> {
         gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_OBJECT_BASICS,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        dev_inspect: false,
+        trace: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -624,6 +669,7 @@ async fn test_package_publish_command() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -691,6 +737,7 @@ async fn test_delete_shared_object() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -724,6 +771,9 @@ async fn test_delete_shared_object() -> Result<(), anyhow::Error> {
         args: vec![],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        dev_inspect: false,
+        trace: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -746,6 +796,9 @@ async fn test_delete_shared_object() -> Result<(), anyhow::Error> {
         args: vec![MgoJsonValue::from_str(&shared_id.to_string()).unwrap()],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        dev_inspect: false,
+        trace: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -798,6 +851,7 @@ async fn test_receive_argument() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -831,6 +885,9 @@ async fn test_receive_argument() -> Result<(), anyhow::Error> {
         args: vec![],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        dev_inspect: false,
+        trace: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -872,6 +929,9 @@ async fn test_receive_argument() -> Result<(), anyhow::Error> {
         ],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        dev_inspect: false,
+        trace: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -924,6 +984,7 @@ async fn test_receive_argument_by_immut_ref() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -957,6 +1018,9 @@ async fn test_receive_argument_by_immut_ref() -> Result<(), anyhow::Error> {
         args: vec![],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        dev_inspect: false,
+        trace: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -998,6 +1062,9 @@ async fn test_receive_argument_by_immut_ref() -> Result<(), anyhow::Error> {
         ],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        dev_inspect: false,
+        trace: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -1050,6 +1117,7 @@ async fn test_receive_argument_by_mut_ref() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -1083,6 +1151,9 @@ async fn test_receive_argument_by_mut_ref() -> Result<(), anyhow::Error> {
         args: vec![],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        dev_inspect: false,
+        trace: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -1124,6 +1195,9 @@ async fn test_receive_argument_by_mut_ref() -> Result<(), anyhow::Error> {
         ],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        dev_inspect: false,
+        trace: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -1178,6 +1252,7 @@ async fn test_package_publish_command_with_unpublished_dependency_succeeds(
         with_unpublished_dependencies,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -1246,6 +1321,7 @@ async fn test_package_publish_command_with_unpublished_dependency_fails(
         with_unpublished_dependencies,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await;
@@ -1292,6 +1368,7 @@ async fn test_package_publish_command_non_zero_unpublished_dep_fails() -> Result
         with_unpublished_dependencies,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await;
@@ -1347,6 +1424,7 @@ async fn test_package_publish_command_failure_invalid() -> Result<(), anyhow::Er
         with_unpublished_dependencies,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await;
@@ -1389,6 +1467,7 @@ async fn test_package_publish_nonexistent_dependency() -> Result<(), anyhow::Err
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await;
@@ -1432,6 +1511,7 @@ async fn test_package_publish_test_flag() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await;
@@ -1487,6 +1567,7 @@ async fn test_package_upgrade_command() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -1559,6 +1640,7 @@ async fn test_package_upgrade_command() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -1621,6 +1703,7 @@ async fn test_native_transfer() -> Result<(), anyhow::Error> {
         gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -1726,6 +1809,7 @@ async fn test_native_transfer() -> Result<(), anyhow::Error> {
         gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -2041,6 +2125,7 @@ async fn test_merge_coin() -> Result<(), anyhow::Error> {
         gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_GENERIC,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -2096,6 +2181,7 @@ async fn test_merge_coin() -> Result<(), anyhow::Error> {
         gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_GENERIC,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -2162,6 +2248,7 @@ async fn test_split_coin() -> Result<(), anyhow::Error> {
         count: None,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -2230,6 +2317,7 @@ async fn test_split_coin() -> Result<(), anyhow::Error> {
         count: Some(3),
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -2301,6 +2389,7 @@ async fn test_split_coin() -> Result<(), anyhow::Error> {
         count: None,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -2337,6 +2426,183 @@ async fn test_split_coin() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[sim_test]
+async fn test_ptb() -> Result<(), anyhow::Error> {
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let address = test_cluster.get_address_0();
+    let context = &mut test_cluster.wallet;
+    let gas_budget = rgp * TEST_ONLY_GAS_UNIT_FOR_GENERIC;
+
+    let ptb = |source: String| MgoClientCommands::Ptb {
+        commands: source.split_whitespace().map(String::from).collect(),
+    };
+
+    // Split coins off the gas coin, split one of them again with a Move call, and keep them all.
+    let source = format!(
+        "--split-coins gas [1000, 2000] --assign coins \
+         --move-call 0x2::coin::split<0x2::mgo::MGO> coins.0 400 --assign split \
+         --transfer-objects [coins.0, coins.1, split] @{address} --gas-budget {gas_budget}"
+    );
+
+    let resp = ptb(format!("{source} --dry-run")).execute(context).await?;
+    let MgoClientCommandResult::DryRun(response) = resp else {
+        panic!("Expected a dry run response, got {resp:?}");
+    };
+    assert!(response.effects.status().is_ok());
+    assert_eq!(response.effects.created().len(), 3);
+
+    let resp = ptb(source).execute(context).await?;
+    let MgoClientCommandResult::Ptb(response) = resp else {
+        panic!("Expected a transaction response, got {resp:?}");
+    };
+    assert!(
+        response.status_ok().unwrap(),
+        "Command failed: {response:?}"
+    );
+
+    let mut values = vec![];
+    for created in response.effects.as_ref().unwrap().created() {
+        let coin = get_parsed_object_assert_existence(created.reference.object_id, context).await;
+        values.push(get_gas_value(&coin));
+    }
+    values.sort();
+    assert_eq!(values, vec![400, 600, 2000]);
+
+    // Dev-inspecting returns the results of each command, and does not need a gas budget.
+    let resp = ptb(format!(
+        "--split-coins gas [1000] --assign coins \
+         --move-call 0x2::coin::value<0x2::mgo::MGO> coins.0 \
+         --transfer-objects [coins.0] @{address} --dev-inspect"
+    ))
+    .execute(context)
+    .await?;
+    let MgoClientCommandResult::DevInspect(results) = resp else {
+        panic!("Expected dev-inspect results, got {resp:?}");
+    };
+    assert_eq!(results.error, None);
+    let (value, _) = &results.results.unwrap()[1].return_values[0];
+    assert_eq!(bcs::from_bytes::<u64>(value)?, 1000);
+
+    // Errors point at the part of the commands that caused them.
+    let err = ptb(format!(
+        "--split-coins gas [1000] --assign coins --move-call 0x2::coin::value<0x2::mgo::MGO> coin \
+         --gas-budget {gas_budget}"
+    ))
+    .execute(context)
+    .await
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("Unknown name 'coin'"), "{err}");
+
+    let err = ptb(format!(
+        "--move-call 0x2::coin::value<0x2::mgo::MGO> 1000 --gas-budget {gas_budget}"
+    ))
+    .execute(context)
+    .await
+    .unwrap_err()
+    .to_string();
+    assert!(
+        err.contains("Expected an object, e.g. @0x..., found a number"),
+        "{err}"
+    );
+
+    Ok(())
+}
+
+#[sim_test]
+async fn test_multisig_session() -> Result<(), anyhow::Error> {
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let addresses = test_cluster.get_addresses();
+    let recipient = addresses[0];
+
+    // A 2-of-3 MultiSig of keys in the wallet, with a coin to pay for its transaction.
+    let keystore = &test_cluster.wallet.config.keystore;
+    let pks = addresses[..3]
+        .iter()
+        .map(|a| keystore.get_key(a).map(|k| k.public()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let multisig_pk = MultiSigPublicKey::new(pks.clone(), vec![1, 1, 1], 2)?;
+    let multisig_address = MgoAddress::from(&multisig_pk);
+    let fund = test_cluster
+        .test_transaction_builder()
+        .await
+        .transfer_mgo(Some(1_000_000_000), multisig_address)
+        .build();
+    let response = test_cluster.sign_and_execute_transaction(&fund).await;
+    let effects = response.effects.unwrap();
+    let gas = effects.created()[0].reference.to_object_ref();
+
+    let tx_data = TestTransactionBuilder::new(multisig_address, gas, rgp)
+        .transfer_mgo(Some(1000), recipient)
+        .build();
+    let temp_dir = tempfile::tempdir()?;
+    let session = temp_dir.path().join("session.json");
+    let context = &mut test_cluster.wallet;
+
+    let resp = MgoClientCommands::MultiSigSession {
+        cmd: MultiSigSessionCommand::Create {
+            tx_bytes: Base64::encode(bcs::to_bytes(&tx_data)?),
+            pks,
+            weights: vec![1, 1, 1],
+            threshold: 2,
+            session: session.clone(),
+        },
+    }
+    .execute(context)
+    .await?;
+    let MgoClientCommandResult::MultiSigSession(MultiSigSessionCommandResult::Status(status)) =
+        resp
+    else {
+        panic!("Expected the status of the session, got {resp:?}");
+    };
+    assert_eq!(status.multisig_address, multisig_address);
+    assert_eq!(status.weight, 0);
+    assert_eq!(status.summary.balance_changes.len(), 2);
+
+    let sign = |address: MgoAddress| MgoClientCommands::MultiSigSession {
+        cmd: MultiSigSessionCommand::Sign {
+            session: session.clone(),
+            address: Some(KeyIdentity::Address(address)),
+            signature: None,
+        },
+    };
+    let execute = || MgoClientCommands::MultiSigSession {
+        cmd: MultiSigSessionCommand::Execute {
+            session: session.clone(),
+        },
+    };
+
+    sign(addresses[2]).execute(context).await?;
+    let err = execute().execute(context).await.unwrap_err().to_string();
+    assert!(err.contains("has not reached the threshold of 2"), "{err}");
+
+    let err = sign(addresses[2])
+        .execute(context)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("has already signed"), "{err}");
+
+    let resp = sign(addresses[0]).execute(context).await?;
+    let MgoClientCommandResult::MultiSigSession(MultiSigSessionCommandResult::Status(status)) =
+        resp
+    else {
+        panic!("Expected the status of the session, got {resp:?}");
+    };
+    assert_eq!(status.weight, 2);
+
+    let resp = execute().execute(context).await?;
+    let response = resp.tx_block_response().unwrap();
+    assert!(
+        response.status_ok().unwrap(),
+        "Command failed: {response:?}"
+    );
+    assert_eq!(response.digest, tx_data.digest());
+    Ok(())
+}
+
 #[sim_test]
 async fn test_signature_flag() -> Result<(), anyhow::Error> {
     let res = SignatureScheme::from_flag("0");
@@ -2410,6 +2676,7 @@ async fn test_serialize_tx() -> Result<(), anyhow::Error> {
         amount: Some(1),
         serialize_unsigned_transaction: true,
         serialize_signed_transaction: false,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -2421,6 +2688,7 @@ async fn test_serialize_tx() -> Result<(), anyhow::Error> {
         amount: Some(1),
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: true,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -2433,6 +2701,7 @@ async fn test_serialize_tx() -> Result<(), anyhow::Error> {
         amount: Some(1),
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: true,
+        expiration: Default::default(),
     }
     .execute(context)
     .await?;
@@ -118,6 +118,12 @@ impl EpochState {
             &receiving_object_refs,
         )?;
 
+        mgo_transaction_checks::deny::check_input_objects_for_signing(
+            tx_data,
+            &input_objects,
+            deny_config,
+        )?;
+
         // Run the transaction input checks that would run when submitting the txn to a validator
         // for signing
         let (gas_status, checked_input_objects) = mgo_transaction_checks::check_transaction_input(
@@ -31,6 +31,14 @@ pub fn gen_transaction_expiration() -> impl Strategy<Value = TransactionExpirati
     prop_oneof![
         Just(TransactionExpiration::None),
         (0u64..=u64::MAX).prop_map(TransactionExpiration::Epoch),
+        (any::<Option<u64>>(), any::<Option<u64>>(), any::<u32>()).prop_map(
+            |(min_timestamp_ms, max_timestamp_ms, nonce)| TransactionExpiration::ValidDuring {
+                min_timestamp_ms,
+                max_timestamp_ms,
+                chain: None,
+                nonce,
+            }
+        ),
     ]
 }
 
@@ -201,6 +201,16 @@ impl Interpreter {
                         .charge_drop_frame(non_ref_vals.into_iter())
                         .map_err(|e| self.set_location(e))?;
 
+                    // The callee's return values are at the top of the operand stack.
+                    if gas_meter.records_return_values() {
+                        if let Ok(return_values) = self
+                            .operand_stack
+                            .last_n(current_frame.function.return_type_count())
+                        {
+                            gas_meter.record_return_values(return_values);
+                        }
+                    }
+
                     profile_close_frame!(gas_meter, current_frame.function.pretty_string());
 
                     if let Some(frame) = self.call_stack.pop() {
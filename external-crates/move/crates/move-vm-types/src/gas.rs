@@ -184,6 +184,21 @@ pub trait GasMeter {
         locals: impl Iterator<Item = impl ValueView>,
     ) -> PartialVMResult<()>;
 
+    /// Whether the meter observes return values through `record_return_values`. The interpreter
+    /// only collects return values for meters that do.
+    fn records_return_values(&self) -> bool {
+        false
+    }
+
+    /// Called when a Move function returns, with the values it returns. Nothing is charged for
+    /// this: it exists so that meters that also trace execution can observe return values, which
+    /// are otherwise only visible for native functions (via `charge_native_function`).
+    fn record_return_values(
+        &mut self,
+        _return_values: impl ExactSizeIterator<Item = impl ValueView>,
+    ) {
+    }
+
     /// Returns the gas left
     fn remaining_gas(&self) -> InternalGas;
 
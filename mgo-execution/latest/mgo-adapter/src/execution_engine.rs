@@ -42,6 +42,7 @@ mod checked {
     use mgo_types::error::{ExecutionError, ExecutionErrorKind};
     use mgo_types::execution::is_certificate_denied;
     use mgo_types::execution_status::ExecutionStatus;
+    use mgo_types::execution_trace::ExecutionTracer;
     use mgo_types::gas::GasCostSummary;
     use mgo_types::gas::MgoGasStatus;
     use mgo_types::inner_temporary_store::InnerTemporaryStore;
@@ -79,6 +80,7 @@ mod checked {
         metrics: Arc<LimitsMetrics>,
         enable_expensive_checks: bool,
         certificate_deny_set: &HashSet<TransactionDigest>,
+        tracer: Option<&mut ExecutionTracer>,
     ) -> (
         InnerTemporaryStore,
         MgoGasStatus,
@@ -128,6 +130,7 @@ mod checked {
             enable_expensive_checks,
             deny_cert,
             contains_deleted_input,
+            tracer,
         );
 
         let status = if let Err(error) = &execution_result {
@@ -243,6 +246,7 @@ mod checked {
             tx_context,
             &mut gas_charger,
             pt,
+            /* tracer */ None,
         )?;
         temporary_store.update_object_version_and_prev_tx();
         Ok(temporary_store.into_inner())
@@ -260,6 +264,7 @@ mod checked {
         enable_expensive_checks: bool,
         deny_cert: bool,
         contains_deleted_input: bool,
+        tracer: Option<&mut ExecutionTracer>,
     ) -> (
         GasCostSummary,
         Result<Mode::ExecutionResults, ExecutionError>,
@@ -298,6 +303,7 @@ mod checked {
                     gas_charger,
                     protocol_config,
                     metrics.clone(),
+                    tracer,
                 )
             };
 
@@ -518,6 +524,7 @@ mod checked {
         gas_charger: &mut GasCharger,
         protocol_config: &ProtocolConfig,
         metrics: Arc<LimitsMetrics>,
+        tracer: Option<&mut ExecutionTracer>,
     ) -> Result<Mode::ExecutionResults, ExecutionError> {
         let result = match transaction_kind {
             TransactionKind::ChangeEpoch(change_epoch) => {
@@ -589,6 +596,7 @@ mod checked {
                     tx_ctx,
                     gas_charger,
                     pt,
+                    tracer,
                 )
             }
             TransactionKind::EndOfEpochTransaction(txns) => {
@@ -827,6 +835,7 @@ mod checked {
             tx_ctx,
             gas_charger,
             advance_epoch_pt,
+            /* tracer */ None,
         );
 
         #[cfg(msim)]
@@ -856,6 +865,7 @@ mod checked {
                     tx_ctx,
                     gas_charger,
                     advance_epoch_safe_mode_pt,
+                    /* tracer */ None,
                 )
                 .expect("Advance epoch with safe mode must succeed");
             }
@@ -893,6 +903,7 @@ mod checked {
                     tx_ctx,
                     gas_charger,
                     publish_pt,
+                    /* tracer */ None,
                 )
                 .expect("System Package Publish must succeed");
             } else {
@@ -963,6 +974,7 @@ mod checked {
             tx_ctx,
             gas_charger,
             pt,
+            /* tracer */ None,
         )
     }
 
@@ -1035,6 +1047,7 @@ mod checked {
             tx_ctx,
             gas_charger,
             pt,
+            /* tracer */ None,
         )
     }
 
@@ -1101,6 +1114,7 @@ mod checked {
             tx_ctx,
             gas_charger,
             pt,
+            /* tracer */ None,
         )
     }
 
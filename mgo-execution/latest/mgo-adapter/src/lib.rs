@@ -10,4 +10,5 @@ pub mod execution_engine;
 pub mod gas_charger;
 pub mod programmable_transactions;
 pub mod temporary_store;
+pub mod tracing_gas_meter;
 pub mod type_layout_resolver;
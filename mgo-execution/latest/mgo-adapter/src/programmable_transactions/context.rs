@@ -16,6 +16,7 @@ mod checked {
     use crate::error::convert_vm_error;
     use crate::gas_charger::GasCharger;
     use crate::programmable_transactions::linkage_view::LinkageView;
+    use crate::tracing_gas_meter::TracingGasMeter;
     use move_binary_format::{
         errors::{Location, PartialVMError, PartialVMResult, VMError, VMResult},
         file_format::{CodeOffset, FunctionDefinitionIndex, TypeParameterIndex},
@@ -53,6 +54,7 @@ mod checked {
             ExecutionResultsV2, ExecutionState, InputObjectMetadata, InputValue, ObjectValue,
             RawValueType, ResultValue, UsageKind,
         },
+        execution_trace::ExecutionTracer,
         metrics::LimitsMetrics,
         move_package::MovePackage,
         object::{Data, MoveObject, Object, ObjectInner, Owner},
@@ -86,6 +88,8 @@ mod checked {
         pub tx_context: &'a mut TxContext,
         /// The gas charger used for metering
         pub gas_charger: &'a mut GasCharger,
+        /// Records a trace of the Move execution, if one was requested
+        pub tracer: Option<&'a mut ExecutionTracer>,
         /// Additional transfers not from the Move runtime
         additional_transfers: Vec<(/* new owner */ MgoAddress, ObjectValue)>,
         /// Newly published packages
@@ -128,6 +132,7 @@ mod checked {
             tx_context: &'a mut TxContext,
             gas_charger: &'a mut GasCharger,
             inputs: Vec<CallArg>,
+            tracer: Option<&'a mut ExecutionTracer>,
         ) -> Result<Self, ExecutionError>
         where
             'a: 'state,
@@ -224,6 +229,7 @@ mod checked {
                 state_view,
                 tx_context,
                 gas_charger,
+                tracer,
                 gas,
                 inputs,
                 results: vec![],
@@ -328,6 +334,11 @@ mod checked {
                     Ok((module_id.clone(), tag, bytes))
                 })
                 .collect::<Result<Vec<_>, ExecutionError>>()?;
+            if let Some(tracer) = self.tracer.as_deref_mut() {
+                for (_, tag, bytes) in &new_events {
+                    tracer.record_event(tag.clone(), bytes.clone());
+                }
+            }
             self.user_events.extend(new_events);
             Ok(())
         }
@@ -946,13 +957,44 @@ mod checked {
         ) -> VMResult<SerializedReturnValues> {
             let gas_status = self.gas_charger.move_gas_status_mut();
             let mut data_store = MgoDataStore::new(&self.linkage_view, &self.new_packages);
+            let Some(tracer) = self.tracer.as_deref_mut() else {
+                return self.vm.get_runtime().execute_function_bypass_visibility(
+                    module,
+                    function_name,
+                    ty_args,
+                    args,
+                    &mut data_store,
+                    gas_status,
+                    &mut self.native_extensions,
+                );
+            };
+
+            // The VM only reports the functions it calls into, so the frame for the function it
+            // is asked to execute is opened here.
+            let type_arguments = ty_args
+                .iter()
+                .map(|ty| self.vm.get_runtime().get_type_tag(ty))
+                .collect::<VMResult<_>>()?;
+            let arguments = args
+                .iter()
+                .map(|arg| Borrow::<[u8]>::borrow(arg).to_vec())
+                .collect();
+            let remaining_gas = move_vm_types::gas::GasMeter::remaining_gas(&*gas_status);
+            tracer.open_frame(
+                module,
+                function_name.as_str(),
+                type_arguments,
+                arguments,
+                remaining_gas.into(),
+            );
+
             self.vm.get_runtime().execute_function_bypass_visibility(
                 module,
                 function_name,
                 ty_args,
                 args,
                 &mut data_store,
-                gas_status,
+                &mut TracingGasMeter::new(gas_status, tracer),
                 &mut self.native_extensions,
             )
         }
@@ -23,6 +23,7 @@ mod checked {
         move_vm::MoveVM,
         session::{LoadedFunctionInstantiation, SerializedReturnValues},
     };
+    use move_vm_types::gas::GasMeter;
     use move_vm_types::loaded_data::runtime_types::{StructType, Type};
     use serde::{de::DeserializeSeed, Deserialize};
     use std::{
@@ -43,6 +44,7 @@ mod checked {
         execution::{
             CommandKind, ExecutionState, ObjectContents, ObjectValue, RawValueType, Value,
         },
+        execution_trace::ExecutionTracer,
         id::{RESOLVED_MGO_ID, UID},
         metrics::LimitsMetrics,
         move_package::{
@@ -75,6 +77,7 @@ mod checked {
         tx_context: &mut TxContext,
         gas_charger: &mut GasCharger,
         pt: ProgrammableTransaction,
+        tracer: Option<&mut ExecutionTracer>,
     ) -> Result<Mode::ExecutionResults, ExecutionError> {
         let ProgrammableTransaction { inputs, commands } = pt;
         let mut context = ExecutionContext::new(
@@ -85,11 +88,21 @@ mod checked {
             tx_context,
             gas_charger,
             inputs,
+            tracer,
         )?;
         // execute commands
         let mut mode_results = Mode::empty_results();
         for (idx, command) in commands.into_iter().enumerate() {
-            if let Err(err) = execute_command::<Mode>(&mut context, &mut mode_results, command) {
+            if let Some(tracer) = context.tracer.as_deref_mut() {
+                tracer.start_command(idx, command_kind_name(&command));
+            }
+            let result = execute_command::<Mode>(&mut context, &mut mode_results, command);
+            if let Some(tracer) = context.tracer.as_deref_mut() {
+                let remaining_gas = GasMeter::remaining_gas(context.gas_charger.move_gas_status());
+                let error = result.as_ref().err().map(|e| e.kind().to_string());
+                tracer.end_command(error, remaining_gas.into());
+            }
+            if let Err(err) = result {
                 let object_runtime: &ObjectRuntime = context.object_runtime();
                 // We still need to record the loaded child objects for replay
                 let loaded_runtime_objects = object_runtime.loaded_runtime_objects();
@@ -119,6 +132,19 @@ mod checked {
         Ok(mode_results)
     }
 
+    /// The name of the kind of `command`, as it appears in execution traces.
+    fn command_kind_name(command: &Command) -> &'static str {
+        match command {
+            Command::MoveCall(_) => "MoveCall",
+            Command::TransferObjects(_, _) => "TransferObjects",
+            Command::SplitCoins(_, _) => "SplitCoins",
+            Command::MergeCoins(_, _) => "MergeCoins",
+            Command::Publish(_, _) => "Publish",
+            Command::MakeMoveVec(_, _) => "MakeMoveVec",
+            Command::Upgrade(_, _, _, _) => "Upgrade",
+        }
+    }
+
     /// Execute a single command
     #[instrument(level = "trace", skip_all)]
     fn execute_command<Mode: ExecutionMode>(
@@ -0,0 +1,342 @@
+// Copyright (c) MangoNet Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+use move_binary_format::errors::PartialVMResult;
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_algebra::{InternalGas, NumArgs, NumBytes},
+    language_storage::ModuleId,
+    u256::U256,
+};
+use move_vm_profiler::GasProfiler;
+use move_vm_types::{
+    gas::{GasMeter, SimpleInstruction},
+    views::{TypeView, ValueView, ValueVisitor},
+};
+use mgo_types::execution_trace::ExecutionTracer;
+
+/// A gas meter that charges exactly what the meter it wraps charges, and additionally reports the
+/// frames the VM enters and leaves (with their arguments, return values and gas) to a tracer.
+///
+/// The VM does not report entering the function it is asked to execute, so the caller must open
+/// that frame on the tracer itself, before executing it.
+pub struct TracingGasMeter<'a, G> {
+    inner: &'a mut G,
+    tracer: &'a mut ExecutionTracer,
+}
+
+impl<'a, G: GasMeter> TracingGasMeter<'a, G> {
+    pub fn new(inner: &'a mut G, tracer: &'a mut ExecutionTracer) -> Self {
+        Self { inner, tracer }
+    }
+
+    fn remaining(&self) -> u64 {
+        self.inner.remaining_gas().into()
+    }
+
+    /// Serializes `values` for the tracer, unless it has stopped recording them.
+    fn traced_values<V: ValueView>(&self, values: &[V]) -> Vec<Vec<u8>> {
+        if self.tracer.is_truncated() {
+            vec![]
+        } else {
+            values.iter().map(to_bcs_bytes).collect()
+        }
+    }
+}
+
+/// Serializes a value the VM exposes through a [`ValueView`] as BCS. References are serialized as
+/// the value they refer to.
+pub fn to_bcs_bytes(value: impl ValueView) -> Vec<u8> {
+    let mut writer = BcsWriter(vec![]);
+    value.visit(&mut writer);
+    writer.0
+}
+
+struct BcsWriter(Vec<u8>);
+
+impl BcsWriter {
+    fn write_len(&mut self, len: usize) {
+        leb128::write::unsigned(&mut self.0, len as u64).unwrap();
+    }
+}
+
+impl ValueVisitor for BcsWriter {
+    fn visit_u8(&mut self, _depth: usize, val: u8) {
+        self.0.push(val);
+    }
+
+    fn visit_u16(&mut self, _depth: usize, val: u16) {
+        self.0.extend(val.to_le_bytes());
+    }
+
+    fn visit_u32(&mut self, _depth: usize, val: u32) {
+        self.0.extend(val.to_le_bytes());
+    }
+
+    fn visit_u64(&mut self, _depth: usize, val: u64) {
+        self.0.extend(val.to_le_bytes());
+    }
+
+    fn visit_u128(&mut self, _depth: usize, val: u128) {
+        self.0.extend(val.to_le_bytes());
+    }
+
+    fn visit_u256(&mut self, _depth: usize, val: U256) {
+        self.0.extend(val.to_le_bytes());
+    }
+
+    fn visit_bool(&mut self, _depth: usize, val: bool) {
+        self.0.push(val as u8);
+    }
+
+    fn visit_address(&mut self, _depth: usize, val: AccountAddress) {
+        self.0.extend(val.into_bytes());
+    }
+
+    fn visit_struct(&mut self, _depth: usize, _len: usize) -> bool {
+        true
+    }
+
+    fn visit_vec(&mut self, _depth: usize, len: usize) -> bool {
+        self.write_len(len);
+        true
+    }
+
+    fn visit_ref(&mut self, _depth: usize, _is_global: bool) -> bool {
+        true
+    }
+
+    fn visit_vec_u8(&mut self, _depth: usize, vals: &[u8]) {
+        self.write_len(vals.len());
+        self.0.extend(vals);
+    }
+}
+
+impl<G: GasMeter> GasMeter for TracingGasMeter<'_, G> {
+    fn charge_simple_instr(&mut self, instr: SimpleInstruction) -> PartialVMResult<()> {
+        self.inner.charge_simple_instr(instr)
+    }
+
+    fn charge_pop(&mut self, popped_val: impl ValueView) -> PartialVMResult<()> {
+        self.inner.charge_pop(popped_val)
+    }
+
+    fn charge_call(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let args: Vec<_> = args.collect();
+        let arguments = self.traced_values(&args);
+        let remaining = self.remaining();
+        self.tracer
+            .open_frame(module_id, func_name, vec![], arguments, remaining);
+
+        self.inner
+            .charge_call(module_id, func_name, args.into_iter(), num_locals)
+    }
+
+    fn charge_call_generic(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let ty_args: Vec<_> = ty_args.collect();
+        let args: Vec<_> = args.collect();
+        let type_arguments = ty_args.iter().map(|ty| ty.to_type_tag()).collect();
+        let arguments = self.traced_values(&args);
+        let remaining = self.remaining();
+        self.tracer
+            .open_frame(module_id, func_name, type_arguments, arguments, remaining);
+
+        self.inner.charge_call_generic(
+            module_id,
+            func_name,
+            ty_args.into_iter(),
+            args.into_iter(),
+            num_locals,
+        )
+    }
+
+    fn charge_ld_const(&mut self, size: NumBytes) -> PartialVMResult<()> {
+        self.inner.charge_ld_const(size)
+    }
+
+    fn charge_ld_const_after_deserialization(
+        &mut self,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_ld_const_after_deserialization(val)
+    }
+
+    fn charge_copy_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.inner.charge_copy_loc(val)
+    }
+
+    fn charge_move_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.inner.charge_move_loc(val)
+    }
+
+    fn charge_store_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.inner.charge_store_loc(val)
+    }
+
+    fn charge_pack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_pack(is_generic, args)
+    }
+
+    fn charge_unpack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_unpack(is_generic, args)
+    }
+
+    fn charge_read_ref(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.inner.charge_read_ref(val)
+    }
+
+    fn charge_write_ref(
+        &mut self,
+        new_val: impl ValueView,
+        old_val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_write_ref(new_val, old_val)
+    }
+
+    fn charge_eq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.inner.charge_eq(lhs, rhs)
+    }
+
+    fn charge_neq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.inner.charge_neq(lhs, rhs)
+    }
+
+    fn charge_vec_pack<'a>(
+        &mut self,
+        ty: impl TypeView + 'a,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_vec_pack(ty, args)
+    }
+
+    fn charge_vec_len(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.inner.charge_vec_len(ty)
+    }
+
+    fn charge_vec_borrow(
+        &mut self,
+        is_mut: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_vec_borrow(is_mut, ty, is_success)
+    }
+
+    fn charge_vec_push_back(
+        &mut self,
+        ty: impl TypeView,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_vec_push_back(ty, val)
+    }
+
+    fn charge_vec_pop_back(
+        &mut self,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_vec_pop_back(ty, val)
+    }
+
+    fn charge_vec_unpack(
+        &mut self,
+        ty: impl TypeView,
+        expect_num_elements: NumArgs,
+        elems: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_vec_unpack(ty, expect_num_elements, elems)
+    }
+
+    fn charge_vec_swap(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.inner.charge_vec_swap(ty)
+    }
+
+    fn charge_native_function(
+        &mut self,
+        amount: InternalGas,
+        ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView>>,
+    ) -> PartialVMResult<()> {
+        // Natives report their return values (or that they aborted) here, which is the last the
+        // meter hears of them.
+        let ret_vals: Option<Vec<_>> = ret_vals.map(|vals| vals.collect());
+        let return_values = ret_vals.as_ref().map(|vals| self.traced_values(vals));
+
+        let result = self
+            .inner
+            .charge_native_function(amount, ret_vals.map(|vals| vals.into_iter()));
+
+        let remaining = self.remaining();
+        let return_values = return_values.filter(|_| result.is_ok());
+        self.tracer
+            .close_frame(return_values, /* is_native */ true, remaining);
+        result
+    }
+
+    fn charge_native_function_before_execution(
+        &mut self,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.inner
+            .charge_native_function_before_execution(ty_args, args)
+    }
+
+    fn charge_drop_frame(
+        &mut self,
+        locals: impl Iterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_drop_frame(locals)
+    }
+
+    fn records_return_values(&self) -> bool {
+        true
+    }
+
+    fn record_return_values(
+        &mut self,
+        return_values: impl ExactSizeIterator<Item = impl ValueView>,
+    ) {
+        let return_values: Vec<_> = return_values.collect();
+        let traced = self.traced_values(&return_values);
+        let remaining = self.remaining();
+        self.tracer
+            .close_frame(Some(traced), /* is_native */ false, remaining);
+
+        if self.inner.records_return_values() {
+            self.inner.record_return_values(return_values.into_iter());
+        }
+    }
+
+    fn remaining_gas(&self) -> InternalGas {
+        self.inner.remaining_gas()
+    }
+
+    fn get_profiler_mut(&mut self) -> Option<&mut GasProfiler> {
+        self.inner.get_profiler_mut()
+    }
+
+    fn set_profiler(&mut self, profiler: GasProfiler) {
+        self.inner.set_profiler(profiler)
+    }
+}
@@ -9,9 +9,10 @@ use mgo_types::{
     committee::EpochId,
     digests::TransactionDigest,
     effects::TransactionEffects,
-    error::ExecutionError,
+    error::{ExecutionError, MgoError, MgoResult},
     execution::TypeLayoutStore,
     execution_mode::ExecutionResult,
+    execution_trace::ExecutionTracer,
     gas::MgoGasStatus,
     inner_temporary_store::InnerTemporaryStore,
     metrics::LimitsMetrics,
@@ -76,6 +77,73 @@ pub trait Executor {
         Result<Vec<ExecutionResult>, ExecutionError>,
     );
 
+    /// Like `execute_transaction_to_effects`, but also records a trace of the transaction's Move
+    /// execution into `tracer`. Execution layers that do not support tracing fail without
+    /// executing the transaction.
+    fn execute_transaction_to_effects_with_tracer(
+        &self,
+        _store: &dyn BackingStore,
+        // Configuration
+        _protocol_config: &ProtocolConfig,
+        _metrics: Arc<LimitsMetrics>,
+        _enable_expensive_checks: bool,
+        _certificate_deny_set: &HashSet<TransactionDigest>,
+        // Epoch
+        _epoch_id: &EpochId,
+        _epoch_timestamp_ms: u64,
+        // Transaction Inputs
+        _input_objects: CheckedInputObjects,
+        // Gas related
+        _gas_coins: Vec<ObjectRef>,
+        _gas_status: MgoGasStatus,
+        // Transaction
+        _transaction_kind: TransactionKind,
+        _transaction_signer: MgoAddress,
+        _transaction_digest: TransactionDigest,
+        _tracer: &mut ExecutionTracer,
+    ) -> MgoResult<(
+        InnerTemporaryStore,
+        MgoGasStatus,
+        TransactionEffects,
+        Result<(), ExecutionError>,
+    )> {
+        Err(tracing_unsupported())
+    }
+
+    /// Like `dev_inspect_transaction`, but also records a trace of the transaction's Move
+    /// execution into `tracer`. Execution layers that do not support tracing fail without
+    /// executing the transaction.
+    fn dev_inspect_transaction_with_tracer(
+        &self,
+        _store: &dyn BackingStore,
+        // Configuration
+        _protocol_config: &ProtocolConfig,
+        _metrics: Arc<LimitsMetrics>,
+        _enable_expensive_checks: bool,
+        _certificate_deny_set: &HashSet<TransactionDigest>,
+        // Epoch
+        _epoch_id: &EpochId,
+        _epoch_timestamp_ms: u64,
+        // Transaction Inputs
+        _input_objects: CheckedInputObjects,
+        // Gas related
+        _gas_coins: Vec<ObjectRef>,
+        _gas_status: MgoGasStatus,
+        // Transaction
+        _transaction_kind: TransactionKind,
+        _transaction_signer: MgoAddress,
+        _transaction_digest: TransactionDigest,
+        _skip_all_checks: bool,
+        _tracer: &mut ExecutionTracer,
+    ) -> MgoResult<(
+        InnerTemporaryStore,
+        MgoGasStatus,
+        TransactionEffects,
+        Result<Vec<ExecutionResult>, ExecutionError>,
+    )> {
+        Err(tracing_unsupported())
+    }
+
     fn update_genesis_state(
         &self,
         store: &dyn BackingStore,
@@ -94,3 +162,10 @@ pub trait Executor {
         store: Box<dyn TypeLayoutStore + 'store>,
     ) -> Box<dyn LayoutResolver + 'r>;
 }
+
+fn tracing_unsupported() -> MgoError {
+    MgoError::UnsupportedFeatureError {
+        error: "execution traces are not supported by this protocol version's execution layer"
+            .to_string(),
+    }
+}
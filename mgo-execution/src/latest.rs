@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::path::PathBuf;
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
 
 use move_binary_format::CompiledModule;
 use move_vm_config::verifier::VerifierConfig;
@@ -15,6 +18,7 @@ use mgo_types::{
     error::{ExecutionError, MgoError, MgoResult},
     execution::TypeLayoutStore,
     execution_mode::{self, ExecutionResult},
+    execution_trace::ExecutionTracer,
     gas::MgoGasStatus,
     inner_temporary_store::InnerTemporaryStore,
     metrics::{BytecodeVerifierMetrics, LimitsMetrics},
@@ -23,6 +27,7 @@ use mgo_types::{
 };
 
 use move_bytecode_verifier_latest::meter::Scope;
+use move_bytecode_verifier_latest::verify_module_with_config_unmetered;
 use move_vm_runtime_latest::move_vm::MoveVM;
 use mgo_adapter_latest::adapter::{new_move_vm, run_metered_move_bytecode_verifier};
 use mgo_adapter_latest::execution_engine::{
@@ -31,7 +36,9 @@ use mgo_adapter_latest::execution_engine::{
 use mgo_adapter_latest::type_layout_resolver::TypeLayoutResolver;
 use mgo_move_natives_latest::all_natives;
 use mgo_types::storage::BackingStore;
-use mgo_verifier_latest::{default_verifier_config, meter::MgoVerifierMeter};
+use mgo_verifier_latest::{
+    default_verifier_config, meter::MgoVerifierMeter, verifier::mgo_verify_module_unmetered,
+};
 
 use crate::executor;
 use crate::verifier;
@@ -112,6 +119,7 @@ impl executor::Executor for Executor {
             metrics,
             enable_expensive_checks,
             certificate_deny_set,
+            None,
         )
     }
 
@@ -153,6 +161,7 @@ impl executor::Executor for Executor {
                 metrics,
                 enable_expensive_checks,
                 certificate_deny_set,
+                None,
             )
         } else {
             execute_transaction_to_effects::<execution_mode::DevInspect<false>>(
@@ -170,10 +179,121 @@ impl executor::Executor for Executor {
                 metrics,
                 enable_expensive_checks,
                 certificate_deny_set,
+                None,
             )
         }
     }
 
+    fn execute_transaction_to_effects_with_tracer(
+        &self,
+        store: &dyn BackingStore,
+        protocol_config: &ProtocolConfig,
+        metrics: Arc<LimitsMetrics>,
+        enable_expensive_checks: bool,
+        certificate_deny_set: &HashSet<TransactionDigest>,
+        epoch_id: &EpochId,
+        epoch_timestamp_ms: u64,
+        input_objects: CheckedInputObjects,
+        gas_coins: Vec<ObjectRef>,
+        gas_status: MgoGasStatus,
+        transaction_kind: TransactionKind,
+        transaction_signer: MgoAddress,
+        transaction_digest: TransactionDigest,
+        tracer: &mut ExecutionTracer,
+    ) -> MgoResult<(
+        InnerTemporaryStore,
+        MgoGasStatus,
+        TransactionEffects,
+        Result<(), ExecutionError>,
+    )> {
+        let (inner_temp_store, gas_status, effects, result) =
+            execute_transaction_to_effects::<execution_mode::Normal>(
+                store,
+                input_objects,
+                gas_coins,
+                gas_status,
+                transaction_kind,
+                transaction_signer,
+                transaction_digest,
+                &self.0,
+                epoch_id,
+                epoch_timestamp_ms,
+                protocol_config,
+                metrics,
+                enable_expensive_checks,
+                certificate_deny_set,
+                Some(&mut *tracer),
+            );
+
+        tracer.record_objects(store.as_object_store(), &inner_temp_store, &effects);
+        Ok((inner_temp_store, gas_status, effects, result))
+    }
+
+    fn dev_inspect_transaction_with_tracer(
+        &self,
+        store: &dyn BackingStore,
+        protocol_config: &ProtocolConfig,
+        metrics: Arc<LimitsMetrics>,
+        enable_expensive_checks: bool,
+        certificate_deny_set: &HashSet<TransactionDigest>,
+        epoch_id: &EpochId,
+        epoch_timestamp_ms: u64,
+        input_objects: CheckedInputObjects,
+        gas_coins: Vec<ObjectRef>,
+        gas_status: MgoGasStatus,
+        transaction_kind: TransactionKind,
+        transaction_signer: MgoAddress,
+        transaction_digest: TransactionDigest,
+        skip_all_checks: bool,
+        tracer: &mut ExecutionTracer,
+    ) -> MgoResult<(
+        InnerTemporaryStore,
+        MgoGasStatus,
+        TransactionEffects,
+        Result<Vec<ExecutionResult>, ExecutionError>,
+    )> {
+        let (inner_temp_store, gas_status, effects, result) = if skip_all_checks {
+            execute_transaction_to_effects::<execution_mode::DevInspect<true>>(
+                store,
+                input_objects,
+                gas_coins,
+                gas_status,
+                transaction_kind,
+                transaction_signer,
+                transaction_digest,
+                &self.0,
+                epoch_id,
+                epoch_timestamp_ms,
+                protocol_config,
+                metrics,
+                enable_expensive_checks,
+                certificate_deny_set,
+                Some(&mut *tracer),
+            )
+        } else {
+            execute_transaction_to_effects::<execution_mode::DevInspect<false>>(
+                store,
+                input_objects,
+                gas_coins,
+                gas_status,
+                transaction_kind,
+                transaction_signer,
+                transaction_digest,
+                &self.0,
+                epoch_id,
+                epoch_timestamp_ms,
+                protocol_config,
+                metrics,
+                enable_expensive_checks,
+                certificate_deny_set,
+                Some(&mut *tracer),
+            )
+        };
+
+        tracer.record_objects(store.as_object_store(), &inner_temp_store, &effects);
+        Ok((inner_temp_store, gas_status, effects, result))
+    }
+
     fn update_genesis_state(
         &self,
         store: &dyn BackingStore,
@@ -232,4 +352,24 @@ impl<'m> verifier::Verifier for Verifier<'m> {
             mod_meter_units_result,
         ))
     }
+
+    fn verify_module_bundle(
+        &mut self,
+        _protocol_config: &ProtocolConfig,
+        modules: &[CompiledModule],
+    ) -> MgoResult<()> {
+        for module in modules {
+            verify_module_with_config_unmetered(&self.config, module).map_err(|e| {
+                MgoError::ModuleVerificationFailure {
+                    error: e.to_string(),
+                }
+            })?;
+            mgo_verify_module_unmetered(module, &BTreeMap::new(), &self.config).map_err(|e| {
+                MgoError::ModuleVerificationFailure {
+                    error: e.to_string(),
+                }
+            })?;
+        }
+        Ok(())
+    }
 }
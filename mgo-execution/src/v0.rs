@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::path::PathBuf;
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
 
 use move_binary_format::CompiledModule;
 use move_vm_config::verifier::VerifierConfig;
@@ -23,6 +26,7 @@ use mgo_types::{
 };
 
 use move_bytecode_verifier_v0::meter::Scope;
+use move_bytecode_verifier_v0::verify_module_with_config_unmetered;
 use move_vm_runtime_v0::move_vm::MoveVM;
 use mgo_adapter_v0::adapter::{
     default_verifier_config, new_move_vm, run_metered_move_bytecode_verifier,
@@ -34,6 +38,7 @@ use mgo_adapter_v0::type_layout_resolver::TypeLayoutResolver;
 use mgo_move_natives_v0::all_natives;
 use mgo_types::storage::BackingStore;
 use mgo_verifier_v0::meter::MgoVerifierMeter;
+use mgo_verifier_v0::verifier::mgo_verify_module_unmetered;
 
 use crate::executor;
 use crate::verifier;
@@ -246,4 +251,24 @@ impl<'m> verifier::Verifier for Verifier<'m> {
             mod_meter_units_result,
         ))
     }
+
+    fn verify_module_bundle(
+        &mut self,
+        protocol_config: &ProtocolConfig,
+        modules: &[CompiledModule],
+    ) -> MgoResult<()> {
+        for module in modules {
+            verify_module_with_config_unmetered(&self.config, module).map_err(|e| {
+                MgoError::ModuleVerificationFailure {
+                    error: e.to_string(),
+                }
+            })?;
+            mgo_verify_module_unmetered(protocol_config, module, &BTreeMap::new()).map_err(
+                |e| MgoError::ModuleVerificationFailure {
+                    error: e.to_string(),
+                },
+            )?;
+        }
+        Ok(())
+    }
 }
@@ -46,6 +46,16 @@ pub trait Verifier {
         protocol_config: &ProtocolConfig,
         config_overrides: &VerifierOverrides,
     ) -> MgoResult<VerifierMeteredValues>;
+
+    /// Run the Move bytecode verifier and the Mgo verifier over `modules`, unmetered, exactly as
+    /// they would be run when the modules are published.
+    ///
+    /// Unlike the metered functions above, this fails if any of the modules fails to verify.
+    fn verify_module_bundle(
+        &mut self,
+        protocol_config: &ProtocolConfig,
+        modules: &[CompiledModule],
+    ) -> MgoResult<()>;
 }
 
 /// Controls verifier config values to override.